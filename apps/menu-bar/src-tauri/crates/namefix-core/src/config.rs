@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::rules::Profile;
+
+/// The part of the Node service's `config.json` this engine acts on. Every
+/// other setting is kept in `extra` and written back untouched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    #[serde(default)]
+    pub watch_dirs: Vec<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Config {
+    /// Read `path`; a missing file is an empty config.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Write to `path` through a temporary file, so a crash never leaves half a config.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let staged = path.with_extension("json.tmp");
+        fs::write(&staged, text)?;
+        fs::rename(&staged, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_it_does_not_know_survive_a_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{ "watchDirs": ["/tmp/in"], "dryRun": true, "theme": "dark", "hotFolders": {} }"#).unwrap();
+
+        let mut config = Config::load(&path).unwrap();
+        assert_eq!((config.watch_dirs.as_slice(), config.dry_run), (["/tmp/in".to_string()].as_slice(), true));
+        config.dry_run = false;
+        config.save(&path).unwrap();
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["theme"], "dark");
+        assert_eq!(saved["dryRun"], false);
+        assert!(saved["hotFolders"].is_object());
+        assert!(Config::load(&dir.path().join("missing.json")).unwrap().watch_dirs.is_empty());
+    }
+}
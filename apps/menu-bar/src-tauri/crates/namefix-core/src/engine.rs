@@ -0,0 +1,425 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::journal::{Journal, UndoOutcome};
+use crate::paths::Paths;
+use crate::rules::{Profile, RuleSet};
+use crate::template::{self, TemplateContext};
+use crate::watcher::Watcher;
+
+/// How often a new file's size is checked while it is still being written.
+const SETTLE_POLL: Duration = Duration::from_millis(250);
+/// How long a file that keeps growing is waited for before it's renamed anyway.
+const SETTLE_MAX: Duration = Duration::from_secs(10);
+
+/// Receives the engine's events by name, with payloads shaped as the Node
+/// service's (`file`, `status`).
+pub type EventSink = Box<dyn Fn(&str, Value) + Send + Sync>;
+
+/// What the engine is doing, the fields of the service's status it fills in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub running: bool,
+    pub directories: Vec<String>,
+    pub dry_run: bool,
+    pub can_redo: bool,
+}
+
+/// What happened to a file that arrived in a watched folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Renamed {
+        target: String,
+        rule: String,
+    },
+    Previewed {
+        target: String,
+        rule: String,
+    },
+    /// No rule renames it, or it already has the name its rule gives it.
+    Unchanged,
+    Failed(String),
+}
+
+struct State {
+    paths: Paths,
+    config: Config,
+    rules: RuleSet,
+    journal: Journal,
+    watcher: Option<Watcher>,
+    running: bool,
+    /// Names this engine just gave files, whose own events are ignored.
+    own_renames: HashSet<PathBuf>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    events: EventSink,
+}
+
+/// Watches the configured folders and renames files that arrive in them by
+/// the enabled rules, journaling each rename so it can be undone. Reads and
+/// writes the Node service's `config.json` and `journal.ndjson`.
+#[derive(Clone)]
+pub struct Engine {
+    shared: Arc<Shared>,
+}
+
+impl Engine {
+    /// Load the config and journal at `paths` and start watching.
+    pub fn start(paths: Paths, events: EventSink) -> Result<Self, String> {
+        let config =
+            Config::load(&paths.config).map_err(|err| format!("Could not read {}: {}", paths.config.display(), err))?;
+        let journal = Journal::open(paths.journal.clone())
+            .map_err(|err| format!("Could not read {}: {}", paths.journal.display(), err))?;
+        let state = State {
+            rules: RuleSet::new(&config.profiles),
+            paths,
+            config,
+            journal,
+            watcher: None,
+            running: false,
+            own_renames: HashSet::new(),
+        };
+        let engine = Self { shared: Arc::new(Shared { state: Mutex::new(state), events }) };
+        engine.set_running(Some(true))?;
+        Ok(engine)
+    }
+
+    pub fn status(&self) -> Status {
+        status_of(&self.lock())
+    }
+
+    /// Start or stop watching; toggles when `desired` is `None`.
+    pub fn set_running(&self, desired: Option<bool>) -> Result<Status, String> {
+        let mut state = self.lock();
+        let running = desired.unwrap_or(!state.running);
+        if running == state.running {
+            return Ok(status_of(&state));
+        }
+        if running {
+            let (arrivals, received) = mpsc::channel();
+            let mut watcher = Watcher::new(arrivals).map_err(|err| err.to_string())?;
+            for directory in &state.config.watch_dirs {
+                if let Err(err) = watcher.watch(Path::new(directory)) {
+                    log::warn!("Not watching {}: {}", directory, err);
+                }
+            }
+            state.watcher = Some(watcher);
+            let engine = self.clone();
+            thread::Builder::new()
+                .name("namefix-core".to_string())
+                .spawn(move || engine.process_arrivals(received))
+                .map_err(|err| err.to_string())?;
+        } else {
+            // Dropping the watcher closes the channel, which ends the worker.
+            state.watcher = None;
+        }
+        state.running = running;
+        Ok(self.emit_status(&state))
+    }
+
+    pub fn set_dry_run(&self, enabled: bool) -> Result<Status, String> {
+        let mut state = self.lock();
+        state.config.dry_run = enabled;
+        save(&state)?;
+        Ok(self.emit_status(&state))
+    }
+
+    pub fn watch_dirs(&self) -> Vec<String> {
+        self.lock().config.watch_dirs.clone()
+    }
+
+    pub fn add_watch_dir(&self, directory: &str) -> Result<Vec<String>, String> {
+        let mut state = self.lock();
+        if !state.config.watch_dirs.iter().any(|dir| dir == directory) {
+            if !Path::new(directory).is_dir() {
+                return Err(format!("{} is not a folder", directory));
+            }
+            if let Some(watcher) = state.watcher.as_mut() {
+                watcher.watch(Path::new(directory)).map_err(|err| err.to_string())?;
+            }
+            state.config.watch_dirs.push(directory.to_string());
+            save(&state)?;
+            self.emit_status(&state);
+        }
+        Ok(state.config.watch_dirs.clone())
+    }
+
+    pub fn remove_watch_dir(&self, directory: &str) -> Result<Vec<String>, String> {
+        let mut state = self.lock();
+        if state.config.watch_dirs.iter().any(|dir| dir == directory) {
+            if let Some(watcher) = state.watcher.as_mut() {
+                let _ = watcher.unwatch(Path::new(directory));
+            }
+            state.config.watch_dirs.retain(|dir| dir != directory);
+            save(&state)?;
+            self.emit_status(&state);
+        }
+        Ok(state.config.watch_dirs.clone())
+    }
+
+    pub fn profiles(&self) -> Vec<Profile> {
+        self.lock().config.profiles.clone()
+    }
+
+    /// Turn a rule on or off; toggles when `enabled` is `None`.
+    pub fn toggle_profile(&self, id: &str, enabled: Option<bool>) -> Result<Vec<Profile>, String> {
+        let mut state = self.lock();
+        let profile = state
+            .config
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.id == id)
+            .ok_or_else(|| format!("No rule with id {}", id))?;
+        profile.enabled = enabled.unwrap_or(!profile.enabled);
+        state.rules = RuleSet::new(&state.config.profiles);
+        save(&state)?;
+        Ok(state.config.profiles.clone())
+    }
+
+    pub fn undo(&self) -> UndoOutcome {
+        let mut state = self.lock();
+        let outcome = state.journal.undo();
+        self.emit_status(&state);
+        outcome
+    }
+
+    pub fn redo(&self) -> UndoOutcome {
+        let mut state = self.lock();
+        let outcome = state.journal.redo();
+        self.emit_status(&state);
+        outcome
+    }
+
+    /// Rename `path` by the first rule matching it, as if it had just
+    /// arrived, and report the outcome as a `file` event.
+    pub fn process(&self, path: &Path) -> Outcome {
+        let mut state = self.lock();
+        if state.own_renames.remove(path) {
+            return Outcome::Unchanged;
+        }
+        let (directory, file) = match (path.parent(), path.file_name()) {
+            (Some(directory), Some(file)) => (directory.to_path_buf(), file.to_string_lossy().into_owned()),
+            _ => return Outcome::Unchanged,
+        };
+        let outcome = rename(&mut state, path, &directory, &file);
+        drop(state);
+        let (kind, target, message) = match &outcome {
+            Outcome::Renamed { target, .. } => ("applied", Some(target), None),
+            Outcome::Previewed { target, .. } => ("preview", Some(target), None),
+            Outcome::Failed(message) => ("error", None, Some(message)),
+            Outcome::Unchanged => return outcome,
+        };
+        let mut payload = json!({
+            "kind": kind,
+            "directory": directory.to_string_lossy(),
+            "file": file,
+            "timestamp": now_ms(),
+        });
+        if let Some(target) = target {
+            payload["target"] = json!(target);
+        }
+        if let Some(message) = message {
+            payload["message"] = json!(message);
+        }
+        (self.shared.events)("file", payload);
+        outcome
+    }
+
+    /// Name each file that arrives once it has stopped growing. Runs until
+    /// the watcher is dropped.
+    fn process_arrivals(&self, arrivals: Receiver<PathBuf>) {
+        for path in arrivals {
+            if settle(&path) {
+                self.process(&path);
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn emit_status(&self, state: &State) -> Status {
+        let status = status_of(state);
+        (self.shared.events)("status", serde_json::to_value(&status).unwrap_or_default());
+        status
+    }
+}
+
+fn status_of(state: &State) -> Status {
+    Status {
+        running: state.running,
+        directories: state.config.watch_dirs.clone(),
+        dry_run: state.config.dry_run,
+        can_redo: state.journal.can_redo(),
+    }
+}
+
+fn save(state: &State) -> Result<(), String> {
+    state.config.save(&state.paths.config).map_err(|err| format!("Could not save settings: {}", err))
+}
+
+fn rename(state: &mut State, path: &Path, directory: &Path, file: &str) -> Outcome {
+    let Some((profile, matches)) = state.rules.find(file) else {
+        return Outcome::Unchanged;
+    };
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Outcome::Unchanged,
+    };
+    let created = metadata.created().or_else(|_| metadata.modified()).unwrap_or_else(|_| SystemTime::now());
+    let context = TemplateContext {
+        file,
+        created: DateTime::<Local>::from(created).naive_local(),
+        prefix: &profile.prefix,
+        counter: 1,
+        fiscal_year_start_month: profile.fiscal_year_start_month.unwrap_or(1),
+        matches,
+    };
+    let name = template::build_name(&profile.template, &context);
+    let rule = profile.id.clone();
+    if name == file || name.is_empty() || name.contains('/') {
+        // Folders in a name are left to the Node service, which creates them.
+        return Outcome::Unchanged;
+    }
+    let target = free_name(directory, &name);
+    if state.config.dry_run {
+        return Outcome::Previewed { target, rule };
+    }
+    let to = directory.join(&target);
+    if let Err(err) = fs::rename(path, &to) {
+        return Outcome::Failed(err.to_string());
+    }
+    if let Err(err) = state.journal.record(path, &to, Some(&rule)) {
+        log::warn!("Renamed {} but could not record it for undo: {}", file, err);
+    }
+    state.own_renames.insert(to);
+    Outcome::Renamed { target, rule }
+}
+
+/// `name`, or the first `_N` variant of it no file in `directory` has.
+fn free_name(directory: &Path, name: &str) -> String {
+    let (stem, ext) = template::split_extension(name);
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while directory.join(&candidate).exists() {
+        candidate = format!("{}_{}{}", stem, n, ext);
+        n += 1;
+    }
+    candidate
+}
+
+/// Wait for a new file to stop growing. False when it's gone or not a file.
+fn settle(path: &Path) -> bool {
+    let started = std::time::Instant::now();
+    let mut last = None;
+    loop {
+        let size = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => return false,
+        };
+        if last == Some(size) || started.elapsed() >= SETTLE_MAX {
+            return true;
+        }
+        last = Some(size);
+        thread::sleep(SETTLE_POLL);
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Sender;
+
+    fn start(dir: &Path, config: Value) -> (Engine, Receiver<(String, Value)>) {
+        let paths = Paths { config: dir.join("config.json"), journal: dir.join("journal.ndjson") };
+        fs::write(&paths.config, config.to_string()).unwrap();
+        let (sender, events) = mpsc::channel();
+        let sender: Mutex<Sender<(String, Value)>> = Mutex::new(sender);
+        let sink: EventSink = Box::new(move |name, payload| {
+            let _ = sender.lock().unwrap().send((name.to_string(), payload));
+        });
+        (Engine::start(paths, sink).unwrap(), events)
+    }
+
+    fn shots_rule() -> Value {
+        json!({
+            "id": "shots",
+            "name": "Shots",
+            "enabled": true,
+            "pattern": "shot*",
+            "template": "<prefix>_<original>",
+            "prefix": "Snap",
+        })
+    }
+
+    #[test]
+    fn arrivals_are_renamed_journaled_and_undone() {
+        let dir = tempfile::tempdir().unwrap();
+        let inbox = dir.path().join("inbox");
+        fs::create_dir(&inbox).unwrap();
+        let (engine, events) = start(dir.path(), json!({ "profiles": [shots_rule()], "theme": "dark" }));
+        engine.add_watch_dir(&inbox.to_string_lossy()).unwrap();
+        // Files are handed to `process` directly here, not by the watcher.
+        engine.set_running(Some(false)).unwrap();
+
+        fs::write(inbox.join("Snap_shot.png"), "taken").unwrap();
+        fs::write(inbox.join("shot.png"), "new").unwrap();
+        let outcome = engine.process(&inbox.join("shot.png"));
+        assert_eq!(outcome, Outcome::Renamed { target: "Snap_shot_2.png".to_string(), rule: "shots".to_string() });
+        assert_eq!(fs::read_to_string(inbox.join("Snap_shot_2.png")).unwrap(), "new");
+        assert_eq!(engine.process(&inbox.join("Snap_shot_2.png")), Outcome::Unchanged);
+        let applied = events.try_iter().find(|(name, _)| name == "file").unwrap().1;
+        assert_eq!((applied["kind"].as_str(), applied["target"].as_str()), (Some("applied"), Some("Snap_shot_2.png")));
+
+        assert!(engine.undo().ok);
+        assert!(inbox.join("shot.png").exists());
+        assert!(engine.status().can_redo);
+
+        engine.set_dry_run(true).unwrap();
+        assert!(matches!(engine.process(&inbox.join("shot.png")), Outcome::Previewed { .. }));
+        assert!(inbox.join("shot.png").exists());
+        assert_eq!(engine.process(&inbox.join("notes.txt")), Outcome::Unchanged);
+
+        let saved: Value = serde_json::from_str(&fs::read_to_string(dir.path().join("config.json")).unwrap()).unwrap();
+        assert_eq!((saved["dryRun"].as_bool(), saved["theme"].as_str()), (Some(true), Some("dark")));
+        assert_eq!(saved["watchDirs"][0].as_str(), Some(inbox.to_string_lossy().as_ref()));
+    }
+
+    #[test]
+    fn files_arriving_in_a_watched_folder_are_renamed() {
+        let dir = tempfile::tempdir().unwrap();
+        let inbox = dir.path().join("inbox");
+        fs::create_dir(&inbox).unwrap();
+        let config = json!({ "profiles": [shots_rule()], "watchDirs": [inbox.to_string_lossy()] });
+        let (engine, events) = start(dir.path(), config);
+        assert!(engine.status().running);
+
+        fs::write(inbox.join("shot.png"), "new").unwrap();
+        let applied = std::iter::from_fn(|| events.recv_timeout(Duration::from_secs(10)).ok())
+            .find(|(name, payload)| name == "file" && payload["kind"] == "applied")
+            .map(|(_, payload)| payload);
+        assert_eq!(applied.unwrap()["target"], "Snap_shot.png");
+        assert!(inbox.join("Snap_shot.png").exists());
+
+        engine.set_running(Some(false)).unwrap();
+        assert!(!engine.status().running);
+    }
+}
@@ -0,0 +1,256 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Undone renames kept for [`Journal::redo`], as in the Node service.
+const MAX_REDO: usize = 50;
+
+/// One rename, written as a line of `journal.ndjson`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub from: String,
+    pub to: String,
+    /// Epoch milliseconds.
+    pub ts: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Details the Node service records (sessions, sizes), kept as they are.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// How an undo or redo went, as the service's `undo` method reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoOutcome {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl UndoOutcome {
+    fn done() -> Self {
+        Self { ok: true, reason: None }
+    }
+
+    fn failed(reason: impl Into<String>) -> Self {
+        Self { ok: false, reason: Some(reason.into()) }
+    }
+}
+
+enum Line {
+    Entry(Entry),
+    /// A line that didn't parse, written back as it was rather than lost.
+    Unreadable(String),
+}
+
+/// The rename journal the Node service keeps, one JSON entry per line, so
+/// renames made by either engine can be undone by the other.
+pub struct Journal {
+    path: PathBuf,
+    lines: Vec<Line>,
+    /// Undone renames, newest last, and where each file was put back.
+    undone: Vec<(Entry, PathBuf)>,
+}
+
+impl Journal {
+    /// Read the journal at `path`; a missing file is an empty journal.
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let lines: Vec<Line> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map(Line::Entry).unwrap_or_else(|_| Line::Unreadable(line.to_string()))
+            })
+            .collect();
+        let unreadable = lines.iter().filter(|line| matches!(line, Line::Unreadable(_))).count();
+        if unreadable > 0 {
+            log::warn!("{} lines of {} could not be read; keeping them as they are", unreadable, path.display());
+        }
+        Ok(Self { path, lines, undone: Vec::new() })
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry(entry) => Some(entry),
+            Line::Unreadable(_) => None,
+        })
+    }
+
+    /// Append a rename of `from` to `to` made by `rule`.
+    pub fn record(&mut self, from: &Path, to: &Path, rule: Option<&str>) -> io::Result<()> {
+        let entry = Entry {
+            id: Some(new_id()),
+            from: from.to_string_lossy().into_owned(),
+            to: to.to_string_lossy().into_owned(),
+            ts: now_ms(),
+            rule: rule.map(str::to_string),
+            extra: Map::new(),
+        };
+        self.append(entry)
+    }
+
+    /// Put the most recently renamed file back.
+    pub fn undo(&mut self) -> UndoOutcome {
+        let Some(index) = self.lines.iter().rposition(|line| matches!(line, Line::Entry(_))) else {
+            return UndoOutcome::failed("empty");
+        };
+        let Line::Entry(entry) = &self.lines[index] else { unreachable!() };
+        let entry = entry.clone();
+        let target = restore_target(Path::new(&entry.from));
+        if let Err(err) = fs::rename(&entry.to, &target) {
+            return UndoOutcome::failed(err.to_string());
+        }
+        self.lines.remove(index);
+        self.undone.push((entry, target));
+        if self.undone.len() > MAX_REDO {
+            self.undone.remove(0);
+        }
+        match self.rewrite() {
+            Ok(()) => UndoOutcome::done(),
+            Err(err) => UndoOutcome::failed(format!("the file was put back, but history could not be saved: {}", err)),
+        }
+    }
+
+    /// Rename the most recently undone file again and put it back in history.
+    pub fn redo(&mut self) -> UndoOutcome {
+        let Some((entry, restored_to)) = self.undone.pop() else {
+            return UndoOutcome::failed("empty");
+        };
+        let to = PathBuf::from(&entry.to);
+        let renamed = if to.exists() {
+            Err(format!("{} already exists", file_name(&to)))
+        } else {
+            fs::rename(&restored_to, &to).map_err(|err| err.to_string())
+        };
+        if let Err(reason) = renamed {
+            self.undone.push((entry, restored_to));
+            return UndoOutcome::failed(reason);
+        }
+        let entry = Entry { from: restored_to.to_string_lossy().into_owned(), ts: now_ms(), ..entry };
+        match self.append(entry) {
+            Ok(()) => UndoOutcome::done(),
+            Err(err) => UndoOutcome::failed(format!("the file was renamed, but history could not be saved: {}", err)),
+        }
+    }
+
+    /// Whether an undone rename can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    fn append(&mut self, entry: Entry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        self.lines.push(Line::Entry(entry));
+        Ok(())
+    }
+
+    /// Write every line out again through a temporary file.
+    fn rewrite(&self) -> io::Result<()> {
+        let mut text = String::new();
+        for line in &self.lines {
+            match line {
+                Line::Entry(entry) => text.push_str(&serde_json::to_string(entry)?),
+                Line::Unreadable(raw) => text.push_str(raw),
+            }
+            text.push('\n');
+        }
+        let staged = self.path.with_extension("ndjson.tmp");
+        fs::write(&staged, text)?;
+        fs::rename(&staged, &self.path)
+    }
+}
+
+/// `from`, or a `_restored` variant of it when another file has taken its name since.
+fn restore_target(from: &Path) -> PathBuf {
+    if !from.exists() {
+        return from.to_path_buf();
+    }
+    let name = file_name(from);
+    let (stem, ext) = crate::template::split_extension(&name);
+    let directory = from.parent().unwrap_or(Path::new(""));
+    let mut candidate = directory.join(format!("{}_restored{}", stem, ext));
+    let mut n = 1;
+    while candidate.exists() {
+        n += 1;
+        candidate = directory.join(format!("{}_restored_{}{}", stem, n, ext));
+    }
+    candidate
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or_default()
+}
+
+/// 32 hex digits, the shape of the ids the Node service derives for old entries.
+fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos()).unwrap_or_default();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed) ^ u64::from(std::process::id()) << 32;
+    format!("{:016x}{:016x}", nanos as u64, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renames_are_undone_and_redone() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a.png"), dir.path().join("b.png"));
+        let path = dir.path().join("journal.ndjson");
+        fs::write(&to, "x").unwrap();
+        fs::write(&path, "{\"from\":\"/old\",\"to\":\"/new\",\"ts\":1,\"session\":\"s1\"}\nnot json\n").unwrap();
+
+        let mut journal = Journal::open(path.clone()).unwrap();
+        journal.record(&from, &to, Some("shots")).unwrap();
+        assert_eq!(journal.entries().count(), 2);
+
+        assert_eq!(journal.undo(), UndoOutcome::done());
+        assert!(from.exists() && !to.exists());
+        assert!(journal.can_redo());
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("\"session\":\"s1\"") && saved.contains("not json\n"));
+        assert!(!saved.contains("b.png"));
+
+        assert_eq!(journal.redo(), UndoOutcome::done());
+        assert!(to.exists() && !from.exists());
+        assert_eq!(journal.entries().last().unwrap().rule.as_deref(), Some("shots"));
+        assert_eq!(Journal::open(path).unwrap().entries().count(), 2);
+        assert_eq!(journal.redo(), UndoOutcome::failed("empty"));
+    }
+
+    #[test]
+    fn undo_keeps_a_file_that_took_the_old_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a.png"), dir.path().join("b.png"));
+        fs::write(&from, "new arrival").unwrap();
+        fs::write(&to, "renamed").unwrap();
+        let mut journal = Journal::open(dir.path().join("journal.ndjson")).unwrap();
+        journal.record(&from, &to, None).unwrap();
+
+        assert!(journal.undo().ok);
+        assert_eq!(fs::read_to_string(&from).unwrap(), "new arrival");
+        assert_eq!(fs::read_to_string(dir.path().join("a_restored.png")).unwrap(), "renamed");
+        assert_eq!(journal.undo(), UndoOutcome::failed("empty"));
+    }
+}
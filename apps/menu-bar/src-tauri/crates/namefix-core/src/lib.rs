@@ -0,0 +1,18 @@
+//! The Namefix rename engine in Rust: watches folders with `notify`, renames
+//! arriving files by the user's rules, and journals each rename for undo.
+//! It shares `config.json` and `journal.ndjson` with the Node service, so
+//! either can take over from the other. See `docs/native-engine.md`.
+
+pub mod config;
+pub mod engine;
+pub mod journal;
+pub mod paths;
+pub mod rules;
+pub mod template;
+pub mod watcher;
+
+pub use config::Config;
+pub use engine::{Engine, EventSink, Outcome, Status};
+pub use journal::{Journal, UndoOutcome};
+pub use paths::Paths;
+pub use rules::{Profile, RuleSet};
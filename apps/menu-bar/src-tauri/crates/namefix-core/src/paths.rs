@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+const APP: &str = "namefix";
+
+/// The files the engine reads and writes. [`Paths::default_locations`] picks
+/// the ones the Node service uses, so users can switch engines either way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub config: PathBuf,
+    pub journal: PathBuf,
+}
+
+impl Paths {
+    /// `config.json` in the config directory and `journal.ndjson` in the
+    /// state directory, or in `data_dir` when the user moved their history.
+    pub fn default_locations(data_dir: Option<&str>) -> Self {
+        let state = data_dir.filter(|dir| !dir.is_empty()).map(PathBuf::from).unwrap_or_else(state_dir);
+        Self { config: config_dir().join("config.json"), journal: state.join("journal.ndjson") }
+    }
+}
+
+/// `NAMEFIX_HOME`, else `$XDG_CONFIG_HOME/namefix`, else the platform's
+/// config directory.
+pub fn config_dir() -> PathBuf {
+    if let Some(home) = non_empty_var("NAMEFIX_HOME") {
+        return PathBuf::from(home);
+    }
+    if let Some(xdg) = non_empty_var("XDG_CONFIG_HOME") {
+        return PathBuf::from(xdg).join(APP);
+    }
+    if cfg!(target_os = "macos") {
+        return home_dir().join("Library").join("Application Support").join(APP);
+    }
+    home_dir().join(".config").join(APP)
+}
+
+/// `$XDG_STATE_HOME/namefix`, else the platform's state directory.
+pub fn state_dir() -> PathBuf {
+    if let Some(xdg) = non_empty_var("XDG_STATE_HOME") {
+        return PathBuf::from(xdg).join(APP);
+    }
+    if cfg!(target_os = "macos") {
+        return home_dir().join("Library").join("Application Support").join(APP);
+    }
+    home_dir().join(".local").join("state").join(APP)
+}
+
+fn home_dir() -> PathBuf {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    non_empty_var(var).map(PathBuf::from).unwrap_or_default()
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
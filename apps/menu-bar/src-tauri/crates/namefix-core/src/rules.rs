@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::template;
+
+/// A rename rule, stored in `config.json` as the Node service's `IProfile`.
+/// Settings this engine doesn't act on are kept in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub pattern: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    pub template: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiscal_year_start_month: Option<u32>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Rule settings only the Node service acts on.
+const NODE_ONLY_SETTINGS: [&str; 4] = ["condition", "caseStyle", "datePrefix", "timeZone"];
+
+impl Profile {
+    /// Why this engine can't name files as the Node service would with this
+    /// rule, if it can't. Such rules are left alone rather than half applied.
+    pub fn unsupported(&self) -> Option<String> {
+        let action = self.extra.get("action").and_then(Value::as_str).unwrap_or("rename");
+        if action != "rename" {
+            return Some(format!("rule '{}' converts files", self.name));
+        }
+        let set = |key: &str| match self.extra.get(key) {
+            None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+            Some(Value::String(value)) => !value.trim().is_empty(),
+            Some(_) => true,
+        };
+        if let Some(setting) = NODE_ONLY_SETTINGS.iter().find(|key| set(key)) {
+            return Some(format!("rule '{}' sets {}", self.name, setting));
+        }
+        if template::uses_unsupported_variables(&self.template) {
+            return Some(format!("rule '{}' uses variables only the Node service fills in", self.name));
+        }
+        None
+    }
+}
+
+enum Pattern {
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+/// The enabled rules this engine can apply, in priority order.
+pub struct RuleSet {
+    rules: Vec<(Profile, Pattern)>,
+}
+
+impl RuleSet {
+    /// Compile `profiles`, logging and leaving out rules with a bad pattern
+    /// or settings this engine can't apply.
+    pub fn new(profiles: &[Profile]) -> Self {
+        let mut enabled: Vec<&Profile> = profiles.iter().filter(|profile| profile.enabled).collect();
+        enabled.sort_by_key(|profile| profile.priority);
+        let rules = enabled
+            .into_iter()
+            .filter_map(|profile| {
+                if let Some(reason) = profile.unsupported() {
+                    log::warn!("Skipping {}; use the Node service for it", reason);
+                    return None;
+                }
+                match compile(profile) {
+                    Ok(pattern) => Some((profile.clone(), pattern)),
+                    Err(err) => {
+                        log::warn!("Skipping rule '{}': {}", profile.name, err);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The first rule matching `file`, with the groups a regex rule
+    /// captured by index and name. Hidden files match nothing.
+    pub fn find(&self, file: &str) -> Option<(&Profile, HashMap<String, String>)> {
+        if file.is_empty() || file.starts_with('.') {
+            return None;
+        }
+        self.rules.iter().find_map(|(profile, pattern)| match pattern {
+            Pattern::Glob(glob) => glob.is_match(file).then(|| (profile, HashMap::new())),
+            Pattern::Regex(regex) => regex.captures(file).map(|captures| {
+                let mut groups: HashMap<String, String> = captures
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, group)| Some((index.to_string(), group?.as_str().to_string())))
+                    .collect();
+                for name in regex.capture_names().flatten() {
+                    if let Some(group) = captures.name(name) {
+                        groups.insert(name.to_string(), group.as_str().to_string());
+                    }
+                }
+                (profile, groups)
+            }),
+        })
+    }
+}
+
+/// Globs match case-insensitively and don't cross folders, as picomatch does
+/// for the Node service.
+fn compile(profile: &Profile) -> Result<Pattern, String> {
+    if profile.is_regex {
+        return Regex::new(&profile.pattern).map(Pattern::Regex).map_err(|err| err.to_string());
+    }
+    GlobBuilder::new(&profile.pattern)
+        .case_insensitive(true)
+        .literal_separator(true)
+        .build()
+        .map(|glob| Pattern::Glob(glob.compile_matcher()))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(value: Value) -> Profile {
+        let mut base = json!({ "id": "p", "name": "P", "enabled": true, "pattern": "*", "template": "<original>" });
+        base.as_object_mut().unwrap().extend(value.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn the_first_matching_rule_by_priority_wins() {
+        let rules = RuleSet::new(&[
+            profile(json!({ "id": "any", "priority": 5 })),
+            profile(json!({ "id": "shots", "pattern": "screenshot*", "priority": 1 })),
+            profile(json!({ "id": "off", "pattern": "*.png", "enabled": false, "priority": 0 })),
+        ]);
+        assert_eq!(rules.find("Screenshot 1.png").unwrap().0.id, "shots");
+        assert_eq!(rules.find("notes.txt").unwrap().0.id, "any");
+        assert!(rules.find(".hidden").is_none());
+    }
+
+    #[test]
+    fn regex_rules_capture_groups() {
+        let rules = RuleSet::new(&[profile(json!({ "pattern": r"^IMG_(?<n>\d+)", "isRegex": true }))]);
+        let (_, groups) = rules.find("IMG_0042.jpg").unwrap();
+        assert_eq!((groups["0"].as_str(), groups["1"].as_str(), groups["n"].as_str()), ("IMG_0042", "0042", "0042"));
+        assert!(rules.find("img_0042.jpg").is_none());
+    }
+
+    #[test]
+    fn rules_needing_the_node_service_are_left_out() {
+        let convert = profile(json!({ "action": "convert" }));
+        let conditional = profile(json!({ "condition": "size > 10" }));
+        let audio = profile(json!({ "template": "<artist> - <title>" }));
+        let plain = profile(json!({ "condition": "", "caseStyle": null, "datePrefix": false }));
+        assert!(convert.unsupported().unwrap().contains("converts"));
+        assert!(conditional.unsupported().unwrap().contains("condition"));
+        assert!(audio.unsupported().is_some());
+        assert_eq!(plain.unsupported(), None);
+        assert!(RuleSet::new(&[convert, conditional, audio]).find("a.png").is_none());
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use regex::{Captures, Regex};
+
+/// Variables the Node service fills from a document's text, audio tags or
+/// the front window, or from `<expr:…>` segments. This engine can't, so
+/// rules that use them are left to the Node service.
+pub const UNSUPPORTED_VARIABLES: [&str; 13] = [
+    "vendor",
+    "amount",
+    "currency",
+    "doc_date",
+    "doc_year",
+    "doc_month",
+    "artist",
+    "album_artist",
+    "album",
+    "title",
+    "track",
+    "disc",
+    "window_title",
+];
+
+/// A pattern compiled on first use.
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        PATTERN.get_or_init(|| Regex::new($pattern).expect("valid pattern"))
+    }};
+}
+
+/// Stands in for a regex group the file's name didn't have, until the name is tidied.
+const MISSING: &str = "\0";
+
+/// What a template is filled in from.
+#[derive(Debug, Clone)]
+pub struct TemplateContext<'a> {
+    /// The file's current name.
+    pub file: &'a str,
+    /// When the file was created, in local time.
+    pub created: NaiveDateTime,
+    pub prefix: &'a str,
+    pub counter: u32,
+    /// First month of the fiscal year, 1-12.
+    pub fiscal_year_start_month: u32,
+    /// Groups a regex rule captured from the file's name, by index and name.
+    pub matches: HashMap<String, String>,
+}
+
+/// Whether `template` uses a variable this engine can't fill in.
+pub fn uses_unsupported_variables(template: &str) -> bool {
+    if template.contains("<expr:") {
+        return true;
+    }
+    regex!(r"<(?:(\w+):)?(\w+)>").captures_iter(template).any(|captures| UNSUPPORTED_VARIABLES.contains(&&captures[2]))
+}
+
+/// `(stem, extension with its dot)`, split as Node's `path.extname` does.
+pub fn split_extension(file: &str) -> (&str, &str) {
+    match file.rfind('.') {
+        Some(index) if index > 0 => file.split_at(index),
+        _ => (file, ""),
+    }
+}
+
+/// The file's new name: the filled-in template with the lowercased
+/// extension appended, unless the template places `<ext>` itself.
+pub fn build_name(template: &str, context: &TemplateContext) -> String {
+    let name = apply_template(template, context);
+    if template.contains("<ext>") {
+        return name;
+    }
+    format!("{}{}", name, split_extension(context.file).1.to_lowercase())
+}
+
+/// Fill in `template`'s variables, as the Node service's `applyTemplate`
+/// does for the ones listed in the service API docs. Unknown variables are
+/// left as written.
+pub fn apply_template(template: &str, context: &TemplateContext) -> String {
+    let d = context.created;
+    let (stem, ext) = split_extension(context.file);
+    let date = d.format("%Y-%m-%d").to_string();
+    let time = d.format("%H-%M-%S").to_string();
+    let (fiscal_year, fiscal_quarter) = fiscal_period(d, context.fiscal_year_start_month);
+    let prefix = sanitize_prefix(if context.prefix.is_empty() { "File" } else { context.prefix });
+    let vars: HashMap<&str, String> = HashMap::from([
+        ("datetime", format!("{}_{}", date, time)),
+        ("date", date),
+        ("time", time),
+        ("original", stem.to_string()),
+        ("ext", ext.to_lowercase()),
+        ("prefix", prefix),
+        ("year", d.year().to_string()),
+        ("month", pad(d.month(), 2)),
+        ("day", pad(d.day(), 2)),
+        ("hour", pad(d.hour(), 2)),
+        ("minute", pad(d.minute(), 2)),
+        ("second", pad(d.second(), 2)),
+        ("week", pad(d.iso_week().week(), 2)),
+        ("fiscal_year", fiscal_year.to_string()),
+        ("fiscal_quarter", format!("Q{}", fiscal_quarter)),
+        ("counter", pad(context.counter, 3)),
+    ]);
+
+    let result = regex!(r"<date:([^<>]+)>").replace_all(template, |c: &Captures| format_date(d, &c[1]));
+    let result =
+        regex!(r"<counter:(\d+)>").replace_all(&result, |c: &Captures| pad(context.counter, c[1].parse().unwrap_or(3)));
+    let result = regex!(r"<match:(\w+)>")
+        .replace_all(&result, |c: &Captures| segment_safe(context.matches.get(&c[1]).map(String::as_str)));
+    let result = regex!(r"<(upper|lower|slug):(\w+)>").replace_all(&result, |c: &Captures| {
+        let value = vars.get(&c[2]).map(String::as_str).unwrap_or_default();
+        match &c[1] {
+            "upper" => value.to_uppercase(),
+            "lower" => value.to_lowercase(),
+            _ => slug(value),
+        }
+    });
+    let result = regex!(r"<(\w+)>")
+        .replace_all(&result, |c: &Captures| vars.get(&c[1]).cloned().unwrap_or_else(|| c[0].to_string()));
+    if !result.contains(MISSING) {
+        return result.into_owned();
+    }
+    // Drop missing groups with the separator after them, then any left dangling or empty.
+    let result = regex!(r"([_\- ]*)\x00+[_\- ]*").replace_all(&result, "$1");
+    let segments: Vec<&str> = result
+        .split('/')
+        .map(|segment| segment.trim_matches(|c| matches!(c, '_' | '-' | ' ')))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    segments.join("/")
+}
+
+/// Format a date with strftime-style directives: %Y %y %m %d %H %M %S %j,
+/// ISO week dates %G %V %u, and %% for a literal percent sign. Unknown
+/// directives are left untouched.
+pub fn format_date(d: NaiveDateTime, format: &str) -> String {
+    let mut output = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        let Some(directive) = chars.next() else {
+            output.push('%');
+            break;
+        };
+        match directive {
+            'Y' => output.push_str(&d.year().to_string()),
+            'y' => output.push_str(&pad(d.year().rem_euclid(100) as u32, 2)),
+            'm' => output.push_str(&pad(d.month(), 2)),
+            'd' => output.push_str(&pad(d.day(), 2)),
+            'H' => output.push_str(&pad(d.hour(), 2)),
+            'M' => output.push_str(&pad(d.minute(), 2)),
+            'S' => output.push_str(&pad(d.second(), 2)),
+            'j' => output.push_str(&pad(d.ordinal(), 3)),
+            'G' => output.push_str(&d.iso_week().year().to_string()),
+            'V' => output.push_str(&pad(d.iso_week().week(), 2)),
+            'u' => output.push_str(&d.weekday().number_from_monday().to_string()),
+            '%' => output.push('%'),
+            other => {
+                output.push('%');
+                output.push(other);
+            }
+        }
+    }
+    output
+}
+
+/// Fiscal year and quarter for a year starting in `start_month` (1-12),
+/// named after the calendar year it ends in.
+pub fn fiscal_period(d: NaiveDateTime, start_month: u32) -> (i32, u32) {
+    let start = if (1..=12).contains(&start_month) { start_month } else { 1 };
+    let offset = (d.month() + 12 - start) % 12;
+    let year = if start == 1 || d.month() < start { d.year() } else { d.year() + 1 };
+    (year, offset / 3 + 1)
+}
+
+fn sanitize_prefix(prefix: &str) -> String {
+    regex!(r"\s+").replace_all(prefix.trim(), "_").into_owned()
+}
+
+fn pad(value: u32, digits: usize) -> String {
+    format!("{:0width$}", value, width = digits)
+}
+
+fn slug(value: &str) -> String {
+    if value == MISSING {
+        return value.to_string();
+    }
+    regex!("[^a-z0-9]+").replace_all(&value.trim().to_lowercase(), "-").trim_matches('-').to_string()
+}
+
+/// A captured group made safe for one path segment: no separators or
+/// characters Windows rejects, and no trailing dots or spaces.
+fn segment_safe(value: Option<&str>) -> String {
+    let safe = regex!(r#"[\\/:*?"<>|\x00-\x1f]"#).replace_all(value.unwrap_or_default(), " ");
+    let safe = regex!(r"\s+").replace_all(&safe, " ");
+    let safe = safe.trim_matches(|c: char| c.is_whitespace() || c == '.');
+    if safe.is_empty() {
+        MISSING.to_string()
+    } else {
+        safe.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn context(file: &str) -> TemplateContext<'_> {
+        TemplateContext {
+            file,
+            created: NaiveDate::from_ymd_opt(2024, 12, 30).unwrap().and_hms_opt(14, 5, 9).unwrap(),
+            prefix: "Screen Shot",
+            counter: 7,
+            fiscal_year_start_month: 4,
+            matches: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn variables_fill_in_like_the_node_service() {
+        let ctx = context("Report Final.PDF");
+        assert_eq!(build_name("<prefix>_<datetime>", &ctx), "Screen_Shot_2024-12-30_14-05-09.pdf");
+        assert_eq!(build_name("<original>-<counter>-<counter:5>", &ctx), "Report Final-007-00007.pdf");
+        assert_eq!(build_name("<date:%G-W%V-%u %j %q %%>", &ctx), "2025-W01-1 365 %q %.pdf");
+        assert_eq!(build_name("<fiscal_year>-<fiscal_quarter>_<week>", &ctx), "2025-Q3_01.pdf");
+        assert_eq!(build_name("<slug:original><ext>", &ctx), "report-final.pdf");
+        assert_eq!(build_name("<upper:original>_<unknown>", &ctx), "REPORT FINAL_<unknown>.pdf");
+    }
+
+    #[test]
+    fn regex_groups_fill_in_and_missing_ones_are_tidied_away() {
+        let mut ctx = context("IMG_1234.jpg");
+        ctx.matches =
+            HashMap::from([("0".to_string(), "IMG_1234".to_string()), ("n".to_string(), "12/34".to_string())]);
+        assert_eq!(build_name("photo_<match:n>", &ctx), "photo_12 34.jpg");
+        assert_eq!(build_name("<match:gone>_photo_<match:0>", &ctx), "photo_IMG_1234.jpg");
+        assert_eq!(build_name("<match:gone>/<match:0>", &ctx), "IMG_1234.jpg");
+    }
+
+    #[test]
+    fn rules_needing_the_node_service_are_recognized() {
+        assert!(uses_unsupported_variables("<artist> - <title>"));
+        assert!(uses_unsupported_variables("<upper:vendor>"));
+        assert!(uses_unsupported_variables("<expr:stem>"));
+        assert!(!uses_unsupported_variables("<date:%Y>/<original>"));
+    }
+}
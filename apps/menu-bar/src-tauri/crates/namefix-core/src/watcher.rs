@@ -0,0 +1,34 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+/// Watches folders, not their subfolders, and reports each path that
+/// appears in one: created there, or renamed or moved into it.
+pub struct Watcher {
+    inner: RecommendedWatcher,
+}
+
+impl Watcher {
+    pub fn new(arrivals: Sender<PathBuf>) -> notify::Result<Self> {
+        let inner = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) => {
+                for path in event.paths {
+                    let _ = arrivals.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("Watch error: {}", err),
+        })?;
+        Ok(Self { inner })
+    }
+
+    pub fn watch(&mut self, directory: &Path) -> notify::Result<()> {
+        self.inner.watch(directory, RecursiveMode::NonRecursive)
+    }
+
+    pub fn unwatch(&mut self, directory: &Path) -> notify::Result<()> {
+        self.inner.unwatch(directory)
+    }
+}
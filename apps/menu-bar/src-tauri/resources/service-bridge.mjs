@@ -1,9 +1,18 @@
 #!/usr/bin/env node
+// Runs NamefixService and answers JSON-RPC, one JSON object per line. Over stdio by default; with
+// `--listen <path>` on a Unix socket (a named pipe on Windows) instead, where any number of clients
+// can connect and the service keeps running when they go, so a restarted menu bar app picks up
+// the same watchers.
 import { createInterface } from 'node:readline/promises';
-import { stdin, stdout, stderr, exit } from 'node:process';
+import { stdin, stdout, stderr, exit, argv, platform } from 'node:process';
 import { fileURLToPath } from 'node:url';
 import path from 'node:path';
-import { access } from 'node:fs/promises';
+import net from 'node:net';
+import { unlinkSync } from 'node:fs';
+import { access, chmod, unlink } from 'node:fs/promises';
+
+const listenIndex = argv.indexOf('--listen');
+const listenPath = listenIndex >= 0 ? argv[listenIndex + 1] : null;
 
 let dead = false;
 
@@ -44,9 +53,11 @@ process.on('unhandledRejection', (reason) => {
 	die(`unhandledRejection: ${reason instanceof Error ? reason.stack : reason}`);
 });
 
-stdout.on('error', () => die('stdout pipe error'));
-stdin.on('error', () => die('stdin pipe error'));
-stdin.on('end', () => die('stdin EOF (parent exited)'));
+if (!listenPath) {
+	stdout.on('error', () => die('stdout pipe error'));
+	stdin.on('error', () => die('stdin pipe error'));
+	stdin.on('end', () => die('stdin EOF (parent exited)'));
+}
 
 for (const sig of ['SIGTERM', 'SIGINT', 'SIGHUP']) {
 	process.on(sig, () => die(`signal ${sig}`));
@@ -79,15 +90,23 @@ const emitterUnsubs = [];
 let serviceReady = false;
 let service = null;
 
-function sendMessage(payload) {
+// Whoever is connected: stdout alone over stdio, each open socket otherwise.
+const clients = new Set();
+
+function write(client, payload) {
 	if (dead) return;
 	try {
-		stdout.write(`${JSON.stringify(payload)}\n`);
+		client.stream.write(`${JSON.stringify(payload)}\n`);
 	} catch {
-		// Don't die here — the stdout 'error' event handler will handle it
+		// Don't die here — the stream's 'error' handler deals with it
 	}
 }
 
+// Events go to every client; responses only to the one that asked.
+function sendMessage(payload) {
+	for (const client of clients) write(client, payload);
+}
+
 function forwardEvents() {
 	emitterUnsubs.push(
 		service.on('status', (status) => {
@@ -111,6 +130,26 @@ function forwardEvents() {
 			sendMessage({ event: 'toast', payload: toast });
 		}),
 	);
+	emitterUnsubs.push(
+		service.on('burst', (burst) => {
+			safeStderr(`[EVENT] burst: ${burst.count} events in ${burst.directory}`);
+			sendMessage({ event: 'burst', payload: burst });
+		}),
+	);
+	emitterUnsubs.push(
+		service.on('import', (progress) => {
+			safeStderr(
+				`[EVENT] import: ${progress.imported}/${progress.total} from ${progress.card}${progress.done ? ' (done)' : ''}`,
+			);
+			sendMessage({ event: 'import', payload: progress });
+		}),
+	);
+	emitterUnsubs.push(
+		service.on('undo', (undo) => {
+			safeStderr(`[EVENT] undo: ${undo.scope} undone=${undo.undone} failed=${undo.failed}`);
+			sendMessage({ event: 'undo', payload: undo });
+		}),
+	);
 	emitterUnsubs.push(
 		service.on('config', (config) => {
 			safeStderr('[EVENT] config changed');
@@ -119,6 +158,53 @@ function forwardEvents() {
 	);
 }
 
+// The client that answers the service's name hook (the menu bar app, which evaluates rule
+// expressions and runs its plugins there), and the questions it hasn't answered yet. A question
+// it takes too long on is answered as if there were no hook.
+const HOOK_TIMEOUT_MS = 2000;
+let hookClient = null;
+let nextHook = 1;
+const hookWaiters = new Map();
+
+function askHookClient(phase, params) {
+	const client = hookClient;
+	if (!client) return Promise.reject(new Error('No name hook client'));
+	const hook = nextHook++;
+	return new Promise((resolve, reject) => {
+		const timeout = () => settleHook(hook, { error: 'Name hook timed out' });
+		const timer = setTimeout(timeout, HOOK_TIMEOUT_MS);
+		hookWaiters.set(hook, { client, timer, resolve, reject });
+		write(client, { hook, phase, params });
+	});
+}
+
+function settleHook(hook, { result, error }) {
+	const waiter = hookWaiters.get(hook);
+	if (!waiter) return;
+	hookWaiters.delete(hook);
+	clearTimeout(waiter.timer);
+	if (error) waiter.reject(new Error(String(error)));
+	else waiter.resolve(result);
+}
+
+const nameHook = {
+	prepare: (request) => askHookClient('prepare', request),
+	review: (request) => askHookClient('review', request),
+};
+
+function installNameHook() {
+	if (serviceReady) service.setNameHook(hookClient ? nameHook : null);
+}
+
+function dropHookClient(client) {
+	for (const [hook, waiter] of hookWaiters) {
+		if (waiter.client === client) settleHook(hook, { error: 'Name hook client disconnected' });
+	}
+	if (hookClient !== client) return;
+	hookClient = null;
+	installNameHook();
+}
+
 // Start init in background — readline loop starts IMMEDIATELY below
 (async () => {
 	try {
@@ -127,6 +213,7 @@ function forwardEvents() {
 		await service.init();
 		await service.start();
 		serviceReady = true;
+		installNameHook();
 		forwardEvents();
 		// Push initial status to Rust so tray updates right away
 		sendMessage({ event: 'status', payload: service.getStatus() });
@@ -145,7 +232,22 @@ function requireReady(label) {
 const handlers = {
 	async getStatus() {
 		if (!serviceReady || !service) {
-			return { running: false, directories: [], dryRun: false, launchOnLogin: false };
+			return {
+				schemaVersion: 2,
+				running: false,
+				directories: [],
+				dryRun: false,
+				launchOnLogin: false,
+				priorityDirectories: [],
+				pinnedDirectories: [],
+				dryRunDirectories: [],
+				snoozedDirectories: [],
+				watchBackend: 'fsevents',
+				originRules: [],
+				directoryGroups: [],
+				directoryStats: [],
+				capabilities: [],
+			};
 		}
 		return service.getStatus();
 	},
@@ -179,6 +281,112 @@ const handlers = {
 		}
 		return svc.getStatus();
 	},
+	async setObserverMode(params = {}) {
+		const svc = requireReady('setObserverMode');
+		if (typeof params.enabled === 'boolean') {
+			await svc.setObserverMode(params.enabled);
+		}
+		return svc.getStatus();
+	},
+	async setLogPrivacy(params = {}) {
+		const svc = requireReady('setLogPrivacy');
+		if (typeof params.enabled === 'boolean') {
+			await svc.setLogPrivacy(params.enabled);
+		}
+		return svc.getStatus();
+	},
+	async repairHistory(params = {}) {
+		const svc = requireReady('repairHistory');
+		return svc.repairHistory(params.mode === 'restore' ? 'restore' : 'salvage');
+	},
+	async exportHistory(params = {}) {
+		const svc = requireReady('exportHistory');
+		const file = params.path;
+		if (typeof file !== 'string' || file.trim().length === 0) {
+			throw new Error('path is required');
+		}
+		return svc.exportHistory(file);
+	},
+	async mergeHistory(params = {}) {
+		const svc = requireReady('mergeHistory');
+		const file = params.path;
+		if (typeof file !== 'string' || file.trim().length === 0) {
+			throw new Error('path is required');
+		}
+		return svc.mergeHistory(file);
+	},
+	async setSyncDir(params = {}) {
+		const svc = requireReady('setSyncDir');
+		await svc.setSyncDir(typeof params.path === 'string' ? params.path : null);
+		return svc.getStatus();
+	},
+	async resolveSyncConflict(params = {}) {
+		const svc = requireReady('resolveSyncConflict');
+		await svc.resolveSyncConflict(params.keep === 'remote' ? 'remote' : 'local');
+		return svc.getStatus();
+	},
+	async exportSyncKey() {
+		const svc = requireReady('exportSyncKey');
+		return svc.exportSyncKey();
+	},
+	async importSyncKey(params = {}) {
+		const svc = requireReady('importSyncKey');
+		if (typeof params.key !== 'string' || params.key.trim().length === 0) {
+			throw new Error('key is required');
+		}
+		await svc.importSyncKey(params.key);
+		return svc.getStatus();
+	},
+	async setLanPairing(params = {}) {
+		const svc = requireReady('setLanPairing');
+		if (typeof params.enabled === 'boolean') {
+			await svc.setLanPairing(params.enabled);
+		}
+		return svc.getStatus();
+	},
+	async startPairing() {
+		const svc = requireReady('startPairing');
+		return svc.startPairing();
+	},
+	async findPeers() {
+		const svc = requireReady('findPeers');
+		return svc.findPeers();
+	},
+	async pairPeer(params = {}) {
+		const svc = requireReady('pairPeer');
+		if (typeof params.peerId !== 'string' || params.peerId.length === 0) {
+			throw new Error('peerId is required');
+		}
+		if (typeof params.code !== 'string' || params.code.trim().length === 0) {
+			throw new Error('code is required');
+		}
+		await svc.pairPeer(params.peerId, params.code);
+		return svc.getStatus();
+	},
+	async getPeerStatus(params = {}) {
+		const svc = requireReady('getPeerStatus');
+		if (typeof params.peerId !== 'string' || params.peerId.length === 0) {
+			throw new Error('peerId is required');
+		}
+		return svc.getPeerStatus(params.peerId);
+	},
+	async unpairPeer(params = {}) {
+		const svc = requireReady('unpairPeer');
+		if (typeof params.peerId !== 'string' || params.peerId.length === 0) {
+			throw new Error('peerId is required');
+		}
+		await svc.unpairPeer(params.peerId);
+		return svc.getStatus();
+	},
+	async setDataDir(params = {}) {
+		const svc = requireReady('setDataDir');
+		await svc.setDataDir(typeof params.path === 'string' ? params.path : null);
+		return svc.getStatus();
+	},
+	async getAuditLog(params = {}) {
+		const svc = requireReady('getAuditLog');
+		return svc.getAuditLog(Number.isInteger(params.limit) ? params.limit : undefined);
+	},
 	async addWatchDir(params = {}) {
 		const svc = requireReady('addWatchDir');
 		const dir = params.directory;
@@ -197,10 +405,280 @@ const handlers = {
 		await svc.removeWatchDir(dir);
 		return svc.getStatus().directories;
 	},
+	async setDirectoryPriority(params = {}) {
+		const svc = requireReady('setDirectoryPriority');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		await svc.setDirectoryPriority(dir, Boolean(params.high));
+		return svc.getStatus();
+	},
+	async setDirectoryDryRun(params = {}) {
+		const svc = requireReady('setDirectoryDryRun');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		await svc.setDirectoryDryRun(dir, Boolean(params.enabled));
+		return svc.getStatus();
+	},
+	async getDirConfig(params = {}) {
+		const svc = requireReady('getDirConfig');
+		if (typeof params.directory !== 'string' || params.directory.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		return svc.getDirConfig(params.directory);
+	},
+	async setDirConfig(params = {}) {
+		const svc = requireReady('setDirConfig');
+		if (typeof params.directory !== 'string' || params.directory.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		const config = params.config ?? {};
+		const isList = (value) => Array.isArray(value) && value.every((v) => typeof v === 'string');
+		const patch = {};
+		if (typeof config.recursive === 'boolean') patch.recursive = config.recursive;
+		if (isList(config.includeExtensions)) patch.includeExtensions = config.includeExtensions;
+		if (isList(config.excludeExtensions)) patch.excludeExtensions = config.excludeExtensions;
+		if (typeof config.dryRun === 'boolean') patch.dryRun = config.dryRun;
+		return svc.setDirConfig(params.directory, patch);
+	},
+	async setWatchBackend(params = {}) {
+		const svc = requireReady('setWatchBackend');
+		if (!['fsevents', 'endpoint-security', 'usn-journal', 'polling'].includes(params.backend)) {
+			throw new Error('backend must be fsevents, endpoint-security, usn-journal or polling');
+		}
+		if (params.pollIntervalMs !== undefined && !Number.isFinite(params.pollIntervalMs)) {
+			throw new Error('pollIntervalMs must be a number');
+		}
+		await svc.setWatchBackend(params.backend, params.pollIntervalMs);
+		return svc.getStatus();
+	},
+	async setDirectoryOriginRule(params = {}) {
+		const svc = requireReady('setDirectoryOriginRule');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		const strings = (list) => (Array.isArray(list) ? list.filter((n) => typeof n === 'string') : []);
+		await svc.setDirectoryOriginRule(dir, { allow: strings(params.allow), deny: strings(params.deny) });
+		return svc.getStatus();
+	},
+	async reorderDirectories(params = {}) {
+		const svc = requireReady('reorderDirectories');
+		if (!Array.isArray(params.order)) {
+			throw new Error('order is required');
+		}
+		await svc.reorderDirectories(params.order.filter((dir) => typeof dir === 'string'));
+		return svc.getStatus();
+	},
+	async setDirectoryPinned(params = {}) {
+		const svc = requireReady('setDirectoryPinned');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		await svc.setDirectoryPinned(dir, Boolean(params.pinned));
+		return svc.getStatus();
+	},
+	async setDirectoryGroup(params = {}) {
+		const svc = requireReady('setDirectoryGroup');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		await svc.setDirectoryGroup(dir, typeof params.group === 'string' ? params.group : null);
+		return svc.getStatus();
+	},
+	async setDirectoryHotFolder(params = {}) {
+		const svc = requireReady('setDirectoryHotFolder');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		const command = Array.isArray(params.command)
+			? params.command.filter((arg) => typeof arg === 'string')
+			: undefined;
+		const destination = typeof params.destination === 'string' ? params.destination : undefined;
+		await svc.setDirectoryHotFolder(dir, { destination, command });
+		return svc.getStatus();
+	},
+	async setWatchExclusions(params = {}) {
+		const svc = requireReady('setWatchExclusions');
+		const names = Array.isArray(params.names) ? params.names.filter((n) => typeof n === 'string') : [];
+		return svc.setWatchExclusions(names);
+	},
+	// The caller answers the name hook from now on, in place of any client before it.
+	async registerNameHook(_params, { client }) {
+		hookClient = client;
+		installNameHook();
+		return true;
+	},
+	async snoozeDirectory(params = {}) {
+		const svc = requireReady('snoozeDirectory');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		if (typeof params.until !== 'number') {
+			throw new Error('until is required');
+		}
+		svc.snoozeDirectory(dir, params.until);
+		return svc.getStatus();
+	},
+	async listImportPresets() {
+		const svc = requireReady('listImportPresets');
+		return svc.getConfig().importPresets ?? [];
+	},
+	async setImportPreset(params = {}) {
+		const svc = requireReady('setImportPreset');
+		const { preset } = params;
+		if (!preset || typeof preset !== 'object') {
+			throw new Error('preset is required');
+		}
+		if (typeof preset.destination !== 'string' || preset.destination.trim().length === 0) {
+			throw new Error('destination is required');
+		}
+		const cfg = await svc.setImportPreset(preset);
+		return cfg.importPresets ?? [];
+	},
+	async deleteImportPreset(params = {}) {
+		const svc = requireReady('deleteImportPreset');
+		if (typeof params.id !== 'string') {
+			throw new Error('preset id is required');
+		}
+		const cfg = await svc.deleteImportPreset(params.id);
+		return cfg.importPresets ?? [];
+	},
+	async runImportPreset(params = {}, { signal } = {}) {
+		const svc = requireReady('runImportPreset');
+		if (typeof params.id !== 'string') {
+			throw new Error('preset id is required');
+		}
+		return svc.runImportPreset(params.id, signal);
+	},
+	async setVolumeReleased(params = {}) {
+		const svc = requireReady('setVolumeReleased');
+		if (typeof params.root !== 'string' || params.root.trim().length === 0) {
+			throw new Error('root is required');
+		}
+		await svc.setVolumeReleased(params.root, params.released === true);
+		return svc.getStatus();
+	},
+	async searchHistory(params = {}) {
+		const svc = requireReady('searchHistory');
+		return svc.searchHistory(params.query ?? {});
+	},
+	async getHistory(params = {}) {
+		const svc = requireReady('getHistory');
+		const limit = Number.isInteger(params.limit) && params.limit > 0 ? params.limit : 50;
+		const offset = Number.isInteger(params.offset) && params.offset > 0 ? params.offset : 0;
+		return svc.getHistory(limit, offset);
+	},
+	async getDirectoryUsage(params = {}) {
+		const svc = requireReady('getDirectoryUsage');
+		if (params.since !== undefined && params.since !== null && !Number.isFinite(params.since)) {
+			throw new Error('since must be a timestamp');
+		}
+		return svc.getDirectoryUsage(params.since ?? undefined);
+	},
+	async recoverFromSleep(params = {}) {
+		const svc = requireReady('recoverFromSleep');
+		if (!Number.isFinite(params.since)) {
+			throw new Error('since must be a timestamp');
+		}
+		return svc.recoverFromSleep(params.since);
+	},
+	async pruneHistory(params = {}) {
+		const svc = requireReady('pruneHistory');
+		return svc.pruneHistory(params.policy ?? undefined);
+	},
+	async runBenchmark(params = {}) {
+		const svc = requireReady('runBenchmark');
+		const options = {};
+		if (typeof params.files === 'number') options.files = params.files;
+		if (typeof params.directory === 'string' && params.directory.trim().length > 0) {
+			options.directory = params.directory;
+		}
+		return svc.runBenchmark(options);
+	},
+	async getBenchmarkResults() {
+		const svc = requireReady('getBenchmarkResults');
+		return svc.getBenchmarkResults();
+	},
+	async planDirectory(params = {}) {
+		const svc = requireReady('planDirectory');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		const profile = typeof params.profile === 'string' ? params.profile : undefined;
+		return svc.planDirectory(dir, profile);
+	},
+	async runOnce(params = {}, { signal } = {}) {
+		const svc = requireReady('runOnce');
+		const dir = params.directory;
+		if (typeof dir !== 'string' || dir.trim().length === 0) {
+			throw new Error('directory is required');
+		}
+		const profile = typeof params.profile === 'string' ? params.profile : undefined;
+		return svc.runOnce(dir, profile, signal);
+	},
+	async applyPlan(params = {}, { signal } = {}) {
+		const svc = requireReady('applyPlan');
+		const plan = params.plan;
+		if (typeof plan?.directory !== 'string' || !Array.isArray(plan.entries)) {
+			throw new Error('plan with a directory and entries is required');
+		}
+		return svc.applyPlan(plan, signal);
+	},
+	async proposeName(params = {}) {
+		const svc = requireReady('proposeName');
+		if (typeof params.name !== 'string') {
+			throw new Error('name is required');
+		}
+		return svc.proposeName(params.name);
+	},
 	async undo() {
 		const svc = requireReady('undo');
 		return svc.undoLast();
 	},
+	async undoEntry(params = {}) {
+		const svc = requireReady('undoEntry');
+		if (typeof params.id !== 'string' || params.id.length === 0) {
+			throw new Error('id is required');
+		}
+		return svc.undoEntry(params.id);
+	},
+	async redo() {
+		const svc = requireReady('redo');
+		return svc.redo();
+	},
+	async startSession(params = {}) {
+		const svc = requireReady('startSession');
+		if (typeof params.name !== 'string' || params.name.trim().length === 0) {
+			throw new Error('session name is required');
+		}
+		await svc.startSession(params.name);
+		return svc.getStatus();
+	},
+	async endSession() {
+		const svc = requireReady('endSession');
+		await svc.endSession();
+		return svc.getStatus();
+	},
+	async listSessions() {
+		const svc = requireReady('listSessions');
+		return svc.listSessions();
+	},
+	async undoSession(params = {}) {
+		const svc = requireReady('undoSession');
+		if (typeof params.id !== 'string' || params.id.length === 0) {
+			throw new Error('session id is required');
+		}
+		return svc.undoSession(params.id);
+	},
 
 	// Profile management
 	async getProfiles() {
@@ -252,6 +730,32 @@ const handlers = {
 		return svc.getProfiles();
 	},
 
+	// Work queue
+	async getQueue() {
+		if (!serviceReady || !service) return { paused: false, active: [], pending: [] };
+		return service.getQueue();
+	},
+	async setQueuePaused(params = {}) {
+		const svc = requireReady('setQueuePaused');
+		return svc.setQueuePaused(Boolean(params.paused));
+	},
+	async reorderQueue(params = {}) {
+		const svc = requireReady('reorderQueue');
+		const { orderedIds } = params;
+		if (!Array.isArray(orderedIds)) {
+			throw new Error('orderedIds is required');
+		}
+		return svc.reorderQueue(orderedIds);
+	},
+	async dropQueueItem(params = {}) {
+		const svc = requireReady('dropQueueItem');
+		const { id } = params;
+		if (typeof id !== 'string') {
+			throw new Error('queue item id is required');
+		}
+		return svc.dropQueueItem(id);
+	},
+
 	async shutdown() {
 		for (const off of emitterUnsubs.splice(0)) {
 			try {
@@ -267,34 +771,167 @@ const handlers = {
 	},
 };
 
-const rl = createInterface({ input: stdin, crlfDelay: Number.POSITIVE_INFINITY });
+// Requests run one at a time, in the order they arrive, whichever client sent them.
+// `cancelRequest` skips the queue so a stuck request can be abandoned: its caller gets an error
+// and the next request starts. Ids are the client's own, so they are kept per client.
+const inflight = new Map();
+let queue = Promise.resolve();
+
+// Reads that change nothing skip the queue too, so the tray and window keep getting status while
+// a long scan, import or history rewrite holds it.
+const READ_ONLY_METHODS = new Set([
+	'getStatus',
+	'listDirectories',
+	'getDirConfig',
+	'getPeerStatus',
+	'getAuditLog',
+	'listImportPresets',
+	'searchHistory',
+	'getHistory',
+	'getDirectoryUsage',
+	'getBenchmarkResults',
+	'proposeName',
+	'listSessions',
+	'getProfiles',
+	'getProfile',
+	'getQueue',
+]);
+
+function requestKey(client, id) {
+	return `${client.id}:${id}`;
+}
 
-for await (const line of rl) {
-	if (dead) break;
+function cancelRequest(client, id) {
+	const controller = inflight.get(requestKey(client, id));
+	if (!controller) return;
+	safeStderr(`[CANCEL] request ${id} from client ${client.id}`);
+	controller.abort(new Error('Request cancelled'));
+}
+
+async function runRequest(client, id, handler, params) {
+	const key = requestKey(client, id);
+	const controller = inflight.get(key);
+	try {
+		controller.signal.throwIfAborted();
+		const work = handler(params, { signal: controller.signal, client });
+		// A cancelled handler may still settle later; don't let that count as unhandled
+		work.catch(() => {});
+		const cancelled = new Promise((_, reject) => {
+			controller.signal.addEventListener('abort', () => reject(controller.signal.reason), {
+				once: true,
+			});
+		});
+		const result = await Promise.race([work, cancelled]);
+		write(client, { id, result });
+	} catch (err) {
+		write(client, { id, error: String(err instanceof Error ? err.message : err) });
+	} finally {
+		inflight.delete(key);
+	}
+}
+
+function handleLine(client, line) {
 	const trimmed = line.trim();
-	if (!trimmed) continue;
+	if (!trimmed) return;
 	let payload;
 	try {
 		payload = JSON.parse(trimmed);
 	} catch (err) {
 		safeStderr(`bad JSON input: ${trimmed}`);
-		sendMessage({ error: 'invalid_json', detail: String(err) });
-		continue;
+		write(client, { error: 'invalid_json', detail: String(err) });
+		return;
 	}
 	const { id, method, params } = payload;
+	// A client answering the name hook
+	if (payload.hookResult !== undefined) {
+		settleHook(payload.hookResult, payload);
+		return;
+	}
+	if (method === 'cancelRequest') {
+		cancelRequest(client, params?.id);
+		return;
+	}
 	if (!method || typeof method !== 'string') {
-		sendMessage({ id, error: 'invalid_method' });
-		continue;
+		write(client, { id, error: 'invalid_method' });
+		return;
 	}
 	const handler = handlers[method];
 	if (!handler) {
-		sendMessage({ id, error: `unknown_method:${method}` });
-		continue;
+		write(client, { id, error: `unknown_method:${method}` });
+		return;
 	}
-	try {
-		const result = await handler(params);
-		sendMessage({ id, result });
-	} catch (err) {
-		sendMessage({ id, error: String(err instanceof Error ? err.message : err) });
+	inflight.set(requestKey(client, id), new AbortController());
+	if (READ_ONLY_METHODS.has(method)) {
+		runRequest(client, id, handler, params);
+		return;
+	}
+	queue = queue.then(() => runRequest(client, id, handler, params));
+}
+
+// Whether a service already answers at `socketPath`, so a second one doesn't take its place.
+function isAnswering(socketPath) {
+	return new Promise((resolve) => {
+		const probe = net.connect(socketPath);
+		probe.once('connect', () => {
+			probe.destroy();
+			resolve(true);
+		});
+		probe.once('error', () => resolve(false));
+	});
+}
+
+async function listen(socketPath) {
+	if (await isAnswering(socketPath)) {
+		die(`another service is already listening at ${socketPath}`);
+		return;
+	}
+	if (platform !== 'win32') {
+		// Left behind by a service that crashed
+		await unlink(socketPath).catch(() => {});
+	}
+	let nextClient = 1;
+	const server = net.createServer((socket) => {
+		const client = { id: nextClient++, stream: socket };
+		clients.add(client);
+		safeStderr(`client ${client.id} connected (${clients.size} connected)`);
+		socket.on('error', () => socket.destroy());
+		socket.on('close', () => {
+			clients.delete(client);
+			dropHookClient(client);
+			for (const [key, controller] of inflight) {
+				if (key.startsWith(`${client.id}:`)) controller.abort(new Error('Client disconnected'));
+			}
+			safeStderr(`client ${client.id} disconnected (${clients.size} connected)`);
+		});
+		// A client that connects to a running service learns its state straight away
+		if (serviceReady) write(client, { event: 'status', payload: service.getStatus() });
+		const lines = createInterface({ input: socket, crlfDelay: Number.POSITIVE_INFINITY });
+		lines.on('line', (line) => handleLine(client, line));
+	});
+	server.on('error', (err) => die(`listen failed: ${err.message}`));
+	server.listen(socketPath, async () => {
+		if (platform !== 'win32') {
+			await chmod(socketPath, 0o600).catch(() => {});
+			process.on('exit', () => {
+				try {
+					unlinkSync(socketPath);
+				} catch {
+					// already gone
+				}
+			});
+		}
+		safeStderr(`listening at ${socketPath}`);
+	});
+}
+
+if (listenPath) {
+	await listen(listenPath);
+} else {
+	const client = { id: 0, stream: stdout };
+	clients.add(client);
+	const rl = createInterface({ input: stdin, crlfDelay: Number.POSITIVE_INFINITY });
+	for await (const line of rl) {
+		if (dead) break;
+		handleLine(client, line);
 	}
 }
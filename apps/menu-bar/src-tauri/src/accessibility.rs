@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::persist::{load_json, save_json};
+
+/// Display preferences for people using assistive technology or needing
+/// stronger contrast than the default tray icon gives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    /// Draw the tray icon as a solid monochrome glyph (a template image on
+    /// macOS, so it follows the menu bar's appearance) instead of the gradient.
+    #[serde(rename = "highContrastIcon", default)]
+    pub high_contrast_icon: bool,
+    /// Turn off transitions and movement in the app's windows.
+    #[serde(rename = "reducedMotion", default)]
+    pub reduced_motion: bool,
+    /// Hold routine toasts for a periodic summary instead of showing each
+    /// one; errors still show at once. See [`crate::notify`].
+    #[serde(rename = "minimalNotifications", default)]
+    pub minimal_notifications: bool,
+}
+
+/// The user's accessibility settings, persisted next to the other menu bar settings.
+pub struct AccessibilityStore {
+    path: PathBuf,
+    settings: Mutex<AccessibilitySettings>,
+}
+
+impl AccessibilityStore {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self { path, settings: Mutex::new(settings) }
+    }
+
+    pub fn get(&self) -> AccessibilitySettings {
+        *self.settings.lock().expect("accessibility lock poisoned")
+    }
+
+    pub fn set(&self, updated: AccessibilitySettings) -> Result<AccessibilitySettings, String> {
+        let mut settings = self.settings.lock().expect("accessibility lock poisoned");
+        save_json(&self.path, &updated)?;
+        *settings = updated;
+        Ok(updated)
+    }
+}
+
+/// What the tray icon shows, spelled out for its tooltip, which screen
+/// readers announce as the icon's description. The attention badge is only a
+/// coloured dot, so its meaning is put into words here.
+pub fn describe(status_label: &str, attention: usize, session: Option<&str>) -> String {
+    let mut parts = vec![format!("Namefix: {}", status_label.trim_start_matches("Status: "))];
+    match attention {
+        0 => {}
+        1 => parts.push("1 problem needs attention".to_string()),
+        n => parts.push(format!("{} problems need attention", n)),
+    }
+    if let Some(session) = session {
+        parts.push(format!("session {} running", session));
+    }
+    parts.join(". ")
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::activity::now_millis;
+use crate::bridge;
+use crate::clipboard_fix;
+use crate::config;
+use crate::entries;
+use crate::pause::{self, PauseDuration};
+use crate::report;
+use crate::state::AppState;
+
+/// Followed by the profile id.
+const TOGGLE_PROFILE_PREFIX: &str = "toggle-profile:";
+
+/// How far back "Scan for Missed Files" looks.
+const SCAN_WINDOW_MS: u64 = 24 * 60 * 60_000;
+
+/// Something the command palette can run, titled for the app's current state
+/// (e.g. "Pause Watching" while running). Tray items for the same action share
+/// its id.
+#[derive(Debug, Clone, Serialize)]
+pub struct Action {
+    pub id: String,
+    pub title: String,
+    /// Heading the palette groups the action under.
+    pub section: &'static str,
+    /// The user's keyboard shortcut for it, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shortcut: Option<String>,
+}
+
+impl Action {
+    fn new(id: impl Into<String>, title: impl Into<String>, section: &'static str) -> Self {
+        Self { id: id.into(), title: title.into(), section, shortcut: None }
+    }
+}
+
+/// Every action available now, in the order the palette lists them.
+pub async fn list(app: &AppHandle<Wry>) -> Result<Vec<Action>, String> {
+    let state = app.state::<AppState>();
+    let status = state.status();
+    let watching = if status.running { "Pause Watching" } else { "Start Watching" };
+    let dry_run = if status.dry_run { "Turn Off Dry Run" } else { "Turn On Dry Run" };
+    let mut actions = vec![
+        Action::new("toggle-running", watching, "Watching"),
+        Action::new("pause-30-minutes", "Pause for 30 Minutes", "Watching"),
+        Action::new("pause-1-hour", "Pause for 1 Hour", "Watching"),
+        Action::new("pause-until-tomorrow", "Pause Until Tomorrow", "Watching"),
+        Action::new("toggle-dry-run", dry_run, "Watching"),
+        Action::new("scan", "Scan for Missed Files", "Watching"),
+        Action::new("undo", "Undo Last Rename", "Renames"),
+        Action::new("rename-folder", "Rename a Folder…", "Renames"),
+        Action::new("fix-clipboard", "Fix Filename on Clipboard", "Renames"),
+        Action::new("export-weekly-report", "Export Weekly Report", "Renames"),
+        Action::new("open-activity", "Show Activity", "Go To"),
+        Action::new("add-directory", "Add Directory…", "Go To"),
+        Action::new("open-profiles", "Show Profiles", "Go To"),
+        Action::new("export-settings", "Export Settings", "Settings"),
+        Action::new("import-settings", "Import Settings…", "Settings"),
+    ];
+    for profile in bridge::get_profiles(&state.bridge).await? {
+        let verb = if profile.enabled { "Disable" } else { "Enable" };
+        let title = format!("{} Profile: {}", verb, profile.name);
+        actions.push(Action::new(format!("{}{}", TOGGLE_PROFILE_PREFIX, profile.id), title, "Profiles"));
+    }
+    for action in &mut actions {
+        action.shortcut = state.shortcuts.get(&action.id);
+    }
+    Ok(actions)
+}
+
+/// Run the action `id` from [`list`]. Actions that only move around the
+/// Preferences window are emitted to it as `ui://navigate` or, for
+/// "Rename a Folder…", `ui://rename-folder`.
+pub async fn invoke(app: &AppHandle<Wry>, id: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let bridge = state.bridge.clone();
+    match id {
+        "toggle-running" => bridge::toggle_running(&bridge, None).await.map(|_| ()),
+        "pause-30-minutes" => pause::pause_for(app, &bridge, PauseDuration::Minutes(30)).await.map(|_| ()),
+        "pause-1-hour" => pause::pause_for(app, &bridge, PauseDuration::Minutes(60)).await.map(|_| ()),
+        "pause-until-tomorrow" => pause::pause_for(app, &bridge, PauseDuration::UntilTomorrow).await.map(|_| ()),
+        "toggle-dry-run" => bridge::set_dry_run(&bridge, !state.status().dry_run).await.map(|_| ()),
+        "scan" => {
+            let since = now_millis().saturating_sub(SCAN_WINDOW_MS);
+            bridge::recover_from_sleep(&bridge, since).await.map(|_| ())
+        }
+        "undo" => {
+            let result = bridge::undo(&bridge).await?;
+            if result.ok {
+                Ok(())
+            } else {
+                Err(result.reason.unwrap_or_else(|| "Nothing to undo".to_string()))
+            }
+        }
+        "rename-folder" => app.emit("ui://rename-folder", ()).map_err(|err| err.to_string()),
+        "fix-clipboard" => clipboard_fix::fix_clipboard(app).await,
+        "export-weekly-report" => {
+            let directory = app.path().app_data_dir().map_err(|err| err.to_string())?.join("reports");
+            let exported = report::export_history(&bridge, &directory, report::DEFAULT_DAYS).await?;
+            entries::reveal(Path::new(&exported.path))
+        }
+        "export-settings" => {
+            let path = app.path().download_dir().map_err(|err| err.to_string())?.join(config::EXPORT_FILE_NAME);
+            config::export(app, &path).await?;
+            entries::reveal(&path)
+        }
+        "import-settings" => navigate(app, "overview", Some("config-file")),
+        "open-activity" => navigate(app, "overview", None),
+        "add-directory" => navigate(app, "directories", Some("new-directory")),
+        "open-profiles" => navigate(app, "profiles", None),
+        _ => match id.strip_prefix(TOGGLE_PROFILE_PREFIX) {
+            Some(profile) => {
+                state.session_profiles.forget(profile);
+                bridge::toggle_profile(&bridge, profile.to_string(), None).await.map(|_| ())
+            }
+            None => Err(format!("Unknown action: {}", id)),
+        },
+    }
+}
+
+fn navigate(app: &AppHandle<Wry>, tab: &str, focus: Option<&str>) -> Result<(), String> {
+    app.emit("ui://navigate", json!({ "tab": tab, "focus": focus })).map_err(|err| err.to_string())
+}
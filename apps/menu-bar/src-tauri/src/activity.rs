@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(not(feature = "native-engine"))]
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::bridge::BridgeEvent;
+#[cfg(not(feature = "native-engine"))]
+use crate::state::AppState;
+
+const MAX_ENTRIES: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ActivityKind {
+    Renamed,
+    Preview,
+    Converted,
+    Trashed,
+    Skipped,
+    Error,
+    Undone,
+    Paused,
+    Resumed,
+    RulesChanged,
+    BridgeDisconnected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub timestamp: u64,
+    pub kind: ActivityKind,
+    pub directory: Option<String>,
+    pub file: Option<String>,
+    pub target: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ActivityEntry {
+    pub fn now(kind: ActivityKind) -> Self {
+        Self {
+            timestamp: now_millis(),
+            kind,
+            directory: None,
+            file: None,
+            target: None,
+            message: None,
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// Time range for `get_activity`, in epoch milliseconds. Missing bounds are open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityWindow {
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Default)]
+struct Tracker {
+    entries: VecDeque<ActivityEntry>,
+    last_running: Option<bool>,
+    last_profiles: Option<String>,
+}
+
+/// Chronological record of what the service did, built from bridge events.
+#[derive(Default)]
+pub struct ActivityLog {
+    inner: Mutex<Tracker>,
+}
+
+impl ActivityLog {
+    #[cfg(not(feature = "native-engine"))]
+    pub fn push(&self, entry: ActivityEntry) {
+        let mut inner = self.inner.lock().expect("activity lock poisoned");
+        push_bounded(&mut inner.entries, entry);
+    }
+
+    /// Translate a bridge event into zero or more activity entries.
+    pub fn observe(&self, event: &BridgeEvent) {
+        let mut inner = self.inner.lock().expect("activity lock poisoned");
+        match event.name.as_str() {
+            "file" => {
+                if let Some(entry) = file_entry(&event.payload) {
+                    push_bounded(&mut inner.entries, entry);
+                }
+            }
+            "undo" => {
+                if let Some(entry) = undo_entry(&event.payload) {
+                    push_bounded(&mut inner.entries, entry);
+                }
+            }
+            "status" => {
+                let Some(running) = event.payload.get("running").and_then(Value::as_bool) else {
+                    return;
+                };
+                let previous = inner.last_running.replace(running);
+                if previous.is_some_and(|was| was != running) {
+                    let kind = if running { ActivityKind::Resumed } else { ActivityKind::Paused };
+                    push_bounded(&mut inner.entries, ActivityEntry::now(kind));
+                }
+            }
+            "config" => {
+                let profiles = event.payload.get("profiles").map(Value::to_string);
+                if profiles.is_none() {
+                    return;
+                }
+                let previous = std::mem::replace(&mut inner.last_profiles, profiles.clone());
+                if previous.is_some() && previous != profiles {
+                    push_bounded(&mut inner.entries, ActivityEntry::now(ActivityKind::RulesChanged));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The newest `limit` entries of the given kinds, newest first.
+    pub fn latest(&self, kinds: &[ActivityKind], limit: usize) -> Vec<ActivityEntry> {
+        let inner = self.inner.lock().expect("activity lock poisoned");
+        inner.entries.iter().rev().filter(|entry| kinds.contains(&entry.kind)).take(limit).cloned().collect()
+    }
+
+    pub fn query(&self, window: &ActivityWindow) -> Vec<ActivityEntry> {
+        let inner = self.inner.lock().expect("activity lock poisoned");
+        let mut matches: Vec<ActivityEntry> = inner
+            .entries
+            .iter()
+            .filter(|entry| window.since.is_none_or(|since| entry.timestamp >= since))
+            .filter(|entry| window.until.is_none_or(|until| entry.timestamp <= until))
+            .cloned()
+            .collect();
+        matches.sort_by_key(|entry| entry.timestamp);
+        if let Some(limit) = window.limit {
+            let excess = matches.len().saturating_sub(limit);
+            matches.drain(..excess);
+        }
+        matches
+    }
+}
+
+fn push_bounded(entries: &mut VecDeque<ActivityEntry>, entry: ActivityEntry) {
+    if entries.len() >= MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+pub fn file_entry(payload: &Value) -> Option<ActivityEntry> {
+    let text = |key: &str| payload.get(key).and_then(Value::as_str).map(str::to_string);
+    let kind = match payload.get("kind").and_then(Value::as_str)? {
+        "applied" => ActivityKind::Renamed,
+        "preview" => ActivityKind::Preview,
+        "converted" => ActivityKind::Converted,
+        "trashed" => ActivityKind::Trashed,
+        "skipped" => ActivityKind::Skipped,
+        "error" | "convert-error" => ActivityKind::Error,
+        _ => return None,
+    };
+    Some(ActivityEntry {
+        timestamp: payload.get("timestamp").and_then(Value::as_u64).unwrap_or_else(now_millis),
+        kind,
+        directory: text("directory"),
+        file: text("file"),
+        target: text("target"),
+        message: text("message"),
+    })
+}
+
+/// An `undo` event from the service, summed up in its message.
+pub fn undo_entry(payload: &Value) -> Option<ActivityEntry> {
+    let count = |key: &str| payload.get(key).and_then(Value::as_u64).unwrap_or(0);
+    let renames = |count: u64| format!("{} rename{}", count, if count == 1 { "" } else { "s" });
+    let (undone, failed) = (count("undone"), count("failed"));
+    let mut message = match payload.get("scope").and_then(Value::as_str)? {
+        "session" => format!("Undid {} of a session", renames(undone)),
+        "entry" => "Undid a rename from history".to_string(),
+        _ => "Undid the last rename".to_string(),
+    };
+    if failed > 0 {
+        message.push_str(&format!("; {} could not be undone", renames(failed)));
+    }
+    Some(ActivityEntry {
+        timestamp: payload.get("timestamp").and_then(Value::as_u64).unwrap_or_else(now_millis),
+        ..ActivityEntry::now(ActivityKind::Undone).with_message(message)
+    })
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Record an entry if the activity log has been registered.
+#[cfg(not(feature = "native-engine"))]
+pub fn record(app: &AppHandle<Wry>, entry: ActivityEntry) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.activity.push(entry);
+    }
+}
@@ -0,0 +1,810 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::activity::now_millis;
+use crate::bridge::{self, Profile, RunOnceResult};
+use crate::pathdisplay::expand_home;
+use crate::persist::{load_json, save_json};
+use crate::preview::{self, PreviewEntry};
+use crate::state::AppState;
+use crate::status_page::{Request, Response};
+
+/// Paths under this belong to the write API rather than the status page.
+pub const PREFIX: &str = "/api/";
+/// Marks a string as a Namefix token, so it is easy to spot in a script.
+const TOKEN_PREFIX: &str = "nfx_";
+const TOKEN_BYTES: usize = 24;
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const MAX_EXPIRY_DAYS: u32 = 365;
+/// Calls kept per token for the settings window; `uses` counts them all.
+const RECENT_USES: usize = 20;
+/// Calls a token may make in a minute before it is answered `429`.
+const RATE_LIMIT: usize = 30;
+const RATE_WINDOW_MS: u64 = 60 * 1000;
+/// Calls over the limit in a row before the token is blocked for a while.
+const BLOCK_AFTER: u32 = 10;
+const BLOCK_MS: u64 = 10 * 60 * 1000;
+/// Requests kept in the access log, which lives only as long as the app.
+const MAX_ACCESS_LOG: usize = 500;
+/// Scans kept for scripts to poll; an older one is reported unknown.
+const MAX_SCAN_JOBS: usize = 20;
+
+/// What a token may change. Each write endpoint needs exactly one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiScope {
+    /// `POST /api/rules`
+    Rules,
+    /// `POST /api/directories`
+    Directories,
+    /// `POST /api/scan`
+    Scan,
+}
+
+impl ApiScope {
+    fn for_path(path: &str) -> Option<Self> {
+        match path.strip_prefix(PREFIX)?.trim_end_matches('/') {
+            "rules" => Some(Self::Rules),
+            "directories" => Some(Self::Directories),
+            "scan" => Some(Self::Scan),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Rules => "rules",
+            Self::Directories => "directories",
+            Self::Scan => "scan",
+        }
+    }
+}
+
+/// A token as kept on disk: only its hash, so the file alone can't be used
+/// to call the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredToken {
+    id: String,
+    name: String,
+    scopes: Vec<ApiScope>,
+    created_at: u64,
+    expires_at: Option<u64>,
+    #[serde(default)]
+    last_used_at: Option<u64>,
+    #[serde(default)]
+    uses: u64,
+    #[serde(default)]
+    recent: Vec<TokenUse>,
+    hash: String,
+}
+
+/// One call made with a token, whether or not it was let through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUse {
+    pub at: u64,
+    pub scope: ApiScope,
+    /// The HTTP status it was answered with, e.g. `201 Created`.
+    pub status: String,
+}
+
+impl StoredToken {
+    fn note(&mut self, scope: ApiScope, status: &str, now: u64) {
+        self.last_used_at = Some(now);
+        self.uses += 1;
+        self.recent.insert(0, TokenUse { at: now, scope, status: status.to_string() });
+        self.recent.truncate(RECENT_USES);
+    }
+
+    fn view(&self, now: u64) -> ApiTokenView {
+        ApiTokenView {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            scopes: self.scopes.clone(),
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+            last_used_at: self.last_used_at,
+            expired: self.expires_at.is_some_and(|at| at <= now),
+            uses: self.uses,
+            recent: self.recent.clone(),
+        }
+    }
+}
+
+/// A token as the settings window lists it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenView {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub last_used_at: Option<u64>,
+    pub expired: bool,
+    pub uses: u64,
+    /// Newest first.
+    pub recent: Vec<TokenUse>,
+}
+
+/// A new token with its secret, which is shown this once.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub view: ApiTokenView,
+}
+
+/// One request under `/api/`, as the access log keeps it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiAccess {
+    pub at: u64,
+    /// The caller's address.
+    pub client: Option<String>,
+    /// The name of the token it sent, when that is one of ours.
+    pub token: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub status: String,
+}
+
+/// Why a request was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Denied {
+    Missing,
+    Unknown,
+    Expired,
+    OutOfScope(ApiScope),
+    /// Over the rate limit; the seconds until it may call again.
+    Throttled(u64),
+    /// Blocked after too many calls over the limit, for this many seconds.
+    Blocked(u64),
+}
+
+impl Denied {
+    fn status(self) -> &'static str {
+        match self {
+            Self::OutOfScope(_) => "403 Forbidden",
+            Self::Throttled(_) | Self::Blocked(_) => "429 Too Many Requests",
+            _ => "401 Unauthorized",
+        }
+    }
+
+    fn response(self) -> Response {
+        let message = match self {
+            Self::Missing => "Send a token as Authorization: Bearer <token>".to_string(),
+            Self::Unknown => "Unknown token".to_string(),
+            Self::Expired => "This token has expired".to_string(),
+            Self::OutOfScope(scope) => format!("This token has no {} scope", scope.label()),
+            Self::Throttled(seconds) => format!("Too many calls; try again in {} seconds", seconds),
+            Self::Blocked(seconds) => {
+                format!("This token is blocked for {} minutes after too many calls", seconds.div_ceil(60))
+            }
+        };
+        let response = Response::json(self.status(), &json!({ "error": message }));
+        match self {
+            Self::OutOfScope(_) => response,
+            Self::Throttled(seconds) | Self::Blocked(seconds) => response.retry_after(seconds),
+            _ => response.challenge(),
+        }
+    }
+}
+
+/// A token's recent calls, for its rate limit.
+#[derive(Debug, Default)]
+struct Rate {
+    /// When the calls let through in the last minute were made, oldest first.
+    calls: VecDeque<u64>,
+    /// Calls turned away since the last one let through.
+    refused: u32,
+    blocked_until: Option<u64>,
+}
+
+impl Rate {
+    /// Let a call at `now` through, unless the token is over its limit or
+    /// blocked. Going on calling over the limit blocks it.
+    fn admit(&mut self, now: u64) -> Result<(), Denied> {
+        if let Some(until) = self.blocked_until {
+            if now < until {
+                return Err(Denied::Blocked(seconds(until - now)));
+            }
+            self.blocked_until = None;
+            self.refused = 0;
+        }
+        while self.calls.front().is_some_and(|at| at + RATE_WINDOW_MS <= now) {
+            self.calls.pop_front();
+        }
+        if self.calls.len() < RATE_LIMIT {
+            self.calls.push_back(now);
+            self.refused = 0;
+            return Ok(());
+        }
+        self.refused += 1;
+        if self.refused >= BLOCK_AFTER {
+            self.blocked_until = Some(now + BLOCK_MS);
+            self.calls.clear();
+            return Err(Denied::Blocked(seconds(BLOCK_MS)));
+        }
+        let oldest = self.calls.front().copied().unwrap_or(now);
+        Err(Denied::Throttled(seconds(oldest + RATE_WINDOW_MS - now)))
+    }
+}
+
+/// Whole seconds in `ms`, rounded up, for a `Retry-After`.
+fn seconds(ms: u64) -> u64 {
+    ms.div_ceil(1000).max(1)
+}
+
+/// Tokens for the write API, each limited to some scopes and optionally
+/// expiring. Managed from the settings window, never over the API itself.
+/// Also keeps each token's rate and a log of every request.
+pub struct ApiTokens {
+    path: PathBuf,
+    tokens: Mutex<Vec<StoredToken>>,
+    rates: Mutex<HashMap<String, Rate>>,
+    /// Newest first.
+    access: Mutex<VecDeque<ApiAccess>>,
+}
+
+impl ApiTokens {
+    pub fn load(path: PathBuf) -> Self {
+        let tokens = load_json(&path);
+        Self { path, tokens: Mutex::new(tokens), rates: Mutex::default(), access: Mutex::default() }
+    }
+
+    pub fn list(&self) -> Vec<ApiTokenView> {
+        let now = now_millis();
+        self.tokens.lock().expect("api tokens lock poisoned").iter().map(|token| token.view(now)).collect()
+    }
+
+    /// Create a token for `scopes` that lapses after `days`, or never.
+    pub fn create(&self, name: &str, scopes: &[ApiScope], days: Option<u32>) -> Result<CreatedApiToken, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Name the token after the script that will use it".to_string());
+        }
+        if scopes.is_empty() {
+            return Err("Choose at least one thing the token may change".to_string());
+        }
+        if days.is_some_and(|days| days == 0 || days > MAX_EXPIRY_DAYS) {
+            return Err(format!("Choose an expiry from 1 to {} days", MAX_EXPIRY_DAYS));
+        }
+        let mut bytes = [0u8; TOKEN_BYTES];
+        getrandom::fill(&mut bytes).map_err(|err| format!("Could not generate a token: {}", err))?;
+        let secret = format!("{}{}", TOKEN_PREFIX, hex(&bytes));
+        let hash = hash(&secret);
+        let now = now_millis();
+        let mut unique = Vec::with_capacity(scopes.len());
+        for scope in scopes {
+            if !unique.contains(scope) {
+                unique.push(*scope);
+            }
+        }
+        let stored = StoredToken {
+            id: hash[..12].to_string(),
+            name: name.to_string(),
+            scopes: unique,
+            created_at: now,
+            expires_at: days.map(|days| now + u64::from(days) * DAY_MS),
+            last_used_at: None,
+            uses: 0,
+            recent: Vec::new(),
+            hash,
+        };
+        let mut tokens = self.tokens.lock().expect("api tokens lock poisoned");
+        let mut updated = tokens.clone();
+        updated.push(stored.clone());
+        save_json(&self.path, &updated)?;
+        *tokens = updated;
+        Ok(CreatedApiToken { token: secret, view: stored.view(now) })
+    }
+
+    pub fn revoke(&self, id: &str) -> Result<Vec<ApiTokenView>, String> {
+        {
+            let mut tokens = self.tokens.lock().expect("api tokens lock poisoned");
+            let updated: Vec<StoredToken> = tokens.iter().filter(|token| token.id != id).cloned().collect();
+            save_json(&self.path, &updated)?;
+            *tokens = updated;
+        }
+        Ok(self.list())
+    }
+
+    /// The id and name of the token `secret` if it may act in `scope` at
+    /// `now` and is within its rate. A refusal is logged against the token;
+    /// an accepted call is logged by `record` once it is answered. Expired
+    /// tokens are kept, so the window can show why a script stopped.
+    fn authorize(&self, secret: Option<&str>, scope: ApiScope, now: u64) -> Result<(String, String), Denied> {
+        let secret = secret.ok_or(Denied::Missing)?;
+        let hash = hash(secret);
+        let mut tokens = self.tokens.lock().expect("api tokens lock poisoned");
+        let token = tokens.iter_mut().find(|token| token.hash == hash).ok_or(Denied::Unknown)?;
+        let denied = if token.expires_at.is_some_and(|at| at <= now) {
+            Denied::Expired
+        } else if !token.scopes.contains(&scope) {
+            Denied::OutOfScope(scope)
+        } else {
+            let mut rates = self.rates.lock().expect("api rates lock poisoned");
+            let rate = rates.entry(token.id.clone()).or_default();
+            let was_blocked = rate.blocked_until.is_some();
+            match rate.admit(now) {
+                Ok(()) => return Ok((token.id.clone(), token.name.clone())),
+                Err(denied) => {
+                    if !was_blocked && rate.blocked_until.is_some() {
+                        log::warn!("API token \"{}\" is blocked for a while after too many calls", token.name);
+                    }
+                    denied
+                }
+            }
+        };
+        token.note(scope, denied.status(), now);
+        // A script over its limit isn't worth a write each; the next saved call keeps the count
+        if !matches!(denied, Denied::Throttled(_) | Denied::Blocked(_)) {
+            self.save_uses(&tokens);
+        }
+        Err(denied)
+    }
+
+    /// The name of the token `secret`, if it is one.
+    fn name_for(&self, secret: &str) -> Option<String> {
+        let hash = hash(secret);
+        let tokens = self.tokens.lock().expect("api tokens lock poisoned");
+        tokens.iter().find(|token| token.hash == hash).map(|token| token.name.clone())
+    }
+
+    /// Every request to the API since the app started, newest first, up to
+    /// the last 500.
+    pub fn access_log(&self) -> Vec<ApiAccess> {
+        self.access.lock().expect("api access lock poisoned").iter().cloned().collect()
+    }
+
+    fn log_access(&self, access: ApiAccess) {
+        let mut log = self.access.lock().expect("api access lock poisoned");
+        log.push_front(access);
+        log.truncate(MAX_ACCESS_LOG);
+    }
+
+    /// Log a call made with token `id` and the status it was answered with.
+    fn record(&self, id: &str, scope: ApiScope, status: &str, now: u64) {
+        let mut tokens = self.tokens.lock().expect("api tokens lock poisoned");
+        if let Some(token) = tokens.iter_mut().find(|token| token.id == id) {
+            token.note(scope, status, now);
+            self.save_uses(&tokens);
+        }
+    }
+
+    fn save_uses(&self, tokens: &[StoredToken]) {
+        if let Err(err) = save_json(&self.path, tokens) {
+            log::debug!("Could not record API token use: {}", err);
+        }
+    }
+}
+
+/// A scan started through `/api/scan`. Scans run in the background, so a
+/// long one doesn't hold up the status page; a script polls with the id.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanJob {
+    pub job: String,
+    pub started_at: u64,
+    /// Unset while the scan is running.
+    pub finished_at: Option<u64>,
+    pub results: Vec<ScanOutcome>,
+    /// Why the scan stopped early; folders scanned before it keep their results.
+    pub error: Option<String>,
+}
+
+/// What a scan did in one folder.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ScanOutcome {
+    Renamed(RunOnceResult),
+    /// The folder is in dry run, or observer mode is on, so nothing was
+    /// renamed; these are the renames it would have made.
+    Planned {
+        directory: String,
+        #[serde(rename = "dryRun")]
+        dry_run: bool,
+        renames: Vec<PreviewEntry>,
+    },
+}
+
+#[derive(Default)]
+struct Scans {
+    next_id: u64,
+    /// Newest first.
+    jobs: VecDeque<ScanJob>,
+}
+
+/// Scans started through the API, the running ones and the last few finished.
+#[derive(Default)]
+pub struct ScanJobs {
+    inner: Mutex<Scans>,
+}
+
+impl ScanJobs {
+    fn start(&self, now: u64) -> String {
+        let mut inner = self.inner.lock().expect("scan jobs lock poisoned");
+        inner.next_id += 1;
+        let job = format!("scan-{}", inner.next_id);
+        let scan = ScanJob { job: job.clone(), started_at: now, finished_at: None, results: Vec::new(), error: None };
+        inner.jobs.push_front(scan);
+        inner.jobs.truncate(MAX_SCAN_JOBS);
+        job
+    }
+
+    fn update(&self, job: &str, apply: impl FnOnce(&mut ScanJob)) {
+        let mut inner = self.inner.lock().expect("scan jobs lock poisoned");
+        if let Some(scan) = inner.jobs.iter_mut().find(|scan| scan.job == job) {
+            apply(scan);
+        }
+    }
+
+    fn get(&self, job: &str) -> Option<ScanJob> {
+        let inner = self.inner.lock().expect("scan jobs lock poisoned");
+        inner.jobs.iter().find(|scan| scan.job == job).cloned()
+    }
+}
+
+fn hash(secret: &str) -> String {
+    hex(&Sha256::digest(secret.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// A failed call: the HTTP status and a message for the script's log.
+type Failure = (&'static str, String);
+
+fn unprocessable(message: impl Into<String>) -> Failure {
+    ("422 Unprocessable Entity", message.into())
+}
+
+fn service_error(message: String) -> Failure {
+    ("502 Bad Gateway", message)
+}
+
+/// Answer a request under `/api/` and add it to the access log.
+pub fn respond(app: &AppHandle<Wry>, request: &Request) -> Response {
+    let tokens = &app.state::<AppState>().api_tokens;
+    let response = answer(app, request);
+    tokens.log_access(ApiAccess {
+        at: now_millis(),
+        client: request.client.map(|ip| ip.to_string()),
+        token: request.token.as_deref().and_then(|secret| tokens.name_for(secret)),
+        method: request.method.clone(),
+        path: request.path.clone(),
+        status: response.status().to_string(),
+    });
+    response
+}
+
+/// Every endpoint takes a JSON body by `POST` and answers with JSON,
+/// `{"error": ...}` when it fails.
+fn answer(app: &AppHandle<Wry>, request: &Request) -> Response {
+    let Some(scope) = ApiScope::for_path(&request.path) else {
+        return Response::json("404 Not Found", &json!({ "error": "No such endpoint" }));
+    };
+    if request.method != "POST" {
+        return Response::json("405 Method Not Allowed", &json!({ "error": "Use POST" }));
+    }
+    let state = app.state::<AppState>();
+    let (id, name) = match state.api_tokens.authorize(request.token.as_deref(), scope, now_millis()) {
+        Ok(token) => token,
+        Err(denied) => return denied.response(),
+    };
+    let body = if request.body.trim().is_empty() { "{}" } else { request.body.as_str() };
+    let result = match serde_json::from_str::<Value>(body) {
+        Ok(body) => async_runtime::block_on(perform(app, scope, body)),
+        Err(err) => Err(("400 Bad Request", format!("The body is not JSON: {}", err))),
+    };
+    let status = match &result {
+        Ok((status, _)) | Err((status, _)) => *status,
+    };
+    state.api_tokens.record(&id, scope, status, now_millis());
+    match result {
+        Ok((status, value)) => {
+            log::info!("API token \"{}\" called {}", name, request.path);
+            Response::json(status, &value)
+        }
+        Err((status, message)) => {
+            log::info!("API token \"{}\" called {}: {}", name, request.path, message);
+            Response::json(status, &json!({ "error": message }))
+        }
+    }
+}
+
+async fn perform(app: &AppHandle<Wry>, scope: ApiScope, body: Value) -> Result<(&'static str, Value), Failure> {
+    let state = app.state::<AppState>();
+    let bridge = &state.bridge;
+    match scope {
+        ApiScope::Rules => {
+            let rule: NewRule = serde_json::from_value(body).map_err(|err| unprocessable(err.to_string()))?;
+            let existing = bridge::get_profiles(bridge).await.map_err(service_error)?;
+            let profile = rule.into_profile(&existing)?;
+            bridge::set_profile(bridge, profile.clone()).await.map_err(service_error)?;
+            Ok(("201 Created", json!(profile)))
+        }
+        ApiScope::Directories => {
+            let request: DirectoryRequest =
+                serde_json::from_value(body).map_err(|err| unprocessable(err.to_string()))?;
+            let path = expand_home(&request.directory);
+            if !path.is_dir() {
+                return Err(unprocessable(format!("{} is not a folder on this Mac", request.directory)));
+            }
+            if let Some(name) = state.exclusions.excluded_component(&path) {
+                return Err(unprocessable(format!("{} is inside an excluded folder ({})", request.directory, name)));
+            }
+            let directory = path.to_string_lossy().into_owned();
+            let status = bridge::get_status(bridge).await.map_err(service_error)?;
+            if status.directories.contains(&directory) {
+                return Ok(("200 OK", json!({ "directories": status.directories })));
+            }
+            let directories = bridge::add_watch_dir(bridge, directory).await.map_err(service_error)?;
+            Ok(("201 Created", json!({ "directories": directories })))
+        }
+        ApiScope::Scan => {
+            let request: ScanRequest = serde_json::from_value(body).map_err(|err| unprocessable(err.to_string()))?;
+            if let Some(job) = request.job {
+                return match state.api_scans.get(&job) {
+                    Some(scan) => Ok(("200 OK", json!(scan))),
+                    None => Err(("404 Not Found", format!("No scan {}", job))),
+                };
+            }
+            let status = bridge::get_status(bridge).await.map_err(service_error)?;
+            let directories = match request.directory {
+                Some(directory) => {
+                    let path = expand_home(&directory).to_string_lossy().into_owned();
+                    if !status.directories.contains(&path) {
+                        return Err(unprocessable(format!("{} is not a watched folder", directory)));
+                    }
+                    vec![path]
+                }
+                None => status.directories.clone(),
+            };
+            // Folders in dry run, or all of them in observer mode, are only planned.
+            let folders: Vec<(String, bool)> = directories
+                .into_iter()
+                .map(|directory| {
+                    let dry_run =
+                        status.observer_mode || status.dry_run || status.dry_run_directories.contains(&directory);
+                    (directory, dry_run)
+                })
+                .collect();
+            let job = state.api_scans.start(now_millis());
+            let (app, id) = (app.clone(), job.clone());
+            async_runtime::spawn(async move { scan(&app, &id, folders, request.profile).await });
+            Ok(("202 Accepted", json!({ "job": job })))
+        }
+    }
+}
+
+/// Run scan `job` over `folders`, recording each folder's outcome as it
+/// finishes so a script polling sees the scan progress.
+async fn scan(app: &AppHandle<Wry>, job: &str, folders: Vec<(String, bool)>, profile: Option<String>) {
+    let state = app.state::<AppState>();
+    let bridge = &state.bridge;
+    for (directory, dry_run) in folders {
+        let outcome = if dry_run {
+            bridge::plan_directory(bridge, directory, profile.clone()).await.map(|plan| ScanOutcome::Planned {
+                renames: preview::renames(&plan),
+                directory: plan.directory,
+                dry_run: true,
+            })
+        } else {
+            bridge::run_once(bridge, directory, profile.clone()).await.map(ScanOutcome::Renamed)
+        };
+        match outcome {
+            Ok(outcome) => state.api_scans.update(job, |scan| scan.results.push(outcome)),
+            Err(err) => {
+                log::warn!("API scan {} stopped: {}", job, err);
+                state.api_scans.update(job, |scan| scan.error = Some(err));
+                break;
+            }
+        }
+    }
+    state.api_scans.update(job, |scan| scan.finished_at = Some(now_millis()));
+}
+
+/// The body of `POST /api/rules`: a rule in the same shape as the service's
+/// profiles, with everything but the name, pattern and template optional.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct NewRule {
+    id: Option<String>,
+    name: String,
+    pattern: String,
+    #[serde(default)]
+    is_regex: bool,
+    template: String,
+    #[serde(default)]
+    prefix: String,
+    #[serde(default = "default_priority")]
+    priority: i32,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    action: Option<String>,
+    fiscal_year_start_month: Option<u8>,
+    time_zone: Option<String>,
+    case_style: Option<String>,
+    date_prefix: Option<bool>,
+    condition: Option<String>,
+}
+
+fn default_priority() -> i32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl NewRule {
+    /// The profile to save. Without an id one is made from the name, so
+    /// running the same script twice reports a conflict instead of adding
+    /// a duplicate.
+    fn into_profile(self, existing: &[Profile]) -> Result<Profile, Failure> {
+        let name = self.name.trim();
+        let profile = Profile {
+            id: self.id.unwrap_or_else(|| bridge::slug(name)),
+            name: name.to_string(),
+            enabled: self.enabled,
+            pattern: self.pattern.trim().to_string(),
+            is_regex: self.is_regex.then_some(true),
+            template: self.template.trim().to_string(),
+            prefix: self.prefix,
+            priority: self.priority,
+            action: self.action.filter(|action| action != "rename"),
+            fiscal_year_start_month: self.fiscal_year_start_month.filter(|month| *month > 1),
+            time_zone: self.time_zone.filter(|zone| !zone.is_empty()),
+            case_style: self.case_style.filter(|style| !style.is_empty()),
+            date_prefix: self.date_prefix.filter(|prefix| *prefix),
+            condition: self.condition.filter(|condition| !condition.trim().is_empty()),
+        };
+        bridge::check_rule(&profile).map_err(unprocessable)?;
+        if profile.id.is_empty() {
+            return Err(unprocessable("Give the rule an id"));
+        }
+        if existing.iter().any(|existing| existing.id == profile.id) {
+            return Err(("409 Conflict", format!("A rule with id \"{}\" already exists", profile.id)));
+        }
+        Ok(profile)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DirectoryRequest {
+    directory: String,
+}
+
+/// The body of `POST /api/scan`: without a directory every watched folder
+/// is scanned, and without a profile every enabled rule applies. With a
+/// `job` it reports on that scan instead of starting one.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ScanRequest {
+    directory: Option<String>,
+    profile: Option<String>,
+    job: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> ApiTokens {
+        let path = std::env::temp_dir().join(format!("namefix-api-tokens-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        ApiTokens::load(path)
+    }
+
+    #[test]
+    fn tokens_are_checked_for_scope_and_expiry() {
+        let tokens = store();
+        let created = tokens.create(" provisioning ", &[ApiScope::Rules], Some(30)).unwrap();
+        assert!(created.token.starts_with(TOKEN_PREFIX));
+        assert_eq!(created.view.name, "provisioning");
+        let now = now_millis();
+        let secret = Some(created.token.as_str());
+
+        let id = created.view.id.clone();
+        assert_eq!(tokens.authorize(secret, ApiScope::Rules, now), Ok((id.clone(), "provisioning".to_string())));
+        assert_eq!(tokens.authorize(secret, ApiScope::Scan, now), Err(Denied::OutOfScope(ApiScope::Scan)));
+        assert_eq!(tokens.authorize(secret, ApiScope::Rules, now + 31 * DAY_MS), Err(Denied::Expired));
+        assert_eq!(tokens.authorize(Some("nfx_guess"), ApiScope::Rules, now), Err(Denied::Unknown));
+        assert_eq!(tokens.authorize(None, ApiScope::Rules, now), Err(Denied::Missing));
+        tokens.record(&id, ApiScope::Rules, "201 Created", now);
+        let view = &tokens.list()[0];
+        assert_eq!(view.uses, 3);
+        assert_eq!(view.last_used_at, Some(now));
+        let statuses: Vec<&str> = view.recent.iter().map(|used| used.status.as_str()).collect();
+        assert_eq!(statuses, ["201 Created", "401 Unauthorized", "403 Forbidden"]);
+
+        tokens.revoke(&created.view.id).unwrap();
+        assert_eq!(tokens.authorize(secret, ApiScope::Rules, now), Err(Denied::Unknown));
+        assert!(tokens.create("script", &[], None).is_err());
+        assert!(tokens.create("script", &[ApiScope::Scan], Some(0)).is_err());
+        let _ = std::fs::remove_file(&tokens.path);
+    }
+
+    #[test]
+    fn tokens_over_their_rate_are_throttled_then_blocked() {
+        let mut rate = Rate::default();
+        let start = 1_000_000;
+        for call in 0..RATE_LIMIT as u64 {
+            assert_eq!(rate.admit(start + call * 100), Ok(()));
+        }
+        assert_eq!(rate.admit(start + 30_000), Err(Denied::Throttled(30)));
+        assert_eq!(rate.admit(start + RATE_WINDOW_MS), Ok(()));
+
+        let mut rate = Rate::default();
+        for _ in 0..RATE_LIMIT {
+            assert_eq!(rate.admit(start), Ok(()));
+        }
+        for _ in 1..BLOCK_AFTER {
+            assert_eq!(rate.admit(start), Err(Denied::Throttled(60)));
+        }
+        assert_eq!(rate.admit(start), Err(Denied::Blocked(BLOCK_MS / 1000)));
+        assert_eq!(rate.admit(start + RATE_WINDOW_MS), Err(Denied::Blocked((BLOCK_MS - RATE_WINDOW_MS) / 1000)));
+        assert_eq!(rate.admit(start + BLOCK_MS), Ok(()));
+        assert_eq!(Denied::Blocked(90).status(), "429 Too Many Requests");
+    }
+
+    #[test]
+    fn new_rules_get_an_id_from_their_name() {
+        let rule: NewRule =
+            serde_json::from_value(json!({ "name": "Camera Imports", "pattern": "IMG_*", "template": "<date>" }))
+                .unwrap();
+        let profile = rule.into_profile(&[]).unwrap();
+        assert_eq!(profile.id, "camera-imports");
+        assert!(profile.enabled);
+        assert_eq!(profile.is_regex, None);
+
+        let again: NewRule =
+            serde_json::from_value(json!({ "name": "Camera imports", "pattern": "IMG_*", "template": "<date>" }))
+                .unwrap();
+        assert_eq!(again.into_profile(&[profile]).unwrap_err().0, "409 Conflict");
+        let shouting: NewRule = serde_json::from_value(
+            json!({ "name": "Loud", "pattern": "*", "template": "<original>", "caseStyle": "shouty" }),
+        )
+        .unwrap();
+        assert_eq!(shouting.into_profile(&[]).unwrap_err().1, "Unknown case style \"shouty\"");
+        assert!(serde_json::from_value::<NewRule>(json!({ "name": "x", "pattern": "*", "template": "t", "oops": 1 }))
+            .is_err());
+        assert_eq!(ApiScope::for_path("/api/scan/"), Some(ApiScope::Scan));
+        assert_eq!(ApiScope::for_path("/api/status"), None);
+    }
+
+    #[test]
+    fn scans_are_kept_for_polling() {
+        let scans = ScanJobs::default();
+        let first = scans.start(1);
+        let job = scans.start(2);
+        assert_ne!(first, job);
+        scans.update(&job, |scan| {
+            scan.results.push(ScanOutcome::Planned {
+                directory: "/tmp/shots".to_string(),
+                dry_run: true,
+                renames: Vec::new(),
+            })
+        });
+        let scan = scans.get(&job).unwrap();
+        assert_eq!(scan.finished_at, None);
+        assert_eq!(json!(scan.results[0]), json!({ "directory": "/tmp/shots", "dryRun": true, "renames": [] }));
+        for started in 3..=MAX_SCAN_JOBS as u64 + 1 {
+            scans.start(started);
+        }
+        assert!(scans.get(&first).is_none());
+        assert!(scans.get(&job).is_some());
+        let request: ScanRequest = serde_json::from_value(json!({ "job": job })).unwrap();
+        assert_eq!(request.job.as_deref(), Some(job.as_str()));
+    }
+}
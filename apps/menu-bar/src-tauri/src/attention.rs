@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::activity::now_millis;
+use crate::bridge::BridgeEvent;
+
+const MAX_ITEMS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttentionKind {
+    Permission,
+    Conflict,
+    Error,
+}
+
+/// An unresolved problem surfaced through the tray badge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttentionItem {
+    pub id: u64,
+    pub kind: AttentionKind,
+    pub directory: Option<String>,
+    pub file: Option<String>,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+#[derive(Default)]
+struct Items {
+    next_id: u64,
+    items: Vec<AttentionItem>,
+}
+
+/// Tracks errors that need the user's attention. Items clear when the same
+/// file is later processed successfully or when the user dismisses them.
+#[derive(Default)]
+pub struct AttentionTracker {
+    inner: Mutex<Items>,
+}
+
+impl AttentionTracker {
+    /// Update from a bridge event; returns true if the set of items changed.
+    pub fn observe(&self, event: &BridgeEvent) -> bool {
+        let text = |key: &str| event.payload.get(key).and_then(Value::as_str).map(str::to_string);
+        let mut inner = self.inner.lock().expect("attention lock poisoned");
+        match (event.name.as_str(), text("kind").as_deref()) {
+            ("file", Some("applied" | "converted")) => {
+                let file = text("file");
+                let before = inner.items.len();
+                inner.items.retain(|item| item.file.is_none() || item.file != file);
+                inner.items.len() != before
+            }
+            ("file", Some("error" | "convert-error")) => {
+                let message = text("message").unwrap_or_else(|| "unknown error".to_string());
+                let kind = classify(&message);
+                push(&mut inner, kind, text("directory"), text("file"), message);
+                true
+            }
+            ("toast", _) if text("level").as_deref() == Some("error") => {
+                let message = text("message").unwrap_or_default();
+                let kind = classify(&message);
+                push(&mut inner, kind, None, None, message);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn items(&self) -> Vec<AttentionItem> {
+        self.inner.lock().expect("attention lock poisoned").items.clone()
+    }
+
+    pub fn resolve(&self, id: u64) -> Vec<AttentionItem> {
+        let mut inner = self.inner.lock().expect("attention lock poisoned");
+        inner.items.retain(|item| item.id != id);
+        inner.items.clone()
+    }
+
+    pub fn clear(&self) {
+        self.inner.lock().expect("attention lock poisoned").items.clear();
+    }
+}
+
+fn push(inner: &mut Items, kind: AttentionKind, directory: Option<String>, file: Option<String>, message: String) {
+    // Repeated failures for the same file collapse into one item.
+    inner.items.retain(|item| !(item.file.is_some() && item.file == file && item.kind == kind));
+    if inner.items.len() >= MAX_ITEMS {
+        inner.items.remove(0);
+    }
+    inner.next_id += 1;
+    let id = inner.next_id;
+    inner.items.push(AttentionItem { id, kind, directory, file, message, timestamp: now_millis() });
+}
+
+fn classify(message: &str) -> AttentionKind {
+    let lower = message.to_lowercase();
+    if lower.contains("eacces") || lower.contains("eperm") || lower.contains("permission") || lower.contains("not permitted") {
+        AttentionKind::Permission
+    } else if lower.contains("eexist") || lower.contains("already exists") || lower.contains("conflict") {
+        AttentionKind::Conflict
+    } else {
+        AttentionKind::Error
+    }
+}
@@ -1,16 +1,38 @@
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::Stdio;
-use std::sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc};
-use tauri::async_runtime::{self, Mutex};
-use tauri::path::BaseDirectory;
+use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+use std::time::Duration;
+use tauri::async_runtime;
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::{broadcast, oneshot};
+use tokio::sync::{broadcast, watch};
+// The Node sidecar's plumbing, left out when the native engine is built in.
+#[cfg(not(feature = "native-engine"))]
+use {
+    crate::activity::{self, ActivityEntry, ActivityKind},
+    crate::engine::EngineFuture,
+    crate::namehook,
+    std::collections::HashMap,
+    std::process::Stdio,
+    std::sync::{atomic::AtomicBool, OnceLock},
+    std::time::Instant,
+    tauri::async_runtime::Mutex,
+    tauri::path::BaseDirectory,
+    tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, ReadHalf, WriteHalf},
+    tokio::process::{Child, Command},
+    tokio::sync::oneshot,
+};
+
+use crate::engine::Engine;
+use crate::logging;
+use crate::notify;
+use crate::persist;
+use crate::push;
+use crate::state::AppState;
+pub use crate::status::{DirectoryConfig, DirectoryStats, HistoryHealth, PairingCode, RenameSession, ServiceStatus};
+use crate::tray;
+use crate::volume;
 
 #[derive(Debug, Clone)]
 pub struct BridgeEvent {
@@ -18,54 +40,197 @@ pub struct BridgeEvent {
     pub payload: Value,
 }
 
+/// The service's end of the connection: a Unix domain socket, or a named
+/// pipe on Windows.
+#[cfg(unix)]
+#[cfg(not(feature = "native-engine"))]
+type ServiceStream = tokio::net::UnixStream;
+#[cfg(windows)]
+#[cfg(not(feature = "native-engine"))]
+type ServiceStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+#[cfg(unix)]
+#[cfg(not(feature = "native-engine"))]
+async fn connect_service(endpoint: &std::path::Path) -> std::io::Result<ServiceStream> {
+    tokio::net::UnixStream::connect(endpoint).await
+}
+
+#[cfg(windows)]
+#[cfg(not(feature = "native-engine"))]
+async fn connect_service(endpoint: &std::path::Path) -> std::io::Result<ServiceStream> {
+    tokio::net::windows::named_pipe::ClientOptions::new().open(endpoint)
+}
+
+/// Where the service listens. One per user, so a menu bar app started again,
+/// or the command line, finds the service an earlier one left running.
+/// `NAMEFIX_SERVICE_SOCKET` overrides it.
+pub fn service_socket_path() -> PathBuf {
+    if let Ok(path) = std::env::var("NAMEFIX_SERVICE_SOCKET") {
+        return PathBuf::from(path);
+    }
+    if cfg!(windows) {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        return PathBuf::from(format!(r"\\.\pipe\namefix-service-{}", user));
+    }
+    persist::config_dir().join("service.sock")
+}
+
+/// How long a freshly spawned service has to start listening.
+#[cfg(not(feature = "native-engine"))]
+const LISTEN_TIMEOUT: Duration = Duration::from_secs(15);
+#[cfg(not(feature = "native-engine"))]
+const LISTEN_POLL: Duration = Duration::from_millis(100);
+
+#[cfg(not(feature = "native-engine"))]
 struct Inner {
-    child: Mutex<Child>,
-    stdin: Mutex<ChildStdin>,
+    /// The service process, when this bridge started it rather than
+    /// connecting to one that was already running.
+    child: Mutex<Option<Child>>,
+    writer: Mutex<WriteHalf<ServiceStream>>,
     pending: Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>,
     counter: AtomicU64,
     dead: AtomicBool,
+    /// Set by [`NodeBridge::shutdown`], so the exit that follows isn't taken for a crash.
+    stopping: AtomicBool,
+    /// Whether the service has sent its first status, i.e. finished starting.
+    ready: watch::Sender<bool>,
+    /// Whether the reader has stopped, i.e. the connection to the service has closed.
+    exited: watch::Sender<bool>,
     events: broadcast::Sender<BridgeEvent>,
 }
 
+#[cfg(not(feature = "native-engine"))]
+impl Inner {
+    async fn write(&self, payload: &Value) -> anyhow::Result<()> {
+        let mut writer = self.writer.lock().await;
+        let serialized = serde_json::to_vec(payload)?;
+        writer.write_all(&serialized).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+/// A request still waiting for its response. Dropped before the response
+/// arrives (it timed out, or the caller gave up on it), it leaves the pending
+/// map and tells the sidecar to cancel the request, so a stuck call doesn't
+/// hold up the ones queued behind it.
+#[cfg(not(feature = "native-engine"))]
+struct PendingRequest {
+    inner: Arc<Inner>,
+    id: u64,
+    done: bool,
+}
+
+#[cfg(not(feature = "native-engine"))]
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        let (inner, id) = (self.inner.clone(), self.id);
+        async_runtime::spawn(async move {
+            inner.pending.lock().await.remove(&id);
+            if inner.dead.load(Ordering::SeqCst) {
+                return;
+            }
+            log::debug!("Cancelling bridge request {}", id);
+            let message = json!({ "method": "cancelRequest", "params": { "id": id } });
+            if let Err(err) = inner.write(&message).await {
+                log::warn!("Failed to cancel bridge request {}: {}", id, err);
+            }
+        });
+    }
+}
+
+/// How long a request waits for its response, unless its method is in
+/// [`METHOD_TIMEOUTS`]. `NAMEFIX_BRIDGE_TIMEOUT_MS` overrides it.
+#[cfg(not(feature = "native-engine"))]
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Methods that walk folders, copy files or rewrite history, with the time
+/// each is given instead of the default. The service runs requests one at a
+/// time, but reads such as `getStatus` skip its queue, so the short default
+/// still holds for them while one of these runs.
+#[cfg(not(feature = "native-engine"))]
+const METHOD_TIMEOUTS: [(&str, Duration); 12] = [
+    ("planDirectory", Duration::from_secs(60)),
+    ("runOnce", Duration::from_secs(5 * 60)),
+    ("applyPlan", Duration::from_secs(5 * 60)),
+    ("runImportPreset", Duration::from_secs(30 * 60)),
+    ("runBenchmark", Duration::from_secs(5 * 60)),
+    ("undoSession", Duration::from_secs(5 * 60)),
+    ("recoverFromSleep", Duration::from_secs(2 * 60)),
+    ("setDataDir", Duration::from_secs(10 * 60)),
+    ("exportHistory", Duration::from_secs(2 * 60)),
+    ("mergeHistory", Duration::from_secs(2 * 60)),
+    ("repairHistory", Duration::from_secs(2 * 60)),
+    ("pruneHistory", Duration::from_secs(60)),
+];
+
+#[cfg(not(feature = "native-engine"))]
+fn timeout_for(method: &str) -> Duration {
+    static DEFAULT: OnceLock<Duration> = OnceLock::new();
+    let default = *DEFAULT.get_or_init(|| {
+        std::env::var("NAMEFIX_BRIDGE_TIMEOUT_MS")
+            .ok()
+            .and_then(|ms| ms.trim().parse().ok())
+            .map_or(DEFAULT_TIMEOUT, Duration::from_millis)
+    });
+    METHOD_TIMEOUTS.iter().find(|(name, _)| *name == method).map_or(default, |(_, timeout)| *timeout)
+}
+
+/// JSON-RPC connection to the Node service, which runs on its own and
+/// listens at [`service_socket_path`]. Several clients can be connected at
+/// once, and the service outlives the app if it crashes or is killed.
 #[derive(Clone)]
+#[cfg(not(feature = "native-engine"))]
 pub struct NodeBridge(Arc<Inner>);
 
+#[cfg(not(feature = "native-engine"))]
 impl NodeBridge {
+    /// Connect to the service, starting it first if none is listening.
     pub async fn new(app_handle: &AppHandle) -> anyhow::Result<Self> {
-        let script_path = resolve_bridge_script(app_handle)?;
-        let mut command = Command::new(node_command()?);
-        command
-            .arg(&script_path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit());
-
-        let mut child = command.spawn()?;
-        let stdin = child.stdin.take().ok_or_else(|| anyhow::anyhow!("bridge stdin unavailable"))?;
-        let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("bridge stdout unavailable"))?;
+        let endpoint = service_socket_path();
+        let (stream, child) = match connect_service(&endpoint).await {
+            Ok(stream) => {
+                log::info!("Connected to the background service already running at {}", endpoint.display());
+                (stream, None)
+            }
+            Err(_) => {
+                let script_path = resolve_bridge_script(app_handle)?;
+                let mut child = spawn_service(&script_path, &endpoint)?;
+                let stream = wait_for_service(&endpoint, &mut child).await?;
+                (stream, Some(child))
+            }
+        };
+        let (reader, writer) = tokio::io::split(stream);
 
         let (events_tx, _events_rx) = broadcast::channel(32);
         let inner = Arc::new(Inner {
             child: Mutex::new(child),
-            stdin: Mutex::new(stdin),
+            writer: Mutex::new(writer),
             pending: Mutex::new(HashMap::new()),
             counter: AtomicU64::new(1),
             dead: AtomicBool::new(false),
+            stopping: AtomicBool::new(false),
+            ready: watch::channel(false).0,
+            exited: watch::channel(false).0,
             events: events_tx.clone(),
         });
 
-        Self::spawn_reader(inner.clone(), stdout, events_tx.clone(), app_handle.clone());
+        Self::spawn_reader(inner.clone(), reader, events_tx.clone(), app_handle.clone());
         Ok(Self(inner))
     }
 
     fn spawn_reader(
         inner: Arc<Inner>,
-        stdout: tokio::process::ChildStdout,
+        reader: ReadHalf<ServiceStream>,
         events_tx: broadcast::Sender<BridgeEvent>,
         app_handle: AppHandle,
     ) {
         async_runtime::spawn(async move {
-            let mut lines = BufReader::new(stdout).lines();
+            let mut lines = BufReader::new(reader).lines();
             while let Ok(Some(line)) = lines.next_line().await {
                 if line.trim().is_empty() {
                     continue;
@@ -75,16 +240,7 @@ impl NodeBridge {
                         if let Some(event) = message.get("event").and_then(|v| v.as_str()) {
                             let payload = message.get("payload").cloned().unwrap_or(Value::Null);
                             match event {
-                                "file" => {
-                                    let kind = payload.get("kind").and_then(|v| v.as_str()).unwrap_or("?");
-                                    let file = payload.get("file").and_then(|v| v.as_str()).unwrap_or("?");
-                                    let target = payload.get("target").and_then(|v| v.as_str());
-                                    if let Some(t) = target {
-                                        log::info!("File event: {} {} → {}", kind, file, t);
-                                    } else {
-                                        log::info!("File event: {} {}", kind, file);
-                                    }
-                                }
+                                "file" => {} // Logged with the other events, in forward_events.
                                 "toast" => {
                                     let level = payload.get("level").and_then(|v| v.as_str()).unwrap_or("info");
                                     let msg = payload.get("message").and_then(|v| v.as_str()).unwrap_or("?");
@@ -94,6 +250,7 @@ impl NodeBridge {
                                     let running = payload.get("running").and_then(|v| v.as_bool()).unwrap_or(false);
                                     let dirs = payload.get("directories").and_then(|v| v.as_array()).map(|a| a.len()).unwrap_or(0);
                                     log::info!("Status: running={}, dirs={}", running, dirs);
+                                    inner.ready.send_replace(true);
                                 }
                                 _ => {
                                     log::debug!("Bridge event: {}", event);
@@ -103,6 +260,8 @@ impl NodeBridge {
                                 name: event.to_string(),
                                 payload,
                             });
+                        } else if let Some(hook) = message.get("hook").and_then(|v| v.as_u64()) {
+                            Self::answer_hook(inner.clone(), app_handle.clone(), hook, message);
                         } else if let Some(id) = message.get("id").and_then(|v| v.as_u64()) {
                             let result = if let Some(error) = message.get("error") {
                                 Err(error.as_str().unwrap_or("unknown bridge error").to_string())
@@ -130,9 +289,12 @@ impl NodeBridge {
                 }
             }
 
-            // Reader loop exited - sidecar crashed or EOF
-            log::error!("Bridge sidecar stdout reader exited unexpectedly");
+            // Reader loop exited - the service crashed, the connection dropped, or we shut it down
             inner.dead.store(true, Ordering::SeqCst);
+            let stopping = inner.stopping.load(Ordering::SeqCst);
+            if !stopping {
+                log::error!("Connection to the background service closed unexpectedly");
+            }
 
             // Notify all pending requests
             {
@@ -143,28 +305,51 @@ impl NodeBridge {
                     let _ = tx.send(Err("Bridge sidecar disconnected".to_string()));
                 }
             }
+            inner.exited.send_replace(true);
+            if stopping {
+                return;
+            }
 
-            // Emit error toast to user
-            let _ = app_handle.emit(
-                "service://toast",
-                serde_json::json!({
-                    "message": "Background service disconnected. Please restart the app.",
-                    "level": "error"
-                }),
+            activity::record(
+                &app_handle,
+                ActivityEntry::now(ActivityKind::BridgeDisconnected).with_message("Bridge sidecar exited"),
             );
+            tray::refresh_attention(&app_handle);
+
+            // The supervisor restarts it; let the user know why things pause for a moment
+            notify::toast(&app_handle, "Background service stopped unexpectedly. Restarting it…", "warn");
         });
     }
 
-    async fn write_request(&self, payload: &Value) -> anyhow::Result<()> {
-        let mut stdin = self.0.stdin.lock().await;
-        let serialized = serde_json::to_vec(payload)?;
-        stdin.write_all(&serialized).await?;
-        stdin.write_all(b"\n").await?;
-        stdin.flush().await?;
-        Ok(())
+    /// Answer the service asking about a name (see [`namehook`]) without
+    /// holding up the reader, which the service's other replies come through.
+    fn answer_hook(inner: Arc<Inner>, app_handle: AppHandle, hook: u64, message: Value) {
+        async_runtime::spawn(async move {
+            let reply = match serde_json::from_value::<namehook::HookRequest>(message) {
+                Ok(request) => json!({ "hookResult": hook, "result": namehook::answer(&app_handle, request).await }),
+                Err(err) => json!({ "hookResult": hook, "error": err.to_string() }),
+            };
+            if let Err(err) = inner.write(&reply).await {
+                log::warn!("Failed to answer name hook {}: {}", hook, err);
+            }
+        });
     }
 
+    /// Send `method` and wait for the response for as long as [`timeout_for`]
+    /// allows it.
     pub async fn invoke<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        self.invoke_with_timeout(method, params, timeout_for(method)).await
+    }
+
+    /// [`invoke`](Self::invoke) with an explicit timeout. If the response
+    /// hasn't come by then, or the returned future is dropped first, the
+    /// sidecar is asked to cancel the request.
+    pub async fn invoke_with_timeout<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<T, String> {
         if self.0.dead.load(Ordering::SeqCst) {
             return Err("Background service disconnected. Please restart the app.".to_string());
         }
@@ -175,23 +360,28 @@ impl NodeBridge {
             let mut pending = self.0.pending.lock().await;
             pending.insert(id, tx);
         }
+        let mut request = PendingRequest { inner: self.0.clone(), id, done: false };
         let payload = json!({
             "id": id,
             "method": method,
             "params": params,
         });
-        if let Err(err) = self.write_request(&payload).await {
+        if let Err(err) = self.0.write(&payload).await {
             log::error!("Bridge write_request failed: {}", err);
             self.0.dead.store(true, Ordering::SeqCst);
             let mut pending = self.0.pending.lock().await;
             if let Some(tx) = pending.remove(&id) {
                 let _ = tx.send(Err("Background service disconnected. Please restart the app.".to_string()));
             }
+            request.done = true;
             return Err("Background service disconnected. Please restart the app.".to_string());
         }
         log::debug!("Bridge request sent, waiting for response...");
 
-        match tokio::time::timeout(std::time::Duration::from_secs(10), rx).await {
+        let response = tokio::time::timeout(timeout, rx).await;
+        // Whatever happened, the entry has left the pending map, except on timeout.
+        request.done = response.is_ok();
+        match response {
             Ok(Ok(Ok(value))) => {
                 log::debug!("Bridge response received: {:?}", value);
                 serde_json::from_value::<T>(value).map_err(|err| err.to_string())
@@ -205,9 +395,7 @@ impl NodeBridge {
                 Err("bridge channel closed".to_string())
             }
             Err(_) => {
-                log::error!("Bridge request timed out: method={}", method);
-                let mut pending = self.0.pending.lock().await;
-                pending.remove(&id);
+                log::error!("Bridge request timed out after {:?}: method={}", timeout, method);
                 Err("Bridge request timed out".to_string())
             }
         }
@@ -217,21 +405,130 @@ impl NodeBridge {
         self.0.events.subscribe()
     }
 
-    /// Gracefully shut down the Node sidecar. Sends "shutdown" command and waits
-    /// briefly for the child process to exit before forcibly killing it.
+    fn is_ready(&self) -> bool {
+        *self.0.ready.borrow()
+    }
+
+    /// Wait for the service to finish starting, for at most `timeout`.
+    async fn wait_ready(&self, timeout: Duration) -> bool {
+        let mut rx = self.0.ready.subscribe();
+        let ready = matches!(tokio::time::timeout(timeout, rx.wait_for(|ready| *ready)).await, Ok(Ok(_)));
+        ready
+    }
+
+    /// Wait for the sidecar to exit, for whatever reason.
+    async fn exited(&self) {
+        let mut rx = self.0.exited.subscribe();
+        let _ = rx.wait_for(|exited| *exited).await;
+    }
+
+    /// Gracefully shut down the Node service, whoever started it. Sends the
+    /// "shutdown" command and, if this bridge spawned it, waits briefly for
+    /// the process to exit before forcibly killing it.
     pub async fn shutdown(&self) {
+        self.0.stopping.store(true, Ordering::SeqCst);
         // Try graceful shutdown via the protocol
-        let _ = self.invoke::<Value>("shutdown", Value::Null).await;
+        let _ = self.invoke_with_timeout::<Value>("shutdown", Value::Null, Duration::from_secs(2)).await;
 
-        // Give the sidecar a moment to flush and exit
-        std::thread::sleep(std::time::Duration::from_millis(200));
+        // Give the service a moment to flush and exit
+        tokio::time::sleep(Duration::from_millis(200)).await;
 
         // Force-kill if still alive
-        let mut child = self.0.child.lock().await;
-        let _ = child.kill().await;
+        if let Some(child) = self.0.child.lock().await.as_mut() {
+            let _ = child.kill().await;
+        }
+    }
+
+    /// Let go of the service as the app quits: shut it down if this bridge
+    /// started it, and otherwise only hang up, leaving the service to the app
+    /// instance or command that started it.
+    pub async fn close(&self) {
+        if self.0.child.lock().await.is_some() {
+            self.shutdown().await;
+            return;
+        }
+        self.0.stopping.store(true, Ordering::SeqCst);
+        let _ = self.0.writer.lock().await.shutdown().await;
     }
 }
 
+#[cfg(not(feature = "native-engine"))]
+impl Engine for NodeBridge {
+    fn call<'a>(&'a self, method: &'a str, params: Value) -> EngineFuture<'a, Result<Value, String>> {
+        Box::pin(self.invoke(method, params))
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.0.dead.load(Ordering::SeqCst)
+    }
+
+    fn shutdown(&self) -> EngineFuture<'_, ()> {
+        Box::pin(NodeBridge::shutdown(self))
+    }
+
+    fn close(&self) -> EngineFuture<'_, ()> {
+        Box::pin(NodeBridge::close(self))
+    }
+}
+
+/// Start the service listening at `endpoint`, in its own process group with
+/// no pipes to this process, so it keeps watching if the app dies.
+#[cfg(not(feature = "native-engine"))]
+fn spawn_service(script_path: &std::path::Path, endpoint: &std::path::Path) -> anyhow::Result<Child> {
+    if let Some(parent) = endpoint.parent().filter(|_| cfg!(unix)) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut command = Command::new(node_command()?);
+    command
+        .arg(script_path)
+        .arg("--listen")
+        .arg(endpoint)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit());
+    #[cfg(unix)]
+    command.process_group(0);
+    Ok(command.spawn()?)
+}
+
+/// Connect to a service just spawned as `child` once it listens, or fail
+/// when it exits first or takes longer than [`LISTEN_TIMEOUT`].
+#[cfg(not(feature = "native-engine"))]
+async fn wait_for_service(endpoint: &std::path::Path, child: &mut Child) -> anyhow::Result<ServiceStream> {
+    let started = Instant::now();
+    loop {
+        if let Ok(stream) = connect_service(endpoint).await {
+            return Ok(stream);
+        }
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!("background service exited while starting ({})", status);
+        }
+        if started.elapsed() >= LISTEN_TIMEOUT {
+            let _ = child.kill().await;
+            anyhow::bail!("background service did not start listening at {}", endpoint.display());
+        }
+        tokio::time::sleep(LISTEN_POLL).await;
+    }
+}
+
+/// The service bridge script for a process without the Tauri app, i.e. the
+/// command line: in the bundle's resources next to the executable, or in the
+/// source tree for development builds.
+pub fn standalone_bridge_script() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = exe.parent()?;
+    let candidates = [
+        // macOS app bundle
+        dir.join("../Resources/resources/service-bridge.mjs"),
+        // Windows, and the deb/rpm layout under /usr/lib
+        dir.join("resources/service-bridge.mjs"),
+        dir.join("../lib/Namefix Menu Bar/resources/service-bridge.mjs"),
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("resources/service-bridge.mjs"),
+    ];
+    candidates.into_iter().find(|path| path.exists())
+}
+
+#[cfg(not(feature = "native-engine"))]
 fn resolve_bridge_script(app_handle: &AppHandle) -> anyhow::Result<PathBuf> {
     let resource_candidates = [
         "service-bridge.mjs",
@@ -254,7 +551,7 @@ fn resolve_bridge_script(app_handle: &AppHandle) -> anyhow::Result<PathBuf> {
     }
 }
 
-fn node_command() -> anyhow::Result<String> {
+pub fn node_command() -> anyhow::Result<String> {
     if let Ok(path) = std::env::var("NAMEFIX_NODE") {
         return Ok(path);
     }
@@ -284,36 +581,334 @@ fn node_command() -> anyhow::Result<String> {
     Err(anyhow::anyhow!("Node.js binary not found. Ensure Node is installed or set NAMEFIX_NODE."))
 }
 
-pub type BridgeState = NodeBridge;
+/// How long a call made during startup waits for the sidecar before giving up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Wait before the first restart after the sidecar exits; doubled after
+/// each restart that doesn't last, up to [`RESTART_DELAY_MAX`].
+#[cfg(not(feature = "native-engine"))]
+const RESTART_DELAY_MIN: Duration = Duration::from_secs(1);
+#[cfg(not(feature = "native-engine"))]
+const RESTART_DELAY_MAX: Duration = Duration::from_secs(60);
+
+/// A sidecar that stays up this long is considered healthy again, and the
+/// next exit starts over from [`RESTART_DELAY_MIN`].
+#[cfg(not(feature = "native-engine"))]
+const RESTART_STABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// Restarts in a row, without the sidecar staying up, before the supervisor
+/// gives up and leaves it to the Error Center's Restart Service.
+#[cfg(not(feature = "native-engine"))]
+const MAX_RESTARTS: u32 = 8;
+
+/// The engine once it has started, or why it couldn't.
+type EngineSlot = Option<Result<Arc<dyn Engine>, String>>;
+
+/// Handle to the engine that exists before the engine does, so the tray and
+/// commands are available immediately. Calls made while the sidecar is still
+/// starting (or restarting) wait for it instead of failing.
+#[derive(Clone)]
+pub struct BridgeHandle {
+    slot: Arc<watch::Sender<EngineSlot>>,
+    /// Bumped by [`restart_bridge`], so a supervisor from before stands down.
+    generation: Arc<AtomicU64>,
+}
+
+impl Default for BridgeHandle {
+    fn default() -> Self {
+        Self { slot: Arc::new(watch::channel(None).0), generation: Arc::new(AtomicU64::new(0)) }
+    }
+}
+
+impl BridgeHandle {
+    pub fn is_starting(&self) -> bool {
+        self.slot.borrow().is_none()
+    }
+
+    /// Why the sidecar failed to start, if it did.
+    pub fn startup_error(&self) -> Option<String> {
+        match &*self.slot.borrow() {
+            Some(Err(err)) => Some(err.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the sidecar failed to start or has exited since.
+    pub fn is_disconnected(&self) -> bool {
+        match &*self.slot.borrow() {
+            Some(Ok(engine)) => engine.is_disconnected(),
+            Some(Err(_)) => true,
+            None => false,
+        }
+    }
+
+    async fn connected(&self) -> Result<Arc<dyn Engine>, String> {
+        let mut rx = self.slot.subscribe();
+        let result = match tokio::time::timeout(CONNECT_TIMEOUT, rx.wait_for(Option::is_some)).await {
+            Ok(Ok(slot)) => slot.clone().expect("wait_for guarantees a value"),
+            Ok(Err(_)) => Err("Background service disconnected. Please restart the app.".to_string()),
+            Err(_) => Err("Background service is still starting. Please try again.".to_string()),
+        };
+        result
+    }
+
+    pub async fn invoke<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        let value = self.connected().await?.call(method, params).await?;
+        serde_json::from_value::<T>(value).map_err(|err| err.to_string())
+    }
+
+    pub async fn shutdown(&self) {
+        let engine = self.slot.borrow().clone();
+        if let Some(Ok(engine)) = engine {
+            engine.shutdown().await;
+        }
+    }
+
+    /// See [`NodeBridge::close`].
+    pub async fn close(&self) {
+        let engine = self.slot.borrow().clone();
+        if let Some(Ok(engine)) = engine {
+            engine.close().await;
+        }
+    }
+
+    fn resolve(&self, result: Result<Arc<dyn Engine>, String>) {
+        self.slot.send_replace(Some(result));
+    }
+}
+
+/// Spawn the sidecar in the background and connect it to the shared bridge
+/// handle. App setup returns straight away; the tray shows "Starting…" until
+/// this resolves. The sidecar is then supervised and restarted if it exits.
+/// Built with `native-engine`, the in-process engine is started instead.
+#[cfg(not(feature = "native-engine"))]
+pub fn connect_bridge(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    let handle = app_handle.state::<AppState>().bridge.clone();
+    let generation = handle.generation.load(Ordering::SeqCst);
+    async_runtime::spawn(async move {
+        match init_bridge(&app_handle).await {
+            Ok(bridge) => {
+                handle.resolve(Ok(Arc::new(bridge.clone())));
+                tray::refresh_status_label(&app_handle);
+                supervise(app_handle, handle, generation, bridge).await;
+            }
+            Err(err) => {
+                log::error!("Failed to start background service: {}", err);
+                handle.resolve(Err(format!("Background service failed to start: {}", err)));
+                notify::toast(&app_handle, format!("Namefix could not start its background service: {}", err), "error");
+                push::critical(&app_handle, format!("Namefix could not start its background service: {}", err));
+                tray::refresh_status_label(&app_handle);
+            }
+        }
+    });
+}
+
+#[cfg(feature = "native-engine")]
+pub fn connect_bridge(app_handle: &AppHandle) {
+    let handle = app_handle.state::<AppState>().bridge.clone();
+    match crate::engine::NativeEngine::start() {
+        Ok(engine) => {
+            forward_events(app_handle, engine.subscribe());
+            handle.resolve(Ok(Arc::new(engine)));
+        }
+        Err(err) => {
+            log::error!("Failed to start the rename engine: {}", err);
+            handle.resolve(Err(format!("Rename engine failed to start: {}", err)));
+            notify::toast(app_handle, format!("Namefix could not start its rename engine: {}", err), "error");
+        }
+    }
+    tray::refresh_status_label(app_handle);
+}
+
+/// Replace a disconnected sidecar with a fresh one. Calls made meanwhile wait
+/// for the new sidecar, as they do at launch.
+pub async fn restart_bridge(app_handle: &AppHandle) {
+    let handle = app_handle.state::<AppState>().bridge.clone();
+    handle.generation.fetch_add(1, Ordering::SeqCst);
+    handle.shutdown().await;
+    handle.slot.send_replace(None);
+    connect_bridge(app_handle);
+}
 
-pub async fn init_bridge(app_handle: &AppHandle) -> anyhow::Result<NodeBridge> {
+/// Restart `bridge` whenever it exits on its own, backing off exponentially
+/// while it keeps failing, and put the new sidecar back the way the old one
+/// was. Returns once the app shuts the sidecar down, [`restart_bridge`] takes
+/// over, or [`MAX_RESTARTS`] restarts in a row haven't stuck.
+#[cfg(not(feature = "native-engine"))]
+async fn supervise(app_handle: AppHandle, handle: BridgeHandle, generation: u64, mut bridge: NodeBridge) {
+    let superseded = || handle.generation.load(Ordering::SeqCst) != generation;
+    let mut delay = RESTART_DELAY_MIN;
+    let mut failures = 0;
+    // What the user last saw, kept until a restarted sidecar has been brought back to it.
+    let mut restore: Option<ServiceStatus> = None;
+    loop {
+        let started = Instant::now();
+        bridge.exited().await;
+        if bridge.0.stopping.load(Ordering::SeqCst) || superseded() {
+            return;
+        }
+        if started.elapsed() >= RESTART_STABLE_AFTER {
+            delay = RESTART_DELAY_MIN;
+            failures = 0;
+        }
+        if bridge.is_ready() {
+            restore = Some(app_handle.state::<AppState>().status());
+        }
+        handle.slot.send_replace(None);
+        tray::refresh_status_label(&app_handle);
+
+        bridge = loop {
+            failures += 1;
+            if failures > MAX_RESTARTS {
+                log::error!("Background service exited {} times in a row; giving up", MAX_RESTARTS);
+                handle.resolve(Err("Background service keeps stopping".to_string()));
+                tray::refresh_status_label(&app_handle);
+                tray::refresh_attention(&app_handle);
+                notify::toast(
+                    &app_handle,
+                    "Background service keeps stopping. Restart it from the Error Center once the problem is fixed.",
+                    "error",
+                );
+                push::critical(&app_handle, "Namefix stopped watching: its background service keeps stopping.");
+                return;
+            }
+            log::warn!("Restarting background service in {:?} (attempt {})", delay, failures);
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(RESTART_DELAY_MAX);
+            if superseded() {
+                return;
+            }
+            match init_bridge(&app_handle).await {
+                Ok(bridge) => break bridge,
+                Err(err) => log::error!("Failed to restart background service: {}", err),
+            }
+        };
+        if superseded() {
+            bridge.shutdown().await;
+            return;
+        }
+
+        let ready = bridge.wait_ready(CONNECT_TIMEOUT).await;
+        handle.resolve(Ok(Arc::new(bridge.clone())));
+        if !ready {
+            // It exited or hung while starting; the next pass handles it.
+            log::warn!("Restarted background service did not report ready");
+            continue;
+        }
+        if let Some(before) = restore.take() {
+            if let Err(err) = replay(&app_handle, &handle, &before).await {
+                log::warn!("Failed to restore service state after restart: {}", err);
+            }
+        }
+        match get_status(&handle).await {
+            Ok(status) => {
+                log::info!("Background service restarted");
+                let _ = app_handle.emit("service://status", &status);
+                let _ = app_handle.emit("service://bridge-restarted", &status);
+            }
+            Err(err) => log::warn!("Failed to fetch status after restart: {}", err),
+        }
+        tray::refresh_status_label(&app_handle);
+        tray::refresh_attention(&app_handle);
+    }
+}
+
+/// Bring a restarted service back to `before`. Watched folders and dry run
+/// are saved in its config and normally come back on their own; this covers
+/// changes the old sidecar hadn't saved yet, and what isn't saved at all: the
+/// watch exclusions the menu bar pushes and whether watching was paused.
+#[cfg(not(feature = "native-engine"))]
+async fn replay(app_handle: &AppHandle, handle: &BridgeHandle, before: &ServiceStatus) -> Result<(), String> {
+    let status = get_status(handle).await?;
+    for directory in before.directories.iter().filter(|dir| !status.directories.contains(dir)) {
+        add_watch_dir(handle, directory.clone()).await?;
+    }
+    if status.dry_run != before.dry_run {
+        set_dry_run(handle, before.dry_run).await?;
+    }
+    set_watch_exclusions(handle, app_handle.state::<AppState>().exclusions.names()).await?;
+    if status.running && !before.running {
+        toggle_running(handle, Some(false)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "native-engine"))]
+async fn init_bridge(app_handle: &AppHandle) -> anyhow::Result<NodeBridge> {
     let bridge = NodeBridge::new(app_handle).await?;
-    let mut rx = bridge.subscribe();
+    // Names the service's rules produce come back here first; see namehook.
+    if let Err(err) = bridge.invoke::<bool>("registerNameHook", Value::Null).await {
+        log::warn!("Could not register the name hook: {}", err);
+    }
+    forward_events(app_handle, bridge.subscribe());
+    Ok(bridge)
+}
+
+/// Pass the engine's events on to the tray, notifications and the window.
+fn forward_events(app_handle: &AppHandle, mut rx: broadcast::Receiver<BridgeEvent>) {
     let emitter_handle = app_handle.clone();
     async_runtime::spawn(async move {
         while let Ok(event) = rx.recv().await {
+            let state = emitter_handle.state::<AppState>();
+            state.activity.observe(&event);
+            state.events.observe(&event);
+            state.telemetry.observe(&event);
+            // Observer mode records problems in the audit log rather than raising them.
+            let observing = state.status().observer_mode;
+            let attention_changed = !observing && state.attention.observe(&event);
+            if event.name == "file" {
+                log_file_event(&event.payload);
+                tray::refresh_recent(&emitter_handle);
+                tray::refresh_stats(&emitter_handle);
+                notify::rename(&emitter_handle, &event.payload);
+                if !observing {
+                    push::observe(&emitter_handle, &event.payload);
+                }
+            }
+            if attention_changed {
+                tray::refresh_attention(&emitter_handle);
+            }
+            if event.name == "import" {
+                if let Ok(progress) = serde_json::from_value::<ImportProgress>(event.payload.clone()) {
+                    if progress.done && progress.eject {
+                        async_runtime::spawn(volume::eject_card(emitter_handle.clone(), progress.card));
+                    }
+                }
+            }
+            if event.name == "burst" {
+                if let Some(directory) = event.payload.get("directory").and_then(Value::as_str) {
+                    tray::offer_snooze(&emitter_handle, directory);
+                }
+            }
+            if event.name == "toast" {
+                notify::forward(&emitter_handle, &event.payload);
+                continue;
+            }
             let event_name = format!("service://{}", event.name);
             let _ = emitter_handle.emit(&event_name, event.payload);
         }
     });
-    Ok(bridge)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ServiceStatus {
-  pub running: bool,
-  pub directories: Vec<String>,
-  #[serde(rename = "dryRun")]
-  pub dry_run: bool,
-  #[serde(rename = "launchOnLogin")]
-  pub launch_on_login: bool,
+/// Log a file event, its names hashed with their folder while log privacy
+/// is on; they're bare names, which the logger's own redaction can't spot.
+fn log_file_event(payload: &Value) {
+    let field = |key| payload.get(key).and_then(Value::as_str);
+    let directory = field("directory").unwrap_or_default();
+    let kind = field("kind").unwrap_or("?");
+    let file = logging::file_name(directory, field("file").unwrap_or("?"));
+    match field("target") {
+        Some(target) => log::info!("File event: {} {} → {}", kind, file, logging::file_name(directory, target)),
+        None => log::info!("File event: {} {}", kind, file),
+    }
 }
 
-pub async fn get_status(bridge: &BridgeState) -> Result<ServiceStatus, String> {
+pub async fn get_status(bridge: &BridgeHandle) -> Result<ServiceStatus, String> {
     bridge.invoke::<ServiceStatus>("getStatus", Value::Null).await
 }
 
-pub async fn toggle_running(bridge: &BridgeState, desired: Option<bool>) -> Result<ServiceStatus, String> {
+pub async fn toggle_running(bridge: &BridgeHandle, desired: Option<bool>) -> Result<ServiceStatus, String> {
     let params = match desired {
         Some(flag) => json!({ "desired": flag }),
         None => json!({}),  // Empty object, not null: JS default params only apply for undefined, and JSON-RPC treats null as defined
@@ -321,26 +916,319 @@ pub async fn toggle_running(bridge: &BridgeState, desired: Option<bool>) -> Resu
     bridge.invoke::<ServiceStatus>("toggleRunning", params).await
 }
 
-pub async fn list_directories(bridge: &BridgeState) -> Result<Vec<String>, String> {
+pub async fn list_directories(bridge: &BridgeHandle) -> Result<Vec<String>, String> {
     bridge.invoke::<Vec<String>>("listDirectories", Value::Null).await
 }
 
-pub async fn set_launch_on_login(bridge: &BridgeState, enabled: bool) -> Result<bool, String> {
+pub async fn set_launch_on_login(bridge: &BridgeHandle, enabled: bool) -> Result<bool, String> {
     let params = json!({ "enabled": enabled });
     bridge.invoke::<bool>("setLaunchOnLogin", params).await
 }
 
-pub async fn set_dry_run(bridge: &BridgeState, enabled: bool) -> Result<ServiceStatus, String> {
+pub async fn set_dry_run(bridge: &BridgeHandle, enabled: bool) -> Result<ServiceStatus, String> {
     let params = json!({ "enabled": enabled });
     bridge.invoke::<ServiceStatus>("setDryRun", params).await
 }
 
-pub async fn add_watch_dir(bridge: &BridgeState, directory: String) -> Result<Vec<String>, String> {
+pub async fn set_observer_mode(bridge: &BridgeHandle, enabled: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "enabled": enabled });
+    bridge.invoke::<ServiceStatus>("setObserverMode", params).await
+}
+
+pub async fn set_log_privacy(bridge: &BridgeHandle, enabled: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "enabled": enabled });
+    bridge.invoke::<ServiceStatus>("setLogPrivacy", params).await
+}
+
+/// Write rename history out again after it went degraded: `salvage` keeps
+/// what could be read, `restore` starts from the latest backup.
+pub async fn repair_history(bridge: &BridgeHandle, mode: &str) -> Result<HistoryHealth, String> {
+    let params = json!({ "mode": mode });
+    bridge.invoke::<HistoryHealth>("repairHistory", params).await
+}
+
+pub async fn set_data_dir(bridge: &BridgeHandle, path: Option<String>) -> Result<ServiceStatus, String> {
+    let params = json!({ "path": path });
+    bridge.invoke::<ServiceStatus>("setDataDir", params).await
+}
+
+/// Sync settings through a file in `path`, e.g. a folder in iCloud Drive or Dropbox,
+/// or stop syncing when None.
+pub async fn set_sync_dir(bridge: &BridgeHandle, path: Option<String>) -> Result<ServiceStatus, String> {
+    let params = json!({ "path": path });
+    bridge.invoke::<ServiceStatus>("setSyncDir", params).await
+}
+
+/// Settle a settings conflict by keeping this machine's settings (`local`) or the
+/// synced folder's (`remote`).
+pub async fn resolve_sync_conflict(bridge: &BridgeHandle, keep: &str) -> Result<ServiceStatus, String> {
+    let params = json!({ "keep": keep });
+    bridge.invoke::<ServiceStatus>("resolveSyncConflict", params).await
+}
+
+/// The pairing key another machine imports to read the encrypted synced settings.
+pub async fn export_sync_key(bridge: &BridgeHandle) -> Result<String, String> {
+    bridge.invoke::<String>("exportSyncKey", Value::Null).await
+}
+
+/// Replace this machine's settings sync key with one exported elsewhere.
+pub async fn import_sync_key(bridge: &BridgeHandle, key: String) -> Result<ServiceStatus, String> {
+    let params = json!({ "key": key });
+    bridge.invoke::<ServiceStatus>("importSyncKey", params).await
+}
+
+/// Another Namefix install found on the local network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyPeer {
+    pub id: String,
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    pub paired: bool,
+}
+
+/// What a paired machine reports about itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerStatus {
+    pub id: String,
+    pub name: String,
+    pub running: bool,
+    pub directories: Vec<String>,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    #[serde(rename = "observerMode")]
+    pub observer_mode: bool,
+}
+
+/// Let other installs on the local network find and pair with this machine.
+pub async fn set_lan_pairing(bridge: &BridgeHandle, enabled: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "enabled": enabled });
+    bridge.invoke::<ServiceStatus>("setLanPairing", params).await
+}
+
+/// Show a short code for another machine to pair with.
+pub async fn start_pairing(bridge: &BridgeHandle) -> Result<PairingCode, String> {
+    bridge.invoke::<PairingCode>("startPairing", Value::Null).await
+}
+
+pub async fn find_peers(bridge: &BridgeHandle) -> Result<Vec<NearbyPeer>, String> {
+    bridge.invoke::<Vec<NearbyPeer>>("findPeers", Value::Null).await
+}
+
+/// Pair with a nearby machine using the code it shows, receiving its settings sync key.
+pub async fn pair_peer(bridge: &BridgeHandle, peer_id: String, code: String) -> Result<ServiceStatus, String> {
+    let params = json!({ "peerId": peer_id, "code": code });
+    bridge.invoke::<ServiceStatus>("pairPeer", params).await
+}
+
+pub async fn get_peer_status(bridge: &BridgeHandle, peer_id: String) -> Result<PeerStatus, String> {
+    let params = json!({ "peerId": peer_id });
+    bridge.invoke::<PeerStatus>("getPeerStatus", params).await
+}
+
+pub async fn unpair_peer(bridge: &BridgeHandle, peer_id: String) -> Result<ServiceStatus, String> {
+    let params = json!({ "peerId": peer_id });
+    bridge.invoke::<ServiceStatus>("unpairPeer", params).await
+}
+
+/// A file event recorded in the audit log, i.e. what happened (or, in observer
+/// mode, would have happened) to a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub kind: String,
+    pub file: String,
+    pub directory: String,
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(rename = "collidedWith", skip_serializing_if = "Option::is_none")]
+    pub collided_with: Option<String>,
+}
+
+/// The newest audit entries first; the service caps `None` at 200.
+pub async fn get_audit_log(bridge: &BridgeHandle, limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+    let mut params = json!({});
+    if let Some(limit) = limit {
+        params["limit"] = json!(limit);
+    }
+    bridge.invoke::<Vec<AuditEntry>>("getAuditLog", params).await
+}
+
+pub async fn add_watch_dir(bridge: &BridgeHandle, directory: String) -> Result<Vec<String>, String> {
     let params = json!({ "directory": directory });
     bridge.invoke::<Vec<String>>("addWatchDir", params).await
 }
 
-pub async fn remove_watch_dir(bridge: &BridgeState, directory: String) -> Result<Vec<String>, String> {
+pub async fn set_directory_priority(bridge: &BridgeHandle, directory: String, high: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "high": high });
+    bridge.invoke::<ServiceStatus>("setDirectoryPriority", params).await
+}
+
+pub async fn set_directory_dry_run(bridge: &BridgeHandle, directory: String, enabled: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "enabled": enabled });
+    bridge.invoke::<ServiceStatus>("setDirectoryDryRun", params).await
+}
+
+/// Changes to a directory's config; fields left as None keep their value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirectoryConfigUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(rename = "includeExtensions", skip_serializing_if = "Option::is_none")]
+    pub include_extensions: Option<Vec<String>>,
+    #[serde(rename = "excludeExtensions", skip_serializing_if = "Option::is_none")]
+    pub exclude_extensions: Option<Vec<String>>,
+    #[serde(rename = "dryRun", skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+}
+
+pub async fn get_dir_config(bridge: &BridgeHandle, directory: String) -> Result<DirectoryConfig, String> {
+    let params = json!({ "directory": directory });
+    bridge.invoke::<DirectoryConfig>("getDirConfig", params).await
+}
+
+/// Change whether a directory's subfolders are watched, which extensions it
+/// renames, or its dry-run setting.
+pub async fn set_dir_config(
+    bridge: &BridgeHandle,
+    directory: String,
+    config: DirectoryConfigUpdate,
+) -> Result<DirectoryConfig, String> {
+    let params = json!({ "directory": directory, "config": config });
+    bridge.invoke::<DirectoryConfig>("setDirConfig", params).await
+}
+
+/// How the service watches directories on this machine.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatchBackend {
+    Fsevents,
+    EndpointSecurity,
+    UsnJournal,
+    Polling,
+}
+
+pub async fn set_watch_backend(
+    bridge: &BridgeHandle,
+    backend: WatchBackend,
+    poll_interval_ms: Option<u64>,
+) -> Result<ServiceStatus, String> {
+    let mut params = json!({ "backend": backend });
+    if let Some(ms) = poll_interval_ms {
+        params["pollIntervalMs"] = json!(ms);
+    }
+    bridge.invoke::<ServiceStatus>("setWatchBackend", params).await
+}
+
+pub async fn set_directory_origin_rule(
+    bridge: &BridgeHandle,
+    directory: String,
+    allow: Vec<String>,
+    deny: Vec<String>,
+) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "allow": allow, "deny": deny });
+    bridge.invoke::<ServiceStatus>("setDirectoryOriginRule", params).await
+}
+
+pub async fn reorder_directories(bridge: &BridgeHandle, order: Vec<String>) -> Result<ServiceStatus, String> {
+    let params = json!({ "order": order });
+    bridge.invoke::<ServiceStatus>("reorderDirectories", params).await
+}
+
+pub async fn set_directory_pinned(bridge: &BridgeHandle, directory: String, pinned: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "pinned": pinned });
+    bridge.invoke::<ServiceStatus>("setDirectoryPinned", params).await
+}
+
+pub async fn set_directory_group(
+    bridge: &BridgeHandle,
+    directory: String,
+    group: Option<String>,
+) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "group": group });
+    bridge.invoke::<ServiceStatus>("setDirectoryGroup", params).await
+}
+
+pub async fn set_directory_hot_folder(
+    bridge: &BridgeHandle,
+    directory: String,
+    destination: Option<String>,
+    command: Option<Vec<String>>,
+) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "destination": destination, "command": command });
+    bridge.invoke::<ServiceStatus>("setDirectoryHotFolder", params).await
+}
+
+/// The name the current rules would give `name`: a bare filename, a path or a
+/// `file://` URL, answered in the same form. None when no rule renames it.
+pub async fn propose_name(bridge: &BridgeHandle, name: String) -> Result<Option<String>, String> {
+    bridge.invoke::<Option<String>>("proposeName", json!({ "name": name })).await
+}
+
+pub async fn snooze_directory(bridge: &BridgeHandle, directory: String, until: u64) -> Result<ServiceStatus, String> {
+    let params = json!({ "directory": directory, "until": until });
+    bridge.invoke::<ServiceStatus>("snoozeDirectory", params).await
+}
+
+/// Moves a camera card's photos into a library folder when the card mounts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreset {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub template: String,
+    pub prefix: String,
+    pub destination: String,
+    /// Eject the card once every file was imported.
+    pub eject: bool,
+}
+
+/// Progress of one preset importing one card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub preset: String,
+    pub card: String,
+    pub total: u64,
+    pub imported: u64,
+    pub failed: u64,
+    pub done: bool,
+    pub eject: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub async fn list_import_presets(bridge: &BridgeHandle) -> Result<Vec<ImportPreset>, String> {
+    bridge.invoke::<Vec<ImportPreset>>("listImportPresets", Value::Null).await
+}
+
+pub async fn set_import_preset(bridge: &BridgeHandle, preset: ImportPreset) -> Result<Vec<ImportPreset>, String> {
+    bridge.invoke::<Vec<ImportPreset>>("setImportPreset", json!({ "preset": preset })).await
+}
+
+pub async fn delete_import_preset(bridge: &BridgeHandle, id: String) -> Result<Vec<ImportPreset>, String> {
+    bridge.invoke::<Vec<ImportPreset>>("deleteImportPreset", json!({ "id": id })).await
+}
+
+/// Run a preset now against every mounted camera card.
+pub async fn run_import_preset(bridge: &BridgeHandle, id: String) -> Result<Vec<ImportProgress>, String> {
+    bridge.invoke::<Vec<ImportProgress>>("runImportPreset", json!({ "id": id })).await
+}
+
+/// Stop (or resume) watching the directories on the volume mounted at `root`,
+/// so it can be ejected.
+pub async fn set_volume_released(bridge: &BridgeHandle, root: &str, released: bool) -> Result<ServiceStatus, String> {
+    let params = json!({ "root": root, "released": released });
+    bridge.invoke::<ServiceStatus>("setVolumeReleased", params).await
+}
+
+pub async fn set_watch_exclusions(bridge: &BridgeHandle, names: Vec<String>) -> Result<Vec<String>, String> {
+    let params = json!({ "names": names });
+    bridge.invoke::<Vec<String>>("setWatchExclusions", params).await
+}
+
+pub async fn remove_watch_dir(bridge: &BridgeHandle, directory: String) -> Result<Vec<String>, String> {
     let params = json!({ "directory": directory });
     bridge.invoke::<Vec<String>>("removeWatchDir", params).await
 }
@@ -351,10 +1239,255 @@ pub struct UndoResult {
     pub reason: Option<String>,
 }
 
-pub async fn undo(bridge: &BridgeState) -> Result<UndoResult, String> {
+pub async fn undo(bridge: &BridgeHandle) -> Result<UndoResult, String> {
     bridge.invoke::<UndoResult>("undo", Value::Null).await
 }
 
+/// Undo the rename with this history id, leaving newer renames in place.
+pub async fn undo_entry(bridge: &BridgeHandle, id: String) -> Result<UndoResult, String> {
+    let params = json!({ "id": id });
+    bridge.invoke::<UndoResult>("undoEntry", params).await
+}
+
+/// Redo the most recent undo since the service started.
+pub async fn redo(bridge: &BridgeHandle) -> Result<UndoResult, String> {
+    bridge.invoke::<UndoResult>("redo", Value::Null).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unique id of the rename; missing from entries recorded before ids were kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "ts")]
+    pub timestamp: u64,
+    pub rule: Option<String>,
+    /// Why the rename had to wait for another app to let go of the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deferred: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// Id of the session that was running when the file was renamed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+}
+
+/// Optional filters for `search_history`. Timestamps are epoch milliseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilters {
+    #[serde(default)]
+    pub regex: bool,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub directory: Option<String>,
+    pub rule: Option<String>,
+    pub session: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub async fn search_history(bridge: &BridgeHandle, query: String, filters: HistoryFilters) -> Result<Vec<HistoryEntry>, String> {
+    let mut params = serde_json::to_value(&filters).map_err(|err| err.to_string())?;
+    params["text"] = Value::String(query);
+    bridge.invoke::<Vec<HistoryEntry>>("searchHistory", json!({ "query": params })).await
+}
+
+/// Renames newest first, `limit` at a time after skipping `offset`.
+pub async fn get_history(bridge: &BridgeHandle, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>, String> {
+    let params = json!({ "limit": limit, "offset": offset });
+    bridge.invoke::<Vec<HistoryEntry>>("getHistory", params).await
+}
+
+/// Write all rename history to `path`, for [`merge_history`] on another
+/// machine. Returns how many renames were written.
+pub async fn export_history(bridge: &BridgeHandle, path: String) -> Result<usize, String> {
+    bridge.invoke::<usize>("exportHistory", json!({ "path": path })).await
+}
+
+/// What merging another machine's exported history added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryMerge {
+    pub imported: usize,
+    /// Renames this machine already had, matched by id.
+    pub duplicates: usize,
+    /// Lines of the export that weren't renames.
+    pub skipped: usize,
+}
+
+pub async fn merge_history(bridge: &BridgeHandle, path: String) -> Result<HistoryMerge, String> {
+    bridge.invoke::<HistoryMerge>("mergeHistory", json!({ "path": path })).await
+}
+
+/// Renames recorded for one watched directory, subfolders included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryUsage {
+    pub directory: String,
+    #[serde(rename = "filesRenamed")]
+    pub files_renamed: u64,
+    #[serde(rename = "bytesProcessed")]
+    pub bytes_processed: u64,
+}
+
+/// Per-directory totals since `since` (epoch milliseconds); `None` means the
+/// start of the current month.
+pub async fn get_directory_usage(bridge: &BridgeHandle, since: Option<u64>) -> Result<Vec<DirectoryUsage>, String> {
+    let mut params = json!({});
+    if let Some(since) = since {
+        params["since"] = json!(since);
+    }
+    bridge.invoke::<Vec<DirectoryUsage>>("getDirectoryUsage", params).await
+}
+
+/// What the service did to recover after the machine woke.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WakeRecovery {
+    pub restarted: u64,
+    #[serde(rename = "caughtUp")]
+    pub caught_up: u64,
+}
+
+/// Restart the watchers and queue files that arrived from `since` (epoch
+/// milliseconds) on, while the watchers may have been stale.
+pub async fn recover_from_sleep(bridge: &BridgeHandle, since: u64) -> Result<WakeRecovery, String> {
+    bridge.invoke::<WakeRecovery>("recoverFromSleep", json!({ "since": since })).await
+}
+
+/// A session with totals of the renames recorded for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    #[serde(flatten)]
+    pub session: RenameSession,
+    #[serde(rename = "filesRenamed")]
+    pub files_renamed: u64,
+    #[serde(rename = "bytesProcessed")]
+    pub bytes_processed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionUndoResult {
+    pub undone: u64,
+    /// Renames that could not be restored; they stay in the journal.
+    pub failed: u64,
+}
+
+/// Start tagging renames with a new named session, ending any running one.
+pub async fn start_session(bridge: &BridgeHandle, name: String) -> Result<ServiceStatus, String> {
+    bridge.invoke::<ServiceStatus>("startSession", json!({ "name": name })).await
+}
+
+pub async fn end_session(bridge: &BridgeHandle) -> Result<ServiceStatus, String> {
+    bridge.invoke::<ServiceStatus>("endSession", Value::Null).await
+}
+
+/// Known sessions, newest first.
+pub async fn list_sessions(bridge: &BridgeHandle) -> Result<Vec<SessionSummary>, String> {
+    bridge.invoke::<Vec<SessionSummary>>("listSessions", Value::Null).await
+}
+
+pub async fn undo_session(bridge: &BridgeHandle, id: String) -> Result<SessionUndoResult, String> {
+    bridge.invoke::<SessionUndoResult>("undoSession", json!({ "id": id })).await
+}
+
+/// Journal retention limits; every field is optional and `None` means unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(rename = "maxAgeDays", skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    #[serde(rename = "maxEntries", skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<u64>,
+    #[serde(rename = "maxBytes", skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneResult {
+    pub removed: u64,
+    /// Entries left, i.e. how many renames can still be undone.
+    pub remaining: u64,
+}
+
+/// Prune the rename journal; `None` applies the configured retention policy.
+pub async fn prune_history(bridge: &BridgeHandle, policy: Option<RetentionPolicy>) -> Result<PruneResult, String> {
+    let params = json!({ "policy": policy });
+    bridge.invoke::<PruneResult>("pruneHistory", params).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub files: u32,
+    pub directory: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
+    #[serde(rename = "totalMs")]
+    pub total_ms: f64,
+    #[serde(rename = "filesPerSecond")]
+    pub files_per_second: f64,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: LatencyPercentiles,
+}
+
+pub async fn run_benchmark(bridge: &BridgeHandle, files: Option<u32>, directory: Option<String>) -> Result<BenchmarkResult, String> {
+    let params = json!({ "files": files, "directory": directory });
+    bridge.invoke::<BenchmarkResult>("runBenchmark", params).await
+}
+
+pub async fn get_benchmark_results(bridge: &BridgeHandle) -> Result<Vec<BenchmarkResult>, String> {
+    bridge.invoke::<Vec<BenchmarkResult>>("getBenchmarkResults", Value::Null).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePlanEntry {
+    pub file: String,
+    pub target: Option<String>,
+    pub rule: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: u64,
+    /// The proposed name was taken, so `target` has an `_N` suffix.
+    #[serde(default)]
+    pub conflict: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePlan {
+    pub directory: String,
+    pub entries: Vec<RenamePlanEntry>,
+}
+
+/// Only `profile` is applied when given, whether or not it is enabled.
+pub async fn plan_directory(bridge: &BridgeHandle, directory: String, profile: Option<String>) -> Result<RenamePlan, String> {
+    let params = json!({ "directory": directory, "profile": profile });
+    bridge.invoke::<RenamePlan>("planDirectory", params).await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunOnceResult {
+    pub directory: String,
+    pub renamed: u32,
+    pub failed: u32,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+pub async fn run_once(bridge: &BridgeHandle, directory: String, profile: Option<String>) -> Result<RunOnceResult, String> {
+    let params = json!({ "directory": directory, "profile": profile });
+    bridge.invoke::<RunOnceResult>("runOnce", params).await
+}
+
+/// Rename the files of `plan`, which may have had entries left out.
+pub async fn apply_plan(bridge: &BridgeHandle, plan: &RenamePlan) -> Result<RunOnceResult, String> {
+    let params = json!({ "plan": plan });
+    bridge.invoke::<RunOnceResult>("applyPlan", params).await
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
     pub id: String,
@@ -368,33 +1501,176 @@ pub struct Profile {
     pub priority: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action: Option<String>,
+    #[serde(rename = "fiscalYearStartMonth", skip_serializing_if = "Option::is_none")]
+    pub fiscal_year_start_month: Option<u8>,
+    #[serde(rename = "timeZone", skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(rename = "caseStyle", default, skip_serializing_if = "Option::is_none")]
+    pub case_style: Option<String>,
+    #[serde(rename = "datePrefix", default, skip_serializing_if = "Option::is_none")]
+    pub date_prefix: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
 }
 
-pub async fn get_profiles(bridge: &BridgeState) -> Result<Vec<Profile>, String> {
+/// What a rule can do with the files it matches.
+pub const RULE_ACTIONS: [&str; 3] = ["rename", "convert", "rename+convert"];
+
+/// Cases a rule can put its new names in.
+pub const CASE_STYLES: [&str; 5] = ["lower", "upper", "title", "kebab", "snake"];
+
+/// A rename rule, as the window calls the service's profiles. Enabled rules
+/// are tried by ascending `priority` and the first whose `pattern` matches
+/// the file name (a glob, or a regex when `is_regex`) names the file:
+/// `template` is filled in, with `<match:N>` taking the regex's groups, then
+/// `case_style` and `date_prefix` are applied to the result. A rule with a
+/// `condition` expression only names files it holds for.
+pub type RenameRule = Profile;
+
+/// Refuse a rule the service would drop when it loads its config.
+pub fn check_rule(rule: &RenameRule) -> Result<(), String> {
+    if [&rule.name, &rule.pattern, &rule.template].iter().any(|field| field.trim().is_empty()) {
+        return Err("A rule needs a name, a pattern and a template".to_string());
+    }
+    if let Some(action) = rule.action.as_deref().filter(|action| !RULE_ACTIONS.contains(action)) {
+        return Err(format!("Unknown action \"{}\"", action));
+    }
+    if let Some(style) = rule.case_style.as_deref().filter(|style| !CASE_STYLES.contains(style)) {
+        return Err(format!("Unknown case style \"{}\"", style));
+    }
+    if rule.fiscal_year_start_month.is_some_and(|month| !(1..=12).contains(&month)) {
+        return Err("The fiscal year must start in a month from 1 to 12".to_string());
+    }
+    Ok(())
+}
+
+/// An id made from a rule's name, such as `camera-imports`.
+pub fn slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for ch in name.chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Every rule in the order it is tried; disabled rules keep their place.
+pub async fn list_rules(bridge: &BridgeHandle) -> Result<Vec<RenameRule>, String> {
+    get_profiles(bridge).await.map(in_evaluation_order)
+}
+
+/// Save a new rule at its priority. Without an id it gets one from its name.
+pub async fn add_rule(bridge: &BridgeHandle, mut rule: RenameRule) -> Result<Vec<RenameRule>, String> {
+    check_rule(&rule)?;
+    let rules = get_profiles(bridge).await?;
+    if rule.id.trim().is_empty() {
+        let base = Some(slug(&rule.name)).filter(|base| !base.is_empty()).unwrap_or_else(|| "rule".to_string());
+        rule.id = (1..)
+            .map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) })
+            .find(|id| rules.iter().all(|existing| &existing.id != id))
+            .unwrap_or(base);
+    } else if rules.iter().any(|existing| existing.id == rule.id) {
+        return Err(format!("A rule with id \"{}\" already exists", rule.id));
+    }
+    set_profile(bridge, rule).await.map(in_evaluation_order)
+}
+
+/// Replace the rule with `rule`'s id.
+pub async fn update_rule(bridge: &BridgeHandle, rule: RenameRule) -> Result<Vec<RenameRule>, String> {
+    check_rule(&rule)?;
+    if get_profile(bridge, rule.id.clone()).await?.is_none() {
+        return Err(format!("No rule with id \"{}\"", rule.id));
+    }
+    set_profile(bridge, rule).await.map(in_evaluation_order)
+}
+
+pub async fn remove_rule(bridge: &BridgeHandle, id: String) -> Result<Vec<RenameRule>, String> {
+    if get_profile(bridge, id.clone()).await?.is_none() {
+        return Err(format!("No rule with id \"{}\"", id));
+    }
+    delete_profile(bridge, id).await.map(in_evaluation_order)
+}
+
+/// Try the rules in `ordered_ids` first, in that order; any left out follow.
+pub async fn reorder_rules(bridge: &BridgeHandle, ordered_ids: Vec<String>) -> Result<Vec<RenameRule>, String> {
+    let rules = list_rules(bridge).await?;
+    if let Some(unknown) = ordered_ids.iter().find(|id| rules.iter().all(|rule| &rule.id != *id)) {
+        return Err(format!("No rule with id \"{}\"", unknown));
+    }
+    let rest = rules.into_iter().map(|rule| rule.id).filter(|id| !ordered_ids.contains(id));
+    let ordered_ids = ordered_ids.iter().cloned().chain(rest).collect();
+    reorder_profiles(bridge, ordered_ids).await.map(in_evaluation_order)
+}
+
+fn in_evaluation_order(mut rules: Vec<RenameRule>) -> Vec<RenameRule> {
+    rules.sort_by_key(|rule| rule.priority);
+    rules
+}
+
+pub async fn get_profiles(bridge: &BridgeHandle) -> Result<Vec<Profile>, String> {
     bridge.invoke::<Vec<Profile>>("getProfiles", Value::Null).await
 }
 
-pub async fn get_profile(bridge: &BridgeState, id: String) -> Result<Option<Profile>, String> {
+pub async fn get_profile(bridge: &BridgeHandle, id: String) -> Result<Option<Profile>, String> {
     let params = json!({ "id": id });
     bridge.invoke::<Option<Profile>>("getProfile", params).await
 }
 
-pub async fn set_profile(bridge: &BridgeState, profile: Profile) -> Result<Vec<Profile>, String> {
+pub async fn set_profile(bridge: &BridgeHandle, profile: Profile) -> Result<Vec<Profile>, String> {
     let params = json!({ "profile": profile });
     bridge.invoke::<Vec<Profile>>("setProfile", params).await
 }
 
-pub async fn delete_profile(bridge: &BridgeState, id: String) -> Result<Vec<Profile>, String> {
+pub async fn delete_profile(bridge: &BridgeHandle, id: String) -> Result<Vec<Profile>, String> {
     let params = json!({ "id": id });
     bridge.invoke::<Vec<Profile>>("deleteProfile", params).await
 }
 
-pub async fn toggle_profile(bridge: &BridgeState, id: String, enabled: Option<bool>) -> Result<Vec<Profile>, String> {
+pub async fn toggle_profile(bridge: &BridgeHandle, id: String, enabled: Option<bool>) -> Result<Vec<Profile>, String> {
     let params = json!({ "id": id, "enabled": enabled });
     bridge.invoke::<Vec<Profile>>("toggleProfile", params).await
 }
 
-pub async fn reorder_profiles(bridge: &BridgeState, ordered_ids: Vec<String>) -> Result<Vec<Profile>, String> {
+pub async fn reorder_profiles(bridge: &BridgeHandle, ordered_ids: Vec<String>) -> Result<Vec<Profile>, String> {
     let params = json!({ "orderedIds": ordered_ids });
     bridge.invoke::<Vec<Profile>>("reorderProfiles", params).await
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub id: String,
+    pub directory: String,
+    pub file: String,
+    pub priority: String,
+    #[serde(rename = "enqueuedAt")]
+    pub enqueued_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    pub paused: bool,
+    pub active: Vec<QueueItem>,
+    pub pending: Vec<QueueItem>,
+}
+
+pub async fn get_queue(bridge: &BridgeHandle) -> Result<QueueSnapshot, String> {
+    bridge.invoke::<QueueSnapshot>("getQueue", Value::Null).await
+}
+
+pub async fn set_queue_paused(bridge: &BridgeHandle, paused: bool) -> Result<QueueSnapshot, String> {
+    let params = json!({ "paused": paused });
+    bridge.invoke::<QueueSnapshot>("setQueuePaused", params).await
+}
+
+pub async fn reorder_queue(bridge: &BridgeHandle, ordered_ids: Vec<String>) -> Result<QueueSnapshot, String> {
+    let params = json!({ "orderedIds": ordered_ids });
+    bridge.invoke::<QueueSnapshot>("reorderQueue", params).await
+}
+
+pub async fn drop_queue_item(bridge: &BridgeHandle, id: String) -> Result<QueueSnapshot, String> {
+    let params = json!({ "id": id });
+    bridge.invoke::<QueueSnapshot>("dropQueueItem", params).await
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::activity::now_millis;
+use crate::bridge::{self, BridgeHandle, SessionSummary};
+
+/// Lines longer than this many octets are folded (RFC 5545 §3.1).
+const MAX_LINE_OCTETS: usize = 75;
+const FILE_NAME: &str = "namefix-sessions.ics";
+
+/// A calendar written to disk, ready to import or subscribe to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarExport {
+    pub path: String,
+    pub sessions: usize,
+}
+
+/// Write every session as an event to `directory`. The file keeps the same
+/// name on each export, so a calendar subscribed to it picks up new sessions.
+pub async fn export_sessions(bridge: &BridgeHandle, directory: &Path) -> Result<CalendarExport, String> {
+    let sessions = bridge::list_sessions(bridge).await?;
+    std::fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+    let path = directory.join(FILE_NAME);
+    std::fs::write(&path, render(&sessions, now_millis())).map_err(|err| err.to_string())?;
+    Ok(CalendarExport { path: path.to_string_lossy().into_owned(), sessions: sessions.len() })
+}
+
+/// One VEVENT per session; a running session ends at `now`.
+fn render(sessions: &[SessionSummary], now: u64) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Namefix//Sessions//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Namefix sessions".to_string(),
+    ];
+    for summary in sessions {
+        let session = &summary.session;
+        let end = session.ended_at.unwrap_or(now).max(session.started_at);
+        let files = summary.files_renamed;
+        let mut description = format!(
+            "{} file{} renamed, {} processed.",
+            files,
+            if files == 1 { "" } else { "s" },
+            bytes(summary.bytes_processed)
+        );
+        if session.ended_at.is_none() {
+            description.push_str(" Still running.");
+        }
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@namefix", text(&session.id)));
+        lines.push(format!("DTSTAMP:{}", utc(now)));
+        lines.push(format!("DTSTART:{}", utc(session.started_at)));
+        lines.push(format!("DTEND:{}", utc(end)));
+        lines.push(format!("SUMMARY:{}", text(&format!("Namefix: {}", session.name))));
+        lines.push(format!("DESCRIPTION:{}", text(&description)));
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// A UTC date-time such as `20240131T093000Z`.
+fn utc(ms: u64) -> String {
+    i64::try_from(ms)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|at| at.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// A TEXT value with its special characters escaped.
+fn text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// `line` with its CRLF ending, split into continuation lines that start
+/// with a space and never break a character apart.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(ch);
+        width += ch.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    let digits = if unit == 0 || value >= 10.0 { 0 } else { 1 };
+    format!("{:.*} {}", digits, value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::RenameSession;
+
+    fn session(name: &str, started_at: u64, ended_at: Option<u64>, files_renamed: u64) -> SessionSummary {
+        SessionSummary {
+            session: RenameSession { id: format!("s-{}", started_at), name: name.to_string(), started_at, ended_at },
+            files_renamed,
+            bytes_processed: 2_500_000,
+        }
+    }
+
+    #[test]
+    fn writes_one_event_per_session() {
+        let now = 1_706_700_000_000;
+        let sessions = [
+            session("Feb camera import, part 1; raw", 1_706_693_400_000, Some(1_706_695_200_000), 1),
+            session("Cleanup", 1_706_698_000_000, None, 240),
+        ];
+        let ics = render(&sessions, now);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("DTSTART:20240131T093000Z\r\nDTEND:20240131T100000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Namefix: Feb camera import\\, part 1\\; raw\r\n"));
+        assert!(ics.contains("DESCRIPTION:1 file renamed\\, 2.5 MB processed.\r\n"));
+        assert!(ics.contains("DTEND:20240131T124000Z\r\nSUMMARY:Namefix: Cleanup\r\n"));
+        assert!(ics.contains("DESCRIPTION:240 files renamed\\, 2.5 MB processed. Still running.\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_on_character_boundaries() {
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+}
@@ -0,0 +1,426 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::bridge::{self, ServiceStatus, UndoResult};
+use crate::pathdisplay::abbreviate_home;
+use crate::pause::{self, PauseDuration, MAX_PAUSE_MINUTES};
+use crate::persist;
+use crate::state::AppState;
+use crate::tray;
+
+/// How long the command line waits for the running app, or a sidecar of its
+/// own, to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+/// A client that doesn't send its command this fast is dropped, so it can't
+/// hold up the next one.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+const COMMANDS: &str = "\
+Commands:
+  status [--json]      Show whether Namefix is watching, and which folders
+  add <dir>            Watch a folder
+  remove <dir>         Stop watching a folder
+  pause [minutes]      Stop watching, for good or for 1 to 10080 minutes (a week)
+  resume               Start watching again
+  undo                 Undo the last rename
+
+Commands go to the running menu bar app. When it isn't running, they go
+to the background service if it is still running, or start it just for
+the command.";
+
+/// A command from the terminal, also the request sent over the socket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum CliCommand {
+    Status { json: bool },
+    Add { directory: String },
+    Remove { directory: String },
+    Pause { minutes: Option<u64> },
+    Resume,
+    Undo,
+    /// Answered without asking anyone.
+    #[serde(skip)]
+    Help,
+}
+
+/// What the running app sends back: text for stdout, or an error for stderr.
+#[derive(Debug, Serialize, Deserialize)]
+struct CliReply {
+    ok: bool,
+    output: String,
+}
+
+impl From<Result<String, String>> for CliReply {
+    fn from(result: Result<String, String>) -> Self {
+        match result {
+            Ok(output) => Self { ok: true, output },
+            Err(output) => Self { ok: false, output },
+        }
+    }
+}
+
+/// Parse the arguments after the program name. `None` means launch the menu
+/// bar app as usual; only the command words, and help, are taken over.
+pub fn parse(args: &[String]) -> Option<Result<CliCommand, String>> {
+    let (name, rest) = args.split_first()?;
+    let command = match (name.as_str(), rest) {
+        ("help" | "--help" | "-h", _) => Ok(CliCommand::Help),
+        ("status", []) => Ok(CliCommand::Status { json: false }),
+        ("status", [flag]) if flag == "--json" => Ok(CliCommand::Status { json: true }),
+        ("add", [directory]) => absolute(directory).map(|directory| CliCommand::Add { directory }),
+        ("remove", [directory]) => absolute(directory).map(|directory| CliCommand::Remove { directory }),
+        ("pause", []) => Ok(CliCommand::Pause { minutes: None }),
+        ("pause", [minutes]) => match minutes.parse::<u64>() {
+            Ok(minutes @ 1..=MAX_PAUSE_MINUTES) => Ok(CliCommand::Pause { minutes: Some(minutes) }),
+            _ => Err(format!("Not a number of minutes from 1 to {}: {}\n\n{}", MAX_PAUSE_MINUTES, minutes, usage())),
+        },
+        ("resume", []) => Ok(CliCommand::Resume),
+        ("undo", []) => Ok(CliCommand::Undo),
+        ("status" | "add" | "remove" | "pause" | "resume" | "undo", _) => Err(usage()),
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Help text, named after however the app's executable was invoked.
+fn usage() -> String {
+    let program = std::env::args_os()
+        .next()
+        .and_then(|arg| Path::new(&arg).file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "namefix_menu_bar".to_string());
+    format!("Usage: {} <command>\n\n{}", program, COMMANDS)
+}
+
+/// `directory` resolved against the working directory, since the app that
+/// receives it runs elsewhere.
+fn absolute(directory: &str) -> Result<String, String> {
+    let path = std::fs::canonicalize(directory)
+        .or_else(|_| std::path::absolute(directory))
+        .map_err(|err| format!("{}: {}", directory, err))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Run `command` and return the process exit code.
+pub fn run(command: Result<CliCommand, String>) -> i32 {
+    let result = command.and_then(|command| match command {
+        CliCommand::Help => Ok(usage()),
+        command => ask_running_app(&command).unwrap_or_else(|| run_standalone(&command)),
+    });
+    match result {
+        Ok(output) => {
+            println!("{}", output);
+            0
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+/// Where the running app listens for commands. Fixed rather than asked of
+/// Tauri, since the command line doesn't start the app.
+fn socket_path() -> PathBuf {
+    match std::env::var("NAMEFIX_SOCKET") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => persist::config_dir().join("cli.sock"),
+    }
+}
+
+/// The running app's reply, or `None` when no app is listening.
+#[cfg(unix)]
+fn ask_running_app(command: &CliCommand) -> Option<Result<String, String>> {
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket_path()).ok()?;
+    let mut exchange = || -> Result<CliReply, String> {
+        stream.set_read_timeout(Some(REPLY_TIMEOUT)).map_err(|err| err.to_string())?;
+        let mut request = serde_json::to_string(command).map_err(|err| err.to_string())?;
+        request.push('\n');
+        stream.write_all(request.as_bytes()).map_err(|err| err.to_string())?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).map_err(|err| err.to_string())?;
+        serde_json::from_str(&line).map_err(|err| format!("Unexpected reply from Namefix: {}", err))
+    };
+    Some(match exchange() {
+        Ok(CliReply { ok: true, output }) => Ok(output),
+        Ok(CliReply { ok: false, output }) => Err(output),
+        Err(err) => Err(format!("Could not talk to the running Namefix: {}", err)),
+    })
+}
+
+#[cfg(not(unix))]
+fn ask_running_app(_command: &CliCommand) -> Option<Result<String, String>> {
+    None
+}
+
+/// Answer commands from the terminal while the app runs. A socket left by
+/// an app that crashed is replaced.
+#[cfg(unix)]
+pub fn serve(app: &AppHandle<Wry>) {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = socket_path();
+    if UnixStream::connect(&path).is_ok() {
+        log::warn!("Another Namefix is answering commands at {}", path.display());
+        return;
+    }
+    let _ = std::fs::remove_file(&path);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            log::warn!("Command line disabled; could not listen at {}: {}", path.display(), err);
+            return;
+        }
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+            let mut line = String::new();
+            if BufReader::new(&stream).read_line(&mut line).is_err() {
+                continue;
+            }
+            let reply = match serde_json::from_str::<CliCommand>(&line) {
+                Ok(command) => CliReply::from(async_runtime::block_on(handle(&app, command))),
+                Err(err) => CliReply { ok: false, output: format!("Unknown command: {}", err) },
+            };
+            let mut writer = &stream;
+            if let Ok(mut reply) = serde_json::to_string(&reply) {
+                reply.push('\n');
+                let _ = writer.write_all(reply.as_bytes());
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn serve(_app: &AppHandle<Wry>) {}
+
+/// Carry out a command in the running app, the way its tray would.
+async fn handle(app: &AppHandle<Wry>, command: CliCommand) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    let bridge = state.bridge.clone();
+    match command {
+        CliCommand::Status { json: true } => {
+            serde_json::to_string_pretty(&bridge::get_status(&bridge).await?).map_err(|err| err.to_string())
+        }
+        CliCommand::Status { json: false } => {
+            let status = bridge::get_status(&bridge).await?;
+            Ok(describe(&tray::status_label(app, &status), &status))
+        }
+        CliCommand::Add { directory } => {
+            if let Some(excluded) = state.exclusions.excluded_component(Path::new(&directory)) {
+                return Err(format!("{} is inside an excluded folder ({})", directory, excluded));
+            }
+            bridge::add_watch_dir(&bridge, directory.clone()).await?;
+            Ok(format!("Watching {}", abbreviate_home(&directory)))
+        }
+        CliCommand::Remove { directory } => {
+            bridge::remove_watch_dir(&bridge, directory.clone()).await?;
+            Ok(format!("Stopped watching {}", abbreviate_home(&directory)))
+        }
+        CliCommand::Pause { minutes: Some(minutes) } => {
+            pause::pause_for(app, &bridge, PauseDuration::Minutes(minutes)).await?;
+            // Requests can come from other clients than `parse`, so bound them here too.
+            Ok(format!("Paused for {}", pause::format_remaining(minutes.min(MAX_PAUSE_MINUTES) * 60_000)))
+        }
+        CliCommand::Pause { minutes: None } => {
+            state.pause.cancel();
+            bridge::toggle_running(&bridge, Some(false)).await?;
+            Ok("Paused".to_string())
+        }
+        CliCommand::Resume => {
+            state.pause.cancel();
+            bridge::toggle_running(&bridge, Some(true)).await?;
+            Ok("Watching".to_string())
+        }
+        CliCommand::Undo => undone(bridge::undo(&bridge).await?),
+        CliCommand::Help => Ok(usage()),
+    }
+}
+
+/// Carry out a command with a background service started just for it, for
+/// when the menu bar app isn't running.
+fn run_standalone(command: &CliCommand) -> Result<String, String> {
+    if matches!(command, CliCommand::Pause { .. } | CliCommand::Resume) {
+        return Err("Namefix isn't running, so there is nothing to pause or resume".to_string());
+    }
+    let mut sidecar = Sidecar::attach().map_or_else(Sidecar::start, Ok)?;
+    let attached = sidecar.child.is_none();
+    let result = match command {
+        CliCommand::Status { json } => sidecar.call::<ServiceStatus>("getStatus", Value::Null).and_then(|status| {
+            if *json {
+                serde_json::to_string_pretty(&status).map_err(|err| err.to_string())
+            } else if !attached {
+                Ok(describe("Status: Not running (the menu bar app is closed)", &status))
+            } else if status.running {
+                Ok(describe("Status: Watching (the menu bar app is closed)", &status))
+            } else {
+                Ok(describe("Status: Paused (the menu bar app is closed)", &status))
+            }
+        }),
+        CliCommand::Add { directory } if attached => sidecar
+            .call::<Vec<String>>("addWatchDir", json!({ "directory": directory }))
+            .map(|_| format!("Watching {}", abbreviate_home(directory))),
+        CliCommand::Add { directory } => sidecar
+            .call::<Vec<String>>("addWatchDir", json!({ "directory": directory }))
+            .map(|_| format!("Will watch {} once Namefix runs", abbreviate_home(directory))),
+        CliCommand::Remove { directory } => sidecar
+            .call::<Vec<String>>("removeWatchDir", json!({ "directory": directory }))
+            .map(|_| format!("Stopped watching {}", abbreviate_home(directory))),
+        CliCommand::Undo => sidecar.call::<UndoResult>("undo", Value::Null).and_then(undone),
+        CliCommand::Pause { .. } | CliCommand::Resume => unreachable!("refused above"),
+        CliCommand::Help => Ok(usage()),
+    };
+    sidecar.stop();
+    result
+}
+
+fn describe(label: &str, status: &ServiceStatus) -> String {
+    let mut lines = vec![label.to_string()];
+    if status.dry_run {
+        lines.push("Dry run: files are not renamed".to_string());
+    }
+    lines.extend(status.directories.iter().map(|directory| format!("  {}", abbreviate_home(directory))));
+    lines.join("\n")
+}
+
+fn undone(result: UndoResult) -> Result<String, String> {
+    if result.ok {
+        Ok("Undid the last rename".to_string())
+    } else {
+        Err(result.reason.unwrap_or_else(|| "Nothing to undo".to_string()))
+    }
+}
+
+/// The service bridge, one request at a time: the service still running
+/// without the app, or one run over stdio for this command.
+struct Sidecar {
+    /// The process this command started, which it stops when done.
+    child: Option<Child>,
+    writer: Box<dyn Write>,
+    reader: BufReader<Box<dyn Read>>,
+    next_id: u64,
+}
+
+impl Sidecar {
+    /// Connect to the service the menu bar app left running, if there is one.
+    #[cfg(unix)]
+    fn attach() -> Option<Self> {
+        let stream = std::os::unix::net::UnixStream::connect(bridge::service_socket_path()).ok()?;
+        stream.set_read_timeout(Some(REPLY_TIMEOUT)).ok()?;
+        let reader: Box<dyn Read> = Box::new(stream.try_clone().ok()?);
+        Some(Self { child: None, writer: Box::new(stream), reader: BufReader::new(reader), next_id: 1 })
+    }
+
+    #[cfg(windows)]
+    fn attach() -> Option<Self> {
+        let pipe = std::fs::OpenOptions::new().read(true).write(true).open(bridge::service_socket_path()).ok()?;
+        let reader: Box<dyn Read> = Box::new(pipe.try_clone().ok()?);
+        Some(Self { child: None, writer: Box::new(pipe), reader: BufReader::new(reader), next_id: 1 })
+    }
+
+    fn start() -> Result<Self, String> {
+        let script = bridge::standalone_bridge_script().ok_or("Could not find the Namefix service script")?;
+        let node = bridge::node_command().map_err(|err| err.to_string())?;
+        let mut child = Command::new(node)
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Could not start the Namefix service: {}", err))?;
+        let stdin = child.stdin.take().ok_or("Service stdin unavailable")?;
+        let stdout: Box<dyn Read> = Box::new(child.stdout.take().ok_or("Service stdout unavailable")?);
+        Ok(Self { child: Some(child), writer: Box::new(stdin), reader: BufReader::new(stdout), next_id: 1 })
+    }
+
+    /// Send `method` and read until its response, skipping the events the
+    /// service emits meanwhile.
+    fn call<T: serde::de::DeserializeOwned>(&mut self, method: &str, params: Value) -> Result<T, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = json!({ "id": id, "method": method, "params": params }).to_string();
+        request.push('\n');
+        self.writer.write_all(request.as_bytes()).map_err(|err| err.to_string())?;
+        self.writer.flush().map_err(|err| err.to_string())?;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line).map_err(|err| err.to_string())? == 0 {
+                return Err("The Namefix service exited".to_string());
+            }
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if message.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(error.as_str().unwrap_or("unknown service error").to_string());
+            }
+            let result = message.get("result").cloned().unwrap_or(Value::Null);
+            return serde_json::from_value(result).map_err(|err| err.to_string());
+        }
+    }
+
+    /// Stop the service if this command started it; one that was already
+    /// running keeps watching.
+    fn stop(mut self) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let _ = self.call::<Value>("shutdown", Value::Null);
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn takes_over_only_command_words() {
+        assert_eq!(parse(&args(&[])), None);
+        assert_eq!(parse(&args(&["--minimized"])), None);
+        assert_eq!(parse(&args(&["--help"])), Some(Ok(CliCommand::Help)));
+        assert_eq!(parse(&args(&["status"])), Some(Ok(CliCommand::Status { json: false })));
+        assert_eq!(parse(&args(&["status", "--json"])), Some(Ok(CliCommand::Status { json: true })));
+        assert_eq!(parse(&args(&["pause", "30"])), Some(Ok(CliCommand::Pause { minutes: Some(30) })));
+        assert!(matches!(parse(&args(&["pause", "soon"])), Some(Err(_))));
+        assert_eq!(parse(&args(&["pause", "10080"])), Some(Ok(CliCommand::Pause { minutes: Some(10_080) })));
+        for minutes in ["0", "10081", "18446744073709551615"] {
+            let error = format!("Not a number of minutes from 1 to 10080: {}\n\n{}", minutes, usage());
+            assert_eq!(parse(&args(&["pause", minutes])), Some(Err(error)));
+        }
+        assert!(matches!(parse(&args(&["undo", "twice"])), Some(Err(text)) if text == usage()));
+
+        let Some(Ok(CliCommand::Add { directory })) = parse(&args(&["add", "."])) else {
+            panic!("add should parse");
+        };
+        assert!(Path::new(&directory).is_absolute());
+    }
+
+    #[test]
+    fn requests_round_trip_over_the_socket() {
+        let command = CliCommand::Remove { directory: "/tmp/inbox".to_string() };
+        let line = serde_json::to_string(&command).unwrap();
+        assert_eq!(line, r#"{"command":"remove","directory":"/tmp/inbox"}"#);
+        assert_eq!(serde_json::from_str::<CliCommand>(&line).unwrap(), command);
+    }
+}
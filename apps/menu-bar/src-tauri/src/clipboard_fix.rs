@@ -0,0 +1,45 @@
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::bridge;
+use crate::entries;
+use crate::notify;
+use crate::state::AppState;
+
+/// Shortcut shown beside the tray item; registered globally on macOS only,
+/// by [`crate::hotkeys::register_all`].
+pub const HOTKEY_LABEL: &str = "Ctrl+Alt+Cmd+V";
+
+/// Rename the filenames, paths or `file://` URLs on the clipboard, one per
+/// line, by the current rules, and put the result back. Lines no rule renames
+/// are kept as they are. A toast says how many changed.
+pub async fn fix_clipboard(app: &AppHandle<Wry>) -> Result<(), String> {
+    let text = app.clipboard().read_text().map_err(|err| format!("Clipboard has no text: {}", err))?;
+    let bridge = app.state::<AppState>().bridge.clone();
+    let mut fixed = 0;
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let proposed = if line.trim().is_empty() {
+            None
+        } else {
+            bridge::propose_name(&bridge, line.to_string()).await?
+        };
+        match proposed {
+            Some(name) => {
+                fixed += 1;
+                lines.push(name);
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+    let message = match fixed {
+        0 => "Nothing on the clipboard matches a rename rule".to_string(),
+        1 if lines.len() == 1 => format!("Copied {}", lines[0]),
+        n => format!("Fixed {} filenames on the clipboard", n),
+    };
+    if fixed > 0 {
+        entries::copy(app, lines.join("\n"))?;
+    }
+    notify::toast(app, message, "info");
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use std::cmp::Ordering;
+
+use icu_collator::{Collator, CollatorOptions, Numeric, Strength};
+
+thread_local! {
+    /// Root-locale collator with numeric ordering, the same rules Finder uses
+    /// to list files: `img2` before `img10`, `éclair` next to `eclair`, and
+    /// case only breaking ties. Per thread because ICU data isn't `Sync`.
+    static COLLATOR: Collator = {
+        let mut options = CollatorOptions::new();
+        options.strength = Some(Strength::Tertiary);
+        options.numeric = Some(Numeric::On);
+        Collator::try_new(&Default::default(), options).expect("compiled collation data is available")
+    };
+}
+
+/// Compare two file names in natural, locale-aware order. Names the collator
+/// considers equal fall back to code point order so sorting stays total.
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    COLLATOR.with(|collator| collator.compare(a, b)).then_with(|| a.cmp(b))
+}
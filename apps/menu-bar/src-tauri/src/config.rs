@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::accessibility::AccessibilitySettings;
+use crate::actions;
+use crate::bridge::{self, BridgeHandle, ServiceStatus};
+use crate::hotkeys;
+use crate::notify;
+use crate::pathdisplay::{abbreviate_home, expand_home};
+use crate::persist::write_atomic;
+use crate::state::AppState;
+use crate::tray::{self, TrayState};
+
+/// Bumped when a field changes meaning; files from a newer build are refused.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Name the tray's Export Settings item gives the file in Downloads.
+pub const EXPORT_FILE_NAME: &str = "namefix-settings.toml";
+
+/// Settings kept in `namefix.toml`, so they outlive the service's own config
+/// and can be carried to another Mac. Folders under the home directory are
+/// written with `~`, so they resolve for a different user name too.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub version: u32,
+    pub watch: WatchConfig,
+    pub notifications: NotificationConfig,
+    /// Accelerators by palette action id.
+    pub shortcuts: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WatchConfig {
+    pub directories: Vec<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// A system notification for each rename.
+    pub system: bool,
+    /// Routine toasts held for a periodic summary.
+    pub minimal: bool,
+}
+
+/// What an import changed, for the window to report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigImport {
+    pub directories: usize,
+    /// Folders missing on this Mac and shortcuts for unknown actions, left out.
+    pub skipped: Vec<String>,
+}
+
+/// `namefix.toml` in the app's config directory, rewritten whenever one of
+/// the settings in it changes.
+pub struct ConfigStore {
+    path: PathBuf,
+    saved: Mutex<Option<AppConfig>>,
+    /// Set once the service's folders were checked against the saved ones;
+    /// until then a freshly replaced service's empty status would wipe them.
+    restored: AtomicBool,
+}
+
+impl ConfigStore {
+    pub fn load(path: PathBuf) -> Self {
+        let saved = match read(&path) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                if path.exists() {
+                    log::warn!("Ignoring {}: {}", path.display(), err);
+                }
+                None
+            }
+        };
+        Self { path, saved: Mutex::new(saved), restored: AtomicBool::new(false) }
+    }
+
+    fn saved(&self) -> Option<AppConfig> {
+        self.saved.lock().expect("config lock poisoned").clone()
+    }
+
+    fn save(&self, config: AppConfig) -> Result<(), String> {
+        let mut saved = self.saved.lock().expect("config lock poisoned");
+        if saved.as_ref() == Some(&config) {
+            return Ok(());
+        }
+        write_atomic(&self.path, render(&config)?.as_bytes())?;
+        *saved = Some(config);
+        Ok(())
+    }
+}
+
+/// Rewrite `namefix.toml` after a settings change, if anything in it changed.
+pub fn persist(app: &AppHandle<Wry>) {
+    let state = app.state::<AppState>();
+    if !state.config.restored.load(Ordering::Acquire) {
+        return;
+    }
+    let Some(status) = state.tray().map(TrayState::status) else {
+        return;
+    };
+    if let Err(err) = state.config.save(snapshot(&state, &status)) {
+        log::warn!("Could not save namefix.toml: {}", err);
+    }
+}
+
+/// Fetch the service's status at startup. When it watches nothing but
+/// `namefix.toml` lists folders, as after its own config was lost or the
+/// service replaced, the folders and dry run are brought back first.
+pub async fn restore(app: &AppHandle<Wry>) -> Result<ServiceStatus, String> {
+    let state = app.state::<AppState>();
+    let result = restore_from_saved(&state).await;
+    state.config.restored.store(true, Ordering::Release);
+    result
+}
+
+async fn restore_from_saved(state: &AppState) -> Result<ServiceStatus, String> {
+    let status = bridge::get_status(&state.bridge).await?;
+    let Some(saved) = state.config.saved().filter(|saved| !saved.watch.directories.is_empty()) else {
+        return Ok(status);
+    };
+    if !status.directories.is_empty() {
+        return Ok(status);
+    }
+    let missing = apply_watch(&state.bridge, &saved.watch, &status).await?;
+    log::info!("Restored watched folders from namefix.toml");
+    if !missing.is_empty() {
+        log::warn!("Saved folders no longer exist: {}", missing.join(", "));
+    }
+    bridge::get_status(&state.bridge).await
+}
+
+/// Write the current settings to `path`.
+pub async fn export(app: &AppHandle<Wry>, path: &Path) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let status = bridge::get_status(&state.bridge).await?;
+    write_atomic(path, render(&snapshot(&state, &status))?.as_bytes())
+}
+
+/// Replace the current settings with those in `path`. Shortcuts are checked
+/// first, so a file with a conflict changes nothing.
+pub async fn import(app: &AppHandle<Wry>, path: &Path) -> Result<ConfigImport, String> {
+    let config = read(path)?;
+    let state = app.state::<AppState>();
+    let known = actions::list(app).await?;
+    let (shortcuts, unknown): (BTreeMap<_, _>, BTreeMap<_, _>) =
+        config.shortcuts.into_iter().partition(|(action, _)| known.iter().any(|known| &known.id == action));
+    let shortcuts = state.shortcuts.replace(&shortcuts)?;
+    hotkeys::register_all(app);
+    tray::apply_shortcuts(app);
+    let _ = app.emit("ui://shortcuts", &shortcuts);
+
+    state.system_notifications.set_enabled(config.notifications.system)?;
+    let accessibility = AccessibilitySettings {
+        minimal_notifications: config.notifications.minimal,
+        ..state.accessibility.get()
+    };
+    let accessibility = state.accessibility.set(accessibility)?;
+    if !accessibility.minimal_notifications {
+        notify::flush(app);
+    }
+    let _ = app.emit("ui://accessibility", accessibility);
+
+    let status = bridge::get_status(&state.bridge).await?;
+    let wanted: Vec<String> =
+        config.watch.directories.iter().map(|dir| expand_home(dir).to_string_lossy().into_owned()).collect();
+    for directory in status.directories.iter().filter(|dir| !wanted.contains(dir)) {
+        bridge::remove_watch_dir(&state.bridge, directory.clone()).await?;
+    }
+    let mut skipped = apply_watch(&state.bridge, &config.watch, &status).await?;
+    skipped.extend(unknown.into_keys().map(|action| format!("shortcut for {}", action)));
+
+    let status = bridge::get_status(&state.bridge).await?;
+    let _ = app.emit("service://status", &status);
+    Ok(ConfigImport { directories: status.directories.len(), skipped })
+}
+
+/// Watch the folders in `watch` that exist here and match its dry run,
+/// returning the folders that don't exist.
+async fn apply_watch(
+    bridge: &BridgeHandle,
+    watch: &WatchConfig,
+    status: &ServiceStatus,
+) -> Result<Vec<String>, String> {
+    let mut missing = Vec::new();
+    for directory in &watch.directories {
+        let path = expand_home(directory);
+        if !path.is_dir() {
+            missing.push(directory.clone());
+            continue;
+        }
+        let path = path.to_string_lossy().into_owned();
+        if !status.directories.contains(&path) {
+            bridge::add_watch_dir(bridge, path).await?;
+        }
+    }
+    if status.dry_run != watch.dry_run {
+        bridge::set_dry_run(bridge, watch.dry_run).await?;
+    }
+    Ok(missing)
+}
+
+/// The settings in effect, with the watched folders from `status`.
+fn snapshot(state: &AppState, status: &ServiceStatus) -> AppConfig {
+    AppConfig {
+        version: CONFIG_VERSION,
+        watch: WatchConfig {
+            directories: status.directories.iter().map(|dir| abbreviate_home(dir)).collect(),
+            dry_run: status.dry_run,
+        },
+        notifications: NotificationConfig {
+            system: state.system_notifications.enabled(),
+            minimal: state.accessibility.get().minimal_notifications,
+        },
+        shortcuts: state.shortcuts.all(),
+    }
+}
+
+fn read(path: &Path) -> Result<AppConfig, String> {
+    let raw = std::fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    parse(&raw)
+}
+
+fn parse(raw: &str) -> Result<AppConfig, String> {
+    let config: AppConfig = toml::from_str(raw).map_err(|err| format!("Not valid settings: {}", err.message()))?;
+    match config.version {
+        0 => Err("Not a Namefix settings file: it has no version".to_string()),
+        version if version > CONFIG_VERSION => {
+            Err(format!("These settings are from a newer Namefix (version {}); update this one first", version))
+        }
+        _ => Ok(config),
+    }
+}
+
+fn render(config: &AppConfig) -> Result<String, String> {
+    let body = toml::to_string_pretty(config).map_err(|err| err.to_string())?;
+    Ok(format!("# Namefix settings. Import them under Move Settings to Another Mac.\n\n{}", body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let config = AppConfig {
+            version: CONFIG_VERSION,
+            watch: WatchConfig { directories: vec!["~/Downloads".to_string()], dry_run: true },
+            notifications: NotificationConfig { system: true, minimal: false },
+            shortcuts: BTreeMap::from([("toggle-running".to_string(), "Cmd+Shift+R".to_string())]),
+        };
+        let rendered = render(&config).unwrap();
+        assert!(rendered.contains("dry-run = true"));
+        assert!(rendered.contains("toggle-running = \"Cmd+Shift+R\""));
+        assert_eq!(parse(&rendered).unwrap(), config);
+    }
+
+    #[test]
+    fn refuses_files_it_cannot_read() {
+        assert!(parse("[watch]\ndirectories = []\n").unwrap_err().contains("no version"));
+        assert!(parse("version = 99\n").unwrap_err().contains("newer Namefix"));
+        assert!(parse("version = \"one\"\n").is_err());
+        let sparse = parse("version = 1\n").unwrap();
+        assert_eq!(sparse.watch, WatchConfig::default());
+    }
+}
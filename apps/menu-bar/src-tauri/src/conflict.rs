@@ -0,0 +1,68 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// What the conflict view shows about one side of a name collision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileFacts {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    /// Epoch milliseconds; absent where the filesystem doesn't record it.
+    pub created: Option<u64>,
+    pub modified: Option<u64>,
+    pub sha256: String,
+}
+
+/// Two files that wanted the same name, side by side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictComparison {
+    pub incoming: FileFacts,
+    pub existing: FileFacts,
+    /// Same contents, so the incoming file is a duplicate rather than a clash.
+    pub identical: bool,
+}
+
+pub fn compare(incoming: &Path, existing: &Path) -> Result<ConflictComparison, String> {
+    let incoming = facts(incoming)?;
+    let existing = facts(existing)?;
+    let identical = incoming.size == existing.size && incoming.sha256 == existing.sha256;
+    Ok(ConflictComparison { incoming, existing, identical })
+}
+
+fn facts(path: &Path) -> Result<FileFacts, String> {
+    let metadata = fs::metadata(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    Ok(FileFacts {
+        path: path.to_string_lossy().into_owned(),
+        name: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+        size: metadata.len(),
+        created: metadata.created().ok().and_then(epoch_millis),
+        modified: metadata.modified().ok().and_then(epoch_millis),
+        sha256: sha256(path).map_err(|err| format!("{}: {}", path.display(), err))?,
+    })
+}
+
+fn sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn epoch_millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|elapsed| elapsed.as_millis() as u64)
+}
@@ -0,0 +1,492 @@
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::activity::{now_millis, ActivityEntry, ActivityKind, ActivityWindow};
+use crate::issues::Issue;
+use crate::pathdisplay::abbreviate_home;
+use crate::persist::{load_json, save_json};
+use crate::push;
+use crate::state::AppState;
+
+/// How often the schedule is checked; a digest goes out within this of being due.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+const DEFAULT_SMTP_PORT: u16 = 587;
+/// Failures listed one by one; the rest are only counted.
+pub const MAX_LISTED_FAILURES: usize = 10;
+/// The login keychain item holding the SMTP password, on macOS.
+const KEYCHAIN_SERVICE: &str = "namefix-email-digest";
+/// `security` exits with this when the keychain has no such item.
+const ITEM_NOT_FOUND: i32 = 44;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DigestFrequency {
+    Daily,
+    #[default]
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn period_ms(self) -> u64 {
+        match self {
+            Self::Daily => DAY_MS,
+            Self::Weekly => 7 * DAY_MS,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+}
+
+/// Where and how often the digest is mailed. The password is not part of
+/// it; see [`EmailDigest::password`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailDigestSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub frequency: DigestFrequency,
+    #[serde(default)]
+    pub to: String,
+    /// Sender address; the recipient's when empty.
+    #[serde(default)]
+    pub from: String,
+    /// SMTP server host. Port 465 is spoken to over TLS, any other must offer STARTTLS.
+    #[serde(default)]
+    pub server: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username: String,
+    /// Start of the period the next digest covers, in epoch milliseconds.
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+fn default_smtp_port() -> u16 {
+    DEFAULT_SMTP_PORT
+}
+
+impl Default for EmailDigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frequency: DigestFrequency::default(),
+            to: String::new(),
+            from: String::new(),
+            server: String::new(),
+            port: DEFAULT_SMTP_PORT,
+            username: String::new(),
+            since: None,
+        }
+    }
+}
+
+impl EmailDigestSettings {
+    fn validate(&self) -> Result<(), String> {
+        if !self.to.contains('@') {
+            return Err("Enter the address to send the digest to".to_string());
+        }
+        if self.server.trim().is_empty() || self.port == 0 {
+            return Err("Enter the SMTP server and port to send through".to_string());
+        }
+        Ok(())
+    }
+
+    fn sender(&self) -> &str {
+        if self.from.trim().is_empty() {
+            &self.to
+        } else {
+            &self.from
+        }
+    }
+}
+
+/// What the settings window shows: the settings, and whether a password is saved.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailDigestView {
+    #[serde(flatten)]
+    pub settings: EmailDigestSettings,
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+}
+
+/// The optional email digest for Macs nobody sits at: what Namefix did over
+/// the last day or week, mailed through the user's own SMTP server.
+pub struct EmailDigest {
+    path: PathBuf,
+    /// Where the password is kept when there is no keychain.
+    secret_path: PathBuf,
+    settings: Mutex<EmailDigestSettings>,
+}
+
+impl EmailDigest {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        let secret_path = path.with_extension("secret");
+        Self { path, secret_path, settings: Mutex::new(settings) }
+    }
+
+    pub fn get(&self) -> EmailDigestSettings {
+        self.settings.lock().expect("email digest lock poisoned").clone()
+    }
+
+    pub fn view(&self) -> EmailDigestView {
+        let settings = self.get();
+        let has_password = !settings.username.is_empty()
+            && self.password(&settings.username).is_ok_and(|password| password.is_some());
+        EmailDigestView { settings, has_password }
+    }
+
+    /// Keep `settings`, and `password` when one is given. Turning the digest
+    /// on starts its first period now.
+    pub fn set(&self, mut settings: EmailDigestSettings, password: Option<&str>) -> Result<EmailDigestView, String> {
+        if settings.enabled {
+            settings.validate()?;
+        }
+        if let Some(password) = password.filter(|password| !password.is_empty()) {
+            if settings.username.is_empty() {
+                return Err("Enter the SMTP user name the password is for".to_string());
+            }
+            self.save_password(&settings.username, password)?;
+        }
+        {
+            let mut current = self.settings.lock().expect("email digest lock poisoned");
+            settings.since = match (settings.enabled, current.since) {
+                (true, Some(since)) if current.enabled => Some(since),
+                (true, _) => Some(now_millis()),
+                (false, _) => None,
+            };
+            save_json(&self.path, &settings)?;
+            *current = settings;
+        }
+        Ok(self.view())
+    }
+
+    fn mark_sent(&self, at: u64) -> Result<(), String> {
+        let mut settings = self.settings.lock().expect("email digest lock poisoned");
+        let mut updated = settings.clone();
+        updated.since = Some(at);
+        save_json(&self.path, &updated)?;
+        *settings = updated;
+        Ok(())
+    }
+
+    /// The saved SMTP password for `account`: in the login keychain on
+    /// macOS, through the `security` tool. Elsewhere there is no keychain to
+    /// use, so it is kept in a file only the user can read.
+    fn password(&self, account: &str) -> Result<Option<String>, String> {
+        if !cfg!(target_os = "macos") {
+            return match std::fs::read_to_string(&self.secret_path) {
+                Ok(password) => Ok(Some(password.trim_end_matches('\n').to_string())),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.to_string()),
+            };
+        }
+        let output = Command::new("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-a", account, "-w"])
+            .output()
+            .map_err(|err| err.to_string())?;
+        match output.status.code() {
+            Some(0) => Ok(Some(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string())),
+            Some(ITEM_NOT_FOUND) => Ok(None),
+            _ => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        }
+    }
+
+    fn save_password(&self, account: &str, password: &str) -> Result<(), String> {
+        if !cfg!(target_os = "macos") {
+            return write_private(&self.secret_path, password);
+        }
+        if password.contains(['\n', '\r']) || account.contains(['\n', '\r']) {
+            return Err("The password and account can't contain line breaks".to_string());
+        }
+        // The command goes to `security -i` on stdin, so the password never
+        // shows up in the process list the way an argument would.
+        let command = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            security_quote(KEYCHAIN_SERVICE),
+            security_quote(account),
+            security_quote(password)
+        );
+        let mut child = Command::new("security")
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| err.to_string())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(command.as_bytes()).map_err(|err| err.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|err| err.to_string())?;
+        // Interactive mode exits cleanly whatever its commands did; failures show on stderr.
+        let error = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if output.status.success() && error.is_empty() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+}
+
+/// `text` as one argument of a `security -i` command line.
+fn security_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn write_private(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|err| err.to_string())?;
+    file.write_all(contents.as_bytes()).map_err(|err| err.to_string())
+}
+
+/// Mail the digest whenever one is due. Runs for the life of the app.
+pub async fn run(app: AppHandle<Wry>) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let settings = app.state::<AppState>().email_digest.get();
+        let period = settings.frequency.period_ms();
+        let due = settings.since.is_some_and(|since| now_millis().saturating_sub(since) >= period);
+        if settings.enabled && due {
+            if let Err(err) = send(&app).await {
+                log::warn!("Could not send the email digest: {}", err);
+            }
+        }
+    }
+}
+
+/// Mail a digest of everything since the last one now, and start the next period.
+pub async fn send(app: &AppHandle<Wry>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let settings = state.email_digest.get();
+    settings.validate()?;
+    let password = match settings.username.as_str() {
+        "" => None,
+        account => state.email_digest.password(account)?,
+    };
+    let now = now_millis();
+    let since = settings.since.unwrap_or_else(|| now.saturating_sub(settings.frequency.period_ms()));
+    let summary = Summary::collect(&state, since);
+    let message = compose(&settings, &summary, since, now);
+
+    let message_path = std::env::temp_dir().join(format!("namefix-digest-{}.eml", std::process::id()));
+    std::fs::write(&message_path, message).map_err(|err| err.to_string())?;
+    let result = push::curl(&curl_config(&settings, password.as_deref(), &message_path)).await;
+    let _ = std::fs::remove_file(&message_path);
+    result.map_err(|err| format!("Sending the digest failed: {}", err))?;
+    state.email_digest.mark_sent(now)
+}
+
+/// What happened in one digest period, also posted by [`crate::webhook`].
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub renamed: usize,
+    pub converted: usize,
+    pub trashed: usize,
+    /// Renames shown in dry run or observer mode and never applied.
+    pub previewed: usize,
+    pub failures: Vec<ActivityEntry>,
+    /// Open problems in the Error Center.
+    pub issues: Vec<Issue>,
+}
+
+impl Summary {
+    /// Everything in the activity log since `since`, and the open issues.
+    pub fn collect(state: &AppState, since: u64) -> Self {
+        let window = ActivityWindow { since: Some(since), until: None, limit: None };
+        Self::new(&state.activity.query(&window), state.issues.list(state))
+    }
+
+    fn new(activity: &[ActivityEntry], issues: Vec<Issue>) -> Self {
+        let mut summary = Self { issues, ..Self::default() };
+        for entry in activity {
+            match entry.kind {
+                ActivityKind::Renamed => summary.renamed += 1,
+                ActivityKind::Converted => summary.converted += 1,
+                ActivityKind::Trashed => summary.trashed += 1,
+                ActivityKind::Preview => summary.previewed += 1,
+                ActivityKind::Error => summary.failures.push(entry.clone()),
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+/// The digest as an RFC 5322 message with a plain-text body.
+fn compose(settings: &EmailDigestSettings, summary: &Summary, since: u64, now: u64) -> String {
+    let frequency = settings.frequency.label();
+    let mut subject = format!("Namefix {} digest: {} renamed", frequency, summary.renamed);
+    if !summary.failures.is_empty() {
+        subject.push_str(&format!(", {} failed", summary.failures.len()));
+    }
+
+    let mut body = vec![
+        format!("What Namefix did from {} to {}.", day(since), day(now)),
+        String::new(),
+        format!("Renamed: {}", summary.renamed),
+        format!("Converted: {}", summary.converted),
+        format!("Moved to Trash: {}", summary.trashed),
+        format!("Previewed but not applied: {}", summary.previewed),
+        format!("Failed: {}", summary.failures.len()),
+    ];
+    if !summary.failures.is_empty() {
+        body.extend([String::new(), "Failures".to_string()]);
+        for failure in summary.failures.iter().rev().take(MAX_LISTED_FAILURES) {
+            body.push(format!("- {}", describe_failure(failure)));
+        }
+        if summary.failures.len() > MAX_LISTED_FAILURES {
+            body.push(format!("- and {} more", summary.failures.len() - MAX_LISTED_FAILURES));
+        }
+    }
+    if !summary.issues.is_empty() {
+        body.extend([String::new(), "Waiting for you in the Error Center".to_string()]);
+        for issue in &summary.issues {
+            let place = issue.directory.as_deref().map(|dir| format!(" ({})", abbreviate_home(dir)));
+            body.push(format!("- {}{}", issue.message, place.unwrap_or_default()));
+        }
+    }
+    body.extend([
+        String::new(),
+        "Counts start from when Namefix last launched if that was more recent.".to_string(),
+    ]);
+
+    let headers = [
+        format!("From: Namefix <{}>", settings.sender()),
+        format!("To: {}", settings.to),
+        format!("Subject: {}", subject),
+        format!("Date: {}", chrono::Local::now().to_rfc2822()),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+        "Content-Transfer-Encoding: 8bit".to_string(),
+    ];
+    format!("{}\r\n\r\n{}\r\n", headers.join("\r\n"), body.join("\r\n"))
+}
+
+/// A failed rename as one line: the file, its folder and the reason.
+pub fn describe_failure(failure: &ActivityEntry) -> String {
+    let file = failure.file.as_deref().unwrap_or("A file");
+    let place = failure.directory.as_deref().map(|dir| format!(" in {}", abbreviate_home(dir)));
+    let reason = failure.message.as_deref().map(|message| format!(": {}", message));
+    format!("{}{}{}", file, place.unwrap_or_default(), reason.unwrap_or_default())
+}
+
+fn day(millis: u64) -> String {
+    i64::try_from(millis)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| time.with_timezone(&chrono::Local).format("%a %-d %b %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// The SMTP request as a curl config for [`push::curl`].
+fn curl_config(settings: &EmailDigestSettings, password: Option<&str>, message: &Path) -> String {
+    let server = settings.server.trim();
+    let mut config = String::new();
+    if settings.port == 465 {
+        config.push_str(&push::config_line("url", &format!("smtps://{}:{}", server, settings.port)));
+    } else {
+        config.push_str(&push::config_line("url", &format!("smtp://{}:{}", server, settings.port)));
+        // Never send the password or the digest unencrypted.
+        config.push_str("ssl-reqd\n");
+    }
+    config.push_str(&push::config_line("mail-from", settings.sender()));
+    config.push_str(&push::config_line("mail-rcpt", &settings.to));
+    if !settings.username.is_empty() {
+        let credentials = format!("{}:{}", settings.username, password.unwrap_or_default());
+        config.push_str(&push::config_line("user", &credentials));
+    }
+    config.push_str(&push::config_line("upload-file", &message.to_string_lossy()));
+    config.push_str(&push::config_line("max-time", "60"));
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> EmailDigestSettings {
+        EmailDigestSettings {
+            enabled: true,
+            to: "me@example.com".to_string(),
+            server: "smtp.example.com".to_string(),
+            username: "me".to_string(),
+            ..EmailDigestSettings::default()
+        }
+    }
+
+    fn failure(file: &str) -> ActivityEntry {
+        ActivityEntry {
+            file: Some(file.to_string()),
+            directory: Some("/srv/inbox".to_string()),
+            message: Some("Permission denied".to_string()),
+            ..ActivityEntry::now(ActivityKind::Error)
+        }
+    }
+
+    #[test]
+    fn summarizes_the_period() {
+        let activity = vec![
+            ActivityEntry::now(ActivityKind::Renamed),
+            ActivityEntry::now(ActivityKind::Renamed),
+            ActivityEntry::now(ActivityKind::Preview),
+            failure("scan.pdf"),
+            ActivityEntry::now(ActivityKind::Paused),
+        ];
+        let summary = Summary::new(&activity, Vec::new());
+        let message = compose(&settings(), &summary, 0, 0);
+
+        assert!(message.contains("Subject: Namefix weekly digest: 2 renamed, 1 failed\r\n"));
+        assert!(message.contains("From: Namefix <me@example.com>\r\n"));
+        assert!(message.contains("Previewed but not applied: 1\r\n"));
+        assert!(message.contains("- scan.pdf in /srv/inbox: Permission denied\r\n"));
+        assert!(!message.contains("Error Center"));
+    }
+
+    #[test]
+    fn requires_tls_and_keeps_the_password_in_config() {
+        let config = curl_config(&settings(), Some("p\"w"), Path::new("/tmp/digest.eml"));
+        assert!(config.contains("url = \"smtp://smtp.example.com:587\"\n"));
+        assert!(config.contains("ssl-reqd\n"));
+        assert!(config.contains("user = \"me:p\\\"w\"\n"));
+
+        let implicit = EmailDigestSettings { port: 465, ..settings() };
+        let config = curl_config(&implicit, None, Path::new("/tmp/digest.eml"));
+        assert!(config.contains("url = \"smtps://smtp.example.com:465\"\n"));
+        assert!(!config.contains("ssl-reqd"));
+    }
+
+    #[test]
+    fn validates_before_enabling() {
+        assert!(EmailDigestSettings { to: "nobody".to_string(), ..settings() }.validate().is_err());
+        assert!(EmailDigestSettings { server: " ".to_string(), ..settings() }.validate().is_err());
+        assert!(settings().validate().is_ok());
+    }
+
+    #[test]
+    fn keychain_arguments_are_quoted_whole() {
+        assert_eq!(security_quote("hunter 2"), r#""hunter 2""#);
+        assert_eq!(security_quote(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+}
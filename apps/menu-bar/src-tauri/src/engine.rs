@@ -0,0 +1,156 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use serde_json::Value;
+
+pub type EngineFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// What [`crate::bridge::BridgeHandle::invoke`] calls into: the Node
+/// sidecar, or with the `native-engine` feature the in-process
+/// `namefix-core` engine. Methods and params are the service's JSON
+/// protocol, so the typed helpers in `bridge.rs` work with either.
+pub trait Engine: Send + Sync {
+    fn call<'a>(&'a self, method: &'a str, params: Value) -> EngineFuture<'a, Result<Value, String>>;
+
+    /// Whether the engine has stopped answering.
+    fn is_disconnected(&self) -> bool;
+
+    /// Stop the engine for good.
+    fn shutdown(&self) -> EngineFuture<'_, ()>;
+
+    /// Let go of the engine as the app quits.
+    fn close(&self) -> EngineFuture<'_, ()> {
+        self.shutdown()
+    }
+}
+
+#[cfg(feature = "native-engine")]
+pub use native::NativeEngine;
+
+#[cfg(feature = "native-engine")]
+mod native {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::Value;
+    use tauri::async_runtime;
+    use tokio::sync::broadcast;
+
+    use super::{Engine, EngineFuture};
+    use crate::bridge::BridgeEvent;
+    use crate::status::ServiceStatus;
+
+    /// The `namefix-core` engine, running in this process. It watches,
+    /// renames by the rules it can apply, and undoes; any other method fails
+    /// with an error saying so rather than doing nothing.
+    pub struct NativeEngine {
+        core: namefix_core::Engine,
+        events: broadcast::Sender<BridgeEvent>,
+    }
+
+    impl NativeEngine {
+        /// Start the engine on the Node service's config and journal.
+        pub fn start() -> Result<Self, String> {
+            let (events, _) = broadcast::channel(256);
+            let sender = events.clone();
+            let sink: namefix_core::EventSink = Box::new(move |name, payload| {
+                let _ = sender.send(BridgeEvent { name: name.to_string(), payload });
+            });
+            let config = namefix_core::Config::load(&namefix_core::paths::config_dir().join("config.json"))
+                .map_err(|err| err.to_string())?;
+            let paths = namefix_core::Paths::default_locations(config.data_dir.as_deref());
+            let core = namefix_core::Engine::start(paths, sink)?;
+            Ok(Self { core, events })
+        }
+
+        pub fn subscribe(&self) -> broadcast::Receiver<BridgeEvent> {
+            self.events.subscribe()
+        }
+
+        fn dispatch(core: &namefix_core::Engine, method: &str, params: Value) -> Result<Value, String> {
+            match method {
+                "getStatus" => to_value(status(core.status())),
+                "toggleRunning" => to_value(status(core.set_running(param(&params, "desired")?)?)),
+                "setDryRun" => to_value(status(core.set_dry_run(required(&params, "enabled")?)?)),
+                "listDirectories" => to_value(core.watch_dirs()),
+                "addWatchDir" => to_value(core.add_watch_dir(&required::<String>(&params, "directory")?)?),
+                "removeWatchDir" => to_value(core.remove_watch_dir(&required::<String>(&params, "directory")?)?),
+                "getProfiles" => to_value(core.profiles()),
+                "toggleProfile" => {
+                    let id: String = required(&params, "id")?;
+                    to_value(core.toggle_profile(&id, param(&params, "enabled")?)?)
+                }
+                "undo" => to_value(core.undo()),
+                "redo" => to_value(core.redo()),
+                "shutdown" => Ok(Value::Null),
+                _ => Err(format!("{} is not available with the native engine", method)),
+            }
+        }
+    }
+
+    impl Engine for NativeEngine {
+        fn call<'a>(&'a self, method: &'a str, params: Value) -> EngineFuture<'a, Result<Value, String>> {
+            let core = self.core.clone();
+            let method = method.to_string();
+            Box::pin(async move {
+                // Renames and undo touch the disk; keep them off the async workers.
+                async_runtime::spawn_blocking(move || Self::dispatch(&core, &method, params))
+                    .await
+                    .map_err(|err| err.to_string())?
+            })
+        }
+
+        fn is_disconnected(&self) -> bool {
+            false
+        }
+
+        fn shutdown(&self) -> EngineFuture<'_, ()> {
+            let core = self.core.clone();
+            Box::pin(async move {
+                let _ = async_runtime::spawn_blocking(move || core.set_running(Some(false))).await;
+            })
+        }
+    }
+
+    /// The engine's status in the shape the app reads from the Node service.
+    fn status(status: namefix_core::Status) -> ServiceStatus {
+        ServiceStatus {
+            running: status.running,
+            directories: status.directories,
+            dry_run: status.dry_run,
+            can_redo: status.can_redo,
+            watch_backend: "notify".to_string(),
+            ..ServiceStatus::default()
+        }
+    }
+
+    fn param<T: DeserializeOwned>(params: &Value, key: &str) -> Result<Option<T>, String> {
+        match params.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value.clone()).map(Some).map_err(|err| format!("{}: {}", key, err)),
+        }
+    }
+
+    fn required<T: DeserializeOwned>(params: &Value, key: &str) -> Result<T, String> {
+        param(params, key)?.ok_or_else(|| format!("missing {}", key))
+    }
+
+    fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+        serde_json::to_value(value).map_err(|err| err.to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        #[test]
+        fn params_are_read_like_the_node_service_reads_them() {
+            let params = json!({ "desired": false, "directory": "/tmp", "enabled": null });
+            assert_eq!(param::<bool>(&params, "desired"), Ok(Some(false)));
+            assert_eq!(param::<bool>(&params, "enabled"), Ok(None));
+            assert_eq!(required::<String>(&params, "directory"), Ok("/tmp".to_string()));
+            assert_eq!(required::<String>(&Value::Null, "id"), Err("missing id".to_string()));
+            assert!(param::<bool>(&params, "directory").is_err());
+        }
+    }
+}
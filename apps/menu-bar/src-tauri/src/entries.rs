@@ -0,0 +1,272 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::bridge::{self, BridgeHandle, HistoryFilters};
+use crate::persist::{load_json, save_json};
+
+/// Renames searched for the history entry behind a recent rename.
+const UNDO_SEARCH_LIMIT: usize = 20;
+
+/// What clicking a rename entry does, in the tray and in the app window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryClickAction {
+    #[default]
+    Reveal,
+    Open,
+    CopyNewPath,
+    Undo,
+}
+
+impl EntryClickAction {
+    /// Menu label for the action.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Reveal => "Reveal in Folder",
+            Self::Open => "Open File",
+            Self::CopyNewPath => "Copy New Path",
+            Self::Undo => "Undo Rename",
+        }
+    }
+}
+
+/// A completed rename: where the file was and where it ended up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenameEntry {
+    pub from: String,
+    pub to: String,
+}
+
+impl RenameEntry {
+    /// The rename an activity entry describes, if it describes one.
+    pub fn from_activity(entry: &ActivityEntry) -> Option<Self> {
+        if !matches!(entry.kind, ActivityKind::Renamed | ActivityKind::Converted) {
+            return None;
+        }
+        let directory = Path::new(entry.directory.as_deref()?);
+        Some(Self {
+            from: directory.join(entry.file.as_deref()?).to_string_lossy().into_owned(),
+            to: directory.join(entry.target.as_deref()?).to_string_lossy().into_owned(),
+        })
+    }
+
+    /// File name before the rename.
+    pub fn old_name(&self) -> String {
+        Path::new(&self.from)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.from.clone())
+    }
+}
+
+/// The user's chosen click action, persisted next to the other menu bar settings.
+pub struct EntryActionStore {
+    path: PathBuf,
+    action: Mutex<EntryClickAction>,
+}
+
+impl EntryActionStore {
+    pub fn load(path: PathBuf) -> Self {
+        let action = load_json(&path);
+        Self { path, action: Mutex::new(action) }
+    }
+
+    pub fn get(&self) -> EntryClickAction {
+        *self.action.lock().expect("entry action lock poisoned")
+    }
+
+    pub fn set(&self, action: EntryClickAction) -> Result<EntryClickAction, String> {
+        save_json(&self.path, &action)?;
+        *self.action.lock().expect("entry action lock poisoned") = action;
+        Ok(action)
+    }
+}
+
+pub async fn perform(
+    app: &AppHandle<Wry>,
+    bridge: &BridgeHandle,
+    action: EntryClickAction,
+    entry: &RenameEntry,
+) -> Result<(), String> {
+    match action {
+        EntryClickAction::Reveal => reveal(Path::new(&entry.to)),
+        EntryClickAction::Open => open(Path::new(&entry.to)),
+        EntryClickAction::CopyNewPath => copy(app, entry.to.clone()),
+        EntryClickAction::Undo => undo(bridge, entry).await,
+    }
+}
+
+pub fn copy(app: &AppHandle<Wry>, text: String) -> Result<(), String> {
+    app.clipboard().write_text(text).map_err(|err| err.to_string())
+}
+
+/// Undo this rename only, found in history by where the file ended up.
+pub async fn undo(bridge: &BridgeHandle, entry: &RenameEntry) -> Result<(), String> {
+    let target = Path::new(&entry.to);
+    let filters = HistoryFilters {
+        directory: target.parent().map(|dir| dir.to_string_lossy().into_owned()),
+        limit: Some(UNDO_SEARCH_LIMIT),
+        ..HistoryFilters::default()
+    };
+    let name = target.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let matches = bridge::search_history(bridge, name, filters).await?;
+    let Some(id) = matches.into_iter().find(|found| found.to == entry.to).and_then(|found| found.id) else {
+        return Err("That rename is no longer in history".to_string());
+    };
+    let result = bridge::undo_entry(bridge, id).await?;
+    if result.ok {
+        Ok(())
+    } else {
+        Err(result.reason.unwrap_or_else(|| "Undo failed".to_string()))
+    }
+}
+
+pub fn reveal(path: &Path) -> Result<(), String> {
+    ensure_exists(path)?;
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let command = {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(path));
+        command
+    };
+    spawn(command)
+}
+
+fn open(path: &Path) -> Result<(), String> {
+    ensure_exists(path)?;
+    #[cfg(target_os = "macos")]
+    let command = {
+        let mut command = Command::new("open");
+        command.arg(path);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(path);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(path);
+        command
+    };
+    spawn(command)
+}
+
+/// Show `path` in the Quick Look panel. `qlmanage -p` drives the same panel
+/// Finder uses, without linking Quick Look into the app.
+pub fn quick_look(path: &Path) -> Result<(), String> {
+    ensure_exists(path)?;
+    if !cfg!(target_os = "macos") {
+        return Err("Quick Look is only available on macOS".to_string());
+    }
+    let mut command = Command::new("qlmanage");
+    command.arg("-p").arg(path).stdout(Stdio::null()).stderr(Stdio::null());
+    spawn(command)
+}
+
+/// An application that can open a given file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenWithApp {
+    pub name: String,
+    pub path: String,
+}
+
+/// Applications registered to open `path`, per LaunchServices. Empty on other
+/// platforms, which have no equivalent registry to ask.
+pub fn open_with_apps(path: &Path) -> Vec<OpenWithApp> {
+    let mut apps: Vec<OpenWithApp> = registered_apps(path)
+        .into_iter()
+        .map(|app| OpenWithApp {
+            name: app.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: app.to_string_lossy().into_owned(),
+        })
+        .collect();
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps.dedup_by(|a, b| a.path == b.path);
+    apps
+}
+
+/// Open `path` with the application at `app`.
+pub fn open_with(path: &Path, app: &Path) -> Result<(), String> {
+    ensure_exists(path)?;
+    if !cfg!(target_os = "macos") {
+        return Err("Open With is only available on macOS".to_string());
+    }
+    let mut command = Command::new("open");
+    command.arg("-a").arg(app).arg(path);
+    spawn(command)
+}
+
+#[cfg(target_os = "macos")]
+fn registered_apps(path: &Path) -> Vec<PathBuf> {
+    launch_services::applications_for(path)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn registered_apps(_path: &Path) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+mod launch_services {
+    use std::path::{Path, PathBuf};
+
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::TCFType;
+    use core_foundation::url::{CFURLRef, CFURL};
+
+    /// `kLSRolesAll`: viewers, editors, and shell handlers alike.
+    const ROLES_ALL: u32 = 0xFFFF_FFFF;
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn LSCopyApplicationURLsForURL(url: CFURLRef, roles: u32) -> CFArrayRef;
+    }
+
+    pub fn applications_for(path: &Path) -> Vec<PathBuf> {
+        let Some(url) = CFURL::from_path(path, false) else {
+            return Vec::new();
+        };
+        // SAFETY: `url` is a valid CFURL for the duration of the call, and the
+        // returned array (if any) is owned by us under the create rule.
+        let array = unsafe { LSCopyApplicationURLsForURL(url.as_concrete_TypeRef(), ROLES_ALL) };
+        if array.is_null() {
+            return Vec::new();
+        }
+        let array: CFArray<CFURL> = unsafe { CFArray::wrap_under_create_rule(array) };
+        array.iter().filter_map(|app| app.to_path()).collect()
+    }
+}
+
+fn ensure_exists(path: &Path) -> Result<(), String> {
+    if path.exists() {
+        Ok(())
+    } else {
+        Err(format!("{} no longer exists", path.display()))
+    }
+}
+
+fn spawn(mut command: Command) -> Result<(), String> {
+    command.spawn().map(|_| ()).map_err(|err| err.to_string())
+}
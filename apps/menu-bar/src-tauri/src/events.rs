@@ -0,0 +1,553 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::activity::{self, ActivityEntry, ActivityKind};
+use crate::bridge::BridgeEvent;
+
+/// Kinds kept in the database; previews, conversions and pauses stay in the
+/// in-memory activity log only.
+const LOGGED: [ActivityKind; 4] =
+    [ActivityKind::Renamed, ActivityKind::Skipped, ActivityKind::Error, ActivityKind::Undone];
+/// Older events are dropped when the app starts.
+const MAX_EVENTS: u64 = 100_000;
+const DEFAULT_LIMIT: usize = 200;
+const MAX_LIMIT: usize = 1_000;
+const WEEK_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+/// Days `EventStats::by_day` reaches back.
+const STATS_DAYS: u64 = 30;
+const DB_FILE: &str = "events.sqlite3";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        timestamp INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        directory TEXT,
+        file TEXT,
+        target TEXT,
+        message TEXT
+    );
+    CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp);
+";
+
+/// Which events `query_events` returns. Empty fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    pub kinds: Vec<ActivityKind>,
+    /// Matched, ignoring case, against the file names, folder and message.
+    pub text: Option<String>,
+    pub directory: Option<String>,
+}
+
+/// Bounds for `query_events`, in epoch milliseconds; missing bounds are open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventRange {
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+/// An event as stored, with the row id it was given.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggedEvent {
+    pub id: i64,
+    #[serde(flatten)]
+    pub entry: ActivityEntry,
+}
+
+/// A number of renames for one folder, extension or day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tally {
+    pub key: String,
+    pub count: u64,
+}
+
+/// Totals over the stored events, for `get_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStats {
+    pub total_renames: u64,
+    /// In the last 7 days.
+    pub renames_this_week: u64,
+    /// Most first; then by name.
+    pub by_directory: Vec<Tally>,
+    /// By the renamed file's extension, lowercase and without the dot;
+    /// most first.
+    pub by_extension: Vec<Tally>,
+    /// By local date as `YYYY-MM-DD`, over the last 30 days, oldest first.
+    /// Days without renames are left out.
+    pub by_day: Vec<Tally>,
+    pub errors: u64,
+    /// Undo actions, whether of one rename or a whole session.
+    pub undos: u64,
+    /// When the oldest stored event happened, so the totals can say since when.
+    pub since: Option<u64>,
+}
+
+/// Where the event log is kept: in the service's data directory when the
+/// user moved it (`data_dir`), otherwise in the app's data directory.
+pub fn db_path(app: &AppHandle, data_dir: Option<&str>) -> tauri::Result<PathBuf> {
+    match data_dir {
+        Some(dir) => Ok(Path::new(dir).join(DB_FILE)),
+        None => Ok(app.path().app_data_dir()?.join(DB_FILE)),
+    }
+}
+
+/// Every rename, skip, error and undo, kept in `events.sqlite3` with the
+/// rest of the history (see [`db_path`]) so it outlives the app. Without the
+/// database, as when it can't be opened, events are simply not kept.
+pub struct EventLog {
+    db: Mutex<Option<Connection>>,
+    /// Where `db` was opened.
+    path: Mutex<PathBuf>,
+}
+
+impl EventLog {
+    pub fn open(path: &Path) -> Self {
+        Self { db: Mutex::new(open_db(path)), path: Mutex::new(path.to_path_buf()) }
+    }
+
+    /// Keep events at `path` from now on, bringing the ones stored so far
+    /// along; any already at `path` are kept too. If they can't be moved,
+    /// they stay where they are.
+    pub fn move_to(&self, path: &Path) {
+        let mut current = self.path.lock().expect("event log lock poisoned");
+        if *current == path {
+            return;
+        }
+        let mut db = self.db.lock().expect("event log lock poisoned");
+        // Closing checkpoints the write-ahead log, so the file holds every event.
+        *db = None;
+        match connect(path).and_then(|moved| merge(&moved, &current).map(|_| moved)) {
+            Ok(moved) => {
+                log::info!("Event log moved to {}", path.display());
+                remove_db(&current);
+                *db = Some(moved);
+                *current = path.to_path_buf();
+            }
+            Err(err) => {
+                log::warn!("Could not move the event log to {}: {}", path.display(), err);
+                *db = open_db(&current);
+            }
+        }
+    }
+
+    /// Store the events among a bridge event's.
+    pub fn observe(&self, event: &BridgeEvent) {
+        let entry = match event.name.as_str() {
+            "file" => activity::file_entry(&event.payload).filter(|entry| LOGGED.contains(&entry.kind)),
+            "undo" => activity::undo_entry(&event.payload),
+            _ => None,
+        };
+        if let Some(entry) = entry {
+            self.append(&entry);
+        }
+    }
+
+    fn append(&self, entry: &ActivityEntry) {
+        let db = self.db.lock().expect("event log lock poisoned");
+        let Some(db) = db.as_ref() else {
+            return;
+        };
+        let inserted = db.execute(
+            "INSERT INTO events (timestamp, kind, directory, file, target, message) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                entry.timestamp as i64,
+                kind_name(entry.kind),
+                entry.directory,
+                entry.file,
+                entry.target,
+                entry.message
+            ],
+        );
+        if let Err(err) = inserted {
+            log::warn!("Could not store event: {}", err);
+        }
+    }
+
+    /// Events matching `filter` within `range`, newest first, at most
+    /// `limit` (200 by default, 1000 at most).
+    pub fn query(
+        &self,
+        filter: &EventFilter,
+        range: &EventRange,
+        limit: Option<usize>,
+    ) -> Result<Vec<LoggedEvent>, String> {
+        let db = self.db.lock().expect("event log lock poisoned");
+        let Some(db) = db.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if !filter.kinds.is_empty() {
+            conditions.push(format!("kind IN ({})", vec!["?"; filter.kinds.len()].join(", ")));
+            values.extend(filter.kinds.iter().map(|kind| SqlValue::Text(kind_name(*kind))));
+        }
+        if let Some(text) = filter.text.as_deref().map(str::trim).filter(|text| !text.is_empty()) {
+            let columns = ["file", "target", "directory", "message"];
+            let matches: Vec<String> = columns.iter().map(|column| format!("{} LIKE ? ESCAPE '\\'", column)).collect();
+            conditions.push(format!("({})", matches.join(" OR ")));
+            let pattern = format!("%{}%", escape_like(text));
+            values.extend(columns.map(|_| SqlValue::Text(pattern.clone())));
+        }
+        if let Some(directory) = filter.directory.as_deref().filter(|directory| !directory.is_empty()) {
+            conditions.push("directory = ?".to_string());
+            values.push(SqlValue::Text(directory.to_string()));
+        }
+        if let Some(since) = range.since {
+            conditions.push("timestamp >= ?".to_string());
+            values.push(SqlValue::Integer(since as i64));
+        }
+        if let Some(until) = range.until {
+            conditions.push("timestamp <= ?".to_string());
+            values.push(SqlValue::Integer(until as i64));
+        }
+        let clause = if conditions.is_empty() { String::new() } else { format!("WHERE {}", conditions.join(" AND ")) };
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+        let sql = format!(
+            "SELECT id, timestamp, kind, directory, file, target, message FROM events {} \
+             ORDER BY timestamp DESC, id DESC LIMIT {}",
+            clause, limit
+        );
+        let mut statement = db.prepare(&sql).map_err(|err| err.to_string())?;
+        let rows = statement
+            .query_map(params_from_iter(values), read_event)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| err.to_string())?;
+        // Rows of a kind this build doesn't know are left out rather than failing the query.
+        Ok(rows.into_iter().flatten().collect())
+    }
+
+    /// Totals over every stored event, as of `now`, counted by the database.
+    pub fn stats(&self, now: u64) -> Result<EventStats, String> {
+        let db = self.db.lock().expect("event log lock poisoned");
+        let Some(db) = db.as_ref() else {
+            return Ok(EventStats::default());
+        };
+        tally(db, now).map_err(|err| err.to_string())
+    }
+
+    /// Files renamed in the 7 days before `now`, for the tray.
+    pub fn renamed_this_week(&self, now: u64) -> u64 {
+        let db = self.db.lock().expect("event log lock poisoned");
+        let Some(db) = db.as_ref() else {
+            return 0;
+        };
+        db.query_row(
+            "SELECT COUNT(*) FROM events WHERE kind = ?1 AND timestamp >= ?2",
+            params![kind_name(ActivityKind::Renamed), now.saturating_sub(WEEK_MS) as i64],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count.max(0) as u64)
+        .unwrap_or(0)
+    }
+
+    /// Delete every stored event, returning how many there were.
+    pub fn clear(&self) -> Result<usize, String> {
+        let db = self.db.lock().expect("event log lock poisoned");
+        let Some(db) = db.as_ref() else {
+            return Ok(0);
+        };
+        db.execute("DELETE FROM events", []).map_err(|err| err.to_string())
+    }
+}
+
+fn open_db(path: &Path) -> Option<Connection> {
+    match connect(path) {
+        Ok(db) => Some(db),
+        Err(err) => {
+            log::warn!("Event log disabled; could not open {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn connect(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(parent) = path.parent() {
+        // A missing folder shows up as the open error below.
+        let _ = std::fs::create_dir_all(parent);
+    }
+    prepare(Connection::open(path)?)
+}
+
+/// Create the table and drop all but the newest events.
+fn prepare(db: Connection) -> rusqlite::Result<Connection> {
+    db.create_scalar_function("extension", 1, FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC, |ctx| {
+        Ok(ctx.get::<Option<String>>(0)?.as_deref().map(extension).unwrap_or_default())
+    })?;
+    db.pragma_update(None, "journal_mode", "WAL")?;
+    db.pragma_update(None, "synchronous", "NORMAL")?;
+    db.execute_batch(SCHEMA)?;
+    db.execute(
+        "DELETE FROM events WHERE id <= (SELECT id FROM events ORDER BY id DESC LIMIT 1 OFFSET ?1)",
+        [MAX_EVENTS as i64],
+    )?;
+    Ok(db)
+}
+
+/// Copy the events stored at `from`, if any, into `db`.
+fn merge(db: &Connection, from: &Path) -> rusqlite::Result<()> {
+    if !from.exists() {
+        return Ok(());
+    }
+    db.execute("ATTACH DATABASE ?1 AS moved", [from.to_string_lossy()])?;
+    let copied = db.execute_batch(
+        "INSERT INTO events (timestamp, kind, directory, file, target, message) \
+         SELECT timestamp, kind, directory, file, target, message FROM moved.events ORDER BY id",
+    );
+    db.execute_batch("DETACH DATABASE moved")?;
+    copied
+}
+
+/// Delete the database at `path` and its write-ahead log files.
+fn remove_db(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = OsString::from(path);
+        file.push(suffix);
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+fn read_event(row: &Row) -> rusqlite::Result<Option<LoggedEvent>> {
+    let kind: String = row.get(2)?;
+    let Ok(kind) = serde_json::from_value::<ActivityKind>(Value::String(kind)) else {
+        return Ok(None);
+    };
+    Ok(Some(LoggedEvent {
+        id: row.get(0)?,
+        entry: ActivityEntry {
+            timestamp: row.get::<_, i64>(1)?.max(0) as u64,
+            kind,
+            directory: row.get(3)?,
+            file: row.get(4)?,
+            target: row.get(5)?,
+            message: row.get(6)?,
+        },
+    }))
+}
+
+/// Count renames, errors and undos, and tally renames by folder, extension
+/// and day, in the database rather than by loading every row.
+fn tally(db: &Connection, now: u64) -> rusqlite::Result<EventStats> {
+    let renamed = kind_name(ActivityKind::Renamed);
+    let mut stats = db.query_row(
+        "SELECT COUNT(*) FILTER (WHERE kind = ?1), COUNT(*) FILTER (WHERE kind = ?1 AND timestamp >= ?2), \
+         COUNT(*) FILTER (WHERE kind = ?3), COUNT(*) FILTER (WHERE kind = ?4), MIN(timestamp) FROM events",
+        params![
+            renamed,
+            now.saturating_sub(WEEK_MS) as i64,
+            kind_name(ActivityKind::Error),
+            kind_name(ActivityKind::Undone)
+        ],
+        |row| {
+            Ok(EventStats {
+                total_renames: count(row.get(0)?),
+                renames_this_week: count(row.get(1)?),
+                errors: count(row.get(2)?),
+                undos: count(row.get(3)?),
+                since: row.get::<_, Option<i64>>(4)?.map(count),
+                ..EventStats::default()
+            })
+        },
+    )?;
+    stats.by_directory = tallies(
+        db,
+        "SELECT directory AS key, COUNT(*) AS count FROM events WHERE kind = ?1 AND directory IS NOT NULL \
+         GROUP BY key ORDER BY count DESC, key",
+        params![renamed],
+    )?;
+    stats.by_extension = tallies(
+        db,
+        "SELECT extension(target) AS key, COUNT(*) AS count FROM events WHERE kind = ?1 \
+         GROUP BY key ORDER BY count DESC, key",
+        params![renamed],
+    )?;
+    let first_day = local_date(now.saturating_sub((STATS_DAYS - 1) * 24 * 60 * 60 * 1000));
+    stats.by_day = tallies(
+        db,
+        "SELECT date(timestamp / 1000, 'unixepoch', 'localtime') AS key, COUNT(*) FROM events WHERE kind = ?1 \
+         GROUP BY key HAVING key >= ?2 ORDER BY key",
+        params![renamed, first_day],
+    )?;
+    Ok(stats)
+}
+
+fn tallies(db: &Connection, sql: &str, params: impl rusqlite::Params) -> rusqlite::Result<Vec<Tally>> {
+    let mut statement = db.prepare(sql)?;
+    let rows = statement.query_map(params, |row| Ok(Tally { key: row.get(0)?, count: count(row.get(1)?) }))?;
+    rows.collect()
+}
+
+fn count(value: i64) -> u64 {
+    value.max(0) as u64
+}
+
+/// A renamed file's extension as `by_extension` keys it: lowercase, without
+/// the dot, and empty when it has none.
+fn extension(target: &str) -> String {
+    Path::new(target).extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+fn local_date(ms: u64) -> String {
+    i64::try_from(ms)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|at| at.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// The tray's line for `count` renames in the last 7 days.
+pub fn week_summary(count: u64) -> String {
+    match count {
+        0 => "No files renamed this week".to_string(),
+        1 => "1 file renamed this week".to_string(),
+        count => format!("{} files renamed this week", count),
+    }
+}
+
+/// The name `kind` is stored under, the same as in JSON.
+fn kind_name(kind: ActivityKind) -> String {
+    serde_json::to_value(kind).ok().and_then(|value| value.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn log() -> EventLog {
+        EventLog {
+            db: Mutex::new(Some(prepare(Connection::open_in_memory().unwrap()).unwrap())),
+            path: Mutex::new(PathBuf::new()),
+        }
+    }
+
+    fn file(kind: &str, file: &str, timestamp: u64) -> BridgeEvent {
+        BridgeEvent {
+            name: "file".to_string(),
+            payload: json!({
+                "kind": kind,
+                "file": file,
+                "target": format!("renamed_{}", file),
+                "directory": "/Users/me/Desktop",
+                "timestamp": timestamp,
+            }),
+        }
+    }
+
+    #[test]
+    fn keeps_renames_skips_errors_and_undos() {
+        let log = log();
+        log.observe(&file("applied", "Screenshot 1.png", 1_000));
+        log.observe(&file("preview", "Screenshot 2.png", 2_000));
+        log.observe(&file("skipped", "notes_100%.txt", 3_000));
+        log.observe(&file("error", "locked.png", 4_000));
+        log.observe(&BridgeEvent {
+            name: "undo".to_string(),
+            payload: json!({ "scope": "session", "undone": 3, "failed": 1, "timestamp": 5_000 }),
+        });
+
+        let all = log.query(&EventFilter::default(), &EventRange::default(), None).unwrap();
+        let kinds: Vec<ActivityKind> = all.iter().map(|event| event.entry.kind).collect();
+        assert_eq!(kinds, [ActivityKind::Undone, ActivityKind::Error, ActivityKind::Skipped, ActivityKind::Renamed]);
+        assert_eq!(
+            all[0].entry.message.as_deref(),
+            Some("Undid 3 renames of a session; 1 rename could not be undone")
+        );
+
+        let search = |filter: EventFilter, range: EventRange| {
+            log.query(&filter, &range, None).unwrap().into_iter().map(|event| event.entry.timestamp).collect::<Vec<_>>()
+        };
+        let text = |text: &str| EventFilter { text: Some(text.to_string()), ..EventFilter::default() };
+        assert_eq!(search(text("SCREENSHOT"), EventRange::default()), [1_000]);
+        assert_eq!(search(text("0%."), EventRange::default()), [3_000]);
+        assert!(search(text("t_1"), EventRange::default()).is_empty());
+        let errors = EventFilter { kinds: vec![ActivityKind::Error, ActivityKind::Skipped], ..EventFilter::default() };
+        assert_eq!(search(errors, EventRange { since: Some(3_500), until: None }), [4_000]);
+        assert_eq!(log.query(&EventFilter::default(), &EventRange::default(), Some(2)).unwrap().len(), 2);
+
+        assert_eq!(log.renamed_this_week(1_000 + WEEK_MS - 1), 1);
+        assert_eq!(log.renamed_this_week(1_000 + WEEK_MS + 1), 0);
+
+        assert_eq!(log.clear().unwrap(), 4);
+        assert!(log.query(&EventFilter::default(), &EventRange::default(), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn tallies_renames_by_folder_extension_and_day() {
+        let day = 24 * 60 * 60 * 1000;
+        let now = 40 * day;
+        let log = log();
+        let add = |days_ago: u64, kind: &str, directory: Option<&str>, target: &str| {
+            let db = log.db.lock().unwrap();
+            db.as_ref()
+                .unwrap()
+                .execute(
+                    "INSERT INTO events (timestamp, kind, directory, target) VALUES (?1, ?2, ?3, ?4)",
+                    params![(now - days_ago * day) as i64, kind, directory, target],
+                )
+                .unwrap();
+        };
+        add(35, "renamed", Some("/Desktop"), "old.PNG");
+        add(3, "renamed", Some("/Desktop"), "shot.png");
+        add(3, "renamed", Some("/Downloads"), "report.pdf");
+        add(1, "renamed", Some("/Downloads"), "Makefile");
+        add(1, "skipped", Some("/Desktop"), "notes.txt");
+        add(1, "error", Some("/Desktop"), "locked.png");
+        add(0, "undone", None, "");
+        let stats = log.stats(now).unwrap();
+        assert_eq!(stats.total_renames, 4);
+        assert_eq!(stats.renames_this_week, 3);
+        assert_eq!((stats.errors, stats.undos), (1, 1));
+        assert_eq!(stats.since, Some(now - 35 * day));
+        let keys = |tallies: &[Tally]| tallies.iter().map(|t| (t.key.clone(), t.count)).collect::<Vec<_>>();
+        assert_eq!(keys(&stats.by_directory), [("/Desktop".to_string(), 2), ("/Downloads".to_string(), 2)]);
+        assert_eq!(
+            keys(&stats.by_extension),
+            [("png".to_string(), 2), (String::new(), 1), ("pdf".to_string(), 1)]
+        );
+        assert_eq!(stats.by_day.len(), 2);
+        assert!(stats.by_day[0].key < stats.by_day[1].key);
+        assert_eq!(stats.by_day.iter().map(|t| t.count).sum::<u64>(), 3);
+        assert_eq!(log.stats(now).unwrap().by_day[0].key, local_date(now - 3 * day));
+
+        assert_eq!(week_summary(0), "No files renamed this week");
+        assert_eq!(week_summary(142), "142 files renamed this week");
+    }
+
+    #[test]
+    fn moving_brings_the_events_along() {
+        let root = std::env::temp_dir().join(format!("namefix-events-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (from, to) = (root.join("app").join(DB_FILE), root.join("moved").join(DB_FILE));
+        EventLog::open(&to).observe(&file("applied", "there.png", 1_000));
+
+        let log = EventLog::open(&from);
+        log.observe(&file("applied", "here.png", 2_000));
+        log.move_to(&to);
+        log.observe(&file("applied", "after.png", 3_000));
+        assert!(!from.exists());
+        let files = |log: &EventLog| {
+            let events = log.query(&EventFilter::default(), &EventRange::default(), None).unwrap();
+            events.into_iter().filter_map(|event| event.entry.file).collect::<Vec<_>>()
+        };
+        assert_eq!(files(&log), ["after.png", "here.png", "there.png"]);
+        drop(log);
+        assert_eq!(files(&EventLog::open(&to)), ["after.png", "here.png", "there.png"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
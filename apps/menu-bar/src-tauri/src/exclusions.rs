@@ -0,0 +1,102 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::bridge;
+use crate::persist::{load_json, save_json};
+use crate::state::AppState;
+
+/// Folder names that are never worth watching: package caches, VCS metadata,
+/// scratch space, and archive-extraction debris.
+pub const BUILTIN_EXCLUSIONS: &[&str] = &["node_modules", ".git", ".tmp", "__MACOSX"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchExclusions {
+    pub builtin: Vec<String>,
+    pub custom: Vec<String>,
+}
+
+/// Folder names excluded from watching. Built-in names are always applied;
+/// user additions are persisted next to the other menu bar settings.
+pub struct ExclusionStore {
+    path: PathBuf,
+    custom: Mutex<Vec<String>>,
+}
+
+impl ExclusionStore {
+    pub fn load(path: PathBuf) -> Self {
+        let custom = load_json(&path);
+        Self { path, custom: Mutex::new(custom) }
+    }
+
+    pub fn get(&self) -> WatchExclusions {
+        WatchExclusions {
+            builtin: BUILTIN_EXCLUSIONS.iter().map(|name| name.to_string()).collect(),
+            custom: self.custom.lock().expect("exclusions lock poisoned").clone(),
+        }
+    }
+
+    /// Every excluded folder name, built-in first.
+    pub fn names(&self) -> Vec<String> {
+        let exclusions = self.get();
+        let mut names = exclusions.builtin;
+        for name in exclusions.custom {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    pub fn set_custom(&self, names: Vec<String>) -> Result<WatchExclusions, String> {
+        let mut cleaned: Vec<String> = Vec::new();
+        for name in names {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                continue;
+            }
+            if name.contains('/') || name.contains('\\') || name == "." || name == ".." {
+                return Err(format!("'{}' is not a folder name", name));
+            }
+            if !cleaned.contains(&name) {
+                cleaned.push(name);
+            }
+        }
+        save_json(&self.path, &cleaned)?;
+        *self.custom.lock().expect("exclusions lock poisoned") = cleaned;
+        Ok(self.get())
+    }
+
+    /// The excluded folder name `path` lives under, if any.
+    pub fn excluded_component(&self, path: &Path) -> Option<String> {
+        let names = self.names();
+        path.components().find_map(|component| match component {
+            Component::Normal(part) => part.to_str().filter(|part| names.iter().any(|n| n == part)).map(str::to_string),
+            _ => None,
+        })
+    }
+}
+
+/// Push the exclusion list to the engine once it has finished starting up.
+pub async fn sync_to_bridge(app: AppHandle<Wry>) {
+    const ATTEMPTS: u32 = 20;
+    for attempt in 1..=ATTEMPTS {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let (bridge, names) = (state.bridge.clone(), state.exclusions.names());
+        match bridge::set_watch_exclusions(&bridge, names).await {
+            Ok(_) => return,
+            Err(err) if err.contains("not ready") && attempt < ATTEMPTS => {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            Err(err) => {
+                log::warn!("Failed to push watch exclusions: {}", err);
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::cell::Cell;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const MAX_OPERATIONS: u64 = 10_000;
+const MAX_CALL_LEVELS: usize = 16;
+const MAX_EXPR_DEPTH: usize = 32;
+const MAX_STRING_SIZE: usize = 4096;
+const MAX_COLLECTION_SIZE: usize = 256;
+/// Wall-clock budget per evaluation, for scripts that stay under the
+/// operation limit but spend long in each operation.
+const MAX_DURATION: Duration = Duration::from_millis(100);
+const TEMPLATE_TOKEN: &str = "<expr:";
+
+/// File facts exposed to expressions as variables (`name`, `stem`, `ext`,
+/// `directory`, `size`, `metadata`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpressionContext {
+    pub name: String,
+    #[serde(default)]
+    pub directory: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpressionMode {
+    /// A rule condition; must evaluate to a boolean.
+    Condition,
+    /// A template containing `<expr:...>` segments.
+    Template,
+}
+
+thread_local! {
+    /// When the evaluation running on this thread has to stop.
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Sandboxed expression evaluator used by rule conditions and templates.
+///
+/// Scripts cannot import modules, call `eval`, or touch the filesystem, and
+/// every evaluation is bounded by operation, depth, size, and time limits.
+pub struct ExpressionEngine {
+    engine: Engine,
+    max_duration: Duration,
+}
+
+impl ExpressionEngine {
+    pub fn new() -> Self {
+        Self::with_limits(MAX_OPERATIONS, MAX_DURATION)
+    }
+
+    fn with_limits(max_operations: u64, max_duration: Duration) -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(max_operations)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_EXPR_DEPTH)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_COLLECTION_SIZE)
+            .set_max_map_size(MAX_COLLECTION_SIZE)
+            .set_allow_looping(false)
+            .disable_symbol("eval");
+        engine.on_print(|text| log::debug!("expr print: {}", text));
+        engine.on_debug(|text, _, pos| log::debug!("expr debug {:?}: {}", pos, text));
+        engine.on_progress(|_| {
+            let expired = DEADLINE.get().is_some_and(|deadline| Instant::now() >= deadline);
+            expired.then_some(Dynamic::UNIT)
+        });
+        Self { engine, max_duration }
+    }
+
+    pub fn eval(&self, expression: &str, context: &ExpressionContext) -> Result<Value, String> {
+        let mut scope = build_scope(context)?;
+        DEADLINE.set(Some(Instant::now() + self.max_duration));
+        let result = self.engine.eval_expression_with_scope::<Dynamic>(&mut scope, expression);
+        DEADLINE.set(None);
+        let result = result.map_err(|err| match err.unwrap_inner() {
+            EvalAltResult::ErrorTerminated(..) => format!("expression ran longer than {:?}", self.max_duration),
+            _ => err.to_string(),
+        })?;
+        rhai::serde::from_dynamic::<Value>(&result).map_err(|err| err.to_string())
+    }
+
+    pub fn eval_condition(&self, expression: &str, context: &ExpressionContext) -> Result<bool, String> {
+        match self.eval(expression, context)? {
+            Value::Bool(flag) => Ok(flag),
+            other => Err(format!("condition must evaluate to a boolean, got {other}")),
+        }
+    }
+
+    /// Render every `<expr:...>` segment of `template`, leaving other template
+    /// variables untouched for the rename service. Comparisons using `>` must
+    /// be parenthesised so they are not read as the end of the segment.
+    pub fn render_template(&self, template: &str, context: &ExpressionContext) -> Result<String, String> {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find(TEMPLATE_TOKEN) {
+            output.push_str(&rest[..start]);
+            let body_start = start + TEMPLATE_TOKEN.len();
+            let end = segment_end(&rest[body_start..])
+                .ok_or_else(|| format!("unterminated <expr:...> at offset {}", template.len() - rest.len() + start))?;
+            let expression = &rest[body_start..body_start + end];
+            match self.eval(expression, context)? {
+                Value::String(text) => output.push_str(&text),
+                Value::Null => {}
+                other => output.push_str(&other.to_string()),
+            }
+            rest = &rest[body_start + end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    pub fn preview(&self, mode: ExpressionMode, input: &str, context: &ExpressionContext) -> Result<Value, String> {
+        match mode {
+            ExpressionMode::Condition => self.eval_condition(input, context).map(Value::Bool),
+            ExpressionMode::Template => self.render_template(input, context).map(Value::String),
+        }
+    }
+}
+
+fn build_scope(context: &ExpressionContext) -> Result<Scope<'static>, String> {
+    let path = Path::new(&context.name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(&context.name).to_string();
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|ext| format!(".{ext}"))
+        .unwrap_or_default();
+    let metadata = rhai::serde::to_dynamic(&context.metadata).map_err(|err| err.to_string())?;
+
+    let mut scope = Scope::new();
+    scope.push_constant("name", context.name.clone());
+    scope.push_constant("stem", stem);
+    scope.push_constant("ext", ext);
+    scope.push_constant("directory", context.directory.clone());
+    scope.push_constant("size", context.size as i64);
+    scope.push_constant_dynamic("metadata", metadata);
+    Ok(scope)
+}
+
+/// Byte offset of the `>` closing an `<expr:` segment, skipping brackets and
+/// string literals.
+fn segment_end(body: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (idx, ch) in body.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '"' | '\'' | '`' => quote = Some(ch),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            '>' if depth == 0 => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 200-item array, for scripts that do a lot without loops.
+    fn items() -> String {
+        format!("\"{}\".split(\"\")", "x".repeat(200))
+    }
+
+    fn context() -> ExpressionContext {
+        ExpressionContext { name: "Invoice 12.pdf".to_string(), size: 2048, ..ExpressionContext::default() }
+    }
+
+    #[test]
+    fn conditions_and_templates_see_the_file() {
+        let engine = ExpressionEngine::new();
+        assert_eq!(engine.eval_condition("size > 1024 && ext == \".pdf\"", &context()), Ok(true));
+        assert!(engine.eval_condition("size", &context()).is_err());
+        let template = "<date>_<expr:stem.to_upper()>_<expr:(size > 1024)>";
+        assert_eq!(engine.render_template(template, &context()), Ok("<date>_INVOICE 12_true".to_string()));
+    }
+
+    #[test]
+    fn runaway_scripts_hit_the_operation_limit() {
+        // Rhai counts a closure's operations apart from its caller's, so a
+        // long script rather than a nested one; the time limit covers those.
+        let sum = format!("({})", ["size"; 20].join(" + "));
+        let array = format!("[{}].len()", [sum.as_str(); 200].join(", "));
+        let script = [array.as_str(); 4].join(" + ");
+        let roomy = ExpressionEngine::with_limits(MAX_OPERATIONS * 10, Duration::from_secs(60));
+        assert_eq!(roomy.eval(&script, &context()), Ok(Value::from(800)));
+        let engine = ExpressionEngine::with_limits(MAX_OPERATIONS, Duration::from_secs(60));
+        assert!(engine.eval(&script, &context()).unwrap_err().starts_with("Too many operations"));
+    }
+
+    #[test]
+    fn runaway_scripts_hit_the_string_size_limit() {
+        let err = ExpressionEngine::new().eval(&format!("{}.reduce(|s, x| s + s, \"ab\")", items()), &context());
+        assert!(err.unwrap_err().contains("Length of string"));
+    }
+
+    #[test]
+    fn runaway_scripts_hit_the_time_limit() {
+        let items = items();
+        let engine = ExpressionEngine::with_limits(0, Duration::from_millis(20));
+        let started = Instant::now();
+        let script = format!("{items}.map(|a| {items}.map(|b| {items}.map(|c| c).len()))");
+        assert_eq!(engine.eval(&script, &context()), Err("expression ran longer than 20ms".to_string()));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::bridge;
+use crate::notify;
+use crate::state::AppState;
+
+/// What became of the paths in one drop, by display name.
+#[derive(Debug, Default)]
+struct DropOutcome {
+    added: Vec<String>,
+    already_watched: Vec<String>,
+    /// Paths that can't be watched, with the reason.
+    rejected: Vec<(String, String)>,
+}
+
+impl DropOutcome {
+    /// One toast for the whole drop: what was added, or else why nothing was.
+    fn message(&self) -> Option<(String, &'static str)> {
+        let skipped = self.already_watched.len() + self.rejected.len();
+        let level = if self.rejected.is_empty() { "info" } else { "warn" };
+        if !self.added.is_empty() {
+            let mut message = match self.added.as_slice() {
+                [name] => format!("Watching {}", name),
+                added => format!("Watching {} folders", added.len()),
+            };
+            if skipped > 0 {
+                message.push_str(&format!("; skipped {}", skipped));
+            }
+            return Some((message, level));
+        }
+        if let Some((name, reason)) = self.rejected.first() {
+            let mut message = format!("Can't watch {}: {}", name, reason);
+            if skipped > 1 {
+                message.push_str(&format!(" (and {} more skipped)", skipped - 1));
+            }
+            return Some((message, level));
+        }
+        match self.already_watched.as_slice() {
+            [] => None,
+            [name] => Some((format!("Already watching {}", name), level)),
+            _ => Some(("Already watching those folders".to_string(), level)),
+        }
+    }
+}
+
+/// Watch the folders dropped on the preferences window or, on macOS, the
+/// app's icon. Files and folders already watched are skipped, and a single
+/// toast reports the result.
+pub fn add_dropped(app: &AppHandle<Wry>, paths: Vec<PathBuf>) {
+    if paths.is_empty() {
+        return;
+    }
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        let outcome = add_all(&app, paths).await;
+        if let Some((message, level)) = outcome.message() {
+            notify::toast(&app, message, level);
+        }
+    });
+}
+
+async fn add_all(app: &AppHandle<Wry>, paths: Vec<PathBuf>) -> DropOutcome {
+    let state = app.state::<AppState>();
+    let mut watched = state.status().directories;
+    let mut outcome = DropOutcome::default();
+    for path in paths {
+        let name = display_name(&path);
+        if !path.is_dir() {
+            outcome.rejected.push((name, "only folders can be watched".to_string()));
+            continue;
+        }
+        if watched.iter().any(|dir| Path::new(dir) == path) {
+            outcome.already_watched.push(name);
+            continue;
+        }
+        if let Some(excluded) = state.exclusions.excluded_component(&path) {
+            outcome.rejected.push((name, format!("it is inside an excluded folder ({})", excluded)));
+            continue;
+        }
+        match bridge::add_watch_dir(&state.bridge, path.to_string_lossy().into_owned()).await {
+            Ok(directories) => {
+                watched = directories;
+                outcome.added.push(name);
+            }
+            Err(err) => outcome.rejected.push((name, err)),
+        }
+    }
+    outcome
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn reports_a_drop_in_one_message() {
+        let added = DropOutcome { added: names(&["Downloads"]), ..DropOutcome::default() };
+        assert_eq!(added.message(), Some(("Watching Downloads".to_string(), "info")));
+
+        let mixed = DropOutcome {
+            added: names(&["Downloads", "Desktop"]),
+            already_watched: names(&["Inbox"]),
+            rejected: vec![("notes.txt".to_string(), "only folders can be watched".to_string())],
+        };
+        assert_eq!(mixed.message(), Some(("Watching 2 folders; skipped 2".to_string(), "warn")));
+
+        let rejected = DropOutcome {
+            already_watched: names(&["Inbox"]),
+            rejected: vec![("notes.txt".to_string(), "only folders can be watched".to_string())],
+            ..DropOutcome::default()
+        };
+        assert_eq!(
+            rejected.message(),
+            Some(("Can't watch notes.txt: only folders can be watched (and 1 more skipped)".to_string(), "warn"))
+        );
+
+        let watched = DropOutcome { already_watched: names(&["Inbox"]), ..DropOutcome::default() };
+        assert_eq!(watched.message(), Some(("Already watching Inbox".to_string(), "info")));
+        assert_eq!(DropOutcome::default().message(), None);
+    }
+}
@@ -0,0 +1,82 @@
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::actions;
+use crate::clipboard_fix;
+use crate::notify;
+use crate::state::AppState;
+
+/// Register the user's shortcuts system-wide, so they work while another app
+/// is in front, along with the clipboard fix shortcut on macOS. Replaces what
+/// was registered before; a shortcut the system won't give us is logged and
+/// left to work only in the Preferences window.
+pub fn register_all(app: &AppHandle<Wry>) {
+    let global = app.global_shortcut();
+    if let Err(err) = global.unregister_all() {
+        log::warn!("Could not clear global shortcuts: {}", err);
+    }
+    let mut bindings: Vec<(String, String)> = app.state::<AppState>().shortcuts.all().into_iter().collect();
+    if cfg!(target_os = "macos") {
+        bindings.push(("fix-clipboard".to_string(), clipboard_fix::HOTKEY_LABEL.to_string()));
+    }
+    for (action, accelerator) in bindings {
+        let shortcut = match parse(&accelerator) {
+            Ok(shortcut) => shortcut,
+            Err(err) => {
+                log::warn!("{}", err);
+                continue;
+            }
+        };
+        let registered = global.on_shortcut(shortcut, move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                run(app, action.clone());
+            }
+        });
+        if let Err(err) = registered {
+            log::warn!("Could not register the global shortcut {}: {}", accelerator, err);
+        }
+    }
+}
+
+/// Fail when another app holds `accelerator` system-wide. One this app has
+/// registered is fine; clashes between its own actions are caught by
+/// [`crate::shortcuts::ShortcutStore`].
+pub fn check_available(app: &AppHandle<Wry>, accelerator: &str) -> Result<(), String> {
+    let shortcut = parse(accelerator)?;
+    let global = app.global_shortcut();
+    if global.is_registered(shortcut) {
+        return Ok(());
+    }
+    global.register(shortcut).map_err(|_| format!("{} is already used by another app", accelerator))?;
+    let _ = global.unregister(shortcut);
+    Ok(())
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator.parse().map_err(|err| format!("{} can't be used as a global shortcut: {}", accelerator, err))
+}
+
+fn run(app: &AppHandle<Wry>, action: String) {
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        if let Err(err) = actions::invoke(&app, &action).await {
+            log::warn!("Shortcut for {} failed: {}", action, err);
+            notify::toast(&app, err, "warn");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shortcuts::normalize;
+
+    #[test]
+    fn normalized_accelerators_parse_as_global_shortcuts() {
+        for accelerator in ["Cmd+Alt+N", "Ctrl+Shift+F5", "Alt+Space", "Cmd+Ctrl+Up", "F13", clipboard_fix::HOTKEY_LABEL] {
+            let normalized = normalize(accelerator).unwrap();
+            assert!(parse(&normalized).is_ok(), "{}", normalized);
+        }
+        assert!(parse("Cmd+Nope").is_err());
+    }
+}
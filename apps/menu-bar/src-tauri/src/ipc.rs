@@ -1,5 +1,43 @@
-use crate::bridge::{self, BridgeState, ServiceStatus};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::accessibility::AccessibilitySettings;
+use crate::actions::{self, Action};
+use crate::api::{ApiAccess, ApiScope, ApiTokenView, CreatedApiToken};
+use crate::activity::{now_millis, ActivityEntry, ActivityWindow};
+use crate::attention::AttentionItem;
+use crate::bridge::{self, RenamePlan, RunOnceResult, ServiceStatus, WatchBackend};
+use crate::calendar;
+use crate::collate;
+use crate::config::{self, ConfigImport};
+use crate::conflict::{self, ConflictComparison};
+use crate::email::{self, EmailDigestSettings, EmailDigestView};
+use crate::entries::{self, EntryClickAction, OpenWithApp, RenameEntry};
+use crate::events::{self, EventFilter, EventRange, EventStats, LoggedEvent};
+use crate::exclusions::WatchExclusions;
+use crate::hotkeys;
+use crate::expr::{ExpressionContext, ExpressionMode};
+use crate::issues::{self, Issue, IssueCategory, Remedy};
+use crate::listing::{self, SortPreview};
+use crate::logging::{self, LogLevels};
+use crate::notify;
+use crate::pathdisplay;
+use crate::pause::{self, PauseDuration};
+use crate::pipeline::{self, Pipeline, PipelineTrace};
+use crate::plugins::PluginInfo;
+use crate::preview::RenamePreview;
+use crate::push::{self, PushEndpoint};
+use crate::report;
+use crate::state::AppState;
+use crate::status_page::{HttpSecurity, StatusPageView};
+use crate::telemetry::TelemetryPreview;
+use crate::thumbnail;
+use crate::tray;
+use crate::volume;
+use crate::webhook::{self, WebhookSettings};
 use anyhow::anyhow;
+use tauri::{Emitter, Manager};
 use tauri_plugin_autostart::ManagerExt;
 
 fn map_bridge_err<T>(result: Result<T, String>) -> tauri::Result<T> {
@@ -7,27 +45,36 @@ fn map_bridge_err<T>(result: Result<T, String>) -> tauri::Result<T> {
 }
 
 #[tauri::command]
-pub async fn get_status(state: tauri::State<'_, BridgeState>) -> tauri::Result<ServiceStatus> {
-    map_bridge_err(bridge::get_status(&state).await)
+pub async fn get_status(state: tauri::State<'_, AppState>) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::get_status(&state.bridge).await)
 }
 
 #[tauri::command]
 pub async fn toggle_running(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     desired: Option<bool>,
 ) -> tauri::Result<ServiceStatus> {
-    map_bridge_err(bridge::toggle_running(&state, desired).await)
+    map_bridge_err(bridge::toggle_running(&state.bridge, desired).await)
 }
 
 #[tauri::command]
-pub async fn list_directories(state: tauri::State<'_, BridgeState>) -> tauri::Result<Vec<String>> {
-    map_bridge_err(bridge::list_directories(&state).await)
+pub async fn pause_for(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    duration: PauseDuration,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(pause::pause_for(&app_handle, &state.bridge, duration).await)
+}
+
+#[tauri::command]
+pub async fn list_directories(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<String>> {
+    map_bridge_err(bridge::list_directories(&state.bridge).await)
 }
 
 #[tauri::command]
 pub async fn set_launch_on_login(
     app_handle: tauri::AppHandle,
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     enabled: bool,
 ) -> tauri::Result<bool> {
     let manager = app_handle.autolaunch();
@@ -37,80 +84,1129 @@ pub async fn set_launch_on_login(
         manager.disable().map_err(|e| tauri::Error::Anyhow(anyhow!(e)))?;
     }
     log::info!("Autostart {}", if enabled { "enabled" } else { "disabled" });
-    map_bridge_err(bridge::set_launch_on_login(&state, enabled).await)
+    map_bridge_err(bridge::set_launch_on_login(&state.bridge, enabled).await)
+}
+
+/// Watch without renaming, logging every outcome to the audit log.
+#[tauri::command]
+pub async fn set_observer_mode(state: tauri::State<'_, AppState>, enabled: bool) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_observer_mode(&state.bridge, enabled).await)
+}
+
+/// Log full file paths, or hashed stand-ins for them, here and in the service.
+/// Rename history keeps full paths either way.
+#[tauri::command]
+pub async fn set_log_privacy(state: tauri::State<'_, AppState>, enabled: bool) -> tauri::Result<ServiceStatus> {
+    let status = map_bridge_err(bridge::set_log_privacy(&state.bridge, enabled).await)?;
+    logging::set_privacy(status.log_privacy);
+    Ok(status)
+}
+
+/// Move the service's history, audit log and logs to `path`, or back to the
+/// default locations when None, without stopping it. The event log moves
+/// with them; the thumbnail cache is dropped rather than copied, since it
+/// refills on demand.
+#[tauri::command]
+pub async fn set_data_dir(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: Option<String>,
+) -> tauri::Result<ServiceStatus> {
+    let old_cache = thumbnail::cache_dir(&app_handle, state.status().data_dir.as_deref())?;
+    let status = map_bridge_err(bridge::set_data_dir(&state.bridge, path).await)?;
+    if thumbnail::cache_dir(&app_handle, status.data_dir.as_deref())? != old_cache {
+        let _ = std::fs::remove_dir_all(&old_cache);
+    }
+    state.events.move_to(&events::db_path(&app_handle, status.data_dir.as_deref())?);
+    Ok(status)
+}
+
+/// Sync settings and rules, but not history, through a file in `path`, or
+/// stop syncing when None. Changes made on other machines apply as they arrive.
+#[tauri::command]
+pub async fn set_sync_dir(state: tauri::State<'_, AppState>, path: Option<String>) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_sync_dir(&state.bridge, path).await)
+}
+
+/// The key for pairing another machine with this one's synced settings.
+#[tauri::command]
+pub async fn export_sync_key(state: tauri::State<'_, AppState>) -> tauri::Result<String> {
+    map_bridge_err(bridge::export_sync_key(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn import_sync_key(state: tauri::State<'_, AppState>, key: String) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::import_sync_key(&state.bridge, key).await)
+}
+
+#[tauri::command]
+pub async fn set_lan_pairing(state: tauri::State<'_, AppState>, enabled: bool) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_lan_pairing(&state.bridge, enabled).await)
+}
+
+#[tauri::command]
+pub async fn start_pairing(state: tauri::State<'_, AppState>) -> tauri::Result<bridge::PairingCode> {
+    map_bridge_err(bridge::start_pairing(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn find_peers(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::NearbyPeer>> {
+    map_bridge_err(bridge::find_peers(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn pair_peer(
+    state: tauri::State<'_, AppState>,
+    peer_id: String,
+    code: String,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::pair_peer(&state.bridge, peer_id, code).await)
+}
+
+#[tauri::command]
+pub async fn get_peer_status(
+    state: tauri::State<'_, AppState>,
+    peer_id: String,
+) -> tauri::Result<bridge::PeerStatus> {
+    map_bridge_err(bridge::get_peer_status(&state.bridge, peer_id).await)
+}
+
+#[tauri::command]
+pub async fn unpair_peer(state: tauri::State<'_, AppState>, peer_id: String) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::unpair_peer(&state.bridge, peer_id).await)
+}
+
+#[tauri::command]
+pub async fn get_audit_log(
+    state: tauri::State<'_, AppState>,
+    limit: Option<usize>,
+) -> tauri::Result<Vec<bridge::AuditEntry>> {
+    map_bridge_err(bridge::get_audit_log(&state.bridge, limit).await)
 }
 
 #[tauri::command]
 pub async fn set_dry_run(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     enabled: bool,
 ) -> tauri::Result<ServiceStatus> {
-    map_bridge_err(bridge::set_dry_run(&state, enabled).await)
+    map_bridge_err(bridge::set_dry_run(&state.bridge, enabled).await)
+}
+
+#[tauri::command]
+pub async fn undo(state: tauri::State<'_, AppState>) -> tauri::Result<bridge::UndoResult> {
+    map_bridge_err(bridge::undo(&state.bridge).await)
 }
 
+/// Undo one rename from history by its id, without undoing the renames after it.
 #[tauri::command]
-pub async fn undo(state: tauri::State<'_, BridgeState>) -> tauri::Result<bridge::UndoResult> {
-    map_bridge_err(bridge::undo(&state).await)
+pub async fn undo_entry(state: tauri::State<'_, AppState>, id: String) -> tauri::Result<bridge::UndoResult> {
+    map_bridge_err(bridge::undo_entry(&state.bridge, id).await)
+}
+
+#[tauri::command]
+pub async fn redo(state: tauri::State<'_, AppState>) -> tauri::Result<bridge::UndoResult> {
+    map_bridge_err(bridge::redo(&state.bridge).await)
+}
+
+/// A page of rename history, newest first: 50 entries unless `limit` says otherwise.
+#[tauri::command]
+pub async fn get_history(
+    state: tauri::State<'_, AppState>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> tauri::Result<Vec<bridge::HistoryEntry>> {
+    map_bridge_err(bridge::get_history(&state.bridge, limit.unwrap_or(50), offset.unwrap_or(0)).await)
 }
 
 #[tauri::command]
 pub async fn add_watch_dir(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     directory: String,
 ) -> tauri::Result<Vec<String>> {
-    map_bridge_err(bridge::add_watch_dir(&state, directory).await)
+    if let Some(name) = state.exclusions.excluded_component(Path::new(&directory)) {
+        return map_bridge_err(Err(format!("{} is inside an excluded folder ({})", directory, name)));
+    }
+    map_bridge_err(bridge::add_watch_dir(&state.bridge, directory).await)
 }
 
 #[tauri::command]
 pub async fn remove_watch_dir(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     directory: String,
 ) -> tauri::Result<Vec<String>> {
-    map_bridge_err(bridge::remove_watch_dir(&state, directory).await)
+    map_bridge_err(bridge::remove_watch_dir(&state.bridge, directory).await)
+}
+
+#[tauri::command]
+pub async fn set_directory_priority(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    high: bool,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_priority(&state.bridge, directory, high).await)
+}
+
+#[tauri::command]
+pub async fn set_directory_dry_run(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    enabled: bool,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_dry_run(&state.bridge, directory, enabled).await)
+}
+
+#[tauri::command]
+pub async fn get_dir_config(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+) -> tauri::Result<bridge::DirectoryConfig> {
+    map_bridge_err(bridge::get_dir_config(&state.bridge, directory).await)
+}
+
+#[tauri::command]
+pub async fn set_dir_config(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    config: bridge::DirectoryConfigUpdate,
+) -> tauri::Result<bridge::DirectoryConfig> {
+    map_bridge_err(bridge::set_dir_config(&state.bridge, directory, config).await)
+}
+
+/// Choose how directories are watched; `poll_interval_ms` tunes the polling backend.
+#[tauri::command]
+pub async fn set_watch_backend(
+    state: tauri::State<'_, AppState>,
+    backend: WatchBackend,
+    poll_interval_ms: Option<u64>,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_watch_backend(&state.bridge, backend, poll_interval_ms).await)
+}
+
+/// Only rename files in `directory` created by the `allow`ed apps (when any
+/// are listed), and never those from `deny`ed ones. Empty lists clear the rule.
+#[tauri::command]
+pub async fn set_directory_origin_rule(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    allow: Vec<String>,
+    deny: Vec<String>,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_origin_rule(&state.bridge, directory, allow, deny).await)
+}
+
+/// Put directories in `order`, or alphabetically by folder name when no order
+/// is given. Pinned directories are still listed first.
+#[tauri::command]
+pub async fn reorder_directories(
+    state: tauri::State<'_, AppState>,
+    order: Option<Vec<String>>,
+) -> tauri::Result<ServiceStatus> {
+    let order = match order {
+        Some(order) => order,
+        None => {
+            let mut directories = map_bridge_err(bridge::get_status(&state.bridge).await)?.directories;
+            directories.sort_by(|a, b| collate::compare_names(folder_name(a), folder_name(b)));
+            directories
+        }
+    };
+    map_bridge_err(bridge::reorder_directories(&state.bridge, order).await)
+}
+
+fn folder_name(dir: &str) -> &str {
+    Path::new(dir).file_name().and_then(|name| name.to_str()).unwrap_or(dir)
+}
+
+#[tauri::command]
+pub async fn set_directory_pinned(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    pinned: bool,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_pinned(&state.bridge, directory, pinned).await)
+}
+
+/// Nest `directory` under `group` in the tray; `None` or an empty name ungroups it.
+#[tauri::command]
+pub async fn set_directory_group(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    group: Option<String>,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_group(&state.bridge, directory, group).await)
+}
+
+/// Hand files renamed in `directory` off to `destination` and/or `command`;
+/// with neither, it stops being a hot folder.
+#[tauri::command]
+pub async fn set_directory_hot_folder(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    destination: Option<String>,
+    command: Option<Vec<String>>,
+) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::set_directory_hot_folder(&state.bridge, directory, destination, command).await)
+}
+
+/// Stop watching the directories on a volume and eject it.
+#[tauri::command]
+pub async fn eject_volume(state: tauri::State<'_, AppState>, root: String) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(volume::eject(&state.bridge, &root).await)
+}
+
+#[tauri::command]
+pub async fn snooze_directory(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    duration: PauseDuration,
+) -> tauri::Result<ServiceStatus> {
+    let until = duration.resume_at(now_millis());
+    map_bridge_err(bridge::snooze_directory(&state.bridge, directory, until).await)
+}
+
+#[tauri::command]
+pub async fn get_watch_exclusions(state: tauri::State<'_, AppState>) -> tauri::Result<WatchExclusions> {
+    Ok(state.exclusions.get())
+}
+
+#[tauri::command]
+pub async fn set_watch_exclusions(
+    state: tauri::State<'_, AppState>,
+    custom: Vec<String>,
+) -> tauri::Result<WatchExclusions> {
+    let exclusions = map_bridge_err(state.exclusions.set_custom(custom))?;
+    map_bridge_err(bridge::set_watch_exclusions(&state.bridge, state.exclusions.names()).await)?;
+    Ok(exclusions)
+}
+
+#[tauri::command]
+pub async fn get_entry_click_action(state: tauri::State<'_, AppState>) -> tauri::Result<EntryClickAction> {
+    Ok(state.entry_action.get())
+}
+
+#[tauri::command]
+pub async fn set_entry_click_action(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    action: EntryClickAction,
+) -> tauri::Result<EntryClickAction> {
+    let action = map_bridge_err(state.entry_action.set(action))?;
+    tray::redraw_recent(&app_handle);
+    Ok(action)
+}
+
+#[tauri::command]
+pub async fn copy_to_clipboard(app_handle: tauri::AppHandle, text: String) -> tauri::Result<()> {
+    map_bridge_err(entries::copy(&app_handle, text))
+}
+
+/// How `directory` lists today and after the current rules rename its files.
+#[tauri::command]
+pub async fn preview_sort_order(state: tauri::State<'_, AppState>, directory: String) -> tauri::Result<SortPreview> {
+    let plan = map_bridge_err(bridge::plan_directory(&state.bridge, directory, None).await)?;
+    Ok(listing::simulate(plan))
+}
+
+/// Every action the Preferences command palette can run right now.
+#[tauri::command]
+pub async fn list_actions(app_handle: tauri::AppHandle) -> tauri::Result<Vec<Action>> {
+    map_bridge_err(actions::list(&app_handle).await)
+}
+
+/// Run an action from [`list_actions`] by id.
+#[tauri::command]
+pub async fn invoke_action(app_handle: tauri::AppHandle, id: String) -> tauri::Result<()> {
+    map_bridge_err(actions::invoke(&app_handle, &id).await)
+}
+
+#[tauri::command]
+pub async fn get_accessibility_settings(state: tauri::State<'_, AppState>) -> tauri::Result<AccessibilitySettings> {
+    Ok(state.accessibility.get())
+}
+
+/// Save the accessibility settings, redrawing the tray icon and showing any
+/// held notifications when minimal notifications are turned off. Windows are
+/// told with `ui://accessibility`.
+#[tauri::command]
+pub async fn set_accessibility_settings(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    settings: AccessibilitySettings,
+) -> tauri::Result<AccessibilitySettings> {
+    let settings = map_bridge_err(state.accessibility.set(settings))?;
+    tray::refresh_icon(&app_handle);
+    if !settings.minimal_notifications {
+        notify::flush(&app_handle);
+    }
+    let _ = app_handle.emit("ui://accessibility", settings);
+    config::persist(&app_handle);
+    Ok(settings)
+}
+
+/// Keyboard shortcuts by action id.
+#[tauri::command]
+pub async fn get_shortcuts(state: tauri::State<'_, AppState>) -> tauri::Result<BTreeMap<String, String>> {
+    Ok(state.shortcuts.all())
+}
+
+/// Bind `accelerator` to the palette action `action`, or clear its shortcut
+/// when None, and register it system-wide. Keys another app holds are
+/// refused like keys bound to another action. Updates the tray and tells
+/// the window with `ui://shortcuts`.
+#[tauri::command]
+pub async fn set_shortcut(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    action: String,
+    accelerator: Option<String>,
+) -> tauri::Result<BTreeMap<String, String>> {
+    if accelerator.is_some() {
+        let known = map_bridge_err(actions::list(&app_handle).await)?;
+        if !known.iter().any(|known| known.id == action) {
+            return map_bridge_err(Err(format!("Unknown action: {}", action)));
+        }
+    }
+    let available = |accelerator: &str| hotkeys::check_available(&app_handle, accelerator);
+    let shortcuts = map_bridge_err(state.shortcuts.set(&action, accelerator.as_deref(), available))?;
+    hotkeys::register_all(&app_handle);
+    tray::apply_shortcuts(&app_handle);
+    let _ = app_handle.emit("ui://shortcuts", &shortcuts);
+    config::persist(&app_handle);
+    Ok(shortcuts)
+}
+
+/// Log levels in effect, by target.
+#[tauri::command]
+pub async fn get_log_levels(state: tauri::State<'_, AppState>) -> tauri::Result<LogLevels> {
+    Ok(state.log_levels.get())
+}
+
+/// Log `target` at `level` from now on, without a restart, or go back to the
+/// `RUST_LOG` level when None.
+#[tauri::command]
+pub async fn set_log_level(
+    state: tauri::State<'_, AppState>,
+    target: String,
+    level: Option<String>,
+) -> tauri::Result<LogLevels> {
+    map_bridge_err(state.log_levels.set(&target, level.as_deref()))
+}
+
+/// What renaming `directory` once would do, with just `profile` or all enabled rules.
+#[tauri::command]
+pub async fn plan_folder(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    profile: Option<String>,
+) -> tauri::Result<RenamePlan> {
+    map_bridge_err(bridge::plan_directory(&state.bridge, directory, profile).await)
+}
+
+/// Rename the files in `directory` once, without watching it.
+#[tauri::command]
+pub async fn run_once(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    profile: Option<String>,
+) -> tauri::Result<RunOnceResult> {
+    map_bridge_err(bridge::run_once(&state.bridge, directory, profile).await)
+}
+
+/// Every rename the rules would make in `directory`, with just `profile` or
+/// all enabled rules, under an id `apply_plan` takes. Nothing is touched, so
+/// this is how a folder is previewed in dry run.
+#[tauri::command]
+pub async fn preview_renames(
+    state: tauri::State<'_, AppState>,
+    directory: String,
+    profile: Option<String>,
+) -> tauri::Result<RenamePreview> {
+    map_bridge_err(state.previews.preview(&state.bridge, directory, profile).await)
+}
+
+/// Make the renames of a preview, or only those of the `selected` files,
+/// whether or not dry run is on.
+#[tauri::command]
+pub async fn apply_plan(
+    state: tauri::State<'_, AppState>,
+    plan_id: String,
+    selected: Option<Vec<String>>,
+) -> tauri::Result<RunOnceResult> {
+    map_bridge_err(state.previews.apply(&state.bridge, &plan_id, selected.as_deref()).await)
+}
+
+/// Finder-style natural order of two file names: -1, 0, or 1, ready for
+/// `Array.prototype.sort`.
+#[tauri::command]
+pub fn compare_names(a: String, b: String) -> i32 {
+    match collate::compare_names(&a, &b) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+#[tauri::command]
+pub async fn quick_look(path: String) -> tauri::Result<()> {
+    map_bridge_err(entries::quick_look(Path::new(&path)))
+}
+
+#[tauri::command]
+pub async fn get_open_with_apps(path: String) -> tauri::Result<Vec<OpenWithApp>> {
+    Ok(entries::open_with_apps(Path::new(&path)))
+}
+
+#[tauri::command]
+pub async fn open_with(path: String, application: String) -> tauri::Result<()> {
+    map_bridge_err(entries::open_with(Path::new(&path), Path::new(&application)))
+}
+
+#[tauri::command]
+pub async fn export_history_report(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    days: Option<u32>,
+) -> tauri::Result<report::HistoryReport> {
+    let directory = app_handle.path().app_data_dir()?.join("reports");
+    map_bridge_err(report::export_history(&state.bridge, &directory, days.unwrap_or(report::DEFAULT_DAYS)).await)
+}
+
+/// Start a named session; renames are tagged with it until `end_session`.
+#[tauri::command]
+pub async fn start_session(state: tauri::State<'_, AppState>, name: String) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::start_session(&state.bridge, name).await)
+}
+
+#[tauri::command]
+pub async fn end_session(state: tauri::State<'_, AppState>) -> tauri::Result<ServiceStatus> {
+    map_bridge_err(bridge::end_session(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn list_sessions(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::SessionSummary>> {
+    map_bridge_err(bridge::list_sessions(&state.bridge).await)
+}
+
+/// Undo every rename made during a session.
+#[tauri::command]
+pub async fn undo_session(state: tauri::State<'_, AppState>, id: String) -> tauri::Result<bridge::SessionUndoResult> {
+    map_bridge_err(bridge::undo_session(&state.bridge, id).await)
+}
+
+/// Write a Markdown report of a session's renames alongside the history reports.
+#[tauri::command]
+pub async fn export_session(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<report::HistoryReport> {
+    let directory = app_handle.path().app_data_dir()?.join("reports");
+    let sessions = map_bridge_err(bridge::list_sessions(&state.bridge).await)?;
+    let Some(session) = sessions.into_iter().find(|session| session.session.id == id) else {
+        return map_bridge_err(Err(format!("Unknown session {}", id)));
+    };
+    map_bridge_err(report::export_session(&state.bridge, &directory, &session).await)
+}
+
+/// Write every session as a calendar event, for a time-tracking calendar.
+#[tauri::command]
+pub async fn export_sessions_calendar(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> tauri::Result<calendar::CalendarExport> {
+    let directory = app_handle.path().app_data_dir()?.join("reports");
+    map_bridge_err(calendar::export_sessions(&state.bridge, &directory).await)
+}
+
+#[tauri::command]
+pub async fn reveal_file(path: String) -> tauri::Result<()> {
+    map_bridge_err(entries::reveal(Path::new(&path)))
+}
+
+/// Run the configured click action on a rename entry shown in the window.
+#[tauri::command]
+pub async fn activate_entry(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    entry: RenameEntry,
+) -> tauri::Result<()> {
+    map_bridge_err(entries::perform(&app_handle, &state.bridge, state.entry_action.get(), &entry).await)
+}
+
+#[tauri::command]
+pub async fn search_history(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    filters: Option<bridge::HistoryFilters>,
+) -> tauri::Result<Vec<bridge::HistoryEntry>> {
+    map_bridge_err(bridge::search_history(&state.bridge, query, filters.unwrap_or_default()).await)
+}
+
+#[tauri::command]
+pub async fn prune_history(
+    state: tauri::State<'_, AppState>,
+    policy: Option<bridge::RetentionPolicy>,
+) -> tauri::Result<bridge::PruneResult> {
+    map_bridge_err(bridge::prune_history(&state.bridge, policy).await)
+}
+
+/// Write all rename history to `path` to carry it to another Mac.
+#[tauri::command]
+pub async fn export_history(state: tauri::State<'_, AppState>, path: String) -> tauri::Result<usize> {
+    map_bridge_err(bridge::export_history(&state.bridge, path).await)
+}
+
+/// Merge history exported on another Mac into this one's, skipping renames
+/// already here, so undo and usage stats follow the user.
+#[tauri::command]
+pub async fn merge_history(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    import_path: String,
+) -> tauri::Result<bridge::HistoryMerge> {
+    let merged = map_bridge_err(bridge::merge_history(&state.bridge, import_path).await)?;
+    tray::refresh_recent(&app_handle);
+    Ok(merged)
+}
+
+/// Write watched folders, dry run, notification settings and shortcuts to a
+/// TOML file at `path` to carry them to another Mac.
+#[tauri::command]
+pub async fn export_config(app_handle: tauri::AppHandle, path: String) -> tauri::Result<()> {
+    map_bridge_err(config::export(&app_handle, &pathdisplay::expand_home(&path)).await)
+}
+
+/// Replace those settings with the ones exported to `path`, skipping folders
+/// that don't exist here.
+#[tauri::command]
+pub async fn import_config(app_handle: tauri::AppHandle, path: String) -> tauri::Result<ConfigImport> {
+    map_bridge_err(config::import(&app_handle, &pathdisplay::expand_home(&path)).await)
+}
+
+#[tauri::command]
+pub async fn get_telemetry_preview(state: tauri::State<'_, AppState>) -> tauri::Result<TelemetryPreview> {
+    Ok(state.telemetry.preview())
+}
+
+#[tauri::command]
+pub async fn get_notifications_enabled(state: tauri::State<'_, AppState>) -> tauri::Result<bool> {
+    Ok(state.system_notifications.enabled())
 }
 
+/// Raise an OS notification, with Undo and Reveal where the platform allows,
+/// for every rename.
 #[tauri::command]
-pub async fn get_profiles(state: tauri::State<'_, BridgeState>) -> tauri::Result<Vec<bridge::Profile>> {
-    map_bridge_err(bridge::get_profiles(&state).await)
+pub async fn set_notifications_enabled(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> tauri::Result<bool> {
+    let enabled = map_bridge_err(state.system_notifications.set_enabled(enabled))?;
+    config::persist(&app_handle);
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub async fn get_email_digest(state: tauri::State<'_, AppState>) -> tauri::Result<EmailDigestView> {
+    Ok(state.email_digest.view())
+}
+
+/// Mail a daily or weekly digest through the given SMTP server. A new
+/// `password` goes to the keychain; leaving it out keeps the saved one.
+#[tauri::command]
+pub async fn set_email_digest(
+    state: tauri::State<'_, AppState>,
+    settings: EmailDigestSettings,
+    password: Option<String>,
+) -> tauri::Result<EmailDigestView> {
+    map_bridge_err(state.email_digest.set(settings, password.as_deref()))
+}
+
+/// Mail the digest now instead of waiting for it to come due.
+#[tauri::command]
+pub async fn send_email_digest(app_handle: tauri::AppHandle) -> tauri::Result<()> {
+    map_bridge_err(email::send(&app_handle).await)
+}
+
+#[tauri::command]
+pub async fn get_push_endpoint(state: tauri::State<'_, AppState>) -> tauri::Result<Option<PushEndpoint>> {
+    Ok(state.push.endpoint())
+}
+
+/// Push critical events to an ntfy topic or Pushover, after a test push gets
+/// through; `None` stops pushing.
+#[tauri::command]
+pub async fn set_push_endpoint(
+    state: tauri::State<'_, AppState>,
+    endpoint: Option<PushEndpoint>,
+) -> tauri::Result<Option<PushEndpoint>> {
+    map_bridge_err(push::set_endpoint(&state.push, endpoint).await)
+}
+
+#[tauri::command]
+pub async fn get_webhook(state: tauri::State<'_, AppState>) -> tauri::Result<Option<WebhookSettings>> {
+    Ok(state.webhook.get())
+}
+
+/// Post alerts, and optionally rename summaries, to a Slack, Discord or
+/// generic JSON webhook after a test post gets through; `None` stops posting.
+#[tauri::command]
+pub async fn set_webhook(
+    state: tauri::State<'_, AppState>,
+    webhook: Option<WebhookSettings>,
+) -> tauri::Result<Option<WebhookSettings>> {
+    map_bridge_err(webhook::set_webhook(&state.webhook, webhook).await)
+}
+
+#[tauri::command]
+pub async fn get_status_page(state: tauri::State<'_, AppState>) -> tauri::Result<StatusPageView> {
+    Ok(state.status_page.view())
+}
+
+/// Serve the read-only status page on the local network, or stop serving it.
+#[tauri::command]
+pub async fn set_status_page(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+    port: u16,
+) -> tauri::Result<StatusPageView> {
+    map_bridge_err(state.status_page.set(&app_handle, enabled, port))
+}
+
+/// Set which browser origins may call the status page server and which
+/// host names it answers to besides addresses, `localhost` and `.local`.
+#[tauri::command]
+pub async fn set_http_security(
+    state: tauri::State<'_, AppState>,
+    options: HttpSecurity,
+) -> tauri::Result<StatusPageView> {
+    map_bridge_err(state.status_page.set_security(options))
+}
+
+#[tauri::command]
+pub async fn list_api_tokens(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<ApiTokenView>> {
+    Ok(state.api_tokens.list())
+}
+
+/// Create a token for the write API; its secret is only returned here.
+#[tauri::command]
+pub async fn create_api_token(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    scopes: Vec<ApiScope>,
+    days: Option<u32>,
+) -> tauri::Result<CreatedApiToken> {
+    map_bridge_err(state.api_tokens.create(&name, &scopes, days))
+}
+
+#[tauri::command]
+pub async fn revoke_api_token(state: tauri::State<'_, AppState>, id: String) -> tauri::Result<Vec<ApiTokenView>> {
+    map_bridge_err(state.api_tokens.revoke(&id))
+}
+
+/// Requests to the API since the app started, newest first.
+#[tauri::command]
+pub async fn get_api_access_log(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<ApiAccess>> {
+    Ok(state.api_tokens.access_log())
+}
+
+#[tauri::command]
+pub async fn set_telemetry_enabled(
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> tauri::Result<TelemetryPreview> {
+    map_bridge_err(state.telemetry.set_enabled(enabled))?;
+    if enabled {
+        // Seed rule counts now rather than waiting for the next config change.
+        if let Ok(profiles) = bridge::get_profiles(&state.bridge).await {
+            let profiles: Vec<serde_json::Value> =
+                profiles.iter().filter_map(|profile| serde_json::to_value(profile).ok()).collect();
+            state.telemetry.record_profiles(&profiles);
+        }
+    }
+    Ok(state.telemetry.preview())
+}
+
+/// Files renamed and bytes processed per watched directory, this month unless
+/// `since` is given.
+#[tauri::command]
+pub async fn get_directory_usage(
+    state: tauri::State<'_, AppState>,
+    since: Option<u64>,
+) -> tauri::Result<Vec<bridge::DirectoryUsage>> {
+    map_bridge_err(bridge::get_directory_usage(&state.bridge, since).await)
+}
+
+#[tauri::command]
+pub async fn run_benchmark(
+    state: tauri::State<'_, AppState>,
+    files: Option<u32>,
+    directory: Option<String>,
+) -> tauri::Result<bridge::BenchmarkResult> {
+    map_bridge_err(bridge::run_benchmark(&state.bridge, files, directory).await)
+}
+
+#[tauri::command]
+pub async fn get_benchmark_results(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::BenchmarkResult>> {
+    map_bridge_err(bridge::get_benchmark_results(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn get_profiles(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::Profile>> {
+    map_bridge_err(bridge::get_profiles(&state.bridge).await)
 }
 
 #[tauri::command]
 pub async fn get_profile(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     id: String,
 ) -> tauri::Result<Option<bridge::Profile>> {
-    map_bridge_err(bridge::get_profile(&state, id).await)
+    map_bridge_err(bridge::get_profile(&state.bridge, id).await)
 }
 
 #[tauri::command]
 pub async fn set_profile(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     profile: bridge::Profile,
 ) -> tauri::Result<Vec<bridge::Profile>> {
-    map_bridge_err(bridge::set_profile(&state, profile).await)
+    map_bridge_err(bridge::set_profile(&state.bridge, profile).await)
+}
+
+#[tauri::command]
+pub async fn list_import_presets(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::ImportPreset>> {
+    map_bridge_err(bridge::list_import_presets(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn set_import_preset(
+    state: tauri::State<'_, AppState>,
+    preset: bridge::ImportPreset,
+) -> tauri::Result<Vec<bridge::ImportPreset>> {
+    map_bridge_err(bridge::set_import_preset(&state.bridge, preset).await)
+}
+
+#[tauri::command]
+pub async fn delete_import_preset(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<Vec<bridge::ImportPreset>> {
+    map_bridge_err(bridge::delete_import_preset(&state.bridge, id).await)
+}
+
+/// Import every mounted camera card with a preset now; progress arrives as `service://import` events.
+#[tauri::command]
+pub async fn run_import_preset(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<Vec<bridge::ImportProgress>> {
+    map_bridge_err(bridge::run_import_preset(&state.bridge, id).await)
 }
 
 #[tauri::command]
 pub async fn delete_profile(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     id: String,
 ) -> tauri::Result<Vec<bridge::Profile>> {
-    map_bridge_err(bridge::delete_profile(&state, id).await)
+    state.session_profiles.forget(&id);
+    map_bridge_err(bridge::delete_profile(&state.bridge, id).await)
 }
 
 #[tauri::command]
 pub async fn toggle_profile(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     id: String,
     enabled: Option<bool>,
 ) -> tauri::Result<Vec<bridge::Profile>> {
-    map_bridge_err(bridge::toggle_profile(&state, id, enabled).await)
+    state.session_profiles.forget(&id);
+    map_bridge_err(bridge::toggle_profile(&state.bridge, id, enabled).await)
 }
 
 #[tauri::command]
 pub async fn reorder_profiles(
-    state: tauri::State<'_, BridgeState>,
+    state: tauri::State<'_, AppState>,
     ordered_ids: Vec<String>,
 ) -> tauri::Result<Vec<bridge::Profile>> {
-    map_bridge_err(bridge::reorder_profiles(&state, ordered_ids).await)
+    map_bridge_err(bridge::reorder_profiles(&state.bridge, ordered_ids).await)
+}
+
+/// Rules in the order they are tried, for the Preferences window.
+#[tauri::command]
+pub async fn list_rules(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<bridge::RenameRule>> {
+    map_bridge_err(bridge::list_rules(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn add_rule(
+    state: tauri::State<'_, AppState>,
+    rule: bridge::RenameRule,
+) -> tauri::Result<Vec<bridge::RenameRule>> {
+    map_bridge_err(bridge::add_rule(&state.bridge, rule).await)
+}
+
+#[tauri::command]
+pub async fn update_rule(
+    state: tauri::State<'_, AppState>,
+    rule: bridge::RenameRule,
+) -> tauri::Result<Vec<bridge::RenameRule>> {
+    map_bridge_err(bridge::update_rule(&state.bridge, rule).await)
+}
+
+#[tauri::command]
+pub async fn remove_rule(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<Vec<bridge::RenameRule>> {
+    map_bridge_err(bridge::remove_rule(&state.bridge, id).await)
+}
+
+#[tauri::command]
+pub async fn reorder_rules(
+    state: tauri::State<'_, AppState>,
+    ordered_ids: Vec<String>,
+) -> tauri::Result<Vec<bridge::RenameRule>> {
+    map_bridge_err(bridge::reorder_rules(&state.bridge, ordered_ids).await)
+}
+
+#[tauri::command]
+pub async fn list_plugins(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<PluginInfo>> {
+    map_bridge_err(state.plugins.reload().await)?;
+    Ok(state.plugins.list().await)
+}
+
+#[tauri::command]
+pub async fn enable_plugin(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    enabled: bool,
+) -> tauri::Result<Vec<PluginInfo>> {
+    map_bridge_err(state.plugins.set_enabled(&id, enabled).await)
+}
+
+#[tauri::command]
+pub async fn preview_plugin_transform(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    metadata: Option<serde_json::Value>,
+) -> tauri::Result<Option<String>> {
+    let metadata = metadata.unwrap_or(serde_json::Value::Null);
+    map_bridge_err(state.plugins.transform(&name, &metadata).await)
+}
+
+#[tauri::command]
+pub async fn eval_expression_preview(
+    state: tauri::State<'_, AppState>,
+    mode: ExpressionMode,
+    expression: String,
+    context: ExpressionContext,
+) -> tauri::Result<serde_json::Value> {
+    map_bridge_err(state.expressions.preview(mode, &expression, &context))
+}
+
+#[tauri::command]
+pub async fn get_pipelines(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<Pipeline>> {
+    Ok(state.pipelines.list().await)
+}
+
+#[tauri::command]
+pub async fn set_pipeline(
+    state: tauri::State<'_, AppState>,
+    pipeline: Pipeline,
+) -> tauri::Result<Vec<Pipeline>> {
+    let profile_ids: Vec<String> = map_bridge_err(bridge::get_profiles(&state.bridge).await)?
+        .into_iter()
+        .map(|profile| profile.id)
+        .collect();
+    map_bridge_err(pipeline::validate(&pipeline, &profile_ids))?;
+    map_bridge_err(state.pipelines.upsert(pipeline).await)
+}
+
+#[tauri::command]
+pub async fn delete_pipeline(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<Vec<Pipeline>> {
+    map_bridge_err(state.pipelines.remove(&id).await)
+}
+
+#[tauri::command]
+pub async fn trace_pipeline(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    context: ExpressionContext,
+) -> tauri::Result<PipelineTrace> {
+    let pipeline = map_bridge_err(state.pipelines.get(&id).await.ok_or_else(|| format!("unknown pipeline: {id}")))?;
+    map_bridge_err(pipeline::trace(&pipeline, &context, &state.expressions))
+}
+
+#[tauri::command]
+pub async fn get_queue(state: tauri::State<'_, AppState>) -> tauri::Result<bridge::QueueSnapshot> {
+    map_bridge_err(bridge::get_queue(&state.bridge).await)
+}
+
+#[tauri::command]
+pub async fn set_queue_paused(
+    state: tauri::State<'_, AppState>,
+    paused: bool,
+) -> tauri::Result<bridge::QueueSnapshot> {
+    map_bridge_err(bridge::set_queue_paused(&state.bridge, paused).await)
+}
+
+#[tauri::command]
+pub async fn reorder_queue(
+    state: tauri::State<'_, AppState>,
+    ordered_ids: Vec<String>,
+) -> tauri::Result<bridge::QueueSnapshot> {
+    map_bridge_err(bridge::reorder_queue(&state.bridge, ordered_ids).await)
+}
+
+#[tauri::command]
+pub async fn drop_queue_item(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> tauri::Result<bridge::QueueSnapshot> {
+    map_bridge_err(bridge::drop_queue_item(&state.bridge, id).await)
+}
+
+#[tauri::command]
+pub async fn get_activity(
+    state: tauri::State<'_, AppState>,
+    window: Option<ActivityWindow>,
+) -> tauri::Result<Vec<ActivityEntry>> {
+    Ok(state.activity.query(&window.unwrap_or_default()))
+}
+
+/// Stored renames, skips, errors and undos, newest first; see [`crate::events`].
+#[tauri::command]
+pub async fn query_events(
+    state: tauri::State<'_, AppState>,
+    filter: Option<EventFilter>,
+    range: Option<EventRange>,
+    limit: Option<usize>,
+) -> tauri::Result<Vec<LoggedEvent>> {
+    map_bridge_err(state.events.query(&filter.unwrap_or_default(), &range.unwrap_or_default(), limit))
+}
+
+#[tauri::command]
+pub async fn clear_events(app_handle: tauri::AppHandle, state: tauri::State<'_, AppState>) -> tauri::Result<usize> {
+    let cleared = map_bridge_err(state.events.clear())?;
+    tray::refresh_stats(&app_handle);
+    Ok(cleared)
+}
+
+/// Rename totals from the event log: overall, by folder, extension and day,
+/// with error and undo counts.
+#[tauri::command]
+pub async fn get_stats(state: tauri::State<'_, AppState>) -> tauri::Result<EventStats> {
+    map_bridge_err(state.events.stats(now_millis()))
+}
+
+#[tauri::command]
+pub async fn get_attention_items(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<AttentionItem>> {
+    Ok(state.attention.items())
+}
+
+#[tauri::command]
+pub async fn resolve_attention_item(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: u64,
+) -> tauri::Result<Vec<AttentionItem>> {
+    let remaining = state.attention.resolve(id);
+    tray::refresh_attention(&app_handle);
+    Ok(remaining)
+}
+
+#[tauri::command]
+pub async fn clear_attention(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> tauri::Result<()> {
+    state.attention.clear();
+    tray::refresh_attention(&app_handle);
+    Ok(())
+}
+
+/// Recurring problems for the Error Center, most recent first.
+#[tauri::command]
+pub async fn list_issues(state: tauri::State<'_, AppState>) -> tauri::Result<Vec<Issue>> {
+    Ok(state.issues.list(&state))
+}
+
+/// Apply one of an issue's `remedies`. `target` is the replacement folder for
+/// [`Remedy::Relocate`]. Returns the issues that remain.
+#[tauri::command]
+pub async fn resolve_issue(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    id: String,
+    remedy: Remedy,
+    target: Option<String>,
+) -> tauri::Result<Vec<Issue>> {
+    let Some(issue) = state.issues.list(&state).into_iter().find(|issue| issue.id == id) else {
+        return map_bridge_err(Err(format!("No open issue {}", id)));
+    };
+    if !issue.remedies.contains(&remedy) {
+        return map_bridge_err(Err(format!("{:?} does not apply to this issue", remedy)));
+    }
+    let directory = issue.directory.clone().unwrap_or_default();
+    match remedy {
+        Remedy::GrantAccess => map_bridge_err(issues::open_privacy_settings())?,
+        Remedy::Relocate => {
+            let Some(target) = target.filter(|target| !target.trim().is_empty()) else {
+                return map_bridge_err(Err("A folder to watch instead is required".to_string()));
+            };
+            map_bridge_err(bridge::add_watch_dir(&state.bridge, target).await)?;
+            map_bridge_err(bridge::remove_watch_dir(&state.bridge, directory).await)?;
+        }
+        Remedy::Remove => {
+            map_bridge_err(bridge::remove_watch_dir(&state.bridge, directory).await)?;
+        }
+        Remedy::PreviewOnly => {
+            map_bridge_err(bridge::set_directory_dry_run(&state.bridge, directory, true).await)?;
+        }
+        Remedy::RestartService => bridge::restart_bridge(&app_handle).await,
+        Remedy::RepairHistory => {
+            map_bridge_err(bridge::repair_history(&state.bridge, "salvage").await)?;
+        }
+        Remedy::RestoreHistory => {
+            map_bridge_err(bridge::repair_history(&state.bridge, "restore").await)?;
+        }
+        Remedy::KeepLocalSettings => {
+            map_bridge_err(bridge::resolve_sync_conflict(&state.bridge, "local").await)?;
+        }
+        Remedy::UseSyncedSettings => {
+            map_bridge_err(bridge::resolve_sync_conflict(&state.bridge, "remote").await)?;
+        }
+        Remedy::Dismiss => {}
+    }
+    for attention_id in &issue.attention_ids {
+        state.attention.resolve(*attention_id);
+    }
+    if issue.category == IssueCategory::BridgeRestart {
+        state.issues.dismiss_bridge();
+    }
+    tray::refresh_attention(&app_handle);
+    Ok(state.issues.list(&state))
+}
+
+/// Compare a renamed file with the one that already held the name it wanted.
+/// Both paths are relative to `directory`.
+#[tauri::command]
+pub async fn compare_conflict(directory: String, incoming: String, existing: String) -> tauri::Result<ConflictComparison> {
+    let directory = Path::new(&directory);
+    map_bridge_err(conflict::compare(&directory.join(incoming), &directory.join(existing)))
+}
+
+/// A cached thumbnail of any file as a `data:` URL, at most `size` pixels
+/// (default 256) on its longest edge.
+#[tauri::command]
+pub async fn get_thumbnail(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+    size: Option<u32>,
+) -> tauri::Result<String> {
+    let cache_dir = thumbnail::cache_dir(&app_handle, state.status().data_dir.as_deref())?;
+    let size = size.unwrap_or(thumbnail::DEFAULT_SIZE);
+    map_bridge_err(thumbnail::thumbnail(&cache_dir, Path::new(&path), size))
 }
@@ -0,0 +1,250 @@
+use std::cmp::Reverse;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::activity::{now_millis, ActivityEntry, ActivityKind};
+use crate::attention::{AttentionItem, AttentionKind};
+use crate::bridge::{HistoryHealth, ServiceStatus};
+use crate::state::AppState;
+
+/// Sidecar disconnects considered when counting restarts.
+const MAX_DISCONNECTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssueCategory {
+    Permission,
+    MissingDirectory,
+    Collision,
+    BridgeRestart,
+    /// Rename history can't be saved and is kept in memory.
+    History,
+    /// Settings changed here and on another machine syncing through the same folder.
+    SettingsSync,
+    Error,
+}
+
+/// A fix the Error Center can apply to an issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Remedy {
+    /// Open the system privacy settings so the app can be granted file access.
+    GrantAccess,
+    /// Watch a different folder in place of a missing one.
+    Relocate,
+    /// Stop watching a missing folder.
+    Remove,
+    /// Only preview renames in the directory, so collisions stop being applied.
+    PreviewOnly,
+    RestartService,
+    /// Write the history that could be read, plus newer renames, back to disk.
+    RepairHistory,
+    /// Replace the history with its latest backup, plus newer renames.
+    RestoreHistory,
+    /// Settle a settings conflict by syncing this machine's settings to the others.
+    KeepLocalSettings,
+    /// Settle a settings conflict by taking the synced folder's settings.
+    UseSyncedSettings,
+    Dismiss,
+}
+
+/// A recurring problem, aggregated from individual failures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Issue {
+    /// Stable across refreshes: the category plus the directory, if any.
+    pub id: String,
+    pub category: IssueCategory,
+    pub directory: Option<String>,
+    /// How many failures the issue stands for.
+    pub count: usize,
+    /// The most recent failure's message.
+    pub message: String,
+    #[serde(rename = "lastSeen")]
+    pub last_seen: u64,
+    pub remedies: Vec<Remedy>,
+    /// Attention items folded into this issue, cleared when it is resolved.
+    #[serde(skip)]
+    pub attention_ids: Vec<u64>,
+}
+
+impl Issue {
+    fn new(category: IssueCategory, directory: Option<String>, message: String, last_seen: u64) -> Self {
+        let id = match &directory {
+            Some(dir) => format!("{}:{}", category_key(category), dir),
+            None => category_key(category).to_string(),
+        };
+        Self {
+            id,
+            category,
+            remedies: remedies_for(category, directory.is_some()),
+            directory,
+            count: 1,
+            message,
+            last_seen,
+            attention_ids: Vec::new(),
+        }
+    }
+}
+
+/// Group the current attention items, missing watched folders, and sidecar
+/// disconnects since `bridge_dismissed_at` into issues, most recent first.
+fn collect(
+    attention: &[AttentionItem],
+    status: &ServiceStatus,
+    disconnects: &[ActivityEntry],
+    bridge_disconnected: bool,
+    bridge_dismissed_at: u64,
+) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = Vec::new();
+
+    for item in attention {
+        let category = match item.kind {
+            AttentionKind::Permission => IssueCategory::Permission,
+            AttentionKind::Conflict => IssueCategory::Collision,
+            AttentionKind::Error => IssueCategory::Error,
+        };
+        let directory = item.directory.clone();
+        match issues.iter_mut().find(|issue| issue.category == category && issue.directory == directory) {
+            Some(issue) => {
+                issue.count += 1;
+                if item.timestamp >= issue.last_seen {
+                    issue.last_seen = item.timestamp;
+                    issue.message = item.message.clone();
+                }
+                issue.attention_ids.push(item.id);
+            }
+            None => {
+                let mut issue = Issue::new(category, directory, item.message.clone(), item.timestamp);
+                issue.attention_ids.push(item.id);
+                issues.push(issue);
+            }
+        }
+    }
+
+    for dir in &status.directories {
+        if !Path::new(dir).is_dir() {
+            let message = format!("{} no longer exists", dir);
+            issues.push(Issue::new(IssueCategory::MissingDirectory, Some(dir.clone()), message, 0));
+        }
+    }
+
+    let recent: Vec<&ActivityEntry> = disconnects
+        .iter()
+        .filter(|entry| entry.kind == ActivityKind::BridgeDisconnected && entry.timestamp > bridge_dismissed_at)
+        .collect();
+    if bridge_disconnected || !recent.is_empty() {
+        let last_seen = recent.iter().map(|entry| entry.timestamp).max().unwrap_or(0);
+        let mut issue = Issue::new(
+            IssueCategory::BridgeRestart,
+            None,
+            "The background service stopped unexpectedly".to_string(),
+            last_seen,
+        );
+        issue.count = recent.len().max(1);
+        if !bridge_disconnected {
+            issue.remedies.retain(|remedy| *remedy != Remedy::RestartService);
+        }
+        issues.push(issue);
+    }
+
+    if status.history.is_degraded() {
+        let message = history_message(&status.history);
+        let mut issue = Issue::new(IssueCategory::History, None, message, now_millis());
+        if status.history.backup.is_none() {
+            issue.remedies.retain(|remedy| *remedy != Remedy::RestoreHistory);
+        }
+        issues.push(issue);
+    }
+
+    let sync = &status.settings_sync;
+    if let Some(conflict) = sync.conflict.as_ref().filter(|_| sync.is_conflict()) {
+        let message = format!(
+            "Settings changed on this Mac and on {}. Choose which to keep; the other machines will follow.",
+            conflict.device_name
+        );
+        issues.push(Issue::new(IssueCategory::SettingsSync, None, message, conflict.updated_at));
+    }
+
+    issues.sort_by_key(|issue| Reverse(issue.last_seen));
+    issues
+}
+
+/// What went wrong with rename history and what each remedy will do about it.
+fn history_message(history: &HistoryHealth) -> String {
+    let problem = match history.reason.as_deref() {
+        Some("corrupt") => match history.bad_lines {
+            Some(1) => "Rename history is damaged: 1 line can't be read".to_string(),
+            Some(n) => format!("Rename history is damaged: {} lines can't be read", n),
+            None => "Rename history is damaged".to_string(),
+        },
+        Some("disk-full") => "The disk is too full to save rename history".to_string(),
+        _ => format!("Rename history can't be saved ({})", history.detail.as_deref().unwrap_or("I/O error")),
+    };
+    let backup = match &history.backup {
+        Some(backup) => format!(" or restore the backup of {} renames", backup.entries),
+        None => String::new(),
+    };
+    format!("{}. New renames are kept until you repair it{}.", problem, backup)
+}
+
+fn remedies_for(category: IssueCategory, has_directory: bool) -> Vec<Remedy> {
+    match category {
+        IssueCategory::Permission => vec![Remedy::GrantAccess, Remedy::Dismiss],
+        IssueCategory::MissingDirectory => vec![Remedy::Relocate, Remedy::Remove],
+        IssueCategory::Collision if has_directory => vec![Remedy::PreviewOnly, Remedy::Dismiss],
+        IssueCategory::BridgeRestart => vec![Remedy::RestartService, Remedy::Dismiss],
+        IssueCategory::History => vec![Remedy::RepairHistory, Remedy::RestoreHistory],
+        IssueCategory::SettingsSync => vec![Remedy::KeepLocalSettings, Remedy::UseSyncedSettings],
+        IssueCategory::Collision | IssueCategory::Error => vec![Remedy::Dismiss],
+    }
+}
+
+fn category_key(category: IssueCategory) -> &'static str {
+    match category {
+        IssueCategory::Permission => "permission",
+        IssueCategory::MissingDirectory => "missing-directory",
+        IssueCategory::Collision => "collision",
+        IssueCategory::BridgeRestart => "bridge-restart",
+        IssueCategory::History => "history",
+        IssueCategory::SettingsSync => "settings-sync",
+        IssueCategory::Error => "error",
+    }
+}
+
+/// Open the pane where file access is granted. macOS only; elsewhere access
+/// is governed by ordinary file permissions.
+pub fn open_privacy_settings() -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Err("File access is managed through file permissions on this platform".to_string());
+    }
+    std::process::Command::new("open")
+        .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles")
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Remembers which sidecar disconnects the user has already dealt with;
+/// everything else an issue is built from is tracked elsewhere.
+#[derive(Default)]
+pub struct IssueCenter {
+    bridge_dismissed_at: AtomicU64,
+}
+
+impl IssueCenter {
+    pub fn list(&self, state: &AppState) -> Vec<Issue> {
+        collect(
+            &state.attention.items(),
+            &state.status(),
+            &state.activity.latest(&[ActivityKind::BridgeDisconnected], MAX_DISCONNECTS),
+            state.bridge.is_disconnected(),
+            self.bridge_dismissed_at.load(Ordering::SeqCst),
+        )
+    }
+
+    pub fn dismiss_bridge(&self) {
+        self.bridge_dismissed_at.store(now_millis(), Ordering::SeqCst);
+    }
+}
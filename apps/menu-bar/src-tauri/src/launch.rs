@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{async_runtime, AppHandle, Emitter, Manager, Wry};
+
+use crate::bridge::{self, BridgeHandle};
+use crate::pathdisplay::{abbreviate_home, expand_home};
+use crate::state::AppState;
+
+/// Flags the menu bar app is launched with, so a login item or a script can
+/// set up how it starts. Launching again while it runs hands them over to
+/// the running app through the single-instance plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchFlags {
+    /// `--paused`: stop watching once started.
+    pub paused: bool,
+    /// `--profile <id or name>`: turn this rule on and the others off for
+    /// this session; see [`SessionProfiles`].
+    pub profile: Option<String>,
+    /// `--add-dir <path>`, repeatable: watch these folders too.
+    pub add_dirs: Vec<String>,
+    /// `--hidden`: don't bring up the Preferences window, as a second launch
+    /// otherwise does.
+    pub hidden: bool,
+    /// Arguments that aren't flags of ours, logged and otherwise ignored so
+    /// a typo in a login item doesn't keep the app from starting.
+    pub ignored: Vec<String>,
+}
+
+/// How the rules `--profile` turned on or off were before, so they can be
+/// put back when the app quits; the service saves every toggle, so the
+/// choice would otherwise outlive the session. Rules toggled or deleted by
+/// hand since are left as they are. If the app doesn't quit normally, the
+/// rules keep the state `--profile` gave them.
+#[derive(Default)]
+pub struct SessionProfiles {
+    saved: Mutex<BTreeMap<String, bool>>,
+}
+
+impl SessionProfiles {
+    /// Note `id`'s state before `--profile` changes it. A later `--profile`
+    /// keeps the first state noted, the user's own.
+    fn remember(&self, id: &str, enabled: bool) {
+        self.saved.lock().expect("session profiles lock poisoned").entry(id.to_string()).or_insert(enabled);
+    }
+
+    /// Leave `id` as it is on quit, now that it was changed by hand.
+    pub fn forget(&self, id: &str) {
+        self.saved.lock().expect("session profiles lock poisoned").remove(id);
+    }
+
+    /// Put the rules `--profile` changed back as they were.
+    pub async fn restore(&self, bridge: &BridgeHandle) {
+        let saved = std::mem::take(&mut *self.saved.lock().expect("session profiles lock poisoned"));
+        for (id, enabled) in saved {
+            if let Err(err) = bridge::toggle_profile(bridge, id.clone(), Some(enabled)).await {
+                log::warn!("Could not restore rule {} after --profile: {}", id, err);
+            }
+        }
+    }
+}
+
+impl LaunchFlags {
+    /// Whether the flags ask the service for anything.
+    fn has_actions(&self) -> bool {
+        self.paused || self.profile.is_some() || !self.add_dirs.is_empty()
+    }
+}
+
+/// Parse the arguments after the program name; relative folders are taken
+/// from `cwd`, the directory the launch happened in.
+pub fn parse(args: &[String], cwd: &Path) -> LaunchFlags {
+    let mut flags = LaunchFlags::default();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        match name {
+            "--paused" if inline.is_none() => flags.paused = true,
+            "--hidden" if inline.is_none() => flags.hidden = true,
+            "--profile" | "--add-dir" => {
+                // A flag that follows isn't a value: `--profile --hidden` still hides.
+                let next = || args.next_if(|next| !next.starts_with("--")).cloned();
+                let Some(value) = inline.or_else(next).filter(|value| !value.is_empty()) else {
+                    flags.ignored.push(arg.clone());
+                    continue;
+                };
+                if name == "--profile" {
+                    flags.profile = Some(value);
+                } else {
+                    flags.add_dirs.push(resolve(&value, cwd).to_string_lossy().into_owned());
+                }
+            }
+            // Older macOS passes a process serial number to apps opened from Finder.
+            _ if arg.starts_with("-psn_") => {}
+            _ => flags.ignored.push(arg.clone()),
+        }
+    }
+    flags
+}
+
+fn resolve(directory: &str, cwd: &Path) -> PathBuf {
+    let path = expand_home(directory);
+    if path.is_absolute() {
+        path
+    } else {
+        cwd.join(path)
+    }
+}
+
+/// Apply the flags of the first launch, once the service is up.
+pub async fn apply(app: &AppHandle<Wry>, flags: &LaunchFlags) {
+    if !flags.ignored.is_empty() {
+        log::warn!("Ignoring unknown launch arguments: {}", flags.ignored.join(" "));
+    }
+    if !flags.has_actions() {
+        return;
+    }
+    if let Err(err) = apply_actions(app, flags).await {
+        log::warn!("Could not apply launch flags: {}", err);
+    }
+}
+
+/// Hand the flags of a second launch to the running app. Without
+/// `--hidden` the Preferences window is brought up, as before flags existed.
+pub fn forward(app: &AppHandle<Wry>, args: &[String], cwd: &str) {
+    let flags = parse(args.get(1..).unwrap_or_default(), Path::new(cwd));
+    log::info!("Another instance attempted to launch; applying its flags here");
+    if !flags.hidden {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+    let app = app.clone();
+    async_runtime::spawn(async move { apply(&app, &flags).await });
+}
+
+async fn apply_actions(app: &AppHandle<Wry>, flags: &LaunchFlags) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let bridge = state.bridge.clone();
+    for directory in &flags.add_dirs {
+        if !Path::new(directory).is_dir() {
+            log::warn!("Not watching {}: no such folder", abbreviate_home(directory));
+        } else if let Some(excluded) = state.exclusions.excluded_component(Path::new(directory)) {
+            log::warn!("Not watching {}: inside an excluded folder ({})", abbreviate_home(directory), excluded);
+        } else {
+            bridge::add_watch_dir(&bridge, directory.clone()).await?;
+        }
+    }
+    if let Some(wanted) = &flags.profile {
+        let profiles = bridge::get_profiles(&bridge).await?;
+        let Some(chosen) = profiles.iter().find(|profile| profile.id == *wanted || profile.name == *wanted) else {
+            return Err(format!("No rule named {}", wanted));
+        };
+        for profile in &profiles {
+            let enabled = profile.id == chosen.id;
+            if profile.enabled != enabled {
+                state.session_profiles.remember(&profile.id, profile.enabled);
+                bridge::toggle_profile(&bridge, profile.id.clone(), Some(enabled)).await?;
+            }
+        }
+    }
+    if flags.paused {
+        state.pause.cancel();
+        bridge::toggle_running(&bridge, Some(false)).await?;
+    }
+    let status = bridge::get_status(&bridge).await?;
+    let _ = app.emit("service://status", &status);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> LaunchFlags {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse(&args, Path::new("/Users/me/Work"))
+    }
+
+    #[test]
+    fn reads_flags_with_separate_or_inline_values() {
+        let flags =
+            parse_args(&["--paused", "--profile", "work", "--add-dir", "Inbox", "--add-dir=/tmp/drop", "--hidden"]);
+        assert_eq!(
+            flags,
+            LaunchFlags {
+                paused: true,
+                profile: Some("work".to_string()),
+                add_dirs: vec!["/Users/me/Work/Inbox".to_string(), "/tmp/drop".to_string()],
+                hidden: true,
+                ignored: Vec::new(),
+            }
+        );
+        assert!(flags.has_actions());
+        assert!(!parse_args(&["--hidden"]).has_actions());
+    }
+
+    #[test]
+    fn ignores_what_it_does_not_know() {
+        let flags = parse_args(&["-psn_0_12345", "--minimized", "--paused=yes", "--profile"]);
+        assert_eq!(flags.ignored, ["--minimized", "--paused=yes", "--profile"]);
+        assert_eq!(flags, LaunchFlags { ignored: flags.ignored.clone(), ..LaunchFlags::default() });
+
+        let flags = parse_args(&["--profile", "--hidden", "--add-dir", "--paused"]);
+        assert_eq!(flags.ignored, ["--profile", "--add-dir"]);
+        assert!(flags.hidden && flags.paused);
+        assert_eq!(flags.profile, None);
+        assert!(flags.add_dirs.is_empty());
+    }
+
+    #[test]
+    fn session_profiles_keep_the_users_own_state() {
+        let session = SessionProfiles::default();
+        session.remember("work", false);
+        session.remember("home", true);
+        session.remember("work", true);
+        session.forget("home");
+        let saved = session.saved.lock().unwrap().clone();
+        assert_eq!(saved, BTreeMap::from([("work".to_string(), false)]));
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::{RenamePlan, RenamePlanEntry};
+use crate::collate::compare_names;
+
+/// One row of a simulated directory listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingRow {
+    pub name: String,
+    /// The file's current name, when the rules would rename it.
+    #[serde(rename = "renamedFrom")]
+    pub renamed_from: Option<String>,
+}
+
+/// A directory listing as it sorts today and as it would sort after the
+/// planned renames, in Finder order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortPreview {
+    pub directory: String,
+    pub before: Vec<String>,
+    pub after: Vec<ListingRow>,
+    /// Whether renamed files end up listed oldest first.
+    pub chronological: bool,
+    /// Renamed files listed ahead of older renamed files.
+    #[serde(rename = "outOfOrder")]
+    pub out_of_order: Vec<String>,
+}
+
+pub fn simulate(plan: RenamePlan) -> SortPreview {
+    let mut before: Vec<String> = plan.entries.iter().map(|entry| entry.file.clone()).collect();
+    before.sort_by(|a, b| compare_names(a, b));
+
+    let mut entries = plan.entries;
+    entries.sort_by(|a, b| compare_names(listed_name(a), listed_name(b)));
+
+    // Walk the renamed files in listing order; any file older than one
+    // already listed breaks chronological order.
+    let mut newest_seen = 0;
+    let mut out_of_order = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.target.is_some()) {
+        if entry.created_at < newest_seen {
+            out_of_order.push(listed_name(entry).to_string());
+        }
+        newest_seen = newest_seen.max(entry.created_at);
+    }
+
+    let after = entries
+        .into_iter()
+        .map(|entry| match entry.target {
+            Some(target) => ListingRow { name: target, renamed_from: Some(entry.file) },
+            None => ListingRow { name: entry.file, renamed_from: None },
+        })
+        .collect();
+
+    SortPreview { directory: plan.directory, before, after, chronological: out_of_order.is_empty(), out_of_order }
+}
+
+fn listed_name(entry: &RenamePlanEntry) -> &str {
+    entry.target.as_deref().unwrap_or(&entry.file)
+}
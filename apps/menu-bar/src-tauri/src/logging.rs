@@ -0,0 +1,285 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::persist::{load_json, save_json};
+
+/// Start of this crate's log targets, which can be left off when naming one,
+/// e.g. `bridge` for `namefix_menu_bar::bridge`.
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+/// Levels set at runtime by target, overriding `RUST_LOG` for that target and
+/// everything under it.
+static OVERRIDES: RwLock<BTreeMap<String, LevelFilter>> = RwLock::new(BTreeMap::new());
+
+/// The most verbose level `RUST_LOG` (or the `info` default) allows anywhere.
+static ENV_LEVEL: OnceLock<LevelFilter> = OnceLock::new();
+
+/// Whether file paths in log lines are replaced with [`redact_path`] stand-ins.
+/// On until the service's status says otherwise.
+static PRIVACY: AtomicBool = AtomicBool::new(true);
+
+/// What a path in a log line can be followed by, besides a quote or line end.
+const PATH_ENDS: [&str; 4] = [" → ", " -> ", ", ", ": "];
+
+/// Filters by [`OVERRIDES`] first, then by `RUST_LOG`.
+struct RuntimeLogger {
+    env: env_logger::Logger,
+    /// Writes whatever reaches it; the filtering happens above.
+    writer: env_logger::Logger,
+}
+
+impl Log for RuntimeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match override_for(metadata.target()) {
+            Some(level) => metadata.level() <= level,
+            None => self.env.enabled(metadata),
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if !PRIVACY.load(Ordering::Relaxed) {
+            self.writer.log(record);
+            return;
+        }
+        let text = record.args().to_string();
+        self.writer.log(
+            &Record::builder()
+                .args(format_args!("{}", redact_paths(&text)))
+                .metadata(record.metadata().clone())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn flush(&self) {
+        self.writer.flush();
+    }
+}
+
+/// Install the logger. `RUST_LOG` still sets the starting levels; targets
+/// given a level with [`LogLevelStore::set`] follow that instead.
+pub fn init() {
+    let env = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let writer = env_logger::Builder::new().filter_level(LevelFilter::Trace).build();
+    let _ = ENV_LEVEL.set(env.filter());
+    if log::set_boxed_logger(Box::new(RuntimeLogger { env, writer })).is_ok() {
+        update_max_level();
+    }
+}
+
+fn override_for(target: &str) -> Option<LevelFilter> {
+    let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+    let overrides = OVERRIDES.read().expect("log levels lock poisoned");
+    overrides
+        .iter()
+        .filter(|(name, _)| {
+            target.strip_prefix(name.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, level)| *level)
+}
+
+/// Let the `log` macros through up to the most verbose level anything wants.
+fn update_max_level() {
+    let env = ENV_LEVEL.get().copied().unwrap_or(LevelFilter::Info);
+    let overrides = OVERRIDES.read().expect("log levels lock poisoned");
+    log::set_max_level(overrides.values().copied().fold(env, Ord::max));
+}
+
+/// Log full paths, or hashed stand-ins for them when `enabled`. Follows the
+/// service's `logPrivacy` setting, so the sidecar's logs and these match.
+pub fn set_privacy(enabled: bool) {
+    PRIVACY.store(enabled, Ordering::Relaxed);
+}
+
+/// A stand-in for `path` that keeps its extension, e.g. `…/#3f2a9c1e.jpg`:
+/// the first 8 hex digits of the path's SHA-256, as the service's logger
+/// writes them, so lines about one file line up across both logs.
+pub fn redact_path(path: &str) -> String {
+    let hash: String = Sha256::digest(path.as_bytes())[..4].iter().map(|byte| format!("{:02x}", byte)).collect();
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| (1..=8).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+    match ext {
+        Some(ext) => format!("…/#{}.{}", hash, ext),
+        None => format!("…/#{}", hash),
+    }
+}
+
+/// How a log line shows `name`, a bare file name in `directory`: while
+/// privacy is on, the [`redact_path`] stand-in for its full path, since
+/// [`redact_paths`] only finds names that start with a root.
+pub fn file_name(directory: &str, name: &str) -> String {
+    hide_name(PRIVACY.load(Ordering::Relaxed), directory, name)
+}
+
+fn hide_name(private: bool, directory: &str, name: &str) -> String {
+    if !private {
+        return name.to_string();
+    }
+    redact_path(&Path::new(directory).join(name).to_string_lossy())
+}
+
+/// `text` with every absolute or home-relative path replaced by
+/// [`redact_path`]. A path starts the text or follows whitespace, a quote,
+/// `(` or `=`, and runs to a quote, the line end or one of [`PATH_ENDS`], so
+/// names with spaces are caught whole.
+pub fn redact_paths(text: &str) -> Cow<'_, str> {
+    let mut redacted = String::new();
+    let mut copied = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        let boundary = text[..index].chars().next_back().is_none_or(|c| c.is_whitespace() || "'\"`(=".contains(c));
+        if boundary && starts_path(rest) {
+            let len = path_len(rest);
+            redacted.push_str(&text[copied..index]);
+            redacted.push_str(&redact_path(&rest[..len]));
+            index += len;
+            copied = index;
+            continue;
+        }
+        index += rest.chars().next().map_or(1, char::len_utf8);
+    }
+    if copied == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(&text[copied..]);
+    Cow::Owned(redacted)
+}
+
+fn starts_path(text: &str) -> bool {
+    let mut chars = text.chars();
+    let after_root = match (chars.next(), chars.next(), chars.next()) {
+        (Some('/'), next, _) => next,
+        (Some('~'), Some('/'), next) => next,
+        (Some(drive), Some(':'), Some('\\')) if drive.is_ascii_alphabetic() => chars.next(),
+        _ => None,
+    };
+    after_root.is_some_and(|c| !c.is_whitespace() && !"'\"`".contains(c))
+}
+
+/// Length of the path at the start of `text`, without trailing whitespace.
+fn path_len(text: &str) -> usize {
+    let quote = text.find(['\'', '"', '`', '\n']).unwrap_or(text.len());
+    let end = PATH_ENDS.iter().filter_map(|sep| text.find(sep)).fold(quote, usize::min);
+    text[..end].trim_end().len()
+}
+
+/// The levels in effect, as shown in the diagnostics card.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevels {
+    /// What `RUST_LOG` allows for targets without a level of their own.
+    pub default: String,
+    pub targets: BTreeMap<String, String>,
+}
+
+/// Log levels set at runtime, persisted next to the other menu bar settings
+/// so a target turned up to debug a problem stays that way across restarts.
+pub struct LogLevelStore {
+    path: PathBuf,
+    levels: Mutex<BTreeMap<String, String>>,
+}
+
+impl LogLevelStore {
+    pub fn load(path: PathBuf) -> Self {
+        let mut levels: BTreeMap<String, String> = load_json(&path);
+        levels.retain(|target, level| match LevelFilter::from_str(level) {
+            Ok(_) => true,
+            Err(_) => {
+                log::warn!("Ignoring unknown log level {} for {}", level, target);
+                false
+            }
+        });
+        apply(&levels);
+        Self { path, levels: Mutex::new(levels) }
+    }
+
+    pub fn get(&self) -> LogLevels {
+        let levels = self.levels.lock().expect("log levels lock poisoned");
+        levels_in_effect(&levels)
+    }
+
+    /// Log `target` (a module such as `bridge`, or another crate such as
+    /// `tauri`) at `level`, or back at the `RUST_LOG` level when None or
+    /// "default".
+    pub fn set(&self, target: &str, level: Option<&str>) -> Result<LogLevels, String> {
+        let target = target.trim();
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        if target.is_empty() {
+            return Err("Log target is empty".to_string());
+        }
+        let mut levels = self.levels.lock().expect("log levels lock poisoned");
+        let mut updated = levels.clone();
+        match level.map(str::trim).filter(|level| !level.is_empty() && !level.eq_ignore_ascii_case("default")) {
+            Some(level) => {
+                let level = LevelFilter::from_str(level).map_err(|_| format!("Unknown log level: {}", level))?;
+                updated.insert(target.to_string(), level.as_str().to_ascii_lowercase());
+            }
+            None => {
+                updated.remove(target);
+            }
+        }
+        save_json(&self.path, &updated)?;
+        apply(&updated);
+        log::info!("Log level for {} set to {}", target, updated.get(target).map_or("default", String::as_str));
+        *levels = updated;
+        Ok(levels_in_effect(&levels))
+    }
+}
+
+fn apply(levels: &BTreeMap<String, String>) {
+    let parsed = levels.iter().filter_map(|(target, level)| Some((target.clone(), LevelFilter::from_str(level).ok()?)));
+    *OVERRIDES.write().expect("log levels lock poisoned") = parsed.collect();
+    update_max_level();
+}
+
+fn levels_in_effect(levels: &BTreeMap<String, String>) -> LogLevels {
+    let default = ENV_LEVEL.get().copied().unwrap_or(LevelFilter::Info);
+    LogLevels { default: default.as_str().to_ascii_lowercase(), targets: levels.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_file_names_are_hashed_with_their_folder() {
+        let line = format!(
+            "File event: applied {} → {}",
+            hide_name(true, "/Users/me/Downloads", "IMG 0042.png"),
+            hide_name(true, "/Users/me/Downloads", "beach trip.png")
+        );
+        let expected = format!(
+            "File event: applied {} → {}",
+            redact_path("/Users/me/Downloads/IMG 0042.png"),
+            redact_path("/Users/me/Downloads/beach trip.png")
+        );
+        assert_eq!(line, expected);
+        assert!(!line.contains("IMG") && !line.contains("beach"), "{}", line);
+        assert_eq!(redact_paths(&line), line);
+        assert_eq!(hide_name(false, "/Users/me/Downloads", "beach trip.png"), "beach trip.png");
+    }
+
+    #[test]
+    fn rooted_paths_are_redacted_in_place() {
+        let line = "Watching /Users/me/Screen Shots, and ~/Desktop";
+        let expected = format!("Watching {}, and {}", redact_path("/Users/me/Screen Shots"), redact_path("~/Desktop"));
+        assert_eq!(redact_paths(line), expected);
+        assert_eq!(redact_paths("File event: applied a.png → b.png"), "File event: applied a.png → b.png");
+    }
+}
@@ -1,28 +1,193 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod accessibility;
+mod actions;
+mod activity;
+mod api;
+mod attention;
 mod bridge;
+mod calendar;
+mod cli;
+mod clipboard_fix;
+mod collate;
+mod config;
+mod conflict;
+mod email;
+mod engine;
+mod entries;
+mod events;
+mod exclusions;
+mod expr;
+mod folder_drop;
+mod hotkeys;
 mod ipc;
+mod issues;
+mod launch;
+mod listing;
+mod logging;
+#[cfg(not(feature = "native-engine"))]
+mod namehook;
+mod notify;
+mod pathdisplay;
+mod pause;
+mod persist;
+mod pipeline;
+mod plugins;
+mod preview;
+mod push;
+mod report;
+mod shortcuts;
+mod state;
+mod status;
+mod status_page;
+mod telemetry;
+mod thumbnail;
 mod tray;
+mod user_switch;
+mod volume;
+mod wake;
+mod webhook;
 
-use bridge::{init_bridge, BridgeState};
-use tauri::{Manager, RunEvent, WindowEvent};
+use bridge::connect_bridge;
+use tauri::{DragDropEvent, Emitter, Manager, RunEvent, WindowEvent};
 use ipc::{
+    activate_entry,
+    add_rule,
     add_watch_dir,
+    apply_plan,
+    clear_attention,
+    clear_events,
+    compare_conflict,
+    compare_names,
+    copy_to_clipboard,
+    create_api_token,
+    delete_import_preset,
+    delete_pipeline,
     delete_profile,
+    drop_queue_item,
+    eject_volume,
+    enable_plugin,
+    end_session,
+    eval_expression_preview,
+    export_config,
+    export_history,
+    export_history_report,
+    export_session,
+    export_sessions_calendar,
+    export_sync_key,
+    find_peers,
+    get_accessibility_settings,
+    get_activity,
+    get_api_access_log,
+    get_attention_items,
+    get_audit_log,
+    get_benchmark_results,
+    get_dir_config,
+    get_directory_usage,
+    get_email_digest,
+    get_entry_click_action,
+    get_history,
+    get_log_levels,
+    get_notifications_enabled,
+    get_open_with_apps,
+    get_peer_status,
+    get_pipelines,
     get_profile,
     get_profiles,
+    get_push_endpoint,
+    get_queue,
+    get_shortcuts,
+    get_stats,
     get_status,
+    get_status_page,
+    get_telemetry_preview,
+    get_thumbnail,
+    get_watch_exclusions,
+    get_webhook,
+    import_config,
+    import_sync_key,
+    invoke_action,
+    list_actions,
+    list_api_tokens,
     list_directories,
+    list_import_presets,
+    list_issues,
+    list_plugins,
+    list_rules,
+    list_sessions,
+    merge_history,
+    open_with,
+    pair_peer,
+    pause_for,
+    plan_folder,
+    preview_plugin_transform,
+    preview_renames,
+    preview_sort_order,
+    prune_history,
+    query_events,
+    quick_look,
+    redo,
+    remove_rule,
     remove_watch_dir,
+    reorder_directories,
     reorder_profiles,
+    reorder_queue,
+    reorder_rules,
+    resolve_attention_item,
+    resolve_issue,
+    reveal_file,
+    revoke_api_token,
+    run_benchmark,
+    run_import_preset,
+    run_once,
+    search_history,
+    send_email_digest,
+    set_accessibility_settings,
+    set_data_dir,
+    set_dir_config,
+    set_directory_dry_run,
+    set_directory_group,
+    set_directory_hot_folder,
+    set_directory_origin_rule,
+    set_directory_pinned,
+    set_directory_priority,
     set_dry_run,
+    set_email_digest,
+    set_entry_click_action,
+    set_http_security,
+    set_import_preset,
+    set_lan_pairing,
     set_launch_on_login,
+    set_log_level,
+    set_log_privacy,
+    set_notifications_enabled,
+    set_observer_mode,
+    set_pipeline,
     set_profile,
+    set_push_endpoint,
+    set_queue_paused,
+    set_shortcut,
+    set_status_page,
+    set_sync_dir,
+    set_telemetry_enabled,
+    set_watch_backend,
+    set_watch_exclusions,
+    set_webhook,
+    snooze_directory,
+    start_pairing,
+    start_session,
     toggle_profile,
     toggle_running,
+    trace_pipeline,
     undo,
+    undo_entry,
+    undo_session,
+    unpair_peer,
+    update_rule,
 };
-use tray::{init_tray, register_status_listener, sync_autostart, TrayState};
+use plugins::PluginHost;
+use state::AppState;
+use tray::{init_tray, install_tray, register_status_listener};
 
 #[cfg(target_os = "macos")]
 use tauri::ActivationPolicy;
@@ -40,84 +205,249 @@ fn autostart_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
 }
 
 fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    // `namefix status` and the other commands run and exit without the app.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = cli::parse(&args) {
+        std::process::exit(cli::run(command));
+    }
+    // Anything else is launch flags: `--paused`, `--profile`, `--add-dir`, `--hidden`.
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let flags = launch::parse(&args, &cwd);
+
+    logging::init();
     log::info!("Namefix Menu Bar starting...");
 
     tauri::Builder::default()
         .plugin(autostart_plugin())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            log::info!("Another instance attempted to launch; focusing existing window");
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
-            }
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            launch::forward(app, &args, &cwd);
         }))
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
                 api.prevent_close();
                 let _ = window.hide();
             }
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                folder_drop::add_dropped(window.app_handle(), paths.clone());
+            }
+            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
+            activate_entry,
+            add_rule,
             add_watch_dir,
+            apply_plan,
+            clear_attention,
+            clear_events,
+            compare_conflict,
+            compare_names,
+            copy_to_clipboard,
+            create_api_token,
+            delete_import_preset,
+            delete_pipeline,
             delete_profile,
+            drop_queue_item,
+            eject_volume,
+            enable_plugin,
+            end_session,
+            eval_expression_preview,
+            export_config,
+            export_history,
+            export_history_report,
+            export_session,
+            export_sessions_calendar,
+            export_sync_key,
+            find_peers,
+            get_accessibility_settings,
+            get_activity,
+            get_api_access_log,
+            get_attention_items,
+            get_audit_log,
+            get_benchmark_results,
+            get_dir_config,
+            get_directory_usage,
+            get_email_digest,
+            get_entry_click_action,
+            get_history,
+            get_log_levels,
+            get_notifications_enabled,
+            get_open_with_apps,
+            get_peer_status,
+            get_pipelines,
             get_profile,
             get_profiles,
+            get_push_endpoint,
+            get_queue,
+            get_shortcuts,
+            get_stats,
             get_status,
+            get_status_page,
+            get_telemetry_preview,
+            get_thumbnail,
+            get_watch_exclusions,
+            get_webhook,
+            import_config,
+            import_sync_key,
+            invoke_action,
+            list_actions,
+            list_api_tokens,
             list_directories,
+            list_import_presets,
+            list_issues,
+            list_plugins,
+            list_rules,
+            list_sessions,
+            merge_history,
+            open_with,
+            pair_peer,
+            pause_for,
+            plan_folder,
+            preview_plugin_transform,
+            preview_renames,
+            preview_sort_order,
+            prune_history,
+            query_events,
+            quick_look,
+            redo,
+            remove_rule,
             remove_watch_dir,
+            reorder_directories,
             reorder_profiles,
+            reorder_queue,
+            reorder_rules,
+            resolve_attention_item,
+            resolve_issue,
+            reveal_file,
+            revoke_api_token,
+            run_benchmark,
+            run_import_preset,
+            run_once,
+            search_history,
+            send_email_digest,
+            set_accessibility_settings,
+            set_data_dir,
+            set_dir_config,
+            set_directory_dry_run,
+            set_directory_group,
+            set_directory_hot_folder,
+            set_directory_origin_rule,
+            set_directory_pinned,
+            set_directory_priority,
             set_dry_run,
+            set_email_digest,
+            set_entry_click_action,
+            set_http_security,
+            set_import_preset,
+            set_lan_pairing,
             set_launch_on_login,
+            set_log_level,
+            set_log_privacy,
+            set_notifications_enabled,
+            set_observer_mode,
+            set_pipeline,
             set_profile,
+            set_push_endpoint,
+            set_queue_paused,
+            set_shortcut,
+            set_status_page,
+            set_sync_dir,
+            set_telemetry_enabled,
+            set_watch_backend,
+            set_watch_exclusions,
+            set_webhook,
+            snooze_directory,
+            start_pairing,
+            start_session,
             toggle_profile,
             toggle_running,
-            undo
+            trace_pipeline,
+            undo,
+            undo_entry,
+            undo_session,
+            unpair_peer,
+            update_rule
         ])
-        .setup(|app| {
+        .setup(move |app| {
             #[cfg(target_os = "macos")]
             {
                 app.set_activation_policy(ActivationPolicy::Accessory);
             }
             let app_handle = app.handle().clone();
-            match tauri::async_runtime::block_on(async { init_bridge(&app_handle).await }) {
-                Ok(bridge) => {
-                    let tray_state = init_tray(&app_handle, &bridge)
-                        .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
-                    register_status_listener(&app_handle);
-                    app.manage::<BridgeState>(bridge);
-                    app.manage::<TrayState>(tray_state);
+            let config_dir = app_handle.path().app_config_dir()?;
+            let plugin_host = PluginHost::new(config_dir.join("plugins"))?;
+            let data_dir = app_handle.path().app_data_dir()?;
+            app.manage(AppState::new(&config_dir, &data_dir, plugin_host));
+            app_handle.state::<AppState>().status_page.start(&app_handle);
+            cli::serve(&app_handle);
 
-                    if let Some(window) = app_handle.get_webview_window("main") {
-                        let _ = window.hide();
-                    }
+            let tray_state = init_tray(&app_handle)
+                .map_err(|err| -> Box<dyn std::error::Error> { Box::new(err) })?;
+            install_tray(&app_handle, tray_state);
+            register_status_listener(&app_handle);
+            connect_bridge(&app_handle);
+            hotkeys::register_all(&app_handle);
 
-                    // Fallback startup sync: if the sidecar's initial status
-                    // event fired before the listener was registered, the
-                    // event-driven sync_autostart never runs. Explicitly
-                    // fetch status here to close the race.
-                    let fallback_handle = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
-                        let bridge_state = fallback_handle.state::<BridgeState>();
-                        match bridge::get_status(bridge_state.inner()).await {
-                            Ok(status) => sync_autostart(&fallback_handle, status.launch_on_login),
-                            Err(e) => log::warn!("Startup autostart sync failed: {}", e),
-                        }
-                    });
+            tauri::async_runtime::spawn(exclusions::sync_to_bridge(app_handle.clone()));
+            tauri::async_runtime::spawn(user_switch::watch(app_handle.clone()));
+            tauri::async_runtime::spawn(wake::watch(app_handle.clone()));
+            tauri::async_runtime::spawn(notify::run_digest(app_handle.clone()));
+            tauri::async_runtime::spawn(email::run(app_handle.clone()));
+            tauri::async_runtime::spawn(webhook::run(app_handle.clone()));
 
-                    Ok(())
+            let plugins_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = plugins_handle.state::<AppState>().plugins.reload().await {
+                    log::warn!("Failed to scan plugins: {}", err);
                 }
-                Err(err) => Err(err.into()),
+            });
+
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.hide();
             }
+
+            // Fetch the initial status off the UI thread and feed it through
+            // the status listener, which fills in the tray and syncs
+            // autostart. This also covers the sidecar's own initial status
+            // event firing before the listener was registered, and brings
+            // back folders from namefix.toml if the service lost its own.
+            let initial_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                match config::restore(&initial_handle).await {
+                    Ok(status) => {
+                        let _ = initial_handle.emit("service://status", status);
+                    }
+                    Err(e) => log::warn!("Initial status fetch failed: {}", e),
+                }
+                launch::apply(&initial_handle, &flags).await;
+            });
+
+            Ok(())
         })
         .build(tauri::generate_context!())
         .expect("error while building Namefix menu bar")
-        .run(|app_handle, event| {
-            if let RunEvent::Exit = event {
-                // Gracefully shut down the Node sidecar before the process exits
-                if let Some(bridge) = app_handle.try_state::<BridgeState>() {
-                    tauri::async_runtime::block_on(bridge.shutdown());
+        .run(|app_handle, event| match event {
+            RunEvent::Exit => {
+                // Stop the background service if this app started it; one left
+                // running by an earlier instance or the command line stays up.
+                if let Some(state) = app_handle.try_state::<AppState>() {
+                    tauri::async_runtime::block_on(async {
+                        state.session_profiles.restore(&state.bridge).await;
+                        state.bridge.close().await;
+                    });
+                }
+            }
+            // Folders dropped on the app's icon, which Info.plist declares it can open
+            #[cfg(target_os = "macos")]
+            RunEvent::Opened { urls } => {
+                let paths = urls.iter().filter_map(|url| url.to_file_path().ok()).collect();
+                folder_drop::add_dropped(app_handle, paths);
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
                 }
             }
+            _ => {}
         });
 }
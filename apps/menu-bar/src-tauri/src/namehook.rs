@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use crate::expr::{ExpressionContext, ExpressionEngine};
+use crate::logging;
+use crate::pipeline::{self, Pipeline};
+use crate::plugins::valid_basename;
+use crate::state::AppState;
+
+/// What the service asks this app while naming a file (its `NameHook`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "phase", content = "params", rename_all = "lowercase")]
+pub enum HookRequest {
+    /// Before a rule with a condition or `<expr:...>` segments is applied.
+    Prepare(RuleRequest),
+    /// With every name a rule produces, before it's applied.
+    Review(NameRequest),
+}
+
+/// A rule about to name a file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleRequest {
+    pub directory: String,
+    pub file: String,
+    pub rule: String,
+    pub size: u64,
+    pub condition: Option<String>,
+    pub template: String,
+}
+
+/// A name one of the service's rules produced.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NameRequest {
+    pub directory: String,
+    pub file: String,
+    pub rule: String,
+    pub size: u64,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookAnswer {
+    /// The rule's template with its expressions filled in.
+    Template(String),
+    /// The name the file should get.
+    Target(String),
+    /// The profile a pipeline handed the file to, to be named by it instead.
+    Handoff(String),
+    /// Why the file should keep its name.
+    Skip(String),
+}
+
+/// Answer `request` from the app's expression engine and plugins.
+pub async fn answer(app: &AppHandle, request: HookRequest) -> HookAnswer {
+    let state = app.state::<AppState>();
+    match request {
+        HookRequest::Prepare(request) => prepare(&state.expressions, &request),
+        HookRequest::Review(request) => {
+            let pipelines = state.pipelines.list().await;
+            match run_pipelines(&pipelines, &state.expressions, &request) {
+                HookAnswer::Target(target) => HookAnswer::Target(review(&state, request, target).await),
+                answer => answer,
+            }
+        }
+    }
+}
+
+/// Check the rule's condition and fill in its template's `<expr:...>`
+/// segments, leaving the rest of the template to the service. An expression
+/// that fails, or that would move the file out of its folder, leaves the file
+/// alone rather than half named.
+fn prepare(engine: &ExpressionEngine, request: &RuleRequest) -> HookAnswer {
+    let context = ExpressionContext {
+        name: request.file.clone(),
+        directory: request.directory.clone(),
+        size: request.size,
+        metadata: json!({ "rule": request.rule }),
+    };
+    if let Some(condition) = request.condition.as_deref().filter(|condition| !condition.trim().is_empty()) {
+        match engine.eval_condition(condition, &context) {
+            Ok(true) => {}
+            Ok(false) => return HookAnswer::Skip("rule's condition is false".to_string()),
+            Err(err) => return HookAnswer::Skip(format!("rule's condition failed: {}", err)),
+        }
+    }
+    match engine.render_template(&request.template, &context) {
+        Ok(template) if valid_basename(&template) => HookAnswer::Template(template),
+        Ok(template) => HookAnswer::Skip(format!("rule's template made {:?}, which isn't a file name", template)),
+        Err(err) => HookAnswer::Skip(format!("rule's template failed: {}", err)),
+    }
+}
+
+/// Run the enabled pipelines in order over the rule's name, each starting
+/// from the name the one before left. A hand-off ends the run; a pipeline
+/// that fails, or that names a path rather than a file, leaves the file alone.
+fn run_pipelines(pipelines: &[Pipeline], engine: &ExpressionEngine, request: &NameRequest) -> HookAnswer {
+    let mut name = request.target.clone();
+    for pipeline in pipelines.iter().filter(|pipeline| pipeline.enabled) {
+        let context = ExpressionContext {
+            name,
+            directory: request.directory.clone(),
+            size: request.size,
+            metadata: json!({ "rule": request.rule, "file": request.file }),
+        };
+        match pipeline::trace(pipeline, &context, engine) {
+            Ok(trace) => match trace.handoff_profile {
+                Some(profile) => return HookAnswer::Handoff(profile),
+                None if valid_basename(&trace.final_name) => name = trace.final_name,
+                None => {
+                    return HookAnswer::Skip(format!(
+                        "pipeline '{}' made {:?}, which isn't a file name",
+                        pipeline.name, trace.final_name
+                    ))
+                }
+            },
+            Err(err) => return HookAnswer::Skip(format!("pipeline '{}' failed: {}", pipeline.name, err)),
+        }
+    }
+    HookAnswer::Target(name)
+}
+
+/// The name the file should get: `target`, as the enabled plugins leave
+/// it. A plugin that fails is logged and its name ignored, so one broken
+/// plugin doesn't hold up renames.
+async fn review(state: &AppState, request: NameRequest, target: String) -> String {
+    let metadata = json!({ "directory": request.directory, "file": request.file, "rule": request.rule });
+    match state.plugins.transform(&target, &metadata).await {
+        Ok(Some(name)) => name,
+        Ok(None) => target,
+        Err(err) => {
+            let file = logging::file_name(&request.directory, &request.file);
+            log::warn!("Keeping {} for {}: {}", logging::file_name(&request.directory, &target), file, err);
+            target
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(condition: Option<&str>, template: &str) -> RuleRequest {
+        RuleRequest {
+            directory: "/Users/me/Downloads".to_string(),
+            file: "scan.pdf".to_string(),
+            rule: "scans".to_string(),
+            size: 2_000_000,
+            condition: condition.map(str::to_string),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn rules_with_expressions_are_prepared() {
+        let engine = ExpressionEngine::new();
+        let prepared = prepare(&engine, &rule(Some("size > 1000000"), "<date>_<expr:stem.to_upper()>"));
+        assert_eq!(prepared, HookAnswer::Template("<date>_SCAN".to_string()));
+
+        let small = prepare(&engine, &rule(Some("size < 1000"), "<date>"));
+        assert_eq!(small, HookAnswer::Skip("rule's condition is false".to_string()));
+        assert!(matches!(prepare(&engine, &rule(Some("size +"), "<date>")), HookAnswer::Skip(_)));
+        assert!(matches!(prepare(&engine, &rule(None, "<expr:nope()>")), HookAnswer::Skip(_)));
+    }
+
+    #[test]
+    fn expressions_cannot_name_a_path() {
+        let engine = ExpressionEngine::new();
+        for template in ["<expr:\"../\" + stem>", "<expr:\"..\">", "<expr:\".\">", "a<expr:\"\\\\\">b"] {
+            let answer = prepare(&engine, &rule(None, template));
+            let skipped = matches!(&answer, HookAnswer::Skip(reason) if reason.ends_with("isn't a file name"));
+            assert!(skipped, "{:?}", answer);
+        }
+    }
+
+    #[test]
+    fn hook_requests_read_the_phase() {
+        let message = json!({
+            "hook": 3,
+            "phase": "review",
+            "params": { "directory": "/tmp", "file": "a.png", "rule": "shots", "size": 12, "target": "b.png" },
+        });
+        let request: HookRequest = serde_json::from_value(message).unwrap();
+        assert!(matches!(request, HookRequest::Review(NameRequest { target, .. }) if target == "b.png"));
+        assert_eq!(serde_json::to_value(HookAnswer::Skip("no".to_string())).unwrap(), json!({ "skip": "no" }));
+    }
+
+    fn pipeline(id: &str, enabled: bool, steps: serde_json::Value) -> Pipeline {
+        serde_json::from_value(json!({ "id": id, "name": id, "enabled": enabled, "entry": "start", "steps": steps }))
+            .unwrap()
+    }
+
+    #[test]
+    fn enabled_pipelines_rename_in_order_and_hand_off() {
+        let engine = ExpressionEngine::new();
+        let request = NameRequest {
+            directory: "/Users/me/Downloads".to_string(),
+            file: "scan.pdf".to_string(),
+            rule: "scans".to_string(),
+            size: 2_000_000,
+            target: "scan.pdf".to_string(),
+        };
+        let big = pipeline(
+            "big",
+            true,
+            json!([
+                { "id": "start", "type": "branch", "condition": "size > 1000000", "then": "tag" },
+                { "id": "tag", "type": "rename", "template": "big-<expr:name>" },
+            ]),
+        );
+        let off = pipeline("off", false, json!([{ "id": "start", "type": "rename", "template": "off-<expr:name>" }]));
+        let upper =
+            pipeline("upper", true, json!([{ "id": "start", "type": "rename", "template": "<expr:name.to_upper()>" }]));
+        let answer = run_pipelines(&[big.clone(), off, upper], &engine, &request);
+        assert_eq!(answer, HookAnswer::Target("BIG-SCAN.PDF".to_string()));
+
+        let handoff = pipeline("docs", true, json!([{ "id": "start", "type": "handoff", "profileId": "docs" }]));
+        assert_eq!(run_pipelines(&[big.clone(), handoff], &engine, &request), HookAnswer::Handoff("docs".to_string()));
+        let serialized = serde_json::to_value(HookAnswer::Handoff("docs".to_string())).unwrap();
+        assert_eq!(serialized, json!({ "handoff": "docs" }));
+
+        let broken =
+            pipeline("broken", true, json!([{ "id": "start", "type": "rename", "template": "<expr:nope()>" }]));
+        assert!(matches!(
+            run_pipelines(&[big, broken], &engine, &request),
+            HookAnswer::Skip(reason) if reason.starts_with("pipeline 'broken' failed")
+        ));
+
+        let escape =
+            pipeline("escape", true, json!([{ "id": "start", "type": "rename", "template": "../<expr:name>" }]));
+        assert_eq!(
+            run_pipelines(&[escape], &engine, &request),
+            HookAnswer::Skip("pipeline 'escape' made \"../scan.pdf\", which isn't a file name".to_string())
+        );
+    }
+}
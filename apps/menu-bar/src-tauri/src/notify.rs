@@ -0,0 +1,242 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{async_runtime, AppHandle, Emitter, Manager, Wry};
+
+use crate::entries::{self, RenameEntry};
+use crate::persist::{load_json, save_json};
+use crate::state::AppState;
+
+/// How often held notifications are summarized in minimal-notification mode.
+const DIGEST_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Toasts held back while minimal notifications are on, oldest first.
+#[derive(Default)]
+pub struct NotificationDigest {
+    held: Mutex<Vec<(String, String)>>,
+}
+
+/// Show a toast in the app's windows. With minimal notifications on, info and
+/// warn toasts are held for the next summary; errors always show at once.
+pub fn toast(app: &AppHandle<Wry>, message: impl Into<String>, level: &str) {
+    let message = message.into();
+    let state = app.state::<AppState>();
+    if level != "error" && state.accessibility.get().minimal_notifications {
+        let mut held = state.notifications.held.lock().expect("digest lock poisoned");
+        held.push((message, level.to_string()));
+        return;
+    }
+    let _ = app.emit("service://toast", json!({ "message": message, "level": level }));
+}
+
+/// [`toast`] for a `{ message, level }` payload from the service.
+pub fn forward(app: &AppHandle<Wry>, payload: &Value) {
+    let message = payload.get("message").and_then(Value::as_str).unwrap_or_default();
+    let level = payload.get("level").and_then(Value::as_str).unwrap_or("info");
+    toast(app, message, level);
+}
+
+/// Show everything held as one summary toast: the oldest message and a count
+/// of the rest, at the most severe level held.
+pub fn flush(app: &AppHandle<Wry>) {
+    let held = std::mem::take(&mut *app.state::<AppState>().notifications.held.lock().expect("digest lock poisoned"));
+    let Some((first, _)) = held.first() else {
+        return;
+    };
+    let level = if held.iter().any(|(_, level)| level == "warn") { "warn" } else { "info" };
+    let message = match held.len() {
+        1 => first.clone(),
+        n => format!("{} (and {} more update{})", first, n - 1, if n == 2 { "" } else { "s" }),
+    };
+    let _ = app.emit("service://toast", json!({ "message": message, "level": level }));
+}
+
+/// Summarize held notifications every [`DIGEST_INTERVAL`]. Runs for the life of the app.
+pub async fn run_digest(app: AppHandle<Wry>) {
+    loop {
+        tokio::time::sleep(DIGEST_INTERVAL).await;
+        flush(&app);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct SystemNotificationSettings {
+    #[serde(default)]
+    enabled: bool,
+}
+
+/// Whether each rename raises an OS notification, persisted next to the
+/// other menu bar settings. Off by default: a busy folder would flood the
+/// notification center.
+pub struct SystemNotifications {
+    path: PathBuf,
+    settings: Mutex<SystemNotificationSettings>,
+}
+
+impl SystemNotifications {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self { path, settings: Mutex::new(settings) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.lock().expect("notification settings lock poisoned").enabled
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<bool, String> {
+        let mut settings = self.settings.lock().expect("notification settings lock poisoned");
+        let updated = SystemNotificationSettings { enabled };
+        save_json(&self.path, &updated)?;
+        *settings = updated;
+        Ok(enabled)
+    }
+}
+
+/// A button on a rename notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenameAction {
+    Undo,
+    Reveal,
+}
+
+impl RenameAction {
+    fn parse(key: &str) -> Option<Self> {
+        match key.trim() {
+            "undo" => Some(Self::Undo),
+            "reveal" => Some(Self::Reveal),
+            _ => None,
+        }
+    }
+}
+
+/// The rename an `applied` file event describes.
+fn applied_rename(payload: &Value) -> Option<RenameEntry> {
+    if payload.get("kind").and_then(Value::as_str) != Some("applied") {
+        return None;
+    }
+    let directory = Path::new(payload.get("directory").and_then(Value::as_str)?);
+    let file = payload.get("file").and_then(Value::as_str)?;
+    let target = payload.get("target").and_then(Value::as_str)?;
+    Some(RenameEntry {
+        from: directory.join(file).to_string_lossy().into_owned(),
+        to: directory.join(target).to_string_lossy().into_owned(),
+    })
+}
+
+/// Raise an OS notification for a file event from the service when it is a
+/// rename and rename notifications are on. Minimal notifications mutes them
+/// along with routine toasts.
+pub fn rename(app: &AppHandle<Wry>, payload: &Value) {
+    let state = app.state::<AppState>();
+    if !state.system_notifications.enabled() || state.accessibility.get().minimal_notifications {
+        return;
+    }
+    let Some(entry) = applied_rename(payload) else {
+        return;
+    };
+    let body = format!("{} → {}", entry.old_name(), display_name(&entry.to));
+    let app = app.clone();
+    // notify-send blocks until the notification is dismissed, so it waits off the event loop.
+    std::thread::spawn(move || {
+        match show(&body) {
+            Ok(Some(action)) => async_runtime::block_on(perform(&app, action, &entry)),
+            Ok(None) => {}
+            Err(err) => log::warn!("Could not show a rename notification: {}", err),
+        }
+    });
+}
+
+async fn perform(app: &AppHandle<Wry>, action: RenameAction, entry: &RenameEntry) {
+    let result = match action {
+        RenameAction::Undo => {
+            let bridge = app.state::<AppState>().bridge.clone();
+            entries::undo(&bridge, entry).await.map(|_| format!("Restored {}", entry.old_name()))
+        }
+        RenameAction::Reveal => entries::reveal(Path::new(&entry.to)).map(|_| String::new()),
+    };
+    match result {
+        Ok(message) if !message.is_empty() => toast(app, message, "info"),
+        Ok(_) => {}
+        Err(err) => toast(app, err, "warn"),
+    }
+}
+
+/// Show the notification and wait for a button, where the platform offers
+/// them. `osascript` notifications on macOS can't carry buttons; Undo stays
+/// in the tray's recent renames there.
+#[cfg(target_os = "macos")]
+fn show(body: &str) -> Result<Option<RenameAction>, String> {
+    // Title and body go in as arguments, so nothing in a file name needs escaping.
+    let script = "on run argv\ndisplay notification (item 2 of argv) with title (item 1 of argv)\nend run";
+    let status = Command::new("osascript")
+        .args(["-e", script, "Namefix renamed a file", body])
+        .status()
+        .map_err(|err| err.to_string())?;
+    if status.success() {
+        Ok(None)
+    } else {
+        Err(format!("osascript exited with {}", status))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn show(body: &str) -> Result<Option<RenameAction>, String> {
+    let output = Command::new("notify-send")
+        .args(["--app-name=Namefix", "--action=undo=Undo", "--action=reveal=Show in Folder", "--wait"])
+        .args(["Namefix renamed a file", body])
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(RenameAction::parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(target_os = "windows")]
+fn show(_body: &str) -> Result<Option<RenameAction>, String> {
+    Err("rename notifications are not available on Windows yet".to_string())
+}
+
+fn display_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applied_events_describe_renames() {
+        let payload = json!({
+            "kind": "applied",
+            "directory": "/Users/me/Desktop",
+            "file": "Screenshot 1.png",
+            "target": "shot_2025.png",
+            "timestamp": 1,
+        });
+        let entry = applied_rename(&payload).expect("rename");
+        assert_eq!(entry.from, "/Users/me/Desktop/Screenshot 1.png");
+        assert_eq!(entry.to, "/Users/me/Desktop/shot_2025.png");
+    }
+
+    #[test]
+    fn other_events_are_not_renames() {
+        let payload = json!({ "kind": "preview", "directory": "/d", "file": "a", "target": "b" });
+        assert!(applied_rename(&payload).is_none());
+        assert!(applied_rename(&json!({ "kind": "applied", "file": "a" })).is_none());
+    }
+
+    #[test]
+    fn parses_notification_buttons() {
+        assert_eq!(RenameAction::parse("undo\n"), Some(RenameAction::Undo));
+        assert_eq!(RenameAction::parse("reveal"), Some(RenameAction::Reveal));
+        assert_eq!(RenameAction::parse(""), None);
+    }
+}
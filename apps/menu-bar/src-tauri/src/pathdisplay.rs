@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+
+/// Widest path, in characters, shown in a menu title before it is shortened.
+pub const MENU_PATH_WIDTH: usize = 40;
+
+const ELLIPSIS: char = '…';
+
+/// `path` as a person would write it: the home directory abbreviated to `~`
+/// and anything longer than `max_chars` shortened in the middle, so both the
+/// root and the folder name stay visible. Two watched folders that are both
+/// called "Inbox" remain distinguishable, unlike with a bare file name.
+pub fn display_path(path: &str, max_chars: usize) -> String {
+    middle_ellipsize(&abbreviate_home(path), max_chars)
+}
+
+/// Replace a leading home directory with `~`.
+pub fn abbreviate_home(path: &str) -> String {
+    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"));
+    let Ok(home) = home else {
+        return path.to_string();
+    };
+    let home = home.trim_end_matches(['/', '\\']);
+    if home.is_empty() {
+        return path.to_string();
+    }
+    match Path::new(path).strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => format!("~{}{}", MAIN_SEPARATOR, rest.display()),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// Replace a leading `~` with the home directory, undoing [`abbreviate_home`].
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => rest.trim_start_matches(['/', '\\']),
+        _ => return PathBuf::from(path),
+    };
+    match std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")) {
+        Ok(home) if !home.is_empty() && rest.is_empty() => PathBuf::from(home),
+        Ok(home) if !home.is_empty() => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Shorten `text` to at most `max_chars` characters by replacing its middle
+/// with an ellipsis. The tail gets the larger share since it holds the
+/// folder name.
+pub fn middle_ellipsize(text: &str, max_chars: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max_chars || max_chars < 3 {
+        return text.to_string();
+    }
+    let keep = max_chars - 1;
+    let head = keep / 3;
+    let tail = keep - head;
+    let mut shortened: String = chars[..head].iter().collect();
+    shortened.push(ELLIPSIS);
+    shortened.extend(&chars[chars.len() - tail..]);
+    shortened
+}
@@ -0,0 +1,130 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{Days, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::activity::now_millis;
+use crate::bridge::{self, BridgeHandle, ServiceStatus};
+use crate::state::AppState;
+use crate::tray;
+
+/// How often the tray countdown is refreshed while a timed pause is active.
+const TICK: Duration = Duration::from_secs(60);
+
+/// Hour of day (local time) at which "until tomorrow" pauses resume.
+const TOMORROW_RESUME_HOUR: u32 = 8;
+
+/// Longest timed pause, a week; longer ones are cut short to it.
+pub const MAX_PAUSE_MINUTES: u64 = 7 * 24 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PauseDuration {
+    Minutes(u64),
+    UntilTomorrow,
+}
+
+impl PauseDuration {
+    /// Epoch milliseconds at which watching should resume.
+    pub fn resume_at(self, now: u64) -> u64 {
+        match self {
+            PauseDuration::Minutes(minutes) => {
+                now.saturating_add(minutes.min(MAX_PAUSE_MINUTES).saturating_mul(60_000))
+            }
+            PauseDuration::UntilTomorrow => {
+                let morning = NaiveTime::from_hms_opt(TOMORROW_RESUME_HOUR, 0, 0).expect("valid time");
+                Local::now()
+                    .date_naive()
+                    .checked_add_days(Days::new(1))
+                    .and_then(|day| day.and_time(morning).and_local_timezone(Local).earliest())
+                    .map(|at| at.timestamp_millis().max(0) as u64)
+                    .unwrap_or(now + 24 * 60 * 60_000)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Timer {
+    resume_at: Option<u64>,
+    generation: u64,
+}
+
+/// Pending auto-resume for a timed pause. Only the most recently armed timer
+/// fires; arming again or cancelling invalidates earlier tasks.
+#[derive(Default)]
+pub struct PauseTimer {
+    inner: Mutex<Timer>,
+}
+
+impl PauseTimer {
+    pub fn resume_at(&self) -> Option<u64> {
+        self.inner.lock().expect("pause lock poisoned").resume_at
+    }
+
+    pub fn cancel(&self) {
+        let mut inner = self.inner.lock().expect("pause lock poisoned");
+        if inner.resume_at.take().is_some() {
+            inner.generation += 1;
+        }
+    }
+
+    fn arm(&self, resume_at: u64) -> u64 {
+        let mut inner = self.inner.lock().expect("pause lock poisoned");
+        inner.generation += 1;
+        inner.resume_at = Some(resume_at);
+        inner.generation
+    }
+
+    /// The deadline for `generation`, or None if it has been superseded.
+    fn deadline(&self, generation: u64) -> Option<u64> {
+        let inner = self.inner.lock().expect("pause lock poisoned");
+        if inner.generation == generation {
+            inner.resume_at
+        } else {
+            None
+        }
+    }
+}
+
+/// Stop watching now and resume automatically once `duration` has elapsed.
+pub async fn pause_for(app: &AppHandle<Wry>, bridge: &BridgeHandle, duration: PauseDuration) -> Result<ServiceStatus, String> {
+    let status = bridge::toggle_running(bridge, Some(false)).await?;
+    let generation = app.state::<AppState>().pause.arm(duration.resume_at(now_millis()));
+    tray::refresh_status_label(app);
+
+    let app_handle = app.clone();
+    let bridge = bridge.clone();
+    async_runtime::spawn(async move {
+        loop {
+            let Some(deadline) = app_handle.state::<AppState>().pause.deadline(generation) else {
+                return;
+            };
+            let remaining = deadline.saturating_sub(now_millis());
+            if remaining == 0 {
+                break;
+            }
+            tokio::time::sleep(TICK.min(Duration::from_millis(remaining))).await;
+            tray::refresh_status_label(&app_handle);
+        }
+        app_handle.state::<AppState>().pause.cancel();
+        log::info!("Timed pause elapsed; resuming watchers");
+        if let Err(err) = bridge::toggle_running(&bridge, Some(true)).await {
+            log::error!("Failed to resume after timed pause: {}", err);
+        }
+    });
+
+    Ok(status)
+}
+
+/// Compact countdown, e.g. "25m" or "9h 12m".
+pub fn format_remaining(remaining_ms: u64) -> String {
+    let minutes = remaining_ms.div_ceil(60_000);
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    }
+}
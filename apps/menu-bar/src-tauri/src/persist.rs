@@ -0,0 +1,48 @@
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Read a JSON document, falling back to `T::default()` when the file is
+/// missing or unreadable.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).unwrap_or_else(|err| {
+            log::warn!("Ignoring malformed {}: {}", path.display(), err);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Write a JSON document atomically (write to a sibling temp file, then rename).
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let serialized = serde_json::to_vec_pretty(value).map_err(|err| err.to_string())?;
+    write_atomic(path, &serialized)
+}
+
+/// Write `contents` to `path` through a sibling temp file, so readers never
+/// see a half-written file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, contents).map_err(|err| err.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|err| err.to_string())
+}
+
+/// The app's config folder, worked out the way Tauri does but without an
+/// app, so the command line and a headless service agree with it.
+pub fn config_dir() -> PathBuf {
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    let config = if cfg!(target_os = "macos") {
+        home.join("Library/Application Support")
+    } else if cfg!(windows) {
+        std::env::var("APPDATA").map(PathBuf::from).unwrap_or(home)
+    } else {
+        std::env::var("XDG_CONFIG_HOME").map(PathBuf::from).unwrap_or_else(|_| home.join(".config"))
+    };
+    config.join("com.namefix.menubar")
+}
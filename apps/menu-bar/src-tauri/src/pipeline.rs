@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::Mutex;
+
+use crate::expr::{ExpressionContext, ExpressionEngine};
+use crate::persist::{load_json, save_json};
+
+/// A branching rename pipeline. Execution starts at `entry` and follows each
+/// step's successor until a step stops, hands off, or has no successor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub entry: String,
+    pub steps: Vec<PipelineStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: StepKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StepKind {
+    /// Rewrite the name using a template (`<expr:...>` segments are evaluated).
+    Rename {
+        template: String,
+        next: Option<String>,
+    },
+    /// Evaluate `condition` and continue with `then` or `otherwise`.
+    Branch {
+        condition: String,
+        then: String,
+        #[serde(rename = "else")]
+        otherwise: Option<String>,
+    },
+    /// Stop processing; the current name is final.
+    Stop,
+    /// Hand the file to a rename profile.
+    Handoff {
+        #[serde(rename = "profileId")]
+        profile_id: String,
+    },
+}
+
+impl StepKind {
+    fn successors(&self) -> Vec<&str> {
+        match self {
+            StepKind::Rename { next, .. } => next.iter().map(String::as_str).collect(),
+            StepKind::Branch { then, otherwise, .. } => {
+                std::iter::once(then.as_str()).chain(otherwise.as_deref()).collect()
+            }
+            StepKind::Stop | StepKind::Handoff { .. } => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub step: String,
+    pub outcome: String,
+    pub name: String,
+}
+
+/// Dry-run result of running a pipeline over a single file name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineTrace {
+    pub steps: Vec<TraceEntry>,
+    #[serde(rename = "finalName")]
+    pub final_name: String,
+    #[serde(rename = "handoffProfile")]
+    pub handoff_profile: Option<String>,
+}
+
+/// Check that step ids are unique, every reference resolves, handoffs target a
+/// known profile, every step can be reached from the entry, and the step
+/// graph is acyclic.
+pub fn validate(pipeline: &Pipeline, profile_ids: &[String]) -> Result<(), String> {
+    let mut steps: HashMap<&str, &PipelineStep> = HashMap::new();
+    for step in &pipeline.steps {
+        if steps.insert(step.id.as_str(), step).is_some() {
+            return Err(format!("duplicate step id '{}'", step.id));
+        }
+    }
+    if !steps.contains_key(pipeline.entry.as_str()) {
+        return Err(format!("entry step '{}' does not exist", pipeline.entry));
+    }
+    for step in &pipeline.steps {
+        for target in step.kind.successors() {
+            if !steps.contains_key(target) {
+                return Err(format!("step '{}' references unknown step '{}'", step.id, target));
+            }
+        }
+        if let StepKind::Handoff { profile_id } = &step.kind {
+            if !profile_ids.iter().any(|id| id == profile_id) {
+                return Err(format!("step '{}' hands off to unknown profile '{}'", step.id, profile_id));
+            }
+        }
+    }
+
+    let mut reached: HashSet<&str> = HashSet::from([pipeline.entry.as_str()]);
+    let mut pending = vec![pipeline.entry.as_str()];
+    while let Some(node) = pending.pop() {
+        pending.extend(steps[node].kind.successors().into_iter().filter(|next| reached.insert(next)));
+    }
+    if let Some(step) = pipeline.steps.iter().find(|step| !reached.contains(step.id.as_str())) {
+        return Err(format!("step '{}' cannot be reached from the entry step", step.id));
+    }
+
+    // Iterative DFS with white/grey/black colouring; a grey hit is a back edge.
+    let mut visiting: HashSet<&str> = HashSet::new();
+    let mut done: HashSet<&str> = HashSet::new();
+    for start in steps.keys().copied() {
+        if done.contains(start) {
+            continue;
+        }
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        visiting.insert(start);
+        while let Some((node, child)) = stack.pop() {
+            let successors = steps[node].kind.successors();
+            if let Some(next) = successors.get(child).copied() {
+                stack.push((node, child + 1));
+                if visiting.contains(next) {
+                    return Err(format!("cycle detected at step '{}'", next));
+                }
+                if !done.contains(next) {
+                    visiting.insert(next);
+                    stack.push((next, 0));
+                }
+            } else {
+                visiting.remove(node);
+                done.insert(node);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Walk a validated pipeline for `context` without touching the filesystem.
+/// Renames use the final name or hand-off; `trace_pipeline` shows the steps.
+pub fn trace(
+    pipeline: &Pipeline,
+    context: &ExpressionContext,
+    engine: &ExpressionEngine,
+) -> Result<PipelineTrace, String> {
+    let steps: HashMap<&str, &PipelineStep> =
+        pipeline.steps.iter().map(|step| (step.id.as_str(), step)).collect();
+    let mut current = context.clone();
+    let mut entries = Vec::new();
+    let mut handoff_profile = None;
+    let mut cursor = Some(pipeline.entry.as_str());
+
+    while let Some(id) = cursor {
+        // Guard against unvalidated input; a valid pipeline visits each step once.
+        if entries.len() > pipeline.steps.len() {
+            return Err("pipeline did not terminate".to_string());
+        }
+        let step = steps.get(id).ok_or_else(|| format!("unknown step '{}'", id))?;
+        let (outcome, next) = match &step.kind {
+            StepKind::Rename { template, next } => {
+                current.name = engine.render_template(template, &current)?;
+                ("renamed".to_string(), next.as_deref())
+            }
+            StepKind::Branch { condition, then, otherwise } => {
+                if engine.eval_condition(condition, &current)? {
+                    (format!("condition true → {}", then), Some(then.as_str()))
+                } else {
+                    let target = otherwise.as_deref();
+                    (format!("condition false → {}", target.unwrap_or("end")), target)
+                }
+            }
+            StepKind::Stop => ("stopped".to_string(), None),
+            StepKind::Handoff { profile_id } => {
+                handoff_profile = Some(profile_id.clone());
+                (format!("handed off to profile {}", profile_id), None)
+            }
+        };
+        entries.push(TraceEntry { step: step.id.clone(), outcome, name: current.name.clone() });
+        cursor = next;
+    }
+
+    Ok(PipelineTrace { steps: entries, final_name: current.name, handoff_profile })
+}
+
+/// Pipelines persisted to `pipelines.json` in the app config directory.
+pub struct PipelineStore {
+    path: PathBuf,
+    pipelines: Mutex<Vec<Pipeline>>,
+}
+
+impl PipelineStore {
+    pub fn load(path: PathBuf) -> Self {
+        let pipelines = load_json(&path);
+        Self { path, pipelines: Mutex::new(pipelines) }
+    }
+
+    pub async fn list(&self) -> Vec<Pipeline> {
+        self.pipelines.lock().await.clone()
+    }
+
+    pub async fn get(&self, id: &str) -> Option<Pipeline> {
+        self.pipelines.lock().await.iter().find(|p| p.id == id).cloned()
+    }
+
+    pub async fn upsert(&self, pipeline: Pipeline) -> Result<Vec<Pipeline>, String> {
+        let mut pipelines = self.pipelines.lock().await;
+        match pipelines.iter_mut().find(|p| p.id == pipeline.id) {
+            Some(existing) => *existing = pipeline,
+            None => pipelines.push(pipeline),
+        }
+        save_json(&self.path, &*pipelines)?;
+        Ok(pipelines.clone())
+    }
+
+    pub async fn remove(&self, id: &str) -> Result<Vec<Pipeline>, String> {
+        let mut pipelines = self.pipelines.lock().await;
+        pipelines.retain(|p| p.id != id);
+        save_json(&self.path, &*pipelines)?;
+        Ok(pipelines.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(id: &str, kind: StepKind) -> PipelineStep {
+        PipelineStep { id: id.to_string(), kind }
+    }
+
+    fn rename(id: &str, template: &str, next: Option<&str>) -> PipelineStep {
+        step(id, StepKind::Rename { template: template.to_string(), next: next.map(str::to_string) })
+    }
+
+    fn branch(id: &str, condition: &str, then: &str, otherwise: Option<&str>) -> PipelineStep {
+        step(
+            id,
+            StepKind::Branch {
+                condition: condition.to_string(),
+                then: then.to_string(),
+                otherwise: otherwise.map(str::to_string),
+            },
+        )
+    }
+
+    fn handoff(id: &str, profile: &str) -> PipelineStep {
+        step(id, StepKind::Handoff { profile_id: profile.to_string() })
+    }
+
+    fn pipeline(steps: Vec<PipelineStep>) -> Pipeline {
+        Pipeline { id: "p".to_string(), name: "P".to_string(), enabled: true, entry: "start".to_string(), steps }
+    }
+
+    fn profiles() -> Vec<String> {
+        vec!["docs".to_string()]
+    }
+
+    #[test]
+    fn branching_pipelines_validate_and_trace() {
+        let pipeline = pipeline(vec![
+            branch("start", "size > 1000", "big", Some("small")),
+            rename("big", "big-<expr:name>", Some("done")),
+            handoff("small", "docs"),
+            step("done", StepKind::Stop),
+        ]);
+        assert_eq!(validate(&pipeline, &profiles()), Ok(()));
+
+        let engine = ExpressionEngine::new();
+        let big = ExpressionContext { name: "a.png".to_string(), size: 5000, ..ExpressionContext::default() };
+        let traced = trace(&pipeline, &big, &engine).unwrap();
+        let steps: Vec<&str> = traced.steps.iter().map(|entry| entry.step.as_str()).collect();
+        assert_eq!(steps, ["start", "big", "done"]);
+        assert_eq!((traced.final_name.as_str(), traced.handoff_profile), ("big-a.png", None));
+
+        let small = ExpressionContext { size: 10, ..big };
+        assert_eq!(trace(&pipeline, &small, &engine).unwrap().handoff_profile.as_deref(), Some("docs"));
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let looping = pipeline(vec![
+            rename("start", "<expr:name>", Some("check")),
+            branch("check", "size > 0", "start", None),
+        ]);
+        // Which step reports the cycle depends on where the search starts.
+        assert!(validate(&looping, &profiles()).unwrap_err().starts_with("cycle detected at step"));
+
+        let own = pipeline(vec![rename("start", "<expr:name>", Some("start"))]);
+        assert_eq!(validate(&own, &profiles()), Err("cycle detected at step 'start'".to_string()));
+    }
+
+    #[test]
+    fn unknown_profile_targets_are_rejected() {
+        let pipeline = pipeline(vec![handoff("start", "gone")]);
+        assert_eq!(
+            validate(&pipeline, &profiles()),
+            Err("step 'start' hands off to unknown profile 'gone'".to_string())
+        );
+    }
+
+    #[test]
+    fn unreachable_steps_are_rejected() {
+        let pipeline = pipeline(vec![
+            rename("start", "<expr:name>", None),
+            step("orphan", StepKind::Stop),
+            handoff("also", "docs"),
+        ]);
+        assert_eq!(
+            validate(&pipeline, &profiles()),
+            Err("step 'orphan' cannot be reached from the entry step".to_string())
+        );
+    }
+}
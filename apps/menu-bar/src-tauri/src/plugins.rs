@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::async_runtime::Mutex;
+use wasmtime::{Config, Engine, ExternType, Instance, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+use crate::logging;
+use crate::persist::{load_json, save_json};
+
+const ENABLED_FILE: &str = "enabled.json";
+const ALLOC_EXPORT: &str = "namefix_alloc";
+const TRANSFORM_EXPORT: &str = "namefix_transform";
+const FUEL_PER_CALL: u64 = 5_000_000;
+const MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Summary of a plugin found in the plugins directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub id: String,
+    pub path: String,
+    pub enabled: bool,
+    /// Load error, if the module failed to compile or lacks the plugin exports.
+    pub error: Option<String>,
+}
+
+struct LoadedPlugin {
+    path: PathBuf,
+    module: Result<Module, String>,
+}
+
+/// Hosts `.wasm` rename plugins.
+///
+/// Plugins are instantiated without any imports, so they have no access to the
+/// filesystem, network, or clock; every call runs with a fuel and memory budget.
+///
+/// ABI: a plugin exports `memory`, `namefix_alloc(len: i32) -> i32` and
+/// `namefix_transform(ptr: i32, len: i32) -> i64`. The input is a UTF-8 JSON
+/// document `{"name": ..., "metadata": {...}}`; the result is `0` to keep the
+/// name unchanged, or `(ptr << 32) | len` pointing at the new UTF-8 name.
+pub struct PluginHost {
+    engine: Engine,
+    dir: PathBuf,
+    plugins: Mutex<BTreeMap<String, LoadedPlugin>>,
+    enabled: Mutex<BTreeSet<String>>,
+}
+
+impl PluginHost {
+    pub fn new(dir: PathBuf) -> anyhow::Result<Self> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        let enabled = load_json(&dir.join(ENABLED_FILE));
+        Ok(Self {
+            engine,
+            dir,
+            plugins: Mutex::new(BTreeMap::new()),
+            enabled: Mutex::new(enabled),
+        })
+    }
+
+    /// Rescan the plugins directory, compiling any modules found there.
+    pub async fn reload(&self) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|err| err.to_string())?;
+        let entries = std::fs::read_dir(&self.dir).map_err(|err| err.to_string())?;
+
+        let mut loaded = BTreeMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("wasm") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else {
+                continue;
+            };
+            let module = Module::from_file(&self.engine, &path)
+                .map_err(|err| err.to_string())
+                .and_then(|module| validate_exports(&module).map(|_| module));
+            if let Err(ref err) = module {
+                log::warn!("Plugin '{}' failed to load: {}", id, err);
+            }
+            loaded.insert(id, LoadedPlugin { path, module });
+        }
+
+        log::info!("Loaded {} plugin(s) from {}", loaded.len(), self.dir.display());
+        *self.plugins.lock().await = loaded;
+        Ok(())
+    }
+
+    pub async fn list(&self) -> Vec<PluginInfo> {
+        let plugins = self.plugins.lock().await;
+        let enabled = self.enabled.lock().await;
+        plugins
+            .iter()
+            .map(|(id, plugin)| PluginInfo {
+                id: id.clone(),
+                path: plugin.path.to_string_lossy().to_string(),
+                enabled: enabled.contains(id),
+                error: plugin.module.as_ref().err().cloned(),
+            })
+            .collect()
+    }
+
+    pub async fn set_enabled(&self, id: &str, enabled: bool) -> Result<Vec<PluginInfo>, String> {
+        {
+            let plugins = self.plugins.lock().await;
+            match plugins.get(id) {
+                None => return Err(format!("unknown plugin: {id}")),
+                Some(LoadedPlugin { module: Err(err), .. }) if enabled => {
+                    return Err(format!("plugin '{id}' cannot be enabled: {err}"));
+                }
+                Some(_) => {}
+            }
+        }
+        {
+            let mut set = self.enabled.lock().await;
+            if enabled {
+                set.insert(id.to_string());
+            } else {
+                set.remove(id);
+            }
+            save_json(&self.dir.join(ENABLED_FILE), &*set)?;
+        }
+        Ok(self.list().await)
+    }
+
+    /// Run every enabled plugin over `name` in id order. Returns `None` if no
+    /// plugin changed the name.
+    pub async fn transform(&self, name: &str, metadata: &Value) -> Result<Option<String>, String> {
+        let plugins = self.plugins.lock().await;
+        let enabled = self.enabled.lock().await;
+        let mut current = name.to_string();
+        let mut changed = false;
+        for (id, plugin) in plugins.iter() {
+            if !enabled.contains(id) {
+                continue;
+            }
+            let Ok(module) = plugin.module.as_ref() else {
+                continue;
+            };
+            match self.call(module, &current, metadata) {
+                Ok(Some(next)) if next != current => {
+                    let directory = metadata.get("directory").and_then(Value::as_str).unwrap_or_default();
+                    let (from, to) = (logging::file_name(directory, &current), logging::file_name(directory, &next));
+                    log::debug!("Plugin '{}' renamed {} → {}", id, from, to);
+                    current = next;
+                    changed = true;
+                }
+                Ok(_) => {}
+                Err(err) => return Err(format!("plugin '{id}' failed: {err}")),
+            }
+        }
+        Ok(changed.then_some(current))
+    }
+
+    fn call(&self, module: &Module, name: &str, metadata: &Value) -> Result<Option<String>, String> {
+        let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|err| err.to_string())?;
+
+        // No imports are provided, so modules that expect WASI or host calls
+        // fail here instead of gaining access to the system.
+        let instance = Instance::new(&mut store, module, &[]).map_err(|err| err.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| "missing exported memory".to_string())?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, ALLOC_EXPORT)
+            .map_err(|err| err.to_string())?;
+        let transform = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, TRANSFORM_EXPORT)
+            .map_err(|err| err.to_string())?;
+
+        let input = serde_json::to_vec(&json!({ "name": name, "metadata": metadata }))
+            .map_err(|err| err.to_string())?;
+        let len = i32::try_from(input.len()).map_err(|_| "input too large".to_string())?;
+        let ptr = alloc.call(&mut store, len).map_err(trap_message)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, &input)
+            .map_err(|err| err.to_string())?;
+
+        let packed = transform.call(&mut store, (ptr, len)).map_err(trap_message)?;
+        if packed == 0 {
+            return Ok(None);
+        }
+        let out_ptr = ((packed as u64) >> 32) as usize;
+        let out_len = ((packed as u64) & 0xffff_ffff) as usize;
+        let data = memory.data(&store);
+        let bytes = out_ptr
+            .checked_add(out_len)
+            .and_then(|end| data.get(out_ptr..end))
+            .ok_or_else(|| "result out of bounds".to_string())?;
+        let next = std::str::from_utf8(bytes).map_err(|err| err.to_string())?;
+        if !valid_basename(next) {
+            return Err(format!("invalid file name returned: {next:?}"));
+        }
+        Ok(Some(next.to_string()))
+    }
+}
+
+/// Whether `name` can only name a file in the folder it's renamed in: not
+/// empty, `.` or `..`, and without a separator or NUL on any platform.
+pub fn valid_basename(name: &str) -> bool {
+    !matches!(name, "" | "." | "..") && !name.contains(['/', '\\', '\0'])
+}
+
+/// What stopped a plugin call: the trap (out of fuel, a failed memory grow
+/// the plugin gave up on) rather than the backtrace wasmtime reports first.
+fn trap_message(err: wasmtime::Error) -> String {
+    match err.downcast_ref::<Trap>() {
+        Some(trap) => trap.to_string(),
+        None => err.to_string(),
+    }
+}
+
+fn validate_exports(module: &Module) -> Result<(), String> {
+    match module.get_export("memory") {
+        Some(ExternType::Memory(_)) => {}
+        Some(_) => return Err("export 'memory' is not a memory".to_string()),
+        None => return Err("missing export 'memory'".to_string()),
+    }
+    validate_func(module, ALLOC_EXPORT, &["i32"], "i32")?;
+    validate_func(module, TRANSFORM_EXPORT, &["i32", "i32"], "i64")?;
+    if module.imports().next().is_some() {
+        return Err("plugins must not import host functions".to_string());
+    }
+    Ok(())
+}
+
+/// Check that `name` is exported as a function with the ABI's signature, so a
+/// mismatch shows when the plugin loads rather than on every rename.
+fn validate_func(module: &Module, name: &str, params: &[&str], result: &str) -> Result<(), String> {
+    let func = match module.get_export(name) {
+        Some(ExternType::Func(func)) => func,
+        Some(_) => return Err(format!("export '{name}' is not a function")),
+        None => return Err(format!("missing export '{name}'")),
+    };
+    let actual: Vec<String> = func.params().map(|ty| ty.to_string()).collect();
+    let results: Vec<String> = func.results().map(|ty| ty.to_string()).collect();
+    if actual != params || results != [result] {
+        return Err(format!("export '{name}' must take ({}) and return {result}", params.join(", ")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes its input back as the new name, so tests see what the host sent.
+    const ECHO: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "namefix_transform") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+                (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                (i64.extend_i32_u (local.get $len)))))"#;
+
+    /// Keeps every name.
+    const KEEP: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "namefix_transform") (param i32 i32) (result i64) i64.const 0))"#;
+
+    /// Never returns.
+    const SPIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "namefix_transform") (param i32 i32) (result i64) (loop br 0) i64.const 0))"#;
+
+    /// Asks for 32 MiB of memory and traps when it doesn't get it.
+    const GREEDY: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32)
+            (if (i32.eq (memory.grow (i32.const 512)) (i32.const -1)) (then unreachable))
+            i32.const 1024)
+        (func (export "namefix_transform") (param i32 i32) (result i64) i64.const 0))"#;
+
+    const NO_TRANSFORM: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32) i32.const 1024))"#;
+
+    const BAD_TRANSFORM: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "namefix_alloc") (param i32) (result i32) i32.const 1024)
+        (func (export "namefix_transform") (param i32) (result i32) i32.const 0))"#;
+
+    #[test]
+    fn only_bare_file_names_are_valid() {
+        for name in ["a.png", ".hidden", "..a", "a..b"] {
+            assert!(valid_basename(name), "{}", name);
+        }
+        for name in ["", ".", "..", "../x", "a/b", "a\\b", "..\\x", "a\0b"] {
+            assert!(!valid_basename(name), "{:?}", name);
+        }
+    }
+
+    async fn host(name: &str, plugins: &[(&str, &str)]) -> PluginHost {
+        let dir = std::env::temp_dir().join(format!("namefix-plugins-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (id, wat) in plugins {
+            std::fs::write(dir.join(format!("{id}.wasm")), wat::parse_str(wat).unwrap()).unwrap();
+        }
+        let host = PluginHost::new(dir).unwrap();
+        host.reload().await.unwrap();
+        host
+    }
+
+    #[test]
+    fn enabled_plugins_get_the_name_and_metadata() {
+        tauri::async_runtime::block_on(async {
+            let host = host("abi", &[("echo", ECHO), ("keep", KEEP)]).await;
+            let metadata = json!({ "rule": "shots" });
+            assert_eq!(host.transform("a.png", &metadata).await, Ok(None));
+
+            host.set_enabled("keep", true).await.unwrap();
+            assert_eq!(host.transform("a.png", &metadata).await, Ok(None));
+
+            host.set_enabled("echo", true).await.unwrap();
+            let echoed = host.transform("a.png", &metadata).await.unwrap().unwrap();
+            let echoed: Value = serde_json::from_str(&echoed).unwrap();
+            assert_eq!(echoed, json!({ "name": "a.png", "metadata": { "rule": "shots" } }));
+        });
+    }
+
+    #[test]
+    fn runaway_plugins_are_stopped() {
+        tauri::async_runtime::block_on(async {
+            let host = host("limits", &[("greedy", GREEDY), ("spin", SPIN)]).await;
+            host.set_enabled("spin", true).await.unwrap();
+            let err = host.transform("a.png", &Value::Null).await.unwrap_err();
+            assert!(err.starts_with("plugin 'spin' failed") && err.contains("fuel"), "{}", err);
+
+            host.set_enabled("spin", false).await.unwrap();
+            host.set_enabled("greedy", true).await.unwrap();
+            let err = host.transform("a.png", &Value::Null).await.unwrap_err();
+            assert!(err.starts_with("plugin 'greedy' failed") && err.contains("unreachable"), "{}", err);
+        });
+    }
+
+    #[test]
+    fn plugins_without_a_usable_transform_do_not_load() {
+        tauri::async_runtime::block_on(async {
+            let host = host("exports", &[("bad", BAD_TRANSFORM), ("none", NO_TRANSFORM)]).await;
+            let errors: Vec<Option<String>> = host.list().await.into_iter().map(|plugin| plugin.error).collect();
+            assert_eq!(
+                errors,
+                [
+                    Some("export 'namefix_transform' must take (i32, i32) and return i64".to_string()),
+                    Some("missing export 'namefix_transform'".to_string()),
+                ]
+            );
+            assert!(host.set_enabled("bad", true).await.is_err());
+            assert!(host.set_enabled("none", true).await.is_err());
+        });
+    }
+}
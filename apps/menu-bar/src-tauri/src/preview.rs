@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::{self, BridgeHandle, RenamePlan, RunOnceResult};
+
+/// Previews kept for `apply`; an older one has to be previewed again.
+const MAX_PLANS: usize = 8;
+
+/// One rename a preview proposes, by file name within its folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewEntry {
+    pub from: String,
+    pub to: String,
+    pub rule: Option<String>,
+    /// `to` was taken, so it has an `_N` suffix.
+    pub conflict: bool,
+}
+
+/// What renaming a folder would do, kept under `plan_id` until applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePreview {
+    pub plan_id: String,
+    pub directory: String,
+    pub entries: Vec<PreviewEntry>,
+    /// Files no rule would rename.
+    pub unchanged: usize,
+}
+
+#[derive(Default)]
+struct Plans {
+    next_id: u64,
+    /// Newest first.
+    plans: VecDeque<(String, RenamePlan)>,
+}
+
+/// Plans shown as previews, so the renames applied are the ones the user
+/// looked at rather than whatever the rules say by then.
+#[derive(Default)]
+pub struct PlanPreviews {
+    inner: Mutex<Plans>,
+}
+
+impl PlanPreviews {
+    /// Plan `directory` with the enabled rules, or just `profile`, without
+    /// touching anything.
+    pub async fn preview(
+        &self,
+        bridge: &BridgeHandle,
+        directory: String,
+        profile: Option<String>,
+    ) -> Result<RenamePreview, String> {
+        let plan = bridge::plan_directory(bridge, directory, profile).await?;
+        let mut inner = self.inner.lock().expect("plan previews lock poisoned");
+        inner.next_id += 1;
+        let plan_id = format!("plan-{}", inner.next_id);
+        let preview = preview_of(&plan_id, &plan);
+        inner.plans.push_front((plan_id, plan));
+        inner.plans.truncate(MAX_PLANS);
+        Ok(preview)
+    }
+
+    /// Rename the files of preview `plan_id`, or only those in `selected`.
+    /// Each preview is applied once.
+    pub async fn apply(
+        &self,
+        bridge: &BridgeHandle,
+        plan_id: &str,
+        selected: Option<&[String]>,
+    ) -> Result<RunOnceResult, String> {
+        let plan = {
+            let mut inner = self.inner.lock().expect("plan previews lock poisoned");
+            let index = inner
+                .plans
+                .iter()
+                .position(|(id, _)| id == plan_id)
+                .ok_or_else(|| "This preview has expired; preview the folder again".to_string())?;
+            let plan = select(inner.plans[index].1.clone(), selected)?;
+            inner.plans.remove(index);
+            plan
+        };
+        bridge::apply_plan(bridge, &plan).await
+    }
+}
+
+/// The renames `plan` proposes, leaving out files no rule would rename.
+pub fn renames(plan: &RenamePlan) -> Vec<PreviewEntry> {
+    plan.entries
+        .iter()
+        .filter_map(|entry| {
+            Some(PreviewEntry {
+                from: entry.file.clone(),
+                to: entry.target.clone()?,
+                rule: entry.rule.clone(),
+                conflict: entry.conflict,
+            })
+        })
+        .collect()
+}
+
+fn preview_of(plan_id: &str, plan: &RenamePlan) -> RenamePreview {
+    let entries = renames(plan);
+    RenamePreview {
+        plan_id: plan_id.to_string(),
+        directory: plan.directory.clone(),
+        unchanged: plan.entries.len() - entries.len(),
+        entries,
+    }
+}
+
+/// `plan`'s renames, only of the files in `selected` when given.
+fn select(mut plan: RenamePlan, selected: Option<&[String]>) -> Result<RenamePlan, String> {
+    plan.entries.retain(|entry| entry.target.is_some());
+    let Some(selected) = selected else {
+        return Ok(plan);
+    };
+    if let Some(file) = selected.iter().find(|file| plan.entries.iter().all(|entry| &entry.file != *file)) {
+        return Err(format!("{} is not renamed in this preview", file));
+    }
+    plan.entries.retain(|entry| selected.contains(&entry.file));
+    if plan.entries.is_empty() {
+        return Err("Choose at least one file to rename".to_string());
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::RenamePlanEntry;
+
+    fn plan() -> RenamePlan {
+        let entry = |file: &str, target: Option<&str>, conflict| RenamePlanEntry {
+            file: file.to_string(),
+            target: target.map(str::to_string),
+            rule: target.map(|_| "screenshots".to_string()),
+            created_at: 0,
+            conflict,
+        };
+        RenamePlan {
+            directory: "/tmp/shots".to_string(),
+            entries: vec![
+                entry("Screenshot 1.png", Some("Screenshot_2024-12-26.png"), false),
+                entry("Screenshot 2.png", Some("Screenshot_2024-12-26_2.png"), true),
+                entry("notes.txt", None, false),
+            ],
+        }
+    }
+
+    #[test]
+    fn lists_only_the_renames() {
+        let preview = preview_of("plan-1", &plan());
+        assert_eq!(preview.unchanged, 1);
+        assert_eq!(preview.entries.len(), 2);
+        assert_eq!(preview.entries[1].to, "Screenshot_2024-12-26_2.png");
+        assert!(preview.entries[1].conflict);
+    }
+
+    #[test]
+    fn applies_just_the_selected_files() {
+        assert_eq!(select(plan(), None).unwrap().entries.len(), 2);
+        let chosen = ["Screenshot 2.png".to_string()];
+        let selected = select(plan(), Some(&chosen)).unwrap();
+        assert_eq!(selected.entries.len(), 1);
+        assert_eq!(selected.entries[0].file, "Screenshot 2.png");
+        assert!(select(plan(), Some(&["notes.txt".to_string()])).is_err());
+        assert!(select(plan(), Some(&[])).is_err());
+    }
+}
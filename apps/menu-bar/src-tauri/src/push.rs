@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::activity::now_millis;
+use crate::persist::{load_json, save_json};
+use crate::state::AppState;
+use crate::webhook;
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+/// Failed renames within [`ERROR_WINDOW_MS`] that count as a burst.
+const ERROR_BURST: usize = 5;
+const ERROR_WINDOW_MS: u64 = 5 * 60 * 1000;
+/// A folder that keeps failing pushes once in this long, not on every file.
+const BURST_COOLDOWN_MS: u64 = 30 * 60 * 1000;
+/// Seconds curl may take before a push is given up.
+const TIMEOUT_SECS: u32 = 15;
+
+/// Where critical events are pushed, so they reach a phone away from the Mac.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum PushEndpoint {
+    /// An ntfy topic URL, on ntfy.sh or a self-hosted server, with an access
+    /// token for protected topics.
+    Ntfy {
+        url: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// A Pushover user key and the token of an application registered for it.
+    Pushover { user: String, token: String },
+}
+
+impl PushEndpoint {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Ntfy { url, .. } if !(url.starts_with("https://") || url.starts_with("http://")) => {
+                Err("Enter the full ntfy topic URL, e.g. https://ntfy.sh/my-topic".to_string())
+            }
+            Self::Pushover { user, token } if user.trim().is_empty() || token.trim().is_empty() => {
+                Err("Pushover needs both your user key and an application token".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PushSettings {
+    #[serde(default)]
+    endpoint: Option<PushEndpoint>,
+}
+
+/// Recent failed renames, for spotting a burst worth pushing.
+#[derive(Default)]
+struct ErrorBursts {
+    recent: VecDeque<u64>,
+    last_pushed: Option<u64>,
+}
+
+impl ErrorBursts {
+    /// Note a failure at `now`, returning how many fell in the window when
+    /// they make a burst that hasn't been pushed lately.
+    fn record(&mut self, now: u64) -> Option<usize> {
+        self.recent.push_back(now);
+        while self.recent.front().is_some_and(|&at| now.saturating_sub(at) > ERROR_WINDOW_MS) {
+            self.recent.pop_front();
+        }
+        let cooling = self.last_pushed.is_some_and(|at| now.saturating_sub(at) < BURST_COOLDOWN_MS);
+        if self.recent.len() < ERROR_BURST || cooling {
+            return None;
+        }
+        self.last_pushed = Some(now);
+        Some(self.recent.len())
+    }
+}
+
+/// The optional push relay, persisted next to the other menu bar settings.
+/// Only critical events are sent: a burst of failed renames, and the
+/// background service stopping for good.
+pub struct PushRelay {
+    path: PathBuf,
+    settings: Mutex<PushSettings>,
+    errors: Mutex<ErrorBursts>,
+}
+
+impl PushRelay {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self { path, settings: Mutex::new(settings), errors: Mutex::new(ErrorBursts::default()) }
+    }
+
+    pub fn endpoint(&self) -> Option<PushEndpoint> {
+        self.settings.lock().expect("push settings lock poisoned").endpoint.clone()
+    }
+
+    fn save(&self, endpoint: Option<PushEndpoint>) -> Result<(), String> {
+        let mut settings = self.settings.lock().expect("push settings lock poisoned");
+        let updated = PushSettings { endpoint };
+        save_json(&self.path, &updated)?;
+        *settings = updated;
+        Ok(())
+    }
+}
+
+/// Send to `endpoint` now. Used by [`critical`] and to try an endpoint out.
+pub async fn send(endpoint: &PushEndpoint, message: &str) -> Result<(), String> {
+    curl(&curl_config(endpoint, message)).await.map_err(|err| format!("Push failed: {}", err))
+}
+
+/// Run curl with `config` as its config file, read from stdin so secrets
+/// stay out of the process list.
+pub async fn curl(config: &str) -> Result<(), String> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run curl: {}", err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes()).await.map_err(|err| err.to_string())?;
+    }
+    let output = child.wait_with_output().await.map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Check `endpoint` with a test push, then keep it. `None` stops pushing.
+pub async fn set_endpoint(relay: &PushRelay, endpoint: Option<PushEndpoint>) -> Result<Option<PushEndpoint>, String> {
+    if let Some(endpoint) = &endpoint {
+        endpoint.validate()?;
+        send(endpoint, "Namefix will send critical alerts here.").await?;
+    }
+    relay.save(endpoint.clone())?;
+    Ok(endpoint)
+}
+
+/// Push `message` in the background, if an endpoint is set, and post it to
+/// the webhook, if one is set.
+pub fn critical(app: &AppHandle<Wry>, message: impl Into<String>) {
+    let message = message.into();
+    webhook::alert(app, &message);
+    let Some(endpoint) = app.state::<AppState>().push.endpoint() else {
+        return;
+    };
+    async_runtime::spawn(async move {
+        if let Err(err) = send(&endpoint, &message).await {
+            log::warn!("Could not push \"{}\": {}", message, err);
+        }
+    });
+}
+
+/// Push when a file event from the service completes a burst of failures.
+pub fn observe(app: &AppHandle<Wry>, payload: &Value) {
+    if !matches!(payload.get("kind").and_then(Value::as_str), Some("error" | "convert-error")) {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let burst = state.push.errors.lock().expect("push errors lock poisoned").record(now_millis());
+    if let Some(count) = burst {
+        critical(app, format!("{} files failed to rename in the last {} minutes", count, ERROR_WINDOW_MS / 60_000));
+    }
+}
+
+/// The whole request as a curl config for [`curl`].
+fn curl_config(endpoint: &PushEndpoint, message: &str) -> String {
+    let mut lines = vec![("max-time", TIMEOUT_SECS.to_string())];
+    match endpoint {
+        PushEndpoint::Ntfy { url, token } => {
+            lines.push(("url", url.clone()));
+            lines.push(("header", "Title: Namefix".to_string()));
+            lines.push(("header", "Priority: high".to_string()));
+            if let Some(token) = token.as_deref().filter(|token| !token.is_empty()) {
+                lines.push(("header", format!("Authorization: Bearer {}", token)));
+            }
+            // data-raw, unlike data-binary, never reads a file for a message starting with "@".
+            lines.push(("data-raw", message.to_string()));
+        }
+        PushEndpoint::Pushover { user, token } => {
+            lines.push(("url", PUSHOVER_URL.to_string()));
+            lines.push(("form-string", format!("token={}", token)));
+            lines.push(("form-string", format!("user={}", user)));
+            lines.push(("form-string", "title=Namefix".to_string()));
+            lines.push(("form-string", "priority=1".to_string()));
+            lines.push(("form-string", format!("message={}", message)));
+        }
+    }
+    lines.into_iter().map(|(key, value)| config_line(key, &value)).collect()
+}
+
+/// One `key = "value"` line of a curl config, with `value` escaped.
+pub fn config_line(key: &str, value: &str) -> String {
+    format!("{} = \"{}\"\n", key, quote(value))
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_need_enough_errors_and_cool_down() {
+        let mut bursts = ErrorBursts::default();
+        for minute in 0..4 {
+            assert_eq!(bursts.record(minute * 60_000), None);
+        }
+        assert_eq!(bursts.record(4 * 60_000), Some(5));
+        assert_eq!(bursts.record(5 * 60_000), None, "cooling down");
+
+        // Past the cooldown the earlier failures have left the window, so it takes five more.
+        let later = 4 * 60_000 + BURST_COOLDOWN_MS;
+        for i in 0..4 {
+            assert_eq!(bursts.record(later + i), None);
+        }
+        assert_eq!(bursts.record(later + 4), Some(5));
+    }
+
+    #[test]
+    fn curl_config_escapes_values() {
+        let endpoint = PushEndpoint::Ntfy { url: "https://ntfy.sh/desk".to_string(), token: Some("tk".to_string()) };
+        let config = curl_config(&endpoint, "a \"quoted\"\nname");
+        assert!(config.contains("url = \"https://ntfy.sh/desk\"\n"));
+        assert!(config.contains("header = \"Authorization: Bearer tk\"\n"));
+        assert!(config.contains("data-raw = \"a \\\"quoted\\\"\\nname\"\n"));
+
+        let pushover = PushEndpoint::Pushover { user: "u".to_string(), token: "t".to_string() };
+        assert!(curl_config(&pushover, "m").contains("form-string = \"message=m\"\n"));
+    }
+
+    #[test]
+    fn endpoints_are_checked_before_use() {
+        let bare = PushEndpoint::Ntfy { url: "ntfy.sh/desk".to_string(), token: None };
+        assert!(bare.validate().is_err());
+        let partial = PushEndpoint::Pushover { user: "u".to_string(), token: " ".to_string() };
+        assert!(partial.validate().is_err());
+        let parsed: PushEndpoint = serde_json::from_str(r#"{"kind":"ntfy","url":"https://ntfy.sh/desk"}"#).unwrap();
+        assert!(parsed.validate().is_ok());
+    }
+}
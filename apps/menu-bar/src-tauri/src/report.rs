@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::activity::now_millis;
+use crate::bridge::{self, BridgeHandle, HistoryEntry, HistoryFilters, SessionSummary};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Default reporting period, matching the tray's weekly report.
+pub const DEFAULT_DAYS: u32 = 7;
+
+/// A report written to disk, ready to be shared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryReport {
+    pub path: String,
+    pub renames: usize,
+    pub since: u64,
+    pub until: u64,
+}
+
+/// Write a Markdown summary of the renames from the last `days` days into
+/// `directory`, named after the period it covers.
+pub async fn export_history(bridge: &BridgeHandle, directory: &Path, days: u32) -> Result<HistoryReport, String> {
+    let until = now_millis();
+    let since = until.saturating_sub(u64::from(days.max(1)) * DAY_MS);
+    let filters = HistoryFilters { since: Some(since), until: Some(until), ..HistoryFilters::default() };
+    let mut entries = bridge::search_history(bridge, String::new(), filters).await?;
+    entries.reverse();
+
+    std::fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+    let path: PathBuf = directory.join(format!("namefix-report-{}-to-{}.md", date(since), date(until)));
+    std::fs::write(&path, render(&entries, "Namefix rename report", since, until)).map_err(|err| err.to_string())?;
+
+    Ok(HistoryReport { path: path.to_string_lossy().into_owned(), renames: entries.len(), since, until })
+}
+
+/// Write a Markdown summary of the renames made during `session` into
+/// `directory`, named after the session and the day it started.
+pub async fn export_session(
+    bridge: &BridgeHandle,
+    directory: &Path,
+    session: &SessionSummary,
+) -> Result<HistoryReport, String> {
+    let since = session.session.started_at;
+    let until = session.session.ended_at.unwrap_or_else(now_millis);
+    let filters = HistoryFilters { session: Some(session.session.id.clone()), ..HistoryFilters::default() };
+    let mut entries = bridge::search_history(bridge, String::new(), filters).await?;
+    entries.reverse();
+
+    std::fs::create_dir_all(directory).map_err(|err| err.to_string())?;
+    let path = directory.join(format!("namefix-session-{}-{}.md", slug(&session.session.name), date(since)));
+    let title = format!("Namefix session: {}", session.session.name);
+    std::fs::write(&path, render(&entries, &title, since, until)).map_err(|err| err.to_string())?;
+
+    Ok(HistoryReport { path: path.to_string_lossy().into_owned(), renames: entries.len(), since, until })
+}
+
+fn render(entries: &[HistoryEntry], title: &str, since: u64, until: u64) -> String {
+    let mut by_folder: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries {
+        let folder = Path::new(&entry.to).parent().map(|dir| dir.to_string_lossy().into_owned()).unwrap_or_default();
+        *by_folder.entry(folder).or_default() += 1;
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title);
+    let _ = writeln!(
+        out,
+        "{} to {}: {} rename{} across {} folder{}.\n",
+        date(since),
+        date(until),
+        entries.len(),
+        if entries.len() == 1 { "" } else { "s" },
+        by_folder.len(),
+        if by_folder.len() == 1 { "" } else { "s" },
+    );
+    if entries.is_empty() {
+        return out;
+    }
+    let _ = writeln!(out, "## By folder\n");
+    for (folder, count) in &by_folder {
+        let _ = writeln!(out, "- {}: {}", folder, count);
+    }
+    let _ = writeln!(out, "\n## Renames\n");
+    let _ = writeln!(out, "| When | From | To |");
+    let _ = writeln!(out, "| --- | --- | --- |");
+    for entry in entries {
+        let _ = writeln!(out, "| {} | {} | {} |", timestamp(entry.timestamp), file_name(&entry.from), file_name(&entry.to));
+    }
+    out
+}
+
+fn local(ms: u64) -> Option<DateTime<Local>> {
+    DateTime::from_timestamp_millis(i64::try_from(ms).ok()?).map(|utc| utc.with_timezone(&Local))
+}
+
+fn date(ms: u64) -> String {
+    local(ms).map(|at| at.format("%Y-%m-%d").to_string()).unwrap_or_default()
+}
+
+fn timestamp(ms: u64) -> String {
+    local(ms).map(|at| at.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default()
+}
+
+/// Lowercase letters and digits of a session name, runs of anything else as one dash.
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for ch in name.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "untitled".to_string() } else { slug.to_string() }
+}
+
+fn file_name(path: &str) -> String {
+    // Pipes would break the table row.
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().replace('|', "\\|"))
+        .unwrap_or_default()
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::clipboard_fix;
+use crate::persist::{load_json, save_json};
+
+/// Modifiers in the order normalized accelerators list them.
+const MODIFIERS: [&str; 4] = ["Cmd", "Ctrl", "Alt", "Shift"];
+
+/// Keys other than letters, digits and F1-F24 a shortcut can use.
+const NAMED_KEYS: [&str; 10] = ["Space", "Enter", "Tab", "Backspace", "Delete", "Escape", "Up", "Down", "Left", "Right"];
+
+/// Keyboard shortcuts for palette actions, keyed by action id, persisted next
+/// to the other menu bar settings. Accelerators are stored normalized, so two
+/// spellings of the same keys are caught as a conflict.
+pub struct ShortcutStore {
+    path: PathBuf,
+    shortcuts: Mutex<BTreeMap<String, String>>,
+}
+
+impl ShortcutStore {
+    pub fn load(path: PathBuf) -> Self {
+        let shortcuts = load_json(&path);
+        Self { path, shortcuts: Mutex::new(shortcuts) }
+    }
+
+    pub fn all(&self) -> BTreeMap<String, String> {
+        self.shortcuts.lock().expect("shortcuts lock poisoned").clone()
+    }
+
+    pub fn get(&self, action: &str) -> Option<String> {
+        self.shortcuts.lock().expect("shortcuts lock poisoned").get(action).cloned()
+    }
+
+    /// Bind `accelerator` to `action`, or clear its shortcut when None. Fails
+    /// when the keys are malformed, reserved, bound to another action, or
+    /// refused by `available`, which checks them with the system.
+    pub fn set(
+        &self,
+        action: &str,
+        accelerator: Option<&str>,
+        available: impl FnOnce(&str) -> Result<(), String>,
+    ) -> Result<BTreeMap<String, String>, String> {
+        let accelerator = accelerator.map(str::trim).filter(|accel| !accel.is_empty()).map(usable).transpose()?;
+        if let Some(accelerator) = &accelerator {
+            // Checked first so a clash between our own actions is reported as one;
+            // the lock isn't held while the system is asked.
+            unbound(&self.shortcuts.lock().expect("shortcuts lock poisoned"), action, accelerator)?;
+            available(accelerator)?;
+        }
+        let mut shortcuts = self.shortcuts.lock().expect("shortcuts lock poisoned");
+        let mut updated = shortcuts.clone();
+        match accelerator {
+            Some(accelerator) => {
+                unbound(&shortcuts, action, &accelerator)?;
+                updated.insert(action.to_string(), accelerator);
+            }
+            None => {
+                updated.remove(action);
+            }
+        }
+        save_json(&self.path, &updated)?;
+        *shortcuts = updated.clone();
+        Ok(updated)
+    }
+
+    /// Swap in a whole set of shortcuts, e.g. from an imported config. Nothing
+    /// changes unless every accelerator is usable and bound only once.
+    pub fn replace(&self, replacement: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+        let mut updated: BTreeMap<String, String> = BTreeMap::new();
+        for (action, accelerator) in replacement {
+            let accelerator = usable(accelerator)?;
+            if let Some((other, _)) = updated.iter().find(|(_, accel)| **accel == accelerator) {
+                return Err(format!("{} is the shortcut for both {} and {}", accelerator, other, action));
+            }
+            updated.insert(action.clone(), accelerator);
+        }
+        let mut shortcuts = self.shortcuts.lock().expect("shortcuts lock poisoned");
+        save_json(&self.path, &updated)?;
+        *shortcuts = updated.clone();
+        Ok(updated)
+    }
+}
+
+/// Fail when `accelerator` is the shortcut for an action other than `action`.
+fn unbound(shortcuts: &BTreeMap<String, String>, action: &str, accelerator: &str) -> Result<(), String> {
+    match shortcuts.iter().find(|(other, accel)| *accel == accelerator && other.as_str() != action) {
+        Some((other, _)) => Err(format!("{} is already the shortcut for {}", accelerator, other)),
+        None => Ok(()),
+    }
+}
+
+/// `accelerator` normalized, unless it is malformed or reserved.
+fn usable(accelerator: &str) -> Result<String, String> {
+    let accelerator = normalize(accelerator)?;
+    match reserved(&accelerator) {
+        Some(use_) => Err(format!("{} is reserved for {}", accelerator, use_)),
+        None => Ok(accelerator),
+    }
+}
+
+/// Canonical form of an accelerator such as "cmd+shift+k": modifiers in a
+/// fixed order followed by one key, e.g. "Cmd+Shift+K". CmdOrCtrl becomes Cmd
+/// on macOS and Ctrl elsewhere. Shortcuts without Cmd, Ctrl or Alt must use a
+/// function key, so they don't swallow typing.
+pub fn normalize(accelerator: &str) -> Result<String, String> {
+    let mut held = [false; MODIFIERS.len()];
+    let mut key = None;
+    for part in accelerator.split('+').map(str::trim) {
+        let modifier = match part.to_ascii_lowercase().as_str() {
+            "cmd" | "command" | "super" | "meta" => Some(0),
+            "ctrl" | "control" => Some(1),
+            "cmdorctrl" | "commandorcontrol" => Some(if cfg!(target_os = "macos") { 0 } else { 1 }),
+            "alt" | "option" => Some(2),
+            "shift" => Some(3),
+            _ => None,
+        };
+        match modifier {
+            Some(index) => held[index] = true,
+            None if key.is_none() => key = Some(normalize_key(part).ok_or_else(|| format!("Unknown key: {}", part))?),
+            None => return Err(format!("{} has more than one key", accelerator)),
+        }
+    }
+    let key = key.ok_or_else(|| format!("{} has no key", accelerator))?;
+    if !held[..3].iter().any(|held| *held) && !is_function_key(&key) {
+        return Err(format!("{} needs Cmd, Ctrl or Alt", accelerator));
+    }
+    let mut parts: Vec<&str> = MODIFIERS.iter().zip(held).filter(|(_, held)| *held).map(|(name, _)| *name).collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+fn normalize_key(key: &str) -> Option<String> {
+    let upper = key.to_ascii_uppercase();
+    if upper.len() == 1 && upper.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Some(upper);
+    }
+    if is_function_key(&upper) {
+        return Some(upper);
+    }
+    NAMED_KEYS.iter().find(|name| name.eq_ignore_ascii_case(key)).map(|name| name.to_string())
+}
+
+fn is_function_key(key: &str) -> bool {
+    key.strip_prefix('F').and_then(|n| n.parse::<u8>().ok()).is_some_and(|n| (1..=24).contains(&n))
+}
+
+/// What an accelerator is already used for by the system, the editor fields
+/// or the app itself, if anything.
+fn reserved(accelerator: &str) -> Option<&'static str> {
+    let primary = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+    let hotkey = cfg!(target_os = "macos").then(|| normalize(clipboard_fix::HOTKEY_LABEL).ok()).flatten();
+    match accelerator.strip_prefix(primary).and_then(|rest| rest.strip_prefix('+')) {
+        Some("K") => Some("the command palette"),
+        Some("Q") => Some("quitting"),
+        Some("W") => Some("closing the window"),
+        Some("A" | "C" | "V" | "X" | "Z" | "Shift+Z") => Some("editing text"),
+        _ if hotkey.as_deref() == Some(accelerator) => Some("fixing the filename on the clipboard"),
+        _ => None,
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::accessibility::AccessibilityStore;
+use crate::activity::ActivityLog;
+use crate::api::{ApiTokens, ScanJobs};
+use crate::attention::AttentionTracker;
+use crate::bridge::{BridgeHandle, ServiceStatus};
+use crate::config::ConfigStore;
+use crate::email::EmailDigest;
+use crate::entries::EntryActionStore;
+use crate::events::EventLog;
+use crate::exclusions::ExclusionStore;
+use crate::expr::ExpressionEngine;
+use crate::issues::IssueCenter;
+use crate::launch::SessionProfiles;
+use crate::logging::LogLevelStore;
+use crate::notify::{NotificationDigest, SystemNotifications};
+use crate::pause::PauseTimer;
+use crate::pipeline::PipelineStore;
+use crate::plugins::PluginHost;
+use crate::preview::PlanPreviews;
+use crate::push::PushRelay;
+use crate::shortcuts::ShortcutStore;
+use crate::status_page::StatusPage;
+use crate::telemetry::Telemetry;
+use crate::tray::TrayState;
+use crate::user_switch::UserSwitch;
+use crate::webhook::WebhookStore;
+
+/// Everything the menu bar shares between commands, tray handlers, and the
+/// bridge event loop, managed once during setup. Each subsystem guards its
+/// own data, so handlers borrow what they need without extra locking here.
+pub struct AppState {
+    pub bridge: BridgeHandle,
+    /// Set once the tray is built; absent only during setup.
+    tray: OnceLock<TrayState>,
+    /// Latest status that arrived before the tray existed, replayed on install.
+    early_status: Mutex<Option<ServiceStatus>>,
+    /// Settings mirrored to `namefix.toml`; see [`crate::config`].
+    pub config: ConfigStore,
+    pub activity: ActivityLog,
+    pub events: EventLog,
+    pub attention: AttentionTracker,
+    pub issues: IssueCenter,
+    pub pause: PauseTimer,
+    pub session_profiles: SessionProfiles,
+    pub user_switch: UserSwitch,
+    pub telemetry: Telemetry,
+    pub exclusions: ExclusionStore,
+    pub entry_action: EntryActionStore,
+    pub shortcuts: ShortcutStore,
+    pub accessibility: AccessibilityStore,
+    pub notifications: NotificationDigest,
+    pub system_notifications: SystemNotifications,
+    pub status_page: StatusPage,
+    pub api_tokens: ApiTokens,
+    pub api_scans: ScanJobs,
+    pub push: PushRelay,
+    pub email_digest: EmailDigest,
+    pub webhook: WebhookStore,
+    pub log_levels: LogLevelStore,
+    pub plugins: PluginHost,
+    pub expressions: ExpressionEngine,
+    pub pipelines: PipelineStore,
+    pub previews: PlanPreviews,
+}
+
+impl AppState {
+    /// Build every subsystem, loading persisted settings from `config_dir`
+    /// and the event log from `data_dir`.
+    pub fn new(config_dir: &Path, data_dir: &Path, plugins: PluginHost) -> Self {
+        Self {
+            bridge: BridgeHandle::default(),
+            tray: OnceLock::new(),
+            early_status: Mutex::new(None),
+            config: ConfigStore::load(config_dir.join("namefix.toml")),
+            activity: ActivityLog::default(),
+            events: EventLog::open(&data_dir.join("events.sqlite3")),
+            attention: AttentionTracker::default(),
+            issues: IssueCenter::default(),
+            pause: PauseTimer::default(),
+            session_profiles: SessionProfiles::default(),
+            user_switch: UserSwitch::default(),
+            telemetry: Telemetry::load(config_dir.join("telemetry.json")),
+            exclusions: ExclusionStore::load(config_dir.join("watch-exclusions.json")),
+            entry_action: EntryActionStore::load(config_dir.join("entry-click-action.json")),
+            shortcuts: ShortcutStore::load(config_dir.join("shortcuts.json")),
+            accessibility: AccessibilityStore::load(config_dir.join("accessibility.json")),
+            notifications: NotificationDigest::default(),
+            system_notifications: SystemNotifications::load(config_dir.join("system-notifications.json")),
+            status_page: StatusPage::load(config_dir.join("status-page.json")),
+            api_tokens: ApiTokens::load(config_dir.join("api-tokens.json")),
+            api_scans: ScanJobs::default(),
+            push: PushRelay::load(config_dir.join("push.json")),
+            email_digest: EmailDigest::load(config_dir.join("email-digest.json")),
+            webhook: WebhookStore::load(config_dir.join("webhook.json")),
+            log_levels: LogLevelStore::load(config_dir.join("log-levels.json")),
+            plugins,
+            expressions: ExpressionEngine::new(),
+            pipelines: PipelineStore::load(config_dir.join("pipelines.json")),
+            previews: PlanPreviews::default(),
+        }
+    }
+
+    pub fn tray(&self) -> Option<&TrayState> {
+        self.tray.get()
+    }
+
+    /// Install the tray, returning any status that arrived before it did.
+    pub fn set_tray(&self, tray: TrayState) -> Option<ServiceStatus> {
+        if self.tray.set(tray).is_err() {
+            log::warn!("Tray was already initialised");
+        }
+        self.early_status.lock().expect("early status lock poisoned").take()
+    }
+
+    /// Hand `status` to the tray, or hold on to it until the tray is installed.
+    pub fn with_tray_or_buffer(&self, status: ServiceStatus, apply: impl FnOnce(&TrayState, &ServiceStatus)) {
+        // The buffer lock is held across the check so set_tray can't slip in between.
+        let mut early = self.early_status.lock().expect("early status lock poisoned");
+        match self.tray() {
+            Some(tray) => apply(tray, &status),
+            None => *early = Some(status),
+        }
+    }
+
+    /// The last status applied to the tray.
+    pub fn status(&self) -> ServiceStatus {
+        self.tray().map(TrayState::status).unwrap_or_default()
+    }
+}
@@ -0,0 +1,409 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Status schema emitted by this build. Bump when adding fields that change
+/// meaning; purely additive fields only need `#[serde(default)]`.
+pub const STATUS_SCHEMA_VERSION: u32 = 2;
+
+/// Optional engine features advertised in `ServiceStatus::capabilities` that
+/// the tray acts on. The webview reads the raw list for the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    SupportsPreview,
+    SupportsRules,
+}
+
+impl Capability {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Capability::SupportsPreview => "supports_preview",
+            Capability::SupportsRules => "supports_rules",
+        }
+    }
+}
+
+/// What a v1 bridge (which predates capability reporting) could do.
+const LEGACY_CAPABILITIES: [Capability; 2] = [Capability::SupportsPreview, Capability::SupportsRules];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryStats {
+    pub directory: String,
+    #[serde(rename = "lastEventAt")]
+    pub last_event_at: Option<u64>,
+    #[serde(rename = "renamesToday")]
+    pub renames_today: u32,
+    #[serde(rename = "errorCount")]
+    pub error_count: u32,
+    /// Events waiting in or being processed by the work queue.
+    pub queued: u32,
+    #[serde(rename = "skippedToday")]
+    pub skipped_today: u32,
+    /// Backend actually watching the directory, which may differ from the configured one.
+    #[serde(rename = "watchMode")]
+    pub watch_mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnoozedDirectory {
+    pub directory: String,
+    /// Epoch milliseconds at which the snooze lifts.
+    pub until: u64,
+}
+
+/// Which apps' files a directory renames, by the origin recorded on each file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryOriginRule {
+    pub directory: String,
+    /// When non-empty, only files from these apps are renamed.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// A directory that hands each renamed file off to a destination, a command, or both.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryHotFolder {
+    pub directory: String,
+    pub destination: Option<String>,
+    /// Program and arguments; `{file}` stands for the file's path.
+    pub command: Option<Vec<String>>,
+}
+
+/// How one watched directory is watched: subfolders, extension filters and
+/// its own dry-run setting.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryConfig {
+    pub directory: String,
+    pub recursive: bool,
+    /// Lowercase extensions without the dot; when non-empty only these are renamed.
+    #[serde(rename = "includeExtensions")]
+    pub include_extensions: Vec<String>,
+    /// Never renamed, even when also in `include_extensions`.
+    #[serde(rename = "excludeExtensions")]
+    pub exclude_extensions: Vec<String>,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+}
+
+/// A directory's user-defined group, shown as a nested submenu in the tray.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryGroup {
+    pub directory: String,
+    pub group: String,
+}
+
+/// A watched directory other users can also write to.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedDirectory {
+    pub directory: String,
+    #[serde(rename = "ownerUid")]
+    pub owner_uid: u32,
+    #[serde(rename = "ownedByOther")]
+    pub owned_by_other: bool,
+    #[serde(rename = "worldWritable")]
+    pub world_writable: bool,
+}
+
+/// Whether the service can save rename history. While degraded it keeps new
+/// renames in memory until the journal is repaired.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryHealth {
+    /// `ok` or `degraded`.
+    pub state: String,
+    /// `corrupt`, `disk-full` or `io-error` while degraded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(rename = "badLines", skip_serializing_if = "Option::is_none")]
+    pub bad_lines: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<HistoryBackup>,
+}
+
+impl HistoryHealth {
+    pub fn is_degraded(&self) -> bool {
+        self.state == "degraded"
+    }
+}
+
+/// The journal's latest backup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryBackup {
+    #[serde(rename = "savedAt")]
+    pub saved_at: u64,
+    pub entries: u64,
+}
+
+/// Whether settings are kept in step with other machines through a synced folder.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsSync {
+    pub dir: Option<String>,
+    /// `off`, `ok`, `conflict`, `locked` (encrypted with a key this machine lacks) or `error`.
+    pub state: String,
+    #[serde(rename = "lastSyncedAt", skip_serializing_if = "Option::is_none")]
+    pub last_synced_at: Option<u64>,
+    /// The other machine whose settings differ from this one's, while in conflict.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<SyncConflict>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl SettingsSync {
+    pub fn is_conflict(&self) -> bool {
+        self.state == "conflict"
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncConflict {
+    #[serde(rename = "deviceName")]
+    pub device_name: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: u64,
+}
+
+/// Pairing with other machines on the local network.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pairing {
+    pub enabled: bool,
+    /// The code this machine is showing, until it is used or expires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<PairingCode>,
+    pub peers: Vec<PairedPeer>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PairingCode {
+    pub code: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PairedPeer {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "pairedAt")]
+    pub paired_at: u64,
+}
+
+/// A removable or external volume holding watched directories, which are only
+/// watched while it is mounted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchedVolume {
+    pub root: String,
+    pub name: String,
+    pub mounted: bool,
+    /// Watchers were stopped so the volume can be ejected.
+    pub released: bool,
+    pub directories: Vec<String>,
+}
+
+/// A named run whose renames are tagged so they can be reviewed and undone together.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenameSession {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: u64,
+    /// Unset while the session is running.
+    #[serde(rename = "endedAt", skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+}
+
+/// Service status as exchanged with the bridge and the webview.
+///
+/// Every field is optional on the wire. Payloads pass through [`migrate`]
+/// before decoding, and fields this build does not know about are kept in
+/// `extra` so they survive the round trip to the UI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(remote = "Self", default)]
+pub struct ServiceStatus {
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: u32,
+    pub running: bool,
+    pub directories: Vec<String>,
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    /// Nothing is renamed; outcomes are written to the audit log without prompting.
+    #[serde(rename = "observerMode")]
+    pub observer_mode: bool,
+    /// Logs get hashed stand-ins for file paths; see [`crate::logging::set_privacy`].
+    #[serde(rename = "logPrivacy")]
+    pub log_privacy: bool,
+    pub history: HistoryHealth,
+    /// Whether an undone rename can be redone.
+    #[serde(rename = "canRedo")]
+    pub can_redo: bool,
+    /// Where the service keeps its history and logs, when moved from the defaults.
+    #[serde(rename = "dataDir")]
+    pub data_dir: Option<String>,
+    #[serde(rename = "settingsSync")]
+    pub settings_sync: SettingsSync,
+    pub pairing: Pairing,
+    #[serde(rename = "launchOnLogin")]
+    pub launch_on_login: bool,
+    #[serde(rename = "priorityDirectories")]
+    pub priority_directories: Vec<String>,
+    #[serde(rename = "pinnedDirectories")]
+    pub pinned_directories: Vec<String>,
+    #[serde(rename = "dryRunDirectories")]
+    pub dry_run_directories: Vec<String>,
+    #[serde(rename = "snoozedDirectories")]
+    pub snoozed_directories: Vec<SnoozedDirectory>,
+    /// `fsevents`, `endpoint-security`, `usn-journal` or `polling`; kept as a string like `capabilities`.
+    #[serde(rename = "watchBackend")]
+    pub watch_backend: String,
+    #[serde(rename = "originRules")]
+    pub origin_rules: Vec<DirectoryOriginRule>,
+    #[serde(rename = "directoryGroups")]
+    pub directory_groups: Vec<DirectoryGroup>,
+    #[serde(rename = "hotFolders")]
+    pub hot_folders: Vec<DirectoryHotFolder>,
+    /// One entry per listed directory.
+    #[serde(rename = "directoryConfigs")]
+    pub directory_configs: Vec<DirectoryConfig>,
+    /// Watched directories shared with other users, where renames may fail on permissions.
+    #[serde(rename = "sharedDirectories")]
+    pub shared_directories: Vec<SharedDirectory>,
+    pub volumes: Vec<WatchedVolume>,
+    /// The session new renames are tagged with, if one is running.
+    #[serde(rename = "activeSession")]
+    pub active_session: Option<RenameSession>,
+    #[serde(rename = "directoryStats")]
+    pub directory_stats: Vec<DirectoryStats>,
+    /// Kept as strings so capabilities from newer engines are not rejected.
+    pub capabilities: Vec<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for ServiceStatus {
+    fn default() -> Self {
+        Self {
+            schema_version: STATUS_SCHEMA_VERSION,
+            running: false,
+            directories: Vec::new(),
+            dry_run: false,
+            observer_mode: false,
+            log_privacy: true,
+            history: HistoryHealth::default(),
+            can_redo: false,
+            data_dir: None,
+            settings_sync: SettingsSync::default(),
+            pairing: Pairing::default(),
+            launch_on_login: false,
+            priority_directories: Vec::new(),
+            pinned_directories: Vec::new(),
+            dry_run_directories: Vec::new(),
+            snoozed_directories: Vec::new(),
+            watch_backend: String::new(),
+            origin_rules: Vec::new(),
+            directory_groups: Vec::new(),
+            hot_folders: Vec::new(),
+            directory_configs: Vec::new(),
+            shared_directories: Vec::new(),
+            volumes: Vec::new(),
+            active_session: None,
+            directory_stats: Vec::new(),
+            capabilities: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl ServiceStatus {
+    /// Whether `directory` only previews renames, either on its own or via the global flag.
+    pub fn is_dry_run(&self, directory: &str) -> bool {
+        self.dry_run || self.dry_run_directories.iter().any(|dir| dir == directory)
+    }
+
+    pub fn snoozed_until(&self, directory: &str) -> Option<u64> {
+        self.snoozed_directories.iter().find(|s| s.directory == directory).map(|s| s.until)
+    }
+
+    pub fn group_of(&self, directory: &str) -> Option<&str> {
+        self.directory_groups.iter().find(|g| g.directory == directory).map(|g| g.group.as_str())
+    }
+
+    pub fn shared(&self, directory: &str) -> Option<&SharedDirectory> {
+        self.shared_directories.iter().find(|s| s.directory == directory)
+    }
+
+    pub fn hot_folder(&self, directory: &str) -> Option<&DirectoryHotFolder> {
+        self.hot_folders.iter().find(|h| h.directory == directory)
+    }
+
+    pub fn dir_config(&self, directory: &str) -> Option<&DirectoryConfig> {
+        self.directory_configs.iter().find(|c| c.directory == directory)
+    }
+
+    pub fn volume_of(&self, directory: &str) -> Option<&WatchedVolume> {
+        self.volumes.iter().find(|v| v.directories.iter().any(|d| d == directory))
+    }
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.iter().any(|c| c == capability.as_str())
+    }
+}
+
+impl Serialize for ServiceStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ServiceStatus::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ServiceStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        ServiceStatus::deserialize(migrate(raw)).map_err(D::Error::custom)
+    }
+}
+
+/// Upgrade an older status payload to the current schema.
+///
+/// - v1 (no `schemaVersion`): the original flat shape. Very old bridges sent a
+///   single `watchDir` instead of `directories`.
+/// - Payloads without `capabilities` come from engines that predate it and
+///   get the legacy set.
+/// - Newer than this build: decoded as-is; unknown fields land in `extra`.
+pub fn migrate(mut raw: Value) -> Value {
+    let Some(object) = raw.as_object_mut() else {
+        return raw;
+    };
+    let version = object.get("schemaVersion").and_then(Value::as_u64).unwrap_or(1) as u32;
+
+    if version < 2 {
+        if !object.contains_key("directories") {
+            if let Some(dir) = object.remove("watchDir").and_then(|v| v.as_str().map(str::to_string)) {
+                object.insert("directories".into(), Value::Array(vec![Value::String(dir)]));
+            }
+        }
+        object.insert("schemaVersion".into(), Value::from(2));
+    } else if version > STATUS_SCHEMA_VERSION {
+        log::debug!("Status schema v{} is newer than v{}; unknown fields preserved", version, STATUS_SCHEMA_VERSION);
+    }
+    if !object.contains_key("capabilities") {
+        let legacy = LEGACY_CAPABILITIES.iter().map(|c| Value::from(c.as_str())).collect();
+        object.insert("capabilities".into(), Value::Array(legacy));
+    }
+    raw
+}
@@ -0,0 +1,708 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::activity::{now_millis, ActivityEntry, ActivityKind};
+use crate::api;
+use crate::bridge::ServiceStatus;
+use crate::pathdisplay::abbreviate_home;
+use crate::persist::{load_json, save_json};
+use crate::state::AppState;
+
+const DEFAULT_PORT: u16 = 7433;
+/// Renames listed on the page, newest first.
+const RECENT_RENAMES: usize = 5;
+/// How often the page reloads itself, in seconds.
+const REFRESH_SECS: u32 = 15;
+/// A client that stalls this long is dropped so it can't hold up the next one.
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+/// Requests are a line, a few headers and for the API a small JSON body;
+/// anything longer is cut off.
+const MAX_REQUEST: u64 = 16 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusPageSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub security: HttpSecurity,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+impl Default for StatusPageSettings {
+    fn default() -> Self {
+        Self { enabled: false, port: DEFAULT_PORT, security: HttpSecurity::default() }
+    }
+}
+
+/// Who may reach the server from a browser. Pages served from
+/// `allowed_origins` may call it cross-origin; other origins are refused.
+/// A `Host` that isn't an address, `localhost`, a `.local` name or one of
+/// `allowed_hosts` is refused too, so a site whose name was rebound to this
+/// machine's address can't read the page or call the API.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HttpSecurity {
+    /// Such as `http://dashboard.local:8080`.
+    pub allowed_origins: Vec<String>,
+    /// Other names this machine is reached by, without a port.
+    pub allowed_hosts: Vec<String>,
+}
+
+impl HttpSecurity {
+    /// The same options, each checked and lowercased the way requests are
+    /// compared with them.
+    fn normalized(self) -> Result<Self, String> {
+        let mut origins = Vec::new();
+        for origin in &self.allowed_origins {
+            let origin = normalize_origin(origin)
+                .ok_or_else(|| format!("{} is not an origin like http://host:port", origin.trim()))?;
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+        let mut hosts = Vec::new();
+        for host in &self.allowed_hosts {
+            let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+            if host.is_empty() || !host.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '.') {
+                return Err(format!("{} is not a host name", host));
+            }
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+        Ok(Self { allowed_origins: origins, allowed_hosts: hosts })
+    }
+
+    /// Whether a request with the `Host` header `host` is for this machine.
+    fn host_allowed(&self, host: &str) -> bool {
+        let host = host.trim().to_ascii_lowercase();
+        if host.starts_with('[') {
+            return true;
+        }
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if port.bytes().all(|byte| byte.is_ascii_digit()) => name,
+            _ => host.as_str(),
+        };
+        let name = name.trim_end_matches('.');
+        name.parse::<IpAddr>().is_ok()
+            || name == "localhost"
+            || name.ends_with(".local")
+            || self.allowed_hosts.iter().any(|allowed| allowed == name)
+    }
+}
+
+/// `raw` as `scheme://host[:port]` in lowercase, if it is an HTTP origin.
+fn normalize_origin(raw: &str) -> Option<String> {
+    let origin = raw.trim().trim_end_matches('/').to_ascii_lowercase();
+    let rest = origin.strip_prefix("http://").or_else(|| origin.strip_prefix("https://"))?;
+    let valid = !rest.is_empty() && !rest.contains(['/', '?', '#', '@']) && !rest.contains(char::is_whitespace);
+    valid.then_some(origin)
+}
+
+/// What the settings window shows: the stored settings and, while the page
+/// is being served, the address to open on another device.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusPageView {
+    pub enabled: bool,
+    pub port: u16,
+    pub url: Option<String>,
+    pub security: HttpSecurity,
+}
+
+/// A running listener; dropping it stops the accept loop.
+struct Server {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+impl Server {
+    fn start(app: AppHandle<Wry>, port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))
+            .map_err(|err| format!("Could not listen on port {}: {}", port, err))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        std::thread::spawn(move || serve(&app, listener, &stopped));
+        log::info!("Status page listening on port {}", port);
+        Ok(Self { port, stop })
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // accept() only returns for a connection, so make one to let the loop see the flag.
+        let _ = TcpStream::connect_timeout(&SocketAddr::from((Ipv4Addr::LOCALHOST, self.port)), IO_TIMEOUT);
+    }
+}
+
+/// A read-only status page for glancing at a headless machine from a phone,
+/// served over plain HTTP on the local network. Off by default, since anyone
+/// on the network can read it. The same server answers the write API in
+/// `api`, which needs a token.
+pub struct StatusPage {
+    path: PathBuf,
+    settings: Mutex<StatusPageSettings>,
+    server: Mutex<Option<Server>>,
+}
+
+impl StatusPage {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self { path, settings: Mutex::new(settings), server: Mutex::new(None) }
+    }
+
+    /// Start serving if the page was left on. Called once during setup.
+    pub fn start(&self, app: &AppHandle<Wry>) {
+        let settings = self.settings.lock().expect("status page lock poisoned").clone();
+        if !settings.enabled {
+            return;
+        }
+        match Server::start(app.clone(), settings.port) {
+            Ok(server) => *self.server.lock().expect("status page lock poisoned") = Some(server),
+            Err(err) => log::warn!("Status page not started: {}", err),
+        }
+    }
+
+    pub fn view(&self) -> StatusPageView {
+        let settings = self.settings.lock().expect("status page lock poisoned").clone();
+        let serving = self.server.lock().expect("status page lock poisoned").as_ref().map(|server| server.port);
+        StatusPageView {
+            enabled: settings.enabled,
+            port: settings.port,
+            url: serving.map(url),
+            security: settings.security,
+        }
+    }
+
+    pub fn security(&self) -> HttpSecurity {
+        self.settings.lock().expect("status page lock poisoned").security.clone()
+    }
+
+    /// Replace the allowed origins and host names. The running server uses
+    /// them from its next request.
+    pub fn set_security(&self, security: HttpSecurity) -> Result<StatusPageView, String> {
+        let security = security.normalized()?;
+        {
+            let mut settings = self.settings.lock().expect("status page lock poisoned");
+            let updated = StatusPageSettings { security, ..settings.clone() };
+            save_json(&self.path, &updated)?;
+            *settings = updated;
+        }
+        Ok(self.view())
+    }
+
+    /// Turn the page on or off, or move it to another port. The new listener
+    /// is bound before the old one stops, so a port that is taken leaves the
+    /// page where it was.
+    pub fn set(&self, app: &AppHandle<Wry>, enabled: bool, port: u16) -> Result<StatusPageView, String> {
+        if port < 1024 {
+            return Err("Choose a port from 1024 to 65535".to_string());
+        }
+        {
+            let mut server = self.server.lock().expect("status page lock poisoned");
+            if !enabled {
+                *server = None;
+            } else if server.as_ref().map(|running| running.port) != Some(port) {
+                *server = Some(Server::start(app.clone(), port)?);
+            }
+            let mut settings = self.settings.lock().expect("status page lock poisoned");
+            let updated = StatusPageSettings { enabled, port, security: settings.security.clone() };
+            save_json(&self.path, &updated)?;
+            *settings = updated;
+        }
+        Ok(self.view())
+    }
+}
+
+/// Where another device on the network reaches port `port`: this machine's
+/// address on the route out, falling back to `localhost` when offline.
+fn url(port: u16) -> String {
+    let host = lan_address().map(|ip| ip.to_string()).unwrap_or_else(|| "localhost".to_string());
+    format!("http://{}:{}/", host, port)
+}
+
+fn lan_address() -> Option<IpAddr> {
+    // Connecting a UDP socket only picks a route; nothing is sent.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified() && !ip.is_loopback()).then_some(ip)
+}
+
+fn serve(app: &AppHandle<Wry>, listener: TcpListener, stop: &AtomicBool) {
+    for stream in listener.incoming() {
+        if stop.load(Ordering::SeqCst) {
+            break;
+        }
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle(app, &stream) {
+                    log::debug!("Status page request failed: {}", err);
+                }
+            }
+            Err(err) => log::warn!("Status page could not accept a connection: {}", err),
+        }
+    }
+    log::info!("Status page stopped");
+}
+
+fn handle(app: &AppHandle<Wry>, stream: &TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut request = read_request(BufReader::new(stream.take(MAX_REQUEST)))?;
+    request.client = stream.peer_addr().ok().map(|addr| addr.ip());
+    let response = match guard(&request, &app.state::<AppState>().status_page.security()) {
+        Err(refused) => {
+            log::info!("Status page refused {} {}: {}", request.method, request.path, refused.body.trim());
+            refused
+        }
+        Ok(Some(origin)) if request.method == "OPTIONS" => {
+            Response::new("204 No Content", "text/plain; charset=utf-8", String::new()).allow_origin(origin)
+        }
+        Ok(cors) => {
+            let response = if request.path.starts_with(api::PREFIX) {
+                api::respond(app, &request)
+            } else {
+                respond(&request, || snapshot(app))
+            };
+            match cors {
+                Some(origin) => response.allow_origin(origin),
+                None => response,
+            }
+        }
+    };
+    let mut stream = stream;
+    stream.write_all(&response.into_bytes(request.method == "HEAD"))?;
+    stream.flush()
+}
+
+/// The parts of an HTTP request the server looks at.
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// The path without its query string.
+    pub path: String,
+    /// The bearer token from the `Authorization` header.
+    pub token: Option<String>,
+    pub body: String,
+    /// Where the request came from, for the API's access log.
+    pub client: Option<IpAddr>,
+    pub host: Option<String>,
+    /// Sent by browsers for cross-origin and `POST` requests.
+    pub origin: Option<String>,
+}
+
+/// Refuse `request` if its `Host` isn't a name for this machine or it comes
+/// from a page on an origin that isn't allowed. Otherwise the origin to
+/// allow in CORS headers, if it is cross-origin.
+fn guard(request: &Request, security: &HttpSecurity) -> Result<Option<String>, Response> {
+    if let Some(host) = request.host.as_deref().filter(|host| !security.host_allowed(host)) {
+        return Err(Response::text("403 Forbidden", &format!("This server does not answer for {}.\n", host)));
+    }
+    let Some(origin) = request.origin.as_deref() else {
+        return Ok(None);
+    };
+    let origin = normalize_origin(origin).unwrap_or_else(|| origin.to_string());
+    let same_origin = request.host.as_deref().map(|host| format!("http://{}", host.to_ascii_lowercase()));
+    if same_origin.as_deref() == Some(origin.as_str()) {
+        Ok(None)
+    } else if security.allowed_origins.contains(&origin) {
+        Ok(Some(origin))
+    } else {
+        Err(Response::text("403 Forbidden", &format!("Requests from {} are not allowed.\n", origin)))
+    }
+}
+
+fn read_request(mut reader: impl BufRead) -> std::io::Result<Request> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let mut request = Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        path: parts.next().unwrap_or_default().split('?').next().unwrap_or_default().to_string(),
+        ..Request::default()
+    };
+    // Headers are read to the end even when unused, so the client isn't reset mid-send.
+    let mut length = 0;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("authorization") {
+                request.token = value
+                    .split_once(' ')
+                    .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                    .map(|(_, token)| token.trim().to_string());
+            } else if name.eq_ignore_ascii_case("host") {
+                request.host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                request.origin = Some(value.to_string());
+            }
+        }
+        header.clear();
+    }
+    if length > 0 {
+        let mut body = Vec::new();
+        reader.take(length).read_to_end(&mut body)?;
+        request.body = String::from_utf8_lossy(&body).into_owned();
+    }
+    Ok(request)
+}
+
+/// A response waiting to be written.
+pub struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+    /// Ask for a bearer token, as a `401` should.
+    challenge: bool,
+    /// Seconds to wait before trying again, for a `429`.
+    retry_after: Option<u64>,
+    /// An origin whose pages may read the response.
+    allow_origin: Option<String>,
+}
+
+impl Response {
+    fn new(status: &'static str, content_type: &'static str, body: String) -> Self {
+        Self { status, content_type, body, challenge: false, retry_after: None, allow_origin: None }
+    }
+
+    pub fn status(&self) -> &'static str {
+        self.status
+    }
+
+    fn text(status: &'static str, body: &str) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", body.to_string())
+    }
+
+    pub fn json(status: &'static str, value: &impl Serialize) -> Self {
+        let body = serde_json::to_string_pretty(value).unwrap_or_else(|_| "{}".to_string());
+        Self::new(status, "application/json", body)
+    }
+
+    pub fn challenge(self) -> Self {
+        Self { challenge: true, ..self }
+    }
+
+    pub fn retry_after(self, seconds: u64) -> Self {
+        Self { retry_after: Some(seconds), ..self }
+    }
+
+    fn allow_origin(self, origin: String) -> Self {
+        Self { allow_origin: Some(origin), ..self }
+    }
+
+    fn into_bytes(self, head: bool) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+            self.status,
+            self.content_type,
+            self.body.len()
+        );
+        if self.challenge {
+            response.push_str("WWW-Authenticate: Bearer realm=\"namefix\"\r\n");
+        }
+        if let Some(seconds) = self.retry_after {
+            response.push_str(&format!("Retry-After: {}\r\n", seconds));
+        }
+        if let Some(origin) = &self.allow_origin {
+            response.push_str(&format!(
+                "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Methods: GET, HEAD, POST\r\n\
+                 Access-Control-Allow-Headers: Authorization, Content-Type\r\nAccess-Control-Max-Age: 600\r\n\
+                 Vary: Origin\r\n",
+                origin
+            ));
+        }
+        response.push_str("\r\n");
+        let mut response = response.into_bytes();
+        if !head {
+            response.extend_from_slice(self.body.as_bytes());
+        }
+        response
+    }
+}
+
+/// The status page's answer to `request`. Only `GET` and `HEAD` are
+/// answered: the page never changes anything.
+fn respond(request: &Request, snapshot: impl FnOnce() -> Snapshot) -> Response {
+    if request.method != "GET" && request.method != "HEAD" {
+        return Response::text("405 Method Not Allowed", "This page is read-only.\n");
+    }
+    match request.path.as_str() {
+        "/" | "/index.html" => Response::new("200 OK", "text/html; charset=utf-8", render_html(&snapshot())),
+        "/status.json" => Response::json("200 OK", &snapshot()),
+        _ => Response::text("404 Not Found", "Not found.\n"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RecentRename {
+    timestamp: u64,
+    from: String,
+    to: String,
+}
+
+/// What the page shows, also served as-is at `/status.json`.
+#[derive(Debug, Clone, Serialize)]
+struct Snapshot {
+    running: bool,
+    /// When a timed pause ends, in epoch milliseconds.
+    #[serde(rename = "pausedUntil")]
+    paused_until: Option<u64>,
+    #[serde(rename = "dryRun")]
+    dry_run: bool,
+    #[serde(rename = "observerMode")]
+    observer_mode: bool,
+    directories: Vec<String>,
+    #[serde(rename = "watchBackend")]
+    watch_backend: String,
+    #[serde(rename = "recentRenames")]
+    recent_renames: Vec<RecentRename>,
+    #[serde(rename = "generatedAt")]
+    generated_at: u64,
+}
+
+impl Snapshot {
+    fn new(status: &ServiceStatus, paused_until: Option<u64>, renames: &[ActivityEntry], now: u64) -> Self {
+        Self {
+            running: status.running,
+            paused_until: paused_until.filter(|_| !status.running),
+            dry_run: status.dry_run,
+            observer_mode: status.observer_mode,
+            directories: status.directories.iter().map(|dir| abbreviate_home(dir)).collect(),
+            watch_backend: status.watch_backend.clone(),
+            recent_renames: renames
+                .iter()
+                .filter_map(|entry| {
+                    Some(RecentRename {
+                        timestamp: entry.timestamp,
+                        from: entry.file.clone()?,
+                        to: entry.target.clone()?,
+                    })
+                })
+                .collect(),
+            generated_at: now,
+        }
+    }
+
+    fn headline(&self) -> String {
+        match (self.running, self.paused_until) {
+            (false, Some(until)) => format!("Paused until {}", clock_time(until)),
+            (false, None) => "Stopped".to_string(),
+            (true, _) if self.observer_mode => "Observing".to_string(),
+            (true, _) if self.dry_run => "Running (dry run)".to_string(),
+            (true, _) => "Running".to_string(),
+        }
+    }
+}
+
+fn snapshot(app: &AppHandle<Wry>) -> Snapshot {
+    let state = app.state::<AppState>();
+    let renames = state.activity.latest(&[ActivityKind::Renamed], RECENT_RENAMES);
+    Snapshot::new(&state.status(), state.pause.resume_at(), &renames, now_millis())
+}
+
+fn clock_time(millis: u64) -> String {
+    i64::try_from(millis)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .map(|time| time.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn render_html(snapshot: &Snapshot) -> String {
+    let list = |items: Vec<String>, empty: &str| {
+        if items.is_empty() {
+            format!("<p class=\"muted\">{}</p>", empty)
+        } else {
+            format!("<ul>{}</ul>", items.into_iter().map(|item| format!("<li>{}</li>", item)).collect::<String>())
+        }
+    };
+    let directories = list(snapshot.directories.iter().map(|dir| escape(dir)).collect(), "No folders watched.");
+    let renames = list(
+        snapshot
+            .recent_renames
+            .iter()
+            .map(|rename| {
+                format!(
+                    "{} → {} <span class=\"muted\">{}</span>",
+                    escape(&rename.from),
+                    escape(&rename.to),
+                    clock_time(rename.timestamp)
+                )
+            })
+            .collect(),
+        "Nothing renamed yet.",
+    );
+    format!(
+        r#"<!doctype html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta http-equiv="refresh" content="{refresh}">
+<title>Namefix: {headline}</title>
+<style>
+:root {{ color-scheme: light dark; font-family: -apple-system, system-ui, sans-serif; }}
+body {{ max-width: 32rem; margin: 0 auto; padding: 1.25rem; line-height: 1.4; }}
+h1 {{ font-size: 1.5rem; margin: 0 0 0.25rem; }}
+h2 {{ font-size: 1rem; margin: 1.5rem 0 0.5rem; }}
+ul {{ padding-left: 1.25rem; margin: 0; }}
+li {{ margin: 0.25rem 0; overflow-wrap: anywhere; }}
+.muted {{ opacity: 0.6; }}
+</style>
+</head>
+<body>
+<h1>{headline}</h1>
+<p class="muted">Watching with {backend} · updated {updated}</p>
+<h2>Folders</h2>
+{directories}
+<h2>Recent Renames</h2>
+{renames}
+</body>
+</html>
+"#,
+        refresh = REFRESH_SECS,
+        headline = escape(&snapshot.headline()),
+        backend = escape(&snapshot.watch_backend),
+        updated = clock_time(snapshot.generated_at),
+        directories = directories,
+        renames = renames,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Snapshot {
+        Snapshot {
+            running: true,
+            paused_until: None,
+            dry_run: false,
+            observer_mode: false,
+            directories: vec!["~/Desktop".to_string()],
+            watch_backend: "fsevents".to_string(),
+            recent_renames: vec![RecentRename {
+                timestamp: 0,
+                from: "<script>.png".to_string(),
+                to: "a & b.png".to_string(),
+            }],
+            generated_at: 0,
+        }
+    }
+
+    fn text(raw: &str) -> String {
+        let request = read_request(raw.as_bytes()).unwrap();
+        String::from_utf8(respond(&request, sample).into_bytes(request.method == "HEAD")).unwrap()
+    }
+
+    #[test]
+    fn serves_page_and_json_read_only() {
+        let page = text("GET / HTTP/1.1\r\n");
+        assert!(page.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(page.contains("Content-Type: text/html"));
+        assert!(page.contains("<h1>Running</h1>"));
+
+        let json = text("GET /status.json?t=1 HTTP/1.1\r\n");
+        let body = json.split("\r\n\r\n").nth(1).unwrap();
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(value["directories"][0], "~/Desktop");
+        assert_eq!(value["recentRenames"][0]["to"], "a & b.png");
+
+        assert!(text("POST / HTTP/1.1\r\n").starts_with("HTTP/1.1 405"));
+        assert!(text("GET /config HTTP/1.1\r\n").starts_with("HTTP/1.1 404"));
+        assert!(text("HEAD / HTTP/1.1\r\n").ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn reads_token_and_body_for_the_api() {
+        let raw = "POST /api/rules?x=1 HTTP/1.1\r\nauthorization: bearer nfx_abc\r\n\
+                   Content-Length: 7\r\n\r\n{\"a\":1}trailing";
+        let request = read_request(raw.as_bytes()).unwrap();
+        assert_eq!(request.path, "/api/rules");
+        assert_eq!(request.token.as_deref(), Some("nfx_abc"));
+        assert_eq!(request.body, "{\"a\":1}");
+        let basic = read_request("GET / HTTP/1.1\r\nAuthorization: Basic eA==\r\n\r\n".as_bytes()).unwrap();
+        assert_eq!(basic.token, None);
+    }
+
+    #[test]
+    fn refuses_rebound_hosts_and_unknown_origins() {
+        let security = HttpSecurity {
+            allowed_origins: vec![" HTTP://Dashboard.local:3000/ ".to_string()],
+            allowed_hosts: vec!["mini.example.lan".to_string()],
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(security.allowed_origins, ["http://dashboard.local:3000"]);
+        let request = |host: &str, origin: Option<&str>| Request {
+            host: Some(host.to_string()),
+            origin: origin.map(str::to_string),
+            ..Request::default()
+        };
+
+        for host in ["192.168.1.20:7433", "localhost:7433", "[::1]:7433", "studio.local", "Mini.Example.Lan:7433"] {
+            assert_eq!(guard(&request(host, None), &security).ok(), Some(None), "{}", host);
+        }
+        assert!(guard(&request("evil.example.com:7433", None), &security).is_err());
+        assert!(guard(&Request::default(), &security).is_ok());
+
+        let from = |origin| guard(&request("192.168.1.20:7433", Some(origin)), &security);
+        assert_eq!(from("http://192.168.1.20:7433").ok(), Some(None));
+        assert_eq!(from("http://dashboard.local:3000").ok(), Some(Some("http://dashboard.local:3000".to_string())));
+        assert!(from("http://evil.example.com").is_err());
+        assert!(from("null").is_err());
+
+        let allowed = Response::text("200 OK", "").allow_origin("http://dashboard.local:3000".to_string());
+        let head = String::from_utf8(allowed.into_bytes(false)).unwrap();
+        assert!(head.contains("Access-Control-Allow-Origin: http://dashboard.local:3000\r\n"));
+        let invalid = HttpSecurity { allowed_origins: vec!["dashboard.local".to_string()], ..HttpSecurity::default() };
+        assert!(invalid.normalized().is_err());
+    }
+
+    #[test]
+    fn escapes_file_names() {
+        let html = render_html(&sample());
+        assert!(html.contains("&lt;script&gt;.png → a &amp; b.png"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn headline_reflects_mode() {
+        let mut snapshot = sample();
+        snapshot.dry_run = true;
+        assert_eq!(snapshot.headline(), "Running (dry run)");
+        snapshot.running = false;
+        assert_eq!(snapshot.headline(), "Stopped");
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::activity::now_millis;
+use crate::bridge::BridgeEvent;
+use crate::persist::{load_json, save_json};
+
+/// Days of per-day rename counts kept in the report.
+const RETAINED_DAYS: usize = 30;
+
+/// Everything telemetry knows. Only aggregate counters are stored: no file
+/// names, paths, patterns, or templates ever enter this struct.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Stored {
+    enabled: bool,
+    #[serde(rename = "consentedAt")]
+    consented_at: Option<u64>,
+    #[serde(rename = "renamesPerDay")]
+    renames_per_day: BTreeMap<String, u64>,
+    #[serde(rename = "ruleTypes")]
+    rule_types: BTreeMap<String, u64>,
+}
+
+/// The exact payload a report would contain, shown to the user before and
+/// after they opt in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryPreview {
+    pub enabled: bool,
+    #[serde(rename = "consentedAt")]
+    pub consented_at: Option<u64>,
+    pub report: TelemetryReport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    #[serde(rename = "appVersion")]
+    pub app_version: String,
+    pub platform: String,
+    pub arch: String,
+    #[serde(rename = "renamesPerDay")]
+    pub renames_per_day: BTreeMap<String, u64>,
+    /// Number of enabled rules of each kind, e.g. `rename`, `convert`, `regex`.
+    #[serde(rename = "ruleTypes")]
+    pub rule_types: BTreeMap<String, u64>,
+}
+
+/// Opt-in usage counters. Off by default; nothing is counted until the user
+/// consents, and opting out discards everything collected so far.
+pub struct Telemetry {
+    path: PathBuf,
+    inner: Mutex<Stored>,
+}
+
+impl Telemetry {
+    pub fn load(path: PathBuf) -> Self {
+        let stored = load_json(&path);
+        Self { path, inner: Mutex::new(stored) }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<TelemetryPreview, String> {
+        let mut inner = self.inner.lock().expect("telemetry lock poisoned");
+        *inner = if enabled {
+            Stored { enabled: true, consented_at: Some(now_millis()), ..inner.clone() }
+        } else {
+            Stored::default()
+        };
+        save_json(&self.path, &*inner)?;
+        Ok(preview(&inner))
+    }
+
+    /// Refresh the rule-type counts from the current profiles, if opted in.
+    pub fn record_profiles(&self, profiles: &[Value]) {
+        let mut inner = self.inner.lock().expect("telemetry lock poisoned");
+        if inner.enabled {
+            inner.rule_types = rule_types(profiles);
+            if let Err(err) = save_json(&self.path, &*inner) {
+                log::warn!("Failed to save telemetry counters: {}", err);
+            }
+        }
+    }
+
+    pub fn preview(&self) -> TelemetryPreview {
+        preview(&self.inner.lock().expect("telemetry lock poisoned"))
+    }
+
+    pub fn observe(&self, event: &BridgeEvent) {
+        let mut inner = self.inner.lock().expect("telemetry lock poisoned");
+        if !inner.enabled {
+            return;
+        }
+        match event.name.as_str() {
+            "file" if matches!(event.payload.get("kind").and_then(Value::as_str), Some("applied" | "converted")) => {
+                let today = Local::now().format("%Y-%m-%d").to_string();
+                *inner.renames_per_day.entry(today).or_default() += 1;
+                while inner.renames_per_day.len() > RETAINED_DAYS {
+                    inner.renames_per_day.pop_first();
+                }
+            }
+            "config" => {
+                let Some(profiles) = event.payload.get("profiles").and_then(Value::as_array) else {
+                    return;
+                };
+                inner.rule_types = rule_types(profiles);
+            }
+            _ => return,
+        }
+        if let Err(err) = save_json(&self.path, &*inner) {
+            log::warn!("Failed to save telemetry counters: {}", err);
+        }
+    }
+}
+
+fn rule_types(profiles: &[Value]) -> BTreeMap<String, u64> {
+    let mut counts = BTreeMap::new();
+    for profile in profiles {
+        if profile.get("enabled").and_then(Value::as_bool) != Some(true) {
+            continue;
+        }
+        let action = profile.get("action").and_then(Value::as_str).unwrap_or("rename");
+        // Only known kinds are counted so user-defined strings never leak.
+        if matches!(action, "rename" | "convert" | "rename+convert") {
+            *counts.entry(action.to_string()).or_default() += 1;
+        }
+        if profile.get("isRegex").and_then(Value::as_bool) == Some(true) {
+            *counts.entry("regex".to_string()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+fn preview(stored: &Stored) -> TelemetryPreview {
+    TelemetryPreview {
+        enabled: stored.enabled,
+        consented_at: stored.consented_at,
+        report: TelemetryReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            platform: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            renames_per_day: stored.renames_per_day.clone(),
+            rule_types: stored.rule_types.clone(),
+        },
+    }
+}
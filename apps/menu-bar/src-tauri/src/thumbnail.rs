@@ -0,0 +1,220 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use image::{ImageFormat, ImageReader};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+/// Edge length, in pixels, used when the caller doesn't ask for one.
+pub const DEFAULT_SIZE: u32 = 256;
+const MIN_SIZE: u32 = 32;
+const MAX_SIZE: u32 = 1024;
+
+/// Larger files get the generic icon rather than being decoded.
+const MAX_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Once the cache grows past this, the least recently used thumbnails go.
+const CACHE_BUDGET_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Where thumbnails are cached: under the service's data directory when the
+/// user moved it (`data_dir`), otherwise in the app's cache directory.
+pub fn cache_dir(app: &AppHandle, data_dir: Option<&str>) -> tauri::Result<PathBuf> {
+    match data_dir {
+        Some(dir) => Ok(Path::new(dir).join("cache").join("thumbnails")),
+        None => Ok(app.path().app_cache_dir()?.join("thumbnails")),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// Decoded in-process.
+    Image,
+    /// Rendered by a system tool: Quick Look on macOS, poppler elsewhere.
+    Document,
+    Other,
+}
+
+/// A thumbnail of the file, at most `size` pixels on its longest edge, as a
+/// `data:` URL the webview can show in an `<img>`. Images are scaled, PDFs show
+/// their first page, and anything else gets an icon labelled with its extension.
+/// Rendered thumbnails are cached in `cache_dir`, keyed by the file's path,
+/// size, and modification time.
+pub fn thumbnail(cache_dir: &Path, path: &Path, size: u32) -> Result<String, String> {
+    let size = size.clamp(MIN_SIZE, MAX_SIZE);
+    let metadata = fs::metadata(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    if !metadata.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    let kind = kind_of(path);
+    if kind == Kind::Other || metadata.len() > MAX_SOURCE_BYTES {
+        return Ok(generic_icon(path));
+    }
+
+    let key = cache_key(path, &metadata, size);
+    let entry = cache_dir.join(format!("{}.png", key));
+    if let Ok(bytes) = fs::read(&entry) {
+        touch(&entry);
+        return Ok(png_url(&bytes));
+    }
+
+    let rendered = match kind {
+        // HEIC and friends decode through Quick Look where the image crate can't.
+        Kind::Image => render_image(path, size).or_else(|_| render_document(cache_dir, &key, path, size)),
+        _ => render_document(cache_dir, &key, path, size),
+    };
+    match rendered {
+        Ok(bytes) => {
+            store(cache_dir, &entry, &bytes);
+            Ok(png_url(&bytes))
+        }
+        Err(_) => Ok(generic_icon(path)),
+    }
+}
+
+fn kind_of(path: &Path) -> Kind {
+    let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+        return Kind::Other;
+    };
+    match ext.to_ascii_lowercase().as_str() {
+        "png" | "jpg" | "jpeg" | "gif" | "webp" | "heic" | "bmp" | "tif" | "tiff" => Kind::Image,
+        "pdf" => Kind::Document,
+        _ => Kind::Other,
+    }
+}
+
+fn render_image(path: &Path, size: u32) -> Result<Vec<u8>, String> {
+    let image = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(|err| err.to_string())?
+        .decode()
+        .map_err(|err| err.to_string())?;
+    let mut bytes = Vec::new();
+    image
+        .thumbnail(size, size)
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+/// Render the first page of a document to PNG in a scratch directory and read it back.
+fn render_document(cache_dir: &Path, key: &str, path: &Path, size: u32) -> Result<Vec<u8>, String> {
+    let scratch = cache_dir.join(format!(".render-{}", key));
+    fs::create_dir_all(&scratch).map_err(|err| err.to_string())?;
+    let result = run_renderer(&scratch, path, size);
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+fn run_renderer(scratch: &Path, path: &Path, size: u32) -> Result<Vec<u8>, String> {
+    let output: PathBuf;
+    let mut command = if cfg!(target_os = "macos") {
+        let name = path.file_name().ok_or("file has no name")?;
+        output = scratch.join(format!("{}.png", name.to_string_lossy()));
+        let mut command = Command::new("qlmanage");
+        command.arg("-t").arg("-s").arg(size.to_string()).arg("-o").arg(scratch).arg(path);
+        command
+    } else if kind_of(path) == Kind::Document {
+        output = scratch.join("page.png");
+        let mut command = Command::new("pdftoppm");
+        command
+            .args(["-png", "-singlefile", "-f", "1", "-l", "1", "-scale-to"])
+            .arg(size.to_string())
+            .arg(path)
+            .arg(scratch.join("page"));
+        command
+    } else {
+        return Err(format!("no renderer for {}", path.display()));
+    };
+    let status = command.output().map_err(|err| err.to_string())?.status;
+    if !status.success() {
+        return Err(format!("thumbnail renderer exited with {}", status));
+    }
+    fs::read(&output).map_err(|err| err.to_string())
+}
+
+fn cache_key(path: &Path, metadata: &fs::Metadata, size: u32) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or(0);
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(modified.to_le_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(size.to_le_bytes());
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Caching is best-effort: a thumbnail that can't be written is still returned.
+fn store(cache_dir: &Path, entry: &Path, bytes: &[u8]) {
+    if fs::create_dir_all(cache_dir).is_err() || fs::write(entry, bytes).is_err() {
+        return;
+    }
+    evict(cache_dir);
+}
+
+/// Mark a cached thumbnail as recently used.
+fn touch(entry: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(entry) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Remove the least recently used thumbnails until the cache fits its budget.
+fn evict(cache_dir: &Path) {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut cached: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "png"))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().unwrap_or(UNIX_EPOCH), metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = cached.iter().map(|(_, len, _)| len).sum();
+    cached.sort_by_key(|(modified, _, _)| *modified);
+    for (_, len, path) in cached {
+        if total <= CACHE_BUDGET_BYTES {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+}
+
+fn png_url(bytes: &[u8]) -> String {
+    format!("data:image/png;base64,{}", STANDARD.encode(bytes))
+}
+
+/// A document icon labelled with the file's extension, as an SVG `data:` URL.
+fn generic_icon(path: &Path) -> String {
+    let label: String = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_uppercase())
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .take(4)
+        .collect();
+    let svg = format!(
+        concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 64 80">"##,
+            r##"<path d="M4 2h40l16 16v58a2 2 0 0 1-2 2H4a2 2 0 0 1-2-2V4a2 2 0 0 1 2-2z" fill="#f3f4f6" stroke="#9ca3af" stroke-width="2"/>"##,
+            r##"<path d="M44 2v16h16" fill="none" stroke="#9ca3af" stroke-width="2"/>"##,
+            r##"<text x="31" y="56" font-family="-apple-system, sans-serif" font-size="14" font-weight="600" text-anchor="middle" fill="#4b5563">{}</text>"##,
+            "</svg>"
+        ),
+        label
+    );
+    format!("data:image/svg+xml;base64,{}", STANDARD.encode(svg))
+}
@@ -1,25 +1,88 @@
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 use tauri::{
     async_runtime,
     image::Image,
-    menu::{CheckMenuItem, MenuBuilder, MenuItem, PredefinedMenuItem, Submenu, SubmenuBuilder},
+    menu::{CheckMenuItem, Menu, MenuBuilder, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu, SubmenuBuilder},
     tray::{TrayIcon, TrayIconBuilder},
     AppHandle, Emitter, Listener, Manager, Wry,
 };
 
-use crate::bridge::{self, BridgeState, ServiceStatus};
+use crate::accessibility;
+use crate::activity::{now_millis, ActivityKind};
+use crate::bridge::{self, DirectoryStats, ServiceStatus};
+use crate::actions;
+use crate::clipboard_fix;
+use crate::config;
+use crate::entries::{self, EntryClickAction, RenameEntry};
+use crate::events;
+use crate::logging;
+use crate::notify;
+use crate::pathdisplay;
+use crate::pause::{self, PauseDuration};
+use crate::report;
+use crate::shortcuts::ShortcutStore;
+use crate::state::AppState;
+use crate::status::{Capability, DirectoryConfig};
+use crate::volume;
 
 const MENU_VERSION: &str = "version-label";
 const MENU_STATUS: &str = "status-label";
+const MENU_STATS: &str = "stats-label";
+const MENU_ATTENTION: &str = "needs-attention";
+const MENU_SNOOZE_OFFER: &str = "snooze-offer";
+const MENU_END_SESSION: &str = "end-session";
 const MENU_TOGGLE_RUNNING: &str = "toggle-running";
+const MENU_PAUSE_FOR: &str = "pause-for";
+const MENU_PAUSE_30_MINUTES: &str = "pause-30-minutes";
+const MENU_PAUSE_1_HOUR: &str = "pause-1-hour";
+const MENU_PAUSE_UNTIL_TOMORROW: &str = "pause-until-tomorrow";
 const MENU_TOGGLE_DRY_RUN: &str = "toggle-dry-run";
 const MENU_LAUNCH_ON_LOGIN: &str = "launch-on-login";
 const MENU_UNDO: &str = "undo";
+const MENU_REDO: &str = "redo";
+const MENU_EXPORT_REPORT: &str = "export-weekly-report";
+const MENU_EXPORT_SETTINGS: &str = "export-settings";
+const MENU_IMPORT_SETTINGS: &str = "import-settings";
+const MENU_FIX_CLIPBOARD: &str = "fix-clipboard";
+const MENU_RENAME_FOLDER: &str = "rename-folder";
 const MENU_OPEN_MAIN: &str = "open-main";
 const MENU_QUIT: &str = "quit";
+/// Tray items named after palette actions, which show the action's shortcut.
+const SHORTCUT_ITEMS: [&str; 11] = [
+    MENU_TOGGLE_RUNNING,
+    MENU_PAUSE_30_MINUTES,
+    MENU_PAUSE_1_HOUR,
+    MENU_PAUSE_UNTIL_TOMORROW,
+    MENU_TOGGLE_DRY_RUN,
+    MENU_UNDO,
+    MENU_FIX_CLIPBOARD,
+    MENU_RENAME_FOLDER,
+    MENU_EXPORT_REPORT,
+    MENU_EXPORT_SETTINGS,
+    MENU_IMPORT_SETTINGS,
+];
 const MENU_DIRECTORIES: &str = "directories";
+const MENU_DIRECTORY_DRY_RUN_PREFIX: &str = "directory-dry-run:";
+const MENU_DIRECTORY_RECURSIVE_PREFIX: &str = "directory-recursive:";
+const MENU_DIRECTORY_SNOOZE_PREFIX: &str = "directory-snooze:";
+/// Followed by the volume's mount point.
+const MENU_DIRECTORY_EJECT_PREFIX: &str = "directory-eject:";
+const MENU_RECENT: &str = "recent-renames";
+const MENU_RECENT_PREFIX: &str = "recent-rename:";
+const MENU_RECENT_COPY_NEW_PREFIX: &str = "recent-copy-new:";
+const MENU_RECENT_COPY_OLD_PREFIX: &str = "recent-copy-old:";
+const MENU_RECENT_QUICK_LOOK_PREFIX: &str = "recent-quick-look:";
+const MENU_RECENT_UNDO_PREFIX: &str = "recent-undo:";
+/// Followed by `<entry index>:<application path>`.
+const MENU_RECENT_OPEN_WITH_PREFIX: &str = "recent-open-with:";
+/// Renames listed under "Recent Renames".
+const RECENT_LIMIT: usize = 5;
+/// Snooze length offered from the tray, both per directory and after a burst.
+const SNOOZE_DURATION: PauseDuration = PauseDuration::Minutes(30);
+const PRIORITY_MARKER: &str = "★";
 
 fn get_version_string() -> String {
     let version = env!("CARGO_PKG_VERSION");
@@ -37,69 +100,242 @@ fn get_version_string() -> String {
 #[derive(Clone)]
 pub struct TrayState {
     tray: TrayIcon<Wry>,
+    menu: Menu<Wry>,
     status_label: MenuItem<Wry>,
+    /// "142 files renamed this week", from the event log.
+    stats_label: MenuItem<Wry>,
+    attention: MenuItem<Wry>,
+    /// Open issues, shown as the icon badge.
+    attention_count: Arc<AtomicUsize>,
+    snooze_offer: MenuItem<Wry>,
+    /// Directory the snooze offer refers to, while it is shown.
+    snooze_offer_directory: Arc<Mutex<Option<String>>>,
+    /// "End Session" item, shown while a session is running.
+    session: MenuItem<Wry>,
     toggle_running: MenuItem<Wry>,
     dry_run: CheckMenuItem<Wry>,
     launch_on_login: CheckMenuItem<Wry>,
     undo: MenuItem<Wry>,
+    redo: MenuItem<Wry>,
     directories: Submenu<Wry>,
+    recent: Submenu<Wry>,
+    /// Renames behind the "Recent Renames" items, indexed by their menu id suffix.
+    recent_entries: Arc<Mutex<Vec<RenameEntry>>>,
     current_status: Arc<Mutex<ServiceStatus>>,
 }
 
 impl TrayState {
+    /// Apply a status update, skipping it when nothing changed since the last one.
     fn apply_status(&self, app: &AppHandle<Wry>, status: &ServiceStatus) -> tauri::Result<()> {
+        let mut writable = self.current_status.lock().expect("status lock poisoned");
+        if *writable == *status {
+            return Ok(());
+        }
+        *writable = status.clone();
+        self.render(app, status)
+    }
+
+    /// Apply a status update unconditionally. Used after menu actions, since
+    /// clicking a check item toggles it natively even if the action failed.
+    fn force_status(&self, app: &AppHandle<Wry>, status: &ServiceStatus) -> tauri::Result<()> {
         let mut writable = self.current_status.lock().expect("status lock poisoned");
         *writable = status.clone();
+        self.render(app, status)
+    }
+
+    fn render(&self, app: &AppHandle<Wry>, status: &ServiceStatus) -> tauri::Result<()> {
+        logging::set_privacy(status.log_privacy);
+        if let Some(state) = app.try_state::<AppState>() {
+            // Also on the first status, when the history was moved in an earlier session.
+            state.events.move_to(&events::db_path(app, status.data_dir.as_deref())?);
+        }
+        if status.running {
+            // Resuming by hand supersedes any timed pause.
+            if let Some(state) = app.try_state::<AppState>() {
+                state.pause.cancel();
+            }
+        }
 
         let run_label = if status.running { "Pause Watching" } else { "Start Watching" };
         self.toggle_running.set_text(run_label)?;
         self.dry_run.set_checked(status.dry_run)?;
+        self.dry_run.set_enabled(status.supports(Capability::SupportsPreview))?;
         self.launch_on_login.set_checked(status.launch_on_login)?;
+        self.redo.set_enabled(status.can_redo)?;
 
-        let directories_label = if status.directories.is_empty() {
-            "Status: Paused (no directories)".to_string()
-        } else if status.running {
-            format!("Status: Watching {} dir{}", status.directories.len(), if status.directories.len() == 1 { "" } else { "s" })
-        } else {
-            "Status: Paused".to_string()
-        };
-        self.status_label.set_text(directories_label)?;
+        self.status_label.set_text(status_label(app, status))?;
+        self.apply_session(status)?;
+        self.apply_description(app, status)?;
+        self.apply_icon(app)?;
+
+        rebuild_directories(app, &self.directories, status)?;
+
+        let offered = self.snooze_offer_directory.lock().expect("snooze lock poisoned").clone();
+        if offered.is_some_and(|dir| status.snoozed_until(&dir).is_some()) {
+            self.set_snooze_offer(None)?;
+        }
+        Ok(())
+    }
+
+    /// Show or hide the "Snooze <dir>" suggestion made after a burst of manual changes.
+    fn set_snooze_offer(&self, directory: Option<&str>) -> tauri::Result<()> {
+        let visible = self.menu.get(MENU_SNOOZE_OFFER).is_some();
+        match directory {
+            Some(dir) => {
+                let label = pathdisplay::display_path(dir, pathdisplay::MENU_PATH_WIDTH);
+                self.snooze_offer.set_text(format!("Busy: Snooze {} for 30 Minutes", label))?;
+                if !visible {
+                    self.menu.insert(&self.snooze_offer, 2)?;
+                }
+            }
+            None if visible => self.menu.remove(&self.snooze_offer)?,
+            None => {}
+        }
+        *self.snooze_offer_directory.lock().expect("snooze lock poisoned") = directory.map(str::to_string);
+        Ok(())
+    }
+
+    /// Show the running session, if any, in the menu.
+    fn apply_session(&self, status: &ServiceStatus) -> tauri::Result<()> {
+        let visible = self.menu.get(MENU_END_SESSION).is_some();
+        match &status.active_session {
+            Some(session) => {
+                self.session.set_text(format!("End Session: {}", session.name))?;
+                if !visible {
+                    self.menu.insert(&self.session, 2)?;
+                }
+            }
+            None => {
+                if visible {
+                    self.menu.remove(&self.session)?;
+                }
+            }
+        }
+        Ok(())
+    }
 
-        rebuild_directories(app, &self.directories, &status.directories)?;
+    /// Describe the icon's state in its tooltip, which screen readers announce.
+    fn apply_description(&self, app: &AppHandle<Wry>, status: &ServiceStatus) -> tauri::Result<()> {
+        let label = status_label(app, status);
+        let session = status.active_session.as_ref().map(|session| session.name.as_str());
+        let attention = self.attention_count.load(Ordering::SeqCst);
+        self.tray.set_tooltip(Some(accessibility::describe(&label, attention, session)))
+    }
+
+    /// Draw the icon for the current state, with the badge while issues are
+    /// open, in the chosen contrast.
+    fn apply_icon(&self, app: &AppHandle<Wry>) -> tauri::Result<()> {
+        let state = IconState::of(app, &self.status());
+        let badge = self.attention_count.load(Ordering::SeqCst) > 0;
+        self.tray.set_icon(Some(tray_icon_image(state, badge, high_contrast_icon(app))?))?;
+        self.tray.set_icon_as_template(cfg!(target_os = "macos"))
+    }
+
+    /// Show or hide the "Error Center" item and the icon badge.
+    fn apply_attention(&self, app: &AppHandle<Wry>, count: usize) -> tauri::Result<()> {
+        let visible = self.menu.get(MENU_ATTENTION).is_some();
+        if count > 0 {
+            self.attention.set_text(format!("Error Center ({})", count))?;
+            if !visible {
+                // Directly below the status label.
+                self.menu.insert(&self.attention, 2)?;
+            }
+        } else if visible {
+            self.menu.remove(&self.attention)?;
+        }
+        self.attention_count.store(count, Ordering::SeqCst);
+        self.apply_icon(app)?;
+        self.apply_description(app, &self.status())
+    }
+
+    fn refresh_status_label(&self, app: &AppHandle<Wry>) -> tauri::Result<()> {
+        let status = self.status();
+        self.status_label.set_text(status_label(app, &status))?;
+        self.apply_description(app, &status)?;
+        // The service starting or failing changes the icon without a status update.
+        self.apply_icon(app)
+    }
+
+    fn apply_recent(&self, app: &AppHandle<Wry>, entries: Vec<RenameEntry>) -> tauri::Result<()> {
+        let mut current = self.recent_entries.lock().expect("recent lock poisoned");
+        if *current == entries {
+            return Ok(());
+        }
+        rebuild_recent(app, &self.recent, &entries)?;
+        *current = entries;
         Ok(())
     }
 
-    fn status(&self) -> ServiceStatus {
+    fn recent_entry(&self, index: usize) -> Option<RenameEntry> {
+        self.recent_entries.lock().expect("recent lock poisoned").get(index).cloned()
+    }
+
+    /// Take the directory the snooze offer refers to and hide the offer.
+    fn take_snooze_offer(&self) -> Option<String> {
+        let offered = self.snooze_offer_directory.lock().expect("snooze lock poisoned").clone();
+        let _ = self.set_snooze_offer(None);
+        offered
+    }
+
+    pub(crate) fn status(&self) -> ServiceStatus {
         self.current_status.lock().expect("status lock poisoned").clone()
     }
 }
 
-pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<TrayState> {
+pub fn init_tray(app: &AppHandle<Wry>) -> tauri::Result<TrayState> {
     let version_item = MenuItem::with_id(app, MENU_VERSION, get_version_string(), true, None::<&str>)?;
     version_item.set_enabled(false)?;
 
     let status_item = MenuItem::with_id(app, MENU_STATUS, "Status: Loading…", true, None::<&str>)?;
     status_item.set_enabled(false)?;
+    let stats_item = MenuItem::with_id(app, MENU_STATS, events::week_summary(0), false, None::<&str>)?;
+
+    let attention_item = MenuItem::with_id(app, MENU_ATTENTION, "Error Center", true, None::<&str>)?;
+    let snooze_offer = MenuItem::with_id(app, MENU_SNOOZE_OFFER, "Snooze", true, None::<&str>)?;
+    let session_item = MenuItem::with_id(app, MENU_END_SESSION, "End Session", true, None::<&str>)?;
 
     let toggle_running = MenuItem::with_id(app, MENU_TOGGLE_RUNNING, "Start Watching", true, None::<&str>)?;
+    let pause_for = SubmenuBuilder::with_id(app, MENU_PAUSE_FOR, "Pause For")
+        .item(&MenuItem::with_id(app, MENU_PAUSE_30_MINUTES, "30 Minutes", true, None::<&str>)?)
+        .item(&MenuItem::with_id(app, MENU_PAUSE_1_HOUR, "1 Hour", true, None::<&str>)?)
+        .item(&MenuItem::with_id(app, MENU_PAUSE_UNTIL_TOMORROW, "Until Tomorrow", true, None::<&str>)?)
+        .build()?;
     let dry_run = CheckMenuItem::with_id(app, MENU_TOGGLE_DRY_RUN, "Dry Run", true, false, None::<&str>)?;
     let launch_on_login = CheckMenuItem::with_id(app, MENU_LAUNCH_ON_LOGIN, "Launch on Login", true, false, None::<&str>)?;
     let undo = MenuItem::with_id(app, MENU_UNDO, "Undo Last Rename", true, None::<&str>)?;
+    let redo = MenuItem::with_id(app, MENU_REDO, "Redo Rename", false, None::<&str>)?;
+    let hotkey = cfg!(target_os = "macos").then_some(clipboard_fix::HOTKEY_LABEL);
+    let fix_clipboard = MenuItem::with_id(app, MENU_FIX_CLIPBOARD, "Fix Filename on Clipboard", true, hotkey)?;
+    let rename_folder = MenuItem::with_id(app, MENU_RENAME_FOLDER, "Rename a Folder…", true, None::<&str>)?;
     let open_main = MenuItem::with_id(app, MENU_OPEN_MAIN, "Preferences...", true, None::<&str>)?;
     let quit_item = PredefinedMenuItem::quit(app, Some("Quit Namefix"))?;
 
     let directories = SubmenuBuilder::with_id(app, MENU_DIRECTORIES, "Directories").build()?;
+    let recent = SubmenuBuilder::with_id(app, MENU_RECENT, "Recent Renames").build()?;
+    rebuild_recent(app, &recent, &[])?;
+    let export_report = MenuItem::with_id(app, MENU_EXPORT_REPORT, "Export Weekly Report…", true, None::<&str>)?;
+    let export_settings = MenuItem::with_id(app, MENU_EXPORT_SETTINGS, "Export Settings", true, None::<&str>)?;
+    let import_settings = MenuItem::with_id(app, MENU_IMPORT_SETTINGS, "Import Settings…", true, None::<&str>)?;
 
     let menu = MenuBuilder::new(app)
         .item(&version_item)
         .item(&status_item)
+        .item(&stats_item)
         .separator()
         .item(&toggle_running)
+        .item(&pause_for)
         .item(&dry_run)
         .item(&launch_on_login)
         .item(&undo)
+        .item(&redo)
+        .item(&fix_clipboard)
         .separator()
         .item(&directories)
+        .item(&recent)
+        .item(&rename_folder)
+        .item(&export_report)
+        .item(&export_settings)
+        .item(&import_settings)
         .separator()
         .item(&open_main)
         .item(&quit_item)
@@ -107,19 +343,19 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
 
     let tray_icon = TrayIconBuilder::with_id("namefix-tray")
         .menu(&menu)
-        .icon(tray_icon_image()?)
-        .icon_as_template(false)
+        .icon(tray_icon_image(IconState::Watching, false, high_contrast_icon(app))?)
+        .icon_as_template(cfg!(target_os = "macos"))
         .tooltip("Namefix")
         .on_menu_event(move |app, event| {
             let event_id = event.id().0.clone();
             let app_handle = app.clone();
             log::info!("Tray menu event received: {}", event_id);
             async_runtime::spawn(async move {
-                let bridge_state = app_handle.state::<BridgeState>();
-                let bridge = bridge_state.inner().clone();
-                drop(bridge_state);
+                let bridge = app_handle.state::<AppState>().bridge.clone();
 
                 log::info!("Processing menu action: {}", event_id);
+                // Status shown before an optimistic update, restored if the action fails.
+                let mut rollback: Option<ServiceStatus> = None;
                 let action_result: Result<(), String> = match event_id.as_str() {
                     MENU_TOGGLE_RUNNING => {
                         log::info!("Calling toggle_running on bridge");
@@ -127,15 +363,22 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
                         log::info!("toggle_running result: {:?}", result);
                         result.map(|_| ())
                     }
+                    MENU_PAUSE_30_MINUTES | MENU_PAUSE_1_HOUR | MENU_PAUSE_UNTIL_TOMORROW => {
+                        let duration = match event_id.as_str() {
+                            MENU_PAUSE_30_MINUTES => PauseDuration::Minutes(30),
+                            MENU_PAUSE_1_HOUR => PauseDuration::Minutes(60),
+                            _ => PauseDuration::UntilTomorrow,
+                        };
+                        pause::pause_for(&app_handle, &bridge, duration).await.map(|_| ())
+                    }
                     MENU_TOGGLE_DRY_RUN => {
-                        let tray_state = app_handle.state::<TrayState>().inner().clone();
-                        let current = tray_state.status();
-                        bridge::set_dry_run(&bridge, !current.dry_run).await.map(|_| ())
+                        let desired = !app_handle.state::<AppState>().status().dry_run;
+                        rollback = apply_optimistic(&app_handle, |status| status.dry_run = desired);
+                        bridge::set_dry_run(&bridge, desired).await.map(|_| ())
                     }
                     MENU_LAUNCH_ON_LOGIN => {
-                        let tray_state = app_handle.state::<TrayState>().inner().clone();
-                        let current = tray_state.status();
-                        let desired = !current.launch_on_login;
+                        let desired = !app_handle.state::<AppState>().status().launch_on_login;
+                        rollback = apply_optimistic(&app_handle, |status| status.launch_on_login = desired);
                         let res = bridge::set_launch_on_login(&bridge, desired).await.map(|_| ());
                         if res.is_ok() {
                             sync_autostart(&app_handle, desired);
@@ -145,6 +388,26 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
                     MENU_UNDO => {
                         bridge::undo(&bridge).await.map(|_| ())
                     }
+                    MENU_REDO => bridge::redo(&bridge).await.map(|_| ()),
+                    MENU_FIX_CLIPBOARD => clipboard_fix::fix_clipboard(&app_handle).await,
+                    MENU_EXPORT_REPORT => match app_handle.path().app_data_dir() {
+                        Ok(data_dir) => {
+                            let directory = data_dir.join("reports");
+                            match report::export_history(&bridge, &directory, report::DEFAULT_DAYS).await {
+                                Ok(exported) => entries::reveal(Path::new(&exported.path)),
+                                Err(err) => Err(err),
+                            }
+                        }
+                        Err(err) => Err(err.to_string()),
+                    },
+                    MENU_EXPORT_SETTINGS => actions::invoke(&app_handle, MENU_EXPORT_SETTINGS).await,
+                    MENU_IMPORT_SETTINGS => {
+                        if let Some(window) = app_handle.get_webview_window("main") {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                        actions::invoke(&app_handle, MENU_IMPORT_SETTINGS).await
+                    }
                     MENU_OPEN_MAIN => {
                         if let Some(window) = app_handle.get_webview_window("main") {
                             let _ = window.show();
@@ -152,20 +415,125 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
                         }
                         Ok(())
                     }
+                    MENU_RENAME_FOLDER => {
+                        if let Some(window) = app_handle.get_webview_window("main") {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                        let _ = app_handle.emit("ui://rename-folder", ());
+                        Ok(())
+                    }
+                    MENU_ATTENTION => {
+                        if let Some(window) = app_handle.get_webview_window("main") {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                        let state = app_handle.state::<AppState>();
+                        let _ = app_handle.emit("ui://needs-attention", state.attention.items());
+                        let _ = app_handle.emit("ui://error-center", state.issues.list(&state));
+                        Ok(())
+                    }
+                    MENU_SNOOZE_OFFER => {
+                        let offered = tray_state(&app_handle).and_then(TrayState::take_snooze_offer);
+                        match offered {
+                            Some(directory) => {
+                                let until = SNOOZE_DURATION.resume_at(now_millis());
+                                bridge::snooze_directory(&bridge, directory, until).await.map(|_| ())
+                            }
+                            None => Ok(()),
+                        }
+                    }
+                    MENU_END_SESSION => bridge::end_session(&bridge).await.map(|_| ()),
                     MENU_QUIT => {
                         app_handle.exit(0);
                         Ok(())
                     }
+                    id if id.starts_with(MENU_DIRECTORY_DRY_RUN_PREFIX) => {
+                        let directory = id[MENU_DIRECTORY_DRY_RUN_PREFIX.len()..].to_string();
+                        let enabled = !app_handle.state::<AppState>().status().dry_run_directories.contains(&directory);
+                        rollback = apply_optimistic(&app_handle, |status| {
+                            status.dry_run_directories.retain(|dir| dir != &directory);
+                            if enabled {
+                                status.dry_run_directories.push(directory.clone());
+                            }
+                        });
+                        bridge::set_directory_dry_run(&bridge, directory, enabled).await.map(|_| ())
+                    }
+                    id if id.starts_with(MENU_DIRECTORY_RECURSIVE_PREFIX) => {
+                        let directory = id[MENU_DIRECTORY_RECURSIVE_PREFIX.len()..].to_string();
+                        let status = app_handle.state::<AppState>().status();
+                        let recursive = !status.dir_config(&directory).is_some_and(|config| config.recursive);
+                        rollback = apply_optimistic(&app_handle, |status| {
+                            if let Some(config) = status.directory_configs.iter_mut().find(|c| c.directory == directory) {
+                                config.recursive = recursive;
+                            }
+                        });
+                        let update = bridge::DirectoryConfigUpdate { recursive: Some(recursive), ..Default::default() };
+                        bridge::set_dir_config(&bridge, directory, update).await.map(|_| ())
+                    }
+                    id if id.starts_with(MENU_RECENT_PREFIX) => {
+                        let entry = recent_entry(&app_handle, &id[MENU_RECENT_PREFIX.len()..]);
+                        let action = app_handle.state::<AppState>().entry_action.get();
+                        match entry {
+                            Some(entry) => entries::perform(&app_handle, &bridge, action, &entry).await,
+                            None => Ok(()),
+                        }
+                    }
+                    id if id.starts_with(MENU_RECENT_COPY_NEW_PREFIX) || id.starts_with(MENU_RECENT_COPY_OLD_PREFIX) => {
+                        let copy_new = id.starts_with(MENU_RECENT_COPY_NEW_PREFIX);
+                        let prefix = if copy_new { MENU_RECENT_COPY_NEW_PREFIX } else { MENU_RECENT_COPY_OLD_PREFIX };
+                        let entry = recent_entry(&app_handle, &id[prefix.len()..]);
+                        match entry {
+                            Some(entry) => entries::copy(&app_handle, if copy_new { entry.to } else { entry.old_name() }),
+                            None => Ok(()),
+                        }
+                    }
+                    id if id.starts_with(MENU_RECENT_UNDO_PREFIX) => {
+                        let entry = recent_entry(&app_handle, &id[MENU_RECENT_UNDO_PREFIX.len()..]);
+                        match entry {
+                            Some(entry) => entries::undo(&bridge, &entry).await,
+                            None => Ok(()),
+                        }
+                    }
+                    id if id.starts_with(MENU_RECENT_QUICK_LOOK_PREFIX) => {
+                        let entry = recent_entry(&app_handle, &id[MENU_RECENT_QUICK_LOOK_PREFIX.len()..]);
+                        match entry {
+                            Some(entry) => entries::quick_look(Path::new(&entry.to)),
+                            None => Ok(()),
+                        }
+                    }
+                    id if id.starts_with(MENU_RECENT_OPEN_WITH_PREFIX) => {
+                        let target = id[MENU_RECENT_OPEN_WITH_PREFIX.len()..].split_once(':');
+                        match target.and_then(|(index, application)| Some((recent_entry(&app_handle, index)?, application))) {
+                            Some((entry, application)) => entries::open_with(Path::new(&entry.to), Path::new(application)),
+                            None => Ok(()),
+                        }
+                    }
+                    id if id.starts_with(MENU_DIRECTORY_SNOOZE_PREFIX) => {
+                        let directory = id[MENU_DIRECTORY_SNOOZE_PREFIX.len()..].to_string();
+                        // Toggle: a snoozed directory is woken by snoozing it until now.
+                        let until = match app_handle.state::<AppState>().status().snoozed_until(&directory) {
+                            Some(_) => now_millis(),
+                            None => SNOOZE_DURATION.resume_at(now_millis()),
+                        };
+                        bridge::snooze_directory(&bridge, directory, until).await.map(|_| ())
+                    }
+                    id if id.starts_with(MENU_DIRECTORY_EJECT_PREFIX) => {
+                        let root = &id[MENU_DIRECTORY_EJECT_PREFIX.len()..];
+                        volume::eject(&bridge, root).await.map(|_| ())
+                    }
                     _ => Ok(()),
                 };
 
                 // Log errors and emit toast for user feedback
                 if let Err(ref err) = action_result {
                     log::error!("Menu action '{}' failed: {}", event_id, err);
-                    let _ = app_handle.emit("service://toast", serde_json::json!({
-                        "message": format!("Action failed: {}", err),
-                        "level": "error"
-                    }));
+                    if let (Some(previous), Some(tray_state)) = (rollback.take(), tray_state(&app_handle)) {
+                        if let Err(err) = tray_state.force_status(&app_handle, &previous) {
+                            log::error!("Failed to revert tray after action: {}", err);
+                        }
+                    }
+                    notify::toast(&app_handle, format!("Action failed: {}", err), "error");
                 }
 
                 // Force status refresh to ensure tray reflects actual state
@@ -174,8 +542,8 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
                 match bridge::get_status(&bridge).await {
                     Ok(status) => {
                         log::info!("Got status: running={}, dirs={}", status.running, status.directories.len());
-                        if let Some(tray_state) = app_handle.try_state::<TrayState>() {
-                            if let Err(err) = tray_state.apply_status(&app_handle, &status) {
+                        if let Some(tray_state) = tray_state(&app_handle) {
+                            if let Err(err) = tray_state.force_status(&app_handle, &status) {
                                 log::error!("Failed to update tray after action: {}", err);
                             } else {
                                 log::info!("Tray updated successfully");
@@ -192,25 +560,81 @@ pub fn init_tray(app: &AppHandle<Wry>, bridge: &BridgeState) -> tauri::Result<Tr
         })
         .build(app)?;
 
-    let initial_status = async_runtime::block_on(bridge::get_status(bridge))
-        .unwrap_or(ServiceStatus { running: false, directories: vec![], dry_run: false, launch_on_login: false });
+    // Placeholder until the initial status arrives through the status listener.
+    let initial_status = ServiceStatus::default();
 
     let tray_state = TrayState {
         tray: tray_icon,
+        menu,
         status_label: status_item,
+        stats_label: stats_item,
+        attention: attention_item,
+        attention_count: Arc::new(AtomicUsize::new(0)),
+        snooze_offer,
+        snooze_offer_directory: Arc::new(Mutex::new(None)),
+        session: session_item,
         toggle_running,
         dry_run,
         launch_on_login,
         undo,
+        redo,
         directories,
+        recent,
+        recent_entries: Arc::new(Mutex::new(Vec::new())),
         current_status: Arc::new(Mutex::new(initial_status.clone())),
     };
 
-    tray_state.apply_status(app, &initial_status)?;
+    tray_state.render(app, &initial_status)?;
 
     Ok(tray_state)
 }
 
+/// Install the built tray and replay any status update that arrived first.
+pub fn install_tray(app: &AppHandle<Wry>, tray_state: TrayState) {
+    let state = app.state::<AppState>();
+    if let Some(status) = state.set_tray(tray_state) {
+        if let Some(tray_state) = state.tray() {
+            if let Err(err) = tray_state.apply_status(app, &status) {
+                log::error!("failed to replay early status: {}", err);
+            }
+        }
+    }
+    apply_shortcuts(app);
+    refresh_stats(app);
+}
+
+/// Show the user's shortcuts beside the tray items for the same actions.
+pub fn apply_shortcuts(app: &AppHandle<Wry>) {
+    if let Some(tray_state) = tray_state(app) {
+        let shortcuts = &app.state::<AppState>().shortcuts;
+        if let Err(err) = set_accelerators(tray_state.menu.items(), shortcuts) {
+            log::error!("failed to update tray shortcuts: {}", err);
+        }
+    }
+}
+
+fn set_accelerators(items: tauri::Result<Vec<MenuItemKind<Wry>>>, shortcuts: &ShortcutStore) -> tauri::Result<()> {
+    for item in items? {
+        let id = item.id().0.as_str();
+        if let MenuItemKind::Submenu(submenu) = &item {
+            set_accelerators(submenu.items(), shortcuts)?;
+            continue;
+        }
+        if !SHORTCUT_ITEMS.contains(&id) {
+            continue;
+        }
+        let accelerator = shortcuts.get(id).or_else(|| {
+            (id == MENU_FIX_CLIPBOARD && cfg!(target_os = "macos")).then(|| clipboard_fix::HOTKEY_LABEL.to_string())
+        });
+        match &item {
+            MenuItemKind::MenuItem(item) => item.set_accelerator(accelerator.as_deref())?,
+            MenuItemKind::Check(item) => item.set_accelerator(accelerator.as_deref())?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 pub fn register_status_listener(app: &AppHandle<Wry>) {
     let app_handle = app.clone();
     app.listen_any("service://status", move |event| {
@@ -221,15 +645,120 @@ pub fn register_status_listener(app: &AppHandle<Wry>) {
             // sidecar finishes loading config) and runtime toggles alike.
             sync_autostart(&app_handle, status.launch_on_login);
 
-            if let Some(tray_state) = app_handle.try_state::<TrayState>() {
-                if let Err(err) = tray_state.apply_status(&app_handle, &status) {
-                    log::error!("failed to update tray: {}", err);
-                }
+            if let Some(state) = app_handle.try_state::<AppState>() {
+                state.with_tray_or_buffer(status, |tray_state, status| {
+                    if let Err(err) = tray_state.apply_status(&app_handle, status) {
+                        log::error!("failed to update tray: {}", err);
+                    }
+                });
+                config::persist(&app_handle);
             }
         }
     });
 }
 
+/// Recompute the status label, e.g. to tick down a timed pause.
+pub fn refresh_status_label(app: &AppHandle<Wry>) {
+    if let Some(tray_state) = tray_state(app) {
+        if let Err(err) = tray_state.refresh_status_label(app) {
+            log::error!("failed to update status label: {}", err);
+        }
+    }
+}
+
+/// Suggest snoozing `directory` after the service reported a burst of manual activity.
+pub fn offer_snooze(app: &AppHandle<Wry>, directory: &str) {
+    let Some(tray_state) = tray_state(app) else {
+        return;
+    };
+    if tray_state.status().snoozed_until(directory).is_some() {
+        return;
+    }
+    if let Err(err) = tray_state.set_snooze_offer(Some(directory)) {
+        log::error!("failed to show snooze offer: {}", err);
+    }
+    let message = format!(
+        "Lots of changes in {}. Snooze it from the menu bar to pause renaming there.",
+        pathdisplay::abbreviate_home(directory)
+    );
+    notify::toast(app, message, "info");
+}
+
+/// Re-read the activity log and update the "Recent Renames" submenu.
+pub fn refresh_recent(app: &AppHandle<Wry>) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let entries = state
+        .activity
+        .latest(&[ActivityKind::Renamed, ActivityKind::Converted], RECENT_LIMIT)
+        .iter()
+        .filter_map(RenameEntry::from_activity)
+        .collect();
+    if let Some(tray_state) = state.tray() {
+        if let Err(err) = tray_state.apply_recent(app, entries) {
+            log::error!("failed to update recent renames: {}", err);
+        }
+    }
+}
+
+/// Redraw "Recent Renames" with the current entries, e.g. after the click action changed.
+pub fn redraw_recent(app: &AppHandle<Wry>) {
+    if let Some(tray_state) = tray_state(app) {
+        let entries = tray_state.recent_entries.lock().expect("recent lock poisoned").clone();
+        if let Err(err) = rebuild_recent(app, &tray_state.recent, &entries) {
+            log::error!("failed to update recent renames: {}", err);
+        }
+    }
+}
+
+/// Recount open issues and update the tray badge.
+/// Count the week's renames again for the line under the status.
+pub fn refresh_stats(app: &AppHandle<Wry>) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let count = state.events.renamed_this_week(now_millis());
+    if let Some(tray_state) = state.tray() {
+        if let Err(err) = tray_state.stats_label.set_text(events::week_summary(count)) {
+            log::error!("failed to update rename stats: {}", err);
+        }
+    }
+}
+
+pub fn refresh_attention(app: &AppHandle<Wry>) {
+    let count = app.try_state::<AppState>().map(|state| state.issues.list(&state).len()).unwrap_or(0);
+    if let Some(tray_state) = tray_state(app) {
+        if let Err(err) = tray_state.apply_attention(app, count) {
+            log::error!("failed to update attention badge: {}", err);
+        }
+    }
+}
+
+/// Show the expected outcome of a menu action straight away instead of after
+/// the round-trip. Returns the status to restore if the action fails; the
+/// refetch that follows every action reconciles either way.
+fn apply_optimistic(app: &AppHandle<Wry>, update: impl FnOnce(&mut ServiceStatus)) -> Option<ServiceStatus> {
+    let tray_state = tray_state(app)?;
+    let previous = tray_state.status();
+    let mut expected = previous.clone();
+    update(&mut expected);
+    if let Err(err) = tray_state.apply_status(app, &expected) {
+        log::error!("failed to apply optimistic update: {}", err);
+    }
+    Some(previous)
+}
+
+/// The rename behind a "Recent Renames" item, from its menu id suffix.
+fn recent_entry(app: &AppHandle<Wry>, index: &str) -> Option<RenameEntry> {
+    tray_state(app)?.recent_entry(index.parse().ok()?)
+}
+
+/// The tray, once setup has built it.
+fn tray_state(app: &AppHandle<Wry>) -> Option<&TrayState> {
+    app.try_state::<AppState>().and_then(|state| state.inner().tray())
+}
+
 pub(crate) fn sync_autostart(app: &AppHandle<Wry>, desired: bool) {
     use tauri_plugin_autostart::ManagerExt;
     let manager = app.autolaunch();
@@ -244,7 +773,35 @@ pub(crate) fn sync_autostart(app: &AppHandle<Wry>, desired: bool) {
     }
 }
 
-fn rebuild_directories(app: &AppHandle<Wry>, submenu: &Submenu<Wry>, directories: &[String]) -> tauri::Result<()> {
+pub fn status_label(app: &AppHandle<Wry>, status: &ServiceStatus) -> String {
+    let Some(state) = app.try_state::<AppState>() else {
+        return "Status: Loading…".to_string();
+    };
+    if state.bridge.is_starting() {
+        return "Status: Starting…".to_string();
+    }
+    if state.bridge.startup_error().is_some() {
+        return "Status: Service unavailable".to_string();
+    }
+    if status.directories.is_empty() {
+        return "Status: Paused (no directories)".to_string();
+    }
+    if status.running {
+        let count = status.directories.len();
+        let verb = if status.observer_mode { "Observing" } else { "Watching" };
+        return format!("Status: {} {} director{}", verb, count, if count == 1 { "y" } else { "ies" });
+    }
+    if state.user_switch.is_paused() {
+        return "Status: Paused (another user is active)".to_string();
+    }
+    match state.pause.resume_at() {
+        Some(at) => format!("Status: Paused (resumes in {})", pause::format_remaining(at.saturating_sub(now_millis()))),
+        None => "Status: Paused".to_string(),
+    }
+}
+
+fn rebuild_directories(app: &AppHandle<Wry>, submenu: &Submenu<Wry>, status: &ServiceStatus) -> tauri::Result<()> {
+    let directories = &status.directories;
     let existing = submenu.items()?;
     for item in existing {
         submenu.remove(&item)?;
@@ -254,24 +811,290 @@ fn rebuild_directories(app: &AppHandle<Wry>, submenu: &Submenu<Wry>, directories
         let empty = MenuItem::new(app, "No directories configured", false, None::<&str>)?;
         empty.set_enabled(false)?;
         submenu.append(&empty)?;
-    } else {
-        for dir in directories {
-            let path = Path::new(dir);
-            let display = path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .map(|name| name.to_string())
-                .unwrap_or_else(|| dir.clone());
-            let item = MenuItem::new(app, display, false, None::<&str>)?;
-            item.set_enabled(false)?;
-            submenu.append(&item)?;
+        return Ok(());
+    }
+
+    // Grouped directories nest under one submenu per group, in the order each
+    // group first appears; ungrouped ones stay at the top level.
+    let mut groups: Vec<(&str, Vec<&String>)> = Vec::new();
+    for dir in directories {
+        let Some(group) = status.group_of(dir) else {
+            submenu.append(&directory_entry(app, status, dir)?)?;
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, members)) => members.push(dir),
+            None => groups.push((group, vec![dir])),
+        }
+    }
+    for (group, members) in groups {
+        let group_menu = Submenu::new(app, group, true)?;
+        for dir in members {
+            group_menu.append(&directory_entry(app, status, dir)?)?;
         }
+        submenu.append(&group_menu)?;
     }
 
     Ok(())
 }
 
-fn tray_icon_image() -> tauri::Result<Image<'static>> {
+fn directory_entry(app: &AppHandle<Wry>, status: &ServiceStatus, dir: &str) -> tauri::Result<Submenu<Wry>> {
+    let display = pathdisplay::display_path(dir, pathdisplay::MENU_PATH_WIDTH);
+    let display = if status.priority_directories.iter().any(|d| d == dir) {
+        format!("{} {}", PRIORITY_MARKER, display)
+    } else {
+        display
+    };
+    let display = if status.is_dry_run(dir) { format!("{} (dry run)", display) } else { display };
+    let snoozed_until = status.snoozed_until(dir);
+    let display = match snoozed_until {
+        Some(until) => format!("{} (snoozed {})", display, pause::format_remaining(until.saturating_sub(now_millis()))),
+        None => display,
+    };
+    let volume = status.volume_of(dir);
+    let display = match volume {
+        Some(volume) if volume.released => format!("{} (ejecting)", display),
+        Some(volume) if !volume.mounted => format!("{} (offline)", display),
+        _ => display,
+    };
+    let stats = status.directory_stats.iter().find(|stats| stats.directory == dir);
+    let display = match stats.and_then(describe_backlog) {
+        Some(backlog) => format!("{} — {}", display, backlog),
+        None => display,
+    };
+    let activity = match stats {
+        Some(stats) => describe_activity(stats),
+        None => "idle".to_string(),
+    };
+    // Menu items have no tooltips, so the full path heads the submenu instead.
+    let path_item = MenuItem::new(app, dir, false, None::<&str>)?;
+    let activity_item = MenuItem::new(app, activity, false, None::<&str>)?;
+    // Global dry run overrides the per-directory setting, so the checkbox
+    // shows the effective state and is locked while it is on.
+    let dry_run_item = CheckMenuItem::with_id(
+        app,
+        format!("{}{}", MENU_DIRECTORY_DRY_RUN_PREFIX, dir),
+        "Dry Run",
+        !status.dry_run,
+        status.is_dry_run(dir),
+        None::<&str>,
+    )?;
+    let config = status.dir_config(dir);
+    let recursive_item = CheckMenuItem::with_id(
+        app,
+        format!("{}{}", MENU_DIRECTORY_RECURSIVE_PREFIX, dir),
+        "Include Subfolders",
+        true,
+        config.is_some_and(|config| config.recursive),
+        None::<&str>,
+    )?;
+    let snooze_item = MenuItem::with_id(
+        app,
+        format!("{}{}", MENU_DIRECTORY_SNOOZE_PREFIX, dir),
+        if snoozed_until.is_some() { "Resume Now" } else { "Snooze for 30 Minutes" },
+        true,
+        None::<&str>,
+    )?;
+    let mut builder = SubmenuBuilder::new(app, display).item(&path_item).item(&activity_item);
+    if let Some(shared) = status.shared(dir) {
+        let warning = if shared.owned_by_other {
+            "⚠ Shared: owned by another user"
+        } else {
+            "⚠ Shared: writable by everyone"
+        };
+        builder = builder.item(&MenuItem::new(app, warning, false, None::<&str>)?);
+    }
+    if let Some(hot_folder) = status.hot_folder(dir) {
+        let label = match &hot_folder.destination {
+            Some(destination) => format!("Hot folder: hands off to {}", display_name(destination)),
+            None => "Hot folder: runs a command".to_string(),
+        };
+        builder = builder.item(&MenuItem::new(app, label, false, None::<&str>)?);
+    }
+    if let Some(filters) = config.and_then(describe_filters) {
+        builder = builder.item(&MenuItem::new(app, filters, false, None::<&str>)?);
+    }
+    builder = builder.item(&dry_run_item).item(&recursive_item).item(&snooze_item);
+    if let Some(volume) = volume.filter(|volume| volume.mounted && !volume.released) {
+        builder = builder.separator().item(&MenuItem::with_id(
+            app,
+            format!("{}{}", MENU_DIRECTORY_EJECT_PREFIX, volume.root),
+            format!("Eject “{}”", volume.name),
+            true,
+            None::<&str>,
+        )?);
+    }
+    builder.build()
+}
+
+/// The directory's extension filters, e.g. "Only .png, .jpg · skips .heic",
+/// or None when every extension is renamed.
+fn describe_filters(config: &DirectoryConfig) -> Option<String> {
+    let list = |extensions: &[String]| extensions.iter().map(|ext| format!(".{}", ext)).collect::<Vec<_>>().join(", ");
+    let mut parts = Vec::new();
+    if !config.include_extensions.is_empty() {
+        parts.push(format!("Only {}", list(&config.include_extensions)));
+    }
+    if !config.exclude_extensions.is_empty() {
+        let verb = if parts.is_empty() { "Skips" } else { "skips" };
+        parts.push(format!("{} {}", verb, list(&config.exclude_extensions)));
+    }
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+fn rebuild_recent(app: &AppHandle<Wry>, submenu: &Submenu<Wry>, entries: &[RenameEntry]) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+    if entries.is_empty() {
+        let empty = MenuItem::new(app, "No renames yet", false, None::<&str>)?;
+        submenu.append(&empty)?;
+    }
+    let action = app.try_state::<AppState>().map(|state| state.entry_action.get()).unwrap_or_default();
+    for (index, entry) in entries.iter().enumerate() {
+        let label = format!("{} → {}", display_name(&entry.from), display_name(&entry.to));
+        let mut item = SubmenuBuilder::new(app, label)
+            .item(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_PREFIX, index), action.label(), true, None::<&str>)?);
+        if action != EntryClickAction::Undo {
+            item = item.item(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_UNDO_PREFIX, index), "Undo Rename", true, None::<&str>)?);
+        }
+        if cfg!(target_os = "macos") {
+            item = item.item(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_QUICK_LOOK_PREFIX, index), "Quick Look", true, None::<&str>)?);
+            let applications = entries::open_with_apps(Path::new(&entry.to));
+            if !applications.is_empty() {
+                let mut open_with = SubmenuBuilder::new(app, "Open With");
+                for application in &applications {
+                    let id = format!("{}{}:{}", MENU_RECENT_OPEN_WITH_PREFIX, index, application.path);
+                    open_with = open_with.item(&MenuItem::with_id(app, id, &application.name, true, None::<&str>)?);
+                }
+                item = item.item(&open_with.build()?);
+            }
+        }
+        let item = item
+            .separator()
+            .item(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_COPY_NEW_PREFIX, index), "Copy New Path", true, None::<&str>)?)
+            .item(&MenuItem::with_id(app, format!("{}{}", MENU_RECENT_COPY_OLD_PREFIX, index), "Copy Old Name", true, None::<&str>)?)
+            .build()?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+fn display_name(dir: &str) -> String {
+    Path::new(dir)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| dir.to_string())
+}
+
+/// Where work is piling up, e.g. "4 queued, 1 skipped"; `None` when nothing is.
+fn describe_backlog(stats: &DirectoryStats) -> Option<String> {
+    let mut parts = Vec::new();
+    if stats.queued > 0 {
+        parts.push(format!("{} queued", stats.queued));
+    }
+    if stats.skipped_today > 0 {
+        parts.push(format!("{} skipped", stats.skipped_today));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Short activity summary for a directory, e.g. "3 today, 2h ago" or "idle".
+fn describe_activity(stats: &DirectoryStats) -> String {
+    let mut parts = Vec::new();
+    if stats.renames_today > 0 {
+        parts.push(format!("{} today", stats.renames_today));
+    }
+    if stats.error_count > 0 {
+        parts.push(format!("{} error{}", stats.error_count, if stats.error_count == 1 { "" } else { "s" }));
+    }
+    match stats.last_event_at {
+        Some(at) => parts.push(format_age(now_millis().saturating_sub(at))),
+        None => parts.push("idle".to_string()),
+    }
+    match stats.watch_mode.as_deref() {
+        Some("polling") => parts.push("polling".to_string()),
+        Some("usn-journal") => parts.push("USN journal".to_string()),
+        Some("endpoint-security") => parts.push("Endpoint Security".to_string()),
+        _ => {}
+    }
+    parts.join(", ")
+}
+
+fn format_age(elapsed_ms: u64) -> String {
+    let minutes = elapsed_ms / 60_000;
+    if minutes < 1 {
+        "just now".to_string()
+    } else if minutes < 60 {
+        format!("{}m ago", minutes)
+    } else if minutes < 60 * 24 {
+        format!("{}h ago", minutes / 60)
+    } else {
+        format!("{}d ago", minutes / (60 * 24))
+    }
+}
+
+/// What the tray icon shows, most urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconState {
+    /// The background service failed to start or keeps stopping.
+    Error,
+    Paused,
+    /// Dry run or observer mode: files are looked at but not renamed.
+    DryRun,
+    Watching,
+}
+
+impl IconState {
+    fn of(app: &AppHandle<Wry>, status: &ServiceStatus) -> Self {
+        let failed = app.try_state::<AppState>().is_some_and(|state| state.bridge.startup_error().is_some());
+        if failed {
+            Self::Error
+        } else if !status.running {
+            Self::Paused
+        } else if status.dry_run || status.observer_mode {
+            Self::DryRun
+        } else {
+            Self::Watching
+        }
+    }
+}
+
+/// Whether (`xf`, `yf`) falls on the mark drawn inside the document for
+/// states without the rename arrow: two bars when paused, an exclamation
+/// mark on error.
+fn on_state_mark(state: IconState, xf: f32, yf: f32, (left, right, top, bottom): (f32, f32, f32, f32)) -> bool {
+    let center = (left + right) / 2.0;
+    let (mark_top, mark_bottom) = (top + 3.0, bottom - 2.5);
+    let in_column = |x: f32| (xf - x).abs() <= 1.0 && yf >= mark_top && yf <= mark_bottom;
+    match state {
+        IconState::Paused => in_column(center - 2.5) || in_column(center + 2.5),
+        IconState::Error => in_column(center) && !(yf > mark_bottom - 3.5 && yf < mark_bottom - 1.5),
+        IconState::DryRun | IconState::Watching => false,
+    }
+}
+
+/// Redraw the tray icon after the high-contrast setting changed.
+pub fn refresh_icon(app: &AppHandle<Wry>) {
+    if let Some(tray_state) = tray_state(app) {
+        if let Err(err) = tray_state.apply_icon(app) {
+            log::error!("failed to redraw tray icon: {}", err);
+        }
+    }
+}
+
+fn high_contrast_icon(app: &AppHandle<Wry>) -> bool {
+    app.try_state::<AppState>().is_some_and(|state| state.accessibility.get().high_contrast_icon)
+}
+
+/// The icon for `state`. macOS always gets the monochrome template so it
+/// follows the menu bar's appearance; elsewhere the gradient is drawn unless
+/// high contrast is on.
+fn tray_icon_image(state: IconState, badge: bool, high_contrast: bool) -> tauri::Result<Image<'static>> {
+    if high_contrast || cfg!(target_os = "macos") {
+        return Ok(glyph_icon_image(state, badge));
+    }
     const SIZE: u32 = 28;
     let mut rgba = vec![0u8; (SIZE * SIZE * 4) as usize];
     let max = (SIZE - 1) as f32;
@@ -308,6 +1131,17 @@ fn tray_icon_image() -> tauri::Result<Image<'static>> {
         xf > doc_right - doc_radius && yf < doc_top + doc_radius && (xf + yf) > folded_corner_threshold
     };
 
+    let badge_center = SIZE as f32 - 5.5;
+    let badge_radius = 4.5;
+
+    // Teal while renaming, amber for a dry run; paused and error draw a mark instead.
+    let (arrow, arrow_head) = match state {
+        IconState::DryRun => ([251.0, 191.0, 36.0], [252.0, 211.0, 77.0]),
+        _ => ([82.0, 223.0, 205.0], [98.0, 228.0, 210.0]),
+    };
+    let has_arrow = matches!(state, IconState::Watching | IconState::DryRun);
+    let doc_bounds = (doc_left, doc_right, doc_top, doc_bottom);
+
     let diagonal_normalization = (1.5_f32).sqrt();
     for y in 0..SIZE {
         for x in 0..SIZE {
@@ -318,7 +1152,11 @@ fn tray_icon_image() -> tauri::Result<Image<'static>> {
             let dy = yf - center;
             let dist = (dx * dx + dy * dy).sqrt();
 
-            if dist > halo_radius {
+            let badge_dx = xf - badge_center;
+            let badge_dy = yf - (SIZE as f32 - badge_center);
+            let in_badge = badge && badge_dx * badge_dx + badge_dy * badge_dy <= badge_radius * badge_radius;
+
+            if dist > halo_radius && !in_badge {
                 rgba[idx + 3] = 0;
                 continue;
             }
@@ -332,6 +1170,10 @@ fn tray_icon_image() -> tauri::Result<Image<'static>> {
             } else {
                 ((halo_radius - dist) / (halo_radius - base_radius)).clamp(0.0, 1.0) * 0.8
             };
+            if state == IconState::Paused {
+                let gray = (r + g + b) / 3.0;
+                (r, g, b) = (gray, gray, gray);
+            }
 
             if in_round_rect(xf, yf) {
                 let doc_shade = 0.65 + 0.15 * ((yf - doc_top) / (doc_bottom - doc_top)).clamp(0.0, 1.0);
@@ -351,22 +1193,31 @@ fn tray_icon_image() -> tauri::Result<Image<'static>> {
             // diagonal rename arrow overlay
             let diagonal_line_y = -1.05 * xf + (center * 2.0 - 2.0);
             let diag = ((yf - diagonal_line_y) / diagonal_normalization).abs();
-            if diag < 1.1 && xf >= 10.0 && xf <= doc_right && yf >= doc_top + 2.0 && yf <= doc_bottom + 1.0 {
-                r = 82.0;
-                g = 223.0;
-                b = 205.0;
+            if has_arrow && diag < 1.1 && xf >= 10.0 && xf <= doc_right && yf >= doc_top + 2.0 && yf <= doc_bottom + 1.0
+            {
+                [r, g, b] = arrow;
                 alpha = 1.0;
             }
             // arrow head
-            if xf > doc_right - 4.5 && yf <= doc_top + 5.5 {
+            if has_arrow && xf > doc_right - 4.5 && yf <= doc_top + 5.5 {
                 let tip = (yf - (doc_top + 1.0)) - (-(xf - (doc_right - 1.5)));
                 if tip <= 0.8 {
-                    r = 98.0;
-                    g = 228.0;
-                    b = 210.0;
+                    [r, g, b] = arrow_head;
                     alpha = 1.0;
                 }
             }
+            if on_state_mark(state, xf, yf, doc_bounds) {
+                (r, g, b) = if state == IconState::Error { (220.0, 38.0, 38.0) } else { (100.0, 116.0, 139.0) };
+                alpha = 1.0;
+            }
+
+            // attention badge (top-right dot)
+            if in_badge {
+                r = 239.0;
+                g = 68.0;
+                b = 68.0;
+                alpha = 1.0;
+            }
 
             rgba[idx] = (r.clamp(0.0, 255.0) * 1.0) as u8;
             rgba[idx + 1] = (g.clamp(0.0, 255.0) * 1.0) as u8;
@@ -377,3 +1228,56 @@ fn tray_icon_image() -> tauri::Result<Image<'static>> {
 
     Ok(Image::new_owned(rgba, SIZE, SIZE))
 }
+
+/// A flat, fully opaque version of the icon: a solid document with the rename
+/// arrow, pause bars or exclamation mark cut out, or an outlined document for
+/// a dry run. On macOS it is a template, drawn in the menu bar's own
+/// foreground colour; elsewhere it is white on a black disc. The badge is a
+/// separate dot, so it reads without relying on colour.
+fn glyph_icon_image(state: IconState, badge: bool) -> Image<'static> {
+    const SIZE: u32 = 28;
+    let template = cfg!(target_os = "macos");
+    let center = (SIZE - 1) as f32 / 2.0;
+    let disc_radius = SIZE as f32 * 0.48;
+    let (doc_left, doc_right, doc_top, doc_bottom) = (7.0, SIZE as f32 - 7.0, 6.0, SIZE as f32 - 6.0);
+    let badge_center = (SIZE as f32 - 5.0, 5.0);
+    let badge_radius = 4.5;
+
+    let mut rgba = vec![0u8; (SIZE * SIZE * 4) as usize];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (xf, yf) = (x as f32, y as f32);
+            let in_disc = (xf - center).hypot(yf - center) <= disc_radius;
+            let badge_dist = (xf - badge_center.0).hypot(yf - badge_center.1);
+            let in_badge = badge && badge_dist <= badge_radius;
+            // A transparent ring keeps the badge apart from the document.
+            let in_badge_gap = badge && !in_badge && badge_dist <= badge_radius + 1.5;
+            let in_doc = xf >= doc_left && xf <= doc_right && yf >= doc_top && yf <= doc_bottom;
+            let on_arrow = (yf - (-xf + SIZE as f32)).abs() < 1.6 && xf >= doc_left + 2.0 && xf <= doc_right - 2.0;
+            let on_mark = on_state_mark(state, xf, yf, (doc_left, doc_right, doc_top, doc_bottom));
+
+            let drawn = match state {
+                IconState::Watching => in_doc && !on_arrow,
+                IconState::DryRun => {
+                    let in_hollow =
+                        xf > doc_left + 2.0 && xf < doc_right - 2.0 && yf > doc_top + 2.0 && yf < doc_bottom - 2.0;
+                    in_doc && (!in_hollow || on_arrow)
+                }
+                IconState::Paused | IconState::Error => in_doc && !on_mark,
+            };
+            let glyph = (drawn && !in_badge_gap) || in_badge;
+            let (color, alpha) = match (template, glyph) {
+                (true, true) => ([0, 0, 0], 255),
+                (true, false) => ([0, 0, 0], 0),
+                (false, true) if in_badge => ([255, 214, 0], 255),
+                (false, true) => ([255, 255, 255], 255),
+                (false, false) if in_disc && !in_badge_gap => ([0, 0, 0], 255),
+                (false, false) => ([0, 0, 0], 0),
+            };
+            let idx = ((y * SIZE + x) * 4) as usize;
+            rgba[idx..idx + 3].copy_from_slice(&color);
+            rgba[idx + 3] = alpha;
+        }
+    }
+    Image::new_owned(rgba, SIZE, SIZE)
+}
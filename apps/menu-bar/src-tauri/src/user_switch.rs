@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::bridge;
+use crate::state::AppState;
+use crate::tray;
+
+/// How often the console session is checked.
+const POLL: Duration = Duration::from_secs(2);
+
+/// Watching paused because another user took over the console through fast
+/// user switching. Both users' instances would otherwise race to rename files
+/// in folders they share.
+#[derive(Default)]
+pub struct UserSwitch {
+    paused: AtomicBool,
+}
+
+impl UserSwitch {
+    /// Whether watching is paused until this user returns.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+}
+
+/// Pause watching while this user's session is switched out and resume when
+/// it returns. Only watchers this pauses are resumed, so a manual or timed
+/// pause is left alone. Runs for the life of the app on macOS.
+pub async fn watch(app: AppHandle<Wry>) {
+    let Some(mut active) = on_console() else {
+        return;
+    };
+    loop {
+        tokio::time::sleep(POLL).await;
+        let Some(now_active) = on_console() else {
+            continue;
+        };
+        if now_active == active {
+            continue;
+        }
+        active = now_active;
+        let state = app.state::<AppState>();
+        if !active {
+            if !state.tray().map(|tray| tray.status().running).unwrap_or(false) {
+                continue;
+            }
+            log::info!("Session switched out; pausing watchers");
+            match bridge::toggle_running(&state.bridge, Some(false)).await {
+                Ok(_) => state.user_switch.paused.store(true, Ordering::SeqCst),
+                Err(err) => log::error!("Failed to pause for user switch: {}", err),
+            }
+        } else if state.user_switch.paused.swap(false, Ordering::SeqCst) {
+            log::info!("Session active again; resuming watchers");
+            if let Err(err) = bridge::toggle_running(&state.bridge, Some(true)).await {
+                log::error!("Failed to resume after user switch: {}", err);
+            }
+        }
+        tray::refresh_status_label(&app);
+    }
+}
+
+/// Whether this user's session owns the console, or None where that can't be told.
+#[cfg(target_os = "macos")]
+fn on_console() -> Option<bool> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::CFString;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    }
+
+    // SAFETY: the call takes no arguments, and the dictionary it returns (if
+    // any) is owned by us under the create rule.
+    let dict = unsafe { CGSessionCopyCurrentDictionary() };
+    if dict.is_null() {
+        return None;
+    }
+    let dict: CFDictionary<CFString, CFType> = unsafe { CFDictionary::wrap_under_create_rule(dict) };
+    let key = CFString::from_static_string("kCGSessionOnConsoleKey");
+    let value = dict.find(&key)?;
+    value.downcast::<CFBoolean>().map(bool::from)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn on_console() -> Option<bool> {
+    None
+}
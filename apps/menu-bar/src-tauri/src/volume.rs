@@ -0,0 +1,60 @@
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use crate::bridge::{self, BridgeHandle, ServiceStatus};
+use crate::notify;
+
+/// Stop watching the directories on the volume mounted at `root`, then eject
+/// it. If the eject fails, say because another app still has files open on
+/// the disk, watching resumes and the error carries the system's reason.
+pub async fn eject(bridge: &BridgeHandle, root: &str) -> Result<ServiceStatus, String> {
+    bridge::set_volume_released(bridge, root, true).await?;
+    match run_eject(root).await {
+        Ok(()) => bridge::get_status(bridge).await,
+        Err(err) => {
+            if let Err(resume) = bridge::set_volume_released(bridge, root, false).await {
+                log::error!("Failed to resume watching {} after eject failed: {}", root, resume);
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Eject a camera card once it has been imported, telling the user if the
+/// system refused.
+pub async fn eject_card(app: AppHandle, root: String) {
+    match run_eject(&root).await {
+        Ok(()) => log::info!("Ejected {} after import", root),
+        Err(err) => {
+            log::warn!("{}", err);
+            notify::toast(&app, err, "warn");
+        }
+    }
+}
+
+async fn run_eject(root: &str) -> Result<(), String> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("diskutil");
+        command.arg("eject").arg(root);
+        command
+    } else if cfg!(target_os = "windows") {
+        let drive = root.trim_end_matches('\\').replace('\'', "''");
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-Command"]).arg(format!(
+            "(New-Object -ComObject Shell.Application).Namespace(17).ParseName('{}').InvokeVerb('Eject')",
+            drive
+        ));
+        command
+    } else {
+        let mut command = Command::new("gio");
+        command.args(["mount", "--eject"]).arg(root);
+        command
+    };
+    let output = command.output().await.map_err(|err| format!("Could not eject {}: {}", root, err))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let reason = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let reason = if reason.is_empty() { String::from_utf8_lossy(&output.stdout).trim().to_string() } else { reason };
+    Err(format!("Could not eject {}: {}", root, reason))
+}
@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::activity::now_millis;
+use crate::bridge;
+use crate::state::AppState;
+
+/// How often the clocks are compared.
+const TICK: Duration = Duration::from_secs(10);
+
+/// Wall-clock time passing this much faster than the monotonic clock means
+/// the machine was asleep.
+const MIN_SLEEP: Duration = Duration::from_secs(30);
+
+/// Recover the watchers whenever the machine wakes from sleep.
+///
+/// The monotonic clock stops while the machine sleeps and the wall clock does
+/// not, so a gap between them marks a wake. That needs no system notification
+/// API and works the same on every platform. A large manual clock change looks
+/// like a wake too, which costs no more than a needless watcher restart.
+pub async fn watch(app: AppHandle<Wry>) {
+    let mut wall = now_millis();
+    let mut monotonic = Instant::now();
+    loop {
+        tokio::time::sleep(TICK).await;
+        let (now_wall, now_monotonic) = (now_millis(), Instant::now());
+        let wall_elapsed = Duration::from_millis(now_wall.saturating_sub(wall));
+        let asleep_since = wall;
+        let slept = wall_elapsed.saturating_sub(now_monotonic - monotonic);
+        wall = now_wall;
+        monotonic = now_monotonic;
+        if slept >= MIN_SLEEP {
+            recover(&app, asleep_since).await;
+        }
+    }
+}
+
+async fn recover(app: &AppHandle<Wry>, asleep_since: u64) {
+    log::info!("Woke from sleep; re-validating watchers");
+    let bridge = app.state::<AppState>().bridge.clone();
+    match bridge::recover_from_sleep(&bridge, asleep_since).await {
+        Ok(recovery) => log::info!(
+            "Restarted {} watcher(s) and caught up on {} file(s) after wake",
+            recovery.restarted,
+            recovery.caught_up
+        ),
+        Err(err) => log::warn!("Failed to recover watchers after wake: {}", err),
+    }
+}
@@ -0,0 +1,415 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{async_runtime, AppHandle, Manager, Wry};
+
+use crate::activity::now_millis;
+use crate::email::{self, DigestFrequency, Summary, MAX_LISTED_FAILURES};
+use crate::pathdisplay::abbreviate_home;
+use crate::persist::{load_json, save_json};
+use crate::push;
+use crate::state::AppState;
+
+/// How often the summary schedule is checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Discord's embed accent colours: red for alerts and failures, teal otherwise.
+const DISCORD_ALERT: u32 = 0xdc2626;
+const DISCORD_OK: u32 = 0x2dd4bf;
+
+/// How posts are shaped for the service behind the URL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WebhookFlavor {
+    /// Plain JSON with an `event` field, for scripts.
+    #[default]
+    Generic,
+    /// A Slack incoming webhook, posted as Block Kit blocks.
+    Slack,
+    /// A Discord channel webhook, posted as an embed.
+    Discord,
+    /// One level of string fields, the shape Zapier's and IFTTT's webhook
+    /// triggers map without a transformation step. IFTTT's `value1`-`value3`
+    /// carry the event, a headline and the details.
+    Flat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSettings {
+    pub url: String,
+    #[serde(default)]
+    pub flavor: WebhookFlavor,
+    /// How often a rename summary is posted; alerts only when `None`.
+    #[serde(default)]
+    pub summaries: Option<DigestFrequency>,
+    /// Start of the period the next summary covers, in epoch milliseconds.
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WebhookFile {
+    #[serde(default)]
+    webhook: Option<WebhookSettings>,
+}
+
+/// What gets posted.
+enum Post<'a> {
+    /// The critical events the push relay sends, see [`push::critical`].
+    Alert(&'a str),
+    Summary { summary: &'a Summary, frequency: DigestFrequency, since: u64, until: u64 },
+}
+
+/// The optional webhook that critical alerts and rename summaries are
+/// posted to, persisted next to the other menu bar settings.
+pub struct WebhookStore {
+    path: PathBuf,
+    settings: Mutex<WebhookFile>,
+}
+
+impl WebhookStore {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self { path, settings: Mutex::new(settings) }
+    }
+
+    pub fn get(&self) -> Option<WebhookSettings> {
+        self.settings.lock().expect("webhook lock poisoned").webhook.clone()
+    }
+
+    fn save(&self, webhook: Option<WebhookSettings>) -> Result<(), String> {
+        let mut settings = self.settings.lock().expect("webhook lock poisoned");
+        let updated = WebhookFile { webhook };
+        save_json(&self.path, &updated)?;
+        *settings = updated;
+        Ok(())
+    }
+}
+
+/// Check `webhook` with a test post, then keep it; summaries start their
+/// first period now. `None` stops posting.
+pub async fn set_webhook(
+    store: &WebhookStore,
+    webhook: Option<WebhookSettings>,
+) -> Result<Option<WebhookSettings>, String> {
+    let webhook = match webhook {
+        Some(mut webhook) => {
+            if !(webhook.url.starts_with("https://") || webhook.url.starts_with("http://")) {
+                return Err("Enter the full webhook URL, starting with https://".to_string());
+            }
+            post(&webhook, &Post::Alert("Namefix will post alerts here.")).await?;
+            webhook.since = webhook.summaries.map(|_| now_millis());
+            Some(webhook)
+        }
+        None => None,
+    };
+    store.save(webhook.clone())?;
+    Ok(webhook)
+}
+
+/// Post `message` as an alert in the background, if a webhook is set.
+pub fn alert(app: &AppHandle<Wry>, message: &str) {
+    let Some(webhook) = app.state::<AppState>().webhook.get() else {
+        return;
+    };
+    let message = message.to_string();
+    async_runtime::spawn(async move {
+        if let Err(err) = post(&webhook, &Post::Alert(&message)).await {
+            log::warn!("Could not post \"{}\": {}", message, err);
+        }
+    });
+}
+
+/// Post a rename summary whenever one is due. Runs for the life of the app.
+pub async fn run(app: AppHandle<Wry>) {
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+        let state = app.state::<AppState>();
+        let Some(webhook) = state.webhook.get() else {
+            continue;
+        };
+        let (Some(frequency), Some(since)) = (webhook.summaries, webhook.since) else {
+            continue;
+        };
+        let until = now_millis();
+        if until.saturating_sub(since) < frequency.period_ms() {
+            continue;
+        }
+        let summary = Summary::collect(&state, since);
+        match post(&webhook, &Post::Summary { summary: &summary, frequency, since, until }).await {
+            Ok(()) => {
+                let updated = WebhookSettings { since: Some(until), ..webhook };
+                if let Err(err) = state.webhook.save(Some(updated)) {
+                    log::warn!("Could not record the webhook summary: {}", err);
+                }
+            }
+            Err(err) => log::warn!("Could not post the rename summary: {}", err),
+        }
+    }
+}
+
+async fn post(webhook: &WebhookSettings, post: &Post<'_>) -> Result<(), String> {
+    let body = payload(webhook.flavor, post, now_millis());
+    let config = [
+        push::config_line("url", &webhook.url),
+        push::config_line("header", "Content-Type: application/json"),
+        push::config_line("data-raw", &body.to_string()),
+        push::config_line("max-time", "15"),
+    ]
+    .concat();
+    push::curl(&config).await.map_err(|err| format!("Webhook failed: {}", err))
+}
+
+/// The JSON body of `post` in `flavor`'s shape.
+fn payload(flavor: WebhookFlavor, post: &Post, now: u64) -> Value {
+    match (flavor, post) {
+        (WebhookFlavor::Generic, Post::Alert(message)) => {
+            json!({ "event": "alert", "message": message, "timestamp": now })
+        }
+        (WebhookFlavor::Generic, Post::Summary { summary, frequency, since, until }) => json!({
+            "event": "summary",
+            "period": frequency,
+            "since": since,
+            "until": until,
+            "renamed": summary.renamed,
+            "converted": summary.converted,
+            "trashed": summary.trashed,
+            "previewed": summary.previewed,
+            "failed": summary.failures.len(),
+            "failures": summary.failures,
+            "issues": summary.issues,
+        }),
+        (WebhookFlavor::Slack, Post::Alert(message)) => json!({
+            "text": format!("Namefix: {}", message),
+            "blocks": [{
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!(":warning: *Namefix*\n{}", slack_escape(message)) },
+            }],
+        }),
+        (WebhookFlavor::Slack, Post::Summary { summary, frequency, .. }) => {
+            let title = summary_title(summary, *frequency);
+            let fields: Vec<Value> = counts(summary)
+                .into_iter()
+                .map(|(name, count)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", name, count) }))
+                .collect();
+            let mut blocks = vec![
+                json!({ "type": "header", "text": { "type": "plain_text", "text": title } }),
+                json!({ "type": "section", "fields": fields }),
+            ];
+            for (heading, lines) in details(summary) {
+                let list: Vec<String> = lines.iter().map(|line| format!("• {}", slack_escape(line))).collect();
+                let text = format!("*{}*\n{}", heading, list.join("\n"));
+                blocks.push(json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } }));
+            }
+            json!({ "text": title, "blocks": blocks })
+        }
+        (WebhookFlavor::Discord, Post::Alert(message)) => json!({
+            "username": "Namefix",
+            "embeds": [{ "title": "Namefix alert", "description": discord_escape(message), "color": DISCORD_ALERT }],
+        }),
+        (WebhookFlavor::Discord, Post::Summary { summary, frequency, .. }) => {
+            let mut fields: Vec<Value> = counts(summary)
+                .into_iter()
+                .map(|(name, count)| json!({ "name": name, "value": count.to_string(), "inline": true }))
+                .collect();
+            for (heading, lines) in details(summary) {
+                let list: Vec<String> = lines.iter().map(|line| format!("• {}", discord_escape(line))).collect();
+                // Discord caps a field's value at 1024 characters.
+                let value: String = list.join("\n").chars().take(1024).collect();
+                fields.push(json!({ "name": heading, "value": value, "inline": false }));
+            }
+            let color = if summary.failures.is_empty() { DISCORD_OK } else { DISCORD_ALERT };
+            json!({
+                "username": "Namefix",
+                "embeds": [{ "title": summary_title(summary, *frequency), "color": color, "fields": fields }],
+            })
+        }
+        (WebhookFlavor::Flat, Post::Alert(message)) => flat(&[
+            ("event", "alert".to_string()),
+            ("title", "Namefix alert".to_string()),
+            ("message", message.to_string()),
+            ("timestamp", iso_time(now)),
+        ]),
+        (WebhookFlavor::Flat, Post::Summary { summary, frequency, since, until }) => {
+            let mut fields = vec![
+                ("event", "summary".to_string()),
+                ("title", summary_title(summary, *frequency)),
+                ("period", frequency.label().to_string()),
+                ("since", iso_time(*since)),
+                ("until", iso_time(*until)),
+            ];
+            let keys = ["renamed", "converted", "trashed", "previewed", "failed"];
+            fields.extend(keys.into_iter().zip(counts(summary)).map(|(key, (_, count))| (key, count.to_string())));
+            let failures = failure_lines(summary).join("\n");
+            let issues = issue_lines(summary).join("\n");
+            let message = [failures.as_str(), issues.as_str()].into_iter().filter(|text| !text.is_empty());
+            fields.push(("message", message.collect::<Vec<_>>().join("\n")));
+            fields.extend([("failures", failures), ("issues", issues), ("timestamp", iso_time(now))]);
+            flat(&fields)
+        }
+    }
+}
+
+/// A flat object of `fields`, with IFTTT's `value1`-`value3` filled from the
+/// event, title and message.
+fn flat(fields: &[(&str, String)]) -> Value {
+    let mut object: Map<String, Value> =
+        fields.iter().map(|(key, value)| (key.to_string(), Value::String(value.clone()))).collect();
+    for (slot, key) in [("value1", "event"), ("value2", "title"), ("value3", "message")] {
+        let value = object.get(key).cloned().unwrap_or_else(|| Value::String(String::new()));
+        object.insert(slot.to_string(), value);
+    }
+    Value::Object(object)
+}
+
+/// Epoch milliseconds as an ISO 8601 UTC time, which both services parse.
+fn iso_time(ms: u64) -> String {
+    i64::try_from(ms)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|at| at.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn summary_title(summary: &Summary, frequency: DigestFrequency) -> String {
+    let mut title = format!("Namefix {} summary: {} renamed", frequency.label(), summary.renamed);
+    if !summary.failures.is_empty() {
+        title.push_str(&format!(", {} failed", summary.failures.len()));
+    }
+    title
+}
+
+fn counts(summary: &Summary) -> [(&'static str, usize); 5] {
+    [
+        ("Renamed", summary.renamed),
+        ("Converted", summary.converted),
+        ("Moved to Trash", summary.trashed),
+        ("Previewed", summary.previewed),
+        ("Failed", summary.failures.len()),
+    ]
+}
+
+/// Lists shown under the counts, left out when empty.
+fn details(summary: &Summary) -> Vec<(&'static str, Vec<String>)> {
+    let sections = [("Failures", failure_lines(summary)), ("Waiting in the Error Center", issue_lines(summary))];
+    sections.into_iter().filter(|(_, lines)| !lines.is_empty()).collect()
+}
+
+/// The most recent failures, newest first, then how many were left out.
+fn failure_lines(summary: &Summary) -> Vec<String> {
+    let mut lines: Vec<String> =
+        summary.failures.iter().rev().take(MAX_LISTED_FAILURES).map(email::describe_failure).collect();
+    if summary.failures.len() > MAX_LISTED_FAILURES {
+        lines.push(format!("and {} more", summary.failures.len() - MAX_LISTED_FAILURES));
+    }
+    lines
+}
+
+fn issue_lines(summary: &Summary) -> Vec<String> {
+    summary
+        .issues
+        .iter()
+        .map(|issue| match &issue.directory {
+            Some(dir) => format!("{} ({})", issue.message, abbreviate_home(dir)),
+            None => issue.message.clone(),
+        })
+        .collect()
+}
+
+/// Slack reads `&`, `<` and `>` as markup in message text.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Keep file names like `__init__.py` from turning into Markdown.
+fn discord_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '~' | '`' | '|' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{ActivityEntry, ActivityKind};
+
+    fn summary() -> Summary {
+        Summary {
+            renamed: 3,
+            failures: vec![ActivityEntry {
+                file: Some("a_b<c>.png".to_string()),
+                directory: Some("/srv/inbox".to_string()),
+                message: Some("Permission denied".to_string()),
+                ..ActivityEntry::now(ActivityKind::Error)
+            }],
+            ..Summary::default()
+        }
+    }
+
+    fn weekly(summary: &Summary) -> Post<'_> {
+        Post::Summary { summary, frequency: DigestFrequency::Weekly, since: 1, until: 2 }
+    }
+
+    #[test]
+    fn generic_posts_plain_fields() {
+        let summary = summary();
+        let body = payload(WebhookFlavor::Generic, &weekly(&summary), 0);
+        assert_eq!(body["event"], "summary");
+        assert_eq!(body["period"], "weekly");
+        assert_eq!(body["renamed"], 3);
+        assert_eq!(body["failures"][0]["file"], "a_b<c>.png");
+
+        let alert = payload(WebhookFlavor::Generic, &Post::Alert("down"), 7);
+        assert_eq!(alert, json!({ "event": "alert", "message": "down", "timestamp": 7 }));
+    }
+
+    #[test]
+    fn slack_posts_blocks() {
+        let summary = summary();
+        let body = payload(WebhookFlavor::Slack, &weekly(&summary), 0);
+        assert_eq!(body["text"], "Namefix weekly summary: 3 renamed, 1 failed");
+        assert_eq!(body["blocks"][0]["type"], "header");
+        assert_eq!(body["blocks"][1]["fields"][0]["text"], "*Renamed*\n3");
+        assert_eq!(
+            body["blocks"][2]["text"]["text"],
+            "*Failures*\n• a_b&lt;c&gt;.png in /srv/inbox: Permission denied"
+        );
+    }
+
+    #[test]
+    fn discord_posts_an_embed() {
+        let summary = summary();
+        let body = payload(WebhookFlavor::Discord, &weekly(&summary), 0);
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["color"], DISCORD_ALERT);
+        assert_eq!(embed["fields"][4], json!({ "name": "Failed", "value": "1", "inline": true }));
+        assert_eq!(embed["fields"][5]["value"], "• a\\_b<c\\>.png in /srv/inbox: Permission denied");
+    }
+
+    #[test]
+    fn flat_posts_only_top_level_strings() {
+        let summary = summary();
+        let body = payload(WebhookFlavor::Flat, &weekly(&summary), 0);
+        let object = body.as_object().unwrap();
+        assert!(object.values().all(Value::is_string));
+        assert_eq!(body["renamed"], "3");
+        assert_eq!(body["failed"], "1");
+        assert_eq!(body["since"], "1970-01-01T00:00:00Z");
+        assert_eq!(body["failures"], "a_b<c>.png in /srv/inbox: Permission denied");
+        assert_eq!(body["value2"], "Namefix weekly summary: 3 renamed, 1 failed");
+        assert_eq!(body["value3"], body["failures"]);
+
+        let alert = payload(WebhookFlavor::Flat, &Post::Alert("down"), 1_706_693_400_000);
+        assert_eq!(alert["value1"], "alert");
+        assert_eq!(alert["value3"], "down");
+        assert_eq!(alert["timestamp"], "2024-01-31T09:30:00Z");
+    }
+}
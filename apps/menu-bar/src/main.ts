@@ -5,9 +5,237 @@ type ServiceStatus = {
 	running: boolean;
 	directories: string[];
 	dryRun: boolean;
+	observerMode?: boolean;
+	logPrivacy?: boolean;
+	canRedo?: boolean;
+	dataDir?: string | null;
+	settingsSync?: SettingsSync;
+	pairing?: Pairing;
 	launchOnLogin: boolean;
+	capabilities?: string[];
+	originRules?: OriginRule[];
+	directoryGroups?: { directory: string; group: string }[];
+	hotFolders?: HotFolder[];
+	sharedDirectories?: SharedDirectory[];
+	volumes?: WatchedVolume[];
+	pinnedDirectories?: string[];
+	activeSession?: RenameSession | null;
 };
 
+type SettingsSync = {
+	dir: string | null;
+	state: 'off' | 'ok' | 'conflict' | 'locked' | 'error';
+	lastSyncedAt?: number;
+	conflict?: { deviceName: string; updatedAt: number };
+	detail?: string;
+};
+
+type Pairing = {
+	enabled: boolean;
+	code?: { code: string; expiresAt: number };
+	peers: { id: string; name: string; pairedAt: number }[];
+};
+
+type NearbyPeer = {
+	id: string;
+	name: string;
+	address: string;
+	port: number;
+	paired: boolean;
+};
+
+type PeerStatus = {
+	name: string;
+	running: boolean;
+	directories: string[];
+	dryRun: boolean;
+	observerMode: boolean;
+};
+
+type SharedDirectory = {
+	directory: string;
+	ownerUid: number;
+	ownedByOther: boolean;
+	worldWritable: boolean;
+};
+
+type WatchedVolume = {
+	root: string;
+	name: string;
+	mounted: boolean;
+	released: boolean;
+	directories: string[];
+};
+
+type ImportPreset = {
+	id: string;
+	name: string;
+	enabled: boolean;
+	template: string;
+	prefix: string;
+	destination: string;
+	eject: boolean;
+};
+
+type ImportProgress = {
+	preset: string;
+	card: string;
+	total: number;
+	imported: number;
+	failed: number;
+	done: boolean;
+	eject: boolean;
+	error?: string;
+};
+
+type RenameSession = {
+	id: string;
+	name: string;
+	startedAt: number;
+	endedAt?: number;
+};
+
+type SessionSummary = RenameSession & { filesRenamed: number; bytesProcessed: number };
+
+type QueueItem = {
+	id: string;
+	directory: string;
+	file: string;
+	priority: string;
+	enqueuedAt: number;
+};
+
+type QueueSnapshot = { paused: boolean; active: QueueItem[]; pending: QueueItem[] };
+
+type HistoryReport = { path: string; renames: number };
+
+type CalendarExport = { path: string; sessions: number };
+
+type HistoryMerge = { imported: number; duplicates: number; skipped: number };
+
+type ConfigImport = { directories: number; skipped: string[] };
+
+type HotFolder = {
+	directory: string;
+	destination?: string;
+	command?: string[];
+};
+
+type OriginRule = {
+	directory: string;
+	allow?: string[];
+	deny?: string[];
+};
+
+type IssueRemedy =
+	| 'grant-access'
+	| 'relocate'
+	| 'remove'
+	| 'preview-only'
+	| 'restart-service'
+	| 'repair-history'
+	| 'restore-history'
+	| 'keep-local-settings'
+	| 'use-synced-settings'
+	| 'dismiss';
+
+type Issue = {
+	id: string;
+	category: string;
+	directory: string | null;
+	count: number;
+	message: string;
+	lastSeen: number;
+	remedies: IssueRemedy[];
+};
+
+const REMEDY_LABELS: Record<IssueRemedy, string> = {
+	'grant-access': 'Grant Access',
+	relocate: 'Relocate',
+	remove: 'Stop Watching',
+	'preview-only': 'Preview Only',
+	'restart-service': 'Restart Service',
+	'repair-history': 'Repair',
+	'restore-history': 'Restore Backup',
+	'keep-local-settings': 'Keep This Mac’s',
+	'use-synced-settings': 'Use Synced',
+	dismiss: 'Dismiss',
+};
+
+type FileEventPayload = {
+	kind: string;
+	directory: string;
+	file: string;
+	target?: string;
+	timestamp: number;
+	collidedWith?: string;
+};
+
+type AuditEntry = FileEventPayload & { message?: string };
+
+/** How many audit entries the audit card shows. */
+const AUDIT_LIMIT = 50;
+
+type LoggedEventKind = 'renamed' | 'skipped' | 'error' | 'undone';
+
+type LoggedEvent = {
+	id: number;
+	timestamp: number;
+	kind: LoggedEventKind;
+	directory: string | null;
+	file: string | null;
+	target: string | null;
+	message: string | null;
+};
+
+type Tally = { key: string; count: number };
+
+type EventStats = {
+	totalRenames: number;
+	renamesThisWeek: number;
+	byDirectory: Tally[];
+	byExtension: Tally[];
+	byDay: Tally[];
+	errors: number;
+	undos: number;
+	since: number | null;
+};
+
+/** How many stored events the Event History card shows. */
+const EVENT_LIMIT = 100;
+
+const EVENT_LABELS: Record<LoggedEventKind, string> = {
+	renamed: 'renamed',
+	skipped: 'skipped',
+	error: 'error',
+	undone: 'undo',
+};
+
+const AUDIT_ACTIONS: Record<string, string> = {
+	preview: 'would rename',
+	skipped: 'skipped',
+	error: 'error',
+	'convert-error': 'error',
+};
+
+type FileFacts = {
+	path: string;
+	name: string;
+	size: number;
+	created: number | null;
+	modified: number | null;
+	sha256: string;
+};
+
+type ConflictComparison = {
+	incoming: FileFacts;
+	existing: FileFacts;
+	identical: boolean;
+};
+
+/** How many recent collisions the conflicts card keeps. */
+const MAX_CONFLICTS = 10;
+
 type ToastPayload = {
 	message: string;
 	level: 'info' | 'warn' | 'error';
@@ -23,6 +251,54 @@ type Profile = {
 	prefix: string;
 	priority: number;
 	action?: 'rename' | 'convert' | 'rename+convert';
+	fiscalYearStartMonth?: number;
+	timeZone?: string;
+	caseStyle?: CaseStyle;
+	datePrefix?: boolean;
+	condition?: string;
+};
+
+type CaseStyle = 'lower' | 'upper' | 'title' | 'kebab' | 'snake';
+
+type PaletteAction = {
+	id: string;
+	title: string;
+	section: string;
+	shortcut?: string;
+};
+
+type LogLevels = {
+	default: string;
+	targets: Record<string, string>;
+};
+
+type RenamePreview = {
+	planId: string;
+	directory: string;
+	entries: { from: string; to: string; rule: string | null; conflict: boolean }[];
+	/** Files no rule would rename. */
+	unchanged: number;
+};
+
+type RunOnceResult = {
+	directory: string;
+	renamed: number;
+	failed: number;
+	error?: string;
+};
+
+type DirectoryUsage = {
+	directory: string;
+	filesRenamed: number;
+	bytesProcessed: number;
+};
+
+type SortPreview = {
+	directory: string;
+	before: string[];
+	after: { name: string; renamedFrom: string | null }[];
+	chronological: boolean;
+	outOfOrder: string[];
 };
 
 const statusIndicator = document.querySelector<HTMLDivElement>('#status-indicator');
@@ -30,13 +306,128 @@ const statusIcon = document.querySelector<HTMLSpanElement>('#status-icon');
 const statusTitle = document.querySelector<HTMLHeadingElement>('#status-title');
 const statusSummary = document.querySelector<HTMLParagraphElement>('#status-summary');
 const directoriesList = document.querySelector<HTMLUListElement>('#directories');
+const sortPreview = document.querySelector<HTMLDivElement>('#sort-preview');
+const sortPreviewSummary = document.querySelector<HTMLParagraphElement>('#sort-preview-summary');
+const sortPreviewBefore = document.querySelector<HTMLOListElement>('#sort-preview-before');
+const sortPreviewAfter = document.querySelector<HTMLOListElement>('#sort-preview-after');
+const sortPreviewClose = document.querySelector<HTMLButtonElement>('#sort-preview-close');
+const sortDirectoriesButton = document.querySelector<HTMLButtonElement>('#sort-directories');
+const usageCard = document.querySelector<HTMLDivElement>('#directory-usage-card');
+const usageList = document.querySelector<HTMLUListElement>('#directory-usage');
+const sessionSummary = document.querySelector<HTMLParagraphElement>('#session-summary');
+const endSessionButton = document.querySelector<HTMLButtonElement>('#end-session');
+const exportCalendarButton = document.querySelector<HTMLButtonElement>(
+	'#export-sessions-calendar',
+);
+const startSessionForm = document.querySelector<HTMLFormElement>('#start-session-form');
+const sessionNameInput = document.querySelector<HTMLInputElement>('#session-name');
+const sessionList = document.querySelector<HTMLUListElement>('#session-list');
+const queueSummary = document.querySelector<HTMLParagraphElement>('#queue-summary');
+const queuePauseButton = document.querySelector<HTMLButtonElement>('#queue-pause');
+const queueList = document.querySelector<HTMLUListElement>('#queue-list');
+const importPresetList = document.querySelector<HTMLUListElement>('#import-presets');
+const importPresetForm = document.querySelector<HTMLFormElement>('#import-preset-form');
+const importNameInput = document.querySelector<HTMLInputElement>('#import-name');
+const importTemplateInput = document.querySelector<HTMLInputElement>('#import-template');
+const importDestinationInput = document.querySelector<HTMLInputElement>('#import-destination');
+const importEjectInput = document.querySelector<HTMLInputElement>('#import-eject');
+const importProgress = document.querySelector<HTMLDivElement>('#import-progress');
+const importProgressLabel = document.querySelector<HTMLDivElement>('#import-progress-label');
+const importProgressBar = document.querySelector<HTMLProgressElement>('#import-progress-bar');
 const toggleButton = document.querySelector<HTMLButtonElement>('#toggle-running');
 const dryRunToggle = document.querySelector<HTMLInputElement>('#dry-run-toggle');
+const observerToggle = document.querySelector<HTMLInputElement>('#observer-toggle');
+const auditCard = document.querySelector<HTMLDivElement>('#audit');
+const auditList = document.querySelector<HTMLUListElement>('#audit-list');
+const eventSearchForm = document.querySelector<HTMLFormElement>('#event-search-form');
+const eventSearchInput = document.querySelector<HTMLInputElement>('#event-search');
+const eventKindSelect = document.querySelector<HTMLSelectElement>('#event-kind');
+const eventList = document.querySelector<HTMLUListElement>('#event-list');
+const clearEventsButton = document.querySelector<HTMLButtonElement>('#clear-events');
+const eventStatsHint = document.querySelector<HTMLElement>('#event-stats');
 const launchToggle = document.querySelector<HTMLInputElement>('#launch-login-toggle');
+const telemetryToggle = document.querySelector<HTMLInputElement>('#telemetry-toggle');
+const highContrastToggle = document.querySelector<HTMLInputElement>('#high-contrast-toggle');
+const reducedMotionToggle = document.querySelector<HTMLInputElement>('#reduced-motion-toggle');
+const minimalNotificationsToggle = document.querySelector<HTMLInputElement>(
+	'#minimal-notifications-toggle',
+);
+const systemNotificationsToggle = document.querySelector<HTMLInputElement>(
+	'#system-notifications-toggle',
+);
+const telemetryPreview = document.querySelector<HTMLPreElement>('#telemetry-preview');
+const entryClickAction = document.querySelector<HTMLSelectElement>('#entry-click-action');
+const shortcutForm = document.querySelector<HTMLFormElement>('#shortcut-form');
+const shortcutAction = document.querySelector<HTMLSelectElement>('#shortcut-action');
+const shortcutKeys = document.querySelector<HTMLInputElement>('#shortcut-keys');
+const dataDirForm = document.querySelector<HTMLFormElement>('#data-dir-form');
+const dataDirInput = document.querySelector<HTMLInputElement>('#data-dir');
+const syncDirForm = document.querySelector<HTMLFormElement>('#sync-dir-form');
+const syncDirInput = document.querySelector<HTMLInputElement>('#sync-dir');
+const syncHint = document.querySelector<HTMLElement>('#sync-hint');
+const syncKeyForm = document.querySelector<HTMLFormElement>('#sync-key-form');
+const syncKeyInput = document.querySelector<HTMLInputElement>('#sync-key');
+const syncKeyShowButton = document.querySelector<HTMLButtonElement>('#sync-key-show');
+const lanPairingToggle = document.querySelector<HTMLInputElement>('#lan-pairing-toggle');
+const pairingForm = document.querySelector<HTMLFormElement>('#pairing-form');
+const pairingCodeInput = document.querySelector<HTMLInputElement>('#pairing-code');
+const pairingShowButton = document.querySelector<HTMLButtonElement>('#pairing-show');
+const peerList = document.querySelector<HTMLUListElement>('#peer-list');
+const pairingHint = document.querySelector<HTMLElement>('#pairing-hint');
+const statusPageToggle = document.querySelector<HTMLInputElement>('#status-page-toggle');
+const statusPageForm = document.querySelector<HTMLFormElement>('#status-page-form');
+const statusPagePortInput = document.querySelector<HTMLInputElement>('#status-page-port');
+const statusPageHint = document.querySelector<HTMLElement>('#status-page-hint');
+const httpSecurityForm = document.querySelector<HTMLFormElement>('#http-security-form');
+const httpAllowedOriginsInput = document.querySelector<HTMLInputElement>('#http-allowed-origins');
+const httpAllowedHostsInput = document.querySelector<HTMLInputElement>('#http-allowed-hosts');
+const apiTokenForm = document.querySelector<HTMLFormElement>('#api-token-form');
+const apiTokenNameInput = document.querySelector<HTMLInputElement>('#api-token-name');
+const apiTokenExpirySelect = document.querySelector<HTMLSelectElement>('#api-token-expiry');
+const apiTokenSecretInput = document.querySelector<HTMLInputElement>('#api-token-secret');
+const apiTokenList = document.querySelector<HTMLUListElement>('#api-token-list');
+const apiAccess = document.querySelector<HTMLDetailsElement>('#api-access');
+const apiAccessLog = document.querySelector<HTMLPreElement>('#api-access-log');
+const pushForm = document.querySelector<HTMLFormElement>('#push-form');
+const pushKindSelect = document.querySelector<HTMLSelectElement>('#push-kind');
+const pushTargetInput = document.querySelector<HTMLInputElement>('#push-target');
+const pushTokenInput = document.querySelector<HTMLInputElement>('#push-token');
+const emailDigestForm = document.querySelector<HTMLFormElement>('#email-digest-form');
+const emailDigestFrequency = document.querySelector<HTMLSelectElement>('#email-digest-frequency');
+const emailDigestTo = document.querySelector<HTMLInputElement>('#email-digest-to');
+const emailDigestServer = document.querySelector<HTMLInputElement>('#email-digest-server');
+const emailDigestPort = document.querySelector<HTMLInputElement>('#email-digest-port');
+const emailDigestUsername = document.querySelector<HTMLInputElement>('#email-digest-username');
+const emailDigestPassword = document.querySelector<HTMLInputElement>('#email-digest-password');
+const emailDigestSendButton = document.querySelector<HTMLButtonElement>('#email-digest-send');
+const webhookForm = document.querySelector<HTMLFormElement>('#webhook-form');
+const webhookFlavor = document.querySelector<HTMLSelectElement>('#webhook-flavor');
+const webhookSummaries = document.querySelector<HTMLSelectElement>('#webhook-summaries');
+const webhookUrl = document.querySelector<HTMLInputElement>('#webhook-url');
+const historyFileForm = document.querySelector<HTMLFormElement>('#history-file-form');
+const historyFileInput = document.querySelector<HTMLInputElement>('#history-file');
+const historyExportButton = document.querySelector<HTMLButtonElement>('#history-export');
+const configFileForm = document.querySelector<HTMLFormElement>('#config-file-form');
+const configFileInput = document.querySelector<HTMLInputElement>('#config-file');
+const configExportButton = document.querySelector<HTMLButtonElement>('#config-export');
+const logPrivacyToggle = document.querySelector<HTMLInputElement>('#log-privacy-toggle');
+const logLevelForm = document.querySelector<HTMLFormElement>('#log-level-form');
+const logTarget = document.querySelector<HTMLInputElement>('#log-target');
+const logLevel = document.querySelector<HTMLSelectElement>('#log-level');
+const logLevelList = document.querySelector<HTMLUListElement>('#log-level-list');
 const undoButton = document.querySelector<HTMLButtonElement>('#undo-button');
+const redoButton = document.querySelector<HTMLButtonElement>('#redo-button');
 const addDirectoryForm = document.querySelector<HTMLFormElement>('#add-directory-form');
 const addDirectoryInput = document.querySelector<HTMLInputElement>('#new-directory');
 const toastContainer = document.querySelector<HTMLDivElement>('#toast');
+const errorCenter = document.querySelector<HTMLDivElement>('#error-center');
+const issuesList = document.querySelector<HTMLUListElement>('#issues');
+const conflictsCard = document.querySelector<HTMLDivElement>('#conflicts');
+const conflictList = document.querySelector<HTMLUListElement>('#conflict-list');
+const conflictDiff = document.querySelector<HTMLDivElement>('#conflict-diff');
+const conflictIncoming = document.querySelector<HTMLDivElement>('#conflict-incoming');
+const conflictExisting = document.querySelector<HTMLDivElement>('#conflict-existing');
+const conflicts: FileEventPayload[] = [];
 
 // Badges
 const badgeDirs = document.querySelector<HTMLSpanElement>('#badge-dirs');
@@ -62,6 +453,13 @@ const profileTemplateInput = document.querySelector<HTMLInputElement>('#profile-
 const profilePrefixInput = document.querySelector<HTMLInputElement>('#profile-prefix');
 const profilePriorityInput = document.querySelector<HTMLInputElement>('#profile-priority');
 const profileActionSelect = document.querySelector<HTMLSelectElement>('#profile-action');
+const profileFiscalStartSelect = document.querySelector<HTMLSelectElement>('#profile-fiscal-start');
+const profileTimeZoneInput = document.querySelector<HTMLInputElement>('#profile-timezone');
+const profileCaseStyleSelect = document.querySelector<HTMLSelectElement>('#profile-case-style');
+const profileDatePrefixInput = document.querySelector<HTMLInputElement>('#profile-date-prefix');
+const profileConditionInput = document.querySelector<HTMLInputElement>('#profile-condition');
+const timeZoneOptions = document.querySelector<HTMLDataListElement>('#timezone-options');
+const previewTimeZone = document.querySelector<HTMLDivElement>('#preview-timezone');
 const deleteProfileBtn = document.querySelector<HTMLButtonElement>('#delete-profile-btn');
 const modalTitle = document.querySelector<HTMLHeadingElement>('#modal-title');
 const modalCloseBtn = document.querySelector<HTMLButtonElement>('.modal-close');
@@ -70,6 +468,22 @@ const previewOriginal = document.querySelector<HTMLDivElement>('#preview-origina
 const previewResult = document.querySelector<HTMLDivElement>('#preview-result');
 const templateAutocomplete = document.querySelector<HTMLDivElement>('#template-autocomplete');
 
+// Command palette elements
+const commandPalette = document.querySelector<HTMLDivElement>('#command-palette');
+const commandPaletteInput = document.querySelector<HTMLInputElement>('#command-palette-input');
+const commandPaletteList = document.querySelector<HTMLUListElement>('#command-palette-list');
+
+// Rename a Folder elements
+const renameFolderModal = document.querySelector<HTMLDivElement>('#rename-folder-modal');
+const renameFolderForm = document.querySelector<HTMLFormElement>('#rename-folder-form');
+const renameFolderInput = document.querySelector<HTMLInputElement>('#rename-folder-directory');
+const renameFolderProfile = document.querySelector<HTMLSelectElement>('#rename-folder-profile');
+const renameFolderSummary = document.querySelector<HTMLDivElement>('#rename-folder-summary');
+const renameFolderPlan = document.querySelector<HTMLOListElement>('#rename-folder-plan');
+const renameFolderApply = document.querySelector<HTMLButtonElement>('#rename-folder-apply');
+const renameFolderClose = document.querySelector<HTMLButtonElement>('#rename-folder-close');
+const renameFolderCancel = document.querySelector<HTMLButtonElement>('#rename-folder-cancel');
+
 // Template variables for autocomplete
 const TEMPLATE_VARIABLES = [
 	{ name: '<date>', desc: '2024-12-26' },
@@ -85,11 +499,44 @@ const TEMPLATE_VARIABLES = [
 	{ name: '<hour>', desc: '21' },
 	{ name: '<minute>', desc: '30' },
 	{ name: '<second>', desc: '00' },
+	{ name: '<week>', desc: '52 (ISO week)' },
+	{ name: '<fiscal_year>', desc: 'Fiscal year' },
+	{ name: '<fiscal_quarter>', desc: 'Q1-Q4' },
+	{ name: '<date:%G-W%V>', desc: '2024-W52' },
+	{ name: '<vendor>', desc: 'From PDF text' },
+	{ name: '<amount>', desc: '1234.50' },
+	{ name: '<currency>', desc: 'USD' },
+	{ name: '<doc_date>', desc: 'Date on the PDF' },
+	{ name: '<doc_year>', desc: 'Year on the PDF' },
+	{ name: '<doc_month>', desc: 'Month on the PDF' },
+	{ name: '<artist>', desc: 'From audio tags' },
+	{ name: '<album_artist>', desc: 'Album artist' },
+	{ name: '<album>', desc: 'Album' },
+	{ name: '<title>', desc: 'Track title' },
+	{ name: '<track>', desc: '01' },
+	{ name: '<disc>', desc: '1' },
+	{ name: '<window_title>', desc: 'Front window (macOS)' },
+	{ name: '<match:1>', desc: 'Regex group 1' },
 ];
 
 let currentStatus: ServiceStatus | null = null;
+/** Session shown as running, so the list refreshes when it starts or ends elsewhere. */
+let shownSessionId: string | null = null;
 let currentProfiles: Profile[] = [];
+let paletteActions: PaletteAction[] = [];
+/** Accelerators by action id, as `set_shortcut` normalized them. */
+let shortcuts: Record<string, string> = {};
+/** Index of the highlighted action among those matching the palette's filter. */
+let paletteSelection = 0;
+/** Plan shown in the Rename a Folder dialog; cleared when its folder or rules change. */
+let folderPlan: RenamePreview | null = null;
 let toastTimeout: ReturnType<typeof setTimeout> | null = null;
+/** Pending reload of the Event History card, so a burst of renames reloads it once. */
+let eventRefreshTimeout: ReturnType<typeof setTimeout> | null = null;
+/** Pending reload of the Queue card, for the same reason. */
+let queueRefreshTimeout: ReturnType<typeof setTimeout> | null = null;
+/** Queue as last shown, so a reorder sends the full order back. */
+let currentQueue: QueueSnapshot | null = null;
 let autocompleteIndex = -1;
 let autocompleteFilter = '';
 
@@ -206,7 +653,9 @@ function confirmAutocomplete(): boolean {
 
 function activateTab(name: string) {
 	for (const button of tabButtons) {
-		button.classList.toggle('active', button.dataset.tabTarget === name);
+		const active = button.dataset.tabTarget === name;
+		button.classList.toggle('active', active);
+		button.setAttribute('aria-selected', String(active));
 	}
 	for (const view of tabViews) {
 		view.classList.toggle('active', view.dataset.tab === name);
@@ -217,6 +666,9 @@ for (const button of tabButtons) {
 	button.addEventListener('click', () => {
 		const target = button.dataset.tabTarget ?? 'overview';
 		activateTab(target);
+		if (target === 'directories') {
+			refreshUsage().catch((error) => console.error('Failed to load usage', error));
+		}
 	});
 }
 
@@ -255,39 +707,267 @@ function clearElement(element: Element) {
 	}
 }
 
-function renderDirectories(status: ServiceStatus) {
-	if (!directoriesList) return;
-	clearElement(directoriesList);
+function folderName(directory: string): string {
+	const sanitized = directory.replace(/[/\\]+$/, '');
+	const segments = sanitized.split(/[/\\]/).filter(Boolean);
+	const baseName = segments.length ? segments[segments.length - 1] : directory;
+	return baseName || '/';
+}
 
-	if (!status.directories.length) {
-		const empty = document.createElement('li');
-		empty.className = 'directory-item';
-		empty.textContent = 'No directories configured yet';
-		empty.style.color = 'var(--text-subtle)';
-		empty.style.justifyContent = 'center';
-		directoriesList.appendChild(empty);
-		return;
+function formatBytes(bytes: number): string {
+	const units = ['bytes', 'KB', 'MB', 'GB', 'TB'];
+	let value = bytes;
+	let unit = 0;
+	while (value >= 1000 && unit < units.length - 1) {
+		value /= 1000;
+		unit++;
 	}
+	const digits = unit === 0 || value >= 10 ? 0 : 1;
+	return `${value.toFixed(digits)} ${units[unit]}`;
+}
 
-	for (const directory of status.directories) {
+async function refreshUsage() {
+	renderUsage(await invoke<DirectoryUsage[]>('get_directory_usage'));
+}
+
+function renderUsage(usage: DirectoryUsage[]) {
+	if (!usageCard || !usageList) return;
+	clearElement(usageList);
+	usageCard.hidden = usage.length === 0;
+
+	for (const entry of usage) {
 		const item = document.createElement('li');
 		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = folderName(entry.directory);
+		chip.title = entry.directory;
+		const summary = document.createElement('span');
+		summary.className = 'directory-path';
+		const count = entry.filesRenamed;
+		const files = `${count.toLocaleString()} file${count === 1 ? '' : 's'}`;
+		const bytes = formatBytes(entry.bytesProcessed);
+		summary.textContent = `${files} renamed, ${bytes} organized this month`;
+		text.append(chip, summary);
+		item.append(text);
+		usageList.appendChild(item);
+	}
+}
 
+async function refreshSessions() {
+	renderSessions(await invoke<SessionSummary[]>('list_sessions'));
+}
+
+function renderActiveSession(session: RenameSession | null) {
+	if (startSessionForm) startSessionForm.hidden = session !== null;
+	if (endSessionButton) endSessionButton.hidden = session === null;
+	if (sessionSummary) {
+		const since = session ? new Date(session.startedAt).toLocaleString() : '';
+		sessionSummary.textContent = session
+			? `Tagging renames with “${session.name}” since ${since}`
+			: 'Tag an import’s renames so they can be reviewed and undone together';
+	}
+}
+
+function renderSessions(sessions: SessionSummary[]) {
+	if (!sessionList) return;
+	clearElement(sessionList);
+
+	for (const session of sessions) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
 		const text = document.createElement('div');
 		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = session.name;
+		const summary = document.createElement('span');
+		summary.className = 'directory-path';
+		const count = session.filesRenamed;
+		const files = `${count.toLocaleString()} file${count === 1 ? '' : 's'}`;
+		const parts = [new Date(session.startedAt).toLocaleDateString(), files];
+		parts.push(formatBytes(session.bytesProcessed));
+		if (session.endedAt === undefined) parts.push('running');
+		summary.textContent = parts.join(' · ');
+		text.append(chip, summary);
+
+		const exportButton = document.createElement('button');
+		exportButton.type = 'button';
+		exportButton.className = 'button-ghost';
+		exportButton.textContent = 'Export';
+		exportButton.addEventListener('click', () => exportSession(session, exportButton));
+
+		const undoSession = document.createElement('button');
+		undoSession.type = 'button';
+		undoSession.className = 'button-ghost';
+		undoSession.textContent = 'Undo All';
+		undoSession.disabled = count === 0;
+		undoSession.addEventListener('click', () => {
+			// Ask once more before restoring a whole session's names
+			if (undoSession.dataset.confirm !== 'true') {
+				undoSession.dataset.confirm = 'true';
+				undoSession.textContent = `Undo ${files}?`;
+				return;
+			}
+			undoSessionRenames(session, undoSession);
+		});
+
+		item.append(text, exportButton, undoSession);
+		sessionList.appendChild(item);
+	}
+}
+
+async function refreshQueue() {
+	renderQueue(await invoke<QueueSnapshot>('get_queue'));
+}
+
+function scheduleQueueRefresh() {
+	if (queueRefreshTimeout) clearTimeout(queueRefreshTimeout);
+	queueRefreshTimeout = setTimeout(() => {
+		queueRefreshTimeout = null;
+		refreshQueue().catch((error) => console.error('Failed to load queue', error));
+	}, 250);
+}
+
+async function updateQueue(command: string, args: Record<string, unknown>) {
+	try {
+		renderQueue(await invoke<QueueSnapshot>(command, args));
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update queue: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		await refreshQueue().catch((err) => console.error('Failed to load queue', err));
+	}
+}
+
+/** Move the waiting item `id` to `index` among the waiting items. */
+function moveQueueItem(id: string, index: number) {
+	if (!currentQueue) return;
+	const ids = currentQueue.pending.map((item) => item.id).filter((pending) => pending !== id);
+	ids.splice(Math.max(0, index), 0, id);
+	updateQueue('reorder_queue', { orderedIds: ids });
+}
 
+function renderQueue(queue: QueueSnapshot) {
+	currentQueue = queue;
+	if (queuePauseButton) {
+		queuePauseButton.textContent = queue.paused ? 'Resume Queue' : 'Pause Queue';
+	}
+	if (queueSummary) {
+		const waiting = queue.pending.length;
+		const parts = [`${waiting.toLocaleString()} waiting`];
+		if (queue.active.length > 0) parts.push(`${queue.active.length} renaming`);
+		if (queue.paused) parts.push('paused');
+		queueSummary.textContent =
+			waiting + queue.active.length === 0 && !queue.paused
+				? 'Files waiting to be renamed'
+				: parts.join(' · ');
+	}
+	if (!queueList) return;
+	clearElement(queueList);
+
+	const rows = [
+		...queue.active.map((item) => ({ item, active: true })),
+		...queue.pending.map((item) => ({ item, active: false })),
+	];
+	rows.forEach(({ item, active }, row) => {
+		const index = row - queue.active.length;
+		const entry = document.createElement('li');
+		entry.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
 		const chip = document.createElement('span');
 		chip.className = 'directory-chip';
-		const sanitized = directory.replace(/[/\\]+$/, '');
-		const segments = sanitized.split(/[/\\]/).filter(Boolean);
-		const baseName = segments.length ? segments[segments.length - 1] : directory;
-		chip.textContent = baseName || '/';
+		chip.textContent = item.file;
+		const details = document.createElement('span');
+		details.className = 'directory-path';
+		const parts = [folderName(item.directory)];
+		if (item.priority !== 'normal') parts.push(`${item.priority} priority`);
+		parts.push(active ? 'renaming' : new Date(item.enqueuedAt).toLocaleTimeString());
+		details.textContent = parts.join(' · ');
+		text.append(chip, details);
+		entry.append(text);
+
+		if (!active) {
+			const nextButton = document.createElement('button');
+			nextButton.type = 'button';
+			nextButton.className = 'button-ghost';
+			nextButton.textContent = 'Do Next';
+			nextButton.title = 'Rename this file before the others waiting';
+			nextButton.disabled = index === 0;
+			nextButton.addEventListener('click', () => moveQueueItem(item.id, 0));
+
+			const upButton = document.createElement('button');
+			upButton.type = 'button';
+			upButton.className = 'button-ghost';
+			upButton.textContent = 'Up';
+			upButton.disabled = index === 0;
+			upButton.addEventListener('click', () => moveQueueItem(item.id, index - 1));
+
+			const downButton = document.createElement('button');
+			downButton.type = 'button';
+			downButton.className = 'button-ghost';
+			downButton.textContent = 'Down';
+			downButton.disabled = index === queue.pending.length - 1;
+			downButton.addEventListener('click', () => moveQueueItem(item.id, index + 1));
+
+			const dropButton = document.createElement('button');
+			dropButton.type = 'button';
+			dropButton.className = 'button-ghost';
+			dropButton.textContent = 'Drop';
+			dropButton.title = 'Leave this file as it is';
+			dropButton.addEventListener('click', () => updateQueue('drop_queue_item', { id: item.id }));
+
+			entry.append(nextButton, upButton, downButton, dropButton);
+		}
+		queueList.appendChild(entry);
+	});
+}
 
-		const label = document.createElement('span');
-		label.className = 'directory-path';
-		label.textContent = directory;
+async function refreshImportPresets() {
+	renderImportPresets(await invoke<ImportPreset[]>('list_import_presets'));
+}
 
-		text.append(chip, label);
+function renderImportPresets(presets: ImportPreset[]) {
+	if (!importPresetList) return;
+	clearElement(importPresetList);
+	for (const preset of presets) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = preset.name;
+		const destination = document.createElement('span');
+		destination.className = 'directory-path';
+		destination.textContent = `→ ${preset.destination}`;
+		const details = document.createElement('span');
+		details.className = 'directory-path';
+		const parts = [preset.template];
+		if (preset.eject) parts.push('ejects when done');
+		if (!preset.enabled) parts.push('off');
+		details.textContent = parts.join(' · ');
+		text.append(chip, destination, details);
+
+		const runButton = document.createElement('button');
+		runButton.type = 'button';
+		runButton.className = 'button-ghost';
+		runButton.textContent = 'Import Now';
+		runButton.title = 'Import every mounted camera card with this preset';
+		runButton.addEventListener('click', () => runImportPreset(preset, runButton));
+
+		const toggleButton = document.createElement('button');
+		toggleButton.type = 'button';
+		toggleButton.className = 'button-ghost';
+		toggleButton.textContent = preset.enabled ? 'Turn Off' : 'Turn On';
+		toggleButton.addEventListener('click', () =>
+			saveImportPreset({ ...preset, enabled: !preset.enabled }),
+		);
 
 		const removeButton = document.createElement('button');
 		removeButton.type = 'button';
@@ -296,129 +976,1722 @@ function renderDirectories(status: ServiceStatus) {
 		removeButton.addEventListener('click', async () => {
 			removeButton.disabled = true;
 			try {
-				await invoke('remove_watch_dir', { directory });
-				showToast(`Removed ${baseName}`, 'info');
+				const id = preset.id;
+				renderImportPresets(await invoke<ImportPreset[]>('delete_import_preset', { id }));
 			} catch (error: unknown) {
 				showToast(
-					`Failed to remove: ${error instanceof Error ? error.message : String(error)}`,
+					`Failed to remove preset: ${error instanceof Error ? error.message : String(error)}`,
 					'error',
 				);
 				removeButton.disabled = false;
 			}
 		});
 
-		item.append(text, removeButton);
-		directoriesList.appendChild(item);
+		item.append(text, runButton, toggleButton, removeButton);
+		importPresetList.appendChild(item);
 	}
 }
 
-function renderStatus(status: ServiceStatus) {
-	currentStatus = status;
-
-	// Update hero section
-	if (statusIndicator) {
-		statusIndicator.dataset.state = status.running ? 'active' : 'paused';
-	}
-	if (statusIcon) {
-		statusIcon.textContent = status.running ? '👁' : '⏸';
-	}
-	if (statusTitle) {
-		statusTitle.textContent = status.running ? 'Watching' : 'Paused';
-	}
-	if (statusSummary) {
-		statusSummary.textContent = formatSummary(status);
-	}
-
-	// Update toggle button
-	if (toggleButton) {
-		toggleButton.textContent = status.running ? 'Pause Watching' : 'Start Watching';
-	}
-
-	// Update switches
-	if (dryRunToggle) dryRunToggle.checked = status.dryRun;
-	if (launchToggle) launchToggle.checked = status.launchOnLogin;
-
-	// Update badges
-	if (metricDirectories) {
-		metricDirectories.textContent = String(status.directories.length);
-	}
-	if (badgeDirs) {
-		badgeDirs.dataset.state = status.directories.length ? 'active' : 'muted';
+async function saveImportPreset(preset: ImportPreset) {
+	try {
+		renderImportPresets(await invoke<ImportPreset[]>('set_import_preset', { preset }));
+		return true;
+	} catch (error: unknown) {
+		showToast(
+			`Failed to save preset: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		return false;
 	}
+}
 
-	if (metricDryRun) {
-		metricDryRun.textContent = status.dryRun ? 'on' : 'off';
-	}
-	if (badgeDryRun) {
-		badgeDryRun.dataset.state = status.dryRun ? 'warn' : 'muted';
+async function addImportPreset() {
+	const destination = importDestinationInput?.value.trim() ?? '';
+	if (!destination) {
+		showToast('Enter a library folder to import into', 'warn');
+		return;
 	}
+	const saved = await saveImportPreset({
+		id: `import-${crypto.randomUUID()}`,
+		name: importNameInput?.value.trim() || 'Camera',
+		enabled: true,
+		template: importTemplateInput?.value.trim() || '<prefix>_<datetime>',
+		prefix: 'IMG',
+		destination,
+		eject: importEjectInput?.checked ?? false,
+	});
+	if (!saved) return;
+	importPresetForm?.reset();
+	if (importEjectInput) importEjectInput.checked = false;
+}
 
-	if (metricLaunch) {
-		metricLaunch.textContent = status.launchOnLogin ? 'on' : 'off';
-	}
-	if (badgeLogin) {
-		badgeLogin.dataset.state = status.launchOnLogin ? 'active' : 'muted';
+async function runImportPreset(preset: ImportPreset, button: HTMLButtonElement) {
+	button.disabled = true;
+	try {
+		const results = await invoke<ImportProgress[]>('run_import_preset', { id: preset.id });
+		if (results.length === 0) showToast('No camera card is mounted', 'info');
+	} catch (error: unknown) {
+		showToast(
+			`Import failed: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button.disabled = false;
 	}
-
-	renderDirectories(status);
 }
 
-async function refreshStatus(retries = 3, delay = 500) {
-	for (let i = 0; i < retries; i++) {
-		try {
-			const status = await invoke<ServiceStatus>('get_status');
-			renderStatus(status);
-			return;
-		} catch {
-			if (i < retries - 1) await new Promise((r) => setTimeout(r, delay * (i + 1)));
-		}
-	}
-	showToast('Unable to connect to service', 'error');
-	if (statusSummary) statusSummary.textContent = 'Unable to connect to service';
+function renderImportProgress(progress: ImportProgress) {
+	if (!importProgress || !importProgressLabel || !importProgressBar) return;
+	importProgress.hidden = false;
+	const card = folderName(progress.card);
+	const handled = progress.imported + progress.failed;
+	importProgressBar.max = Math.max(progress.total, 1);
+	importProgressBar.value = progress.done ? importProgressBar.max : handled;
+	const failed = progress.failed > 0 ? `, ${progress.failed} failed` : '';
+	importProgressLabel.textContent = progress.done
+		? `Imported ${progress.imported} of ${progress.total} from ${card}${failed}`
+		: `Importing from ${card}: ${handled} of ${progress.total}${failed}`;
 }
 
-async function toggleRunning() {
-	if (!currentStatus) return;
-	toggleButton?.setAttribute('disabled', 'true');
+async function startSession(name: string) {
+	if (!name.trim()) {
+		showToast('Enter a session name', 'warn');
+		return;
+	}
 	try {
-		await invoke<ServiceStatus>('toggle_running', { desired: !currentStatus.running });
+		const status = await invoke<ServiceStatus>('start_session', { name });
+		if (sessionNameInput) sessionNameInput.value = '';
+		renderStatus(status);
+		await refreshSessions();
 	} catch (error: unknown) {
 		showToast(
-			`Failed to toggle: ${error instanceof Error ? error.message : String(error)}`,
+			`Failed to start session: ${error instanceof Error ? error.message : String(error)}`,
 			'error',
 		);
-		toggleButton?.removeAttribute('disabled');
 	}
 }
 
-async function setDryRun(enabled: boolean) {
+async function endSession() {
 	try {
-		await invoke<ServiceStatus>('set_dry_run', { enabled });
+		renderStatus(await invoke<ServiceStatus>('end_session'));
+		await refreshSessions();
 	} catch (error: unknown) {
 		showToast(
-			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			`Failed to end session: ${error instanceof Error ? error.message : String(error)}`,
 			'error',
 		);
-		if (dryRunToggle && currentStatus) dryRunToggle.checked = currentStatus.dryRun;
 	}
 }
 
-async function setLaunchOnLogin(enabled: boolean) {
+async function exportSession(session: SessionSummary, button: HTMLButtonElement) {
+	button.disabled = true;
 	try {
-		await invoke<boolean>('set_launch_on_login', { enabled });
+		const report = await invoke<HistoryReport>('export_session', { id: session.id });
+		await invoke('reveal_file', { path: report.path });
 	} catch (error: unknown) {
 		showToast(
-			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			`Failed to export session: ${error instanceof Error ? error.message : String(error)}`,
 			'error',
 		);
-		if (launchToggle && currentStatus) launchToggle.checked = currentStatus.launchOnLogin;
+	} finally {
+		button.disabled = false;
 	}
 }
 
-async function undoLast() {
-	undoButton?.setAttribute('disabled', 'true');
+async function exportSessionsCalendar() {
+	if (exportCalendarButton) exportCalendarButton.disabled = true;
 	try {
-		const result = await invoke<{ ok: boolean; reason?: string }>('undo');
+		const calendar = await invoke<CalendarExport>('export_sessions_calendar');
+		await invoke('reveal_file', { path: calendar.path });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to export sessions: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		if (exportCalendarButton) exportCalendarButton.disabled = false;
+	}
+}
+
+async function undoSessionRenames(session: SessionSummary, button: HTMLButtonElement) {
+	button.disabled = true;
+	try {
+		await invoke('undo_session', { id: session.id });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to undo session: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+	await refreshSessions().catch((error) => console.error('Failed to load sessions', error));
+}
+
+function renderDirectories(status: ServiceStatus) {
+	if (!directoriesList) return;
+	clearElement(directoriesList);
+
+	if (!status.directories.length) {
+		const empty = document.createElement('li');
+		empty.className = 'directory-item';
+		empty.textContent = 'No directories configured yet';
+		empty.style.color = 'var(--text-subtle)';
+		empty.style.justifyContent = 'center';
+		directoriesList.appendChild(empty);
+		return;
+	}
+
+	for (const directory of status.directories) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = folderName(directory);
+		chip.title = directory;
+
+		const label = document.createElement('span');
+		label.className = 'directory-path';
+		label.textContent = directory;
+
+		text.append(chip, label);
+
+		const rule = status.originRules?.find((entry) => entry.directory === directory);
+		const origins = document.createElement('span');
+		origins.className = 'directory-path';
+		origins.textContent = describeOriginRule(rule);
+		if (origins.textContent) text.append(origins);
+
+		const group = status.directoryGroups?.find((entry) => entry.directory === directory)?.group;
+		if (group) {
+			const groupLabel = document.createElement('span');
+			groupLabel.className = 'directory-path';
+			groupLabel.textContent = `Group: ${group}`;
+			text.append(groupLabel);
+		}
+
+		const hotFolder = status.hotFolders?.find((entry) => entry.directory === directory);
+		const handOff = document.createElement('span');
+		handOff.className = 'directory-path';
+		handOff.textContent = describeHotFolder(hotFolder);
+		if (handOff.textContent) text.append(handOff);
+
+		const shared = status.sharedDirectories?.find((entry) => entry.directory === directory);
+		if (shared) {
+			const sharedLabel = document.createElement('span');
+			sharedLabel.className = 'directory-path';
+			sharedLabel.textContent = shared.ownedByOther
+				? `Shared: owned by uid ${shared.ownerUid}`
+				: 'Shared: writable by everyone';
+			sharedLabel.title = 'Files other users create here may not be renamable';
+			text.append(sharedLabel);
+		}
+
+		const volume = status.volumes?.find((entry) => entry.directories.includes(directory));
+		if (volume && (!volume.mounted || volume.released)) {
+			const volumeLabel = document.createElement('span');
+			volumeLabel.className = 'directory-path';
+			volumeLabel.textContent = volume.released
+				? `Ejecting ${volume.name}`
+				: `Offline: ${volume.name} is not mounted`;
+			text.append(volumeLabel);
+		}
+
+		const pinned = status.pinnedDirectories?.includes(directory) ?? false;
+		const pinButton = document.createElement('button');
+		pinButton.type = 'button';
+		pinButton.className = 'button-ghost';
+		pinButton.textContent = pinned ? 'Unpin' : 'Pin';
+		pinButton.title = 'Keep this directory at the top of the list and tray menu';
+		pinButton.addEventListener('click', async () => {
+			pinButton.disabled = true;
+			try {
+				await invoke('set_directory_pinned', { directory, pinned: !pinned });
+			} catch (error: unknown) {
+				showToast(
+					`Failed to ${pinned ? 'unpin' : 'pin'}: ${error instanceof Error ? error.message : String(error)}`,
+					'error',
+				);
+				pinButton.disabled = false;
+			}
+		});
+
+		const groupButton = document.createElement('button');
+		groupButton.type = 'button';
+		groupButton.className = 'button-ghost';
+		groupButton.textContent = 'Group';
+		groupButton.title = 'Nest this directory under a group in the tray menu';
+		groupButton.addEventListener('click', () => {
+			groupButton.disabled = true;
+			const groups = [...new Set(status.directoryGroups?.map((entry) => entry.group))];
+			text.append(directoryGroupForm(directory, group, groups));
+		});
+
+		const appsButton = document.createElement('button');
+		appsButton.type = 'button';
+		appsButton.className = 'button-ghost';
+		appsButton.textContent = 'Apps';
+		appsButton.title = 'Choose which apps’ files get renamed here';
+		appsButton.addEventListener('click', () => {
+			appsButton.disabled = true;
+			origins.remove();
+			text.append(originRuleForm(directory, rule));
+		});
+
+		const handOffButton = document.createElement('button');
+		handOffButton.type = 'button';
+		handOffButton.className = 'button-ghost';
+		handOffButton.textContent = 'Hand Off';
+		handOffButton.title = 'Move renamed files elsewhere or run a command on them';
+		handOffButton.addEventListener('click', () => {
+			handOffButton.disabled = true;
+			handOff.remove();
+			text.append(hotFolderForm(directory, hotFolder));
+		});
+
+		const removeButton = document.createElement('button');
+		removeButton.type = 'button';
+		removeButton.className = 'button-ghost';
+		removeButton.textContent = 'Remove';
+		removeButton.addEventListener('click', async () => {
+			removeButton.disabled = true;
+			try {
+				await invoke('remove_watch_dir', { directory });
+				showToast(`Removed ${baseName}`, 'info');
+			} catch (error: unknown) {
+				showToast(
+					`Failed to remove: ${error instanceof Error ? error.message : String(error)}`,
+					'error',
+				);
+				removeButton.disabled = false;
+			}
+		});
+
+		const previewButton = document.createElement('button');
+		previewButton.type = 'button';
+		previewButton.className = 'button-ghost';
+		previewButton.textContent = 'Preview Order';
+		previewButton.addEventListener('click', async () => {
+			previewButton.disabled = true;
+			try {
+				renderSortPreview(await invoke<SortPreview>('preview_sort_order', { directory }));
+			} catch (error: unknown) {
+				showToast(
+					`Failed to preview: ${error instanceof Error ? error.message : String(error)}`,
+					'error',
+				);
+			} finally {
+				previewButton.disabled = false;
+			}
+		});
+
+		const renamesButton = document.createElement('button');
+		renamesButton.type = 'button';
+		renamesButton.className = 'button-ghost';
+		renamesButton.textContent = 'Preview Renames';
+		renamesButton.title = 'See what dry run would rename here, then rename the files you pick';
+		renamesButton.hidden = !status.dryRun;
+		renamesButton.addEventListener('click', () => openRenameFolder(directory));
+
+		item.append(
+			text,
+			pinButton,
+			groupButton,
+			appsButton,
+			handOffButton,
+			previewButton,
+			renamesButton,
+			removeButton,
+		);
+		if (volume?.mounted && !volume.released) {
+			const ejectButton = document.createElement('button');
+			ejectButton.type = 'button';
+			ejectButton.className = 'button-ghost';
+			ejectButton.textContent = 'Eject';
+			ejectButton.title = `Stop watching and eject ${volume.name}`;
+			ejectButton.addEventListener('click', async () => {
+				ejectButton.disabled = true;
+				try {
+					await invoke('eject_volume', { root: volume.root });
+					showToast(`Ejected ${volume.name}`, 'info');
+				} catch (error: unknown) {
+					showToast(error instanceof Error ? error.message : String(error), 'error');
+					ejectButton.disabled = false;
+				}
+			});
+			item.append(ejectButton);
+		}
+		directoriesList.appendChild(item);
+	}
+}
+
+function describeOriginRule(rule: OriginRule | undefined): string {
+	const parts: string[] = [];
+	if (rule?.allow?.length) parts.push(`Only from ${rule.allow.join(', ')}`);
+	if (rule?.deny?.length) parts.push(`Never from ${rule.deny.join(', ')}`);
+	return parts.join(' · ');
+}
+
+function originRuleForm(directory: string, rule: OriginRule | undefined): HTMLFormElement {
+	const form = document.createElement('form');
+	form.className = 'add-directory-form';
+	const appList = (value: string) =>
+		value
+			.split(',')
+			.map((app) => app.trim())
+			.filter(Boolean);
+
+	const allow = document.createElement('input');
+	allow.type = 'text';
+	allow.placeholder = 'Only from, e.g. Screenshot, Safari';
+	allow.value = rule?.allow?.join(', ') ?? '';
+	const deny = document.createElement('input');
+	deny.type = 'text';
+	deny.placeholder = 'Never from';
+	deny.value = rule?.deny?.join(', ') ?? '';
+	const save = document.createElement('button');
+	save.type = 'submit';
+	save.className = 'button-secondary';
+	save.textContent = 'Save';
+
+	form.append(allow, deny, save);
+	form.addEventListener('submit', async (event) => {
+		event.preventDefault();
+		save.disabled = true;
+		try {
+			// The service re-emits its status, which re-renders this list.
+			await invoke('set_directory_origin_rule', {
+				directory,
+				allow: appList(allow.value),
+				deny: appList(deny.value),
+			});
+		} catch (error: unknown) {
+			showToast(
+				`Failed to save app rule: ${error instanceof Error ? error.message : String(error)}`,
+				'error',
+			);
+			save.disabled = false;
+		}
+	});
+	return form;
+}
+
+function describeHotFolder(hotFolder: HotFolder | undefined): string {
+	const parts: string[] = [];
+	if (hotFolder?.destination) parts.push(`Hands off to ${folderName(hotFolder.destination)}`);
+	if (hotFolder?.command?.length) parts.push(`Runs ${hotFolder.command.join(' ')}`);
+	return parts.join(' · ');
+}
+
+function hotFolderForm(directory: string, hotFolder: HotFolder | undefined): HTMLFormElement {
+	const form = document.createElement('form');
+	form.className = 'add-directory-form';
+
+	const destination = document.createElement('input');
+	destination.type = 'text';
+	destination.placeholder = 'Move to, e.g. ~/Print Queue';
+	destination.value = hotFolder?.destination ?? '';
+	const command = document.createElement('input');
+	command.type = 'text';
+	command.placeholder = 'Then run, e.g. lp -d Office {file}';
+	command.value = hotFolder?.command?.join(' ') ?? '';
+	const save = document.createElement('button');
+	save.type = 'submit';
+	save.className = 'button-secondary';
+	save.textContent = 'Save';
+
+	form.append(destination, command, save);
+	form.addEventListener('submit', async (event) => {
+		event.preventDefault();
+		save.disabled = true;
+		try {
+			// Leaving both empty turns the hot folder back into an ordinary one.
+			await invoke('set_directory_hot_folder', {
+				directory,
+				destination: destination.value.trim() || null,
+				command: command.value.trim() ? command.value.trim().split(/\s+/) : null,
+			});
+		} catch (error: unknown) {
+			showToast(
+				`Failed to save hand-off: ${error instanceof Error ? error.message : String(error)}`,
+				'error',
+			);
+			save.disabled = false;
+		}
+	});
+	return form;
+}
+
+function directoryGroupForm(
+	directory: string,
+	current: string | undefined,
+	groups: string[],
+): HTMLFormElement {
+	const form = document.createElement('form');
+	form.className = 'add-directory-form';
+
+	const listId = 'directory-group-options';
+	const options = document.createElement('datalist');
+	options.id = listId;
+	for (const name of groups) {
+		const option = document.createElement('option');
+		option.value = name;
+		options.append(option);
+	}
+	const input = document.createElement('input');
+	input.type = 'text';
+	input.placeholder = 'Group, e.g. Work (empty to ungroup)';
+	input.value = current ?? '';
+	input.setAttribute('list', listId);
+	const save = document.createElement('button');
+	save.type = 'submit';
+	save.className = 'button-secondary';
+	save.textContent = 'Save';
+
+	form.append(input, options, save);
+	form.addEventListener('submit', async (event) => {
+		event.preventDefault();
+		save.disabled = true;
+		try {
+			// The service re-emits its status, which re-renders this list.
+			await invoke('set_directory_group', { directory, group: input.value.trim() || null });
+		} catch (error: unknown) {
+			showToast(
+				`Failed to save group: ${error instanceof Error ? error.message : String(error)}`,
+				'error',
+			);
+			save.disabled = false;
+		}
+	});
+	return form;
+}
+
+function renderSortPreview(preview: SortPreview) {
+	if (!sortPreview || !sortPreviewBefore || !sortPreviewAfter) return;
+	clearElement(sortPreviewBefore);
+	clearElement(sortPreviewAfter);
+
+	for (const name of preview.before) {
+		const row = document.createElement('li');
+		row.textContent = name;
+		sortPreviewBefore.appendChild(row);
+	}
+
+	const outOfOrder = new Set(preview.outOfOrder);
+	for (const entry of preview.after) {
+		const row = document.createElement('li');
+		row.textContent = entry.name;
+		if (entry.renamedFrom) {
+			row.classList.add(outOfOrder.has(entry.name) ? 'out-of-order' : 'renamed');
+			row.title = `Renamed from ${entry.renamedFrom}`;
+		}
+		sortPreviewAfter.appendChild(row);
+	}
+
+	const renamed = preview.after.filter((entry) => entry.renamedFrom).length;
+	const files = (count: number) => `${count} renamed file${count === 1 ? '' : 's'}`;
+	if (sortPreviewSummary) {
+		if (renamed === 0) {
+			sortPreviewSummary.textContent = `No files in ${preview.directory} would be renamed`;
+		} else if (preview.chronological) {
+			sortPreviewSummary.textContent = `${files(renamed)} will list oldest first`;
+		} else {
+			const count = preview.outOfOrder.length;
+			sortPreviewSummary.textContent = `${files(count)} will list out of chronological order`;
+		}
+	}
+	sortPreview.hidden = false;
+}
+
+function renderStatus(status: ServiceStatus) {
+	currentStatus = status;
+
+	// Update hero section
+	if (statusIndicator) {
+		statusIndicator.dataset.state = status.running ? 'active' : 'paused';
+	}
+	if (statusIcon) {
+		statusIcon.textContent = status.running ? '👁' : '⏸';
+	}
+	if (statusTitle) {
+		statusTitle.textContent = status.running ? 'Watching' : 'Paused';
+	}
+	if (statusSummary) {
+		statusSummary.textContent = formatSummary(status);
+	}
+
+	// Update toggle button
+	if (toggleButton) {
+		toggleButton.textContent = status.running ? 'Pause Watching' : 'Start Watching';
+	}
+
+	// Update switches
+	if (dryRunToggle) {
+		dryRunToggle.checked = status.dryRun;
+		dryRunToggle.disabled = !(status.capabilities ?? []).includes('supports_preview');
+	}
+	if (launchToggle) launchToggle.checked = status.launchOnLogin;
+	if (observerToggle) observerToggle.checked = status.observerMode ?? false;
+	if (logPrivacyToggle) logPrivacyToggle.checked = status.logPrivacy ?? true;
+	if (redoButton) redoButton.disabled = !status.canRedo;
+	if (dataDirInput && document.activeElement !== dataDirInput) {
+		dataDirInput.value = status.dataDir ?? '';
+	}
+	if (syncDirInput && document.activeElement !== syncDirInput) {
+		syncDirInput.value = status.settingsSync?.dir ?? '';
+	}
+	if (syncHint) syncHint.textContent = formatSettingsSync(status.settingsSync);
+	if (lanPairingToggle) lanPairingToggle.checked = status.pairing?.enabled ?? false;
+	if (pairingShowButton) pairingShowButton.disabled = !status.pairing?.enabled;
+	if (pairingHint) pairingHint.textContent = formatPairing(status.pairing);
+	if (auditCard) {
+		const observing = status.observerMode ?? false;
+		if (observing && auditCard.hidden) {
+			refreshAudit().catch((error) => console.error('Failed to load audit log', error));
+		}
+		auditCard.hidden = !observing;
+	}
+
+	// Update badges
+	if (metricDirectories) {
+		metricDirectories.textContent = String(status.directories.length);
+	}
+	if (badgeDirs) {
+		badgeDirs.dataset.state = status.directories.length ? 'active' : 'muted';
+	}
+
+	if (metricDryRun) {
+		metricDryRun.textContent = status.dryRun ? 'on' : 'off';
+	}
+	if (badgeDryRun) {
+		badgeDryRun.dataset.state = status.dryRun ? 'warn' : 'muted';
+	}
+
+	if (metricLaunch) {
+		metricLaunch.textContent = status.launchOnLogin ? 'on' : 'off';
+	}
+	if (badgeLogin) {
+		badgeLogin.dataset.state = status.launchOnLogin ? 'active' : 'muted';
+	}
+
+	renderDirectories(status);
+
+	const session = status.activeSession ?? null;
+	renderActiveSession(session);
+	if ((session?.id ?? null) !== shownSessionId) {
+		shownSessionId = session?.id ?? null;
+		refreshSessions().catch((error) => console.error('Failed to load sessions', error));
+	}
+}
+
+async function refreshStatus(retries = 3, delay = 500) {
+	for (let i = 0; i < retries; i++) {
+		try {
+			const status = await invoke<ServiceStatus>('get_status');
+			renderStatus(status);
+			return;
+		} catch {
+			if (i < retries - 1) await new Promise((r) => setTimeout(r, delay * (i + 1)));
+		}
+	}
+	showToast('Unable to connect to service', 'error');
+	if (statusSummary) statusSummary.textContent = 'Unable to connect to service';
+}
+
+async function toggleRunning() {
+	if (!currentStatus) return;
+	toggleButton?.setAttribute('disabled', 'true');
+	try {
+		await invoke<ServiceStatus>('toggle_running', { desired: !currentStatus.running });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to toggle: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		toggleButton?.removeAttribute('disabled');
+	}
+}
+
+async function setDryRun(enabled: boolean) {
+	try {
+		await invoke<ServiceStatus>('set_dry_run', { enabled });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (dryRunToggle && currentStatus) dryRunToggle.checked = currentStatus.dryRun;
+	}
+}
+
+async function setObserverMode(enabled: boolean) {
+	try {
+		await invoke<ServiceStatus>('set_observer_mode', { enabled });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (observerToggle && currentStatus) {
+			observerToggle.checked = currentStatus.observerMode ?? false;
+		}
+	}
+}
+
+async function setLogPrivacy(enabled: boolean) {
+	try {
+		await invoke<ServiceStatus>('set_log_privacy', { enabled });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (logPrivacyToggle && currentStatus) {
+			logPrivacyToggle.checked = currentStatus.logPrivacy ?? true;
+		}
+	}
+}
+
+async function moveDataDir(path: string | null) {
+	const button = dataDirForm?.querySelector<HTMLButtonElement>('button');
+	button?.setAttribute('disabled', 'true');
+	try {
+		const status = await invoke<ServiceStatus>('set_data_dir', { path });
+		showToast(
+			status.dataDir ? `Data moved to ${status.dataDir}` : 'Data moved to the default location',
+		);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to move data: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (dataDirInput) dataDirInput.value = currentStatus?.dataDir ?? '';
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+const SYNC_HINT =
+	'Keep settings and rules, but not history, the same on every Mac that syncs through this folder.';
+
+function formatSettingsSync(sync: SettingsSync | undefined): string {
+	switch (sync?.state) {
+		case 'ok':
+			return sync.lastSyncedAt
+				? `In sync. Last change ${new Date(sync.lastSyncedAt).toLocaleString()}.`
+				: 'In sync.';
+		case 'conflict': {
+			const other = sync.conflict?.deviceName ?? 'another Mac';
+			return `Settings also changed on ${other}. Choose which to keep in the Error Center.`;
+		}
+		case 'locked':
+			return 'Synced settings are locked with another Mac’s key. Import its pairing key below.';
+		case 'error':
+			return `Can't sync: ${sync.detail ?? 'the folder is unavailable'}.`;
+		default:
+			return `${SYNC_HINT} Leave empty to stop.`;
+	}
+}
+
+async function setSyncDir(path: string | null) {
+	const button = syncDirForm?.querySelector<HTMLButtonElement>('button');
+	button?.setAttribute('disabled', 'true');
+	try {
+		const status = await invoke<ServiceStatus>('set_sync_dir', { path });
+		const dir = status.settingsSync?.dir;
+		showToast(dir ? `Syncing settings through ${dir}` : 'Settings sync is off');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to sync settings: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (syncDirInput) syncDirInput.value = currentStatus?.settingsSync?.dir ?? '';
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+async function showSyncKey() {
+	syncKeyShowButton?.setAttribute('disabled', 'true');
+	try {
+		const key = await invoke<string>('export_sync_key');
+		if (syncKeyInput) {
+			syncKeyInput.value = key;
+			syncKeyInput.select();
+		}
+		showToast('Copy this key and import it on your other Mac. Anyone with it can read your rules.');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to read the pairing key: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		syncKeyShowButton?.removeAttribute('disabled');
+	}
+}
+
+async function importSyncKey(key: string) {
+	const button = syncKeyForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	button?.setAttribute('disabled', 'true');
+	try {
+		await invoke<ServiceStatus>('import_sync_key', { key });
+		if (syncKeyInput) syncKeyInput.value = '';
+		showToast('Pairing key imported');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to import the key: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+function formatPairing(pairing: Pairing | undefined): string {
+	if (pairing?.code) {
+		const { code, expiresAt } = pairing.code;
+		const until = new Date(expiresAt).toLocaleTimeString();
+		return `Enter ${code.slice(0, 3)} ${code.slice(3)} on the other Mac before ${until}.`;
+	}
+	const paired = pairing?.peers.length ?? 0;
+	const count = paired === 1 ? '1 Mac' : `${paired} Macs`;
+	const intro = paired > 0 ? `Paired with ${count}. ` : '';
+	return `${intro}Show a code on one Mac, then find it from the other and enter the code to pair.`;
+}
+
+async function setLanPairing(enabled: boolean) {
+	try {
+		await invoke<ServiceStatus>('set_lan_pairing', { enabled });
+		if (!enabled && peerList) clearElement(peerList);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (lanPairingToggle) lanPairingToggle.checked = currentStatus?.pairing?.enabled ?? false;
+	}
+}
+
+async function showPairingCode() {
+	try {
+		const { code } = await invoke<{ code: string; expiresAt: number }>('start_pairing');
+		showToast(`Pairing code: ${code.slice(0, 3)} ${code.slice(3)}`);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to show a code: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+}
+
+async function findPeers() {
+	const button = pairingForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	button?.setAttribute('disabled', 'true');
+	try {
+		const peers = await invoke<NearbyPeer[]>('find_peers');
+		renderPeers(peers);
+		if (peers.length === 0) showToast('No other Macs running Namefix were found', 'warn');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to look for Macs: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+function renderPeers(peers: NearbyPeer[]) {
+	if (!peerList) return;
+	clearElement(peerList);
+	for (const peer of peers) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = peer.name;
+		const detail = document.createElement('span');
+		detail.className = 'directory-path';
+		detail.textContent = peer.paired ? `Paired · ${peer.address}` : peer.address;
+		text.append(chip, detail);
+		item.append(text);
+
+		if (peer.paired) {
+			const statusButton = document.createElement('button');
+			statusButton.type = 'button';
+			statusButton.className = 'button-ghost';
+			statusButton.textContent = 'Status';
+			statusButton.addEventListener('click', () => showPeerStatus(peer, statusButton));
+			const unpairButton = document.createElement('button');
+			unpairButton.type = 'button';
+			unpairButton.className = 'button-ghost';
+			unpairButton.textContent = 'Unpair';
+			unpairButton.addEventListener('click', () => unpairPeer(peer, unpairButton));
+			item.append(statusButton, unpairButton);
+		} else {
+			const pairButton = document.createElement('button');
+			pairButton.type = 'button';
+			pairButton.className = 'button-secondary';
+			pairButton.textContent = 'Pair';
+			pairButton.addEventListener('click', () => pairPeer(peer, pairButton));
+			item.append(pairButton);
+		}
+		peerList.appendChild(item);
+	}
+}
+
+async function pairPeer(peer: NearbyPeer, button: HTMLButtonElement) {
+	const code = pairingCodeInput?.value.replace(/\s+/g, '') ?? '';
+	if (code.length === 0) {
+		showToast(`Enter the code shown on ${peer.name} first`, 'warn');
+		pairingCodeInput?.focus();
+		return;
+	}
+	button.disabled = true;
+	try {
+		await invoke<ServiceStatus>('pair_peer', { peerId: peer.id, code });
+		if (pairingCodeInput) pairingCodeInput.value = '';
+		await findPeers();
+	} catch (error: unknown) {
+		showToast(
+			`Failed to pair: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		button.disabled = false;
+	}
+}
+
+async function showPeerStatus(peer: NearbyPeer, button: HTMLButtonElement) {
+	button.disabled = true;
+	try {
+		const status = await invoke<PeerStatus>('get_peer_status', { peerId: peer.id });
+		const count = status.directories.length;
+		const parts = [status.running ? 'running' : 'paused'];
+		parts.push(`watching ${count} director${count === 1 ? 'y' : 'ies'}`);
+		if (status.dryRun) parts.push('dry run');
+		if (status.observerMode) parts.push('observer mode');
+		showToast(`${status.name}: ${parts.join(', ')}`);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to reach ${peer.name}: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button.disabled = false;
+	}
+}
+
+async function unpairPeer(peer: NearbyPeer, button: HTMLButtonElement) {
+	button.disabled = true;
+	try {
+		await invoke<ServiceStatus>('unpair_peer', { peerId: peer.id });
+		showToast(`Unpaired from ${peer.name}`);
+		await findPeers();
+	} catch (error: unknown) {
+		showToast(
+			`Failed to unpair: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		button.disabled = false;
+	}
+}
+
+async function exportHistory(path: string) {
+	historyExportButton?.setAttribute('disabled', 'true');
+	try {
+		const count = await invoke<number>('export_history', { path });
+		showToast(`Exported ${count} rename${count === 1 ? '' : 's'} to ${path}`);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to export history: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		historyExportButton?.removeAttribute('disabled');
+	}
+}
+
+async function mergeHistory(importPath: string) {
+	const button = historyFileForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	button?.setAttribute('disabled', 'true');
+	try {
+		const merged = await invoke<HistoryMerge>('merge_history', { importPath });
+		if (merged.duplicates > 0) {
+			const already = merged.duplicates === 1 ? '1 rename was' : `${merged.duplicates} renames were`;
+			showToast(`${already} already here`);
+		}
+	} catch (error: unknown) {
+		showToast(
+			`Failed to merge history: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+async function exportConfig(path: string) {
+	configExportButton?.setAttribute('disabled', 'true');
+	try {
+		await invoke('export_config', { path });
+		showToast(`Exported settings to ${path}`);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to export settings: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		configExportButton?.removeAttribute('disabled');
+	}
+}
+
+async function importConfig(path: string) {
+	const button = configFileForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	button?.setAttribute('disabled', 'true');
+	try {
+		const imported = await invoke<ConfigImport>('import_config', { path });
+		await refreshSystemNotifications();
+		if (imported.skipped.length > 0) {
+			showToast(`Settings imported; skipped ${imported.skipped.join(', ')}`, 'warn');
+		} else {
+			showToast('Settings imported');
+		}
+	} catch (error: unknown) {
+		showToast(
+			`Failed to import settings: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		button?.removeAttribute('disabled');
+	}
+}
+
+async function refreshAudit() {
+	renderAudit(await invoke<AuditEntry[]>('get_audit_log', { limit: AUDIT_LIMIT }));
+}
+
+function renderAudit(entries: AuditEntry[]) {
+	if (!auditList) return;
+	clearElement(auditList);
+	for (const entry of entries) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = entry.collidedWith ? 'collision' : (AUDIT_ACTIONS[entry.kind] ?? entry.kind);
+		const detail = document.createElement('span');
+		detail.className = 'directory-path';
+		const outcome = entry.target ? `${entry.file} → ${entry.target}` : entry.file;
+		detail.textContent = entry.message ? `${outcome} (${entry.message})` : outcome;
+		detail.title = `${entry.directory} · ${new Date(entry.timestamp).toLocaleString()}`;
+		text.append(chip, detail);
+		item.append(text);
+		auditList.appendChild(item);
+	}
+}
+
+async function refreshEvents() {
+	const kind = eventKindSelect?.value;
+	const events = await invoke<LoggedEvent[]>('query_events', {
+		filter: { kinds: kind ? [kind] : [], text: eventSearchInput?.value ?? '' },
+		range: null,
+		limit: EVENT_LIMIT,
+	});
+	renderEvents(events);
+	renderEventStats(await invoke<EventStats>('get_stats'));
+}
+
+function renderEventStats(stats: EventStats) {
+	if (!eventStatsHint) return;
+	const plural = (count: number, noun: string) => `${count} ${noun}${count === 1 ? '' : 's'}`;
+	const parts = [
+		`${plural(stats.totalRenames, 'rename')} (${stats.renamesThisWeek} this week)`,
+		plural(stats.errors, 'error'),
+		plural(stats.undos, 'undo'),
+	];
+	const top = stats.byDirectory[0];
+	if (top) parts.push(`most in ${top.key}`);
+	const since = stats.since ? ` since ${new Date(stats.since).toLocaleDateString()}` : '';
+	eventStatsHint.textContent = `${parts.join(' · ')}${since}`;
+}
+
+function scheduleEventRefresh() {
+	if (eventRefreshTimeout) clearTimeout(eventRefreshTimeout);
+	eventRefreshTimeout = setTimeout(() => {
+		eventRefreshTimeout = null;
+		refreshEvents().catch((error) => console.error('Failed to load event history', error));
+	}, 500);
+}
+
+function renderEvents(events: LoggedEvent[]) {
+	if (!eventList) return;
+	clearElement(eventList);
+	if (events.length === 0) {
+		const empty = document.createElement('li');
+		empty.className = 'directory-item';
+		empty.textContent = 'No events match';
+		empty.style.color = 'var(--text-subtle)';
+		empty.style.justifyContent = 'center';
+		eventList.appendChild(empty);
+		return;
+	}
+	for (const event of events) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = EVENT_LABELS[event.kind] ?? event.kind;
+		const detail = document.createElement('span');
+		detail.className = 'directory-path';
+		const outcome = event.target ? `${event.file} → ${event.target}` : (event.file ?? '');
+		detail.textContent = [outcome, event.message].filter(Boolean).join(' · ');
+		const when = new Date(event.timestamp).toLocaleString();
+		detail.title = event.directory ? `${event.directory} · ${when}` : when;
+		text.append(chip, detail);
+		item.append(text);
+		eventList.appendChild(item);
+	}
+}
+
+async function clearEvents() {
+	clearEventsButton?.setAttribute('disabled', 'true');
+	try {
+		const removed = await invoke<number>('clear_events');
+		showToast(`Cleared ${removed} event${removed === 1 ? '' : 's'}`);
+		await refreshEvents();
+	} catch (error: unknown) {
+		showToast(
+			`Failed to clear events: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		clearEventsButton?.removeAttribute('disabled');
+	}
+}
+
+async function setLaunchOnLogin(enabled: boolean) {
+	try {
+		await invoke<boolean>('set_launch_on_login', { enabled });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (launchToggle && currentStatus) launchToggle.checked = currentStatus.launchOnLogin;
+	}
+}
+
+type TelemetryPreview = {
+	enabled: boolean;
+	consentedAt: number | null;
+	report: Record<string, unknown>;
+};
+
+function renderTelemetry(preview: TelemetryPreview) {
+	if (telemetryToggle) telemetryToggle.checked = preview.enabled;
+	if (telemetryPreview) telemetryPreview.textContent = JSON.stringify(preview.report, null, 2);
+}
+
+async function refreshTelemetry() {
+	renderTelemetry(await invoke<TelemetryPreview>('get_telemetry_preview'));
+}
+
+async function setTelemetryEnabled(enabled: boolean) {
+	try {
+		renderTelemetry(await invoke<TelemetryPreview>('set_telemetry_enabled', { enabled }));
+		showToast(enabled ? 'Thanks for sharing usage stats' : 'Usage stats off and cleared', 'info');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (telemetryToggle) telemetryToggle.checked = !enabled;
+	}
+}
+
+type AccessibilitySettings = {
+	highContrastIcon: boolean;
+	reducedMotion: boolean;
+	minimalNotifications: boolean;
+};
+
+let accessibility: AccessibilitySettings = {
+	highContrastIcon: false,
+	reducedMotion: false,
+	minimalNotifications: false,
+};
+
+function renderAccessibility(settings: AccessibilitySettings) {
+	accessibility = settings;
+	if (highContrastToggle) highContrastToggle.checked = settings.highContrastIcon;
+	if (reducedMotionToggle) reducedMotionToggle.checked = settings.reducedMotion;
+	if (minimalNotificationsToggle) {
+		minimalNotificationsToggle.checked = settings.minimalNotifications;
+	}
+	document.documentElement.toggleAttribute('data-reduced-motion', settings.reducedMotion);
+}
+
+async function refreshAccessibility() {
+	renderAccessibility(await invoke<AccessibilitySettings>('get_accessibility_settings'));
+}
+
+async function updateAccessibility(change: Partial<AccessibilitySettings>) {
+	try {
+		const settings = { ...accessibility, ...change };
+		renderAccessibility(
+			await invoke<AccessibilitySettings>('set_accessibility_settings', { settings }),
+		);
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		renderAccessibility(accessibility);
+	}
+}
+
+async function refreshSystemNotifications() {
+	const enabled = await invoke<boolean>('get_notifications_enabled');
+	if (systemNotificationsToggle) systemNotificationsToggle.checked = enabled;
+}
+
+async function setSystemNotifications(enabled: boolean) {
+	try {
+		await invoke<boolean>('set_notifications_enabled', { enabled });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		if (systemNotificationsToggle) systemNotificationsToggle.checked = !enabled;
+	}
+}
+
+type HttpSecurity = { allowedOrigins: string[]; allowedHosts: string[] };
+
+type StatusPage = { enabled: boolean; port: number; url: string | null; security: HttpSecurity };
+
+function renderStatusPage(page: StatusPage) {
+	if (statusPageToggle) statusPageToggle.checked = page.enabled;
+	if (statusPagePortInput) statusPagePortInput.value = String(page.port);
+	if (statusPageHint) {
+		statusPageHint.textContent = page.url
+			? `Open ${page.url} on a phone or another computer on this network.`
+			: 'The same status is served as JSON at /status.json.';
+	}
+	if (httpAllowedOriginsInput) {
+		httpAllowedOriginsInput.value = page.security.allowedOrigins.join(', ');
+	}
+	if (httpAllowedHostsInput) httpAllowedHostsInput.value = page.security.allowedHosts.join(', ');
+}
+
+function splitList(value: string | undefined): string[] {
+	return (value ?? '')
+		.split(',')
+		.map((entry) => entry.trim())
+		.filter(Boolean);
+}
+
+async function setHttpSecurity() {
+	const options: HttpSecurity = {
+		allowedOrigins: splitList(httpAllowedOriginsInput?.value),
+		allowedHosts: splitList(httpAllowedHostsInput?.value),
+	};
+	try {
+		renderStatusPage(await invoke<StatusPage>('set_http_security', { options }));
+		showToast('Allowed origins saved');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to save allowed origins: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+}
+
+async function refreshStatusPage() {
+	renderStatusPage(await invoke<StatusPage>('get_status_page'));
+}
+
+async function setStatusPage(enabled: boolean) {
+	const port = Number.parseInt(statusPagePortInput?.value ?? '', 10) || 0;
+	try {
+		renderStatusPage(await invoke<StatusPage>('set_status_page', { enabled, port }));
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update the status page: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		await refreshStatusPage().catch(() => {});
+	}
+}
+
+type ApiScope = 'rules' | 'directories' | 'scan';
+
+type ApiToken = {
+	id: string;
+	name: string;
+	scopes: ApiScope[];
+	createdAt: number;
+	expiresAt: number | null;
+	lastUsedAt: number | null;
+	expired: boolean;
+	uses: number;
+	/** Newest first. */
+	recent: { at: number; scope: ApiScope; status: string }[];
+};
+
+type ApiAccess = {
+	at: number;
+	client: string | null;
+	token: string | null;
+	method: string;
+	path: string;
+	status: string;
+};
+
+const API_SCOPE_LABELS: Record<ApiScope, string> = {
+	rules: 'rules',
+	directories: 'folders',
+	scan: 'scan',
+};
+
+function describeApiToken(token: ApiToken): string {
+	const parts = [token.scopes.map((scope) => API_SCOPE_LABELS[scope]).join(', ')];
+	if (token.expired) {
+		parts.push('expired');
+	} else if (token.expiresAt) {
+		parts.push(`expires ${new Date(token.expiresAt).toLocaleDateString()}`);
+	} else {
+		parts.push('never expires');
+	}
+	parts.push(
+		token.lastUsedAt ? `last used ${new Date(token.lastUsedAt).toLocaleString()}` : 'never used',
+	);
+	return parts.join(' · ');
+}
+
+function renderApiTokens(tokens: ApiToken[]) {
+	if (!apiTokenList) return;
+	clearElement(apiTokenList);
+	for (const token of tokens) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = token.name;
+		const detail = document.createElement('span');
+		detail.className = 'directory-path';
+		detail.textContent = describeApiToken(token);
+		text.append(chip, detail);
+		if (token.uses > 0) {
+			const usage = document.createElement('details');
+			usage.className = 'token-usage';
+			const summary = document.createElement('summary');
+			summary.textContent = `${token.uses} call${token.uses === 1 ? '' : 's'}`;
+			const calls = document.createElement('pre');
+			calls.textContent = token.recent
+				.map((use) => `${new Date(use.at).toLocaleString()}  /api/${use.scope}  ${use.status}`)
+				.join('\n');
+			usage.append(summary, calls);
+			text.append(usage);
+		}
+		const revokeButton = document.createElement('button');
+		revokeButton.type = 'button';
+		revokeButton.className = 'button-ghost';
+		revokeButton.textContent = 'Revoke';
+		revokeButton.addEventListener('click', () => revokeApiToken(token, revokeButton));
+		item.append(text, revokeButton);
+		apiTokenList.appendChild(item);
+	}
+}
+
+function renderApiAccess(log: ApiAccess[]) {
+	if (!apiAccess || !apiAccessLog) return;
+	apiAccess.hidden = log.length === 0;
+	apiAccessLog.textContent = log
+		.map((access) =>
+			[
+				new Date(access.at).toLocaleString(),
+				access.client ?? 'unknown',
+				access.token ?? 'no token',
+				`${access.method} ${access.path}`,
+				access.status,
+			].join('  '),
+		)
+		.join('\n');
+}
+
+async function refreshApiTokens() {
+	renderApiTokens(await invoke<ApiToken[]>('list_api_tokens'));
+	renderApiAccess(await invoke<ApiAccess[]>('get_api_access_log'));
+}
+
+async function createApiToken() {
+	const checked = apiTokenForm?.querySelectorAll<HTMLInputElement>(
+		'input[name="api-token-scope"]:checked',
+	);
+	const scopes = Array.from(checked ?? [], (input) => input.value as ApiScope);
+	const days = Number.parseInt(apiTokenExpirySelect?.value ?? '', 10);
+	const button = apiTokenForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	if (button) button.disabled = true;
+	try {
+		const created = await invoke<ApiToken & { token: string }>('create_api_token', {
+			name: apiTokenNameInput?.value ?? '',
+			scopes,
+			days: Number.isNaN(days) ? null : days,
+		});
+		if (apiTokenSecretInput) {
+			apiTokenSecretInput.value = created.token;
+			apiTokenSecretInput.hidden = false;
+			apiTokenSecretInput.select();
+		}
+		if (apiTokenNameInput) apiTokenNameInput.value = '';
+		showToast('Copy this token now; it is not shown again.');
+		await refreshApiTokens();
+	} catch (error: unknown) {
+		showToast(
+			`Failed to create a token: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		if (button) button.disabled = false;
+	}
+}
+
+async function revokeApiToken(token: ApiToken, button: HTMLButtonElement) {
+	button.disabled = true;
+	try {
+		renderApiTokens(await invoke<ApiToken[]>('revoke_api_token', { id: token.id }));
+		if (apiTokenSecretInput) {
+			apiTokenSecretInput.value = '';
+			apiTokenSecretInput.hidden = true;
+		}
+	} catch (error: unknown) {
+		showToast(
+			`Failed to revoke the token: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		button.disabled = false;
+	}
+}
+
+type PushEndpoint =
+	| { kind: 'ntfy'; url: string; token: string | null }
+	| { kind: 'pushover'; user: string; token: string };
+
+/** Placeholders for the target and token fields of each push service. */
+const PUSH_FIELDS: Record<string, [string, string]> = {
+	'': ['', ''],
+	ntfy: ['https://ntfy.sh/your-topic', 'Access token (optional)'],
+	pushover: ['Your user key', 'Application token'],
+};
+
+function renderPushKind(kind: string) {
+	const [target, token] = PUSH_FIELDS[kind] ?? PUSH_FIELDS[''];
+	if (pushTargetInput) {
+		pushTargetInput.placeholder = target;
+		pushTargetInput.disabled = kind === '';
+	}
+	if (pushTokenInput) {
+		pushTokenInput.placeholder = token;
+		pushTokenInput.disabled = kind === '';
+	}
+}
+
+function renderPushEndpoint(endpoint: PushEndpoint | null) {
+	const kind = endpoint?.kind ?? '';
+	if (pushKindSelect) pushKindSelect.value = kind;
+	if (pushTargetInput) {
+		pushTargetInput.value = endpoint?.kind === 'ntfy' ? endpoint.url : (endpoint?.user ?? '');
+	}
+	if (pushTokenInput) pushTokenInput.value = endpoint?.token ?? '';
+	renderPushKind(kind);
+}
+
+async function refreshPushEndpoint() {
+	renderPushEndpoint(await invoke<PushEndpoint | null>('get_push_endpoint'));
+}
+
+async function savePushEndpoint() {
+	const target = pushTargetInput?.value.trim() ?? '';
+	const token = pushTokenInput?.value.trim() ?? '';
+	let endpoint: PushEndpoint | null = null;
+	if (pushKindSelect?.value === 'ntfy') {
+		endpoint = { kind: 'ntfy', url: target, token: token || null };
+	} else if (pushKindSelect?.value === 'pushover') {
+		endpoint = { kind: 'pushover', user: target, token };
+	}
+	const button = pushForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	if (button) button.disabled = true;
+	try {
+		renderPushEndpoint(await invoke<PushEndpoint | null>('set_push_endpoint', { endpoint }));
+		showToast(endpoint ? 'Test alert sent' : 'Push alerts turned off');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to save push alerts: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		if (button) button.disabled = false;
+	}
+}
+
+type EmailDigest = {
+	enabled: boolean;
+	frequency: 'daily' | 'weekly';
+	to: string;
+	from: string;
+	server: string;
+	port: number;
+	username: string;
+	since: number | null;
+	hasPassword: boolean;
+};
+
+let emailDigest: EmailDigest | null = null;
+
+function renderEmailDigest(digest: EmailDigest) {
+	emailDigest = digest;
+	if (emailDigestFrequency) emailDigestFrequency.value = digest.enabled ? digest.frequency : '';
+	if (emailDigestTo) emailDigestTo.value = digest.to;
+	if (emailDigestServer) emailDigestServer.value = digest.server;
+	if (emailDigestPort) emailDigestPort.value = String(digest.port);
+	if (emailDigestUsername) emailDigestUsername.value = digest.username;
+	if (emailDigestPassword) {
+		emailDigestPassword.value = '';
+		emailDigestPassword.placeholder = digest.hasPassword ? 'Saved in the keychain' : 'Password';
+	}
+}
+
+async function refreshEmailDigest() {
+	renderEmailDigest(await invoke<EmailDigest>('get_email_digest'));
+}
+
+async function saveEmailDigest() {
+	const frequency = emailDigestFrequency?.value ?? '';
+	const settings = {
+		...emailDigest,
+		enabled: frequency !== '',
+		frequency: frequency || emailDigest?.frequency || 'weekly',
+		to: emailDigestTo?.value.trim() ?? '',
+		server: emailDigestServer?.value.trim() ?? '',
+		port: Number.parseInt(emailDigestPort?.value ?? '', 10) || 587,
+		username: emailDigestUsername?.value.trim() ?? '',
+	};
+	const password = emailDigestPassword?.value || null;
+	try {
+		renderEmailDigest(await invoke<EmailDigest>('set_email_digest', { settings, password }));
+		showToast(settings.enabled ? `Email digest will be sent ${frequency}` : 'Email digest off');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to save the email digest: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+}
+
+async function sendEmailDigest() {
+	if (emailDigestSendButton) emailDigestSendButton.disabled = true;
+	try {
+		await invoke('send_email_digest');
+		showToast('Email digest sent');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to send the email digest: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		if (emailDigestSendButton) emailDigestSendButton.disabled = false;
+	}
+}
+
+type Webhook = {
+	url: string;
+	flavor: 'generic' | 'slack' | 'discord' | 'flat';
+	summaries: 'daily' | 'weekly' | null;
+	since: number | null;
+};
+
+function renderWebhook(webhook: Webhook | null) {
+	if (webhookFlavor) webhookFlavor.value = webhook?.flavor ?? '';
+	if (webhookSummaries) webhookSummaries.value = webhook?.summaries ?? '';
+	if (webhookUrl) webhookUrl.value = webhook?.url ?? '';
+}
+
+async function refreshWebhook() {
+	renderWebhook(await invoke<Webhook | null>('get_webhook'));
+}
+
+async function saveWebhook() {
+	const flavor = webhookFlavor?.value ?? '';
+	const webhook = flavor
+		? {
+				url: webhookUrl?.value.trim() ?? '',
+				flavor,
+				summaries: webhookSummaries?.value || null,
+			}
+		: null;
+	const button = webhookForm?.querySelector<HTMLButtonElement>('button[type="submit"]');
+	if (button) button.disabled = true;
+	try {
+		renderWebhook(await invoke<Webhook | null>('set_webhook', { webhook }));
+		showToast(webhook ? 'Test alert posted' : 'Webhook turned off');
+	} catch (error: unknown) {
+		showToast(
+			`Failed to save the webhook: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	} finally {
+		if (button) button.disabled = false;
+	}
+}
+
+type EntryClickAction = 'reveal' | 'open' | 'copy-new-path' | 'undo';
+
+async function refreshEntryClickAction() {
+	const action = await invoke<EntryClickAction>('get_entry_click_action');
+	if (entryClickAction) entryClickAction.value = action;
+}
+
+async function setEntryClickAction(action: EntryClickAction) {
+	try {
+		await invoke<EntryClickAction>('set_entry_click_action', { action });
+	} catch (error: unknown) {
+		showToast(
+			`Failed to update: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		await refreshEntryClickAction();
+	}
+}
+
+/** Keys other than letters, digits and function keys, by `KeyboardEvent.code`. */
+const NAMED_KEYS: Record<string, string> = {
+	Space: 'Space',
+	Enter: 'Enter',
+	Tab: 'Tab',
+	Backspace: 'Backspace',
+	Delete: 'Delete',
+	Escape: 'Escape',
+	ArrowUp: 'Up',
+	ArrowDown: 'Down',
+	ArrowLeft: 'Left',
+	ArrowRight: 'Right',
+};
+
+/**
+ * A key press written the way shortcuts are stored, e.g. `Cmd+Shift+K`, or null while only
+ * modifiers are held. Uses the physical key, so Alt combinations aren't read as symbols.
+ */
+function acceleratorFor(event: KeyboardEvent): string | null {
+	const code = event.code;
+	const key = /^(Key[A-Z]|Digit\d)$/.test(code)
+		? code.slice(-1)
+		: /^F\d{1,2}$/.test(code)
+			? code
+			: NAMED_KEYS[code];
+	if (!key) return null;
+	const modifiers = [
+		event.metaKey && 'Cmd',
+		event.ctrlKey && 'Ctrl',
+		event.altKey && 'Alt',
+		event.shiftKey && 'Shift',
+	].filter(Boolean);
+	return [...modifiers, key].join('+');
+}
+
+async function refreshShortcuts() {
+	shortcuts = await invoke<Record<string, string>>('get_shortcuts');
+	const actions = await invoke<PaletteAction[]>('list_actions');
+	if (!shortcutAction) return;
+	const selected = shortcutAction.value;
+	clearElement(shortcutAction);
+	for (const action of actions) {
+		shortcutAction.appendChild(new Option(`${action.section}: ${action.title}`, action.id));
+	}
+	if (actions.some((action) => action.id === selected)) shortcutAction.value = selected;
+	showShortcut();
+}
+
+function showShortcut() {
+	if (shortcutKeys) shortcutKeys.value = shortcuts[shortcutAction?.value ?? ''] ?? '';
+}
+
+async function saveShortcut() {
+	const action = shortcutAction?.value;
+	if (!action) return;
+	try {
+		shortcuts = await invoke<Record<string, string>>('set_shortcut', {
+			action,
+			accelerator: shortcutKeys?.value || null,
+		});
+		showShortcut();
+		showToast(shortcutKeys?.value ? `Shortcut set to ${shortcutKeys.value}` : 'Shortcut removed');
+	} catch (error: unknown) {
+		showToast(error instanceof Error ? error.message : String(error), 'error');
+	}
+}
+
+async function refreshLogLevels() {
+	renderLogLevels(await invoke<LogLevels>('get_log_levels'));
+}
+
+function renderLogLevels(levels: LogLevels) {
+	if (!logLevelList) return;
+	clearElement(logLevelList);
+	const rows: [string, string][] = Object.entries(levels.targets);
+	rows.push(['Everything else', levels.default]);
+
+	for (const [target, level] of rows) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = level;
+		const name = document.createElement('span');
+		name.className = 'directory-path';
+		name.textContent = target;
+		text.append(chip, name);
+		item.appendChild(text);
+
+		if (target in levels.targets) {
+			const reset = document.createElement('button');
+			reset.type = 'button';
+			reset.className = 'button-ghost';
+			reset.textContent = 'Reset';
+			reset.setAttribute('aria-label', `Reset log level for ${target}`);
+			reset.addEventListener('click', () => setLogLevel(target, null));
+			item.appendChild(reset);
+		}
+		logLevelList.appendChild(item);
+	}
+}
+
+async function setLogLevel(target: string, level: string | null) {
+	try {
+		renderLogLevels(await invoke<LogLevels>('set_log_level', { target, level }));
+		showToast(level ? `Logging ${target} at ${level}` : `Logging ${target} at the default level`);
+	} catch (error: unknown) {
+		showToast(error instanceof Error ? error.message : String(error), 'error');
+	}
+}
+
+async function undoLast() {
+	undoButton?.setAttribute('disabled', 'true');
+	try {
+		const result = await invoke<{ ok: boolean; reason?: string }>('undo');
 		if (result.ok) {
 			showToast('Undo applied', 'info');
 		} else {
@@ -431,26 +2704,234 @@ async function undoLast() {
 	}
 }
 
+async function redo() {
+	redoButton?.setAttribute('disabled', 'true');
+	try {
+		const result = await invoke<{ ok: boolean; reason?: string }>('redo');
+		if (result.ok) {
+			showToast('Redo applied', 'info');
+		} else {
+			const reason = result.reason === 'empty' ? 'Nothing to redo' : result.reason;
+			showToast(reason ?? 'Redo failed', 'warn');
+		}
+	} catch (error: unknown) {
+		showToast(`Redo failed: ${error instanceof Error ? error.message : String(error)}`, 'error');
+	} finally {
+		if (redoButton) redoButton.disabled = !currentStatus?.canRedo;
+	}
+}
+
 async function addDirectory(directory: string) {
 	const trimmed = directory.trim();
 	if (!trimmed) {
 		showToast('Enter a directory path', 'warn');
 		return;
 	}
-	addDirectoryInput?.setAttribute('disabled', 'true');
+	addDirectoryInput?.setAttribute('disabled', 'true');
+	try {
+		await invoke('add_watch_dir', { directory: trimmed });
+		const segments = trimmed
+			.replace(/[/\\]+$/, '')
+			.split(/[/\\]/)
+			.filter(Boolean);
+		const baseName = segments.length ? segments[segments.length - 1] : trimmed;
+		showToast(`Added ${baseName}`, 'info');
+		if (addDirectoryInput) addDirectoryInput.value = '';
+	} catch (error: unknown) {
+		showToast(`Failed to add: ${error instanceof Error ? error.message : String(error)}`, 'error');
+	} finally {
+		addDirectoryInput?.removeAttribute('disabled');
+	}
+}
+
+// --- Command Palette ---
+
+async function openPalette() {
+	if (!commandPalette) return;
+	try {
+		paletteActions = await invoke<PaletteAction[]>('list_actions');
+	} catch (error: unknown) {
+		showToast(
+			`Commands unavailable: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		return;
+	}
+	if (commandPaletteInput) commandPaletteInput.value = '';
+	paletteSelection = 0;
+	renderPalette();
+	commandPalette.hidden = false;
+	commandPaletteInput?.focus();
+}
+
+function closePalette() {
+	if (commandPalette) commandPalette.hidden = true;
+}
+
+/** Actions whose title or section contains every word typed into the palette. */
+function matchingActions(): PaletteAction[] {
+	const words = (commandPaletteInput?.value ?? '').toLowerCase().split(/\s+/).filter(Boolean);
+	return paletteActions.filter((action) => {
+		const text = `${action.section} ${action.title}`.toLowerCase();
+		return words.every((word) => text.includes(word));
+	});
+}
+
+function renderPalette() {
+	if (!commandPaletteList) return;
+	clearElement(commandPaletteList);
+	const actions = matchingActions();
+	paletteSelection = Math.min(paletteSelection, Math.max(actions.length - 1, 0));
+	actions.forEach((action, index) => {
+		const item = document.createElement('li');
+		item.className = 'autocomplete-item';
+		item.id = `command-${index}`;
+		item.setAttribute('role', 'option');
+		item.setAttribute('aria-selected', String(index === paletteSelection));
+		item.classList.toggle('selected', index === paletteSelection);
+		const title = document.createElement('span');
+		title.className = 'var-name';
+		title.textContent = action.title;
+		const section = document.createElement('span');
+		section.className = 'var-desc';
+		section.textContent = action.shortcut
+			? `${action.section} · ${action.shortcut}`
+			: action.section;
+		item.append(title, section);
+		item.addEventListener('click', () => runPaletteAction(action));
+		commandPaletteList.appendChild(item);
+	});
+	const selected = commandPaletteList.querySelector('.selected');
+	selected?.scrollIntoView({ block: 'nearest' });
+	if (selected) commandPaletteInput?.setAttribute('aria-activedescendant', selected.id);
+	else commandPaletteInput?.removeAttribute('aria-activedescendant');
+}
+
+async function runPaletteAction(action: PaletteAction) {
+	closePalette();
+	try {
+		await invoke('invoke_action', { id: action.id });
+	} catch (error: unknown) {
+		showToast(
+			`${action.title} failed: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+}
+
+// --- Rename a Folder ---
+
+function openRenameFolder(directory?: string) {
+	if (!renameFolderModal) return;
+	if (renameFolderProfile) {
+		clearElement(renameFolderProfile);
+		renameFolderProfile.appendChild(new Option('All enabled profiles', ''));
+		for (const profile of currentProfiles) {
+			const label = profile.enabled ? profile.name : `${profile.name} (disabled)`;
+			renameFolderProfile.appendChild(new Option(label, profile.id));
+		}
+	}
+	resetFolderPlan();
+	renameFolderModal.hidden = false;
+	renameFolderInput?.focus();
+	if (directory && renameFolderInput) {
+		renameFolderInput.value = directory;
+		previewFolder();
+	}
+}
+
+function closeRenameFolder() {
+	if (renameFolderModal) renameFolderModal.hidden = true;
+}
+
+function resetFolderPlan() {
+	folderPlan = null;
+	if (renameFolderPlan) clearElement(renameFolderPlan);
+	if (renameFolderSummary) renameFolderSummary.textContent = 'Preview';
+	renameFolderApply?.setAttribute('disabled', 'true');
+}
+
+async function previewFolder() {
+	const directory = renameFolderInput?.value.trim() ?? '';
+	if (!directory) {
+		showToast('Enter a folder path', 'warn');
+		return;
+	}
+	const profile = renameFolderProfile?.value || null;
+	try {
+		renderFolderPlan(await invoke<RenamePreview>('preview_renames', { directory, profile }));
+	} catch (error: unknown) {
+		resetFolderPlan();
+		showToast(`Preview failed: ${error instanceof Error ? error.message : String(error)}`, 'error');
+	}
+}
+
+function renderFolderPlan(plan: RenamePreview) {
+	folderPlan = plan;
+	if (!renameFolderPlan) return;
+	clearElement(renameFolderPlan);
+	for (const entry of plan.entries) {
+		const row = document.createElement('li');
+		row.className = entry.conflict ? 'renamed conflict' : 'renamed';
+		const label = document.createElement('label');
+		const checkbox = document.createElement('input');
+		checkbox.type = 'checkbox';
+		checkbox.checked = true;
+		checkbox.value = entry.from;
+		checkbox.addEventListener('change', updateFolderPlanSelection);
+		const from = document.createElement('del');
+		from.textContent = entry.from;
+		const to = document.createElement('ins');
+		to.textContent = entry.to;
+		label.append(checkbox, from, ' → ', to);
+		if (entry.conflict) label.title = 'The name is taken, so this one gets a number';
+		row.appendChild(label);
+		renameFolderPlan.appendChild(row);
+	}
+	updateFolderPlanSelection();
+}
+
+/** The files ticked in the Rename a Folder preview. */
+function selectedFolderPlanFiles(): string[] {
+	const boxes = renameFolderPlan?.querySelectorAll<HTMLInputElement>('input:checked') ?? [];
+	return Array.from(boxes, (box) => box.value);
+}
+
+function updateFolderPlanSelection() {
+	if (!folderPlan) return;
+	const total = folderPlan.entries.length;
+	const selected = selectedFolderPlanFiles().length;
+	if (renameFolderSummary) {
+		const conflicts = folderPlan.entries.filter((entry) => entry.conflict).length;
+		renameFolderSummary.textContent =
+			total === 0
+				? `No files in ${folderPlan.directory} would be renamed`
+				: `${selected} of ${total + folderPlan.unchanged} files will be renamed` +
+					(conflicts > 0 ? `, ${conflicts} with a number added to a taken name` : '');
+	}
+	if (selected > 0) renameFolderApply?.removeAttribute('disabled');
+	else renameFolderApply?.setAttribute('disabled', 'true');
+}
+
+async function applyFolderPlan() {
+	if (!folderPlan) return;
+	renameFolderApply?.setAttribute('disabled', 'true');
 	try {
-		await invoke('add_watch_dir', { directory: trimmed });
-		const segments = trimmed
-			.replace(/[/\\]+$/, '')
-			.split(/[/\\]/)
-			.filter(Boolean);
-		const baseName = segments.length ? segments[segments.length - 1] : trimmed;
-		showToast(`Added ${baseName}`, 'info');
-		if (addDirectoryInput) addDirectoryInput.value = '';
+		const result = await invoke<RunOnceResult>('apply_plan', {
+			planId: folderPlan.planId,
+			selected: selectedFolderPlanFiles(),
+		});
+		const files = `${result.renamed} file${result.renamed === 1 ? '' : 's'}`;
+		if (result.failed > 0) {
+			showToast(`Renamed ${files}; ${result.failed} failed (${result.error})`, 'warn');
+		} else {
+			showToast(`Renamed ${files}`, 'info');
+		}
+		closeRenameFolder();
 	} catch (error: unknown) {
-		showToast(`Failed to add: ${error instanceof Error ? error.message : String(error)}`, 'error');
-	} finally {
-		addDirectoryInput?.removeAttribute('disabled');
+		// The preview is used up either way, so take a fresh one
+		resetFolderPlan();
+		showToast(`Rename failed: ${error instanceof Error ? error.message : String(error)}`, 'error');
 	}
 }
 
@@ -475,7 +2956,7 @@ function renderProfiles(profiles: Profile[]) {
 		return;
 	}
 
-	for (const profile of profiles) {
+	for (const [index, profile] of profiles.entries()) {
 		const item = document.createElement('li');
 		item.className = 'profile-item';
 		item.dataset.enabled = String(profile.enabled);
@@ -498,10 +2979,16 @@ function renderProfiles(profiles: Profile[]) {
 		const rule = document.createElement('span');
 		rule.className = 'profile-rule';
 
-		if (profile.action && profile.action !== 'rename') {
+		const tags = [
+			profile.action && profile.action !== 'rename' ? profile.action : null,
+			profile.caseStyle ?? null,
+			profile.datePrefix ? 'dated' : null,
+		];
+		for (const tag of tags) {
+			if (!tag) continue;
 			const actionTag = document.createElement('span');
 			actionTag.className = 'action-tag';
-			actionTag.textContent = profile.action;
+			actionTag.textContent = tag;
 			rule.appendChild(actionTag);
 		}
 
@@ -526,7 +3013,15 @@ function renderProfiles(profiles: Profile[]) {
 		editBtn.textContent = 'Edit';
 		editBtn.addEventListener('click', () => openProfileModal(profile));
 
-		actions.appendChild(editBtn);
+		const upBtn = document.createElement('button');
+		upBtn.type = 'button';
+		upBtn.className = 'button-ghost';
+		upBtn.textContent = '\u2191';
+		upBtn.title = 'Try this rule earlier';
+		upBtn.disabled = index === 0;
+		upBtn.addEventListener('click', () => moveRuleEarlier(profile.id));
+
+		actions.append(upBtn, editBtn);
 		item.append(checkbox, info, actions);
 		profilesList.appendChild(item);
 	}
@@ -545,8 +3040,14 @@ function openProfileModal(profile?: Profile) {
 	if (profileIsRegexInput) profileIsRegexInput.checked = profile?.isRegex ?? false;
 	if (profileTemplateInput) profileTemplateInput.value = profile?.template ?? '<prefix>_<datetime>';
 	if (profilePrefixInput) profilePrefixInput.value = profile?.prefix ?? '';
-	if (profilePriorityInput) profilePriorityInput.value = String(profile?.priority ?? 1);
+	const nextPriority = Math.max(0, ...currentProfiles.map((p) => p.priority)) + 1;
+	if (profilePriorityInput) profilePriorityInput.value = String(profile?.priority ?? nextPriority);
 	if (profileActionSelect) profileActionSelect.value = profile?.action ?? 'rename';
+	if (profileFiscalStartSelect) profileFiscalStartSelect.value = String(profile?.fiscalYearStartMonth ?? 1);
+	if (profileTimeZoneInput) profileTimeZoneInput.value = profile?.timeZone ?? '';
+	if (profileCaseStyleSelect) profileCaseStyleSelect.value = profile?.caseStyle ?? '';
+	if (profileDatePrefixInput) profileDatePrefixInput.checked = profile?.datePrefix ?? false;
+	if (profileConditionInput) profileConditionInput.value = profile?.condition ?? '';
 
 	updatePreview();
 	profileModal.hidden = false;
@@ -556,15 +3057,111 @@ function closeProfileModal() {
 	if (profileModal) profileModal.hidden = true;
 }
 
+// Sample screenshot time used by the profile preview.
+const SAMPLE_TIME = new Date(2024, 11, 26, 21, 30, 0);
+
+function isValidTimeZone(timeZone: string): boolean {
+	try {
+		new Intl.DateTimeFormat('en-US', { timeZone });
+		return true;
+	} catch {
+		return false;
+	}
+}
+
+/**
+ * Shift `d` so its local fields read as the wall clock in `timeZone`,
+ * mirroring how the service renders date variables.
+ */
+function inTimeZone(d: Date, timeZone: string): Date {
+	if (!timeZone || !isValidTimeZone(timeZone)) return d;
+	const parts = new Intl.DateTimeFormat('en-US', {
+		timeZone,
+		hourCycle: 'h23',
+		year: 'numeric',
+		month: 'numeric',
+		day: 'numeric',
+		hour: 'numeric',
+		minute: 'numeric',
+		second: 'numeric',
+	}).formatToParts(d);
+	const field = (type: Intl.DateTimeFormatPartTypes) =>
+		Number(parts.find((part) => part.type === type)?.value ?? 0);
+	return new Date(
+		field('year'),
+		field('month') - 1,
+		field('day'),
+		field('hour'),
+		field('minute'),
+		field('second'),
+	);
+}
+
+/**
+ * strftime-style directive values for `d`, matching the service's <date:FORMAT>.
+ */
+function dateDirectives(d: Date): Record<string, string> {
+	const two = (n: number) => String(n).padStart(2, '0');
+	const weekday = d.getDay() || 7;
+	const thursday = new Date(d.getFullYear(), d.getMonth(), d.getDate() + 4 - weekday);
+	const daysSince = (from: Date, to: Date) => Math.round((to.getTime() - from.getTime()) / 86_400_000);
+	const isoWeek =
+		Math.floor(daysSince(new Date(thursday.getFullYear(), 0, 1), thursday) / 7) + 1;
+	const midnight = new Date(d.getFullYear(), d.getMonth(), d.getDate());
+	return {
+		Y: String(d.getFullYear()),
+		y: two(d.getFullYear() % 100),
+		m: two(d.getMonth() + 1),
+		d: two(d.getDate()),
+		H: two(d.getHours()),
+		M: two(d.getMinutes()),
+		S: two(d.getSeconds()),
+		j: String(daysSince(new Date(d.getFullYear(), 0, 1), midnight) + 1).padStart(3, '0'),
+		G: String(thursday.getFullYear()),
+		V: two(isoWeek),
+		u: String(weekday),
+		'%': '%',
+	};
+}
+
+function formatDirectives(format: string, values: Record<string, string>): string {
+	return format.replace(/%([A-Za-z%])/g, (match, directive: string) => values[directive] ?? match);
+}
+
+function fillTimeZoneOptions() {
+	if (!timeZoneOptions) return;
+	// Older WebViews lack supportedValuesOf; free text still works there.
+	const zones = typeof Intl.supportedValuesOf === 'function' ? Intl.supportedValuesOf('timeZone') : [];
+	for (const zone of ['UTC', ...zones]) {
+		const option = document.createElement('option');
+		option.value = zone;
+		timeZoneOptions.append(option);
+	}
+}
+
 function updatePreview() {
 	const prefix = profilePrefixInput?.value ?? '';
 	const template = profileTemplateInput?.value || '<prefix>_<datetime>';
 	const action = (profileActionSelect?.value as Profile['action']) ?? 'rename';
+	const timeZone = profileTimeZoneInput?.value.trim() ?? '';
+	const caseStyle = (profileCaseStyleSelect?.value || undefined) as CaseStyle | undefined;
 
 	const isConvertOnly = action === 'convert';
+	const when = dateDirectives(inTimeZone(SAMPLE_TIME, timeZone));
+	const month = Number(when.m);
+	const fiscalStart = Number.parseInt(profileFiscalStartSelect?.value ?? '1', 10) || 1;
+	// Fiscal years are named for the calendar year they end in.
+	const sampleFiscal = {
+		year: fiscalStart === 1 || month < fiscalStart ? Number(when.Y) : Number(when.Y) + 1,
+		quarter: Math.floor(((month - fiscalStart + 12) % 12) / 3) + 1,
+	};
+	const date = `${when.Y}-${when.m}-${when.d}`;
+	const time = `${when.H}-${when.M}-${when.S}`;
 
 	// Use HEIC sample for convert actions to show format change
 	const sampleOriginal = isConvertOnly ? 'IMG_1234.heic' : 'Screenshot 2024-12-26 at 21.30.00.png';
+	const pattern = profilePatternInput?.value ?? '';
+	const matches = profileIsRegexInput?.checked ? sampleMatches(pattern) : null;
 
 	let result: string;
 	if (isConvertOnly) {
@@ -573,29 +3170,102 @@ function updatePreview() {
 	} else {
 		result = template
 			.replace(/<prefix>/g, prefix.replace(/\s+/g, '_'))
-			.replace(/<date>/g, '2024-12-26')
-			.replace(/<time>/g, '21-30-00')
-			.replace(/<datetime>/g, '2024-12-26_21-30-00')
+			.replace(/<date>/g, date)
+			.replace(/<time>/g, time)
+			.replace(/<datetime>/g, `${date}_${time}`)
 			.replace(/<original>/g, 'Screenshot 2024-12-26 at 21.30.00')
 			.replace(/<ext>/g, action === 'rename+convert' ? '.jpeg' : '.png')
 			.replace(/<counter>/g, '001')
-			.replace(/<year>/g, '2024')
-			.replace(/<month>/g, '12')
-			.replace(/<day>/g, '26')
-			.replace(/<hour>/g, '21')
-			.replace(/<minute>/g, '30')
-			.replace(/<second>/g, '00');
-
-		// Add extension if template doesn't use <ext>
-		if (!template.includes('<ext>')) {
-			result += action === 'rename+convert' ? '.jpeg' : '.png';
+			.replace(/<year>/g, when.Y)
+			.replace(/<month>/g, when.m)
+			.replace(/<day>/g, when.d)
+			.replace(/<hour>/g, when.H)
+			.replace(/<minute>/g, when.M)
+			.replace(/<second>/g, when.S)
+			.replace(/<week>/g, when.V)
+			.replace(/<fiscal_year>/g, String(sampleFiscal.year))
+			.replace(/<fiscal_quarter>/g, `Q${sampleFiscal.quarter}`)
+			.replace(/<vendor>/g, 'Acme Supplies')
+			.replace(/<amount>/g, '1234.50')
+			.replace(/<currency>/g, 'USD')
+			.replace(/<doc_date>/g, date)
+			.replace(/<doc_year>/g, when.Y)
+			.replace(/<doc_month>/g, when.m)
+			.replace(/<album_artist>|<artist>/g, 'Björk')
+			.replace(/<album>/g, 'Debut')
+			.replace(/<title>/g, 'Human Behaviour')
+			.replace(/<track>/g, '01')
+			.replace(/<disc>/g, '1')
+			.replace(/<window_title>/g, 'Xcode — MainView.swift')
+			.replace(/<date:([^<>]+)>/g, (_, format: string) => formatDirectives(format, when))
+			.replace(/<match:(\w+)>/g, (_, group: string) => matches?.[group] ?? '');
+
+		// Case and date prefix leave the extension alone
+		const ext = action === 'rename+convert' ? '.jpeg' : '.png';
+		const usesExt = template.includes('<ext>');
+		const stem = usesExt && result.endsWith(ext) ? result.slice(0, -ext.length) : result;
+		const parts = stem
+			.split('/')
+			.map((part) => (caseStyle ? applyCaseStyle(part, caseStyle) : part));
+		const last = parts.pop() ?? '';
+		if (profileDatePrefixInput?.checked && !last.startsWith(date)) {
+			parts.push(`${date}${caseStyle === 'kebab' ? '-' : '_'}${last}`);
+		} else {
+			parts.push(last);
 		}
+		result = parts.join('/') + (usesExt && !result.endsWith(ext) ? '' : ext);
 	}
 
 	if (previewOriginal) {
 		previewOriginal.textContent = isConvertOnly ? 'IMG_1234.heic' : sampleOriginal;
 	}
 	if (previewResult) previewResult.textContent = result;
+	if (previewTimeZone) {
+		const overridden = timeZone !== '' && !isConvertOnly;
+		previewTimeZone.hidden = !overridden;
+		if (overridden) {
+			previewTimeZone.textContent = isValidTimeZone(timeZone)
+				? `Local 2024-12-26 21:30 is ${date} ${when.H}:${when.M} in ${timeZone}`
+				: `Unknown time zone "${timeZone}"`;
+		}
+	}
+}
+
+/** Groups the rule's regex captures from the sample name, for <match:…> in the preview. */
+function sampleMatches(pattern: string): Record<string, string> | null {
+	let match: RegExpExecArray | null;
+	try {
+		match = new RegExp(pattern).exec('Screenshot 2024-12-26 at 21.30.00.png');
+	} catch {
+		return null;
+	}
+	if (!match) return null;
+	const groups: Record<string, string> = { ...match.groups };
+	match.forEach((value, i) => {
+		groups[String(i)] = value ?? '';
+	});
+	return groups;
+}
+
+/** Mirrors the service's case styles, for the preview. */
+function applyCaseStyle(name: string, style: CaseStyle): string {
+	switch (style) {
+		case 'lower':
+			return name.toLowerCase();
+		case 'upper':
+			return name.toUpperCase();
+		case 'title':
+			return name
+				.toLowerCase()
+				.replace(
+					/(^|[^\p{L}\p{N}'])(\p{L})/gu,
+					(_, before: string, letter: string) => before + letter.toUpperCase(),
+				);
+		default: {
+			const words = name.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter(Boolean);
+			return words.length ? words.join(style === 'kebab' ? '-' : '_') : name;
+		}
+	}
 }
 
 async function saveProfile() {
@@ -607,11 +3277,20 @@ async function saveProfile() {
 	const prefix = profilePrefixInput?.value.trim();
 	const priority = Number.parseInt(profilePriorityInput?.value ?? '1', 10);
 	const action = (profileActionSelect?.value as Profile['action']) ?? 'rename';
+	const fiscalStart = Number.parseInt(profileFiscalStartSelect?.value ?? '1', 10);
+	const timeZone = profileTimeZoneInput?.value.trim() ?? '';
+	const caseStyle = (profileCaseStyleSelect?.value || undefined) as CaseStyle | undefined;
+	const datePrefix = profileDatePrefixInput?.checked ?? false;
+	const condition = profileConditionInput?.value.trim() ?? '';
 
 	if (!name || !pattern || !template) {
 		showToast('Please fill in all required fields', 'warn');
 		return;
 	}
+	if (timeZone && !isValidTimeZone(timeZone)) {
+		showToast(`Unknown time zone "${timeZone}"`, 'warn');
+		return;
+	}
 
 	const profile: Profile = {
 		id,
@@ -623,6 +3302,11 @@ async function saveProfile() {
 		prefix,
 		priority: Number.isNaN(priority) ? 1 : priority,
 		action: action !== 'rename' ? action : undefined,
+		fiscalYearStartMonth: fiscalStart > 1 ? fiscalStart : undefined,
+		timeZone: timeZone || undefined,
+		caseStyle,
+		datePrefix: datePrefix || undefined,
+		condition: condition || undefined,
 	};
 
 	// Preserve enabled state if editing
@@ -632,7 +3316,8 @@ async function saveProfile() {
 	}
 
 	try {
-		const profiles = await invoke<Profile[]>('set_profile', { profile });
+		const command = existing ? 'update_rule' : 'add_rule';
+		const profiles = await invoke<Profile[]>(command, { rule: profile });
 		renderProfiles(profiles);
 		closeProfileModal();
 		showToast(`Profile "${name}" saved`, 'info');
@@ -649,7 +3334,7 @@ async function deleteCurrentProfile() {
 	const name = profile?.name ?? 'Profile';
 
 	try {
-		const profiles = await invoke<Profile[]>('delete_profile', { id });
+		const profiles = await invoke<Profile[]>('remove_rule', { id });
 		renderProfiles(profiles);
 		closeProfileModal();
 		showToast(`Deleted "${name}"`, 'info');
@@ -674,9 +3359,25 @@ async function toggleProfileEnabled(id: string, enabled: boolean) {
 	}
 }
 
+/** Swap a rule with the one tried just before it. */
+async function moveRuleEarlier(id: string) {
+	const ids = currentProfiles.map((p) => p.id);
+	const index = ids.indexOf(id);
+	if (index < 1) return;
+	[ids[index - 1], ids[index]] = [ids[index], ids[index - 1]];
+	try {
+		renderProfiles(await invoke<Profile[]>('reorder_rules', { orderedIds: ids }));
+	} catch (error: unknown) {
+		showToast(
+			`Failed to reorder: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+	}
+}
+
 async function refreshProfiles() {
 	try {
-		const profiles = await invoke<Profile[]>('get_profiles');
+		const profiles = await invoke<Profile[]>('list_rules');
 		renderProfiles(profiles);
 	} catch (error: unknown) {
 		showToast(
@@ -692,11 +3393,158 @@ function wireUI() {
 		const target = event.currentTarget as HTMLInputElement;
 		setDryRun(target.checked);
 	});
+	observerToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setObserverMode(target.checked);
+	});
 	launchToggle?.addEventListener('change', (event) => {
 		const target = event.currentTarget as HTMLInputElement;
 		setLaunchOnLogin(target.checked);
 	});
+	dataDirForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		moveDataDir(dataDirInput?.value.trim() || null);
+	});
+	syncDirForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		setSyncDir(syncDirInput?.value.trim() || null);
+	});
+	syncKeyForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		const key = syncKeyInput?.value.trim();
+		if (key) importSyncKey(key);
+	});
+	syncKeyShowButton?.addEventListener('click', () => {
+		showSyncKey();
+	});
+	lanPairingToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setLanPairing(target.checked);
+	});
+	pairingShowButton?.addEventListener('click', () => {
+		showPairingCode();
+	});
+	pairingForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		findPeers();
+	});
+	statusPageToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setStatusPage(target.checked);
+	});
+	statusPageForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		setStatusPage(statusPageToggle?.checked ?? false);
+	});
+	eventSearchForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		scheduleEventRefresh();
+	});
+	eventSearchInput?.addEventListener('input', scheduleEventRefresh);
+	eventKindSelect?.addEventListener('change', scheduleEventRefresh);
+	clearEventsButton?.addEventListener('click', () => {
+		clearEvents();
+	});
+	httpSecurityForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		setHttpSecurity();
+	});
+	apiTokenForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		createApiToken();
+	});
+	pushKindSelect?.addEventListener('change', (event) => {
+		renderPushKind((event.currentTarget as HTMLSelectElement).value);
+	});
+	pushForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		savePushEndpoint();
+	});
+	emailDigestForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		saveEmailDigest();
+	});
+	emailDigestSendButton?.addEventListener('click', () => {
+		sendEmailDigest();
+	});
+	webhookForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		saveWebhook();
+	});
+	historyFileForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		const file = historyFileInput?.value.trim();
+		if (file) mergeHistory(file);
+	});
+	historyExportButton?.addEventListener('click', () => {
+		const file = historyFileInput?.value.trim();
+		if (file) exportHistory(file);
+	});
+	configFileForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		const file = configFileInput?.value.trim();
+		if (file) importConfig(file);
+	});
+	configExportButton?.addEventListener('click', () => {
+		const file = configFileInput?.value.trim();
+		if (file) exportConfig(file);
+	});
+	logPrivacyToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setLogPrivacy(target.checked);
+	});
+	telemetryToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setTelemetryEnabled(target.checked);
+	});
+	highContrastToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		updateAccessibility({ highContrastIcon: target.checked });
+	});
+	reducedMotionToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		updateAccessibility({ reducedMotion: target.checked });
+	});
+	minimalNotificationsToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		updateAccessibility({ minimalNotifications: target.checked });
+	});
+	systemNotificationsToggle?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLInputElement;
+		setSystemNotifications(target.checked);
+	});
+	entryClickAction?.addEventListener('change', (event) => {
+		const target = event.currentTarget as HTMLSelectElement;
+		setEntryClickAction(target.value as EntryClickAction);
+	});
 	undoButton?.addEventListener('click', undoLast);
+	redoButton?.addEventListener('click', redo);
+	sortPreviewClose?.addEventListener('click', () => {
+		if (sortPreview) sortPreview.hidden = true;
+	});
+	sortDirectoriesButton?.addEventListener('click', async () => {
+		try {
+			await invoke('reorder_directories', {});
+		} catch (error: unknown) {
+			showToast(
+				`Failed to sort directories: ${error instanceof Error ? error.message : String(error)}`,
+				'error',
+			);
+		}
+	});
+	startSessionForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		if (sessionNameInput) startSession(sessionNameInput.value);
+	});
+	endSessionButton?.addEventListener('click', endSession);
+	exportCalendarButton?.addEventListener('click', exportSessionsCalendar);
+	queuePauseButton?.addEventListener('click', () =>
+		updateQueue('set_queue_paused', { paused: !(currentQueue?.paused ?? false) }),
+	);
+	importPresetForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		addImportPreset();
+	});
 	addDirectoryForm?.addEventListener('submit', (event) => {
 		event.preventDefault();
 		if (addDirectoryInput) {
@@ -704,6 +3552,86 @@ function wireUI() {
 		}
 	});
 
+	// Command palette and shortcut wiring
+	document.addEventListener('keydown', (event) => {
+		if ((event.metaKey || event.ctrlKey) && event.key.toLowerCase() === 'k') {
+			event.preventDefault();
+			if (commandPalette?.hidden) openPalette();
+			else closePalette();
+			return;
+		}
+		if (event.target === shortcutKeys) return;
+		const accelerator = acceleratorFor(event);
+		const action = Object.keys(shortcuts).find((id) => shortcuts[id] === accelerator);
+		if (action) {
+			event.preventDefault();
+			invoke('invoke_action', { id: action }).catch((error: unknown) => {
+				showToast(error instanceof Error ? error.message : String(error), 'error');
+			});
+		}
+	});
+	shortcutAction?.addEventListener('change', showShortcut);
+	shortcutKeys?.addEventListener('keydown', (event) => {
+		if (event.key === 'Tab' && !event.metaKey && !event.ctrlKey && !event.altKey) return;
+		event.preventDefault();
+		const input = event.currentTarget as HTMLInputElement;
+		const plain = !event.metaKey && !event.ctrlKey && !event.altKey && !event.shiftKey;
+		if (plain && (event.key === 'Backspace' || event.key === 'Delete')) {
+			input.value = '';
+			return;
+		}
+		const accelerator = acceleratorFor(event);
+		if (accelerator) input.value = accelerator;
+	});
+	logLevelForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		const target = logTarget?.value.trim();
+		if (!target || !logLevel) return;
+		setLogLevel(target, logLevel.value).then(() => {
+			if (logTarget) logTarget.value = '';
+		});
+	});
+	shortcutForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		saveShortcut();
+	});
+	commandPaletteInput?.addEventListener('input', () => {
+		paletteSelection = 0;
+		renderPalette();
+	});
+	commandPaletteInput?.addEventListener('keydown', (event) => {
+		const count = matchingActions().length;
+		if (event.key === 'ArrowDown' && count > 0) {
+			event.preventDefault();
+			paletteSelection = (paletteSelection + 1) % count;
+			renderPalette();
+		} else if (event.key === 'ArrowUp' && count > 0) {
+			event.preventDefault();
+			paletteSelection = (paletteSelection - 1 + count) % count;
+			renderPalette();
+		} else if (event.key === 'Enter') {
+			event.preventDefault();
+			const action = matchingActions()[paletteSelection];
+			if (action) runPaletteAction(action);
+		} else if (event.key === 'Escape') {
+			event.preventDefault();
+			closePalette();
+		}
+	});
+	commandPalette?.querySelector('.modal-backdrop')?.addEventListener('click', closePalette);
+
+	// Rename a Folder wiring
+	renameFolderForm?.addEventListener('submit', (event) => {
+		event.preventDefault();
+		previewFolder();
+	});
+	renameFolderInput?.addEventListener('input', resetFolderPlan);
+	renameFolderProfile?.addEventListener('change', resetFolderPlan);
+	renameFolderApply?.addEventListener('click', applyFolderPlan);
+	renameFolderClose?.addEventListener('click', closeRenameFolder);
+	renameFolderCancel?.addEventListener('click', closeRenameFolder);
+	renameFolderModal?.querySelector('.modal-backdrop')?.addEventListener('click', closeRenameFolder);
+
 	// Profile UI wiring
 	addProfileBtn?.addEventListener('click', () => openProfileModal());
 	modalCloseBtn?.addEventListener('click', closeProfileModal);
@@ -761,17 +3689,243 @@ function wireUI() {
 
 	profilePrefixInput?.addEventListener('input', updatePreview);
 	profileActionSelect?.addEventListener('change', updatePreview);
+	profileFiscalStartSelect?.addEventListener('change', updatePreview);
+	profileTimeZoneInput?.addEventListener('input', updatePreview);
+	profileCaseStyleSelect?.addEventListener('change', updatePreview);
+	profileDatePrefixInput?.addEventListener('change', updatePreview);
+	profilePatternInput?.addEventListener('input', updatePreview);
+	profileIsRegexInput?.addEventListener('change', updatePreview);
+	fillTimeZoneOptions();
 
 	// Close modal on backdrop click
 	profileModal?.querySelector('.modal-backdrop')?.addEventListener('click', closeProfileModal);
 }
 
+async function refreshIssues() {
+	renderIssues(await invoke<Issue[]>('list_issues'));
+}
+
+function renderIssues(issues: Issue[]) {
+	if (!errorCenter || !issuesList) return;
+	clearElement(issuesList);
+	errorCenter.hidden = issues.length === 0;
+
+	for (const issue of issues) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = issue.count > 1 ? `${issue.category} ×${issue.count}` : issue.category;
+		const message = document.createElement('span');
+		message.className = 'directory-path';
+		message.textContent = issue.message;
+		text.append(chip, message);
+		item.append(text);
+
+		for (const remedy of issue.remedies) {
+			const button = document.createElement('button');
+			button.type = 'button';
+			button.className = 'button-ghost';
+			button.textContent = REMEDY_LABELS[remedy] ?? remedy;
+			button.addEventListener('click', () => {
+				if (remedy === 'relocate') {
+					button.disabled = true;
+					text.append(relocateForm(issue));
+					return;
+				}
+				resolveIssue(issue, remedy, button);
+			});
+			item.append(button);
+		}
+		issuesList.appendChild(item);
+	}
+}
+
+function relocateForm(issue: Issue): HTMLFormElement {
+	const form = document.createElement('form');
+	form.className = 'add-directory-form';
+	const input = document.createElement('input');
+	input.type = 'text';
+	input.placeholder = 'Watch this folder instead';
+	const save = document.createElement('button');
+	save.type = 'submit';
+	save.className = 'button-secondary';
+	save.textContent = 'Relocate';
+	form.append(input, save);
+	form.addEventListener('submit', (event) => {
+		event.preventDefault();
+		resolveIssue(issue, 'relocate', save, input.value.trim());
+	});
+	return form;
+}
+
+async function resolveIssue(
+	issue: Issue,
+	remedy: IssueRemedy,
+	button: HTMLButtonElement,
+	target?: string,
+) {
+	button.disabled = true;
+	try {
+		renderIssues(await invoke<Issue[]>('resolve_issue', { id: issue.id, remedy, target }));
+	} catch (error: unknown) {
+		showToast(
+			`Failed to fix issue: ${error instanceof Error ? error.message : String(error)}`,
+			'error',
+		);
+		button.disabled = false;
+	}
+}
+
+function recordConflict(event: FileEventPayload) {
+	// Observed collisions go to the audit log instead
+	if (currentStatus?.observerMode) return;
+	if (!event.collidedWith || !event.target) return;
+	conflicts.unshift(event);
+	conflicts.splice(MAX_CONFLICTS);
+	renderConflicts();
+}
+
+function renderConflicts() {
+	if (!conflictsCard || !conflictList) return;
+	clearElement(conflictList);
+	conflictsCard.hidden = conflicts.length === 0;
+
+	for (const conflict of conflicts) {
+		const item = document.createElement('li');
+		item.className = 'directory-item';
+		const text = document.createElement('div');
+		text.className = 'directory-text';
+		const chip = document.createElement('span');
+		chip.className = 'directory-chip';
+		chip.textContent = conflict.target ?? conflict.file;
+		const label = document.createElement('span');
+		label.className = 'directory-path';
+		const verb = conflict.kind === 'preview' ? 'would rename' : 'renamed';
+		label.textContent = `${conflict.collidedWith} was taken · ${verb} ${conflict.file}`;
+		text.append(chip, label);
+
+		const compare = document.createElement('button');
+		compare.type = 'button';
+		compare.className = 'button-ghost';
+		compare.textContent = 'Compare';
+		compare.addEventListener('click', async () => {
+			compare.disabled = true;
+			try {
+				// Previews haven't moved the file yet, so it is still under its original name.
+				const incoming = conflict.kind === 'preview' ? conflict.file : conflict.target;
+				const comparison = await invoke<ConflictComparison>('compare_conflict', {
+					directory: conflict.directory,
+					incoming,
+					existing: conflict.collidedWith,
+				});
+				await renderConflictDiff(comparison);
+			} catch (error: unknown) {
+				showToast(
+					`Failed to compare: ${error instanceof Error ? error.message : String(error)}`,
+					'error',
+				);
+			} finally {
+				compare.disabled = false;
+			}
+		});
+
+		item.append(text, compare);
+		conflictList.appendChild(item);
+	}
+}
+
+async function renderConflictDiff(comparison: ConflictComparison) {
+	if (!conflictDiff || !conflictIncoming || !conflictExisting) return;
+	const sides: [HTMLDivElement, FileFacts, string][] = [
+		[conflictIncoming, comparison.incoming, 'New file'],
+		[conflictExisting, comparison.existing, 'Already there'],
+	];
+	for (const [column, facts, heading] of sides) {
+		clearElement(column);
+		const label = document.createElement('div');
+		label.className = 'preview-label';
+		label.textContent = heading;
+		column.append(label);
+
+		try {
+			const img = document.createElement('img');
+			img.className = 'conflict-thumb';
+			img.alt = facts.name;
+			img.src = await invoke<string>('get_thumbnail', { path: facts.path, size: 320 });
+			column.append(img);
+		} catch {
+			// Facts alone still tell the files apart
+		}
+
+		const details = document.createElement('p');
+		details.className = 'conflict-facts';
+		const when = (ms: number | null) => (ms === null ? 'unknown' : new Date(ms).toLocaleString());
+		details.textContent = [
+			facts.name,
+			`${facts.size.toLocaleString()} bytes`,
+			`created ${when(facts.created)}`,
+			`modified ${when(facts.modified)}`,
+			`sha256 ${facts.sha256.slice(0, 12)}…`,
+		].join(' · ');
+		column.append(details);
+	}
+	if (comparison.identical) {
+		showToast('The two files have identical contents', 'info');
+	}
+	conflictDiff.hidden = false;
+}
+
 async function bootstrap() {
 	wireUI();
 
 	await listen<ServiceStatus>('service://status', (event) => {
 		renderStatus(event.payload);
 		toggleButton?.removeAttribute('disabled');
+		refreshIssues().catch((error) => console.error('Failed to load issues', error));
+	});
+
+	await listen<FileEventPayload>('service://file', (event) => {
+		recordConflict(event.payload);
+		if (currentStatus?.observerMode) {
+			refreshAudit().catch((error) => console.error('Failed to load audit log', error));
+		}
+		scheduleEventRefresh();
+		scheduleQueueRefresh();
+	});
+
+	await listen('service://undo', () => {
+		scheduleEventRefresh();
+	});
+
+	await listen<ImportProgress>('service://import', (event) => {
+		renderImportProgress(event.payload);
+	});
+
+	await listen<Issue[]>('ui://error-center', (event) => {
+		activateTab('overview');
+		renderIssues(event.payload);
+	});
+
+	await listen<{ tab: string; focus: string | null }>('ui://navigate', (event) => {
+		tabButtons.find((button) => button.dataset.tabTarget === event.payload.tab)?.click();
+		if (event.payload.focus) document.getElementById(event.payload.focus)?.focus();
+	});
+
+	await listen<Record<string, string>>('ui://shortcuts', (event) => {
+		shortcuts = event.payload;
+		showShortcut();
+	});
+
+	await listen<AccessibilitySettings>('ui://accessibility', (event) => {
+		renderAccessibility(event.payload);
+	});
+
+	await listen('ui://rename-folder', () => {
+		openRenameFolder();
 	});
 
 	await listen<ToastPayload>('service://toast', (event) => {
@@ -779,9 +3933,18 @@ async function bootstrap() {
 		showToast(payload.message, payload.level);
 	});
 
+	// The service was restarted after exiting; anything shown may be out of date
+	await listen<ServiceStatus>('service://bridge-restarted', (event) => {
+		renderStatus(event.payload);
+		refreshProfiles();
+		refreshIssues().catch((error) => console.error('Failed to load issues', error));
+		showToast('Background service restarted', 'info');
+	});
+
 	// Listen for config changes to refresh profiles
 	await listen('service://config', () => {
 		refreshProfiles();
+		refreshShortcuts().catch((error) => console.error('Failed to load shortcuts', error));
 	});
 
 	// Load initial data
@@ -794,6 +3957,73 @@ async function bootstrap() {
 	refreshProfiles().catch((error) => {
 		console.error('Failed to load profiles', error);
 	});
+
+	refreshTelemetry().catch((error) => {
+		console.error('Failed to load telemetry settings', error);
+	});
+
+	refreshEntryClickAction().catch((error) => {
+		console.error('Failed to load click action', error);
+	});
+
+	refreshShortcuts().catch((error) => {
+		console.error('Failed to load shortcuts', error);
+	});
+
+	refreshAccessibility().catch((error) => {
+		console.error('Failed to load accessibility settings', error);
+	});
+
+	refreshSystemNotifications().catch((error) => {
+		console.error('Failed to load notification settings', error);
+	});
+
+	refreshEvents().catch((error) => {
+		console.error('Failed to load event history', error);
+	});
+	refreshStatusPage().catch((error) => {
+		console.error('Failed to load status page settings', error);
+	});
+
+	refreshApiTokens().catch((error) => {
+		console.error('Failed to load API tokens', error);
+	});
+
+	refreshPushEndpoint().catch((error) => {
+		console.error('Failed to load push alert settings', error);
+	});
+
+	refreshEmailDigest().catch((error) => {
+		console.error('Failed to load email digest settings', error);
+	});
+
+	refreshWebhook().catch((error) => {
+		console.error('Failed to load webhook settings', error);
+	});
+
+	refreshLogLevels().catch((error) => {
+		console.error('Failed to load log levels', error);
+	});
+
+	refreshIssues().catch((error) => {
+		console.error('Failed to load issues', error);
+	});
+
+	refreshUsage().catch((error) => {
+		console.error('Failed to load usage', error);
+	});
+
+	refreshSessions().catch((error) => {
+		console.error('Failed to load sessions', error);
+	});
+
+	refreshQueue().catch((error) => {
+		console.error('Failed to load queue', error);
+	});
+
+	refreshImportPresets().catch((error) => {
+		console.error('Failed to load import presets', error);
+	});
 }
 
 bootstrap().catch((error) => {
@@ -74,6 +74,7 @@ class StubWatcher implements IWatchService {
 		this.disposed = true;
 	});
 	handler: ((event: WatchEvent) => void) | null = null;
+	recursive = false;
 	stopped = false;
 	disposed = false;
 
@@ -150,8 +151,9 @@ describe('NamefixService', () => {
 			logger: noopLogger,
 			converter: mockConverter as unknown as ConversionService,
 			trasher: mockTrasher as unknown as TrashService,
-			watcherFactory: (dir) => {
+			watcherFactory: (dir, _fsSafe, _backend, recursive) => {
 				const watcher = new StubWatcher();
+				watcher.recursive = recursive;
 				watchers.set(dir, watcher);
 				createdDirs.push(dir);
 				return watcher;
@@ -260,6 +262,326 @@ describe('NamefixService', () => {
 		}
 	});
 
+	it('reports per-directory activity in status', async () => {
+		const service = createService();
+		await service.init();
+		await service.start();
+
+		const dir = Array.from(watchers.keys())[0];
+		const watcher = dir ? watchers.get(dir) : undefined;
+		if (!dir || !watcher) {
+			throw new Error('Expected watcher to exist');
+		}
+		const initial = service.getStatus().directoryStats.find((s) => s.directory === dir);
+		expect(initial).toMatchObject({ lastEventAt: null, renamesToday: 0, errorCount: 0 });
+
+		watcher.trigger({
+			path: path.join(dir, 'Screenshot 2025-10-30 at 09.00.00.png'),
+			birthtimeMs: Date.now(),
+			mtimeMs: Date.now(),
+			size: 10,
+		});
+		await new Promise((resolve) => setTimeout(resolve, 50));
+
+		const stats = service.getStatus().directoryStats.find((s) => s.directory === dir);
+		expect(stats?.lastEventAt).not.toBeNull();
+	});
+
+	it('tracks priority directories in status', async () => {
+		const service = createService();
+		await service.init();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) {
+			throw new Error('Expected watch directory to be defined');
+		}
+
+		await service.setDirectoryPriority(dirA, true);
+		expect(service.getStatus().priorityDirectories).toEqual([dirA]);
+
+		await service.setDirectoryPriority(dirA, false);
+		expect(service.getStatus().priorityDirectories).toEqual([]);
+	});
+
+	it('lists pinned directories first and honours explicit order', async () => {
+		const service = createService();
+		await service.init();
+		const [dirA, dirB] = service.getStatus().directories;
+		if (!dirA || !dirB) {
+			throw new Error('Expected watch directories to be defined');
+		}
+
+		await service.reorderDirectories([dirB]);
+		expect(service.getStatus().directories).toEqual([dirB, dirA]);
+
+		await service.setDirectoryPinned(dirA, true);
+		expect(service.getStatus().directories).toEqual([dirA, dirB]);
+		expect(service.getStatus().pinnedDirectories).toEqual([dirA]);
+	});
+
+	it('turns log privacy on by default and passes changes to the logger', async () => {
+		const setPrivacy = vi.fn();
+		const service = new NamefixService({
+			configStore,
+			logger: { ...noopLogger, setPrivacy },
+			watcherFactory: () => new StubWatcher(),
+		});
+		await service.init();
+		expect(service.getStatus().logPrivacy).toBe(true);
+		expect(setPrivacy).toHaveBeenLastCalledWith(true);
+
+		await service.setLogPrivacy(false);
+		expect(service.getStatus().logPrivacy).toBe(false);
+		expect(setPrivacy).toHaveBeenLastCalledWith(false);
+	});
+
+	it('previews renames in directories with per-directory dry run', async () => {
+		await configStore.set({ dryRun: false });
+		const service = createService();
+		await service.init();
+		await service.start();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) throw new Error('Expected watch directory to be defined');
+		const watcher = watchers.get(dirA);
+		if (!watcher) throw new Error('watcher missing');
+
+		await service.setDirectoryDryRun(dirA, true);
+		expect(service.getStatus().dryRunDirectories).toEqual([dirA]);
+
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+		watcher.trigger({
+			path: path.join(dirA, 'Screenshot 2025-10-30 at 09.00.00.png'),
+			birthtimeMs: Date.now(),
+			mtimeMs: Date.now(),
+			size: 10,
+		});
+
+		await new Promise((resolve) => setTimeout(resolve, 25));
+		expect(events[0]?.kind).toBe('preview');
+
+		await service.setDirectoryDryRun(dirA, false);
+		expect(service.getStatus().dryRunDirectories).toEqual([]);
+	});
+
+	it('applies per-directory extension filters and restarts watchers for subfolders', async () => {
+		const service = createService();
+		await service.init();
+		await service.start();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) throw new Error('Expected watch directory to be defined');
+		const watcher = watchers.get(dirA);
+		if (!watcher) throw new Error('watcher missing');
+
+		const config = await service.setDirConfig(dirA, { excludeExtensions: ['.PNG', 'png'] });
+		expect(config).toEqual({
+			directory: dirA,
+			recursive: false,
+			includeExtensions: [],
+			excludeExtensions: ['png'],
+			dryRun: false,
+		});
+		expect(service.getStatus().directoryConfigs).toContainEqual(config);
+
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+		const shot = (ext: string) => ({
+			path: path.join(dirA, `Screenshot 2025-10-30 at 09.00.00.${ext}`),
+			birthtimeMs: Date.now(),
+			mtimeMs: Date.now(),
+			size: 10,
+		});
+		watcher.trigger(shot('png'));
+		watcher.trigger(shot('jpg'));
+		await new Promise((resolve) => setTimeout(resolve, 25));
+		expect(events.map((event) => event.file)).toEqual(['Screenshot 2025-10-30 at 09.00.00.jpg']);
+
+		await service.setDirConfig(dirA, { recursive: true });
+		expect(watcher.stopped).toBe(true);
+		expect(watchers.get(dirA)?.recursive).toBe(true);
+		expect(service.getDirConfig(dirA).excludeExtensions).toEqual(['png']);
+	});
+
+	it('skips events from snoozed directories until the snooze lifts', async () => {
+		const service = createService();
+		await service.init();
+		await service.start();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) throw new Error('Expected watch directory to be defined');
+		const watcher = watchers.get(dirA);
+		if (!watcher) throw new Error('watcher missing');
+
+		const until = Date.now() + 60_000;
+		service.snoozeDirectory(dirA, until);
+		expect(service.getStatus().snoozedDirectories).toEqual([{ directory: dirA, until }]);
+
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+		watcher.trigger({
+			path: path.join(dirA, 'Screenshot 2025-10-30 at 09.00.00.png'),
+			birthtimeMs: Date.now(),
+			mtimeMs: Date.now(),
+			size: 10,
+		});
+		expect(events).toHaveLength(1);
+		expect(events[0]).toMatchObject({ kind: 'skipped', message: 'snoozed' });
+
+		service.snoozeDirectory(dirA, Date.now() - 1);
+		expect(service.getStatus().snoozedDirectories).toEqual([]);
+	});
+
+	it('restarts watchers and catches up on files that arrived during sleep', async () => {
+		const service = createService();
+		await service.init();
+		await service.start();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) throw new Error('Expected watch directory to be defined');
+		const stale = watchers.get(dirA);
+
+		const since = Date.now() - 1_000;
+		await fs.writeFile(path.join(dirA, 'Screenshot 2025-10-30 at 09.00.00.png'), 'a');
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+
+		const result = await service.recoverFromSleep(since);
+		expect(result).toEqual({ restarted: 2, caughtUp: 1 });
+		expect(stale?.stop).toHaveBeenCalled();
+		expect(watchers.get(dirA)).not.toBe(stale);
+
+		await new Promise((resolve) => setTimeout(resolve, 25));
+		expect(events).toHaveLength(1);
+		expect(events[0]).toMatchObject({ kind: 'preview', directory: dirA });
+	});
+
+	it('ignores events inside excluded folders', async () => {
+		const service = createService();
+		await service.init();
+		await service.start();
+		const [dirA] = service.getStatus().directories;
+		if (!dirA) throw new Error('Expected watch directory to be defined');
+		const watcher = watchers.get(dirA);
+		if (!watcher) throw new Error('watcher missing');
+
+		service.setWatchExclusions(['node_modules']);
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+		watcher.trigger({
+			path: path.join(dirA, 'node_modules', 'Screenshot 2025-10-30 at 09.00.00.png'),
+			birthtimeMs: Date.now(),
+			mtimeMs: Date.now(),
+			size: 10,
+		});
+
+		await new Promise((resolve) => setTimeout(resolve, 25));
+		expect(events).toHaveLength(0);
+	});
+
+	it('advertises engine capabilities in status', async () => {
+		const service = createService();
+		await service.init();
+		const { capabilities } = service.getStatus();
+		expect(capabilities).toContain('supports_preview');
+		expect(capabilities).toContain('supports_rules');
+		expect(capabilities).not.toContain('native_engine');
+	});
+
+	it('plans renames for a directory without touching files', async () => {
+		const service = createService();
+		await service.init();
+
+		const [dir] = (await configStore.get()).watchDirs;
+		if (!dir) throw new Error('dir missing');
+		await fs.writeFile(path.join(dir, 'Screenshot 1.png'), 'a');
+		await fs.writeFile(path.join(dir, 'Screenshot 2.png'), 'b');
+		await fs.writeFile(path.join(dir, 'notes.txt'), 'c');
+
+		const plan = await service.planDirectory(dir);
+		const byFile = new Map(plan.entries.map((entry) => [entry.file, entry]));
+
+		expect(byFile.get('notes.txt')?.target).toBeNull();
+		const targets = ['Screenshot 1.png', 'Screenshot 2.png'].map((f) => byFile.get(f)?.target);
+		for (const target of targets) {
+			expect(target).toMatch(/^Screenshot_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}(?:_\d+)?\.png$/);
+		}
+		expect(new Set(targets).size).toBe(2);
+		expect(byFile.get('Screenshot 1.png')?.rule).toBe('screenshots');
+		const remaining = (await fs.readdir(dir)).sort();
+		expect(remaining).toEqual(['Screenshot 1.png', 'Screenshot 2.png', 'notes.txt']);
+	});
+
+	it('proposes names for bare filenames and paths', async () => {
+		const service = createService();
+		await service.init();
+
+		const [dir] = (await configStore.get()).watchDirs;
+		if (!dir) throw new Error('dir missing');
+		const file = path.join(dir, 'Screenshot 1.png');
+		await fs.writeFile(file, 'a');
+
+		const pattern = /^Screenshot_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}\.png$/;
+		expect(await service.proposeName('Screenshot 2024-02-04 at 10.15.32.png')).toMatch(pattern);
+		const proposed = await service.proposeName(file);
+		expect(path.dirname(proposed ?? '')).toBe(dir);
+		expect(path.basename(proposed ?? '')).toMatch(pattern);
+		expect(await service.proposeName('notes.txt')).toBeNull();
+		expect(await fs.readdir(dir)).toEqual(['Screenshot 1.png']);
+	});
+
+	it('renames a folder once with a chosen profile', async () => {
+		const service = createService();
+		await service.init();
+
+		const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-once-'));
+		await fs.writeFile(path.join(dir, 'Screenshot 1.png'), 'a');
+		await fs.writeFile(path.join(dir, 'notes.txt'), 'b');
+		const events: ServiceFileEvent[] = [];
+		service.on('file', (event) => events.push(event));
+
+		await expect(service.runOnce(dir, 'missing')).rejects.toThrow('Unknown profile');
+		const result = await service.runOnce(dir, 'screenshots');
+
+		expect(result).toMatchObject({ directory: dir, renamed: 1, failed: 0 });
+		const files = (await fs.readdir(dir)).sort();
+		expect(files[0]).toMatch(/^Screenshot_\d{4}-\d{2}-\d{2}_\d{2}-\d{2}-\d{2}\.png$/);
+		expect(files[1]).toBe('notes.txt');
+		expect(events.map((e) => e.kind)).toEqual(['applied']);
+		await fs.rm(dir, { recursive: true, force: true });
+	});
+
+	it('applies just the chosen entries of a plan', async () => {
+		const service = createService();
+		await service.init();
+
+		const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-plan-'));
+		await fs.writeFile(path.join(dir, 'Screenshot 1.png'), 'a');
+		await fs.writeFile(path.join(dir, 'Screenshot 2.png'), 'b');
+
+		const plan = await service.planDirectory(dir, 'screenshots');
+		const chosen = plan.entries.filter((entry) => entry.file === 'Screenshot 2.png');
+		const escape = { file: 'Screenshot 1.png', target: '../escaped.png', createdAt: 0 };
+		const result = await service.applyPlan({ directory: dir, entries: [...chosen, escape] });
+
+		expect(result).toMatchObject({ renamed: 1, failed: 1 });
+		expect(result.error).toContain('not a rename within the folder');
+		const files = (await fs.readdir(dir)).sort();
+		expect(files).toEqual(['Screenshot 1.png', chosen[0]?.target].sort());
+		await fs.rm(dir, { recursive: true, force: true });
+	});
+
+	it('stops a one-off rename once its signal is aborted', async () => {
+		const service = createService();
+		await service.init();
+
+		const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-once-'));
+		await fs.writeFile(path.join(dir, 'Screenshot 1.png'), 'a');
+		const controller = new AbortController();
+		controller.abort();
+
+		const result = await service.runOnce(dir, 'screenshots', controller.signal);
+		expect(result).toMatchObject({ renamed: 0, failed: 0 });
+		expect(await fs.readdir(dir)).toEqual(['Screenshot 1.png']);
+		await fs.rm(dir, { recursive: true, force: true });
+	});
+
 	it('profile with action: rename (or missing) triggers rename only', async () => {
 		const service = createService();
 		await service.init();
@@ -1,26 +1,94 @@
 import path from 'node:path';
+import { randomUUID } from 'node:crypto';
 import os from 'node:os';
 import fs from 'node:fs/promises';
+import { fileURLToPath, pathToFileURL } from 'node:url';
 import type {
+	DirectoryOptions,
+	DirectoryUsage,
+	HistoryEntry,
+	HistoryHealth,
+	HistoryMerge,
+	HistoryQuery,
+	HistoryRepair,
+	HotFolder,
 	IConfig,
 	IConfigStore,
+	ImportPreset,
+	ImportProgress,
 	IWatchService,
 	ILogger,
 	IProfile,
+	JournalDetails,
+	NearbyPeer,
+	OriginRule,
+	PairedPeer,
+	PairingCode,
+	PeerStatus,
+	PruneResult,
+	RenamePlan,
+	RenamePlanEntry,
+	RenameSession,
+	RetentionPolicy,
+	RunOnceResult,
+	SessionSummary,
+	SessionUndoResult,
+	SettingsSyncStatus,
+	SyncResolution,
+	WakeRecovery,
+	WatchBackend,
+	WatchEvent,
 	WatchServiceErrorHandler,
 } from '../types/index.js';
-import { ConfigStore } from './config/ConfigStore.js';
+import { ConfigStore, normalizeExtensions } from './config/ConfigStore.js';
+import { SettingsSync } from './config/SettingsSync.js';
+import { PairingService } from './pairing/PairingService.js';
 import { Logger } from './log/Logger.js';
 import { EventBus } from './events/EventBus.js';
-import { RenameService } from './rename/RenameService.js';
+import { RenameService, type NameHook } from './rename/RenameService.js';
+import { buildName, claimName, usesWindowTitle } from './rename/NameTemplate.js';
 import { Matcher, ProfileMatcher } from './rename/Matcher.js';
-import { FsSafe } from './fs/FsSafe.js';
+import { FsSafe, isLockedError } from './fs/FsSafe.js';
+import { OpenFileProbe } from './fs/OpenFileProbe.js';
+import { FileOriginProbe, isOriginAllowed, originFromExecutable } from './fs/FileOrigin.js';
+import { FrontWindowProbe } from './fs/FrontWindowProbe.js';
 import { WatchService } from './fs/WatchService.js';
+import { EndpointSecurityWatchService } from './fs/EndpointSecurityWatchService.js';
+import { PollingWatchService } from './fs/PollingWatchService.js';
+import { isNetworkFilesystem } from './fs/NetworkFs.js';
+import { detectSharing } from './fs/SharedDirectory.js';
+import { copyDataFiles, finishMove } from './fs/DataDir.js';
+import { isMounted, volumeName, volumeRoot } from './fs/Volume.js';
+import { findCameraCards, runImport } from './import/ImportWorkflow.js';
+import { moveInto, runHandOffCommand } from './handoff/HandOff.js';
+import {
+	USN_JOURNAL_ENTRY_THRESHOLD,
+	UsnJournalWatchService,
+	hasAtLeastEntries,
+} from './fs/UsnJournalWatchService.js';
+import { BurstDetector } from './fs/BurstDetector.js';
+import { runBenchmark, type BenchmarkOptions, type BenchmarkResult } from './bench/Benchmark.js';
 import { JournalStore } from './journal/JournalStore.js';
+import { AuditLog } from './audit/AuditLog.js';
 import { ConversionService } from './convert/ConversionService.js';
 import { TrashService } from './convert/TrashService.js';
-import type { ServiceEventMap, ServiceStatus } from '../types/service.js';
+import { WorkQueue, type QueueSnapshot } from './queue/WorkQueue.js';
+import {
+	SERVICE_STATUS_SCHEMA_VERSION,
+	type DirectoryConfig,
+	type DirectoryOriginRule,
+	type DirectoryStats,
+	type ServiceCapability,
+	type ServiceEventMap,
+	type ServiceFileEvent,
+	type ServiceStatus,
+	type SharedDirectory,
+	type SnoozedDirectory,
+	type WatchedVolume,
+} from '../types/service.js';
 import { TypedEmitter } from '../utils/TypedEmitter.js';
+import { dataLocations, logsDir, setDataDir } from '../utils/paths.js';
+import { userId } from '../utils/user.js';
 
 /**
  * Shared orchestrator responsible for configuration, directory watching, and rename lifecycles.
@@ -36,23 +104,65 @@ export class NamefixService {
 	private converter: ConversionService;
 	private trasher: TrashService;
 	private fsSafe: FsSafe;
+	private openFiles: OpenFileProbe;
+	private origins: FileOriginProbe;
+	private windows: FrontWindowProbe;
 	private journal: JournalStore;
+	private sync: SettingsSync;
+	/** The sync state last reported, so each conflict or failure is announced once. */
+	private syncState: SettingsSyncStatus['state'] = 'off';
+	private pairing: PairingService;
+	private audit = new AuditLog();
 	/** @deprecated Legacy matcher for backwards compatibility */
 	private matcher: Matcher | null = null;
 	/** Profile-based matcher for new config format */
 	private profileMatcher: ProfileMatcher | null = null;
 	private watchers = new Map<string, IWatchService>();
 	private watcherErrorUnsubscribers = new Map<string, () => void>();
+	private watchModes = new Map<string, WatchBackend>();
+	private sharedDirs = new Map<string, SharedDirectory>();
 	private running = false;
 	private config: IConfig | null = null;
 	private unsubscribeConfig: (() => void) | null = null;
 	private watcherLock: Promise<void> = Promise.resolve();
-	private createWatcher: (dir: string, fsSafe: FsSafe) => IWatchService;
+	private createWatcher: (
+		dir: string,
+		fsSafe: FsSafe,
+		backend: WatchBackend,
+		recursive: boolean,
+	) => IWatchService;
+	private queue: WorkQueue;
+	private directoryStats = new Map<string, DirectoryStats & { day: string }>();
+	private bursts = new BurstDetector();
+	/** Folder names whose contents are never processed; pushed by the host app. */
+	private watchExclusions = new Set<string>();
+	private benchmarkResults: BenchmarkResult[] = [];
+	private static readonly MAX_BENCHMARK_RESULTS = 10;
+	private snoozes = new Map<string, { until: number; timer: ReturnType<typeof setTimeout> }>();
+	/** Waits between checks while a file is held open elsewhere; the rename fails after the last. */
+	private static readonly OPEN_FILE_RETRY_DELAYS_MS = [1_000, 3_000, 10_000, 30_000];
 
 	// Health monitoring
 	private healthCheckInterval: ReturnType<typeof setInterval> | null = null;
 	private static readonly HEALTH_CHECK_INTERVAL_MS = 30_000; // 30 seconds
 	private static readonly MAX_RESTART_ATTEMPTS = 3;
+	private historyPruneInterval: ReturnType<typeof setInterval> | null = null;
+	private static readonly HISTORY_PRUNE_INTERVAL_MS = 6 * 60 * 60 * 1000; // 6 hours
+	private volumeMonitorInterval: ReturnType<typeof setInterval> | null = null;
+	private static readonly VOLUME_CHECK_INTERVAL_MS = 5_000;
+	/** Mount state of each volume holding a watched directory, as last checked. */
+	private mountedVolumes = new Map<string, boolean>();
+	/** Volumes whose watchers were stopped so they can be ejected. */
+	private releasedVolumes = new Set<string>();
+	/** Camera cards mounted at the last check; null until the first one. */
+	private knownCards: Set<string> | null = null;
+	/** `<preset>:<card>` of imports in progress. */
+	private importing = new Set<string>();
+	private static readonly CAPABILITIES: ServiceCapability[] = [
+		'supports_preview',
+		'supports_rules',
+		'supports_queue',
+	];
 	private watcherRestartAttempts = new Map<string, number>();
 
 	constructor(
@@ -64,7 +174,15 @@ export class NamefixService {
 			renamer?: RenameService;
 			converter?: ConversionService;
 			trasher?: TrashService;
-			watcherFactory?: (dir: string, fsSafe: FsSafe) => IWatchService;
+			openFiles?: OpenFileProbe;
+			origins?: FileOriginProbe;
+			windows?: FrontWindowProbe;
+			watcherFactory?: (
+				dir: string,
+				fsSafe: FsSafe,
+				backend: WatchBackend,
+				recursive: boolean,
+			) => IWatchService;
 		} = {},
 	) {
 		this.configStore = deps.configStore ?? new ConfigStore();
@@ -74,8 +192,47 @@ export class NamefixService {
 		this.renamer = deps.renamer ?? new RenameService();
 		this.converter = deps.converter ?? new ConversionService();
 		this.trasher = deps.trasher ?? new TrashService();
-		this.journal = new JournalStore(this.fsSafe);
-		this.createWatcher = deps.watcherFactory ?? ((dir, fsSafe) => new WatchService(dir, fsSafe));
+		this.openFiles = deps.openFiles ?? new OpenFileProbe();
+		this.origins = deps.origins ?? new FileOriginProbe();
+		this.windows = deps.windows ?? new FrontWindowProbe();
+		this.journal = new JournalStore(this.fsSafe, (health) => this.onHistoryHealth(health));
+		this.sync = new SettingsSync(this.configStore, this.logger, (status) =>
+			this.onSyncStatus(status),
+		);
+		this.pairing = new PairingService(
+			{ export: () => this.sync.exportKey(), import: (code) => this.sync.importKey(code) },
+			() => {
+				const cfg = this.getConfig();
+				return {
+					running: this.running,
+					directories: this.getListedDirs(cfg),
+					dryRun: cfg.dryRun,
+					observerMode: cfg.observerMode ?? false,
+				};
+			},
+			this.logger,
+			() => this.emitStatus(),
+		);
+		this.createWatcher =
+			deps.watcherFactory ??
+			((dir, fsSafe, backend, recursive) => {
+				if (backend === 'endpoint-security') {
+					return new EndpointSecurityWatchService(dir, fsSafe, recursive);
+				}
+				if (backend === 'usn-journal') return new UsnJournalWatchService(dir, fsSafe);
+				if (backend === 'polling') {
+					const interval = this.getConfig().pollIntervalMs;
+					return new PollingWatchService(dir, fsSafe, interval, recursive);
+				}
+				return new WatchService(dir, fsSafe, recursive);
+			});
+		this.queue = new WorkQueue(
+			4,
+			(err) => {
+				this.logger.error(err instanceof Error ? err : String(err));
+			},
+			() => this.emitStatus(),
+		);
 	}
 
 	async init(overrides?: Partial<IConfig>): Promise<IConfig> {
@@ -84,8 +241,12 @@ export class NamefixService {
 			cfg = await this.configStore.set({ ...overrides });
 		}
 		this.applyConfig(cfg);
+		await this.journal.check();
 		this.unsubscribeConfig = this.configStore.onChange((next) => {
 			this.applyConfig(next);
+			this.sync.localChanged().catch(() => {
+				/* reported through the sync status */
+			});
 			if (this.running) {
 				this.syncWatchers().catch((err) => {
 					this.logger.error(err instanceof Error ? err : String(err));
@@ -103,6 +264,8 @@ export class NamefixService {
 		this.watcherRestartAttempts.clear();
 		await this.syncWatchers();
 		this.startHealthMonitor();
+		this.startHistoryPruning();
+		this.startVolumeMonitor();
 		this.emitStatus();
 	}
 
@@ -110,6 +273,8 @@ export class NamefixService {
 		if (!this.running) return;
 		this.running = false;
 		this.stopHealthMonitor();
+		this.stopHistoryPruning();
+		this.stopVolumeMonitor();
 		await this.syncWatchers();
 		this.emitStatus();
 	}
@@ -159,6 +324,32 @@ export class NamefixService {
 		return await this.configStore.set({ profiles });
 	}
 
+	async setImportPreset(preset: ImportPreset): Promise<IConfig> {
+		const presets = (this.getConfig().importPresets ?? []).filter((p) => p.id !== preset.id);
+		return await this.configStore.set({ importPresets: [...presets, preset] });
+	}
+
+	async deleteImportPreset(id: string): Promise<IConfig> {
+		const importPresets = (this.getConfig().importPresets ?? []).filter((p) => p.id !== id);
+		return await this.configStore.set({ importPresets });
+	}
+
+	/**
+	 * Run a preset now against every mounted camera card, whether or not it is enabled. Aborting
+	 * `signal` stops before the next card.
+	 */
+	async runImportPreset(id: string, signal?: AbortSignal): Promise<ImportProgress[]> {
+		const preset = this.getConfig().importPresets?.find((p) => p.id === id);
+		if (!preset) throw new Error(`No import preset ${id}`);
+		const results: ImportProgress[] = [];
+		for (const card of await findCameraCards()) {
+			signal?.throwIfAborted();
+			const result = await this.importCard(card, preset);
+			if (result) results.push(result);
+		}
+		return results;
+	}
+
 	/**
 	 * Delete a profile by ID.
 	 */
@@ -230,6 +421,28 @@ export class NamefixService {
 		return await this.configStore.set({ watchDir: resolved });
 	}
 
+	/**
+	 * Move the listed directories to the front, in the given order. Directories left out keep
+	 * their relative order after them, and unknown entries are ignored.
+	 */
+	async reorderDirectories(order: string[]): Promise<IConfig> {
+		const cfg = this.getConfig();
+		const current = this.getWatchDirs(cfg);
+		const front = this.normalizeDirs(order).filter((dir) => current.includes(dir));
+		const watchDirs = [...front, ...current.filter((dir) => !front.includes(dir))];
+		return await this.configStore.set({ watchDirs });
+	}
+
+	/**
+	 * Pin a directory so it is listed ahead of unpinned ones, or unpin it.
+	 */
+	async setDirectoryPinned(dir: string, pinned: boolean): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const current = (this.getConfig().pinnedDirs ?? []).filter((d) => d !== resolved);
+		return await this.configStore.set({ pinnedDirs: pinned ? [...current, resolved] : current });
+	}
+
 	async setWatchDirs(dirs: string[]): Promise<IConfig> {
 		const normalized = this.normalizeDirs(dirs);
 		if (!normalized.length) {
@@ -245,24 +458,846 @@ export class NamefixService {
 
 	getStatus(): ServiceStatus {
 		const cfg = this.getConfig();
+		const queued = this.queue.countByDirectory();
 		return {
+			schemaVersion: SERVICE_STATUS_SCHEMA_VERSION,
 			running: this.running,
-			directories: this.getWatchDirs(cfg),
+			directories: this.getListedDirs(cfg),
 			dryRun: cfg.dryRun,
+			observerMode: cfg.observerMode ?? false,
+			logPrivacy: cfg.logPrivacy ?? true,
+			history: this.journal.getHealth(),
+			canRedo: this.journal.redoCount() > 0,
+			dataDir: cfg.dataDir ?? null,
+			settingsSync: this.sync.getStatus(),
+			pairing: this.pairing.getStatus(),
 			launchOnLogin: cfg.launchOnLogin,
+			priorityDirectories: cfg.priorityDirs ?? [],
+			pinnedDirectories: cfg.pinnedDirs ?? [],
+			dryRunDirectories: cfg.dryRunDirs ?? [],
+			snoozedDirectories: this.getSnoozedDirectories(),
+			watchBackend: cfg.watchBackend ?? 'fsevents',
+			originRules: this.getOriginRules(cfg),
+			directoryGroups: Object.entries(cfg.directoryGroups ?? {}).map(([directory, group]) => ({
+				directory,
+				group,
+			})),
+			hotFolders: Object.entries(cfg.hotFolders ?? {}).map(([directory, hotFolder]) => ({
+				directory,
+				...hotFolder,
+			})),
+			directoryConfigs: this.getListedDirs(cfg).map((dir) => this.dirConfigOf(cfg, dir)),
+			sharedDirectories: this.getWatchDirs(cfg).flatMap((dir) => this.sharedDirs.get(dir) ?? []),
+			volumes: this.getVolumes(cfg),
+			activeSession: activeSession(cfg),
+			directoryStats: this.getWatchDirs(cfg).map((directory) =>
+				this.statsFor(directory, queued),
+			),
+			capabilities: [...NamefixService.CAPABILITIES],
+		};
+	}
+
+	/**
+	 * Mark or unmark a directory as high priority for the work queue.
+	 */
+	async setDirectoryPriority(dir: string, high: boolean): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const current = (this.getConfig().priorityDirs ?? []).filter((d) => d !== resolved);
+		const priorityDirs = high ? [...current, resolved] : current;
+		return await this.configStore.set({ priorityDirs });
+	}
+
+	/**
+	 * Enable or disable dry run for a single directory. Has no visible effect while the global
+	 * dry-run flag is on.
+	 */
+	async setDirectoryDryRun(dir: string, enabled: boolean): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const current = (this.getConfig().dryRunDirs ?? []).filter((d) => d !== resolved);
+		const dryRunDirs = enabled ? [...current, resolved] : current;
+		return await this.configStore.set({ dryRunDirs });
+	}
+
+	/** Recursion, extension filters and dry run for one directory. */
+	getDirConfig(dir: string): DirectoryConfig {
+		return this.dirConfigOf(this.getConfig(), this.normalizePath(dir));
+	}
+
+	/**
+	 * Change how one directory is watched; fields left out keep their value. Turning `recursive`
+	 * on or off restarts the directory's watcher. `dryRun` is the same setting as
+	 * {@link setDirectoryDryRun}.
+	 */
+	async setDirConfig(
+		dir: string,
+		patch: Partial<Omit<DirectoryConfig, 'directory'>>,
+	): Promise<DirectoryConfig> {
+		const resolved = this.normalizePath(dir);
+		const cfg = this.getConfig();
+		const current = this.dirConfigOf(cfg, resolved);
+		const next: DirectoryConfig = {
+			...current,
+			...(patch.recursive !== undefined ? { recursive: patch.recursive } : {}),
+			...(patch.includeExtensions
+				? { includeExtensions: normalizeExtensions(patch.includeExtensions) }
+				: {}),
+			...(patch.excludeExtensions
+				? { excludeExtensions: normalizeExtensions(patch.excludeExtensions) }
+				: {}),
+			...(patch.dryRun !== undefined ? { dryRun: patch.dryRun } : {}),
+		};
+		const directoryOptions = { ...(cfg.directoryOptions ?? {}) };
+		const options: DirectoryOptions = {
+			...(next.recursive ? { recursive: true } : {}),
+			...(next.includeExtensions.length > 0 ? { includeExtensions: next.includeExtensions } : {}),
+			...(next.excludeExtensions.length > 0 ? { excludeExtensions: next.excludeExtensions } : {}),
+		};
+		if (Object.keys(options).length > 0) directoryOptions[resolved] = options;
+		else delete directoryOptions[resolved];
+		const dryRunDirs = (cfg.dryRunDirs ?? []).filter((d) => d !== resolved);
+		if (next.dryRun) dryRunDirs.push(resolved);
+		await this.configStore.set({ directoryOptions, dryRunDirs });
+
+		const watcher = this.watchers.get(resolved);
+		if (watcher && next.recursive !== current.recursive) {
+			await this.withWatcherLock(() => this.stopWatcher(resolved, watcher));
+			await this.syncWatchers();
+		}
+		return next;
+	}
+
+	private dirConfigOf(cfg: IConfig, directory: string): DirectoryConfig {
+		const options = cfg.directoryOptions?.[directory];
+		return {
+			directory,
+			recursive: options?.recursive ?? false,
+			includeExtensions: options?.includeExtensions ?? [],
+			excludeExtensions: options?.excludeExtensions ?? [],
+			dryRun: (cfg.dryRunDirs ?? []).includes(directory),
 		};
 	}
 
+	/**
+	 * Switch how directories are watched, restarting running watchers on the new backend.
+	 * `pollIntervalMs` also updates the polling backend's interval when given.
+	 */
+	async setWatchBackend(backend: WatchBackend, pollIntervalMs?: number): Promise<IConfig> {
+		const patch: Partial<IConfig> = { watchBackend: backend };
+		if (pollIntervalMs !== undefined) patch.pollIntervalMs = pollIntervalMs;
+		const cfg = await this.configStore.set(patch);
+		await this.withWatcherLock(async () => {
+			await Promise.allSettled(
+				[...this.watchers].map(([dir, watcher]) => this.stopWatcher(dir, watcher)),
+			);
+		});
+		await this.syncWatchers();
+		return cfg;
+	}
+
+	/**
+	 * Restrict which apps' files a directory renames. A rule with empty lists removes it.
+	 */
+	async setDirectoryOriginRule(dir: string, rule: OriginRule): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const originRules = { ...(this.getConfig().originRules ?? {}) };
+		const allow = rule.allow?.filter((app) => app.trim().length > 0) ?? [];
+		const deny = rule.deny?.filter((app) => app.trim().length > 0) ?? [];
+		if (allow.length === 0 && deny.length === 0) {
+			delete originRules[resolved];
+		} else {
+			originRules[resolved] = { allow, deny };
+		}
+		return await this.configStore.set({ originRules });
+	}
+
+	/**
+	 * File a directory under a named group, or take it out of its group with an empty name.
+	 */
+	async setDirectoryGroup(dir: string, group: string | null): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const directoryGroups = { ...(this.getConfig().directoryGroups ?? {}) };
+		const name = group?.trim() ?? '';
+		if (name.length === 0) {
+			delete directoryGroups[resolved];
+		} else {
+			directoryGroups[resolved] = name;
+		}
+		return await this.configStore.set({ directoryGroups });
+	}
+
+	/**
+	 * Make a directory a hot folder that hands each file off once renamed, or an ordinary one again
+	 * with null or an empty hand-off.
+	 */
+	async setDirectoryHotFolder(dir: string, hotFolder: HotFolder | null): Promise<IConfig> {
+		if (!dir || dir.trim().length === 0) return this.getConfig();
+		const resolved = this.normalizePath(dir);
+		const hotFolders = { ...(this.getConfig().hotFolders ?? {}) };
+		const destination = hotFolder?.destination?.trim();
+		const command = hotFolder?.command?.filter((arg) => arg.length > 0) ?? [];
+		if (!destination && command.length === 0) {
+			delete hotFolders[resolved];
+		} else {
+			hotFolders[resolved] = {
+				...(destination ? { destination: this.normalizePath(destination) } : {}),
+				...(command.length > 0 ? { command } : {}),
+			};
+		}
+		return await this.configStore.set({ hotFolders });
+	}
+
+	private getOriginRules(cfg: IConfig): DirectoryOriginRule[] {
+		return Object.entries(cfg.originRules ?? {}).map(([directory, rule]) => ({
+			directory,
+			...rule,
+		}));
+	}
+
+	setWatchExclusions(names: string[]): string[] {
+		this.watchExclusions = new Set(names.filter((name) => name.trim().length > 0));
+		return [...this.watchExclusions];
+	}
+
+	/** Let `hook` take part in naming files, as NameHook describes; `null` removes it. */
+	setNameHook(hook: NameHook | null): void {
+		this.renamer.setNameHook(hook, (id) => this.getConfig().profiles?.find((p) => p.id === id));
+	}
+
+	/**
+	 * Ignore events from a directory until `until` (epoch ms). Passing a time in the past lifts
+	 * the snooze early.
+	 */
+	snoozeDirectory(dir: string, until: number): void {
+		if (!dir || dir.trim().length === 0) return;
+		const resolved = this.normalizePath(dir);
+		const existing = this.snoozes.get(resolved);
+		if (existing) clearTimeout(existing.timer);
+		this.snoozes.delete(resolved);
+
+		const remaining = until - Date.now();
+		if (remaining > 0) {
+			const timer = setTimeout(() => {
+				this.snoozes.delete(resolved);
+				this.emitStatus();
+			}, remaining);
+			timer.unref?.();
+			this.snoozes.set(resolved, { until, timer });
+		}
+		this.emitStatus();
+	}
+
+	getSnoozedDirectories(): SnoozedDirectory[] {
+		return [...this.snoozes].map(([directory, { until }]) => ({ directory, until }));
+	}
+
 	async undoLast(): Promise<{ ok: boolean; reason?: string }> {
 		const res = await this.journal.undo();
 		if (res.ok) {
 			this.emit('toast', { level: 'info', message: 'Undo applied' });
+			this.emit('undo', { scope: 'last', undone: 1, failed: 0, timestamp: Date.now() });
 		} else {
 			this.emit('toast', { level: 'error', message: res.reason || 'Undo failed' });
 		}
+		this.emitStatus();
 		return res;
 	}
 
+	/** Undo one rename from history by its id, leaving the renames after it in place. */
+	async undoEntry(id: string): Promise<{ ok: boolean; reason?: string }> {
+		const res = await this.journal.undoEntry(id);
+		if (res.ok) {
+			this.emit('toast', { level: 'info', message: 'Undo applied' });
+			this.emit('undo', { scope: 'entry', undone: 1, failed: 0, timestamp: Date.now() });
+		} else {
+			this.emit('toast', { level: 'error', message: res.reason || 'Undo failed' });
+		}
+		this.emitStatus();
+		return res;
+	}
+
+	/** Redo the most recent undo of this run. */
+	async redo(): Promise<{ ok: boolean; reason?: string }> {
+		const res = await this.journal.redo();
+		if (res.ok) {
+			this.emit('toast', { level: 'info', message: 'Redo applied' });
+		} else {
+			const message = res.reason === 'empty' ? 'Nothing to redo' : res.reason || 'Redo failed';
+			this.emit('toast', { level: 'error', message });
+		}
+		this.emitStatus();
+		return res;
+	}
+
+	/** Renames newest first, a page at a time. */
+	async getHistory(limit: number, offset = 0): Promise<HistoryEntry[]> {
+		return await this.journal.search({ limit, offset });
+	}
+
+	async searchHistory(query: HistoryQuery): Promise<HistoryEntry[]> {
+		return await this.journal.search(query);
+	}
+
+	/**
+	 * Turn observer mode on or off. While it is on nothing is renamed or converted, and every file
+	 * event is appended to the audit log.
+	 */
+	async setObserverMode(enabled: boolean): Promise<IConfig> {
+		this.logger.info(enabled ? 'Observer mode on' : 'Observer mode off');
+		return await this.configStore.set({ observerMode: enabled });
+	}
+
+	/**
+	 * Log full file paths (`false`) or hashed stand-ins for them (`true`). The journal keeps full
+	 * paths either way.
+	 */
+	async setLogPrivacy(enabled: boolean): Promise<IConfig> {
+		const cfg = await this.configStore.set({ logPrivacy: enabled });
+		this.logger.info(enabled ? 'Log privacy on' : 'Log privacy off');
+		return cfg;
+	}
+
+	/**
+	 * Share config and rules with other machines through a settings file in `dir`, a folder synced
+	 * by iCloud Drive, Dropbox or similar, or stop when null. Changes on either side are picked up
+	 * as they happen. See {@link SettingsSync}.
+	 */
+	async setSyncDir(dir: string | null): Promise<IConfig> {
+		const target = dir && dir.trim().length > 0 ? this.normalizePath(dir) : undefined;
+		const cfg = await this.configStore.set({ syncDir: target });
+		await this.sync.setDir(cfg.syncDir ?? null);
+		this.logger.info(target ? 'Settings sync on' : 'Settings sync off', { dir: target });
+		return cfg;
+	}
+
+	/** Settle a settings sync conflict by keeping this machine's settings or the other one's. */
+	async resolveSyncConflict(keep: SyncResolution): Promise<void> {
+		await this.sync.resolve(keep);
+		const message =
+			keep === 'remote' ? 'Using the synced settings' : "This Mac's settings are now synced";
+		this.emit('toast', { level: 'info', message });
+	}
+
+	/** A pairing key that lets another machine read this one's synced settings. */
+	async exportSyncKey(): Promise<string> {
+		return await this.sync.exportKey();
+	}
+
+	/** Read synced settings with the pairing key exported by another machine. */
+	async importSyncKey(code: string): Promise<void> {
+		await this.sync.importKey(code);
+	}
+
+	/**
+	 * Let other Namefix installs on the local network find this machine and pair with it. Pairing
+	 * hands over the settings sync key, so paired machines can sync settings and read each other's
+	 * status. See {@link PairingService}.
+	 */
+	async setLanPairing(enabled: boolean): Promise<IConfig> {
+		const cfg = await this.configStore.set({ lanPairing: enabled });
+		await this.pairing.setEnabled(enabled);
+		this.logger.info(enabled ? 'Local network pairing on' : 'Local network pairing off');
+		return cfg;
+	}
+
+	/** Show a short code that another machine enters to pair with this one. */
+	startPairing(): PairingCode {
+		return this.pairing.startPairing();
+	}
+
+	/** Namefix installs on the local network that can be paired with. */
+	async findPeers(): Promise<NearbyPeer[]> {
+		return await this.pairing.findPeers();
+	}
+
+	/** Pair with a machine found by {@link findPeers}, using the code it shows. */
+	async pairPeer(peerId: string, code: string): Promise<PairedPeer> {
+		const peer = await this.pairing.pair(peerId, code);
+		this.emit('toast', { level: 'info', message: `Paired with ${peer.name}` });
+		return peer;
+	}
+
+	/** What a paired machine on the local network is doing. */
+	async getPeerStatus(peerId: string): Promise<PeerStatus> {
+		return await this.pairing.peerStatus(peerId);
+	}
+
+	/** Forget a paired machine. */
+	async unpairPeer(peerId: string): Promise<void> {
+		await this.pairing.unpair(peerId);
+	}
+
+	private onSyncStatus(status: SettingsSyncStatus) {
+		const changed = status.state !== this.syncState;
+		this.syncState = status.state;
+		if (changed && status.state === 'conflict' && status.conflict) {
+			const { deviceName } = status.conflict;
+			this.emit('toast', {
+				level: 'warn',
+				message: `Settings changed here and on ${deviceName}; choose which to keep`,
+			});
+		}
+		if (changed && status.state === 'locked') {
+			this.emit('toast', {
+				level: 'warn',
+				message: 'Synced settings are encrypted; import the pairing key from your other Mac',
+			});
+		}
+		if (changed && status.state === 'error') {
+			this.emit('toast', { level: 'error', message: `Settings sync failed: ${status.detail}` });
+		}
+		this.emitStatus();
+	}
+
+	/**
+	 * Move the rename journal, audit log and logs to `dir`, or back to the default locations when
+	 * null, without stopping. Renames are held while the files are copied and checked; only then
+	 * does the service switch over, append anything logged meanwhile, and delete the originals.
+	 * If a copy fails, the copies are removed and nothing changes.
+	 */
+	async setDataDir(dir: string | null): Promise<IConfig> {
+		const target = dir && dir.trim().length > 0 ? path.resolve(dir.trim()) : null;
+		const from = dataLocations(this.getConfig().dataDir ?? null);
+		const to = dataLocations(target);
+		if (from.state === to.state && from.logs === to.logs) return this.getConfig();
+
+		const wasPaused = this.queue.snapshot().paused;
+		this.queue.setPaused(true);
+		try {
+			await this.queue.whenIdle();
+			const copied = await copyDataFiles(from, to);
+			const cfg = await this.configStore.set({ dataDir: target ?? undefined });
+			const left = await finishMove(copied);
+			if (left.length > 0) {
+				this.logger.warn('Could not remove old data files', { files: left });
+			}
+			this.logger.info('Data directory moved', { from: from.state, to: to.state });
+			return cfg;
+		} finally {
+			this.queue.setPaused(wasPaused);
+		}
+	}
+
+	/**
+	 * Write rename history out again after it went degraded, from what could be read
+	 * (`salvage`) or from the latest backup (`restore`). See {@link JournalStore.repair}.
+	 */
+	async repairHistory(mode: HistoryRepair): Promise<HistoryHealth> {
+		const health = await this.journal.repair(mode);
+		const message =
+			mode === 'restore' ? 'Rename history restored from backup' : 'Rename history repaired';
+		this.emit('toast', { level: 'info', message });
+		return health;
+	}
+
+	/** Write rename history to `file` for {@link mergeHistory} on another machine. */
+	async exportHistory(file: string): Promise<number> {
+		const entries = await this.journal.exportTo(this.normalizePath(file));
+		this.logger.info('Rename history exported', { file, entries });
+		return entries;
+	}
+
+	/**
+	 * Add the renames in another machine's {@link exportHistory} file that aren't here yet, so undo
+	 * and usage stats carry over. Merging the same file again adds nothing.
+	 */
+	async mergeHistory(file: string): Promise<HistoryMerge> {
+		const result = await this.journal.merge(this.normalizePath(file));
+		this.logger.info('Rename history merged', { file, ...result });
+		const renames = result.imported === 1 ? '1 rename' : `${result.imported} renames`;
+		this.emit('toast', { level: 'info', message: `Merged ${renames} into history` });
+		this.emitStatus();
+		return result;
+	}
+
+	private onHistoryHealth(health: HistoryHealth) {
+		if (health.state === 'degraded') {
+			const { reason, detail } = health;
+			this.logger.error('Rename history degraded', { reason, detail });
+			const problem =
+				reason === 'corrupt'
+					? 'Rename history is damaged'
+					: reason === 'disk-full'
+						? 'The disk is full'
+						: 'Rename history could not be saved';
+			this.emit('toast', {
+				level: 'error',
+				message: `${problem}; new renames are kept in memory until it is repaired`,
+			});
+		}
+		this.emitStatus();
+	}
+
+	/** Recorded audit events, newest first. */
+	async getAuditLog(limit?: number): Promise<ServiceFileEvent[]> {
+		return await this.audit.recent(limit);
+	}
+
+	/**
+	 * Start a named session; renames are tagged with it until it ends. Ends any session already
+	 * running.
+	 */
+	async startSession(name: string): Promise<IConfig> {
+		const trimmed = name.trim();
+		if (trimmed.length === 0) throw new Error('session name is required');
+		const now = Date.now();
+		const sessions = endSessions(this.getConfig().sessions ?? [], now);
+		sessions.push({ id: randomUUID(), name: trimmed, startedAt: now });
+		this.logger.info('Session started', { name: trimmed });
+		return await this.configStore.set({ sessions });
+	}
+
+	async endSession(): Promise<IConfig> {
+		const cfg = this.getConfig();
+		if (!activeSession(cfg)) return cfg;
+		return await this.configStore.set({ sessions: endSessions(cfg.sessions ?? [], Date.now()) });
+	}
+
+	/** Known sessions, newest first, with totals from the rename journal. */
+	async listSessions(): Promise<SessionSummary[]> {
+		const summaries: SessionSummary[] = [];
+		for (const session of [...(this.getConfig().sessions ?? [])].reverse()) {
+			const entries = await this.journal.search({ session: session.id });
+			summaries.push({
+				...session,
+				filesRenamed: entries.length,
+				bytesProcessed: entries.reduce((sum, entry) => sum + (entry.bytes ?? 0), 0),
+			});
+		}
+		return summaries;
+	}
+
+	/** Undo every rename made during a session, newest first. */
+	async undoSession(id: string): Promise<SessionUndoResult> {
+		const result = await this.journal.undoSession(id);
+		const renames = (count: number) => `${count} rename${count === 1 ? '' : 's'}`;
+		if (result.failed > 0) {
+			this.emit('toast', {
+				level: 'warn',
+				message: `Undid ${renames(result.undone)}; ${renames(result.failed)} could not be undone`,
+			});
+		} else {
+			this.emit('toast', { level: 'info', message: `Undid ${renames(result.undone)}` });
+		}
+		if (result.undone + result.failed > 0) {
+			this.emit('undo', { scope: 'session', ...result, timestamp: Date.now() });
+		}
+		return result;
+	}
+
+	private journalDetails(ev: { size: number }, details: JournalDetails = {}): JournalDetails {
+		const session = activeSession(this.getConfig());
+		return { ...details, bytes: ev.size, session: session?.id };
+	}
+
+	/**
+	 * Files renamed and bytes processed per watched directory since `since` (epoch ms), defaulting
+	 * to the start of the current month. Counts come from the rename journal, so renames it has
+	 * pruned are not included.
+	 */
+	async getDirectoryUsage(since: number = startOfMonth()): Promise<DirectoryUsage[]> {
+		return await this.journal.usage(this.getListedDirs(this.getConfig()), since);
+	}
+
+	/**
+	 * Apply a retention policy to the rename journal (the configured one if omitted). Warns when
+	 * entries were removed, since those renames can no longer be undone.
+	 */
+	async pruneHistory(policy?: RetentionPolicy): Promise<PruneResult> {
+		const result = await this.journal.prune(policy ?? this.getConfig().historyRetention ?? {});
+		if (result.removed > 0) {
+			this.logger.info('Pruned rename history', { ...result });
+			const entries = result.removed === 1 ? 'entry' : 'entries';
+			const renames = result.remaining === 1 ? 'rename' : 'renames';
+			this.emit('toast', {
+				level: 'warn',
+				message: `Pruned ${result.removed} history ${entries}; undo now reaches back ${result.remaining} ${renames}`,
+			});
+		}
+		return result;
+	}
+
+	/**
+	 * Measure rename throughput on a scratch directory. Uses its own renamer so in-flight targets of
+	 * the live service are unaffected.
+	 */
+	async runBenchmark(options: BenchmarkOptions = {}): Promise<BenchmarkResult> {
+		const directory = options.directory ? this.normalizePath(options.directory) : undefined;
+		const result = await runBenchmark(new RenameService(), this.fsSafe, { ...options, directory });
+		this.benchmarkResults = [result, ...this.benchmarkResults].slice(
+			0,
+			NamefixService.MAX_BENCHMARK_RESULTS,
+		);
+		this.logger.info('Benchmark finished', { ...result });
+		return result;
+	}
+
+	/** Results of benchmarks run since the service started, newest first. */
+	getBenchmarkResults(): BenchmarkResult[] {
+		return [...this.benchmarkResults];
+	}
+
+	/**
+	 * Restart every watcher after the machine wakes, since FSEvents streams can go stale across
+	 * sleep without reporting an error, then queue the files that arrived from `since` (epoch
+	 * milliseconds) on. Restarting drops pending jobs, so those are queued again too. Files the
+	 * rules have already handled are left alone by the usual checks.
+	 */
+	async recoverFromSleep(since: number): Promise<WakeRecovery> {
+		if (!this.running) return { restarted: 0, caughtUp: 0 };
+		const requeue = new Set(this.queue.snapshot().pending.map((item) => item.file));
+		const restarted = await this.withWatcherLock(async () => {
+			let count = 0;
+			for (const [dir, watcher] of [...this.watchers]) {
+				await this.stopWatcher(dir, watcher);
+				try {
+					await this.startWatcher(dir);
+					count++;
+				} catch (err) {
+					this.logger.error('Failed to restart watcher after wake', {
+						dir,
+						error: err instanceof Error ? err.message : String(err),
+					});
+				}
+			}
+			return count;
+		});
+
+		const { active, pending } = this.queue.snapshot();
+		const queued = new Set([...active, ...pending].map((item) => item.file));
+		let caughtUp = 0;
+		for (const dir of [...this.watchers.keys()]) {
+			let names: string[];
+			try {
+				const dirents = await fs.readdir(dir, { withFileTypes: true });
+				names = dirents.filter((d) => d.isFile() && !d.name.startsWith('.')).map((d) => d.name);
+			} catch {
+				continue;
+			}
+			for (const name of names) {
+				const full = path.join(dir, name);
+				if (queued.has(full)) continue;
+				try {
+					const st = await fs.stat(full);
+					if ((st.birthtimeMs || st.mtimeMs) < since && !requeue.has(full)) continue;
+					if (!(await this.fsSafe.isStable(full))) continue;
+					this.dispatch(dir, {
+						path: full,
+						birthtimeMs: st.birthtimeMs,
+						mtimeMs: st.mtimeMs,
+						size: st.size,
+					});
+					caughtUp++;
+				} catch {
+					// Gone or unreadable since the listing; a watcher will report it if it comes back
+				}
+			}
+		}
+
+		this.logger.info('Recovered watchers after wake', { restarted, caughtUp });
+		if (caughtUp > 0) {
+			this.emit('toast', {
+				level: 'info',
+				message: `Caught up on ${caughtUp} file${caughtUp === 1 ? '' : 's'} from while asleep`,
+			});
+		}
+		this.emitStatus();
+		return { restarted, caughtUp };
+	}
+
+	/**
+	 * The name the current rules would give a file, or null when none renames it. `input` is a
+	 * bare filename, named as if created now, or the path or `file://` URL of an existing file,
+	 * whose creation time and tags are used; paths and URLs come back in the same form. Nothing is
+	 * renamed or reserved, so the result may still collide with a file that has that name.
+	 */
+	async proposeName(input: string): Promise<string | null> {
+		const trimmed = input.trim();
+		if (trimmed.length === 0) return null;
+		let birthtime = new Date();
+		let srcPath = trimmed;
+		const isUrl = trimmed.startsWith('file://');
+		const isPath = isUrl || path.isAbsolute(trimmed) || trimmed.startsWith('~/');
+		if (isPath) {
+			try {
+				srcPath = isUrl ? fileURLToPath(trimmed) : this.normalizePath(trimmed);
+				const st = await fs.stat(srcPath);
+				birthtime = new Date(st.birthtimeMs || st.mtimeMs);
+			} catch {
+				// Not on disk (e.g. copied from a document), or a URL of another host; name it as if
+				// created now
+			}
+		}
+		const proposal = await this.propose(srcPath, birthtime, this.getConfig());
+		if (!proposal) return null;
+		if (!isPath) return proposal.target;
+		const target = path.join(path.dirname(srcPath), proposal.target);
+		return isUrl ? pathToFileURL(target).href : target;
+	}
+
+	/**
+	 * The name and rule the current rules give the file at `srcPath`, if any renames it. With
+	 * `only`, just that matcher's profiles apply and the legacy prefix rule is skipped.
+	 */
+	private async propose(
+		srcPath: string,
+		birthtime: Date,
+		cfg: IConfig,
+		only?: ProfileMatcher,
+	): Promise<{ target: string; rule: string } | null> {
+		const file = path.basename(srcPath);
+		const profile = (only ?? this.profileMatcher)?.match(file);
+		if (profile) {
+			const renames = (profile.action ?? 'rename') !== 'convert';
+			if (!renames || !this.renamer.needsRenameForProfile(file, profile)) return null;
+			const tags = await this.renamer.tagsFor(srcPath, profile);
+			const named = await this.renamer.nameForProfile(srcPath, { birthtime, ...tags }, profile);
+			if ('skip' in named || named.target === file) return null;
+			return { target: named.target, rule: profile.id };
+		}
+		if (!only && this.matcher?.test(file) && this.renamer.needsRename(file, cfg.prefix)) {
+			const target = buildName(cfg.prefix || 'Screenshot', birthtime, path.extname(file) || '.png');
+			return { target, rule: 'legacy' };
+		}
+		return null;
+	}
+
+	/**
+	 * Work out what the current rules would name every file in `dir`, without touching anything.
+	 * Proposed names that collide get the same `_N` suffixes a live rename would. Conversions are
+	 * not simulated, so converted files keep their current extension here. With `profileId`, only
+	 * that profile is applied, whether or not it is enabled.
+	 */
+	async planDirectory(dir: string, profileId?: string): Promise<RenamePlan> {
+		const directory = this.normalizePath(dir);
+		const cfg = this.getConfig();
+		let only: ProfileMatcher | undefined;
+		if (profileId) {
+			const profile = cfg.profiles?.find((p) => p.id === profileId);
+			if (!profile) throw new Error(`Unknown profile: ${profileId}`);
+			only = new ProfileMatcher([{ ...profile, enabled: true }]);
+		}
+		const dirents = await fs.readdir(directory, { withFileTypes: true });
+		const files = dirents.filter((d) => d.isFile() && !d.name.startsWith('.')).map((d) => d.name);
+
+		const entries: RenamePlanEntry[] = [];
+		for (const file of files) {
+			const srcPath = path.join(directory, file);
+			const st = await fs.stat(srcPath);
+			const birthtime = new Date(st.birthtimeMs || st.mtimeMs);
+			const proposal = await this.propose(srcPath, birthtime, cfg, only);
+			entries.push({
+				file,
+				target: proposal?.target ?? null,
+				rule: proposal?.rule,
+				createdAt: birthtime.getTime(),
+			});
+		}
+
+		// Names that stay put are taken; claim proposed names in creation order, as arrivals would.
+		const taken = new Set(entries.filter((e) => e.target === null).map((e) => e.file));
+		const renamed = entries
+			.filter((e) => e.target !== null)
+			.sort((a, b) => a.createdAt - b.createdAt);
+		for (const entry of renamed) {
+			const target = claimName(taken, entry.target as string);
+			if (target !== entry.target) entry.conflict = true;
+			entry.target = target;
+		}
+		return { directory, entries };
+	}
+
+	/**
+	 * Rename the files in `dir` once, as `planDirectory(dir, profileId)` proposes, whether or not
+	 * the folder is watched. Files are renamed in creation order; one whose proposed name is taken
+	 * by another file by then is left alone and counted as failed. Renames are journaled and
+	 * emitted like live ones, so they can be undone. Dry run does not apply, since the plan is the
+	 * preview. Aborting `signal` stops before the next file; renames already made stay.
+	 */
+	async runOnce(dir: string, profileId?: string, signal?: AbortSignal): Promise<RunOnceResult> {
+		return this.applyPlan(await this.planDirectory(dir, profileId), signal);
+	}
+
+	/**
+	 * Rename the files of a plan from `planDirectory`, which may since have had entries left out.
+	 * Each rename is checked as it is made, as in `runOnce`; one that would leave the plan's
+	 * directory, or whose file is gone, counts as failed.
+	 */
+	async applyPlan(plan: RenamePlan, signal?: AbortSignal): Promise<RunOnceResult> {
+		const directory = this.normalizePath(plan.directory);
+		const result: RunOnceResult = { directory, renamed: 0, failed: 0 };
+		const renames = plan.entries
+			.filter((e) => e.target !== null)
+			.sort((a, b) => a.createdAt - b.createdAt);
+		for (const entry of renames) {
+			if (signal?.aborted) {
+				this.logger.info('one-off rename cancelled', { directory, renamed: result.renamed });
+				break;
+			}
+			const from = path.join(directory, entry.file);
+			const to = path.join(directory, entry.target as string);
+			try {
+				const relative = path.relative(directory, to);
+				const outside =
+					!relative || relative.split(path.sep)[0] === '..' || path.isAbsolute(relative);
+				if (entry.file !== path.basename(entry.file) || outside) {
+					throw new Error('not a rename within the folder');
+				}
+				const st = await fs.stat(from);
+				const existing = await fs.stat(to).catch(() => null);
+				// A case-only rename finds the file itself on case-insensitive volumes
+				if (existing && (existing.ino !== st.ino || existing.dev !== st.dev)) {
+					throw new Error(`${entry.target} already exists`);
+				}
+				await this.fsSafe.atomicRename(from, to);
+				await this.journal.record(from, to, this.journalDetails(st, { rule: entry.rule }));
+				this.emit('file', {
+					kind: 'applied',
+					directory,
+					file: entry.file,
+					target: entry.target as string,
+					timestamp: Date.now(),
+				});
+				result.renamed++;
+			} catch (e: unknown) {
+				const message = e instanceof Error ? e.message : String(e);
+				this.logger.warn('one-off rename failed', { path: from, error: message });
+				this.emit('file', {
+					kind: 'error',
+					directory,
+					file: entry.file,
+					timestamp: Date.now(),
+					message,
+				});
+				result.failed++;
+				result.error = `${entry.file}: ${message}`;
+			}
+		}
+		return result;
+	}
+
+	getQueue(): QueueSnapshot {
+		return this.queue.snapshot();
+	}
+
+	setQueuePaused(paused: boolean): QueueSnapshot {
+		this.queue.setPaused(paused);
+		return this.queue.snapshot();
+	}
+
+	reorderQueue(orderedIds: string[]): QueueSnapshot {
+		this.queue.reorder(orderedIds);
+		return this.queue.snapshot();
+	}
+
+	dropQueueItem(id: string): QueueSnapshot {
+		this.queue.drop(id);
+		return this.queue.snapshot();
+	}
+
 	/**
 	 * Subscribe to service events. Returns an unsubscribe handle for convenience.
 	 */
@@ -274,13 +1309,84 @@ export class NamefixService {
 	}
 
 	private emit<K extends keyof ServiceEventMap>(event: K, payload: ServiceEventMap[K]) {
+		if (this.config?.observerMode) {
+			// Bursts would prompt to snooze; observing must stay silent
+			if (event === 'burst') return;
+			if (event === 'file') {
+				this.audit.record(payload as ServiceFileEvent).catch((error) => {
+					this.logger.warn('Failed to write audit log', { error: String(error) });
+				});
+			}
+		}
 		this.emitter.emit(event, payload);
+		if (event === 'file') {
+			this.recordDirectoryEvent(payload as ServiceEventMap['file']);
+			this.emitStatus();
+		}
+	}
+
+	private recordDirectoryEvent(event: ServiceEventMap['file']) {
+		const today = new Date(event.timestamp).toDateString();
+		const current = this.directoryStats.get(event.directory);
+		const stats: DirectoryStats & { day: string } =
+			current && current.day === today
+				? current
+				: {
+						directory: event.directory,
+						lastEventAt: null,
+						renamesToday: 0,
+						errorCount: current?.errorCount ?? 0,
+						queued: 0,
+						skippedToday: 0,
+						day: today,
+					};
+		stats.lastEventAt = event.timestamp;
+		if (event.kind === 'applied' || event.kind === 'converted') stats.renamesToday += 1;
+		if (event.kind === 'error' || event.kind === 'convert-error') stats.errorCount += 1;
+		if (event.kind === 'skipped') stats.skippedToday += 1;
+		this.directoryStats.set(event.directory, stats);
+	}
+
+	private isExcluded(dir: string, file: string): boolean {
+		const folders = path.relative(dir, path.dirname(file)).split(path.sep);
+		return folders.some((folder) => this.watchExclusions.has(folder));
+	}
+
+	private extensionAllowed(cfg: IConfig, directory: string, ext: string): boolean {
+		const options = cfg.directoryOptions?.[directory];
+		if (!options) return true;
+		const bare = ext.replace(/^\./, '').toLowerCase();
+		if (options.excludeExtensions?.includes(bare)) return false;
+		const include = options.includeExtensions ?? [];
+		return include.length === 0 || include.includes(bare);
+	}
+
+	private isDryRun(cfg: IConfig, directory: string): boolean {
+		return cfg.observerMode === true || cfg.dryRun || (cfg.dryRunDirs ?? []).includes(directory);
+	}
+
+	private statsFor(directory: string, queued: Map<string, number>): DirectoryStats {
+		const stats = this.directoryStats.get(directory);
+		const today = new Date().toDateString();
+		const current = stats && stats.day === today;
+		return {
+			directory,
+			lastEventAt: stats?.lastEventAt ?? null,
+			renamesToday: current ? stats.renamesToday : 0,
+			errorCount: stats?.errorCount ?? 0,
+			queued: queued.get(directory) ?? 0,
+			skippedToday: current ? stats.skippedToday : 0,
+			watchMode: this.watchModes.get(directory) ?? null,
+		};
 	}
 
 	private async syncWatchers(): Promise<void> {
+		await this.refreshVolumes();
 		await this.withWatcherLock(async () => {
 			const cfg = this.getConfig();
-			const desiredDirs = this.running ? this.getWatchDirs(cfg) : [];
+			const desiredDirs = this.running
+				? this.getWatchDirs(cfg).filter((dir) => this.isVolumeAvailable(dir))
+				: [];
 			const desiredSet = new Set(desiredDirs);
 
 			const stops: Array<Promise<void>> = [];
@@ -321,6 +1427,7 @@ export class NamefixService {
 					const failedWatcher = this.watchers.get(dir);
 					if (failedWatcher) {
 						this.watchers.delete(dir);
+						this.watchModes.delete(dir);
 						try {
 							await failedWatcher.stop?.();
 						} catch {
@@ -342,9 +1449,38 @@ export class NamefixService {
 		});
 	}
 
+	/**
+	 * The configured backend, except that while the default is configured, directories on network
+	 * shares are polled, and on Windows directories too large for `fs.watch` to keep up with are
+	 * moved onto the USN journal.
+	 */
+	private async backendFor(dir: string, recursive: boolean): Promise<WatchBackend> {
+		const configured = this.getConfig().watchBackend ?? 'fsevents';
+		if (configured === 'usn-journal' && recursive) {
+			// The journal only names a file's parent folder, so subfolders go through fs.watch
+			this.logger.info('Watching subfolders through fs.watch instead of the USN journal', { dir });
+			return 'fsevents';
+		}
+		if (configured !== 'fsevents') return configured;
+		if (await isNetworkFilesystem(dir)) {
+			this.logger.info('Polling directory on a network filesystem', { dir });
+			return 'polling';
+		}
+		if (process.platform !== 'win32' || recursive) return configured;
+		if (!(await hasAtLeastEntries(dir, USN_JOURNAL_ENTRY_THRESHOLD))) return configured;
+		this.logger.info('Watching large directory through the USN journal', { dir });
+		return 'usn-journal';
+	}
+
 	private async startWatcher(dir: string): Promise<void> {
+		// Creating the directory on an unmounted volume would put it on the system disk instead
+		const root = volumeRoot(dir);
+		if (root && !(await isMounted(root))) throw new Error(`${root} is not mounted`);
 		await this.ensureDir(dir);
-		const watcher = this.createWatcher(dir, this.fsSafe);
+		await this.checkSharing(dir);
+		const recursive = this.getConfig().directoryOptions?.[dir]?.recursive === true;
+		const backend = await this.backendFor(dir, recursive);
+		const watcher = this.createWatcher(dir, this.fsSafe, backend, recursive);
 
 		// Register error handler if available
 		if (typeof watcher.onError === 'function') {
@@ -359,14 +1495,40 @@ export class NamefixService {
 		}
 
 		this.watchers.set(dir, watcher);
-		await watcher.start((ev) => {
-			this.handleWatchEvent(dir, ev).catch((err) => {
-				this.logger.error(err instanceof Error ? err : String(err));
+		this.watchModes.set(dir, backend);
+		await watcher.start((ev) => this.dispatch(dir, ev));
+	}
+
+	private dispatch(dir: string, ev: WatchEvent) {
+		if (this.isExcluded(dir, ev.path)) return;
+		if (this.snoozes.has(dir)) {
+			this.emit('file', {
+				kind: 'skipped',
+				directory: dir,
+				file: path.basename(ev.path),
+				timestamp: Date.now(),
+				message: 'snoozed',
 			});
-		});
+			return;
+		}
+		const burst = this.bursts.record(dir);
+		if (burst !== null) {
+			this.emit('burst', {
+				directory: dir,
+				count: burst,
+				windowMs: this.bursts.windowMs,
+				timestamp: Date.now(),
+			});
+		}
+		const priority = this.config?.priorityDirs?.includes(dir) ? 'high' : 'normal';
+		this.queue.enqueue(dir, ev.path, () => this.handleWatchEvent(dir, ev), priority);
+		this.emitStatus();
 	}
 
 	private async stopWatcher(dir: string, watcher: IWatchService): Promise<void> {
+		this.queue.dropDirectory(dir);
+		this.bursts.forget(dir);
+
 		// Clean up error handler
 		const unsubscribe = this.watcherErrorUnsubscribers.get(dir);
 		if (unsubscribe) {
@@ -388,9 +1550,28 @@ export class NamefixService {
 			this.logger.error(err instanceof Error ? err : String(err));
 		} finally {
 			this.watchers.delete(dir);
+			this.watchModes.delete(dir);
 		}
 	}
 
+	/** Warn when a directory is shared with other users, whose files we may not be able to rename. */
+	private async checkSharing(dir: string) {
+		const sharing = await detectSharing(dir, userId());
+		if (!sharing) {
+			this.sharedDirs.delete(dir);
+			return;
+		}
+		const known = this.sharedDirs.has(dir);
+		this.sharedDirs.set(dir, sharing);
+		if (known) return;
+		this.logger.warn('Watching a directory shared with other users', { ...sharing });
+		const how = sharing.ownedByOther ? `owned by uid ${sharing.ownerUid}` : 'writable by everyone';
+		this.emit('toast', {
+			level: 'warn',
+			message: `${path.basename(dir)} is ${how}; files other users create there may fail to rename`,
+		});
+	}
+
 	private async withWatcherLock<T>(fn: () => Promise<T>): Promise<T> {
 		const previous = this.watcherLock;
 		let release: () => void = () => {};
@@ -413,6 +1594,22 @@ export class NamefixService {
 			watchDirs: normalizedDirs,
 			watchDir: primaryDir,
 		};
+		this.logger.setPrivacy?.(cfg.logPrivacy ?? true);
+		this.sync.setDir(cfg.syncDir ?? null).catch(() => {
+			/* reported through the sync status */
+		});
+		this.pairing.setEnabled(cfg.lanPairing ?? false).catch((err) => {
+			this.logger.warn('Local network pairing failed to start', {
+				error: err instanceof Error ? err.message : String(err),
+			});
+		});
+		const logs = logsDir();
+		setDataDir(cfg.dataDir ?? null);
+		if (logsDir() !== logs) {
+			this.logger.reopen?.().catch(() => {
+				/* keeps logging to the ring */
+			});
+		}
 		// Initialize profile matcher if profiles exist
 		if (cfg.profiles && cfg.profiles.length > 0) {
 			this.profileMatcher = new ProfileMatcher(cfg.profiles);
@@ -436,6 +1633,13 @@ export class NamefixService {
 		return this.normalizeDirs(cfg.watchDirs, cfg.watchDir);
 	}
 
+	/** Watched directories as UIs list them: pinned ones first, each part in `watchDirs` order. */
+	private getListedDirs(cfg: IConfig): string[] {
+		const pinned = new Set(cfg.pinnedDirs ?? []);
+		const dirs = this.getWatchDirs(cfg);
+		return [...dirs.filter((dir) => pinned.has(dir)), ...dirs.filter((dir) => !pinned.has(dir))];
+	}
+
 	private async handleWatchEvent(
 		directory: string,
 		ev: { path: string; birthtimeMs: number; mtimeMs: number; size: number },
@@ -445,17 +1649,33 @@ export class NamefixService {
 		const extVal = path.extname(ev.path);
 		const dir = path.dirname(ev.path);
 
-		// Try profile-based matching first
+		// Try profile-based matching first, then the legacy include/exclude patterns
 		const matchedProfile = this.profileMatcher?.match(basename);
+		if (!matchedProfile && !this.matcher?.test(basename)) return; // No match - file is ignored
+		if (!this.extensionAllowed(cfg, directory, extVal)) return;
+
+		const originRule = cfg.originRules?.[directory];
+		if (originRule) {
+			const origin = ev.process
+				? originFromExecutable(ev.process)
+				: await this.origins.originOf(ev.path);
+			if (!isOriginAllowed(origin, originRule)) {
+				this.emit('file', {
+					kind: 'skipped',
+					directory,
+					file: basename,
+					timestamp: Date.now(),
+					message: `origin ${origin ?? 'unknown'} not allowed`,
+				});
+				return;
+			}
+		}
 
 		if (matchedProfile) {
-			// Profile-based renaming
 			await this.handleProfileRename(directory, ev, basename, extVal, dir, matchedProfile, cfg);
-		} else if (this.matcher?.test(basename)) {
-			// Legacy fallback: use include/exclude patterns
+		} else {
 			await this.handleLegacyRename(directory, ev, basename, extVal, dir, cfg);
 		}
-		// No match - file is ignored
 	}
 
 	private async handleProfileRename(
@@ -483,6 +1703,34 @@ export class NamefixService {
 		await this.handleRenameOnly(directory, ev, basename, extVal, dir, profile, cfg);
 	}
 
+	/**
+	 * Pass a file a hot folder just renamed to its destination and command. Runs alongside the
+	 * queue so a slow command doesn't hold it up; the move is journaled, so undo brings it back.
+	 */
+	private async handOff(directory: string, file: string, ev: { size: number }) {
+		const hotFolder = this.getConfig().hotFolders?.[directory];
+		if (!hotFolder) return;
+		let current = file;
+		try {
+			if (hotFolder.destination) {
+				current = await moveInto(file, hotFolder.destination, this.fsSafe);
+				await this.journal.record(file, current, this.journalDetails(ev, { rule: 'hot-folder' }));
+			}
+			if (hotFolder.command) await runHandOffCommand(hotFolder.command, current);
+			this.logger.info('handed off', { file, to: current });
+		} catch (e: unknown) {
+			const error = e instanceof Error ? e : new Error(String(e));
+			this.logger.error(error);
+			this.emit('file', {
+				kind: 'error',
+				directory,
+				file: path.basename(current),
+				timestamp: Date.now(),
+				message: `hand-off failed: ${error.message}`,
+			});
+		}
+	}
+
 	private async handleConvert(
 		directory: string,
 		ev: { path: string; birthtimeMs: number; mtimeMs: number; size: number },
@@ -502,7 +1750,7 @@ export class NamefixService {
 			return;
 		}
 
-		if (cfg.dryRun) {
+		if (this.isDryRun(cfg, directory)) {
 			const targetName = `${path.basename(basename, extVal)}.jpeg`;
 			this.emit('file', {
 				kind: 'preview',
@@ -530,7 +1778,8 @@ export class NamefixService {
 				to: result.destPath,
 				format: 'jpeg',
 			});
-			await this.journal.record(ev.path, result.destPath);
+			await this.journal.record(ev.path, result.destPath, this.journalDetails(ev));
+			void this.handOff(directory, result.destPath, ev);
 
 			// Trash the original
 			try {
@@ -567,6 +1816,15 @@ export class NamefixService {
 		}
 	}
 
+	/** The front window when a file appeared, for profiles whose template names files after it. */
+	private async windowTitleFor(
+		profile: IProfile,
+		ev: { birthtimeMs: number; mtimeMs: number },
+	): Promise<string | undefined> {
+		if (!usesWindowTitle(profile.template)) return undefined;
+		return (await this.windows.titleAt(ev.birthtimeMs || ev.mtimeMs)) ?? undefined;
+	}
+
 	private async handleRenameAndConvert(
 		directory: string,
 		ev: { path: string; birthtimeMs: number; mtimeMs: number; size: number },
@@ -587,7 +1845,7 @@ export class NamefixService {
 			return;
 		}
 
-		if (cfg.dryRun) {
+		if (this.isDryRun(cfg, directory)) {
 			const convertedName = `${path.basename(basename, extVal)}.jpeg`;
 			this.emit('file', {
 				kind: 'preview',
@@ -600,6 +1858,9 @@ export class NamefixService {
 		}
 
 		try {
+			// The front window changes quickly, so look it up before converting
+			const windowTitle = await this.windowTitleFor(profile, ev);
+
 			// Step 1: Convert
 			const result = await this.converter.convert(ev.path, { outputFormat: 'jpeg' });
 			const convertedBasename = path.basename(result.destPath);
@@ -621,14 +1882,18 @@ export class NamefixService {
 			const convertedExt = path.extname(result.destPath);
 			const { filename: targetBase } = await this.renamer.targetForProfile(
 				result.destPath,
-				{ birthtime: new Date(ev.birthtimeMs), ext: convertedExt },
+				{ birthtime: new Date(ev.birthtimeMs), ext: convertedExt, windowTitle },
 				profile,
 			);
 			const targetPath = path.join(dir, targetBase);
 
 			try {
-				await this.fsSafe.atomicRename(result.destPath, targetPath);
-				await this.journal.record(ev.path, targetPath);
+				// The name hook may keep the converted file's own name
+				if (targetPath !== result.destPath) {
+					await this.fsSafe.atomicRename(result.destPath, targetPath);
+				}
+				await this.journal.record(ev.path, targetPath, this.journalDetails(ev, { rule: profile.id }));
+				void this.handOff(directory, targetPath, ev);
 				this.emit('file', {
 					kind: 'applied',
 					directory,
@@ -705,21 +1970,41 @@ export class NamefixService {
 			return;
 		}
 
-		const { filename: targetBase } = await this.renamer.targetForProfile(
+		const {
+			filename: targetBase,
+			collidedWith,
+			skipped,
+		} = await this.renamer.targetForProfile(
 			ev.path,
-			{ birthtime: new Date(ev.birthtimeMs), ext: extVal },
+			{
+				birthtime: new Date(ev.birthtimeMs),
+				ext: extVal,
+				windowTitle: await this.windowTitleFor(profile, ev),
+			},
 			profile,
 		);
 		const targetPath = path.join(dir, targetBase);
+		// Templates built from file tags give back the same name when the tags are missing
+		if (targetBase === basename) {
+			this.emit('file', {
+				kind: 'skipped',
+				directory,
+				file: basename,
+				timestamp: Date.now(),
+				message: skipped ?? 'idempotent',
+			});
+			return;
+		}
 
 		try {
-			if (cfg.dryRun) {
+			if (this.isDryRun(cfg, directory)) {
 				this.emit('file', {
 					kind: 'preview',
 					directory,
 					file: basename,
 					target: targetBase,
 					timestamp: Date.now(),
+					...(collidedWith ? { collidedWith } : {}),
 				});
 				this.logger.info('preview', { from: ev.path, to: targetPath, profile: profile.name });
 				return;
@@ -741,16 +2026,22 @@ export class NamefixService {
 			}
 
 			try {
-				await this.fsSafe.atomicRename(ev.path, targetPath);
-				await this.journal.record(ev.path, targetPath);
+				const deferred = await this.renameWhenClosed(ev.path, targetPath, cfg);
+				await this.journal.record(
+					ev.path,
+					targetPath,
+					this.journalDetails(ev, { rule: profile.id, deferred }),
+				);
 				this.emit('file', {
 					kind: 'applied',
 					directory,
 					file: basename,
 					target: targetBase,
 					timestamp: Date.now(),
+					...(collidedWith ? { collidedWith } : {}),
 				});
 				this.eventBus.emit('file:renamed', { from: ev.path, to: targetPath });
+				void this.handOff(directory, targetPath, ev);
 			} catch (e: unknown) {
 				const error = e instanceof Error ? e : new Error(String(e));
 				const message = error.message || 'rename failed';
@@ -769,6 +2060,37 @@ export class NamefixService {
 		}
 	}
 
+	/**
+	 * Rename `from` once no other process holds it open, checking again after each retry delay.
+	 * Returns why the rename had to wait, if it did, so the journal can record it; throws once the
+	 * file is still held after the last retry.
+	 */
+	private async renameWhenClosed(
+		from: string,
+		to: string,
+		cfg: IConfig,
+	): Promise<string | undefined> {
+		let deferred: string | undefined;
+		for (const wait of [...NamefixService.OPEN_FILE_RETRY_DELAYS_MS, null]) {
+			const holder = cfg.checkOpenFiles ? await this.openFiles.findHolder(from) : null;
+			if (holder) {
+				deferred = `open in ${holder}`;
+			} else {
+				try {
+					await this.fsSafe.atomicRename(from, to);
+					return deferred;
+				} catch (err) {
+					if (!isLockedError(err)) throw err;
+					deferred = 'locked by another process';
+				}
+			}
+			if (wait === null) break;
+			this.logger.info('rename deferred', { path: from, reason: deferred, retryInMs: wait });
+			await delay(wait);
+		}
+		throw new Error(`${path.basename(from)} is ${deferred}; gave up waiting`);
+	}
+
 	private async handleLegacyRename(
 		directory: string,
 		ev: { path: string; birthtimeMs: number; mtimeMs: number; size: number },
@@ -796,7 +2118,7 @@ export class NamefixService {
 		const targetPath = path.join(dir, targetBase);
 
 		try {
-			if (cfg.dryRun) {
+			if (this.isDryRun(cfg, directory)) {
 				this.emit('file', {
 					kind: 'preview',
 					directory,
@@ -824,8 +2146,12 @@ export class NamefixService {
 			}
 
 			try {
-				await this.fsSafe.atomicRename(ev.path, targetPath);
-				await this.journal.record(ev.path, targetPath);
+				const deferred = await this.renameWhenClosed(ev.path, targetPath, cfg);
+				await this.journal.record(
+					ev.path,
+					targetPath,
+					this.journalDetails(ev, { rule: 'legacy', deferred }),
+				);
 				this.emit('file', {
 					kind: 'applied',
 					directory,
@@ -834,6 +2160,7 @@ export class NamefixService {
 					timestamp: Date.now(),
 				});
 				this.eventBus.emit('file:renamed', { from: ev.path, to: targetPath });
+				void this.handOff(directory, targetPath, ev);
 			} catch (e: unknown) {
 				const error = e instanceof Error ? e : new Error(String(e));
 				const message = error.message || 'rename failed';
@@ -854,13 +2181,7 @@ export class NamefixService {
 
 	private emitStatus() {
 		if (!this.config) return;
-		const dirs = this.getWatchDirs(this.config);
-		this.emit('status', {
-			running: this.running,
-			directories: dirs,
-			dryRun: this.config.dryRun,
-			launchOnLogin: this.config.launchOnLogin,
-		});
+		this.emit('status', this.getStatus());
 	}
 
 	private normalizeDirs(dirs?: (string | null | undefined)[], fallback?: string): string[] {
@@ -900,6 +2221,161 @@ export class NamefixService {
 		}, NamefixService.HEALTH_CHECK_INTERVAL_MS);
 	}
 
+	private startHistoryPruning(): void {
+		this.stopHistoryPruning();
+		const run = () => {
+			const policy = this.config?.historyRetention ?? {};
+			if (Object.keys(policy).length === 0) return;
+			this.pruneHistory(policy).catch((err) => {
+				this.logger.error(err instanceof Error ? err : String(err));
+			});
+		};
+		run();
+		this.historyPruneInterval = setInterval(run, NamefixService.HISTORY_PRUNE_INTERVAL_MS);
+		this.historyPruneInterval.unref?.();
+	}
+
+	private stopHistoryPruning(): void {
+		if (this.historyPruneInterval) {
+			clearInterval(this.historyPruneInterval);
+			this.historyPruneInterval = null;
+		}
+	}
+
+	/**
+	 * Start or stop watchers as the volumes holding watched directories mount and unmount. A
+	 * released volume stays unwatched until it unmounts or is reclaimed.
+	 */
+	async setVolumeReleased(root: string, released: boolean): Promise<void> {
+		if (released) this.releasedVolumes.add(root);
+		else this.releasedVolumes.delete(root);
+		await this.syncWatchers();
+	}
+
+	private startVolumeMonitor(): void {
+		this.stopVolumeMonitor();
+		this.volumeMonitorInterval = setInterval(() => {
+			this.refreshVolumes()
+				.then((changed) => (changed ? this.syncWatchers() : undefined))
+				.catch((err) => {
+					this.logger.error(err instanceof Error ? err : String(err));
+				});
+			this.checkCameraCards().catch((err) => {
+				this.logger.error(err instanceof Error ? err : String(err));
+			});
+		}, NamefixService.VOLUME_CHECK_INTERVAL_MS);
+		this.volumeMonitorInterval.unref?.();
+	}
+
+	private stopVolumeMonitor(): void {
+		if (this.volumeMonitorInterval) {
+			clearInterval(this.volumeMonitorInterval);
+			this.volumeMonitorInterval = null;
+		}
+		this.knownCards = null;
+	}
+
+	/**
+	 * Import newly mounted camera cards with the first enabled preset. Cards already mounted when
+	 * the service started are left for a manual import.
+	 */
+	private async checkCameraCards(): Promise<void> {
+		const presets = this.getConfig().importPresets ?? [];
+		const cards = await findCameraCards();
+		const known = this.knownCards;
+		this.knownCards = new Set(cards);
+		if (known === null) return;
+		const preset = presets.find((p) => p.enabled);
+		if (!preset) return;
+		for (const card of cards.filter((c) => !known.has(c))) {
+			this.logger.info('Camera card mounted', { card, preset: preset.id });
+			if (this.isDryRun(this.getConfig(), card)) {
+				this.emit('toast', {
+					level: 'info',
+					message: `${volumeName(card)} mounted; imports don't run while previewing`,
+				});
+				continue;
+			}
+			await this.importCard(card, preset);
+		}
+	}
+
+	private async importCard(card: string, preset: ImportPreset): Promise<ImportProgress | null> {
+		const key = `${preset.id}:${card}`;
+		if (this.importing.has(key)) return null;
+		this.importing.add(key);
+		try {
+			const result = await runImport(card, preset, (progress) => this.emit('import', progress));
+			this.logger.info('Import finished', { ...result });
+			const from = volumeName(card);
+			const into = path.basename(preset.destination);
+			this.emit('toast', {
+				level: result.failed > 0 ? 'warn' : 'info',
+				message:
+					result.failed > 0
+						? `Imported ${result.imported} of ${result.total} files from ${from}; ${result.error}`
+						: `Imported ${result.imported} files from ${from} into ${into}`,
+			});
+			return result;
+		} finally {
+			this.importing.delete(key);
+		}
+	}
+
+	/** Re-check which volumes are mounted, returning whether any changed since the last check. */
+	private async refreshVolumes(): Promise<boolean> {
+		const roots = new Set<string>();
+		for (const dir of this.getWatchDirs(this.getConfig())) {
+			const root = volumeRoot(dir);
+			if (root) roots.add(root);
+		}
+		let changed = false;
+		for (const root of roots) {
+			const mounted = await isMounted(root);
+			const previous = this.mountedVolumes.get(root);
+			this.mountedVolumes.set(root, mounted);
+			if (previous === undefined || previous === mounted) continue;
+			changed = true;
+			if (!mounted) this.releasedVolumes.delete(root);
+			this.logger.info(mounted ? 'Volume mounted' : 'Volume unmounted', { root });
+			this.emit('toast', {
+				level: 'info',
+				message: mounted
+					? `${volumeName(root)} mounted; watching its folders again`
+					: `${volumeName(root)} unmounted; its folders are paused`,
+			});
+		}
+		for (const root of this.mountedVolumes.keys()) {
+			if (!roots.has(root)) this.mountedVolumes.delete(root);
+		}
+		if (changed) this.emitStatus();
+		return changed;
+	}
+
+	private isVolumeAvailable(dir: string): boolean {
+		const root = volumeRoot(dir);
+		if (!root) return true;
+		return this.mountedVolumes.get(root) === true && !this.releasedVolumes.has(root);
+	}
+
+	private getVolumes(cfg: IConfig): WatchedVolume[] {
+		const volumes = new Map<string, WatchedVolume>();
+		for (const dir of this.getListedDirs(cfg)) {
+			const root = volumeRoot(dir);
+			if (!root) continue;
+			const volume = volumes.get(root) ?? {
+				root,
+				name: volumeName(root),
+				mounted: this.mountedVolumes.get(root) ?? false,
+				released: this.releasedVolumes.has(root),
+				directories: [],
+			};
+			volume.directories.push(dir);
+			volumes.set(root, volume);
+		}
+		return [...volumes.values()];
+	}
+
 	private stopHealthMonitor(): void {
 		if (this.healthCheckInterval) {
 			clearInterval(this.healthCheckInterval);
@@ -925,6 +2401,9 @@ export class NamefixService {
 			try {
 				await fs.access(dir);
 			} catch {
+				// An unmounted volume isn't a broken watcher; the volume monitor stops it
+				const root = volumeRoot(dir);
+				if (root && !(await isMounted(root))) continue;
 				this.logger.warn('Watch directory no longer accessible', { dir });
 				unhealthyDirs.push(dir);
 			}
@@ -977,6 +2456,22 @@ async function pathExists(p: string): Promise<boolean> {
 	}
 }
 
+function activeSession(cfg: IConfig): RenameSession | null {
+	const last = cfg.sessions?.[cfg.sessions.length - 1];
+	return last && last.endedAt === undefined ? last : null;
+}
+
+/** `sessions` with any running session ended at `now`. */
+function endSessions(sessions: RenameSession[], now: number): RenameSession[] {
+	return sessions.map((session) =>
+		session.endedAt === undefined ? { ...session, endedAt: now } : session,
+	);
+}
+
+function startOfMonth(now = new Date()): number {
+	return new Date(now.getFullYear(), now.getMonth(), 1).getTime();
+}
+
 function delay(ms: number) {
 	return new Promise((resolve) => setTimeout(resolve, ms));
 }
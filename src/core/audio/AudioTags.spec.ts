@@ -0,0 +1,126 @@
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { readAudioTags } from './AudioTags.js';
+
+function syncsafe(n: number): Buffer {
+	return Buffer.from([(n >> 21) & 0x7f, (n >> 14) & 0x7f, (n >> 7) & 0x7f, n & 0x7f]);
+}
+
+/** An ID3v2.4 tag of UTF-8 text frames followed by some padding and audio. */
+function mp3(frames: [string, string][]): Buffer {
+	const encoded = frames.map(([id, text]) => {
+		const body = Buffer.concat([Buffer.from([3]), Buffer.from(text, 'utf8')]);
+		return Buffer.concat([Buffer.from(id, 'latin1'), syncsafe(body.length), Buffer.alloc(2), body]);
+	});
+	const tag = Buffer.concat([...encoded, Buffer.alloc(16)]);
+	const header = Buffer.concat([
+		Buffer.from('ID3', 'latin1'),
+		Buffer.from([4, 0, 0]),
+		syncsafe(tag.length),
+	]);
+	return Buffer.concat([header, tag, Buffer.alloc(64)]);
+}
+
+function flac(comments: string[]): Buffer {
+	const entries = comments.map((comment) => {
+		const text = Buffer.from(comment, 'utf8');
+		const length = Buffer.alloc(4);
+		length.writeUInt32LE(text.length);
+		return Buffer.concat([length, text]);
+	});
+	const count = Buffer.alloc(4);
+	count.writeUInt32LE(entries.length);
+	const block = Buffer.concat([Buffer.from([4, 0, 0, 0]), Buffer.from('test'), count, ...entries]);
+	const header = Buffer.from([0x84, 0, 0, 0]);
+	header.writeUIntBE(block.length, 1, 3);
+	const streamInfo = Buffer.concat([Buffer.from([0, 0, 0, 34]), Buffer.alloc(34)]);
+	return Buffer.concat([Buffer.from('fLaC', 'latin1'), streamInfo, header, block]);
+}
+
+function atom(type: string, ...children: Buffer[]): Buffer {
+	const body = Buffer.concat(children);
+	const size = Buffer.alloc(4);
+	size.writeUInt32BE(body.length + 8);
+	return Buffer.concat([size, Buffer.from(type, 'latin1'), body]);
+}
+
+/** An M4A with its metadata after the media data, as many encoders write it. */
+function m4a(items: [string, Buffer][]): Buffer {
+	// Each value sits in a data atom after its type indicator (1 = UTF-8) and locale
+	const data = (value: Buffer) => atom('data', Buffer.from([0, 0, 0, 1]), Buffer.alloc(4), value);
+	const ilst = atom('ilst', ...items.map(([type, value]) => atom(type, data(value))));
+	return Buffer.concat([
+		atom('ftyp', Buffer.from('M4A \0\0\0\0', 'latin1')),
+		atom('mdat', Buffer.alloc(32)),
+		atom('moov', atom('udta', atom('meta', Buffer.alloc(4), ilst))),
+	]);
+}
+
+describe('readAudioTags', () => {
+	let root: string;
+
+	beforeEach(async () => {
+		root = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-audio-'));
+	});
+
+	afterEach(async () => {
+		await fs.rm(root, { recursive: true, force: true });
+	});
+
+	async function tagsOf(name: string, data: Buffer) {
+		const file = path.join(root, name);
+		await fs.writeFile(file, data);
+		return await readAudioTags(file);
+	}
+
+	it('reads ID3v2 frames from MP3s', async () => {
+		const data = mp3([
+			['TPE1', 'Björk'],
+			['TALB', 'Debut'],
+			['TIT2', 'Human Behaviour'],
+			['TRCK', '1/11'],
+		]);
+		expect(await tagsOf('a.mp3', data)).toEqual({
+			artist: 'Björk',
+			album: 'Debut',
+			title: 'Human Behaviour',
+			track: 1,
+		});
+	});
+
+	it('reads Vorbis comments from FLAC', async () => {
+		const data = flac([
+			'ARTIST=Miles Davis',
+			'ALBUM=Kind of Blue',
+			'title=So What',
+			'TRACKNUMBER=01',
+		]);
+		expect(await tagsOf('b.flac', data)).toEqual({
+			artist: 'Miles Davis',
+			album: 'Kind of Blue',
+			title: 'So What',
+			track: 1,
+		});
+	});
+
+	it('reads iTunes atoms from M4A', async () => {
+		const data = m4a([
+			['\xa9ART', Buffer.from('Daft Punk')],
+			['aART', Buffer.from('Daft Punk')],
+			['\xa9nam', Buffer.from('One More Time')],
+			['trkn', Buffer.from([0, 0, 0, 1, 0, 14, 0, 0])],
+		]);
+		expect(await tagsOf('c.m4a', data)).toEqual({
+			artist: 'Daft Punk',
+			albumArtist: 'Daft Punk',
+			title: 'One More Time',
+			track: 1,
+		});
+	});
+
+	it('returns nothing for untagged files', async () => {
+		expect(await tagsOf('d.mp3', Buffer.alloc(256))).toEqual({});
+	});
+});
@@ -0,0 +1,293 @@
+import fs, { type FileHandle } from 'node:fs/promises';
+
+/** Tags read from an audio file; only those present are set. */
+export type AudioTags = {
+	artist?: string;
+	albumArtist?: string;
+	album?: string;
+	title?: string;
+	track?: number;
+	disc?: number;
+};
+
+/** Extensions `readAudioTags` understands. */
+export const AUDIO_EXTENSIONS = new Set(['.mp3', '.m4a', '.mp4', '.aac', '.flac', '.ogg', '.opus']);
+
+/** Metadata blocks larger than this are cover art or corrupt, and are not read. */
+const MAX_TAG_BYTES = 4 * 1024 * 1024;
+
+/**
+ * Tags of an MP3 (ID3v2, falling back to ID3v1), MP4/M4A (iTunes atoms), FLAC or Ogg
+ * Vorbis/Opus file. Files in other formats, or with no tags, yield an empty object.
+ */
+export async function readAudioTags(file: string): Promise<AudioTags> {
+	const handle = await fs.open(file, 'r');
+	try {
+		const { size } = await handle.stat();
+		const head = await readAt(handle, 0, Math.min(size, 64 * 1024));
+		if (head.toString('latin1', 0, 3) === 'ID3') return await readId3(handle, head, size);
+		if (head.toString('latin1', 0, 4) === 'fLaC') return await readFlac(handle, size);
+		if (head.toString('latin1', 0, 4) === 'OggS') return readOgg(head);
+		if (head.toString('latin1', 4, 8) === 'ftyp') return await readMp4(handle, size);
+		return await readId3v1(handle, size);
+	} finally {
+		await handle.close();
+	}
+}
+
+async function readAt(handle: FileHandle, position: number, length: number): Promise<Buffer> {
+	const buffer = Buffer.alloc(Math.max(0, length));
+	const { bytesRead } = await handle.read(buffer, 0, buffer.length, position);
+	return buffer.subarray(0, bytesRead);
+}
+
+/** `3/12` and `3` both give 3; anything else gives undefined. */
+function parseNumber(value: string | undefined): number | undefined {
+	const n = Number.parseInt(value ?? '', 10);
+	return Number.isFinite(n) && n > 0 ? n : undefined;
+}
+
+type TextTag = 'artist' | 'albumArtist' | 'album' | 'title';
+
+function setText(tags: AudioTags, key: TextTag, value: string) {
+	const text = value.replace(/\0.*$/s, '').trim();
+	if (text) tags[key] = text;
+}
+
+// ID3v2.3/2.4 frame ids, with their ID3v2.2 equivalents
+const ID3_FRAMES: Record<string, keyof AudioTags> = {
+	TPE1: 'artist',
+	TP1: 'artist',
+	TPE2: 'albumArtist',
+	TP2: 'albumArtist',
+	TALB: 'album',
+	TAL: 'album',
+	TIT2: 'title',
+	TT2: 'title',
+	TRCK: 'track',
+	TRK: 'track',
+	TPOS: 'disc',
+	TPA: 'disc',
+};
+
+function syncsafe(buffer: Buffer, offset: number): number {
+	return (
+		((buffer[offset] ?? 0) << 21) |
+		((buffer[offset + 1] ?? 0) << 14) |
+		((buffer[offset + 2] ?? 0) << 7) |
+		(buffer[offset + 3] ?? 0)
+	);
+}
+
+async function readId3(handle: FileHandle, head: Buffer, size: number): Promise<AudioTags> {
+	const version = head[3] ?? 0;
+	const flags = head[5] ?? 0;
+	const tagSize = syncsafe(head, 6);
+	if (version < 2 || version > 4 || tagSize > MAX_TAG_BYTES) return await readId3v1(handle, size);
+	const tag = await readAt(handle, 10, tagSize);
+	const tags: AudioTags = {};
+	let offset = 0;
+	// An extended header comes first when flagged; v2.4 counts its own size, v2.3 doesn't
+	if (flags & 0x40 && version >= 3) {
+		offset = version === 4 ? syncsafe(tag, 0) : tag.readUInt32BE(0) + 4;
+	}
+	const idLength = version === 2 ? 3 : 4;
+	const headerLength = version === 2 ? 6 : 10;
+	while (offset + headerLength <= tag.length) {
+		const id = tag.toString('latin1', offset, offset + idLength);
+		if (!/^[A-Z0-9]+$/.test(id)) break; // Padding
+		const frameSize =
+			version === 2
+				? tag.readUIntBE(offset + 3, 3)
+				: version === 4
+					? syncsafe(tag, offset + 4)
+					: tag.readUInt32BE(offset + 4);
+		const body = tag.subarray(offset + headerLength, offset + headerLength + frameSize);
+		offset += headerLength + frameSize;
+		const key = ID3_FRAMES[id];
+		if (!key || body.length < 2) continue;
+		const text = decodeId3Text(body);
+		if (key === 'track' || key === 'disc') {
+			const n = parseNumber(text);
+			if (n) tags[key] = n;
+		} else {
+			setText(tags, key, text);
+		}
+	}
+	return Object.keys(tags).length > 0 ? tags : await readId3v1(handle, size);
+}
+
+function decodeId3Text(body: Buffer): string {
+	const encoding = body[0];
+	const data = body.subarray(1);
+	switch (encoding) {
+		case 1:
+			return decodeUtf16(data);
+		case 2:
+			return decodeUtf16BE(data);
+		case 3:
+			return data.toString('utf8');
+		default:
+			return data.toString('latin1');
+	}
+}
+
+function decodeUtf16(data: Buffer): string {
+	if (data[0] === 0xfe && data[1] === 0xff) return decodeUtf16BE(data.subarray(2));
+	const start = data[0] === 0xff && data[1] === 0xfe ? 2 : 0;
+	return data.subarray(start, start + ((data.length - start) & ~1)).toString('utf16le');
+}
+
+function decodeUtf16BE(data: Buffer): string {
+	const swapped = Buffer.from(data.subarray(0, data.length & ~1));
+	return swapped.swap16().toString('utf16le');
+}
+
+async function readId3v1(handle: FileHandle, size: number): Promise<AudioTags> {
+	if (size < 128) return {};
+	const tag = await readAt(handle, size - 128, 128);
+	if (tag.toString('latin1', 0, 3) !== 'TAG') return {};
+	const tags: AudioTags = {};
+	setText(tags, 'title', tag.toString('latin1', 3, 33));
+	setText(tags, 'artist', tag.toString('latin1', 33, 63));
+	setText(tags, 'album', tag.toString('latin1', 63, 93));
+	// ID3v1.1 keeps the track in the comment's last byte
+	if (tag[125] === 0 && tag[126]) tags.track = tag[126];
+	return tags;
+}
+
+const VORBIS_FIELDS: Record<string, keyof AudioTags> = {
+	ARTIST: 'artist',
+	ALBUMARTIST: 'albumArtist',
+	'ALBUM ARTIST': 'albumArtist',
+	ALBUM: 'album',
+	TITLE: 'title',
+	TRACKNUMBER: 'track',
+	DISCNUMBER: 'disc',
+};
+
+/** A Vorbis comment block: vendor string, then `KEY=value` entries, lengths little-endian. */
+function parseVorbisComments(block: Buffer): AudioTags {
+	const tags: AudioTags = {};
+	if (block.length < 8) return tags;
+	let offset = 4 + block.readUInt32LE(0);
+	if (offset + 4 > block.length) return tags;
+	const count = block.readUInt32LE(offset);
+	offset += 4;
+	for (let i = 0; i < count && offset + 4 <= block.length; i++) {
+		const length = block.readUInt32LE(offset);
+		const entry = block.toString('utf8', offset + 4, Math.min(block.length, offset + 4 + length));
+		offset += 4 + length;
+		const separator = entry.indexOf('=');
+		const key = VORBIS_FIELDS[entry.slice(0, separator).toUpperCase()];
+		if (separator < 0 || !key || tags[key] !== undefined) continue;
+		const value = entry.slice(separator + 1);
+		if (key === 'track' || key === 'disc') {
+			const n = parseNumber(value);
+			if (n) tags[key] = n;
+		} else {
+			setText(tags, key, value);
+		}
+	}
+	return tags;
+}
+
+async function readFlac(handle: FileHandle, size: number): Promise<AudioTags> {
+	let offset = 4;
+	while (offset + 4 <= size) {
+		const header = await readAt(handle, offset, 4);
+		if (header.length < 4) break;
+		const type = (header[0] ?? 0) & 0x7f;
+		const length = header.readUIntBE(1, 3);
+		if (type === 4 && length <= MAX_TAG_BYTES) {
+			return parseVorbisComments(await readAt(handle, offset + 4, length));
+		}
+		if ((header[0] ?? 0) & 0x80) break; // Last metadata block
+		offset += 4 + length;
+	}
+	return {};
+}
+
+/**
+ * Ogg keeps comments in the second packet, which nearly always fits in the first pages; page
+ * headers inside it are not stripped, so a comment block spanning pages may lose its tail.
+ */
+function readOgg(head: Buffer): AudioTags {
+	for (const marker of ['\x03vorbis', 'OpusTags']) {
+		const index = head.indexOf(marker, 0, 'latin1');
+		if (index >= 0) return parseVorbisComments(head.subarray(index + marker.length));
+	}
+	return {};
+}
+
+const MP4_ITEMS: Record<string, keyof AudioTags> = {
+	'\xa9ART': 'artist',
+	aART: 'albumArtist',
+	'\xa9alb': 'album',
+	'\xa9nam': 'title',
+	trkn: 'track',
+	disk: 'disc',
+};
+
+/** Child atoms of an MP4 container: `[type, body]` pairs. */
+function* atoms(buffer: Buffer, start = 0): Generator<[string, Buffer]> {
+	let offset = start;
+	while (offset + 8 <= buffer.length) {
+		let size = buffer.readUInt32BE(offset);
+		const type = buffer.toString('latin1', offset + 4, offset + 8);
+		let headerLength = 8;
+		if (size === 1 && offset + 16 <= buffer.length) {
+			size = Number(buffer.readBigUInt64BE(offset + 8));
+			headerLength = 16;
+		} else if (size === 0) {
+			size = buffer.length - offset;
+		}
+		if (size < headerLength) return;
+		yield [type, buffer.subarray(offset + headerLength, offset + size)];
+		offset += size;
+	}
+}
+
+function child(buffer: Buffer, type: string, start = 0): Buffer | undefined {
+	for (const [t, body] of atoms(buffer, start)) if (t === type) return body;
+	return undefined;
+}
+
+async function readMp4(handle: FileHandle, size: number): Promise<AudioTags> {
+	// Find the top-level moov atom, which may come after the media data
+	let offset = 0;
+	let moov: Buffer | undefined;
+	while (offset + 8 <= size) {
+		const header = await readAt(handle, offset, 16);
+		if (header.length < 8) break;
+		let atomSize = header.readUInt32BE(0);
+		if (atomSize === 1 && header.length >= 16) atomSize = Number(header.readBigUInt64BE(8));
+		else if (atomSize === 0) atomSize = size - offset;
+		if (atomSize < 8) break;
+		if (header.toString('latin1', 4, 8) === 'moov') {
+			if (atomSize > MAX_TAG_BYTES * 4) break;
+			moov = (await readAt(handle, offset, atomSize)).subarray(8);
+			break;
+		}
+		offset += atomSize;
+	}
+	const udta = moov && child(moov, 'udta');
+	const meta = udta && child(udta, 'meta');
+	// meta is a full box: four bytes of version and flags precede its children
+	const ilst = meta && child(meta, 'ilst', 4);
+	const tags: AudioTags = {};
+	if (!ilst) return tags;
+	for (const [type, item] of atoms(ilst)) {
+		const key = MP4_ITEMS[type];
+		const data = key && child(item, 'data');
+		// data holds a type indicator and locale before the value
+		if (!key || !data || data.length < 8) continue;
+		const value = data.subarray(8);
+		if (key === 'track' || key === 'disc') {
+			if (value.length >= 4 && value.readUInt16BE(2) > 0) tags[key] = value.readUInt16BE(2);
+		} else {
+			setText(tags, key, value.toString('utf8'));
+		}
+	}
+	return tags;
+}
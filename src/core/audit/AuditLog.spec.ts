@@ -0,0 +1,38 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { AuditLog } from './AuditLog.js';
+
+describe('AuditLog', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-audit-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('is empty before anything is recorded', async () => {
+		expect(await new AuditLog().recent()).toEqual([]);
+	});
+
+	it('returns the newest events first, up to the limit', async () => {
+		const audit = new AuditLog();
+		const directory = '/shots';
+		await Promise.all([
+			audit.record({ kind: 'preview', file: 'a.png', target: 'b.png', directory, timestamp: 1 }),
+			audit.record({ kind: 'skipped', file: 'c.png', directory, timestamp: 2, message: 'exists' }),
+			audit.record({ kind: 'error', file: 'e.png', directory, timestamp: 3, message: 'EACCES' }),
+		]);
+		const recent = await audit.recent(2);
+		expect(recent.map((event) => event.file)).toEqual(['e.png', 'c.png']);
+	});
+});
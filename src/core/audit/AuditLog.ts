@@ -0,0 +1,51 @@
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import type { ServiceFileEvent } from '../../types/service.js';
+import { stateDir } from '../../utils/paths.js';
+import { restoreOwnership } from '../../utils/user.js';
+
+function auditPath() {
+	return path.join(stateDir('namefix'), 'audit.ndjson');
+}
+
+/**
+ * Append-only NDJSON record of file events. Observer mode writes what the service would have done
+ * to each file here, so rules can be evaluated without renaming anything.
+ */
+export class AuditLog {
+	private writes: Promise<void> = Promise.resolve();
+
+	/** Append an event. Writes are serialized so concurrent events never interleave. */
+	record(event: ServiceFileEvent): Promise<void> {
+		const write = this.writes.then(() => this.append(event));
+		this.writes = write.catch(() => {});
+		return write;
+	}
+
+	private async append(event: ServiceFileEvent) {
+		await fs.mkdir(path.dirname(auditPath()), { recursive: true });
+		await fs.appendFile(auditPath(), `${JSON.stringify(event)}\n`, 'utf8');
+		restoreOwnership(auditPath());
+	}
+
+	/** The newest `limit` events, newest first. Lines that don't parse are skipped. */
+	async recent(limit = 200): Promise<ServiceFileEvent[]> {
+		let data: string;
+		try {
+			data = await fs.readFile(auditPath(), 'utf8');
+		} catch (e: unknown) {
+			if ((e as NodeJS.ErrnoException).code === 'ENOENT') return [];
+			throw e;
+		}
+		const events: ServiceFileEvent[] = [];
+		for (const line of data.split(/\r?\n/)) {
+			if (!line) continue;
+			try {
+				events.push(JSON.parse(line));
+			} catch {
+				// A torn final line from a crash mid-write
+			}
+		}
+		return events.slice(-limit).reverse();
+	}
+}
@@ -0,0 +1,42 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { percentile, runBenchmark } from './Benchmark.js';
+import { FsSafe } from '../fs/FsSafe.js';
+import { RenameService } from '../rename/RenameService.js';
+
+describe('percentile', () => {
+	it('uses the nearest rank', () => {
+		const values = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+		expect(percentile(values, 50)).toBe(5);
+		expect(percentile(values, 90)).toBe(9);
+		expect(percentile(values, 99)).toBe(10);
+		expect(percentile([], 50)).toBe(0);
+	});
+});
+
+describe('runBenchmark', () => {
+	let parent: string;
+
+	beforeEach(async () => {
+		parent = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-bench-spec-'));
+	});
+
+	afterEach(async () => {
+		await fs.rm(parent, { recursive: true, force: true });
+	});
+
+	it('processes every file and removes the scratch directory', async () => {
+		const result = await runBenchmark(new RenameService(), new FsSafe(), {
+			files: 20,
+			directory: parent,
+		});
+
+		expect(result.files).toBe(20);
+		expect(result.directory).toBe(parent);
+		expect(result.latencyMs.p50).toBeLessThanOrEqual(result.latencyMs.p99);
+		expect(result.latencyMs.p99).toBeLessThanOrEqual(result.latencyMs.max);
+		expect(await fs.readdir(parent)).toEqual([]);
+	});
+});
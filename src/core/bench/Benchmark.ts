@@ -0,0 +1,113 @@
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { performance } from 'node:perf_hooks';
+import type { IProfile } from '../../types/index.js';
+import type { FsSafe } from '../fs/FsSafe.js';
+import { ProfileMatcher } from '../rename/Matcher.js';
+import { DEFAULT_TEMPLATE } from '../rename/NameTemplate.js';
+import type { RenameService } from '../rename/RenameService.js';
+
+export type BenchmarkOptions = {
+	/** Number of synthetic files to process. */
+	files?: number;
+	/** Parent directory for the scratch folder, to measure a specific disk. Defaults to the OS temp dir. */
+	directory?: string;
+};
+
+export type BenchmarkResult = {
+	files: number;
+	directory: string;
+	startedAt: number;
+	totalMs: number;
+	filesPerSecond: number;
+	latencyMs: { p50: number; p90: number; p99: number; max: number };
+};
+
+const DEFAULT_FILES = 500;
+const MAX_FILES = 10_000;
+
+const BENCHMARK_PROFILE: IProfile = {
+	id: 'benchmark',
+	name: 'Benchmark',
+	enabled: true,
+	pattern: 'Screenshot*',
+	isRegex: false,
+	template: DEFAULT_TEMPLATE,
+	prefix: 'Screenshot',
+	priority: 1,
+};
+
+/**
+ * Runs the match → target → rename path over a scratch directory of synthetic files and reports
+ * throughput and per-file latency. Watching and stability polling are excluded so the numbers
+ * reflect rename work and the disk, not timers. The scratch directory is always removed.
+ */
+export async function runBenchmark(
+	renamer: RenameService,
+	fsSafe: FsSafe,
+	options: BenchmarkOptions = {},
+): Promise<BenchmarkResult> {
+	const files = Math.min(Math.max(1, Math.floor(options.files ?? DEFAULT_FILES)), MAX_FILES);
+	const parent = options.directory ?? os.tmpdir();
+	const scratch = await fs.mkdtemp(path.join(parent, 'namefix-bench-'));
+	const matcher = new ProfileMatcher([BENCHMARK_PROFILE]);
+	const startedAt = Date.now();
+
+	try {
+		const sources: string[] = [];
+		for (let i = 0; i < files; i++) {
+			const source = path.join(scratch, `Screenshot bench ${i}.png`);
+			await fs.writeFile(source, 'namefix');
+			sources.push(source);
+		}
+
+		const latencies: number[] = [];
+		const begin = performance.now();
+		for (const [i, source] of sources.entries()) {
+			const fileStart = performance.now();
+			const basename = path.basename(source);
+			const profile = matcher.match(basename);
+			if (profile && renamer.needsRenameForProfile(basename, profile)) {
+				const { filename } = await renamer.targetForProfile(
+					source,
+					// One second apart so targets don't collide and probe for free names.
+					{ birthtime: new Date(startedAt + i * 1000), ext: '.png' },
+					profile,
+				);
+				await fsSafe.atomicRename(source, path.join(scratch, filename));
+				renamer.release(scratch, filename);
+			}
+			latencies.push(performance.now() - fileStart);
+		}
+		const totalMs = performance.now() - begin;
+
+		latencies.sort((a, b) => a - b);
+		return {
+			files,
+			directory: parent,
+			startedAt,
+			totalMs: round(totalMs),
+			filesPerSecond: round(totalMs > 0 ? (files / totalMs) * 1000 : 0),
+			latencyMs: {
+				p50: round(percentile(latencies, 50)),
+				p90: round(percentile(latencies, 90)),
+				p99: round(percentile(latencies, 99)),
+				max: round(latencies[latencies.length - 1] ?? 0),
+			},
+		};
+	} finally {
+		await fs.rm(scratch, { recursive: true, force: true });
+	}
+}
+
+/** Nearest-rank percentile of an ascending array. */
+export function percentile(sorted: number[], p: number): number {
+	if (sorted.length === 0) return 0;
+	const rank = Math.ceil((p / 100) * sorted.length);
+	return sorted[Math.min(sorted.length, Math.max(1, rank)) - 1] ?? 0;
+}
+
+function round(value: number): number {
+	return Math.round(value * 100) / 100;
+}
@@ -1,9 +1,26 @@
 import fs from 'node:fs/promises';
 import fscb from 'node:fs';
 import path from 'node:path';
-import type { IConfig, IConfigStore, IProfile } from '../../types/index';
+import type {
+	DirectoryOptions,
+	HotFolder,
+	IConfig,
+	IConfigStore,
+	ImportPreset,
+	IProfile,
+	OriginRule,
+	RenameSession,
+	RetentionPolicy,
+} from '../../types/index';
 import { configDir } from '../../utils/paths.js';
-import { DEFAULT_PROFILES, DEFAULT_TEMPLATE, generateProfileId } from '../rename/NameTemplate.js';
+import { restoreOwnership } from '../../utils/user.js';
+import {
+	DEFAULT_PROFILES,
+	DEFAULT_TEMPLATE,
+	generateProfileId,
+	isValidTimeZone,
+} from '../rename/NameTemplate.js';
+import { DEFAULT_POLL_INTERVAL_MS } from '../fs/PollingWatchService.js';
 
 const DEFAULT_WATCH_DIR = process.env.HOME ? path.join(process.env.HOME, 'Desktop') : '';
 
@@ -16,6 +33,22 @@ const DEFAULT_CONFIG: IConfig = {
 	dryRun: false,
 	theme: 'default',
 	launchOnLogin: false,
+	priorityDirs: [],
+	pinnedDirs: [],
+	dryRunDirs: [],
+	observerMode: false,
+	logPrivacy: true,
+	historyRetention: {},
+	checkOpenFiles: false,
+	lanPairing: false,
+	watchBackend: 'fsevents',
+	pollIntervalMs: DEFAULT_POLL_INTERVAL_MS,
+	originRules: {},
+	directoryGroups: {},
+	sessions: [],
+	importPresets: [],
+	hotFolders: {},
+	directoryOptions: {},
 	profiles: [...DEFAULT_PROFILES],
 };
 
@@ -24,6 +57,19 @@ function isStringArray(v: unknown): v is string[] {
 }
 
 const VALID_ACTIONS = new Set(['rename', 'convert', 'rename+convert']);
+const VALID_CASE_STYLES = new Set(['lower', 'upper', 'title', 'kebab', 'snake']);
+const MIN_POLL_INTERVAL_MS = 1_000;
+const VALID_WATCH_BACKENDS = new Set(['fsevents', 'endpoint-security', 'usn-journal', 'polling']);
+/** Older sessions are forgotten; their journal entries keep the session id. */
+const MAX_SESSIONS = 100;
+
+function isMonth(v: unknown): boolean {
+	return Number.isInteger(v) && (v as number) >= 1 && (v as number) <= 12;
+}
+
+function isTimeZone(v: unknown): boolean {
+	return typeof v === 'string' && isValidTimeZone(v);
+}
 
 function isValidProfile(p: unknown): p is IProfile {
 	if (typeof p !== 'object' || p === null) return false;
@@ -39,6 +85,15 @@ function isValidProfile(p: unknown): p is IProfile {
 	if (!baseValid) return false;
 	// action is optional; if present, must be a valid value
 	if (obj.action !== undefined && !VALID_ACTIONS.has(obj.action as string)) return false;
+	// fiscalYearStartMonth is optional; if present, must be a month number
+	if (obj.fiscalYearStartMonth !== undefined && !isMonth(obj.fiscalYearStartMonth)) return false;
+	// timeZone is optional; if present, must be a zone the runtime knows
+	if (obj.timeZone !== undefined && !isTimeZone(obj.timeZone)) return false;
+	// caseStyle and datePrefix are optional; if present, must be a known style and a flag
+	if (obj.caseStyle !== undefined && !VALID_CASE_STYLES.has(obj.caseStyle as string)) return false;
+	if (obj.datePrefix !== undefined && typeof obj.datePrefix !== 'boolean') return false;
+	// condition is optional; if present, must be an expression's text
+	if (obj.condition !== undefined && typeof obj.condition !== 'string') return false;
 	return true;
 }
 
@@ -108,6 +163,128 @@ function migrateToProfiles(input: Partial<IConfig>): IProfile[] {
 	return profiles;
 }
 
+function sanitizeRetention(input: unknown): RetentionPolicy {
+	if (typeof input !== 'object' || input === null) return {};
+	const raw = input as Record<string, unknown>;
+	const policy: RetentionPolicy = {};
+	for (const key of ['maxAgeDays', 'maxEntries', 'maxBytes'] as const) {
+		const value = raw[key];
+		if (typeof value === 'number' && Number.isFinite(value) && value >= 0) {
+			policy[key] = Math.floor(value);
+		}
+	}
+	return policy;
+}
+
+function sanitizeOriginRules(input: unknown): Record<string, OriginRule> {
+	if (typeof input !== 'object' || input === null) return {};
+	const rules: Record<string, OriginRule> = {};
+	for (const [dir, value] of Object.entries(input as Record<string, unknown>)) {
+		if (typeof value !== 'object' || value === null) continue;
+		const raw = value as Record<string, unknown>;
+		const rule: OriginRule = {};
+		if (isStringArray(raw.allow) && raw.allow.length > 0) rule.allow = raw.allow;
+		if (isStringArray(raw.deny) && raw.deny.length > 0) rule.deny = raw.deny;
+		if (rule.allow || rule.deny) rules[path.resolve(dir)] = rule;
+	}
+	return rules;
+}
+
+function sanitizeHotFolders(input: unknown): Record<string, HotFolder> {
+	if (typeof input !== 'object' || input === null) return {};
+	const hotFolders: Record<string, HotFolder> = {};
+	for (const [dir, value] of Object.entries(input as Record<string, unknown>)) {
+		if (typeof value !== 'object' || value === null) continue;
+		const raw = value as Record<string, unknown>;
+		const hotFolder: HotFolder = {};
+		if (typeof raw.destination === 'string' && raw.destination.trim().length > 0) {
+			hotFolder.destination = path.resolve(raw.destination.trim());
+		}
+		if (isStringArray(raw.command) && raw.command.length > 0 && raw.command[0]?.trim()) {
+			hotFolder.command = raw.command;
+		}
+		if (hotFolder.destination || hotFolder.command) hotFolders[path.resolve(dir)] = hotFolder;
+	}
+	return hotFolders;
+}
+
+/** Lowercase, without leading dots or duplicates, so `.PNG` and `png` are the same extension. */
+export function normalizeExtensions(input: unknown): string[] {
+	if (!isStringArray(input)) return [];
+	const extensions = input.map((ext) => ext.trim().replace(/^\.+/, '').toLowerCase());
+	return [...new Set(extensions.filter((ext) => ext.length > 0))];
+}
+
+function sanitizeDirectoryOptions(input: unknown): Record<string, DirectoryOptions> {
+	if (typeof input !== 'object' || input === null) return {};
+	const options: Record<string, DirectoryOptions> = {};
+	for (const [dir, value] of Object.entries(input as Record<string, unknown>)) {
+		if (typeof value !== 'object' || value === null) continue;
+		const raw = value as Record<string, unknown>;
+		const entry: DirectoryOptions = {};
+		if (raw.recursive === true) entry.recursive = true;
+		const include = normalizeExtensions(raw.includeExtensions);
+		if (include.length > 0) entry.includeExtensions = include;
+		const exclude = normalizeExtensions(raw.excludeExtensions);
+		if (exclude.length > 0) entry.excludeExtensions = exclude;
+		if (Object.keys(entry).length > 0) options[path.resolve(dir)] = entry;
+	}
+	return options;
+}
+
+function sanitizeDirectoryGroups(input: unknown): Record<string, string> {
+	if (typeof input !== 'object' || input === null) return {};
+	const groups: Record<string, string> = {};
+	for (const [dir, value] of Object.entries(input as Record<string, unknown>)) {
+		if (typeof value === 'string' && value.trim().length > 0) groups[path.resolve(dir)] = value.trim();
+	}
+	return groups;
+}
+
+function sanitizeSessions(input: unknown): RenameSession[] {
+	if (!Array.isArray(input)) return [];
+	const sessions: RenameSession[] = [];
+	for (const value of input) {
+		if (typeof value !== 'object' || value === null) continue;
+		const raw = value as Record<string, unknown>;
+		if (typeof raw.id !== 'string' || raw.id.length === 0) continue;
+		if (typeof raw.name !== 'string' || raw.name.trim().length === 0) continue;
+		if (typeof raw.startedAt !== 'number' || !Number.isFinite(raw.startedAt)) continue;
+		const session: RenameSession = { id: raw.id, name: raw.name.trim(), startedAt: raw.startedAt };
+		if (typeof raw.endedAt === 'number' && Number.isFinite(raw.endedAt))
+			session.endedAt = raw.endedAt;
+		sessions.push(session);
+	}
+	// Only the last session can still be running
+	for (let i = 0; i < sessions.length - 1; i++) {
+		const session = sessions[i] as RenameSession;
+		session.endedAt ??= (sessions[i + 1] as RenameSession).startedAt;
+	}
+	return sessions.slice(-MAX_SESSIONS);
+}
+
+function sanitizeImportPresets(input: unknown): ImportPreset[] {
+	if (!Array.isArray(input)) return [];
+	const presets: ImportPreset[] = [];
+	for (const value of input) {
+		if (typeof value !== 'object' || value === null) continue;
+		const raw = value as Record<string, unknown>;
+		if (typeof raw.id !== 'string' || raw.id.length === 0) continue;
+		if (typeof raw.destination !== 'string' || raw.destination.trim().length === 0) continue;
+		if (presets.some((preset) => preset.id === raw.id)) continue;
+		presets.push({
+			id: raw.id,
+			name: typeof raw.name === 'string' && raw.name.trim() ? raw.name.trim() : 'Camera Import',
+			enabled: raw.enabled !== false,
+			template: typeof raw.template === 'string' && raw.template ? raw.template : DEFAULT_TEMPLATE,
+			prefix: typeof raw.prefix === 'string' ? raw.prefix : 'IMG',
+			destination: path.resolve(raw.destination),
+			eject: raw.eject === true,
+		});
+	}
+	return presets;
+}
+
 function validateConfig(input: Partial<IConfig>): IConfig {
 	const cfg: IConfig = { ...DEFAULT_CONFIG, ...input } as IConfig;
 	const candidateDirs = Array.isArray(input.watchDirs)
@@ -131,6 +308,44 @@ function validateConfig(input: Partial<IConfig>): IConfig {
 	if (typeof cfg.dryRun !== 'boolean') cfg.dryRun = DEFAULT_CONFIG.dryRun;
 	if (typeof cfg.theme !== 'string' || cfg.theme.length === 0) cfg.theme = DEFAULT_CONFIG.theme;
 	if (typeof cfg.launchOnLogin !== 'boolean') cfg.launchOnLogin = DEFAULT_CONFIG.launchOnLogin;
+	cfg.priorityDirs = isStringArray(cfg.priorityDirs)
+		? cfg.priorityDirs.map((dir) => path.resolve(dir))
+		: [];
+	cfg.pinnedDirs = isStringArray(cfg.pinnedDirs)
+		? cfg.pinnedDirs.map((dir) => path.resolve(dir))
+		: [];
+	cfg.dryRunDirs = isStringArray(cfg.dryRunDirs) ? cfg.dryRunDirs.map((dir) => path.resolve(dir)) : [];
+	if (typeof cfg.observerMode !== 'boolean') cfg.observerMode = DEFAULT_CONFIG.observerMode;
+	if (typeof cfg.logPrivacy !== 'boolean') cfg.logPrivacy = DEFAULT_CONFIG.logPrivacy;
+	if (typeof cfg.dataDir === 'string' && cfg.dataDir.trim().length > 0) {
+		cfg.dataDir = path.resolve(cfg.dataDir);
+	} else {
+		cfg.dataDir = undefined;
+	}
+	if (typeof cfg.syncDir === 'string' && cfg.syncDir.trim().length > 0) {
+		cfg.syncDir = path.resolve(cfg.syncDir);
+	} else {
+		cfg.syncDir = undefined;
+	}
+	cfg.historyRetention = sanitizeRetention(cfg.historyRetention);
+	if (typeof cfg.checkOpenFiles !== 'boolean') cfg.checkOpenFiles = DEFAULT_CONFIG.checkOpenFiles;
+	if (typeof cfg.lanPairing !== 'boolean') cfg.lanPairing = DEFAULT_CONFIG.lanPairing;
+	if (!VALID_WATCH_BACKENDS.has(cfg.watchBackend as string)) {
+		cfg.watchBackend = DEFAULT_CONFIG.watchBackend;
+	}
+	if (
+		typeof cfg.pollIntervalMs !== 'number' ||
+		!Number.isFinite(cfg.pollIntervalMs) ||
+		cfg.pollIntervalMs < MIN_POLL_INTERVAL_MS
+	) {
+		cfg.pollIntervalMs = DEFAULT_CONFIG.pollIntervalMs;
+	}
+	cfg.originRules = sanitizeOriginRules(cfg.originRules);
+	cfg.directoryGroups = sanitizeDirectoryGroups(cfg.directoryGroups);
+	cfg.sessions = sanitizeSessions(cfg.sessions);
+	cfg.importPresets = sanitizeImportPresets(cfg.importPresets);
+	cfg.hotFolders = sanitizeHotFolders(cfg.hotFolders);
+	cfg.directoryOptions = sanitizeDirectoryOptions(cfg.directoryOptions);
 
 	// Handle profiles migration and validation
 	cfg.profiles = migrateToProfiles(input);
@@ -237,6 +452,8 @@ export class ConfigStore implements IConfigStore {
 			} catch {
 				/* ignore */
 			}
+			restoreOwnership(cfgDir);
+			restoreOwnership(configFile);
 		} catch {
 			// In read-only/sandboxed env, skip persistence
 		}
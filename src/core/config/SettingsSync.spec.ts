@@ -0,0 +1,176 @@
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import type { IConfig, IConfigStore } from '../../types/index';
+import { SettingsSync, SYNC_FILE } from './SettingsSync.js';
+import type { ISyncKeyStore } from './SyncKey.js';
+
+const noopLogger = { info: () => {}, warn: () => {}, error: () => {} };
+
+class MemoryConfigStore implements IConfigStore {
+	constructor(private cfg: IConfig) {}
+
+	async get(): Promise<IConfig> {
+		return { ...this.cfg };
+	}
+
+	async set(next: Partial<IConfig>): Promise<IConfig> {
+		this.cfg = { ...this.cfg, ...next };
+		return this.get();
+	}
+
+	onChange(): () => void {
+		return () => {};
+	}
+}
+
+class MemoryKeyStore implements ISyncKeyStore {
+	key: Buffer | null = null;
+
+	async load(): Promise<Buffer | null> {
+		return this.key;
+	}
+
+	async save(key: Buffer): Promise<void> {
+		this.key = key;
+	}
+}
+
+const baseConfig = {
+	watchDir: '/Users/me/Desktop',
+	watchDirs: ['/Users/me/Desktop'],
+	prefix: 'Screenshot',
+	include: ['Screenshot*'],
+	exclude: [],
+	dryRun: false,
+	theme: 'default',
+	launchOnLogin: false,
+	profiles: [],
+} as IConfig;
+
+describe('SettingsSync', () => {
+	let tempRoot: string;
+	let syncDir: string;
+	/** The key every machine shares unless given its own, as if already paired. */
+	let pairedKeys: MemoryKeyStore;
+	const syncs: SettingsSync[] = [];
+
+	/** A machine with its own config and sync state, syncing through `syncDir`. */
+	const machine = (name: string, overrides: Partial<IConfig> = {}, keys = pairedKeys) => {
+		const store = new MemoryConfigStore({ ...baseConfig, ...overrides });
+		const statePath = path.join(tempRoot, name, 'sync-state.json');
+		const sync = new SettingsSync(store, noopLogger, () => {}, statePath, keys);
+		syncs.push(sync);
+		return { store, sync };
+	};
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-sync-'));
+		syncDir = path.join(tempRoot, 'iCloud');
+		await fs.mkdir(syncDir);
+		pairedKeys = new MemoryKeyStore();
+	});
+
+	afterEach(async () => {
+		for (const sync of syncs.splice(0)) sync.dispose();
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('shares changes made on one machine with the other, but not machine settings', async () => {
+		const a = machine('a');
+		await a.sync.setDir(syncDir);
+
+		const b = machine('b', { launchOnLogin: true });
+		await b.sync.setDir(syncDir);
+		expect(b.sync.getStatus().state).toBe('ok');
+
+		await b.store.set({ prefix: 'Shot' });
+		await b.sync.localChanged();
+		await a.sync.refresh();
+		expect((await a.store.get()).prefix).toBe('Shot');
+		expect((await a.store.get()).launchOnLogin).toBe(false);
+		expect(a.sync.getStatus().state).toBe('ok');
+	});
+
+	it('reports a conflict when both machines changed settings and keeps the chosen side', async () => {
+		const a = machine('a');
+		const b = machine('b');
+		await a.sync.setDir(syncDir);
+		await b.sync.setDir(syncDir);
+
+		await a.store.set({ prefix: 'FromA' });
+		await a.sync.localChanged();
+		await b.store.set({ prefix: 'FromB' });
+		await b.sync.localChanged();
+		expect(b.sync.getStatus()).toMatchObject({
+			state: 'conflict',
+			conflict: { deviceName: os.hostname() },
+		});
+
+		await b.sync.resolve('remote');
+		expect((await b.store.get()).prefix).toBe('FromA');
+		expect(b.sync.getStatus().state).toBe('ok');
+	});
+
+	it('treats joining a folder with different settings as a conflict', async () => {
+		await machine('a').sync.setDir(syncDir);
+		const b = machine('b', { dryRun: true });
+		await b.sync.setDir(syncDir);
+		expect(b.sync.getStatus().state).toBe('conflict');
+
+		await b.sync.resolve('local');
+		const c = machine('c');
+		await c.sync.setDir(syncDir);
+		await c.sync.resolve('remote');
+		expect((await c.store.get()).dryRun).toBe(true);
+	});
+
+	it('encrypts the settings so only paired machines can read them', async () => {
+		const a = machine('a', { include: ['~/Clients/Acme/*'] });
+		await a.sync.setDir(syncDir);
+		const raw = await fs.readFile(path.join(syncDir, SYNC_FILE), 'utf8');
+		expect(raw).not.toContain('Acme');
+		expect(JSON.parse(raw)).not.toHaveProperty('settings');
+
+		const b = machine('b', {}, new MemoryKeyStore());
+		await b.sync.setDir(syncDir);
+		expect(b.sync.getStatus().state).toBe('locked');
+		await expect(b.sync.importKey('not a key')).rejects.toThrow('Not a Namefix pairing key');
+
+		await b.sync.importKey(await a.sync.exportKey());
+		expect(b.sync.getStatus().state).toBe('conflict');
+		await b.sync.resolve('remote');
+		expect((await b.store.get()).include).toEqual(['~/Clients/Acme/*']);
+	});
+
+	it('rejects a settings file that was altered', async () => {
+		const a = machine('a');
+		await a.sync.setDir(syncDir);
+		const file = path.join(syncDir, SYNC_FILE);
+		const written = JSON.parse(await fs.readFile(file, 'utf8'));
+		await fs.writeFile(file, JSON.stringify({ ...written, deviceName: 'Mallory' }));
+
+		await a.sync.refresh();
+		expect(a.sync.getStatus()).toMatchObject({ state: 'error' });
+	});
+
+	it('refuses settings files that are not encrypted', async () => {
+		const plain = {
+			version: 1,
+			device: 'mallory',
+			deviceName: 'Mallory',
+			updatedAt: Date.now(),
+			clock: { mallory: 5 },
+			settings: { include: ['~/Secrets/*'] },
+		};
+		await fs.writeFile(path.join(syncDir, SYNC_FILE), JSON.stringify(plain));
+		const a = machine('a');
+		await a.sync.setDir(syncDir);
+		expect(a.sync.getStatus()).toMatchObject({
+			state: 'error',
+			detail: `${SYNC_FILE} is not a Namefix settings file`,
+		});
+		expect((await a.store.get()).include).toEqual(['Screenshot*']);
+	});
+});
@@ -0,0 +1,422 @@
+import { randomUUID } from 'node:crypto';
+import fscb from 'node:fs';
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import type {
+	IConfig,
+	IConfigStore,
+	ILogger,
+	SettingsSyncStatus,
+	SyncResolution,
+} from '../../types/index';
+import { configDir } from '../../utils/paths.js';
+import { restoreOwnership } from '../../utils/user.js';
+import {
+	decodePairingKey,
+	encodePairingKey,
+	generateKey,
+	type ISyncKeyStore,
+	KeychainKeyStore,
+	keyId,
+	type Sealed,
+	seal,
+	unseal,
+} from './SyncKey.js';
+
+/** The settings file kept in the synced folder. */
+export const SYNC_FILE = 'namefix-settings.json';
+
+/** Settings that describe this machine rather than the user's rules, so they never sync. */
+const LOCAL_KEYS: (keyof IConfig)[] = [
+	'syncDir',
+	'lanPairing',
+	'dataDir',
+	'launchOnLogin',
+	'watchBackend',
+	'pollIntervalMs',
+	'checkOpenFiles',
+	'sessions',
+];
+
+/** How long a changed settings file is left to settle before it is read. */
+const SETTLE_MS = 500;
+
+/** How many changes each machine has written to the settings file: a vector clock. */
+type Clock = Record<string, number>;
+
+type SyncHeader = {
+	version: 2;
+	device: string;
+	deviceName: string;
+	updatedAt: number;
+	clock: Clock;
+};
+
+/** The settings file, decrypted. */
+type SyncFile = SyncHeader & { settings: Partial<IConfig> };
+
+/** The synced settings are encrypted with a key this machine doesn't have. */
+class SyncLockedError extends Error {
+	constructor(deviceName: string) {
+		super(
+			`Settings synced from ${deviceName} are encrypted with a key this machine doesn't have. ` +
+				'Import the pairing key from that machine.',
+		);
+	}
+}
+
+/** What this machine remembers between runs about the folder it syncs through. */
+type SyncState = {
+	device: string;
+	dir: string | null;
+	/** The changes already reflected in this machine's settings. */
+	clock: Clock;
+	/** Settings changed here since they were last written to the folder. */
+	dirty: boolean;
+};
+
+/**
+ * Keeps config and rules (not history) in step across machines through a file in a folder the
+ * user syncs with iCloud Drive, Dropbox or similar. Each machine counts the changes it writes; a
+ * file carrying changes this machine hasn't seen is applied, unless settings also changed here,
+ * which is a conflict the user settles with {@link SettingsSync.resolve}.
+ *
+ * Rules name folders, so the settings are encrypted with a key kept in the keychain and the sync
+ * provider only sees the clock. Other machines get the key as a pairing key, see
+ * {@link SettingsSync.exportKey}.
+ */
+export class SettingsSync {
+	private state: SyncState | null = null;
+	private status: SettingsSyncStatus = { dir: null, state: 'off' };
+	private watcher: fscb.FSWatcher | null = null;
+	private settle: NodeJS.Timeout | null = null;
+	private chain: Promise<void> = Promise.resolve();
+	/** Set while synced settings are written into the config, so they aren't synced back. */
+	private applying = false;
+
+	constructor(
+		private readonly configStore: IConfigStore,
+		private readonly logger: ILogger,
+		private readonly onStatusChange: (status: SettingsSyncStatus) => void = () => {},
+		private readonly stateFile = path.join(configDir('namefix'), 'sync-state.json'),
+		private readonly keys: ISyncKeyStore = new KeychainKeyStore(),
+	) {}
+
+	getStatus(): SettingsSyncStatus {
+		return { ...this.status };
+	}
+
+	/**
+	 * Sync through `dir`, or stop when null. A folder joined for the first time counts as changed
+	 * here, so settings that differ from its file are a conflict rather than silently replaced.
+	 */
+	setDir(dir: string | null): Promise<void> {
+		return this.enqueue(async () => {
+			const state = await this.loadState();
+			if (dir === null ? state.dir === null : dir === this.status.dir && this.watcher) return;
+			this.unwatch();
+			if (state.dir !== dir) {
+				Object.assign(state, { dir, clock: {}, dirty: dir !== null });
+				await this.saveState();
+			}
+			if (!dir) {
+				this.setStatus({ dir: null, state: 'off' });
+				return;
+			}
+			this.watch(dir);
+			await this.reconcile();
+		});
+	}
+
+	/** Settings changed here; write them to the synced folder. */
+	localChanged(): Promise<void> {
+		if (this.applying || !this.status.dir) return Promise.resolve();
+		return this.enqueue(async () => {
+			if (!this.state) return;
+			this.state.dirty = true;
+			await this.reconcile();
+		});
+	}
+
+	/** Read the synced folder's settings file again. Changes to it are also picked up on their own. */
+	refresh(): Promise<void> {
+		return this.enqueue(() => this.reconcile());
+	}
+
+	/** Settle a conflict by keeping this machine's settings or the synced folder's. */
+	resolve(keep: SyncResolution): Promise<void> {
+		return this.enqueue(async () => {
+			const state = this.state;
+			if (!state?.dir) throw new Error('Settings sync is off');
+			const remote = await this.read(state.dir);
+			if (keep === 'remote' && remote) await this.apply(remote);
+			else await this.write(state.dir, remote);
+		});
+	}
+
+	/** The pairing key another machine imports to read the synced settings, created if need be. */
+	exportKey(): Promise<string> {
+		return this.enqueue(async () => encodePairingKey(await this.key()));
+	}
+
+	/** Use the key exported by another machine, replacing this one's, and read the settings again. */
+	importKey(code: string): Promise<void> {
+		return this.enqueue(async () => {
+			await this.keys.save(decodePairingKey(code));
+			this.logger.info('Imported a settings sync pairing key');
+			await this.reconcile();
+		});
+	}
+
+	dispose(): void {
+		this.unwatch();
+	}
+
+	/** Bring this machine and the synced folder's file into agreement, if it can be done safely. */
+	private async reconcile() {
+		const state = this.state;
+		if (!state?.dir) return;
+		try {
+			const remote = await this.read(state.dir);
+			const local = syncedSettings(await this.configStore.get());
+			if (remote && sameSettings(local, remote.settings)) {
+				state.clock = mergeClocks(state.clock, remote.clock);
+				state.dirty = false;
+				await this.saveState();
+				this.setStatus({ dir: state.dir, state: 'ok', lastSyncedAt: remote.updatedAt });
+			} else if (remote && !covers(state.clock, remote.clock)) {
+				if (state.dirty) {
+					const conflict = { deviceName: remote.deviceName, updatedAt: remote.updatedAt };
+					this.setStatus({ dir: state.dir, state: 'conflict', conflict });
+				} else {
+					await this.apply(remote);
+				}
+			} else {
+				await this.write(state.dir, remote);
+			}
+		} catch (e: unknown) {
+			const detail = e instanceof Error ? e.message : String(e);
+			if (e instanceof SyncLockedError) {
+				this.logger.warn('Synced settings are encrypted with another key', { dir: state.dir });
+				this.setStatus({ dir: state.dir, state: 'locked', detail });
+				return;
+			}
+			this.logger.warn('Settings sync failed', { dir: state.dir, error: detail });
+			this.setStatus({ dir: state.dir, state: 'error', detail });
+		}
+	}
+
+	/** Take the synced settings, keeping this machine's own. */
+	private async apply(remote: SyncFile) {
+		const state = this.state as SyncState;
+		this.applying = true;
+		try {
+			await this.configStore.set(syncedSettings(remote.settings));
+		} finally {
+			this.applying = false;
+		}
+		state.clock = mergeClocks(state.clock, remote.clock);
+		state.dirty = false;
+		await this.saveState();
+		this.logger.info('Applied synced settings', { from: remote.deviceName });
+		this.setStatus({ dir: state.dir, state: 'ok', lastSyncedAt: remote.updatedAt });
+	}
+
+	/** Write this machine's settings to the synced folder as the newest change. */
+	private async write(dir: string, remote: SyncFile | null) {
+		const state = this.state as SyncState;
+		const clock = mergeClocks(state.clock, remote?.clock ?? {});
+		clock[state.device] = (clock[state.device] ?? 0) + 1;
+		const header: SyncHeader = {
+			version: 2,
+			device: state.device,
+			deviceName: os.hostname(),
+			updatedAt: Date.now(),
+			clock,
+		};
+		const settings = JSON.stringify(syncedSettings(await this.configStore.get()));
+		const encrypted = seal(await this.key(), settings, stableStringify(header));
+		const file = `${JSON.stringify({ ...header, encrypted }, null, 2)}\n`;
+		await writeAtomic(path.join(dir, SYNC_FILE), file);
+		state.clock = clock;
+		state.dirty = false;
+		await this.saveState();
+		this.setStatus({ dir, state: 'ok', lastSyncedAt: header.updatedAt });
+	}
+
+	/** The settings file in `dir`, decrypted, or null if there isn't one yet. */
+	private async read(dir: string): Promise<SyncFile | null> {
+		const parsed = await readSyncFile(dir);
+		if (!parsed) return null;
+		const { device, deviceName, updatedAt, clock, encrypted } = parsed;
+		const header: SyncHeader = { version: 2, device, deviceName, updatedAt, clock };
+		const key = await this.keys.load();
+		if (!key || keyId(key) !== encrypted.keyId) throw new SyncLockedError(header.deviceName);
+		let settings: Partial<IConfig>;
+		try {
+			settings = JSON.parse(unseal(key, encrypted, stableStringify(header)));
+		} catch {
+			throw new Error(`${SYNC_FILE} was changed outside Namefix or is damaged`);
+		}
+		return { ...header, settings };
+	}
+
+	/** This machine's key, created on first use. */
+	private async key(): Promise<Buffer> {
+		const existing = await this.keys.load();
+		if (existing) return existing;
+		const key = generateKey();
+		await this.keys.save(key);
+		this.logger.info('Created a settings sync key');
+		return key;
+	}
+
+	private watch(dir: string) {
+		try {
+			this.watcher = fscb.watch(dir, (_event, filename) => {
+				if (filename && filename.toString() !== SYNC_FILE) return;
+				if (this.settle) clearTimeout(this.settle);
+				this.settle = setTimeout(() => {
+					this.settle = null;
+					this.refresh().catch(() => {});
+				}, SETTLE_MS);
+			});
+			this.watcher.on('error', (err) => {
+				this.logger.warn('Stopped watching the synced folder', { dir, error: err.message });
+			});
+			this.watcher.unref();
+		} catch (e: unknown) {
+			// reconcile() reports the folder as unusable
+			this.logger.warn('Could not watch the synced folder', {
+				dir,
+				error: e instanceof Error ? e.message : String(e),
+			});
+		}
+	}
+
+	private unwatch() {
+		if (this.settle) clearTimeout(this.settle);
+		this.settle = null;
+		this.watcher?.close();
+		this.watcher = null;
+	}
+
+	private setStatus(status: SettingsSyncStatus) {
+		this.status = status;
+		this.onStatusChange(this.getStatus());
+	}
+
+	private enqueue<T>(task: () => Promise<T>): Promise<T> {
+		const run = this.chain.then(task);
+		this.chain = run.then(
+			() => {},
+			() => {},
+		);
+		return run;
+	}
+
+	private async loadState(): Promise<SyncState> {
+		if (this.state) return this.state;
+		let saved: Partial<SyncState> = {};
+		try {
+			saved = JSON.parse(await fs.readFile(this.stateFile, 'utf8'));
+		} catch {
+			// First run, or unreadable: start over with a new device id
+		}
+		this.state = {
+			device: typeof saved.device === 'string' ? saved.device : randomUUID(),
+			dir: typeof saved.dir === 'string' ? saved.dir : null,
+			clock: isClock(saved.clock) ? saved.clock : {},
+			dirty: saved.dirty === true,
+		};
+		return this.state;
+	}
+
+	private async saveState() {
+		try {
+			await fs.mkdir(path.dirname(this.stateFile), { recursive: true });
+			await writeAtomic(this.stateFile, JSON.stringify(this.state, null, 2));
+		} catch (e: unknown) {
+			this.logger.warn('Could not save settings sync state', {
+				error: e instanceof Error ? e.message : String(e),
+			});
+		}
+	}
+}
+
+/** `cfg` without the settings that belong to this machine. */
+function syncedSettings(cfg: Partial<IConfig>): Partial<IConfig> {
+	const settings: Partial<IConfig> = { ...cfg };
+	for (const key of LOCAL_KEYS) delete settings[key];
+	return settings;
+}
+
+function sameSettings(a: Partial<IConfig>, b: Partial<IConfig>): boolean {
+	return stableStringify(a) === stableStringify(syncedSettings(b));
+}
+
+/** JSON with object keys sorted, so two machines' copies compare equal whatever their order. */
+function stableStringify(value: unknown): string {
+	return JSON.stringify(value, (_key, inner) => {
+		if (inner === null || typeof inner !== 'object' || Array.isArray(inner)) return inner;
+		return Object.fromEntries(Object.entries(inner).sort(([a], [b]) => (a < b ? -1 : 1)));
+	});
+}
+
+/** Whether `clock` has seen every change `other` has. */
+function covers(clock: Clock, other: Clock): boolean {
+	return Object.entries(other).every(([device, count]) => (clock[device] ?? 0) >= count);
+}
+
+function mergeClocks(a: Clock, b: Clock): Clock {
+	const merged = { ...a };
+	for (const [device, count] of Object.entries(b)) {
+		merged[device] = Math.max(merged[device] ?? 0, count);
+	}
+	return merged;
+}
+
+function isClock(value: unknown): value is Clock {
+	return (
+		typeof value === 'object' &&
+		value !== null &&
+		Object.values(value).every((count) => Number.isInteger(count))
+	);
+}
+
+/** The settings file in `dir` as stored, or null if there isn't one yet. */
+async function readSyncFile(
+	dir: string,
+): Promise<(SyncHeader & { encrypted: Sealed }) | null> {
+	let raw: string;
+	try {
+		raw = await fs.readFile(path.join(dir, SYNC_FILE), 'utf8');
+	} catch (e: unknown) {
+		if ((e as NodeJS.ErrnoException).code === 'ENOENT') {
+			await fs.access(dir);
+			return null;
+		}
+		throw e;
+	}
+	const parsed = JSON.parse(raw);
+	// Only encrypted files are read; settings in the clear could come from anyone with the folder
+	const encrypted = parsed?.version === 2 && parsed.encrypted;
+	if (!isClock(parsed?.clock) || typeof encrypted !== 'object' || encrypted === null) {
+		throw new Error(`${SYNC_FILE} is not a Namefix settings file`);
+	}
+	return parsed;
+}
+
+/** Replace `file` in one step; synced folders upload half-written files otherwise. */
+async function writeAtomic(file: string, data: string) {
+	const tmp = path.join(path.dirname(file), `.${path.basename(file)}.tmp`);
+	try {
+		await fs.writeFile(tmp, data, 'utf8');
+		await fs.rename(tmp, file);
+	} catch (e: unknown) {
+		await fs.rm(tmp, { force: true }).catch(() => {});
+		throw e;
+	}
+	restoreOwnership(file);
+}
@@ -0,0 +1,168 @@
+import { execFile as execFileCb, spawn } from 'node:child_process';
+import { createCipheriv, createDecipheriv, createHash, randomBytes } from 'node:crypto';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { promisify } from 'node:util';
+import { configDir } from '../../utils/paths.js';
+import { restoreOwnership } from '../../utils/user.js';
+
+const execFile = promisify(execFileCb);
+
+/** The login keychain item holding the key, on macOS. */
+const KEYCHAIN_SERVICE = 'namefix-settings-sync';
+const KEYCHAIN_ACCOUNT = 'namefix';
+/** `security` exits with this when the keychain has no such item. */
+const ITEM_NOT_FOUND = 44;
+
+const KEY_BYTES = 32;
+const IV_BYTES = 12;
+/** GCM's full tag; fixed when decrypting so a shortened tag is refused rather than checked. */
+const TAG_BYTES = 16;
+const CIPHER = 'aes-256-gcm';
+/** Starts every pairing key, so a pasted value can be told apart from other text. */
+const PAIRING_PREFIX = 'namefix-sync-';
+
+/** Where this machine keeps the key synced settings are encrypted with. */
+export interface ISyncKeyStore {
+	load(): Promise<Buffer | null>;
+	save(key: Buffer): Promise<void>;
+}
+
+/** Settings encrypted with {@link seal}, as stored in the synced folder. */
+export type Sealed = {
+	/** {@link keyId} of the key they were encrypted with. */
+	keyId: string;
+	iv: string;
+	tag: string;
+	data: string;
+};
+
+/**
+ * The key in the login keychain on macOS, through the `security` tool. Elsewhere there is no
+ * keychain to use, so it is kept in `sync.key` in the config directory, readable only by the user.
+ */
+export class KeychainKeyStore implements ISyncKeyStore {
+	constructor(
+		private readonly platform: NodeJS.Platform = process.platform,
+		private readonly keyFile = path.join(configDir('namefix'), 'sync.key'),
+	) {}
+
+	async load(): Promise<Buffer | null> {
+		if (this.platform !== 'darwin') {
+			try {
+				return decodeKey(await fs.readFile(this.keyFile, 'utf8'));
+			} catch (e: unknown) {
+				if ((e as NodeJS.ErrnoException).code === 'ENOENT') return null;
+				throw e;
+			}
+		}
+		try {
+			const { stdout } = await execFile(
+				'security',
+				['find-generic-password', '-s', KEYCHAIN_SERVICE, '-a', KEYCHAIN_ACCOUNT, '-w'],
+				{ timeout: 5_000 },
+			);
+			return decodeKey(stdout);
+		} catch (e: unknown) {
+			if ((e as { code?: unknown }).code === ITEM_NOT_FOUND) return null;
+			throw e;
+		}
+	}
+
+	async save(key: Buffer): Promise<void> {
+		const encoded = key.toString('base64');
+		if (this.platform !== 'darwin') {
+			await fs.mkdir(path.dirname(this.keyFile), { recursive: true });
+			await fs.writeFile(this.keyFile, `${encoded}\n`, { mode: 0o600 });
+			await fs.chmod(this.keyFile, 0o600);
+			restoreOwnership(this.keyFile);
+			return;
+		}
+		await securityInteractive([
+			'add-generic-password',
+			'-U',
+			'-s',
+			KEYCHAIN_SERVICE,
+			'-a',
+			KEYCHAIN_ACCOUNT,
+			'-w',
+			encoded,
+		]);
+	}
+}
+
+/**
+ * Run one `security` command by writing it to `security -i` on stdin, so a secret among `args`
+ * doesn't show in the process list the way an argument would. Interactive mode exits cleanly
+ * whatever the command did, so anything it reports on stderr counts as a failure.
+ */
+function securityInteractive(args: string[]): Promise<void> {
+	return new Promise((resolve, reject) => {
+		const child = spawn('security', ['-i'], { stdio: ['pipe', 'ignore', 'pipe'], timeout: 5_000 });
+		let stderr = '';
+		child.stderr.on('data', (chunk: Buffer) => {
+			stderr += chunk.toString('utf8');
+		});
+		child.on('error', reject);
+		child.on('close', (code) => {
+			if (code === 0 && !stderr.trim()) resolve();
+			else reject(new Error(stderr.trim() || `security exited with ${code}`));
+		});
+		const [command, ...rest] = args;
+		const quoted = rest.map((arg) => `"${arg.replace(/[\\"]/g, '\\$&')}"`);
+		child.stdin.end(`${[command, ...quoted].join(' ')}\n`);
+	});
+}
+
+export function generateKey(): Buffer {
+	return randomBytes(KEY_BYTES);
+}
+
+/** A short fingerprint of `key`, stored beside the data so a wrong key is reported as such. */
+export function keyId(key: Buffer): string {
+	return createHash('sha256').update(key).digest('hex').slice(0, 16);
+}
+
+/** `key` as text to paste into another machine with {@link decodePairingKey}. */
+export function encodePairingKey(key: Buffer): string {
+	return `${PAIRING_PREFIX}${key.toString('base64url')}`;
+}
+
+export function decodePairingKey(code: string): Buffer {
+	const trimmed = code.trim();
+	if (!trimmed.startsWith(PAIRING_PREFIX)) throw new Error('Not a Namefix pairing key');
+	const key = Buffer.from(trimmed.slice(PAIRING_PREFIX.length), 'base64url');
+	if (key.length !== KEY_BYTES) throw new Error('The pairing key is incomplete');
+	return key;
+}
+
+/** Encrypt `plaintext`, authenticating `header` with it so neither can be changed unnoticed. */
+export function seal(key: Buffer, plaintext: string, header: string): Sealed {
+	const iv = randomBytes(IV_BYTES);
+	const cipher = createCipheriv(CIPHER, key, iv);
+	cipher.setAAD(Buffer.from(header, 'utf8'));
+	const data = Buffer.concat([cipher.update(plaintext, 'utf8'), cipher.final()]);
+	return {
+		keyId: keyId(key),
+		iv: iv.toString('base64'),
+		tag: cipher.getAuthTag().toString('base64'),
+		data: data.toString('base64'),
+	};
+}
+
+/** Decrypt what {@link seal} produced; throws if it or `header` was altered. */
+export function unseal(key: Buffer, sealed: Sealed, header: string): string {
+	const decipher = createDecipheriv(CIPHER, key, Buffer.from(sealed.iv, 'base64'), {
+		authTagLength: TAG_BYTES,
+	});
+	decipher.setAAD(Buffer.from(header, 'utf8'));
+	decipher.setAuthTag(Buffer.from(sealed.tag, 'base64'));
+	const data = Buffer.concat([decipher.update(Buffer.from(sealed.data, 'base64')), decipher.final()]);
+	return data.toString('utf8');
+}
+
+function decodeKey(text: string): Buffer {
+	const key = Buffer.from(text.trim(), 'base64');
+	if (key.length !== KEY_BYTES) throw new Error('The stored settings sync key is damaged');
+	return key;
+}
@@ -0,0 +1,29 @@
+import { describe, expect, test } from 'vitest';
+import { BurstDetector } from './BurstDetector.js';
+
+describe('BurstDetector', () => {
+	test('reports once the threshold is reached inside the window', () => {
+		const detector = new BurstDetector({ threshold: 3, windowMs: 1_000, cooldownMs: 5_000 });
+		expect(detector.record('/dir', 0)).toBeNull();
+		expect(detector.record('/dir', 100)).toBeNull();
+		expect(detector.record('/dir', 200)).toBe(3);
+	});
+
+	test('ignores events that fell out of the window', () => {
+		const detector = new BurstDetector({ threshold: 3, windowMs: 1_000, cooldownMs: 5_000 });
+		detector.record('/dir', 0);
+		detector.record('/dir', 100);
+		expect(detector.record('/dir', 1_500)).toBeNull();
+	});
+
+	test('stays quiet during the cooldown and tracks directories separately', () => {
+		const detector = new BurstDetector({ threshold: 2, windowMs: 1_000, cooldownMs: 5_000 });
+		detector.record('/a', 0);
+		expect(detector.record('/a', 10)).toBe(2);
+		expect(detector.record('/a', 20)).toBeNull();
+		detector.record('/b', 30);
+		expect(detector.record('/b', 40)).toBe(2);
+		detector.record('/a', 6_000);
+		expect(detector.record('/a', 6_010)).toBe(2);
+	});
+});
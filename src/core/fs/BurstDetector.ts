@@ -0,0 +1,53 @@
+export type BurstOptions = {
+	/** Events within `windowMs` needed to count as a burst. */
+	threshold: number;
+	windowMs: number;
+	/** Minimum gap between two reported bursts for the same directory. */
+	cooldownMs: number;
+};
+
+const DEFAULT_OPTIONS: BurstOptions = {
+	threshold: 25,
+	windowMs: 60_000,
+	cooldownMs: 10 * 60_000,
+};
+
+/**
+ * Sliding-window counter that flags directories receiving far more file events than screenshots
+ * or downloads normally produce, which usually means someone is reorganising the folder by hand.
+ */
+export class BurstDetector {
+	private readonly options: BurstOptions;
+	private events = new Map<string, number[]>();
+	private lastReported = new Map<string, number>();
+
+	constructor(options: Partial<BurstOptions> = {}) {
+		this.options = { ...DEFAULT_OPTIONS, ...options };
+	}
+
+	get windowMs(): number {
+		return this.options.windowMs;
+	}
+
+	/**
+	 * Record an event for `directory`. Returns the event count in the current window when this
+	 * event tips the directory into a burst, or null otherwise.
+	 */
+	record(directory: string, now = Date.now()): number | null {
+		const { threshold, windowMs, cooldownMs } = this.options;
+		const recent = (this.events.get(directory) ?? []).filter((at) => now - at < windowMs);
+		recent.push(now);
+		this.events.set(directory, recent);
+		if (recent.length < threshold) return null;
+
+		const last = this.lastReported.get(directory);
+		if (last !== undefined && now - last < cooldownMs) return null;
+		this.lastReported.set(directory, now);
+		return recent.length;
+	}
+
+	forget(directory: string): void {
+		this.events.delete(directory);
+		this.lastReported.delete(directory);
+	}
+}
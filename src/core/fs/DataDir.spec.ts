@@ -0,0 +1,51 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { copyDataFiles, finishMove } from './DataDir.js';
+
+describe('data directory moves', () => {
+	let tempRoot: string;
+	let from: { state: string; logs: string };
+	let to: { state: string; logs: string };
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-datadir-'));
+		from = { state: path.join(tempRoot, 'old'), logs: path.join(tempRoot, 'old-logs') };
+		to = { state: path.join(tempRoot, 'new'), logs: path.join(tempRoot, 'new', 'logs') };
+		await fs.mkdir(from.state, { recursive: true });
+		await fs.mkdir(from.logs, { recursive: true });
+		await fs.writeFile(path.join(from.state, 'journal.ndjson'), '{"from":"a","to":"b"}\n');
+		await fs.writeFile(path.join(from.state, 'config.json'), '{}');
+		await fs.writeFile(path.join(from.logs, 'session.log'), 'line 1\n');
+	});
+
+	afterEach(async () => {
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('copies history and logs, then moves what was written meanwhile', async () => {
+		const copied = await copyDataFiles(from, to);
+		expect(copied.map((file) => path.basename(file.to)).sort()).toEqual([
+			'journal.ndjson',
+			'session.log',
+		]);
+		await fs.appendFile(path.join(from.logs, 'session.log'), 'line 2\n');
+
+		expect(await finishMove(copied)).toEqual([]);
+		expect(await fs.readFile(path.join(to.logs, 'session.log'), 'utf8')).toBe('line 1\nline 2\n');
+		await expect(fs.access(path.join(from.state, 'journal.ndjson'))).rejects.toThrow();
+		// Config isn't data
+		await expect(fs.access(path.join(from.state, 'config.json'))).resolves.toBeUndefined();
+	});
+
+	it('leaves everything in place when the target already has history', async () => {
+		await fs.mkdir(to.state, { recursive: true });
+		await fs.writeFile(path.join(to.state, 'journal.ndjson'), '');
+
+		await expect(copyDataFiles(from, to)).rejects.toThrow(/already exists/);
+		expect(await fs.readFile(path.join(to.state, 'journal.ndjson'), 'utf8')).toBe('');
+		await expect(fs.access(path.join(to.logs, 'session.log'))).rejects.toThrow();
+		await expect(fs.access(path.join(from.state, 'journal.ndjson'))).resolves.toBeUndefined();
+	});
+});
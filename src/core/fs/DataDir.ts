@@ -0,0 +1,105 @@
+import { constants as fsConstants } from 'node:fs';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { restoreOwnership } from '../../utils/user.js';
+
+/** Files the service keeps in its state directory. Config stays where it is. */
+export const STATE_FILES = ['journal.ndjson', 'journal.ndjson.bak', 'audit.ndjson'];
+
+export type DataLocations = { state: string; logs: string };
+
+/** A file copied to the new data directory, with how many bytes the copy holds. */
+export type CopiedFile = { from: string; to: string; bytes: number };
+
+/**
+ * Copy the history, audit log and log files from `from` to `to` and check each copy against the
+ * original. Nothing already in `to` is overwritten. If any file fails, the copies made so far are
+ * removed and the originals stay in use.
+ */
+export async function copyDataFiles(from: DataLocations, to: DataLocations): Promise<CopiedFile[]> {
+	const pairs: [string, string][] = [];
+	for (const name of STATE_FILES) {
+		pairs.push([path.join(from.state, name), path.join(to.state, name)]);
+	}
+	if (from.logs !== to.logs) {
+		for (const name of await listFiles(from.logs)) {
+			pairs.push([path.join(from.logs, name), path.join(to.logs, name)]);
+		}
+	}
+
+	const copied: CopiedFile[] = [];
+	try {
+		for (const [source, target] of pairs) {
+			if (!(await exists(source))) continue;
+			await fs.mkdir(path.dirname(target), { recursive: true });
+			restoreOwnership(path.dirname(target));
+			try {
+				await fs.copyFile(source, target, fsConstants.COPYFILE_EXCL);
+			} catch (error) {
+				if ((error as NodeJS.ErrnoException).code === 'EEXIST') {
+					throw new Error(`${target} already exists`);
+				}
+				throw error;
+			}
+			const entry = { from: source, to: target, bytes: 0 };
+			copied.push(entry);
+			entry.bytes = await verifyCopy(source, target);
+			restoreOwnership(target);
+		}
+	} catch (error) {
+		await Promise.all(copied.map((file) => fs.rm(file.to, { force: true })));
+		throw error;
+	}
+	return copied;
+}
+
+/**
+ * Append whatever was written to each original after it was copied, then delete the originals.
+ * Returns the files that could not be deleted.
+ */
+export async function finishMove(copied: CopiedFile[]): Promise<string[]> {
+	const left: string[] = [];
+	for (const file of copied) {
+		try {
+			const original = await fs.readFile(file.from);
+			if (original.length > file.bytes) {
+				await fs.appendFile(file.to, original.subarray(file.bytes));
+			}
+			await fs.unlink(file.from);
+		} catch {
+			left.push(file.from);
+		}
+	}
+	return left;
+}
+
+/**
+ * Check that `target` matches the start of `source`, which may have grown since the copy (the
+ * service keeps logging while it moves). Returns the copy's size.
+ */
+async function verifyCopy(source: string, target: string): Promise<number> {
+	const copy = await fs.readFile(target);
+	const original = await fs.readFile(source);
+	if (original.length < copy.length || !original.subarray(0, copy.length).equals(copy)) {
+		throw new Error(`${path.basename(source)} changed while it was being copied; try again`);
+	}
+	return copy.length;
+}
+
+async function listFiles(dir: string): Promise<string[]> {
+	try {
+		const dirents = await fs.readdir(dir, { withFileTypes: true });
+		return dirents.filter((d) => d.isFile()).map((d) => d.name);
+	} catch {
+		return [];
+	}
+}
+
+async function exists(file: string): Promise<boolean> {
+	try {
+		await fs.access(file);
+		return true;
+	} catch {
+		return false;
+	}
+}
@@ -0,0 +1,37 @@
+import { describe, expect, test } from 'vitest';
+import { parseEsEvent } from './EndpointSecurityWatchService.js';
+
+const screencapture = '/System/Library/CoreServices/screencaptureui.app/Contents/MacOS/screencaptureui';
+
+describe('parseEsEvent', () => {
+	test('reads new-path creates', () => {
+		const line = JSON.stringify({
+			process: { executable: { path: screencapture } },
+			event: {
+				create: {
+					destination_type: 1,
+					destination: { new_path: { dir: { path: '/Users/me/Desktop' }, filename: 'shot.png' } },
+				},
+			},
+		});
+		expect(parseEsEvent(line)).toEqual({ path: '/Users/me/Desktop/shot.png', process: screencapture });
+	});
+
+	test('reads renames onto existing files', () => {
+		const line = JSON.stringify({
+			event: {
+				rename: {
+					source: { path: '/Users/me/Desktop/.shot.png' },
+					destination_type: 0,
+					destination: { existing_file: { path: '/Users/me/Desktop/shot.png' } },
+				},
+			},
+		});
+		expect(parseEsEvent(line)).toEqual({ path: '/Users/me/Desktop/shot.png', process: null });
+	});
+
+	test('ignores other events and malformed lines', () => {
+		expect(parseEsEvent(JSON.stringify({ event: { exec: {} } }))).toBeNull();
+		expect(parseEsEvent('not json')).toBeNull();
+	});
+});
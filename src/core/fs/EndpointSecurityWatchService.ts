@@ -0,0 +1,179 @@
+import { spawn, type ChildProcess } from 'node:child_process';
+import fsp from 'node:fs/promises';
+import path from 'node:path';
+import readline from 'node:readline';
+import type { FsSafe } from './FsSafe.js';
+import { isHiddenPath, WatchService } from './WatchService.js';
+import type { IWatchService, WatchEvent, WatchServiceErrorHandler } from '../../types/index';
+
+/** How long eslogger gets to fail (e.g. missing privileges) before it counts as started. */
+const STARTUP_GRACE_MS = 500;
+
+/** A file that appeared, and the executable of the process that put it there. */
+export type EsFileEvent = { path: string; process: string | null };
+
+/**
+ * Watches a directory through Endpoint Security by streaming `eslogger create rename` (macOS 13+).
+ * Unlike FSEvents, every event names the process responsible and none are coalesced, so files
+ * that arrive through a temporary name (as screenshots do) are still seen.
+ *
+ * eslogger needs root and Full Disk Access. When it cannot run, the watcher reports why through
+ * its error handlers and falls back to the FSEvents-based {@link WatchService}.
+ */
+export class EndpointSecurityWatchService implements IWatchService {
+	private child: ChildProcess | null = null;
+	private fallback: WatchService | null = null;
+	private healthy = false;
+	private errorHandlers = new Set<WatchServiceErrorHandler>();
+	private pending = new Set<string>();
+
+	constructor(
+		private readonly dir: string,
+		private readonly fsSafe: FsSafe,
+		private readonly recursive = false,
+	) {}
+
+	async start(onAdd: (event: WatchEvent) => void): Promise<void> {
+		await this.stop();
+		try {
+			this.child = await this.spawnLogger(onAdd);
+			this.healthy = true;
+		} catch (error) {
+			const reason = error instanceof Error ? error.message : String(error);
+			this.report(new Error(`Endpoint Security unavailable (${reason}); using FSEvents`));
+			this.fallback = new WatchService(this.dir, this.fsSafe, this.recursive);
+			for (const handler of this.errorHandlers) this.fallback.onError(handler);
+			await this.fallback.start(onAdd);
+		}
+	}
+
+	private spawnLogger(onAdd: (event: WatchEvent) => void): Promise<ChildProcess> {
+		return new Promise((resolve, reject) => {
+			const child = spawn('eslogger', ['create', 'rename'], { stdio: ['ignore', 'pipe', 'pipe'] });
+			let stderr = '';
+			let started = false;
+			child.stderr?.on('data', (chunk: Buffer) => {
+				stderr += chunk.toString();
+			});
+			child.once('error', (err) => {
+				if (!started) reject(err);
+			});
+			child.once('exit', (code) => {
+				if (!started) {
+					reject(new Error(stderr.trim() || `eslogger exited with code ${code}`));
+					return;
+				}
+				if (this.child === child) {
+					this.healthy = false;
+					this.report(new Error(`eslogger exited with code ${code}`));
+				}
+			});
+
+			const lines = readline.createInterface({ input: child.stdout as NodeJS.ReadableStream });
+			lines.on('line', (line) => {
+				const event = parseEsEvent(line);
+				if (!event || !this.covers(event.path)) return;
+				if (this.pending.has(event.path)) return;
+				this.pending.add(event.path);
+				this.handleNewFile(event, onAdd).finally(() => this.pending.delete(event.path));
+			});
+
+			setTimeout(() => {
+				if (child.exitCode !== null || child.killed) return;
+				started = true;
+				resolve(child);
+			}, STARTUP_GRACE_MS);
+		});
+	}
+
+	/** Whether `file` is in the directory, or below it when recursive, and not hidden. */
+	private covers(file: string): boolean {
+		const relative = path.relative(this.dir, file);
+		if (relative.startsWith('..') || path.isAbsolute(relative)) return false;
+		if (!this.recursive && path.dirname(file) !== this.dir) return false;
+		return !isHiddenPath(relative);
+	}
+
+	private async handleNewFile(event: EsFileEvent, onAdd: (event: WatchEvent) => void) {
+		try {
+			const st = await fsp.stat(event.path);
+			if (!st.isFile()) return;
+			if (!(await this.fsSafe.isStable(event.path))) return;
+			onAdd({
+				path: event.path,
+				birthtimeMs: st.birthtimeMs,
+				mtimeMs: st.mtimeMs,
+				size: st.size,
+				process: event.process ?? undefined,
+			});
+		} catch (error) {
+			const err = error instanceof Error ? error : new Error(String(error));
+			// ENOENT is expected for files that disappear during processing
+			if ((err as NodeJS.ErrnoException).code !== 'ENOENT') this.report(err);
+		}
+	}
+
+	private report(error: Error) {
+		for (const handler of this.errorHandlers) {
+			try {
+				handler(error, this.dir);
+			} catch {
+				// Don't let handler errors propagate
+			}
+		}
+	}
+
+	async stop(): Promise<void> {
+		this.healthy = false;
+		const child = this.child;
+		this.child = null;
+		child?.kill();
+		const fallback = this.fallback;
+		this.fallback = null;
+		await fallback?.stop();
+	}
+
+	isHealthy(): boolean {
+		if (this.fallback) return this.fallback.isHealthy();
+		return this.healthy && this.child !== null;
+	}
+
+	onError(handler: WatchServiceErrorHandler): () => void {
+		this.errorHandlers.add(handler);
+		return () => this.errorHandlers.delete(handler);
+	}
+
+	dispose(): void | Promise<void> {
+		this.errorHandlers.clear();
+		return this.stop();
+	}
+}
+
+type EsFile = { path?: string };
+type EsDestination = { existing_file?: EsFile; new_path?: { dir?: EsFile; filename?: string } };
+type EsMessage = {
+	process?: { executable?: EsFile };
+	event?: {
+		create?: { destination?: EsDestination };
+		rename?: { destination?: EsDestination };
+	};
+};
+
+/** The file a `create` or `rename` line from eslogger's JSON output points at, if any. */
+export function parseEsEvent(line: string): EsFileEvent | null {
+	let message: EsMessage;
+	try {
+		message = JSON.parse(line) as EsMessage;
+	} catch {
+		return null;
+	}
+	const destination = message.event?.create?.destination ?? message.event?.rename?.destination;
+	const target = destination?.existing_file?.path ?? joinNewPath(destination?.new_path);
+	if (!target) return null;
+	return { path: target, process: message.process?.executable?.path ?? null };
+}
+
+function joinNewPath(newPath: EsDestination['new_path']): string | undefined {
+	if (!newPath?.dir?.path || !newPath.filename) return undefined;
+	return path.join(newPath.dir.path, newPath.filename);
+}
@@ -0,0 +1,51 @@
+import { describe, expect, test } from 'vitest';
+import { isOriginAllowed, originFromExecutable, parseQuarantineAgent } from './FileOrigin.js';
+
+describe('parseQuarantineAgent', () => {
+	test('reads the agent field', () => {
+		expect(parseQuarantineAgent('0083;65a1b2c3;Safari;9F2E-41\n')).toBe('Safari');
+	});
+
+	test('returns null when the agent is missing', () => {
+		expect(parseQuarantineAgent('0083;65a1b2c3;;')).toBeNull();
+		expect(parseQuarantineAgent('0083')).toBeNull();
+	});
+});
+
+describe('originFromExecutable', () => {
+	test('names the outermost app bundle', () => {
+		const helper =
+			'/Applications/Google Chrome.app/Contents/Frameworks/Helper.app/Contents/MacOS/Helper';
+		expect(originFromExecutable(helper)).toBe('Google Chrome');
+	});
+
+	test('maps the screenshot tool to Screenshot', () => {
+		const ui = '/System/Library/CoreServices/screencaptureui.app/Contents/MacOS/screencaptureui';
+		expect(originFromExecutable(ui)).toBe('Screenshot');
+		expect(originFromExecutable('/usr/sbin/screencapture')).toBe('Screenshot');
+	});
+
+	test('falls back to the binary name', () => {
+		expect(originFromExecutable('/usr/bin/curl')).toBe('curl');
+	});
+});
+
+describe('isOriginAllowed', () => {
+	test('allows everything without lists', () => {
+		expect(isOriginAllowed(null, {})).toBe(true);
+		expect(isOriginAllowed('Safari', {})).toBe(true);
+	});
+
+	test('admits only allowlisted apps, case-insensitively', () => {
+		const rule = { allow: ['Screenshot', 'safari'] };
+		expect(isOriginAllowed('Safari', rule)).toBe(true);
+		expect(isOriginAllowed('Pixelmator Pro', rule)).toBe(false);
+		expect(isOriginAllowed(null, rule)).toBe(false);
+	});
+
+	test('lets the denylist win', () => {
+		const rule = { allow: ['Safari'], deny: ['Safari'] };
+		expect(isOriginAllowed('Safari', rule)).toBe(false);
+		expect(isOriginAllowed(null, { deny: ['Safari'] })).toBe(true);
+	});
+});
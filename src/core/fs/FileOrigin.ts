@@ -0,0 +1,66 @@
+import { execFile as execFileCb } from 'node:child_process';
+import { promisify } from 'node:util';
+import type { OriginRule } from '../../types/index.js';
+
+const execFile = promisify(execFileCb);
+
+/** Origin reported for files the macOS screenshot tool wrote. */
+export const SCREENSHOT_ORIGIN = 'Screenshot';
+
+/**
+ * Works out which app produced a file from the extended attributes macOS leaves on it: the
+ * screenshot tool tags its captures, and browsers and other downloaders stamp a quarantine
+ * record naming themselves. Files without either, and every file on other platforms, have no
+ * known origin.
+ */
+export class FileOriginProbe {
+	async originOf(p: string): Promise<string | null> {
+		if (process.platform !== 'darwin') return null;
+		if ((await readXattr(p, 'com.apple.metadata:kMDItemIsScreenCapture')) !== null) {
+			return SCREENSHOT_ORIGIN;
+		}
+		const quarantine = await readXattr(p, 'com.apple.quarantine');
+		return quarantine ? parseQuarantineAgent(quarantine) : null;
+	}
+}
+
+/**
+ * App name for the executable that created a file, as Endpoint Security reports it: the
+ * enclosing `.app` bundle's name, or the binary's own name outside one. The screenshot tool
+ * maps to {@link SCREENSHOT_ORIGIN}, matching what the xattr probe reports for its captures.
+ */
+export function originFromExecutable(executable: string): string {
+	const bundle = executable.match(/([^/]+)\.app\//g)?.[0]?.slice(0, -'.app/'.length);
+	const name = bundle ?? executable.split('/').pop() ?? executable;
+	return /^screencapture(ui)?$/i.test(name) ? SCREENSHOT_ORIGIN : name;
+}
+
+/**
+ * The downloading app named in a `com.apple.quarantine` value (`flags;timestamp;agent;uuid`).
+ */
+export function parseQuarantineAgent(value: string): string | null {
+	const agent = value.trim().split(';')[2]?.trim();
+	return agent ? agent : null;
+}
+
+/**
+ * Whether a file from `origin` may be renamed under `rule`. The denylist wins; a non-empty
+ * allowlist admits only the apps it names, so files of unknown origin are left alone.
+ */
+export function isOriginAllowed(origin: string | null, rule: OriginRule): boolean {
+	const matches = (apps: string[] | undefined) =>
+		origin !== null && (apps ?? []).some((app) => app.toLowerCase() === origin.toLowerCase());
+	if (matches(rule.deny)) return false;
+	if (rule.allow && rule.allow.length > 0) return matches(rule.allow);
+	return true;
+}
+
+async function readXattr(p: string, name: string): Promise<string | null> {
+	try {
+		const { stdout } = await execFile('xattr', ['-p', name, p], { timeout: 2_000 });
+		return stdout;
+	} catch {
+		// Missing attribute (or xattr itself); either way there is nothing to read.
+		return null;
+	}
+}
@@ -0,0 +1,24 @@
+import { describe, expect, test } from 'vitest';
+import { formatWindow, parseFrontWindow } from './FrontWindowProbe.js';
+
+describe('parseFrontWindow', () => {
+	test('reads the app and window title lines', () => {
+		expect(parseFrontWindow('Xcode\nMainView.swift\n')).toEqual({
+			app: 'Xcode',
+			title: 'MainView.swift',
+		});
+		expect(parseFrontWindow('Finder\n\n')).toEqual({ app: 'Finder', title: '' });
+	});
+
+	test('returns null without an app', () => {
+		expect(parseFrontWindow('\n')).toBeNull();
+	});
+});
+
+describe('formatWindow', () => {
+	test('joins the app and title, skipping a title that repeats the app', () => {
+		expect(formatWindow('Xcode', 'MainView.swift')).toBe('Xcode — MainView.swift');
+		expect(formatWindow('Calculator', 'Calculator')).toBe('Calculator');
+		expect(formatWindow('Finder', '')).toBe('Finder');
+	});
+});
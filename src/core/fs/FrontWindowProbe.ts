@@ -0,0 +1,65 @@
+import { execFile as execFileCb } from 'node:child_process';
+import { promisify } from 'node:util';
+
+const execFile = promisify(execFileCb);
+
+/** Files older than this when they reach the probe are named without a window title. */
+const CAPTURE_WINDOW_MS = 5_000;
+const RETRY_DELAY_MS = 250;
+
+/** Processes that hold the foreground while the screenshot tool itself is capturing. */
+const CAPTURE_APPS = new Set(['screencaptureui', 'screencapture', 'Screenshot']);
+
+const SCRIPT = `tell application "System Events"
+	set frontApp to first application process whose frontmost is true
+	set windowTitle to ""
+	try
+		set windowTitle to name of front window of frontApp
+	end try
+	return (name of frontApp) & linefeed & windowTitle
+end tell`;
+
+/**
+ * Reads the frontmost app and its window title through the accessibility API (System Events), so
+ * screenshots can be named after what they show. macOS only; it needs the Accessibility
+ * permission, and reports nothing without it.
+ */
+export class FrontWindowProbe {
+	private unavailable = process.platform !== 'darwin';
+
+	/**
+	 * The front window as `App — Title` for a file created at `createdAt` (epoch ms), while that is
+	 * recent enough to still be the window that was captured. Waits out the screenshot tool's own
+	 * overlay, which is frontmost for a moment after a capture.
+	 */
+	async titleAt(createdAt: number): Promise<string | null> {
+		while (!this.unavailable && Date.now() - createdAt <= CAPTURE_WINDOW_MS) {
+			const front = await this.query();
+			if (front === null) return null;
+			if (!CAPTURE_APPS.has(front.app)) return formatWindow(front.app, front.title);
+			await new Promise((resolve) => setTimeout(resolve, RETRY_DELAY_MS));
+		}
+		return null;
+	}
+
+	private async query(): Promise<{ app: string; title: string } | null> {
+		try {
+			const { stdout } = await execFile('osascript', ['-e', SCRIPT], { timeout: 2_000 });
+			return parseFrontWindow(stdout);
+		} catch (err) {
+			if ((err as NodeJS.ErrnoException).code === 'ENOENT') this.unavailable = true;
+			return null;
+		}
+	}
+}
+
+/** The app name and window title the probe's script prints on two lines. */
+export function parseFrontWindow(output: string): { app: string; title: string } | null {
+	const [app = '', title = ''] = output.replace(/\r?\n$/, '').split('\n');
+	return app.trim() ? { app: app.trim(), title: title.trim() } : null;
+}
+
+/** `Xcode — MainView.swift`, or just the app when the window has no title of its own. */
+export function formatWindow(app: string, title: string): string {
+	return title && title !== app ? `${app} — ${title}` : app;
+}
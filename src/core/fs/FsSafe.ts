@@ -89,3 +89,14 @@ function isMissingError(err: unknown): err is NodeJS.ErrnoException {
 		(err as NodeJS.ErrnoException).code === 'ENOENT'
 	);
 }
+
+/**
+ * Whether a rename failed because another process has the file open. Windows reports sharing
+ * violations as EBUSY or EPERM/EACCES; elsewhere only EBUSY means that.
+ */
+export function isLockedError(err: unknown): boolean {
+	if (typeof err !== 'object' || err === null || !('code' in err)) return false;
+	const code = (err as NodeJS.ErrnoException).code;
+	if (code === 'EBUSY') return true;
+	return process.platform === 'win32' && (code === 'EPERM' || code === 'EACCES');
+}
@@ -0,0 +1,25 @@
+import { describe, expect, test } from 'vitest';
+import { mountTypeFor, parseMountTable } from './NetworkFs.js';
+
+const mounts = [
+	'/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)',
+	'/dev/disk3s5 on /System/Volumes/Data (apfs, local, journaled, nobrowse)',
+	'//me@nas._smb._tcp.local/Photos on /Volumes/Photos (smbfs, nodev, nosuid, mounted by me)',
+	'nas:/exports/scans on /Volumes/My Scans (nfs, asynchronous)',
+].join('\n');
+
+describe('parseMountTable', () => {
+	test('reads mount points and types', () => {
+		expect(parseMountTable(mounts)).toContainEqual({ mountPoint: '/Volumes/My Scans', type: 'nfs' });
+		expect(parseMountTable(mounts)).toHaveLength(4);
+	});
+});
+
+describe('mountTypeFor', () => {
+	test('uses the deepest containing mount', () => {
+		const entries = parseMountTable(mounts);
+		expect(mountTypeFor(entries, '/Volumes/Photos/2024')).toBe('smbfs');
+		expect(mountTypeFor(entries, '/Volumes/My Scans')).toBe('nfs');
+		expect(mountTypeFor(entries, '/Volumes/Photography')).toBe('apfs');
+	});
+});
@@ -0,0 +1,61 @@
+import { execFile as execFileCb } from 'node:child_process';
+import fsp from 'node:fs/promises';
+import path from 'node:path';
+import { promisify } from 'node:util';
+
+const execFile = promisify(execFileCb);
+
+/** `statfs` magic numbers of Linux network filesystems (NFS, SMB, CIFS, SMB2, AFS, Coda, NCP). */
+const LINUX_NETWORK_MAGIC = new Set([
+	0x6969, 0x517b, 0xff534d42, 0xfe534d42, 0x5346414f, 0x73757245, 0x564c,
+]);
+
+/** `mount` filesystem types on macOS that are served over the network. */
+const DARWIN_NETWORK_TYPES = new Set(['smbfs', 'nfs', 'afpfs', 'webdav', 'cifs', 'ftp']);
+
+/**
+ * Whether `dir` lives on a network filesystem, where change notifications are unreliable.
+ * Detection failures count as local so watching still starts normally.
+ */
+export async function isNetworkFilesystem(dir: string): Promise<boolean> {
+	try {
+		switch (process.platform) {
+			case 'win32':
+				return /^[\\/]{2}[^\\/]/.test(path.resolve(dir));
+			case 'linux':
+				return LINUX_NETWORK_MAGIC.has((await fsp.statfs(dir)).type >>> 0);
+			case 'darwin': {
+				const { stdout } = await execFile('mount', [], { timeout: 2_000 });
+				const type = mountTypeFor(parseMountTable(stdout), await fsp.realpath(dir));
+				return type !== null && DARWIN_NETWORK_TYPES.has(type);
+			}
+			default:
+				return false;
+		}
+	} catch {
+		return false;
+	}
+}
+
+export type MountEntry = { mountPoint: string; type: string };
+
+/** Entries of macOS `mount` output: `//user@host/share on /Volumes/share (smbfs, nodev, ...)`. */
+export function parseMountTable(output: string): MountEntry[] {
+	const entries: MountEntry[] = [];
+	for (const line of output.split('\n')) {
+		const match = / on (.+) \(([^,)]+)/.exec(line);
+		if (match?.[1] && match[2]) entries.push({ mountPoint: match[1], type: match[2].trim() });
+	}
+	return entries;
+}
+
+/** Filesystem type of the deepest mount containing `p`, or null when none does. */
+export function mountTypeFor(entries: MountEntry[], p: string): string | null {
+	let best: MountEntry | null = null;
+	for (const entry of entries) {
+		const root = entry.mountPoint.endsWith('/') ? entry.mountPoint : `${entry.mountPoint}/`;
+		if (p !== entry.mountPoint && !p.startsWith(root)) continue;
+		if (!best || entry.mountPoint.length > best.mountPoint.length) best = entry;
+	}
+	return best?.type ?? null;
+}
@@ -0,0 +1,16 @@
+import { describe, expect, test } from 'vitest';
+import { parseLsof } from './OpenFileProbe.js';
+
+describe('parseLsof', () => {
+	test('reports the first holding process', () => {
+		expect(parseLsof('p412\ncPreview\nf5\np77\ncQuickLookUIService\n')).toBe('Preview (pid 412)');
+	});
+
+	test('falls back to the pid when the command is missing', () => {
+		expect(parseLsof('p412\n')).toBe('pid 412');
+	});
+
+	test('returns null for empty output', () => {
+		expect(parseLsof('')).toBeNull();
+	});
+});
@@ -0,0 +1,48 @@
+import { execFile as execFileCb } from 'node:child_process';
+import { promisify } from 'node:util';
+
+const execFile = promisify(execFileCb);
+
+/**
+ * Finds processes holding a file open, so renames can wait for them instead of pulling the file
+ * out from under an app that is still writing or displaying it. Uses `lsof` on macOS and Linux;
+ * Windows has no equivalent CLI, but there an open file makes the rename itself fail with a
+ * sharing violation, which the service treats the same way.
+ */
+export class OpenFileProbe {
+	private unavailable = process.platform === 'win32';
+
+	/** A description of the first process holding `p` open (e.g. `Preview (pid 412)`), or null. */
+	async findHolder(p: string): Promise<string | null> {
+		if (this.unavailable) return null;
+		try {
+			const { stdout } = await execFile('lsof', ['-F', 'pc', '--', p], { timeout: 2_000 });
+			return parseLsof(stdout);
+		} catch (err) {
+			// lsof exits 1 when nothing holds the file open.
+			if (isExitError(err) && err.code === 1) return null;
+			if (isMissingCommand(err)) this.unavailable = true;
+			return null;
+		}
+	}
+}
+
+/** First process in `lsof -F pc` output, formatted as `command (pid N)`. */
+export function parseLsof(output: string): string | null {
+	let pid: string | null = null;
+	for (const line of output.split('\n')) {
+		if (line.startsWith('p')) pid = line.slice(1);
+		else if (line.startsWith('c') && pid) return `${line.slice(1)} (pid ${pid})`;
+	}
+	return pid ? `pid ${pid}` : null;
+}
+
+function isExitError(err: unknown): err is { code: number } {
+	return (
+		typeof err === 'object' && err !== null && typeof (err as { code?: unknown }).code === 'number'
+	);
+}
+
+function isMissingCommand(err: unknown): boolean {
+	return typeof err === 'object' && err !== null && (err as NodeJS.ErrnoException).code === 'ENOENT';
+}
@@ -0,0 +1,118 @@
+import { createHash } from 'node:crypto';
+import fsp from 'node:fs/promises';
+import path from 'node:path';
+import type { FsSafe } from './FsSafe.js';
+import { isHiddenPath } from './WatchService.js';
+import type { IWatchService, WatchEvent, WatchServiceErrorHandler } from '../../types/index';
+
+export const DEFAULT_POLL_INTERVAL_MS = 5_000;
+
+/**
+ * Watches a directory by listing it on an interval, for filesystems whose change notifications
+ * can't be trusted (SMB and NFS shares drop or never deliver them). Each poll costs one directory
+ * read; the sorted listing is hashed so unchanged directories are skipped, and only names that
+ * weren't there on the previous poll are stat'ed.
+ */
+export class PollingWatchService implements IWatchService {
+	private timer: NodeJS.Timeout | null = null;
+	private healthy = false;
+	private polling = false;
+	private errorHandlers = new Set<WatchServiceErrorHandler>();
+	private pending = new Set<string>();
+	private known = new Set<string>();
+	private fingerprint = '';
+
+	constructor(
+		private readonly dir: string,
+		private readonly fsSafe: FsSafe,
+		private readonly intervalMs = DEFAULT_POLL_INTERVAL_MS,
+		/** List subfolders too; each poll then reads the whole tree. */
+		private readonly recursive = false,
+	) {}
+
+	async start(onAdd: (event: WatchEvent) => void): Promise<void> {
+		await this.stop();
+		// Files already present when watching starts are not new arrivals.
+		const names = await this.list();
+		this.known = new Set(names);
+		this.fingerprint = listingHash(names);
+		this.healthy = true;
+		this.timer = setInterval(() => void this.poll(onAdd), this.intervalMs);
+	}
+
+	private async poll(onAdd: (event: WatchEvent) => void) {
+		if (this.polling || !this.healthy) return;
+		this.polling = true;
+		try {
+			const names = await this.list();
+			const hash = listingHash(names);
+			if (hash === this.fingerprint) return;
+			this.fingerprint = hash;
+			const previous = this.known;
+			this.known = new Set(names);
+			for (const name of names) {
+				if (previous.has(name) || isHiddenPath(name)) continue;
+				const full = path.join(this.dir, name);
+				if (this.pending.has(full)) continue;
+				this.pending.add(full);
+				this.handleNewFile(full, onAdd).finally(() => this.pending.delete(full));
+			}
+		} catch (error) {
+			this.report(error instanceof Error ? error : new Error(String(error)));
+		} finally {
+			this.polling = false;
+		}
+	}
+
+	private async list(): Promise<string[]> {
+		return (await fsp.readdir(this.dir, { recursive: this.recursive })).sort();
+	}
+
+	private async handleNewFile(full: string, onAdd: (event: WatchEvent) => void) {
+		try {
+			const st = await fsp.stat(full);
+			if (!st.isFile()) return;
+			if (!(await this.fsSafe.isStable(full))) return;
+			onAdd({ path: full, birthtimeMs: st.birthtimeMs, mtimeMs: st.mtimeMs, size: st.size });
+		} catch (error) {
+			const err = error instanceof Error ? error : new Error(String(error));
+			// ENOENT is expected for files that disappear during processing
+			if ((err as NodeJS.ErrnoException).code !== 'ENOENT') this.report(err);
+		}
+	}
+
+	private report(error: Error) {
+		for (const handler of this.errorHandlers) {
+			try {
+				handler(error, this.dir);
+			} catch {
+				// Don't let handler errors propagate
+			}
+		}
+	}
+
+	async stop(): Promise<void> {
+		this.healthy = false;
+		if (this.timer) clearInterval(this.timer);
+		this.timer = null;
+	}
+
+	isHealthy(): boolean {
+		return this.healthy && this.timer !== null;
+	}
+
+	onError(handler: WatchServiceErrorHandler): () => void {
+		this.errorHandlers.add(handler);
+		return () => this.errorHandlers.delete(handler);
+	}
+
+	dispose(): void | Promise<void> {
+		this.errorHandlers.clear();
+		return this.stop();
+	}
+}
+
+/** Hash of a sorted directory listing, to tell cheaply whether anything was added or removed. */
+export function listingHash(names: string[]): string {
+	return createHash('sha1').update(names.join('\0')).digest('hex');
+}
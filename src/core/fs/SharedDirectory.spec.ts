@@ -0,0 +1,42 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { detectSharing } from './SharedDirectory.js';
+
+describe.skipIf(process.platform === 'win32')('detectSharing', () => {
+	let dir: string;
+	const uid = process.getuid?.() ?? 0;
+
+	beforeEach(async () => {
+		dir = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-shared-'));
+		await fs.chmod(dir, 0o755);
+	});
+
+	afterEach(async () => {
+		await fs.rm(dir, { recursive: true, force: true });
+	});
+
+	it('treats a private directory of the current user as unshared', async () => {
+		expect(await detectSharing(dir, uid)).toBeNull();
+	});
+
+	it('flags directories owned by someone else', async () => {
+		expect(await detectSharing(dir, uid + 1)).toEqual({
+			directory: dir,
+			ownerUid: uid,
+			ownedByOther: true,
+			worldWritable: false,
+		});
+	});
+
+	it('flags world-writable directories', async () => {
+		await fs.chmod(dir, 0o777);
+		expect((await detectSharing(dir, uid))?.worldWritable).toBe(true);
+	});
+
+	it('ignores platforms without ownership and missing directories', async () => {
+		expect(await detectSharing(dir, null)).toBeNull();
+		expect(await detectSharing(path.join(dir, 'missing'), uid)).toBeNull();
+	});
+});
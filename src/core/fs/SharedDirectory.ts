@@ -0,0 +1,27 @@
+import fs from 'node:fs/promises';
+import type { SharedDirectory } from '../../types/service.js';
+
+const WORLD_WRITABLE = 0o002;
+
+/**
+ * How `dir` is shared with other users, or null if it isn't. A directory is shared when another
+ * user owns it, so renaming depends on its group or world permissions, or when anyone may write
+ * to it, so other users' files land there and may not be renamable. `uid` is the user Namefix
+ * works for; pass null where the platform has no ownership model (Windows).
+ */
+export async function detectSharing(
+	dir: string,
+	uid: number | null,
+): Promise<SharedDirectory | null> {
+	if (uid === null) return null;
+	let st: Awaited<ReturnType<typeof fs.stat>>;
+	try {
+		st = await fs.stat(dir);
+	} catch {
+		return null;
+	}
+	const ownedByOther = st.uid !== uid;
+	const worldWritable = (st.mode & WORLD_WRITABLE) !== 0;
+	if (!ownedByOther && !worldWritable) return null;
+	return { directory: dir, ownerUid: st.uid, ownedByOther, worldWritable };
+}
@@ -0,0 +1,33 @@
+import { describe, expect, test } from 'vitest';
+import { normalizeFileId, parseUsnCsv } from './UsnJournalWatchService.js';
+
+const header =
+	'Usn,File name,File name length,Reason,Time stamp,File attributes,File ID,Parent file ID,Source info,Security ID,Major version,Minor version,Record length';
+
+describe('parseUsnCsv', () => {
+	test('reads records by header column', () => {
+		const output = [
+			'',
+			header,
+			'0x0000000012345678,"shot, final.png",30,0x80000100,01/02/2025 10:00:00,0x00000020,0x00000000000000000001000000000abc,0x0000000000000000000500000001ff3a,0x00000000,0,3,0,112',
+		].join('\r\n');
+		expect(parseUsnCsv(output)).toEqual([
+			{
+				usn: 0x12345678n,
+				name: 'shot, final.png',
+				reason: 0x80000100,
+				parentId: '0x0000000000000000000500000001ff3a',
+			},
+		]);
+	});
+
+	test('returns nothing without a header', () => {
+		expect(parseUsnCsv('Error:  Access is denied.')).toEqual([]);
+	});
+});
+
+describe('normalizeFileId', () => {
+	test('ignores padding and case', () => {
+		expect(normalizeFileId('0x0000000000000000000500000001FF3A')).toBe(normalizeFileId('0x500000001ff3a'));
+	});
+});
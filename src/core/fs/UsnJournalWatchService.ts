@@ -0,0 +1,241 @@
+import { execFile as execFileCb } from 'node:child_process';
+import fsp from 'node:fs/promises';
+import path from 'node:path';
+import { promisify } from 'node:util';
+import type { FsSafe } from './FsSafe.js';
+import { WatchService } from './WatchService.js';
+import type { IWatchService, WatchEvent, WatchServiceErrorHandler } from '../../types/index';
+
+const execFile = promisify(execFileCb);
+
+/** Directories with at least this many entries are watched through the USN journal on Windows. */
+export const USN_JOURNAL_ENTRY_THRESHOLD = 100_000;
+
+const POLL_INTERVAL_MS = 1_000;
+const FSUTIL_MAX_BUFFER = 64 * 1024 * 1024;
+
+const USN_REASON_FILE_CREATE = 0x100;
+const USN_REASON_FILE_DELETE = 0x200;
+const USN_REASON_RENAME_NEW_NAME = 0x2000;
+const USN_REASON_CLOSE = 0x80000000;
+
+/** One change record from `fsutil usn readjournal ... csv`. */
+export type UsnRecord = { usn: bigint; name: string; reason: number; parentId: string };
+
+/**
+ * Watches a directory by polling the NTFS change journal with `fsutil usn readjournal`. The
+ * journal is per volume, so the cost of a poll depends on how much changed rather than on how
+ * many files the directory holds, which keeps very large directories cheap to watch where
+ * `fs.watch` struggles.
+ *
+ * Reading the journal requires an elevated process. When it cannot be read, the watcher reports
+ * why through its error handlers and falls back to the `fs.watch`-based {@link WatchService}.
+ */
+export class UsnJournalWatchService implements IWatchService {
+	private timer: NodeJS.Timeout | null = null;
+	private fallback: WatchService | null = null;
+	private healthy = false;
+	private polling = false;
+	private errorHandlers = new Set<WatchServiceErrorHandler>();
+	private pending = new Set<string>();
+	private volume: string;
+	private dirId = '';
+	private nextUsn = 0n;
+
+	constructor(
+		private readonly dir: string,
+		private readonly fsSafe: FsSafe,
+	) {
+		this.volume = path.parse(path.resolve(dir)).root.replace(/\\$/, '');
+	}
+
+	async start(onAdd: (event: WatchEvent) => void): Promise<void> {
+		await this.stop();
+		try {
+			this.dirId = await this.queryFileId(this.dir);
+			this.nextUsn = await this.queryNextUsn();
+		} catch (error) {
+			const reason = error instanceof Error ? error.message : String(error);
+			this.report(new Error(`USN journal unavailable (${reason}); using fs.watch`));
+			this.fallback = new WatchService(this.dir, this.fsSafe);
+			for (const handler of this.errorHandlers) this.fallback.onError(handler);
+			await this.fallback.start(onAdd);
+			return;
+		}
+		this.healthy = true;
+		this.timer = setInterval(() => void this.poll(onAdd), POLL_INTERVAL_MS);
+	}
+
+	private async poll(onAdd: (event: WatchEvent) => void) {
+		if (this.polling || !this.healthy) return;
+		this.polling = true;
+		try {
+			const { stdout } = await execFile(
+				'fsutil',
+				['usn', 'readjournal', this.volume, `startusn=${this.nextUsn}`, 'csv'],
+				{ maxBuffer: FSUTIL_MAX_BUFFER, windowsHide: true },
+			);
+			for (const record of parseUsnCsv(stdout)) {
+				if (record.usn >= this.nextUsn) this.nextUsn = record.usn + 1n;
+				if (!isFinishedArrival(record.reason)) continue;
+				if (normalizeFileId(record.parentId) !== this.dirId) continue;
+				if (record.name.startsWith('.')) continue;
+				const full = path.join(this.dir, record.name);
+				if (this.pending.has(full)) continue;
+				this.pending.add(full);
+				this.handleNewFile(full, onAdd).finally(() => this.pending.delete(full));
+			}
+		} catch (error) {
+			this.report(error instanceof Error ? error : new Error(String(error)));
+		} finally {
+			this.polling = false;
+		}
+	}
+
+	private async handleNewFile(full: string, onAdd: (event: WatchEvent) => void) {
+		try {
+			const st = await fsp.stat(full);
+			if (!st.isFile()) return;
+			if (!(await this.fsSafe.isStable(full))) return;
+			onAdd({ path: full, birthtimeMs: st.birthtimeMs, mtimeMs: st.mtimeMs, size: st.size });
+		} catch (error) {
+			const err = error instanceof Error ? error : new Error(String(error));
+			// ENOENT is expected for files that disappear during processing
+			if ((err as NodeJS.ErrnoException).code !== 'ENOENT') this.report(err);
+		}
+	}
+
+	private async queryFileId(p: string): Promise<string> {
+		const { stdout } = await execFile('fsutil', ['file', 'queryfileid', p], { windowsHide: true });
+		const match = /0x[0-9a-f]+/i.exec(stdout);
+		if (!match) throw new Error(`unexpected fsutil output: ${stdout.trim()}`);
+		return normalizeFileId(match[0]);
+	}
+
+	private async queryNextUsn(): Promise<bigint> {
+		const { stdout } = await execFile('fsutil', ['usn', 'queryjournal', this.volume], {
+			windowsHide: true,
+		});
+		const match = /Next Usn\s*:\s*(0x[0-9a-f]+)/i.exec(stdout);
+		if (!match?.[1]) throw new Error(`unexpected fsutil output: ${stdout.trim()}`);
+		return BigInt(match[1]);
+	}
+
+	private report(error: Error) {
+		for (const handler of this.errorHandlers) {
+			try {
+				handler(error, this.dir);
+			} catch {
+				// Don't let handler errors propagate
+			}
+		}
+	}
+
+	async stop(): Promise<void> {
+		this.healthy = false;
+		if (this.timer) clearInterval(this.timer);
+		this.timer = null;
+		const fallback = this.fallback;
+		this.fallback = null;
+		await fallback?.stop();
+	}
+
+	isHealthy(): boolean {
+		if (this.fallback) return this.fallback.isHealthy();
+		return this.healthy && this.timer !== null;
+	}
+
+	onError(handler: WatchServiceErrorHandler): () => void {
+		this.errorHandlers.add(handler);
+		return () => this.errorHandlers.delete(handler);
+	}
+
+	dispose(): void | Promise<void> {
+		this.errorHandlers.clear();
+		return this.stop();
+	}
+}
+
+/** Whether `dir` holds at least `threshold` entries, reading no further than needed. */
+export async function hasAtLeastEntries(dir: string, threshold: number): Promise<boolean> {
+	let count = 0;
+	try {
+		for await (const _entry of await fsp.opendir(dir)) {
+			if (++count >= threshold) return true;
+		}
+	} catch {
+		// Unreadable directories stay on the default watcher
+	}
+	return false;
+}
+
+/** A closed record for a file that was created or renamed into place and not deleted. */
+function isFinishedArrival(reason: number): boolean {
+	if ((reason & USN_REASON_CLOSE) === 0 || (reason & USN_REASON_FILE_DELETE) !== 0) return false;
+	return (reason & (USN_REASON_FILE_CREATE | USN_REASON_RENAME_NEW_NAME)) !== 0;
+}
+
+/** File IDs compared as lowercase hex without padding, whatever width fsutil printed. */
+export function normalizeFileId(id: string): string {
+	return BigInt(id).toString(16);
+}
+
+/** Records from `fsutil usn readjournal <volume> csv`, located by the header's column names. */
+export function parseUsnCsv(output: string): UsnRecord[] {
+	const lines = output.split(/\r?\n/).filter((line) => line.trim() !== '');
+	const headerIndex = lines.findIndex((line) => /^"?Usn"?\s*,/i.test(line));
+	if (headerIndex === -1) return [];
+	const header = splitCsvLine(lines[headerIndex] as string).map((h) => h.trim().toLowerCase());
+	const col = (name: string) => header.indexOf(name);
+	const [usnCol, nameCol, reasonCol, parentCol] = [
+		col('usn'),
+		col('file name'),
+		col('reason'),
+		col('parent file id'),
+	];
+	if ([usnCol, nameCol, reasonCol, parentCol].includes(-1)) return [];
+
+	const records: UsnRecord[] = [];
+	for (const line of lines.slice(headerIndex + 1)) {
+		const fields = splitCsvLine(line);
+		try {
+			records.push({
+				usn: BigInt(fields[usnCol]?.trim() ?? ''),
+				name: fields[nameCol] ?? '',
+				reason: Number.parseInt(fields[reasonCol]?.trim() ?? '', 16),
+				parentId: fields[parentCol]?.trim() ?? '',
+			});
+		} catch {
+			// Skip lines that aren't records (e.g. trailing summaries)
+		}
+	}
+	return records.filter((r) => Number.isFinite(r.reason) && r.parentId.startsWith('0x'));
+}
+
+function splitCsvLine(line: string): string[] {
+	const fields: string[] = [];
+	let current = '';
+	let quoted = false;
+	for (let i = 0; i < line.length; i++) {
+		const ch = line[i];
+		if (quoted) {
+			if (ch === '"' && line[i + 1] === '"') {
+				current += '"';
+				i++;
+			} else if (ch === '"') {
+				quoted = false;
+			} else {
+				current += ch;
+			}
+		} else if (ch === '"') {
+			quoted = true;
+		} else if (ch === ',') {
+			fields.push(current);
+			current = '';
+		} else {
+			current += ch;
+		}
+	}
+	fields.push(current);
+	return fields;
+}
@@ -0,0 +1,31 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { describe, expect, test } from 'vitest';
+import { isMounted, volumeRoot } from './Volume.js';
+
+describe('volumeRoot', () => {
+	test('finds macOS volume mount points', () => {
+		expect(volumeRoot('/Volumes/Camera Card/DCIM', 'darwin')).toBe('/Volumes/Camera Card');
+		expect(volumeRoot('/Users/me/Desktop', 'darwin')).toBeNull();
+	});
+
+	test('finds Linux removable media mount points', () => {
+		expect(volumeRoot('/media/me/USB/photos', 'linux')).toBe('/media/me/USB');
+		expect(volumeRoot('/run/media/me/USB', 'linux')).toBe('/run/media/me/USB');
+		expect(volumeRoot('/mnt/backup/scans', 'linux')).toBe('/mnt/backup');
+		expect(volumeRoot('/home/me/Pictures', 'linux')).toBeNull();
+	});
+});
+
+describe('isMounted', () => {
+	test('treats a plain directory as unmounted', async () => {
+		const dir = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-volume-'));
+		try {
+			expect(await isMounted(dir)).toBe(false);
+			expect(await isMounted(path.join(dir, 'missing'))).toBe(false);
+		} finally {
+			await fs.rm(dir, { recursive: true, force: true });
+		}
+	});
+});
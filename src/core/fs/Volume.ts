@@ -0,0 +1,46 @@
+import fsp from 'node:fs/promises';
+import path from 'node:path';
+
+/**
+ * The mount point of the removable or external volume holding `dir`, or null when `dir` is on
+ * the system disk. Recognised by where each platform mounts such volumes: `/Volumes/<name>` on
+ * macOS, `/media/<user>/<name>`, `/run/media/<user>/<name>` or `/mnt/<name>` on Linux, and any
+ * drive but the system drive on Windows.
+ */
+export function volumeRoot(
+	dir: string,
+	platform: NodeJS.Platform = process.platform,
+): string | null {
+	switch (platform) {
+		case 'darwin':
+			return /^\/Volumes\/[^/]+/.exec(dir)?.[0] ?? null;
+		case 'linux':
+			return /^(?:\/(?:run\/)?media\/[^/]+\/[^/]+|\/mnt\/[^/]+)/.exec(dir)?.[0] ?? null;
+		case 'win32': {
+			const drive = /^[a-z]:/i.exec(dir)?.[0];
+			const system = process.env.SystemDrive ?? 'C:';
+			return drive && drive.toUpperCase() !== system.toUpperCase() ? `${drive}\\` : null;
+		}
+		default:
+			return null;
+	}
+}
+
+/** How a volume is named to the user: its folder name, or the drive on Windows. */
+export function volumeName(root: string): string {
+	return path.basename(root) || root;
+}
+
+/**
+ * Whether a volume is mounted at `root`: it exists and sits on a different device from the
+ * directory it is mounted in. Drive roots only need to exist.
+ */
+export async function isMounted(root: string): Promise<boolean> {
+	const parent = path.dirname(root);
+	try {
+		const st = await fsp.stat(root);
+		return parent === root || st.dev !== (await fsp.stat(parent)).dev;
+	} catch {
+		return false;
+	}
+}
@@ -4,6 +4,11 @@ import path from 'node:path';
 import type { FsSafe } from './FsSafe.js';
 import type { IWatchService, WatchEvent, WatchServiceErrorHandler } from '../../types/index';
 
+/** Whether any folder on the way from the watched directory to `name` is hidden, or it is. */
+export function isHiddenPath(name: string): boolean {
+	return name.split(/[\\/]/).some((part) => part.startsWith('.'));
+}
+
 export class WatchService implements IWatchService {
 	private watcher: fs.FSWatcher | null = null;
 	private healthy = false;
@@ -13,12 +18,14 @@ export class WatchService implements IWatchService {
 	constructor(
 		private readonly dir: string,
 		private readonly fsSafe: FsSafe,
+		/** Also watch subfolders, through FSEvents on macOS and ReadDirectoryChangesW on Windows. */
+		private readonly recursive = false,
 	) {}
 
 	async start(onAdd: (event: WatchEvent) => void): Promise<void> {
 		this.stopCurrent();
 
-		this.watcher = fs.watch(this.dir, { persistent: true, recursive: false });
+		this.watcher = fs.watch(this.dir, { persistent: true, recursive: this.recursive });
 
 		this.watcher.on('error', (error: Error) => {
 			this.healthy = false;
@@ -34,7 +41,7 @@ export class WatchService implements IWatchService {
 		this.watcher.on('change', (_eventType, filename) => {
 			if (!this.healthy || !filename) return;
 			const name = typeof filename === 'string' ? filename : filename.toString();
-			if (name.startsWith('.')) return;
+			if (isHiddenPath(name)) return;
 			const full = path.join(this.dir, name);
 			if (this.pending.has(full)) return;
 			this.pending.add(full);
@@ -0,0 +1,50 @@
+import fs from 'node:fs/promises';
+import os from 'node:os';
+import path from 'node:path';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { FsSafe } from '../fs/FsSafe.js';
+import { commandArgs, moveInto } from './HandOff.js';
+
+describe('commandArgs', () => {
+	it('substitutes the file path for placeholders', () => {
+		expect(commandArgs(['lp', '-d', 'office', '{file}'], '/tmp/a.pdf')).toEqual([
+			'lp',
+			['-d', 'office', '/tmp/a.pdf'],
+		]);
+		expect(commandArgs(['upload', '--src={file}'], '/tmp/a.pdf')).toEqual([
+			'upload',
+			['--src=/tmp/a.pdf'],
+		]);
+	});
+
+	it('appends the file path when no argument mentions it', () => {
+		expect(commandArgs(['./upload.sh'], '/tmp/a.pdf')).toEqual(['./upload.sh', ['/tmp/a.pdf']]);
+	});
+});
+
+describe('moveInto', () => {
+	let root: string;
+
+	beforeEach(async () => {
+		root = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-handoff-'));
+	});
+
+	afterEach(async () => {
+		await fs.rm(root, { recursive: true, force: true });
+	});
+
+	it('moves the file into the destination without overwriting', async () => {
+		const destination = path.join(root, 'out');
+		await fs.mkdir(destination);
+		await fs.writeFile(path.join(destination, 'Scan.pdf'), 'old');
+		const file = path.join(root, 'Scan.pdf');
+		await fs.writeFile(file, 'new');
+
+		const moved = await moveInto(file, destination, new FsSafe());
+
+		expect(moved).toBe(path.join(destination, 'Scan_2.pdf'));
+		expect(await fs.readFile(moved, 'utf8')).toBe('new');
+		expect(await fs.readFile(path.join(destination, 'Scan.pdf'), 'utf8')).toBe('old');
+		await expect(fs.access(file)).rejects.toThrow();
+	});
+});
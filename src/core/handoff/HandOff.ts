@@ -0,0 +1,51 @@
+import { execFile as execFileCb } from 'node:child_process';
+import { constants as fsConstants } from 'node:fs';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import { promisify } from 'node:util';
+import type { FsSafe } from '../fs/FsSafe.js';
+import { claimName } from '../rename/NameTemplate.js';
+
+const execFile = promisify(execFileCb);
+
+/** Hand-off commands still running after this long are killed and reported as failed. */
+const COMMAND_TIMEOUT_MS = 5 * 60_000;
+
+/** Stands for the file's path in a hand-off command's arguments. */
+export const FILE_PLACEHOLDER = '{file}';
+
+/**
+ * Move `file` into `destination`, keeping its name unless one there already has it, in which case
+ * it gets an `_N` suffix. Moves across volumes copy and then remove the original. Returns the
+ * file's new path.
+ */
+export async function moveInto(file: string, destination: string, fsSafe: FsSafe): Promise<string> {
+	await fs.mkdir(destination, { recursive: true });
+	const taken = new Set(await fs.readdir(destination));
+	const target = path.join(destination, claimName(taken, path.basename(file)));
+	try {
+		await fsSafe.atomicRename(file, target);
+	} catch (error) {
+		if ((error as NodeJS.ErrnoException).code !== 'EXDEV') throw error;
+		await fs.copyFile(file, target, fsConstants.COPYFILE_EXCL);
+		await fs.unlink(file);
+	}
+	return target;
+}
+
+/**
+ * The program and arguments to run for `file`: every `{file}` in the arguments is replaced by its
+ * path, and the path is appended when no argument mentions it.
+ */
+export function commandArgs(command: string[], file: string): [string, string[]] {
+	const [program = '', ...args] = command;
+	const mentioned = args.some((arg) => arg.includes(FILE_PLACEHOLDER));
+	const resolved = args.map((arg) => arg.replaceAll(FILE_PLACEHOLDER, file));
+	return [program, mentioned ? resolved : [...resolved, file]];
+}
+
+/** Run a hand-off command for `file` without a shell, failing on a non-zero exit or timeout. */
+export async function runHandOffCommand(command: string[], file: string): Promise<void> {
+	const [program, args] = commandArgs(command, file);
+	await execFile(program, args, { timeout: COMMAND_TIMEOUT_MS, windowsHide: true });
+}
@@ -0,0 +1,65 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import type { ImportPreset, ImportProgress } from '../../types/index.js';
+import { runImport } from './ImportWorkflow.js';
+
+describe('runImport', () => {
+	let root: string;
+	let card: string;
+	let preset: ImportPreset;
+
+	beforeEach(async () => {
+		root = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-import-'));
+		card = path.join(root, 'card');
+		await fs.mkdir(path.join(card, 'DCIM', '100CANON'), { recursive: true });
+		await fs.mkdir(path.join(card, 'DCIM', '101CANON'), { recursive: true });
+		preset = {
+			id: 'camera',
+			name: 'Camera',
+			enabled: true,
+			template: '<prefix>_<original>',
+			prefix: 'Trip',
+			destination: path.join(root, 'library'),
+			eject: true,
+		};
+	});
+
+	afterEach(async () => {
+		await fs.rm(root, { recursive: true, force: true });
+	});
+
+	it('moves renamed files into the destination and reports progress', async () => {
+		await fs.writeFile(path.join(card, 'DCIM', '100CANON', 'IMG_0001.JPG'), 'a');
+		await fs.writeFile(path.join(card, 'DCIM', '101CANON', 'IMG_0001.JPG'), 'b');
+		await fs.writeFile(path.join(card, 'DCIM', '100CANON', '.hidden'), 'c');
+
+		const updates: ImportProgress[] = [];
+		const result = await runImport(card, preset, (progress) => updates.push(progress));
+
+		expect(result).toMatchObject({ total: 2, imported: 2, failed: 0, done: true, eject: true });
+		expect((await fs.readdir(preset.destination)).sort()).toEqual([
+			'Trip_IMG_0001.jpg',
+			'Trip_IMG_0001_2.jpg',
+		]);
+		expect(await fs.readdir(path.join(card, 'DCIM', '100CANON'))).toEqual(['.hidden']);
+		expect(updates.map((update) => update.imported)).toEqual([0, 1, 2, 2]);
+	});
+
+	it('never overwrites library files and does not eject after a failure', async () => {
+		await fs.writeFile(path.join(card, 'DCIM', '100CANON', 'IMG_0001.JPG'), 'a');
+		await fs.mkdir(preset.destination);
+		await fs.writeFile(path.join(preset.destination, 'Trip_IMG_0001.jpg'), 'existing');
+
+		const result = await runImport(card, preset, () => {});
+
+		expect(result).toMatchObject({ imported: 1, failed: 0 });
+		expect(await fs.readFile(path.join(preset.destination, 'Trip_IMG_0001.jpg'), 'utf8')).toBe(
+			'existing',
+		);
+
+		const missing = await runImport(path.join(root, 'no-card'), preset, () => {});
+		expect(missing).toMatchObject({ failed: 1, done: true, eject: false });
+	});
+});
@@ -0,0 +1,136 @@
+import { constants as fsConstants } from 'node:fs';
+import fs from 'node:fs/promises';
+import path from 'node:path';
+import type { ImportPreset, ImportProgress } from '../../types/index.js';
+import { isMounted } from '../fs/Volume.js';
+import {
+	buildNameFromTemplate,
+	claimName,
+	DEFAULT_TEMPLATE,
+	getExt,
+} from '../rename/NameTemplate.js';
+
+/** Where cameras store photos, per the DCF standard. */
+export const DCIM = 'DCIM';
+
+/** Mount points of volumes with a `DCIM` folder, i.e. camera cards and cameras in storage mode. */
+export async function findCameraCards(
+	platform: NodeJS.Platform = process.platform,
+): Promise<string[]> {
+	const cards: string[] = [];
+	for (const root of await mountedVolumes(platform)) {
+		try {
+			if ((await fs.stat(path.join(root, DCIM))).isDirectory() && (await isMounted(root))) {
+				cards.push(root);
+			}
+		} catch {
+			// No DCIM folder
+		}
+	}
+	return cards;
+}
+
+async function mountedVolumes(platform: NodeJS.Platform): Promise<string[]> {
+	switch (platform) {
+		case 'darwin':
+			return await children('/Volumes');
+		case 'linux': {
+			const users = [...(await children('/media')), ...(await children('/run/media'))];
+			return (await Promise.all(users.map(children))).flat();
+		}
+		case 'win32':
+			return 'DEFGHIJKLMNOPQRSTUVWXYZ'.split('').map((drive) => `${drive}:\\`);
+		default:
+			return [];
+	}
+}
+
+async function children(dir: string): Promise<string[]> {
+	try {
+		const dirents = await fs.readdir(dir, { withFileTypes: true });
+		return dirents.filter((d) => !d.name.startsWith('.')).map((d) => path.join(dir, d.name));
+	} catch {
+		return [];
+	}
+}
+
+/** Files under `dir`, depth first in name order, skipping hidden files and folders. */
+async function collectFiles(dir: string): Promise<string[]> {
+	const dirents = await fs.readdir(dir, { withFileTypes: true });
+	dirents.sort((a, b) => a.name.localeCompare(b.name));
+	const files: string[] = [];
+	for (const dirent of dirents) {
+		if (dirent.name.startsWith('.')) continue;
+		const full = path.join(dir, dirent.name);
+		if (dirent.isDirectory()) files.push(...(await collectFiles(full)));
+		else if (dirent.isFile()) files.push(full);
+	}
+	return files;
+}
+
+/**
+ * Move every file in the card's `DCIM` folder into the preset's destination, named by its
+ * template from the file's creation time. Names already in the destination get `_N` suffixes.
+ * Files are copied before the original is removed, so a failure never loses a photo; a card
+ * that is write-protected keeps its originals. `onProgress` is called before the first file and
+ * after each one.
+ */
+export async function runImport(
+	card: string,
+	preset: ImportPreset,
+	onProgress: (progress: ImportProgress) => void,
+): Promise<ImportProgress> {
+	const progress: ImportProgress = {
+		preset: preset.id,
+		card,
+		total: 0,
+		imported: 0,
+		failed: 0,
+		done: false,
+		eject: false,
+	};
+	try {
+		const files = await collectFiles(path.join(card, DCIM));
+		progress.total = files.length;
+		await fs.mkdir(preset.destination, { recursive: true });
+		const taken = new Set(await fs.readdir(preset.destination));
+		onProgress({ ...progress });
+
+		for (const file of files) {
+			try {
+				const st = await fs.stat(file);
+				const base = buildNameFromTemplate(preset.template || DEFAULT_TEMPLATE, {
+					originalPath: file,
+					birthtime: new Date(st.birthtimeMs || st.mtimeMs),
+					ext: getExt(file),
+					prefix: preset.prefix || 'IMG',
+				});
+				await moveFile(file, path.join(preset.destination, claimName(taken, base)));
+				progress.imported++;
+			} catch (error) {
+				const reason = error instanceof Error ? error.message : String(error);
+				progress.failed++;
+				progress.error = `${path.basename(file)}: ${reason}`;
+			}
+			onProgress({ ...progress });
+		}
+	} catch (error) {
+		progress.failed++;
+		progress.error = error instanceof Error ? error.message : String(error);
+	}
+	progress.done = true;
+	progress.eject = preset.eject && progress.failed === 0;
+	onProgress({ ...progress });
+	return progress;
+}
+
+/** Copy across devices without overwriting, then remove the original if the card allows it. */
+async function moveFile(from: string, to: string) {
+	await fs.copyFile(from, to, fsConstants.COPYFILE_EXCL);
+	try {
+		await fs.unlink(from);
+	} catch (error) {
+		const code = (error as NodeJS.ErrnoException).code;
+		if (code !== 'EROFS' && code !== 'EACCES' && code !== 'EPERM') throw error;
+	}
+}
@@ -0,0 +1,324 @@
+import os from 'node:os';
+import path from 'node:path';
+import fs from 'node:fs/promises';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { JournalStore } from './JournalStore.js';
+import { FsSafe } from '../fs/FsSafe.js';
+
+describe('JournalStore.search', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	let journal: JournalStore;
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+		journal = new JournalStore(new FsSafe());
+		await journal.record(
+			'/shots/Screenshot 1.png',
+			'/shots/Screenshot_2025-01-01.png',
+			{ rule: 'screenshots' },
+		);
+		await journal.record(
+			'/downloads/invoice.pdf',
+			'/downloads/2025-02-01_invoice.pdf',
+			{ rule: 'invoices' },
+		);
+		await journal.record('/shots/nested/Screen Recording.mov', '/shots/nested/Recording.mov');
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('matches old and new names case-insensitively, newest first', async () => {
+		const results = await journal.search({ text: 'screen' });
+		expect(results.map((r) => r.to)).toEqual([
+			'/shots/nested/Recording.mov',
+			'/shots/Screenshot_2025-01-01.png',
+		]);
+		expect(await journal.search({ text: '2025-02' })).toHaveLength(1);
+	});
+
+	it('keeps the reason a rename was deferred', async () => {
+		await journal.record('/shots/a.png', '/shots/b.png', {
+			rule: 'screenshots',
+			deferred: 'open in Preview (pid 412)',
+		});
+		const [latest] = await journal.search({ limit: 1 });
+		expect(latest?.deferred).toBe('open in Preview (pid 412)');
+		expect((await journal.search({ text: 'invoice' }))[0]?.deferred).toBeUndefined();
+	});
+
+	it('supports regex, rule, and directory filters', async () => {
+		expect(await journal.search({ text: '^\\d{4}-', regex: true })).toHaveLength(1);
+		const byRule = await journal.search({ rule: 'screenshots' });
+		expect(byRule.map((r) => r.rule)).toEqual(['screenshots']);
+		expect(await journal.search({ directory: '/shots' })).toHaveLength(2);
+		expect(await journal.search({ directory: '/shots/nested', limit: 5 })).toHaveLength(1);
+	});
+
+	it('pages through results with offset', async () => {
+		const page = await journal.search({ offset: 1, limit: 1 });
+		expect(page.map((r) => r.to)).toEqual(['/downloads/2025-02-01_invoice.pdf']);
+		expect(page[0]?.id).toEqual(expect.any(String));
+	});
+
+	it('totals renames and bytes per directory, counting subfolders', async () => {
+		await journal.record('/shots/big.png', '/shots/2025_big.png', { bytes: 2048 });
+		await journal.record('/shots/nested/clip.mov', '/shots/nested/2025_clip.mov', { bytes: 512 });
+		const usage = await journal.usage(['/shots', '/shots/nested', '/music'], 0);
+		expect(usage).toEqual([
+			{ directory: '/shots', filesRenamed: 4, bytesProcessed: 2560 },
+			{ directory: '/shots/nested', filesRenamed: 2, bytesProcessed: 512 },
+			{ directory: '/music', filesRenamed: 0, bytesProcessed: 0 },
+		]);
+		const [later] = await journal.usage(['/shots'], Date.now() + 1000);
+		expect(later?.filesRenamed).toBe(0);
+	});
+
+	it('rejects invalid regular expressions', async () => {
+		await expect(journal.search({ text: '(', regex: true })).rejects.toThrow();
+	});
+});
+
+describe('JournalStore.prune', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	let journal: JournalStore;
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+		journal = new JournalStore(new FsSafe());
+		for (let i = 0; i < 5; i++) {
+			await journal.record(`/shots/file-${i}.png`, `/shots/renamed-${i}.png`);
+		}
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('keeps the newest entries within maxEntries', async () => {
+		expect(await journal.prune({ maxEntries: 2 })).toEqual({ removed: 3, remaining: 2 });
+		const left = await journal.search({});
+		expect(left.map((e) => e.to)).toEqual(['/shots/renamed-4.png', '/shots/renamed-3.png']);
+	});
+
+	it('drops entries older than maxAgeDays', async () => {
+		const later = Date.now() + 2 * 24 * 60 * 60 * 1000;
+		expect(await journal.prune({ maxAgeDays: 1 }, later)).toEqual({ removed: 5, remaining: 0 });
+	});
+
+	it('is a no-op for an empty policy', async () => {
+		expect(await journal.prune({})).toEqual({ removed: 0, remaining: 5 });
+	});
+});
+
+describe('JournalStore.undoSession', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	let journal: JournalStore;
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+		journal = new JournalStore(new FsSafe());
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('restores only the renames tagged with the session', async () => {
+		const file = (name: string) => path.join(tempRoot, name);
+		for (const name of ['import-a.png', 'import-b.png', 'other.png', 'stuck.png']) {
+			await fs.writeFile(file(`renamed-${name}`), name);
+		}
+		await journal.record(file('import-a.png'), file('renamed-import-a.png'), { session: 's1' });
+		await journal.record(file('other.png'), file('renamed-other.png'));
+		await journal.record(file('import-b.png'), file('renamed-import-b.png'), { session: 's1' });
+		// Its original folder is now a file, so it can't be put back
+		await fs.writeFile(file('blocked'), '');
+		const stuck = path.join(file('blocked'), 'stuck.png');
+		await journal.record(stuck, file('renamed-stuck.png'), { session: 's1' });
+
+		expect(await journal.undoSession('s1')).toEqual({ undone: 2, failed: 1 });
+		expect(await fs.readFile(file('import-a.png'), 'utf8')).toBe('import-a.png');
+		expect(await fs.readFile(file('import-b.png'), 'utf8')).toBe('import-b.png');
+		const left = await journal.search({});
+		expect(left.map((e) => path.basename(e.from))).toEqual(['stuck.png', 'other.png']);
+		expect(await journal.search({ session: 's1' })).toHaveLength(1);
+	});
+});
+
+describe('JournalStore.undoEntry and redo', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	let journal: JournalStore;
+	const file = (name: string) => path.join(tempRoot, name);
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+		journal = new JournalStore(new FsSafe());
+		for (const name of ['a', 'b', 'c']) {
+			await fs.writeFile(file(`renamed-${name}.png`), name);
+			await journal.record(file(`${name}.png`), file(`renamed-${name}.png`));
+		}
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('undoes one rename without touching newer ones, and redoes it', async () => {
+		const [, middle] = await journal.search({});
+		expect(await journal.undoEntry(middle?.id as string)).toEqual({ ok: true });
+		expect(await fs.readFile(file('b.png'), 'utf8')).toBe('b');
+		expect(await fs.readFile(file('renamed-c.png'), 'utf8')).toBe('c');
+		expect((await journal.search({})).map((e) => path.basename(e.to))).toEqual([
+			'renamed-c.png',
+			'renamed-a.png',
+		]);
+		expect(journal.redoCount()).toBe(1);
+
+		expect(await journal.redo()).toEqual({ ok: true });
+		expect(await fs.readFile(file('renamed-b.png'), 'utf8')).toBe('b');
+		const [latest] = await journal.search({});
+		expect(latest).toMatchObject({ id: middle?.id, to: file('renamed-b.png') });
+		expect(await journal.redo()).toEqual({ ok: false, reason: 'empty' });
+	});
+
+	it('refuses to undo a rename whose file was renamed again', async () => {
+		await fs.rename(file('renamed-a.png'), file('final-a.png'));
+		await journal.record(file('renamed-a.png'), file('final-a.png'));
+		const first = (await journal.search({})).at(-1);
+
+		const result = await journal.undoEntry(first?.id as string);
+		expect(result).toMatchObject({ ok: false, reason: expect.stringContaining('renamed again') });
+		expect(await journal.undoEntry('missing')).toMatchObject({ ok: false });
+		expect(await journal.search({})).toHaveLength(4);
+	});
+});
+
+describe('JournalStore health', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	const journalFile = () => path.join(tempRoot, 'namefix', 'journal.ndjson');
+	const entryLine = (name: string) =>
+		`${JSON.stringify({ from: `/a/${name}`, to: `/b/${name}`, ts: 1 })}\n`;
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+		process.env.XDG_STATE_HOME = tempRoot;
+		await fs.mkdir(path.dirname(journalFile()), { recursive: true });
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('keeps renames in memory while the journal is corrupt and salvages it on repair', async () => {
+		await fs.writeFile(journalFile(), `${entryLine('one')}{"from": "/a/tw\n${entryLine('three')}`);
+		const changes: string[] = [];
+		const journal = new JournalStore(new FsSafe(), (health) => changes.push(health.state));
+
+		const health = await journal.check();
+		expect(health).toMatchObject({ state: 'degraded', reason: 'corrupt', badLines: 1 });
+		await journal.record('/a/four', '/b/four');
+		expect(await fs.readFile(journalFile(), 'utf8')).not.toContain('four');
+		expect(await journal.search({})).toHaveLength(3);
+
+		expect((await journal.repair('salvage')).state).toBe('ok');
+		expect(changes).toEqual(['degraded', 'ok']);
+		const lines = (await fs.readFile(journalFile(), 'utf8')).trim().split('\n');
+		expect(lines.map((line) => JSON.parse(line).to)).toEqual(['/b/one', '/b/three', '/b/four']);
+		const kept = await fs.readdir(path.dirname(journalFile()));
+		expect(kept.some((name) => name.startsWith('journal.ndjson.corrupt-'))).toBe(true);
+	});
+
+	it('restores the latest backup plus renames recorded since', async () => {
+		await fs.writeFile(journalFile(), entryLine('one') + entryLine('two'));
+		expect((await new JournalStore(new FsSafe()).check()).backup?.entries).toBe(2);
+
+		await fs.writeFile(journalFile(), 'not json at all\n');
+		const journal = new JournalStore(new FsSafe());
+		expect((await journal.check()).state).toBe('degraded');
+		await journal.record('/a/three', '/b/three');
+
+		await journal.repair('restore');
+		const results = await journal.search({});
+		expect(results.map((entry) => entry.to)).toEqual(['/b/three', '/b/two', '/b/one']);
+	});
+});
+
+describe('JournalStore.merge', () => {
+	let tempRoot: string;
+	let previousState: string | undefined;
+	const exportFile = () => path.join(tempRoot, 'history.ndjson');
+	/** Switch to `machine`'s state directory, starting its journal with `entries` if given. */
+	const useMachine = async (machine: string, entries: object[] = []) => {
+		process.env.XDG_STATE_HOME = path.join(tempRoot, machine);
+		const dir = path.join(tempRoot, machine, 'namefix');
+		await fs.mkdir(dir, { recursive: true });
+		const lines = entries.map((entry) => `${JSON.stringify(entry)}\n`).join('');
+		await fs.writeFile(path.join(dir, 'journal.ndjson'), lines);
+		return new JournalStore(new FsSafe());
+	};
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-journal-'));
+		previousState = process.env.XDG_STATE_HOME;
+	});
+
+	afterEach(async () => {
+		if (previousState === undefined) Reflect.deleteProperty(process.env, 'XDG_STATE_HOME');
+		else process.env.XDG_STATE_HOME = previousState;
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('adds another machine’s renames once, in time order', async () => {
+		const old = await useMachine('old', [
+			{ id: 'a', from: '/a/one', to: '/b/one', ts: 1 },
+			{ id: 'b', from: '/a/three', to: '/b/three', ts: 3 },
+		]);
+		expect(await old.exportTo(exportFile())).toBe(2);
+
+		const fresh = await useMachine('new', [{ id: 'c', from: '/a/two', to: '/b/two', ts: 2 }]);
+		expect(await fresh.merge(exportFile())).toEqual({ imported: 2, duplicates: 0, skipped: 0 });
+		expect(await fresh.merge(exportFile())).toEqual({ imported: 0, duplicates: 2, skipped: 0 });
+		const results = await fresh.search({});
+		expect(results.map((entry) => entry.to)).toEqual(['/b/three', '/b/two', '/b/one']);
+	});
+
+	it('matches entries recorded before ids were kept by their contents', async () => {
+		const legacy = { from: '/a/old', to: '/b/old', ts: 5 };
+		await (await useMachine('old', [legacy])).exportTo(exportFile());
+
+		const fresh = await useMachine('new', [legacy]);
+		expect(await fresh.merge(exportFile())).toMatchObject({ imported: 0, duplicates: 1 });
+	});
+
+	it('rejects files that are not history exports', async () => {
+		await fs.writeFile(exportFile(), 'hello\n');
+		const fresh = await useMachine('new');
+		await expect(fresh.merge(exportFile())).rejects.toThrow('not a Namefix history export');
+	});
+});
@@ -1,11 +1,26 @@
+import { createHash, randomUUID } from 'node:crypto';
 import fs from 'node:fs/promises';
 import fscb from 'node:fs';
 import path from 'node:path';
 import { stateDir } from '../../utils/paths.js';
-import type { IJournalStore } from '../../types/index';
+import { restoreOwnership } from '../../utils/user.js';
+import type {
+	DirectoryUsage,
+	HistoryBackup,
+	HistoryEntry,
+	HistoryHealth,
+	HistoryMerge,
+	HistoryRepair,
+	HistoryQuery,
+	IJournalStore,
+	JournalDetails,
+	PruneResult,
+	RetentionPolicy,
+	SessionUndoResult,
+} from '../../types/index';
 import type { FsSafe } from '../fs/FsSafe.js';
 
-type Entry = { from: string; to: string; ts: number };
+type Entry = HistoryEntry;
 
 function journalDir() {
 	return stateDir('namefix');
@@ -15,43 +30,209 @@ function journalPath() {
 	return path.join(journalDir(), 'journal.ndjson');
 }
 
+function backupPath() {
+	return `${journalPath()}.bak`;
+}
+
+/** Below this much free space the journal isn't written, rather than risk a half-written file. */
+const MIN_FREE_MB = 16;
+
+/** Undone renames kept for {@link JournalStore.redo}. */
+const MAX_REDO = 50;
+
+/**
+ * The rename journal, one JSON entry per line. When it can't be read or written (unreadable lines,
+ * a full disk, I/O errors) the store goes degraded: entries are kept in memory only, so renames
+ * and undo keep working, until {@link JournalStore.repair} writes them out again.
+ */
 export class JournalStore implements IJournalStore {
 	private cache: Entry[] = [];
-	constructor(private readonly fsSafe: FsSafe) {}
+	private health: HistoryHealth = { state: 'ok' };
+	private backupInfo: HistoryBackup | null = null;
+	/** Undone renames, newest last, and where each file was put back. Kept for this run only. */
+	private undone: { entry: Entry; restoredTo: string }[] = [];
+	constructor(
+		private readonly fsSafe: FsSafe,
+		private readonly onHealthChange: (health: HistoryHealth) => void = () => {},
+	) {}
 
 	private async ensure() {
 		await fs.mkdir(journalDir(), { recursive: true });
+		restoreOwnership(journalDir());
 	}
 
 	private async load(): Promise<Entry[]> {
-		await this.ensure();
+		// Degraded: memory is the only complete copy
+		if (this.health.state === 'degraded') return this.cache;
 		try {
-			const data = await fs.readFile(journalPath(), 'utf8');
-			const lines = data.split(/\r?\n/).filter(Boolean);
-			this.cache = lines.map((l) => JSON.parse(l));
-		} catch (e: unknown) {
-			if (isNodeError(e) && e.code !== 'ENOENT') {
-				throw e;
+			await this.ensure();
+			const read = await readEntries(journalPath());
+			this.cache = read?.entries ?? [];
+			if (read && read.badLines > 0) {
+				const lines = read.badLines === 1 ? '1 line' : `${read.badLines} lines`;
+				this.degrade('corrupt', `${lines} of the journal could not be read`, read.badLines);
 			}
+		} catch (e: unknown) {
 			this.cache = [];
+			this.degrade(writeFailure(e), e instanceof Error ? e.message : String(e));
 		}
 		return this.cache;
 	}
 
-	async record(from: string, to: string): Promise<void> {
-		await this.ensure();
-		const entry: Entry = { from, to, ts: Date.now() };
-		await fs.appendFile(journalPath(), `${JSON.stringify(entry)}\n`, 'utf8');
+	async record(from: string, to: string, details: JournalDetails = {}): Promise<void> {
+		const entry: Entry = { id: randomUUID(), from, to, ts: Date.now() };
+		if (details.rule) entry.rule = details.rule;
+		if (details.deferred) entry.deferred = details.deferred;
+		if (details.bytes !== undefined) entry.bytes = details.bytes;
+		if (details.session) entry.session = details.session;
+		await this.append(entry);
+	}
+
+	private async append(entry: Entry) {
+		if (this.health.state === 'ok') {
+			try {
+				await this.ensure();
+				await fs.appendFile(journalPath(), `${JSON.stringify(entry)}\n`, 'utf8');
+				restoreOwnership(journalPath());
+			} catch (e: unknown) {
+				// Keep everything in memory from here; the file may now end in a partial line
+				const read = await readEntries(journalPath()).catch(() => null);
+				if (read) this.cache = read.entries;
+				this.degrade(writeFailure(e), e instanceof Error ? e.message : String(e));
+			}
+		}
 		this.cache.push(entry);
 	}
 
+	getHealth(): HistoryHealth {
+		return { ...this.health, ...(this.backupInfo ? { backup: this.backupInfo } : {}) };
+	}
+
+	/**
+	 * Read the journal and make sure there is room to keep writing it. A healthy journal is then
+	 * backed up to `journal.ndjson.bak`; otherwise the store goes degraded, leaving the last good
+	 * backup alone.
+	 */
+	async check(): Promise<HistoryHealth> {
+		this.backupInfo = await readBackupInfo();
+		await this.load();
+		if (this.health.state === 'ok') {
+			if ((await freeBytes(journalDir())) < MIN_FREE_MB * 1024 * 1024) {
+				this.degrade('disk-full', `Less than ${MIN_FREE_MB} MB free`);
+			} else {
+				await this.backup();
+			}
+		}
+		return this.getHealth();
+	}
+
+	/**
+	 * Write the journal out again and leave degraded mode. `salvage` keeps every entry that could be
+	 * read plus those recorded since; `restore` starts from the latest backup and adds the entries
+	 * recorded after it was taken. A corrupt journal is kept beside the new one as
+	 * `journal.ndjson.corrupt-<time>`. Throws, staying degraded, when the disk still refuses.
+	 */
+	async repair(mode: HistoryRepair): Promise<HistoryHealth> {
+		let entries = this.cache;
+		if (mode === 'restore') {
+			const backup = await readEntries(backupPath());
+			if (!backup) throw new Error('There is no history backup to restore');
+			const since = backup.entries.at(-1)?.ts ?? 0;
+			const known = new Set(backup.entries.map((entry) => JSON.stringify(entry)));
+			const newer = this.cache.filter(
+				(entry) => entry.ts >= since && !known.has(JSON.stringify(entry)),
+			);
+			entries = [...backup.entries, ...newer];
+		}
+		if ((await freeBytes(journalDir())) < MIN_FREE_MB * 1024 * 1024) {
+			throw new Error(`Free up space first; less than ${MIN_FREE_MB} MB is free`);
+		}
+		if (this.health.reason === 'corrupt' && (await existsSafe(journalPath()))) {
+			await fs.rename(journalPath(), `${journalPath()}.corrupt-${Date.now()}`);
+		}
+		await this.ensure();
+		await this.writeAll(entries);
+		this.cache = entries;
+		this.health = { state: 'ok' };
+		await this.backup();
+		this.onHealthChange(this.getHealth());
+		return this.getHealth();
+	}
+
+	private degrade(reason: HistoryHealth['reason'], detail: string, badLines?: number) {
+		const changed = this.health.state === 'ok' || this.health.reason !== reason;
+		this.health = { state: 'degraded', reason, detail, ...(badLines ? { badLines } : {}) };
+		if (changed) this.onHealthChange(this.getHealth());
+	}
+
+	private async backup() {
+		try {
+			if (!(await existsSafe(journalPath()))) return;
+			await fs.copyFile(journalPath(), backupPath());
+			restoreOwnership(backupPath());
+			this.backupInfo = { savedAt: Date.now(), entries: this.cache.length };
+		} catch (e: unknown) {
+			if (writeFailure(e) === 'disk-full') {
+				this.degrade('disk-full', e instanceof Error ? e.message : String(e));
+			}
+		}
+	}
+
 	async undo(): Promise<{ ok: boolean; reason?: string }> {
 		if (!this.cache.length) await this.load();
-		const last = this.cache.pop();
+		if (!this.cache.length) return { ok: false, reason: 'empty' };
+		return await this.revert(this.cache.length - 1);
+	}
+
+	/**
+	 * Undo the rename with this {@link HistoryEntry.id}, leaving newer renames alone. Refused when
+	 * the file was renamed again since, as the newer rename has to be undone first.
+	 */
+	async undoEntry(id: string): Promise<{ ok: boolean; reason?: string }> {
+		const entries = await this.load();
+		const index = entries.findIndex((entry) => entryId(entry) === id);
+		if (index < 0) return { ok: false, reason: 'That rename is no longer in history' };
+		const entry = entries[index] as Entry;
+		if (entries.slice(index + 1).some((later) => later.from === entry.to)) {
+			const name = path.basename(entry.to);
+			return { ok: false, reason: `${name} was renamed again since; undo that first` };
+		}
+		return await this.revert(index);
+	}
+
+	/** Rename the most recently undone file again and put it back in history. */
+	async redo(): Promise<{ ok: boolean; reason?: string }> {
+		const last = this.undone.pop();
 		if (!last) return { ok: false, reason: 'empty' };
+		const { entry, restoredTo } = last;
+		try {
+			if (await existsSafe(entry.to)) {
+				throw new Error(`${path.basename(entry.to)} already exists`);
+			}
+			await this.fsSafe.atomicRename(restoredTo, entry.to);
+		} catch (e: unknown) {
+			this.undone.push(last);
+			return { ok: false, reason: e instanceof Error ? e.message : 'rename_failed' };
+		}
+		await this.load();
+		await this.append({ ...entry, id: entryId(entry), from: restoredTo, ts: Date.now() });
+		return { ok: true };
+	}
+
+	/** How many undone renames {@link JournalStore.redo} can still bring back. */
+	redoCount(): number {
+		return this.undone.length;
+	}
+
+	/** Put the file of `cache[index]` back and drop the entry, remembering it for redo. */
+	private async revert(index: number): Promise<{ ok: boolean; reason?: string }> {
+		const entry = this.cache[index] as Entry;
 		try {
-			const target = await this.restoreTarget(last);
-			await this.fsSafe.atomicRename(last.to, target);
+			const target = await this.restoreTarget(entry);
+			await this.fsSafe.atomicRename(entry.to, target);
+			this.cache.splice(index, 1);
+			this.undone.push({ entry, restoredTo: target });
+			if (this.undone.length > MAX_REDO) this.undone.shift();
 			await this.rewrite();
 			return { ok: true };
 		} catch (e: unknown) {
@@ -60,6 +241,187 @@ export class JournalStore implements IJournalStore {
 		}
 	}
 
+	/**
+	 * Undo every rename recorded for `session`, newest first. Renames that cannot be restored stay in
+	 * the journal so they can be retried.
+	 */
+	async undoSession(session: string): Promise<SessionUndoResult> {
+		const entries = await this.load();
+		const kept: Entry[] = [];
+		let undone = 0;
+		let failed = 0;
+		for (let i = entries.length - 1; i >= 0; i--) {
+			const entry = entries[i] as Entry;
+			if (entry.session !== session) {
+				kept.push(entry);
+				continue;
+			}
+			try {
+				await this.fsSafe.atomicRename(entry.to, await this.restoreTarget(entry));
+				undone++;
+			} catch {
+				kept.push(entry);
+				failed++;
+			}
+		}
+		this.cache = kept.reverse();
+		if (undone > 0) await this.rewrite();
+		return { undone, failed };
+	}
+
+	/**
+	 * Newest-first renames matching every given filter. Throws if `regex` is set and `text` is not
+	 * a valid pattern.
+	 */
+	async search(query: HistoryQuery): Promise<HistoryEntry[]> {
+		const entries = await this.load();
+		const text = query.text?.trim();
+		let matchesText: (name: string) => boolean = () => true;
+		if (text && query.regex) {
+			const pattern = new RegExp(text, 'i');
+			matchesText = (name) => pattern.test(name);
+		} else if (text) {
+			const needle = text.toLowerCase();
+			matchesText = (name) => name.toLowerCase().includes(needle);
+		}
+		const directory = query.directory ? path.resolve(query.directory) : null;
+
+		let skip = query.offset ?? 0;
+		const results: HistoryEntry[] = [];
+		for (let i = entries.length - 1; i >= 0; i--) {
+			const entry = entries[i] as Entry;
+			if (query.since !== undefined && entry.ts < query.since) continue;
+			if (query.until !== undefined && entry.ts > query.until) continue;
+			if (query.rule && entry.rule !== query.rule) continue;
+			if (query.session && entry.session !== query.session) continue;
+			if (directory && !isWithin(directory, entry.from)) continue;
+			if (!matchesText(path.basename(entry.from)) && !matchesText(path.basename(entry.to))) continue;
+			if (skip > 0) {
+				skip--;
+				continue;
+			}
+			results.push({ ...entry, id: entryId(entry) });
+			if (query.limit && results.length >= query.limit) break;
+		}
+		return results;
+	}
+
+	/**
+	 * Renames since `since` (epoch ms) per directory, in the order given. A rename counts towards
+	 * every given directory that contains it, so nested watched folders each see their own totals.
+	 */
+	async usage(directories: string[], since: number): Promise<DirectoryUsage[]> {
+		const entries = await this.load();
+		const totals = directories.map((dir) => ({
+			directory: dir,
+			resolved: path.resolve(dir),
+			filesRenamed: 0,
+			bytesProcessed: 0,
+		}));
+		for (const entry of entries) {
+			if (entry.ts < since) continue;
+			for (const total of totals) {
+				if (!isWithin(total.resolved, entry.from)) continue;
+				total.filesRenamed += 1;
+				total.bytesProcessed += entry.bytes ?? 0;
+			}
+		}
+		return totals.map(({ directory, filesRenamed, bytesProcessed }) => ({
+			directory,
+			filesRenamed,
+			bytesProcessed,
+		}));
+	}
+
+	/**
+	 * Drop the oldest entries until the journal satisfies `policy`. Pruned renames can no longer be
+	 * undone.
+	 */
+	async prune(policy: RetentionPolicy, now = Date.now()): Promise<PruneResult> {
+		const entries = await this.load();
+		let start = 0;
+		if (policy.maxAgeDays !== undefined) {
+			const cutoff = now - policy.maxAgeDays * 24 * 60 * 60 * 1000;
+			while (start < entries.length && (entries[start] as Entry).ts < cutoff) start++;
+		}
+		if (policy.maxEntries !== undefined) {
+			start = Math.max(start, entries.length - policy.maxEntries);
+		}
+		if (policy.maxBytes !== undefined) {
+			let bytes = 0;
+			let keepFrom = entries.length;
+			while (keepFrom > start) {
+				const line = Buffer.byteLength(`${JSON.stringify(entries[keepFrom - 1])}\n`, 'utf8');
+				if (bytes + line > policy.maxBytes) break;
+				bytes += line;
+				keepFrom--;
+			}
+			start = keepFrom;
+		}
+
+		if (start > 0) {
+			this.cache = entries.slice(start);
+			await this.rewrite();
+		}
+		return { removed: start, remaining: this.cache.length };
+	}
+
+	/**
+	 * Write every entry to `file` as NDJSON for {@link JournalStore.merge} on another machine.
+	 * Entries from before ids were kept get {@link entryId}'s stand-in, so merging the same export
+	 * twice adds nothing. Returns how many entries were written.
+	 */
+	async exportTo(file: string): Promise<number> {
+		const entries = (await this.load()).map((entry) => ({ ...entry, id: entryId(entry) }));
+		await fs.mkdir(path.dirname(file), { recursive: true });
+		const tmp = `${file}.tmp`;
+		try {
+			await fs.writeFile(tmp, entries.map((e) => `${JSON.stringify(e)}\n`).join(''), 'utf8');
+			await fs.rename(tmp, file);
+		} catch (e: unknown) {
+			await fs.rm(tmp, { force: true }).catch(() => {});
+			throw e;
+		}
+		restoreOwnership(file);
+		return entries.length;
+	}
+
+	/**
+	 * Add the entries of another machine's {@link JournalStore.exportTo} file that aren't here yet,
+	 * matched by id, keeping the journal in time order so undo and usage see them where they
+	 * happened. Throws if `file` has no entries at all.
+	 */
+	async merge(file: string): Promise<HistoryMerge> {
+		const read = await readEntries(file);
+		if (!read) throw new Error(`${file} does not exist`);
+		if (!read.entries.length && read.badLines > 0) {
+			throw new Error(`${path.basename(file)} is not a Namefix history export`);
+		}
+		const entries = await this.load();
+		const known = new Set(entries.map(entryId));
+		const incoming: Entry[] = [];
+		let duplicates = 0;
+		let skipped = read.badLines;
+		for (const entry of read.entries) {
+			if (typeof entry.ts !== 'number') {
+				skipped++;
+				continue;
+			}
+			const id = entryId(entry);
+			if (known.has(id)) {
+				duplicates++;
+				continue;
+			}
+			known.add(id);
+			incoming.push({ ...entry, id });
+		}
+		if (incoming.length) {
+			this.cache = [...entries, ...incoming].sort((a, b) => a.ts - b.ts);
+			await this.rewrite();
+		}
+		return { imported: incoming.length, duplicates, skipped };
+	}
+
 	private async restoreTarget(entry: Entry): Promise<string> {
 		// If original is free, use it; else add _restored suffix
 		const exists = await existsSafe(entry.from);
@@ -77,11 +439,25 @@ export class JournalStore implements IJournalStore {
 	}
 
 	private async rewrite() {
+		if (this.health.state === 'degraded') return;
+		try {
+			await this.writeAll(this.cache);
+		} catch (e: unknown) {
+			this.degrade(writeFailure(e), e instanceof Error ? e.message : String(e));
+		}
+	}
+
+	private async writeAll(entries: Entry[]) {
 		const tmp = `${journalPath()}.tmp`;
-		const data =
-			this.cache.map((e) => JSON.stringify(e)).join('\n') + (this.cache.length ? '\n' : '');
-		await fs.writeFile(tmp, data, 'utf8');
-		await fs.rename(tmp, journalPath());
+		const data = entries.map((e) => JSON.stringify(e)).join('\n') + (entries.length ? '\n' : '');
+		try {
+			await fs.writeFile(tmp, data, 'utf8');
+			await fs.rename(tmp, journalPath());
+		} catch (e: unknown) {
+			await fs.rm(tmp, { force: true }).catch(() => {});
+			throw e;
+		}
+		restoreOwnership(journalPath());
 	}
 
 	dispose(): void | Promise<void> {
@@ -89,6 +465,18 @@ export class JournalStore implements IJournalStore {
 	}
 }
 
+/** The entry's id, or for entries recorded before ids were kept, one derived from its contents. */
+function entryId(entry: Entry): string {
+	if (entry.id) return entry.id;
+	const key = JSON.stringify([entry.ts, entry.from, entry.to]);
+	return createHash('sha256').update(key).digest('hex').slice(0, 32);
+}
+
+function isWithin(directory: string, file: string): boolean {
+	const relative = path.relative(directory, path.dirname(file));
+	return relative === '' || (!relative.startsWith('..') && !path.isAbsolute(relative));
+}
+
 async function existsSafe(p: string): Promise<boolean> {
 	try {
 		await fs.access(p);
@@ -98,6 +486,55 @@ async function existsSafe(p: string): Promise<boolean> {
 	}
 }
 
+/** Entries in `file`, skipping lines that aren't JSON, or null if there is no such file. */
+async function readEntries(file: string): Promise<{ entries: Entry[]; badLines: number } | null> {
+	let data: string;
+	try {
+		data = await fs.readFile(file, 'utf8');
+	} catch (e: unknown) {
+		if (isNodeError(e) && e.code === 'ENOENT') return null;
+		throw e;
+	}
+	const entries: Entry[] = [];
+	let badLines = 0;
+	for (const line of data.split(/\r?\n/)) {
+		if (!line) continue;
+		try {
+			const entry = JSON.parse(line);
+			if (typeof entry?.from === 'string' && typeof entry?.to === 'string') entries.push(entry);
+			else badLines++;
+		} catch {
+			badLines++;
+		}
+	}
+	return { entries, badLines };
+}
+
+async function readBackupInfo(): Promise<HistoryBackup | null> {
+	try {
+		const st = await fs.stat(backupPath());
+		const backup = await readEntries(backupPath());
+		return backup ? { savedAt: st.mtimeMs, entries: backup.entries.length } : null;
+	} catch {
+		return null;
+	}
+}
+
+async function freeBytes(dir: string): Promise<number> {
+	try {
+		const st = await fs.statfs(dir);
+		return st.bavail * st.bsize;
+	} catch {
+		return Number.POSITIVE_INFINITY;
+	}
+}
+
+/** Why writing (or reading) the journal failed, for {@link HistoryHealth.reason}. */
+function writeFailure(err: unknown): 'disk-full' | 'io-error' {
+	const full = isNodeError(err) && (err.code === 'ENOSPC' || err.code === 'EDQUOT');
+	return full ? 'disk-full' : 'io-error';
+}
+
 function isNodeError(err: unknown): err is NodeJS.ErrnoException {
 	return typeof err === 'object' && err !== null && 'code' in err;
 }
@@ -3,6 +3,7 @@ import fsp from 'node:fs/promises';
 import path from 'node:path';
 import type { ILogger } from '../../types/index';
 import { logsDir } from '../../utils/paths.js';
+import { redactPaths, redactValue } from './redact.js';
 
 type Level = 'info' | 'warn' | 'error' | 'debug';
 
@@ -11,6 +12,8 @@ export class Logger implements ILogger {
 	private ring: string[] = [];
 	private max = 500;
 	private logFile: string | null = null;
+	/** Replace file paths with hashed stand-ins; see {@link redactPaths}. */
+	private privacy = true;
 
 	constructor() {
 		this.init().catch(() => {
@@ -21,6 +24,8 @@ export class Logger implements ILogger {
 	private async init() {
 		const dir = logsDir('namefix');
 		await fsp.mkdir(dir, { recursive: true });
+		// The data dir moved meanwhile; reopen() opens the new one
+		if (dir !== logsDir('namefix')) return;
 		this.logFile = path.join(dir, 'session.log');
 		this.stream = fs.createWriteStream(this.logFile, { flags: 'a', encoding: 'utf8' });
 	}
@@ -30,7 +35,23 @@ export class Logger implements ILogger {
 		if (this.ring.length > this.max) this.ring.shift();
 	}
 
-	private write(level: Level, msg: string, meta?: Record<string, unknown>) {
+	/** Continue in `session.log` under the current logs directory, e.g. after the data dir moved. */
+	async reopen(): Promise<void> {
+		const previous = this.stream;
+		this.stream = null;
+		await new Promise<void>((resolve) => (previous ? previous.end(resolve) : resolve()));
+		await this.init();
+	}
+
+	/** Log full paths (`false`) or hashed stand-ins for them (`true`, the default). */
+	setPrivacy(enabled: boolean): void {
+		this.privacy = enabled;
+	}
+
+	private write(level: Level, text: string, details?: Record<string, unknown>) {
+		const msg = this.privacy ? redactPaths(text) : text;
+		const meta =
+			this.privacy && details ? (redactValue(details) as Record<string, unknown>) : details;
 		const ts = new Date().toISOString();
 		const rec = { ts, level, msg, ...(meta ? { meta } : {}) };
 		const line = JSON.stringify(rec);
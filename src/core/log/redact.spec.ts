@@ -0,0 +1,38 @@
+import { describe, expect, it } from 'vitest';
+
+import { redactPath, redactPaths, redactValue } from './redact.js';
+
+describe('redactPaths', () => {
+	it('replaces paths with a stable hash that keeps the extension', () => {
+		const stand = redactPath('/Users/me/Desktop/Tax Return 2024.pdf');
+		expect(stand).toMatch(/^…\/#[0-9a-f]{8}\.pdf$/);
+		expect(redactPath('/Users/me/Desktop/Tax Return 2024.pdf')).toBe(stand);
+		expect(redactPath('/Users/me/Desktop/other.pdf')).not.toBe(stand);
+	});
+
+	it('finds paths with spaces in log messages', () => {
+		const from = '/Users/me/Desktop/Screen Shot 1.png';
+		const to = '/Users/me/Desktop/Screenshot_1.png';
+		expect(redactPaths(`renamed ${from} → ${to}`)).toBe(
+			`renamed ${redactPath(from)} → ${redactPath(to)}`,
+		);
+		expect(redactPaths(`ENOENT: no such file, rename '${from}' -> '${to}'`)).toBe(
+			`ENOENT: no such file, rename '${redactPath(from)}' -> '${redactPath(to)}'`,
+		);
+		expect(redactPaths('C:\\Users\\me\\a.jpg')).toBe(redactPath('C:\\Users\\me\\a.jpg'));
+	});
+
+	it('leaves text without paths alone', () => {
+		expect(redactPaths('Watcher stopped and/or restarted 1/2 times')).toBe(
+			'Watcher stopped and/or restarted 1/2 times',
+		);
+	});
+
+	it('redacts nested metadata', () => {
+		expect(redactValue({ dir: '/tmp/x', attempts: 3, files: ['~/a.txt'] })).toEqual({
+			dir: redactPath('/tmp/x'),
+			attempts: 3,
+			files: [redactPath('~/a.txt')],
+		});
+	});
+});
@@ -0,0 +1,42 @@
+import { createHash } from 'node:crypto';
+import path from 'node:path';
+
+/**
+ * An absolute or home-relative path at the start of the text or after whitespace, a quote, `(`
+ * or `=`. It runs to the next quote or line end, or to a separator log lines put after a path
+ * (` → `, ` -> `, `, `, `: `), so names with spaces are caught whole.
+ */
+const PATH_IN_TEXT = /(^|[\s'"`(=])((?:~?\/|[A-Za-z]:\\)[^\s'"`](?:(?! → | -> |, |: )[^'"`\n])*)/g;
+
+/**
+ * A stand-in for `file` that keeps its extension, e.g. `…/#3f2a9c1e.jpg`. The same path always
+ * gives the same stand-in, here and in the menu bar's logs, so lines about one file still line
+ * up.
+ */
+export function redactPath(file: string): string {
+	const hash = createHash('sha256').update(file).digest('hex').slice(0, 8);
+	const ext = path.extname(file);
+	return `…/#${hash}${/^\.[A-Za-z0-9]{1,8}$/.test(ext) ? ext : ''}`;
+}
+
+/** `text` with every path in it replaced by {@link redactPath}. */
+export function redactPaths(text: string): string {
+	return text.replace(PATH_IN_TEXT, (_match, lead: string, file: string) => {
+		const trimmed = file.trimEnd();
+		return `${lead}${redactPath(trimmed)}${file.slice(trimmed.length)}`;
+	});
+}
+
+/** {@link redactPaths} applied to every string in log metadata, however deeply nested. */
+export function redactValue(value: unknown): unknown {
+	if (typeof value === 'string') return redactPaths(value);
+	if (Array.isArray(value)) return value.map(redactValue);
+	if (
+		value !== null &&
+		typeof value === 'object' &&
+		Object.getPrototypeOf(value) === Object.prototype
+	) {
+		return Object.fromEntries(Object.entries(value).map(([key, inner]) => [key, redactValue(inner)]));
+	}
+	return value;
+}
@@ -0,0 +1,202 @@
+import dgram from 'node:dgram';
+import os from 'node:os';
+import type { ILogger } from '../../types/index';
+import {
+	MDNS_ADDRESS,
+	MDNS_PORT,
+	type MdnsMessage,
+	type MdnsRecord,
+	RecordType,
+	decode,
+	encodeQuery,
+	encodeResponse,
+} from './mdns.js';
+
+/** The DNS-SD service type Namefix installs advertise themselves under. */
+export const SERVICE_TYPE = '_namefix._tcp.local';
+
+/** How long, in seconds, other machines may cache the advertisement. */
+const TTL = 120;
+const BROWSE_MS = 1500;
+
+/** This machine, as advertised: its pairing endpoint listens on `port`. */
+export type Advertisement = { id: string; name: string; port: number };
+
+/** Another Namefix install found on the local network. */
+export type Found = { id: string; name: string; address: string; port: number };
+
+export interface IDiscovery {
+	/** Answer other machines' queries with `ad`, or stop answering when null. */
+	advertise(ad: Advertisement | null): Promise<void>;
+	/** Ask the network for Namefix installs and collect the answers that arrive in `timeoutMs`. */
+	browse(timeoutMs?: number): Promise<Found[]>;
+	close(): void;
+}
+
+type Listener = (message: MdnsMessage, from: dgram.RemoteInfo) => void;
+
+/** {@link IDiscovery} over multicast DNS, sharing port 5353 with the system's own responder. */
+export class MdnsDiscovery implements IDiscovery {
+	private socket: Promise<dgram.Socket> | null = null;
+	private ad: Advertisement | null = null;
+	private listeners = new Set<Listener>();
+
+	constructor(private readonly logger: ILogger) {}
+
+	async advertise(ad: Advertisement | null): Promise<void> {
+		const previous = this.ad;
+		this.ad = ad;
+		if (ad) {
+			const socket = await this.open();
+			this.send(socket, encodeResponse(records(ad, TTL)));
+			return;
+		}
+		if (previous && this.socket) {
+			// Goodbye: a zero TTL tells other machines to forget this one now
+			this.send(await this.socket, encodeResponse(records(previous, 0)));
+		}
+		if (this.listeners.size === 0) this.close();
+	}
+
+	async browse(timeoutMs = BROWSE_MS): Promise<Found[]> {
+		const socket = await this.open();
+		const found: MdnsRecord[] = [];
+		const senders = new Map<string, string>();
+		const listener: Listener = (message, from) => {
+			if (!message.response) return;
+			for (const record of message.records) {
+				found.push(record);
+				if (record.type === 'SRV') senders.set(record.name, from.address);
+			}
+		};
+		this.listeners.add(listener);
+		try {
+			this.send(socket, encodeQuery(SERVICE_TYPE, RecordType.PTR));
+			await new Promise((resolve) => setTimeout(resolve, timeoutMs));
+		} finally {
+			this.listeners.delete(listener);
+			if (!this.ad && this.listeners.size === 0) this.close();
+		}
+		return assemble(found, senders);
+	}
+
+	close(): void {
+		const socket = this.socket;
+		this.socket = null;
+		socket?.then((s) => s.close()).catch(() => {});
+	}
+
+	private open(): Promise<dgram.Socket> {
+		if (this.socket) return this.socket;
+		this.socket = new Promise<dgram.Socket>((resolve, reject) => {
+			const socket = dgram.createSocket({ type: 'udp4', reuseAddr: true });
+			socket.once('error', reject);
+			socket.bind(MDNS_PORT, () => {
+				socket.off('error', reject);
+				socket.on('error', (err) => {
+					this.logger.warn('Local network discovery failed', { error: err.message });
+				});
+				socket.on('message', (packet, from) => this.onMessage(socket, packet, from));
+				try {
+					socket.addMembership(MDNS_ADDRESS);
+					socket.setMulticastTTL(255);
+					// Lets two installs on one machine find each other
+					socket.setMulticastLoopback(true);
+				} catch (e: unknown) {
+					socket.close();
+					reject(e);
+					return;
+				}
+				socket.unref();
+				resolve(socket);
+			});
+		});
+		this.socket.catch(() => {
+			this.socket = null;
+		});
+		return this.socket;
+	}
+
+	private onMessage(socket: dgram.Socket, packet: Buffer, from: dgram.RemoteInfo) {
+		let message: MdnsMessage;
+		try {
+			message = decode(packet);
+		} catch {
+			return;
+		}
+		for (const listener of this.listeners) listener(message, from);
+		const ad = this.ad;
+		if (message.response || !ad) return;
+		const instance = instanceName(ad).toLowerCase();
+		const asked = message.questions.some((q) => {
+			const name = q.name.toLowerCase();
+			const browsing = q.type === RecordType.PTR || q.type === RecordType.ANY;
+			return (name === SERVICE_TYPE && browsing) || name === instance;
+		});
+		if (!asked) return;
+		// Queries from any port but 5353 come from simple resolvers that expect a unicast reply
+		if (from.port !== MDNS_PORT) {
+			socket.send(encodeResponse(records(ad, TTL), message.id), from.port, from.address);
+		} else {
+			this.send(socket, encodeResponse(records(ad, TTL)));
+		}
+	}
+
+	private send(socket: dgram.Socket, packet: Buffer) {
+		socket.send(packet, MDNS_PORT, MDNS_ADDRESS, (err) => {
+			if (err) this.logger.warn('Could not reach the local network', { error: err.message });
+		});
+	}
+}
+
+function instanceName(ad: Advertisement): string {
+	const label = ad.name.replace(/[.\s]+/g, '-').slice(0, 40);
+	return `${label}-${ad.id.slice(0, 4)}.${SERVICE_TYPE}`;
+}
+
+/** A host name of its own, so the system's records for the machine's name are left alone. */
+function hostName(ad: Advertisement): string {
+	return `namefix-${ad.id.slice(0, 8)}.local`;
+}
+
+function records(ad: Advertisement, ttl: number): MdnsRecord[] {
+	const instance = instanceName(ad);
+	const host = hostName(ad);
+	const addresses = Object.values(os.networkInterfaces())
+		.flat()
+		.filter((iface) => iface && iface.family === 'IPv4' && !iface.internal)
+		.map((iface) => iface?.address as string);
+	return [
+		{ type: 'PTR', name: SERVICE_TYPE, ttl, target: instance },
+		{ type: 'SRV', name: instance, ttl, port: ad.port, target: host },
+		{ type: 'TXT', name: instance, ttl, data: { id: ad.id, name: ad.name, v: '1' } },
+		...addresses.map((address): MdnsRecord => ({ type: 'A', name: host, ttl, address })),
+	];
+}
+
+/**
+ * Installs described by the collected records. The address a reply came from is preferred over
+ * its A records, which may name interfaces this machine can't reach.
+ */
+function assemble(found: MdnsRecord[], senders: Map<string, string>): Found[] {
+	const peers = new Map<string, Found>();
+	const instances = found.filter(
+		(r): r is Extract<MdnsRecord, { type: 'PTR' }> => r.type === 'PTR' && r.name === SERVICE_TYPE,
+	);
+	for (const { target: instance, ttl } of instances) {
+		if (ttl === 0) continue;
+		const srv = found.find((r) => r.type === 'SRV' && r.name === instance);
+		const txt = found.find((r) => r.type === 'TXT' && r.name === instance);
+		if (srv?.type !== 'SRV' || txt?.type !== 'TXT' || !txt.data.id) continue;
+		const a = found.find((r) => r.type === 'A' && r.name === srv.target);
+		const address = senders.get(instance) ?? (a?.type === 'A' ? a.address : undefined);
+		if (!address) continue;
+		peers.set(txt.data.id, {
+			id: txt.data.id,
+			name: txt.data.name ?? instance,
+			address,
+			port: srv.port,
+		});
+	}
+	return [...peers.values()];
+}
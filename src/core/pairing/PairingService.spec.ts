@@ -0,0 +1,191 @@
+import fs from 'node:fs/promises';
+import net from 'node:net';
+import os from 'node:os';
+import path from 'node:path';
+import readline from 'node:readline';
+import { afterEach, beforeEach, describe, expect, it } from 'vitest';
+import { encodePairingKey, generateKey } from '../config/SyncKey.js';
+import type { Advertisement, Found, IDiscovery } from './Discovery.js';
+import { decode, encodeQuery, encodeResponse, type MdnsRecord, RecordType } from './mdns.js';
+import { PairingService } from './PairingService.js';
+
+const noopLogger = { info: () => {}, warn: () => {}, error: () => {} };
+
+/** Stands in for multicast DNS: every machine's advertisement is visible on localhost. */
+class LoopbackDiscovery implements IDiscovery {
+	ad: Advertisement | null = null;
+
+	constructor(private readonly network: Set<LoopbackDiscovery>) {
+		network.add(this);
+	}
+
+	async advertise(ad: Advertisement | null): Promise<void> {
+		this.ad = ad;
+	}
+
+	async browse(): Promise<Found[]> {
+		return [...this.network]
+			.map((d) => d.ad)
+			.filter((ad): ad is Advertisement => ad !== null)
+			.map((ad) => ({ ...ad, address: '127.0.0.1' }));
+	}
+
+	close(): void {}
+}
+
+describe('PairingService', () => {
+	let tempRoot: string;
+	let network: Set<LoopbackDiscovery>;
+	const services: PairingService[] = [];
+
+	/** A machine with its own sync key and paired-peers file. */
+	const machine = (name: string) => {
+		const keys = { code: encodePairingKey(generateKey()) };
+		const service = new PairingService(
+			{
+				export: async () => keys.code,
+				import: async (code) => {
+					keys.code = code;
+				},
+			},
+			() => ({
+				running: true,
+				directories: [`/Users/${name}/Desktop`],
+				dryRun: false,
+				observerMode: false,
+			}),
+			noopLogger,
+			() => {},
+			new LoopbackDiscovery(network),
+			path.join(tempRoot, name, 'paired-peers.json'),
+		);
+		services.push(service);
+		return { keys, service };
+	};
+
+	/** Turn both on and find `b` from `a`. */
+	const nearby = async (a: PairingService, b: PairingService) => {
+		await a.setEnabled(true);
+		await b.setEnabled(true);
+		const peers = await a.findPeers();
+		expect(peers).toHaveLength(1);
+		return peers[0];
+	};
+
+	beforeEach(async () => {
+		tempRoot = await fs.mkdtemp(path.join(os.tmpdir(), 'namefix-pairing-'));
+		network = new Set();
+	});
+
+	afterEach(async () => {
+		await Promise.all(services.splice(0).map((s) => s.dispose()));
+		await fs.rm(tempRoot, { recursive: true, force: true });
+	});
+
+	it('hands the sync key to a machine that enters the code', async () => {
+		const laptop = machine('laptop');
+		const desktop = machine('desktop');
+		const peer = await nearby(laptop.service, desktop.service);
+		const { code } = desktop.service.startPairing();
+
+		const paired = await laptop.service.pair(peer.id, code);
+
+		expect(laptop.keys.code).toBe(desktop.keys.code);
+		expect(paired.id).toBe(peer.id);
+		expect(laptop.service.getStatus().peers.map((p) => p.id)).toEqual([peer.id]);
+		expect(desktop.service.getStatus().peers).toHaveLength(1);
+		expect(desktop.service.getStatus().code).toBeUndefined();
+		expect((await laptop.service.findPeers())[0].paired).toBe(true);
+	});
+
+	it('rejects a wrong code and withdraws the code after repeated guesses', async () => {
+		const laptop = machine('laptop');
+		const desktop = machine('desktop');
+		const before = laptop.keys.code;
+		const peer = await nearby(laptop.service, desktop.service);
+		const { code } = desktop.service.startPairing();
+		const wrong = code === '000000' ? '111111' : '000000';
+
+		await expect(laptop.service.pair(peer.id, wrong)).rejects.toThrow('Wrong pairing code');
+		await expect(laptop.service.pair(peer.id, wrong)).rejects.toThrow('Wrong pairing code');
+		await expect(laptop.service.pair(peer.id, wrong)).rejects.toThrow('Wrong pairing code');
+		await expect(laptop.service.pair(peer.id, code)).rejects.toThrow('not showing a pairing code');
+
+		expect(laptop.keys.code).toBe(before);
+		expect(desktop.service.getStatus().peers).toEqual([]);
+	});
+
+	it('refuses shares that would make the key predictable', async () => {
+		const laptop = machine('laptop');
+		const desktop = machine('desktop');
+		const peer = await nearby(laptop.service, desktop.service);
+		desktop.service.startPairing();
+
+		/** Answer the hello by hand, the way another machine would. */
+		const exchange = async (share: string) => {
+			const socket = net.connect({ host: peer.address, port: peer.port });
+			const lines = readline.createInterface({ input: socket })[Symbol.asyncIterator]();
+			const hello = JSON.parse((await lines.next()).value);
+			const request = { type: 'pair', device: 'x', name: 'x', share, proof: '00' };
+			socket.write(`${JSON.stringify(request)}\n`);
+			const reply = JSON.parse((await lines.next()).value);
+			socket.destroy();
+			return { hello, reply };
+		};
+
+		const { hello, reply } = await exchange('0'.repeat(64));
+		expect(hello.share).toMatch(/^[0-9a-f]{64}$/);
+		expect(reply).toEqual({ type: 'error', message: 'Wrong pairing code' });
+		// A fresh share for every connection, so a recorded answer is no use later
+		expect((await exchange(hello.share)).hello.share).not.toBe(hello.share);
+		expect(desktop.service.getStatus().peers).toEqual([]);
+	});
+
+	it('shares status only with paired machines', async () => {
+		const laptop = machine('laptop');
+		const desktop = machine('desktop');
+		const peer = await nearby(laptop.service, desktop.service);
+
+		await expect(laptop.service.peerStatus(peer.id)).rejects.toThrow('Pair with that Mac first');
+
+		await laptop.service.pair(peer.id, desktop.service.startPairing().code);
+		const status = await laptop.service.peerStatus(peer.id);
+		expect(status.directories).toEqual(['/Users/desktop/Desktop']);
+		expect(status.running).toBe(true);
+
+		await desktop.service.unpair(desktop.service.getStatus().peers[0].id);
+		await expect(laptop.service.peerStatus(peer.id)).rejects.toThrow('Not paired with this Mac');
+	});
+
+	it('requires pairing to be on before showing a code', () => {
+		const { service } = machine('laptop');
+		expect(() => service.startPairing()).toThrow('Turn on local network pairing first');
+	});
+});
+
+describe('mdns', () => {
+	it('decodes the records it encodes', () => {
+		const instance = 'desk._namefix._tcp.local';
+		const records: MdnsRecord[] = [
+			{ type: 'PTR', name: '_namefix._tcp.local', ttl: 120, target: instance },
+			{ type: 'SRV', name: instance, ttl: 120, port: 4242, target: 'namefix-ab.local' },
+			{ type: 'TXT', name: instance, ttl: 120, data: { id: 'ab', name: 'Desk' } },
+			{ type: 'A', name: 'namefix-ab.local', ttl: 120, address: '192.168.1.20' },
+		];
+
+		const message = decode(encodeResponse(records, 7));
+
+		expect(message).toEqual({ id: 7, response: true, questions: [], records });
+	});
+
+	it('decodes queries and rejects pointers that loop', () => {
+		const query = decode(encodeQuery('_namefix._tcp.local', RecordType.PTR));
+		expect(query.response).toBe(false);
+		expect(query.questions).toEqual([{ name: '_namefix._tcp.local', type: RecordType.PTR }]);
+
+		// One question whose name points at itself
+		const header = encodeQuery('a', RecordType.PTR).subarray(0, 12);
+		const looping = Buffer.concat([header, Buffer.from([0xc0, 12, 0, 12, 0, 1])]);
+		expect(() => decode(looping)).toThrow(RangeError);
+	});
+});
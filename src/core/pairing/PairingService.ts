@@ -0,0 +1,500 @@
+import {
+	type KeyObject,
+	createHash,
+	createHmac,
+	createPublicKey,
+	diffieHellman,
+	generateKeyPairSync,
+	hkdfSync,
+	randomBytes,
+	randomInt,
+	randomUUID,
+	timingSafeEqual,
+} from 'node:crypto';
+import fs from 'node:fs/promises';
+import net from 'node:net';
+import os from 'node:os';
+import path from 'node:path';
+import type {
+	ILogger,
+	NearbyPeer,
+	PairedPeer,
+	PairingCode,
+	PairingStatus,
+	PeerStatus,
+} from '../../types/index';
+import { configDir } from '../../utils/paths.js';
+import { restoreOwnership } from '../../utils/user.js';
+import { type Sealed, decodePairingKey, seal, unseal } from '../config/SyncKey.js';
+import { type Found, type IDiscovery, MdnsDiscovery } from './Discovery.js';
+
+/** How long a pairing code shown on this machine can be used. */
+const CODE_TTL_MS = 2 * 60_000;
+/** Wrong codes tried before the code is withdrawn, so it can't be guessed. */
+const MAX_ATTEMPTS = 3;
+/** How long a connection to another machine, or from one, may take. */
+const CONNECT_TIMEOUT_MS = 5_000;
+const PROTOCOL_VERSION = 2;
+/** Authenticated with the sealed sync key, so it can't be passed off as anything else. */
+const SEAL_HEADER = 'namefix-pair-key';
+
+/** The settings sync key, as {@link SettingsSync} hands it out and takes it in. */
+export type PairingKeys = {
+	export(): Promise<string>;
+	import(code: string): Promise<void>;
+};
+
+type Saved = { device: string; peers: PairedPeer[] };
+
+type Message = Record<string, unknown> & { type: string };
+
+/** One side's half of {@link startExchange}: its secret and the share it sends. */
+type Exchange = { privateKey: KeyObject; share: string };
+
+/**
+ * Pairs Namefix installs on the same network. One machine shows a short code; the other finds it
+ * over multicast DNS, and the two agree on a key with CPace, which only works out when both used
+ * the same code. The joining machine then receives the settings sync key sealed with that key.
+ * Paired machines hold the same sync key, which is also what lets them ask each other for status.
+ */
+export class PairingService {
+	private saved: Saved | null = null;
+	private server: net.Server | null = null;
+	private code: (PairingCode & { attempts: number }) | null = null;
+	private nearby = new Map<string, NearbyPeer>();
+	private chain: Promise<void> = Promise.resolve();
+
+	constructor(
+		private readonly keys: PairingKeys,
+		private readonly status: () => Omit<PeerStatus, 'id' | 'name'>,
+		private readonly logger: ILogger,
+		private readonly onChange: () => void = () => {},
+		private readonly discovery: IDiscovery = new MdnsDiscovery(logger),
+		private readonly peersFile = path.join(configDir('namefix'), 'paired-peers.json'),
+	) {}
+
+	getStatus(): PairingStatus {
+		const code = this.code && this.code.expiresAt > Date.now() ? this.code : null;
+		return {
+			enabled: this.server !== null,
+			...(code ? { code: { code: code.code, expiresAt: code.expiresAt } } : {}),
+			peers: [...(this.saved?.peers ?? [])],
+		};
+	}
+
+	/** Listen for other machines and advertise this one, or stop. */
+	setEnabled(enabled: boolean): Promise<void> {
+		const next = this.chain.then(() => this.toggle(enabled));
+		this.chain = next.catch(() => {});
+		return next;
+	}
+
+	private async toggle(enabled: boolean) {
+		if (enabled === (this.server !== null)) return;
+		const current = this.server;
+		if (current) {
+			this.server = null;
+			this.code = null;
+			await this.discovery.advertise(null).catch(() => {});
+			await new Promise<void>((resolve) => current.close(() => resolve()));
+			this.onChange();
+			return;
+		}
+		const { device } = await this.load();
+		const server = net.createServer((socket) => {
+			this.serve(socket).catch((e: unknown) => {
+				this.logger.warn('Pairing request failed', {
+					error: e instanceof Error ? e.message : String(e),
+				});
+			});
+		});
+		await new Promise<void>((resolve, reject) => {
+			server.once('error', reject);
+			server.listen(0, () => {
+				server.off('error', reject);
+				resolve();
+			});
+		});
+		server.unref();
+		this.server = server;
+		const { port } = server.address() as net.AddressInfo;
+		try {
+			await this.discovery.advertise({ id: device, name: os.hostname(), port });
+		} catch (e: unknown) {
+			// Still reachable by address; only discovery is lost
+			this.logger.warn('Could not advertise on the local network', {
+				error: e instanceof Error ? e.message : String(e),
+			});
+		}
+		this.onChange();
+	}
+
+	/** Show a new code for another machine to pair with, replacing any earlier one. */
+	startPairing(): PairingCode {
+		if (!this.server) throw new Error('Turn on local network pairing first');
+		const code = String(randomInt(0, 1_000_000)).padStart(6, '0');
+		this.code = { code, expiresAt: Date.now() + CODE_TTL_MS, attempts: 0 };
+		this.onChange();
+		return { code, expiresAt: this.code.expiresAt };
+	}
+
+	/** Namefix installs on the local network, other than this one. */
+	async findPeers(): Promise<NearbyPeer[]> {
+		const { device, peers } = await this.load();
+		const found = await this.discovery.browse();
+		this.nearby.clear();
+		for (const peer of found) {
+			if (peer.id === device) continue;
+			const paired = peers.some((p) => p.id === peer.id);
+			this.nearby.set(peer.id, { ...peer, paired });
+		}
+		return [...this.nearby.values()];
+	}
+
+	/** Pair with a machine from the last {@link findPeers} using the code it shows. */
+	async pair(peerId: string, code: string): Promise<PairedPeer> {
+		const peer = this.nearby.get(peerId);
+		if (!peer) throw new Error('That Mac is no longer nearby; look for it again');
+		return await this.pairWith(peer, code);
+	}
+
+	/** Pair with the machine listening at `peer.address` and `peer.port`. */
+	async pairWith(peer: Pick<Found, 'address' | 'port'>, code: string): Promise<PairedPeer> {
+		const { device } = await this.load();
+		const trimmed = code.replace(/\s+/g, '');
+		const session: { key: Buffer | null } = { key: null };
+		const reply = await this.request(peer, (hello) => {
+			if (typeof hello.share !== 'string') {
+				throw new Error('This Mac is not showing a pairing code');
+			}
+			const challenge = String(hello.challenge);
+			const mine = startExchange(trimmed, challenge);
+			const key = finishExchange(mine, hello.share, transcript(challenge, hello.share, mine.share));
+			if (!key) throw new Error('The other Mac sent an invalid pairing request');
+			session.key = key;
+			return {
+				type: 'pair',
+				device,
+				name: os.hostname(),
+				share: mine.share,
+				proof: mac(key, 'client'),
+			};
+		});
+		if (reply.message.type !== 'paired') throw new Error(errorOf(reply.message));
+		const { key } = session;
+		if (!key || !sameMac(key, 'server', reply.message.proof)) {
+			throw new Error('The other Mac did not prove it knows the code');
+		}
+		await this.keys.import(unseal(key, reply.message.sealed as Sealed, SEAL_HEADER));
+		const paired: PairedPeer = {
+			id: String(reply.hello.device),
+			name: String(reply.hello.name),
+			pairedAt: Date.now(),
+		};
+		await this.remember(paired);
+		this.logger.info('Paired with another Mac', { name: paired.name });
+		return paired;
+	}
+
+	/** Ask a paired machine from the last {@link findPeers} for its status. */
+	async peerStatus(peerId: string): Promise<PeerStatus> {
+		const { device, peers } = await this.load();
+		const paired = peers.find((p) => p.id === peerId);
+		if (!paired) throw new Error('Pair with that Mac first');
+		if (!this.nearby.has(peerId)) await this.findPeers();
+		const peer = this.nearby.get(peerId);
+		if (!peer) throw new Error(`${paired.name} isn't on this network right now`);
+		const syncKey = decodePairingKey(await this.keys.export());
+		const reply = await this.request(peer, (hello) => ({
+			type: 'status',
+			device,
+			proof: mac(syncKey, String(hello.challenge)),
+		}));
+		if (reply.message.type !== 'status') throw new Error(errorOf(reply.message));
+		const status = reply.message.status as Omit<PeerStatus, 'id' | 'name'>;
+		return { ...status, id: paired.id, name: String(reply.hello.name) };
+	}
+
+	/** Forget a paired machine; it can no longer ask this one for status. */
+	async unpair(peerId: string): Promise<void> {
+		const saved = await this.load();
+		saved.peers = saved.peers.filter((p) => p.id !== peerId);
+		await this.save();
+		this.onChange();
+	}
+
+	async dispose(): Promise<void> {
+		await this.setEnabled(false);
+		this.discovery.close();
+	}
+
+	/** Answer one request from another machine. */
+	private async serve(socket: net.Socket) {
+		socket.setTimeout(CONNECT_TIMEOUT_MS, () => socket.destroy());
+		const lines = lineReader(socket);
+		const { device, peers } = await this.load();
+		const challenge = randomBytes(16).toString('hex');
+		// Each connection gets its own share, so one answer can't be replayed on another
+		const code = this.code && this.code.expiresAt > Date.now() ? this.code : null;
+		const exchange = code ? { code, ...startExchange(code.code, challenge) } : null;
+		send(socket, {
+			type: 'hello',
+			version: PROTOCOL_VERSION,
+			device,
+			name: os.hostname(),
+			challenge,
+			...(exchange ? { share: exchange.share } : {}),
+		});
+		const request = await lines.next();
+		let reply: Message;
+		if (request?.type === 'pair') {
+			reply = await this.answerPairing(request, challenge, exchange);
+		} else if (request?.type === 'status') {
+			const syncKey = decodePairingKey(await this.keys.export());
+			const known = peers.some((p) => p.id === request.device);
+			reply =
+				known && sameMac(syncKey, challenge, request.proof)
+					? { type: 'status', status: this.status() }
+					: { type: 'error', message: 'Not paired with this Mac' };
+		} else {
+			reply = { type: 'error', message: 'Unknown request' };
+		}
+		socket.end(`${JSON.stringify(reply)}\n`);
+	}
+
+	private async answerPairing(
+		request: Message,
+		challenge: string,
+		exchange: (Exchange & { code: PairingCode }) | null,
+	): Promise<Message> {
+		const code = this.code;
+		if (!exchange || !code || code !== exchange.code || code.expiresAt <= Date.now()) {
+			return { type: 'error', message: 'This Mac is not showing a pairing code' };
+		}
+		const key = finishExchange(
+			exchange,
+			request.share,
+			transcript(challenge, exchange.share, request.share),
+		);
+		if (!key || !sameMac(key, 'client', request.proof)) {
+			code.attempts++;
+			if (code.attempts >= MAX_ATTEMPTS) {
+				this.code = null;
+				this.logger.warn('Withdrew the pairing code after repeated wrong guesses');
+				this.onChange();
+			}
+			return { type: 'error', message: 'Wrong pairing code' };
+		}
+		this.code = null;
+		const sealed = seal(key, await this.keys.export(), SEAL_HEADER);
+		await this.remember({
+			id: String(request.device),
+			name: String(request.name),
+			pairedAt: Date.now(),
+		});
+		this.logger.info('Paired with another Mac', { name: String(request.name) });
+		return { type: 'paired', proof: mac(key, 'server'), sealed };
+	}
+
+	/** Connect to `peer`, read its hello, send what `build` makes of it and read the reply. */
+	private async request(
+		peer: Pick<Found, 'address' | 'port'>,
+		build: (hello: Message) => Message | Promise<Message>,
+	): Promise<{ hello: Message; message: Message }> {
+		const socket = net.connect({ host: peer.address, port: peer.port });
+		socket.setTimeout(CONNECT_TIMEOUT_MS, () =>
+			socket.destroy(new Error('The other Mac did not answer in time')),
+		);
+		try {
+			const lines = lineReader(socket);
+			const hello = await lines.next();
+			if (hello?.type !== 'hello' || hello.version !== PROTOCOL_VERSION) {
+				throw new Error('The other Mac runs an incompatible version of Namefix');
+			}
+			send(socket, await build(hello));
+			const message = await lines.next();
+			if (!message) throw new Error('The other Mac closed the connection');
+			return { hello, message };
+		} finally {
+			socket.destroy();
+		}
+	}
+
+	private async remember(peer: PairedPeer) {
+		const saved = await this.load();
+		saved.peers = [...saved.peers.filter((p) => p.id !== peer.id), peer];
+		await this.save();
+		this.onChange();
+	}
+
+	private async load(): Promise<Saved> {
+		if (this.saved) return this.saved;
+		let raw: Partial<Saved> = {};
+		try {
+			raw = JSON.parse(await fs.readFile(this.peersFile, 'utf8'));
+		} catch {
+			// First run: a new device id and no peers
+		}
+		this.saved = {
+			device: typeof raw.device === 'string' ? raw.device : randomUUID(),
+			peers: Array.isArray(raw.peers) ? raw.peers.filter(isPairedPeer) : [],
+		};
+		if (raw.device !== this.saved.device) await this.save();
+		return this.saved;
+	}
+
+	private async save() {
+		try {
+			await fs.mkdir(path.dirname(this.peersFile), { recursive: true });
+			await fs.writeFile(this.peersFile, JSON.stringify(this.saved, null, 2), 'utf8');
+			restoreOwnership(this.peersFile);
+		} catch (e: unknown) {
+			this.logger.warn('Could not save paired Macs', {
+				error: e instanceof Error ? e.message : String(e),
+			});
+		}
+	}
+}
+
+/** The field prime and Montgomery coefficient of Curve25519. */
+const P = 2n ** 255n - 19n;
+const A = 486662n;
+
+/**
+ * Start a CPace exchange (draft-irtf-cfrg-cpace) over X25519: a random secret times a generator
+ * that only the code and the challenge decide. Someone who records both shares can't check
+ * guesses at the code against them; each guess needs a live exchange, and a wrong one costs an
+ * attempt.
+ */
+function startExchange(code: string, challenge: string): Exchange {
+	const digest = createHash('sha512').update(`namefix-pair:${challenge}:${code}`).digest();
+	const generator = curvePoint(elligator2(littleEndian(digest) % P));
+	const { privateKey } = generateKeyPairSync('x25519');
+	return { privateKey, share: diffieHellman({ privateKey, publicKey: generator }).toString('hex') };
+}
+
+/** The key both sides share if they used the same code, or null for a share that isn't one. */
+function finishExchange(mine: Exchange, theirs: unknown, info: string): Buffer | null {
+	if (typeof theirs !== 'string' || !/^[0-9a-f]{64}$/.test(theirs)) return null;
+	let secret: Buffer;
+	try {
+		const publicKey = curvePoint(Buffer.from(theirs, 'hex'));
+		secret = diffieHellman({ privateKey: mine.privateKey, publicKey });
+	} catch {
+		// A low-order share gives an all-zero secret, which X25519 refuses
+		return null;
+	}
+	return Buffer.from(hkdfSync('sha256', secret, '', info, 32));
+}
+
+/** What both sides said, in order, so the key covers the whole exchange. */
+function transcript(challenge: string, serverShare: unknown, clientShare: unknown): string {
+	return `namefix-pair:${challenge}:${String(serverShare)}:${String(clientShare)}`;
+}
+
+/** Elligator 2 (RFC 9380): a field element to a Curve25519 u-coordinate, hiding its log. */
+function elligator2(r: bigint): bigint {
+	const w = modP(-A * power(1n + 2n * r * r, P - 2n));
+	const curve = modP(w * w * w + A * w * w + w);
+	return power(curve, (P - 1n) / 2n) === P - 1n ? modP(-w - A) : w;
+}
+
+function curvePoint(u: bigint | Buffer): KeyObject {
+	const bytes =
+		typeof u === 'bigint' ? Buffer.from(u.toString(16).padStart(64, '0'), 'hex').reverse() : u;
+	const jwk = { kty: 'OKP', crv: 'X25519', x: bytes.toString('base64url') };
+	return createPublicKey({ key: jwk, format: 'jwk' });
+}
+
+function littleEndian(bytes: Buffer): bigint {
+	return BigInt(`0x${Buffer.from(bytes).reverse().toString('hex')}`);
+}
+
+function modP(x: bigint): bigint {
+	return ((x % P) + P) % P;
+}
+
+function power(base: bigint, exponent: bigint): bigint {
+	let result = 1n;
+	let b = modP(base);
+	for (let e = exponent; e > 0n; e >>= 1n) {
+		if (e & 1n) result = (result * b) % P;
+		b = (b * b) % P;
+	}
+	return result;
+}
+
+function mac(key: Buffer, text: string): string {
+	return createHmac('sha256', key).update(text).digest('hex');
+}
+
+function sameMac(key: Buffer, text: string, proof: unknown): boolean {
+	if (typeof proof !== 'string') return false;
+	const expected = Buffer.from(mac(key, text), 'hex');
+	const given = Buffer.from(proof, 'hex');
+	return given.length === expected.length && timingSafeEqual(given, expected);
+}
+
+function errorOf(message: Message): string {
+	return typeof message.message === 'string' ? message.message : 'The other Mac refused';
+}
+
+function isPairedPeer(value: unknown): value is PairedPeer {
+	const peer = value as PairedPeer;
+	return typeof peer?.id === 'string' && typeof peer.name === 'string';
+}
+
+function send(socket: net.Socket, message: Message) {
+	socket.write(`${JSON.stringify(message)}\n`);
+}
+
+/** Reads JSON lines from `socket`; `next()` resolves null once it closes. */
+function lineReader(socket: net.Socket) {
+	let buffer = '';
+	const queue: (Message | null)[] = [];
+	let waiting: ((message: Message | null) => void) | null = null;
+	let failure: Error | null = null;
+	const push = (message: Message | null) => {
+		if (waiting) {
+			const resolve = waiting;
+			waiting = null;
+			resolve(message);
+		} else {
+			queue.push(message);
+		}
+	};
+	socket.setEncoding('utf8');
+	socket.on('data', (chunk: string) => {
+		buffer += chunk;
+		if (buffer.length > 64 * 1024) {
+			socket.destroy(new Error('Message too large'));
+			return;
+		}
+		let newline = buffer.indexOf('\n');
+		while (newline >= 0) {
+			const line = buffer.slice(0, newline);
+			buffer = buffer.slice(newline + 1);
+			try {
+				const parsed = JSON.parse(line);
+				if (parsed && typeof parsed.type === 'string') push(parsed);
+			} catch {
+				// ignore lines that aren't messages
+			}
+			newline = buffer.indexOf('\n');
+		}
+	});
+	socket.on('error', (err) => {
+		failure = err;
+		push(null);
+	});
+	socket.on('close', () => push(null));
+	return {
+		next(): Promise<Message | null> {
+			if (queue.length > 0) return Promise.resolve(queue.shift() ?? null);
+			if (failure) return Promise.reject(failure);
+			return new Promise((resolve, reject) => {
+				waiting = (message) => (message === null && failure ? reject(failure) : resolve(message));
+			});
+		},
+	};
+}
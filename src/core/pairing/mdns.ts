@@ -0,0 +1,217 @@
+/**
+ * Just enough of multicast DNS (RFC 6762) and DNS-SD (RFC 6763) to advertise and find other
+ * Namefix installs on the local network: PTR, SRV, TXT and A records, no probing or caching.
+ */
+
+export const MDNS_ADDRESS = '224.0.0.251';
+export const MDNS_PORT = 5353;
+
+export const RecordType = { A: 1, PTR: 12, TXT: 16, SRV: 33, ANY: 255 } as const;
+
+/** Set on a record's class: it replaces what the receiver has cached for the name. */
+const CACHE_FLUSH = 0x8000;
+const CLASS_IN = 1;
+const FLAG_RESPONSE = 0x8000;
+const FLAG_AUTHORITATIVE = 0x0400;
+
+export type MdnsRecord =
+	| { type: 'PTR'; name: string; ttl: number; target: string }
+	| { type: 'SRV'; name: string; ttl: number; port: number; target: string }
+	| { type: 'TXT'; name: string; ttl: number; data: Record<string, string> }
+	| { type: 'A'; name: string; ttl: number; address: string };
+
+export type MdnsQuestion = { name: string; type: number };
+
+export type MdnsMessage = {
+	id: number;
+	response: boolean;
+	questions: MdnsQuestion[];
+	/** Answers and additional records together; unsupported types are left out. */
+	records: MdnsRecord[];
+};
+
+export function encodeQuery(name: string, type: number, id = 0): Buffer {
+	return Buffer.concat([header(id, 0, 1, 0), encodeName(name), u16(type), u16(CLASS_IN)]);
+}
+
+export function encodeResponse(records: MdnsRecord[], id = 0): Buffer {
+	const flags = FLAG_RESPONSE | FLAG_AUTHORITATIVE;
+	return Buffer.concat([header(id, flags, 0, records.length), ...records.map(encodeRecord)]);
+}
+
+/** Parse a packet; throws a RangeError if it is cut short. */
+export function decode(packet: Buffer): MdnsMessage {
+	const reader = new Reader(packet);
+	const id = reader.u16();
+	const flags = reader.u16();
+	const questionCount = reader.u16();
+	const answerCount = reader.u16() + reader.u16() + reader.u16();
+	const questions: MdnsQuestion[] = [];
+	for (let i = 0; i < questionCount; i++) {
+		const name = reader.name();
+		questions.push({ name, type: reader.u16() });
+		reader.u16();
+	}
+	const records: MdnsRecord[] = [];
+	for (let i = 0; i < answerCount; i++) {
+		const record = reader.record();
+		if (record) records.push(record);
+	}
+	return { id, response: (flags & FLAG_RESPONSE) !== 0, questions, records };
+}
+
+function header(id: number, flags: number, questions: number, answers: number): Buffer {
+	const buf = Buffer.alloc(12);
+	buf.writeUInt16BE(id, 0);
+	buf.writeUInt16BE(flags, 2);
+	buf.writeUInt16BE(questions, 4);
+	buf.writeUInt16BE(answers, 6);
+	return buf;
+}
+
+function encodeRecord(record: MdnsRecord): Buffer {
+	let data: Buffer;
+	switch (record.type) {
+		case 'PTR':
+			data = encodeName(record.target);
+			break;
+		case 'SRV':
+			data = Buffer.concat([u16(0), u16(0), u16(record.port), encodeName(record.target)]);
+			break;
+		case 'TXT':
+			data = Buffer.concat(
+				Object.entries(record.data).map(([key, value]) => {
+					const entry = Buffer.from(`${key}=${value}`, 'utf8');
+					return Buffer.concat([Buffer.from([entry.length]), entry]);
+				}),
+			);
+			break;
+		case 'A':
+			data = Buffer.from(record.address.split('.').map(Number));
+			break;
+	}
+	// Shared PTR records must not flush other responders' instances from caches
+	const cls = record.type === 'PTR' ? CLASS_IN : CLASS_IN | CACHE_FLUSH;
+	const ttl = Buffer.alloc(4);
+	ttl.writeUInt32BE(record.ttl, 0);
+	return Buffer.concat([
+		encodeName(record.name),
+		u16(RecordType[record.type]),
+		u16(cls),
+		ttl,
+		u16(data.length),
+		data,
+	]);
+}
+
+function encodeName(name: string): Buffer {
+	const labels = name.split('.').filter((label) => label.length > 0);
+	const parts = labels.map((label) => {
+		const bytes = Buffer.from(label, 'utf8').subarray(0, 63);
+		return Buffer.concat([Buffer.from([bytes.length]), bytes]);
+	});
+	return Buffer.concat([...parts, Buffer.from([0])]);
+}
+
+function u16(value: number): Buffer {
+	const buf = Buffer.alloc(2);
+	buf.writeUInt16BE(value, 0);
+	return buf;
+}
+
+class Reader {
+	private offset = 0;
+
+	constructor(private readonly buf: Buffer) {}
+
+	u16(): number {
+		const value = this.buf.readUInt16BE(this.offset);
+		this.offset += 2;
+		return value;
+	}
+
+	u32(): number {
+		const value = this.buf.readUInt32BE(this.offset);
+		this.offset += 4;
+		return value;
+	}
+
+	name(): string {
+		const [name, next] = readName(this.buf, this.offset);
+		this.offset = next;
+		return name;
+	}
+
+	record(): MdnsRecord | null {
+		const name = this.name();
+		const type = this.u16();
+		this.u16();
+		const ttl = this.u32();
+		const length = this.u16();
+		const start = this.offset;
+		const end = start + length;
+		if (end > this.buf.length) throw new RangeError('Record runs past the end of the packet');
+		this.offset = end;
+		switch (type) {
+			case RecordType.PTR:
+				return { type: 'PTR', name, ttl, target: readName(this.buf, start)[0] };
+			case RecordType.SRV:
+				return {
+					type: 'SRV',
+					name,
+					ttl,
+					port: this.buf.readUInt16BE(start + 4),
+					target: readName(this.buf, start + 6)[0],
+				};
+			case RecordType.TXT:
+				return { type: 'TXT', name, ttl, data: readTxt(this.buf.subarray(start, end)) };
+			case RecordType.A:
+				if (length !== 4) return null;
+				return { type: 'A', name, ttl, address: [...this.buf.subarray(start, end)].join('.') };
+			default:
+				return null;
+		}
+	}
+}
+
+/** The name at `offset`, following compression pointers, and the offset just past it. */
+function readName(buf: Buffer, offset: number): [string, number] {
+	const labels: string[] = [];
+	let position = offset;
+	let next = -1;
+	// Each pointer must go backwards, which also rules out loops
+	let limit = offset;
+	for (;;) {
+		const length = buf.readUInt8(position);
+		if (length === 0) {
+			position += 1;
+			break;
+		}
+		if ((length & 0xc0) === 0xc0) {
+			const target = buf.readUInt16BE(position) & 0x3fff;
+			if (next < 0) next = position + 2;
+			if (target >= limit) throw new RangeError('Name pointer does not point backwards');
+			limit = target;
+			position = target;
+			continue;
+		}
+		const end = position + 1 + length;
+		if (end > buf.length) throw new RangeError('Label runs past the end of the packet');
+		labels.push(buf.toString('utf8', position + 1, end));
+		position = end;
+	}
+	return [labels.join('.'), next < 0 ? position : next];
+}
+
+function readTxt(data: Buffer): Record<string, string> {
+	const entries: Record<string, string> = {};
+	let position = 0;
+	while (position < data.length) {
+		const length = data.readUInt8(position);
+		const entry = data.toString('utf8', position + 1, position + 1 + length);
+		position += 1 + length;
+		const eq = entry.indexOf('=');
+		if (eq > 0) entries[entry.slice(0, eq)] = entry.slice(eq + 1);
+	}
+	return entries;
+}
@@ -0,0 +1,42 @@
+import { describe, expect, it } from 'vitest';
+import { parseDate, parseInvoiceFields } from './InvoiceFields.js';
+
+describe('parseInvoiceFields', () => {
+	it('reads the vendor, final total and labelled date', () => {
+		const text = [
+			'Acme Supplies',
+			'INVOICE #1043',
+			'Invoice date: March 5, 2024',
+			'Due date: April 4, 2024',
+			'Subtotal $1,100.00',
+			'Total $1,234.50',
+		].join('\n');
+		expect(parseInvoiceFields(text)).toEqual({
+			vendor: 'Acme Supplies',
+			amount: '1234.50',
+			currency: 'USD',
+			date: new Date(2024, 2, 5),
+		});
+	});
+
+	it('prefers a labelled vendor and handles European amounts', () => {
+		const text = 'Rechnung\nFrom: Büro Müller GmbH\nDatum 05.03.2024\nTotal 1.234,50 EUR';
+		expect(parseInvoiceFields(text)).toEqual({
+			vendor: 'Büro Müller GmbH',
+			amount: '1234.50',
+			currency: 'EUR',
+			date: new Date(2024, 2, 5),
+		});
+	});
+
+	it('leaves out what it cannot find', () => {
+		expect(parseInvoiceFields('')).toEqual({});
+	});
+});
+
+describe('parseDate', () => {
+	it('skips impossible dates', () => {
+		expect(parseDate('2024-02-30 or 12/31/2023')).toEqual(new Date(2023, 11, 31));
+		expect(parseDate('Invoice 12, 2024, paid 3rd April 2024')).toEqual(new Date(2024, 3, 3));
+	});
+});
@@ -0,0 +1,188 @@
+/** What an invoice or receipt says about itself, as far as its text shows. */
+export type DocumentFields = {
+	vendor?: string;
+	/** Total with a `.` decimal separator and no grouping, e.g. `1234.50`. */
+	amount?: string;
+	/** ISO 4217 code, e.g. `EUR`. */
+	currency?: string;
+	/** Invoice or receipt date, at local midnight. */
+	date?: Date;
+};
+
+// '\x80' is the euro sign in the WinAnsi encoding most PDF fonts use
+const CURRENCY_SYMBOLS: Record<string, string> = {
+	$: 'USD',
+	'€': 'EUR',
+	'\x80': 'EUR',
+	'£': 'GBP',
+	'¥': 'JPY',
+	'₹': 'INR',
+};
+const CURRENCY_CODES = new Set([
+	'USD',
+	'EUR',
+	'GBP',
+	'JPY',
+	'CHF',
+	'CAD',
+	'AUD',
+	'INR',
+	'SEK',
+	'NOK',
+	'DKK',
+]);
+
+const MONTHS = ['jan', 'feb', 'mar', 'apr', 'may', 'jun', 'jul', 'aug', 'sep', 'oct', 'nov', 'dec'];
+
+const TOTAL_RE = /\b(?:grand total|total due|amount due|amount paid|balance due|total)\b/i;
+const MONEY_RE =
+	/(?:\b([A-Z]{3})\s?|([$€£¥₹\x80])\s?)?(\d{1,3}(?:[,.' ]\d{3})+(?:[.,]\d{2})?|\d+(?:[.,]\d{2})?)(?:\s?([A-Z]{3})\b|\s?([€£\x80]))?/g;
+const VENDOR_LABEL_RE = /^(?:from|vendor|seller|supplier|merchant|billed by|sold by)\s*:?\s+(.+)$/i;
+const NOT_VENDOR_RE =
+	/\b(?:invoice|receipt|statement|bill to|ship to|page|date|number|no\.|total|tax|vat)\b|#/i;
+
+/**
+ * Pick the vendor, total, currency and date out of an invoice's or receipt's text (see
+ * `pdfText`). Only what can be found is set; the heuristics favour labelled values (`Total`,
+ * `Invoice date`, `From:`) and fall back to the first plausible one.
+ */
+export function parseInvoiceFields(text: string): DocumentFields {
+	const lines = text
+		.split('\n')
+		.map((line) => line.trim())
+		.filter(Boolean);
+	const fields: DocumentFields = {};
+
+	const vendor = findVendor(lines);
+	if (vendor) fields.vendor = vendor;
+
+	const total = findTotal(lines);
+	if (total?.amount) fields.amount = total.amount;
+	const currency = total?.currency ?? findCurrency(text);
+	if (currency) fields.currency = currency;
+
+	const date = findDate(lines);
+	if (date) fields.date = date;
+	return fields;
+}
+
+function findVendor(lines: string[]): string | undefined {
+	for (const line of lines) {
+		const labelled = VENDOR_LABEL_RE.exec(line)?.[1];
+		if (labelled) return cleanVendor(labelled);
+	}
+	const first = lines.find((line) => {
+		const letters = line.replace(/[^\p{L}]/gu, '').length;
+		if (letters < 2 || letters < line.length / 2 || line.length > 60) return false;
+		return !NOT_VENDOR_RE.test(line);
+	});
+	return first ? cleanVendor(first) : undefined;
+}
+
+function cleanVendor(name: string): string | undefined {
+	const cleaned = name
+		.replace(/[\\/:*?"<>|]/g, ' ')
+		.replace(/\s+/g, ' ')
+		.replace(/^[\s.,;-]+|[\s.,;-]+$/g, '');
+	return cleaned || undefined;
+}
+
+function findTotal(lines: string[]): { amount?: string; currency?: string } | undefined {
+	// The last total wins: it usually follows the subtotal and tax lines
+	for (let i = lines.length - 1; i >= 0; i--) {
+		const line = lines[i] as string;
+		if (!TOTAL_RE.test(line)) continue;
+		const money = lastMoney(line) ?? lastMoney(lines[i + 1] ?? '');
+		if (money) return money;
+	}
+	return undefined;
+}
+
+function lastMoney(line: string): { amount: string; currency?: string } | undefined {
+	const matches = [...line.matchAll(MONEY_RE)];
+	const match = matches.at(-1);
+	if (!match?.[3]) return undefined;
+	const code = [match[1], match[4]].find((c) => c && CURRENCY_CODES.has(c));
+	const symbol = match[2] ?? match[5];
+	return {
+		amount: normalizeAmount(match[3]),
+		currency: code ?? (symbol ? CURRENCY_SYMBOLS[symbol] : undefined),
+	};
+}
+
+/** `1.234,50`, `1,234.50` and `1 234,50` all become `1234.50`. */
+function normalizeAmount(raw: string): string {
+	const decimal = /[.,](\d{2})$/.exec(raw);
+	const whole = (decimal ? raw.slice(0, -3) : raw).replace(/[^\d]/g, '');
+	return decimal ? `${whole}.${decimal[1]}` : whole;
+}
+
+function findCurrency(text: string): string | undefined {
+	for (const [symbol, code] of Object.entries(CURRENCY_SYMBOLS)) {
+		if (text.includes(symbol)) return code;
+	}
+	for (const [, code] of text.matchAll(/\b([A-Z]{3})\b/g)) {
+		if (code && CURRENCY_CODES.has(code)) return code;
+	}
+	return undefined;
+}
+
+function findDate(lines: string[]): Date | undefined {
+	const labelled = lines.findIndex((line) => /\bdate\b/i.test(line) && !/\bdue\b/i.test(line));
+	if (labelled >= 0) {
+		const date = parseDate(lines[labelled] as string) ?? parseDate(lines[labelled + 1] ?? '');
+		if (date) return date;
+	}
+	for (const line of lines) {
+		const date = parseDate(line);
+		if (date) return date;
+	}
+	return undefined;
+}
+
+const DATE_FORMATS: [RegExp, (m: RegExpMatchArray) => Date | undefined][] = [
+	[/\b(\d{4})-(\d{1,2})-(\d{1,2})\b/g, (m) => makeDate(m[1], m[2], m[3])],
+	[/\b(\d{1,2})\.(\d{1,2})\.(\d{4})\b/g, (m) => makeDate(m[3], m[2], m[1])],
+	[/\b(\d{1,2})\/(\d{1,2})\/(\d{4})\b/g, (m) => makeDate(m[3], m[1], m[2])],
+	[
+		/\b([A-Za-z]{3,9})\.?\s+(\d{1,2})(?:st|nd|rd|th)?,?\s+(\d{4})\b/g,
+		(m) => makeDate(m[3], monthNumber(m[1]), m[2]),
+	],
+	[
+		/\b(\d{1,2})(?:st|nd|rd|th)?\s+([A-Za-z]{3,9})\.?,?\s+(\d{4})\b/g,
+		(m) => makeDate(m[3], monthNumber(m[2]), m[1]),
+	],
+];
+
+/**
+ * The first date in `text`: `2024-03-05`, `05.03.2024` (day first), `03/05/2024` (month first),
+ * `March 5, 2024` or `5 March 2024`.
+ */
+export function parseDate(text: string): Date | undefined {
+	for (const [pattern, build] of DATE_FORMATS) {
+		for (const match of text.matchAll(pattern)) {
+			const date = build(match);
+			if (date) return date;
+		}
+	}
+	return undefined;
+}
+
+function monthNumber(name: string | undefined): string | undefined {
+	const index = MONTHS.indexOf((name ?? '').slice(0, 3).toLowerCase());
+	return index >= 0 ? String(index + 1) : undefined;
+}
+
+function makeDate(
+	year: string | undefined,
+	month: string | undefined,
+	day: string | undefined,
+): Date | undefined {
+	const y = Number(year);
+	const m = Number(month);
+	const d = Number(day);
+	if (!y || !m || !d || m > 12 || d > 31) return undefined;
+	const date = new Date(y, m - 1, d);
+	// Reject rollovers like 31 February
+	return date.getMonth() === m - 1 ? date : undefined;
+}
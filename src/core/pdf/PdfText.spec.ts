@@ -0,0 +1,24 @@
+import zlib from 'node:zlib';
+import { describe, expect, it } from 'vitest';
+import { pdfText } from './PdfText.js';
+
+function pdf(content: string): Buffer {
+	const stream = zlib.deflateSync(Buffer.from(content, 'latin1'));
+	return Buffer.concat([
+		Buffer.from('%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n'),
+		Buffer.from(`4 0 obj\n<< /Length ${stream.length} /Filter /FlateDecode >>\nstream\n`),
+		stream,
+		Buffer.from('\nendstream\nendobj\n%%EOF'),
+	]);
+}
+
+describe('pdfText', () => {
+	it('reads text from compressed content streams a line at a time', () => {
+		const content = [
+			'BT /F1 12 Tf 72 720 Td (Acme \\(UK\\) Ltd) Tj',
+			'0 -14 Td [(Invoice date:)-400(March 5, 2024)] TJ',
+			'T* <546F74616C> Tj ET',
+		].join('\n');
+		expect(pdfText(pdf(content))).toBe('Acme (UK) Ltd\nInvoice date: March 5, 2024\nTotal');
+	});
+});
@@ -0,0 +1,125 @@
+import fs from 'node:fs/promises';
+import zlib from 'node:zlib';
+
+/** PDFs larger than this aren't read; invoices and receipts are far smaller. */
+const MAX_PDF_BYTES = 8 * 1024 * 1024;
+
+/** Text of a PDF file, or an empty string when it is too large to be a typical document. */
+export async function extractPdfText(file: string): Promise<string> {
+	const st = await fs.stat(file);
+	if (st.size > MAX_PDF_BYTES) return '';
+	return pdfText(await fs.readFile(file));
+}
+
+/**
+ * Text shown by a PDF's content streams, one line per line of text. Only uncompressed and Flate
+ * streams are read and glyphs are taken as Latin-1, so text in fonts with custom encodings comes
+ * out garbled, and scans without a text layer have none.
+ */
+export function pdfText(data: Buffer): string {
+	const raw = data.toString('latin1');
+	const lines: string[] = [];
+	const streamRe = /\bobj\s*<<([\s\S]*?)>>\s*stream\r?\n/g;
+	for (let match = streamRe.exec(raw); match; match = streamRe.exec(raw)) {
+		const dict = match[1] ?? '';
+		const start = match.index + match[0].length;
+		const end = raw.indexOf('endstream', start);
+		if (end < 0) break;
+		streamRe.lastIndex = end;
+		// Images and embedded fonts hold no page text
+		if (/\/Subtype\s*\/Image|\/Length1|\/Type\s*\/XObject/.test(dict)) continue;
+		const bytes = data.subarray(start, end);
+		let content: string;
+		if (/\/FlateDecode/.test(dict)) {
+			try {
+				content = zlib
+					.inflateSync(bytes, { finishFlush: zlib.constants.Z_SYNC_FLUSH })
+					.toString('latin1');
+			} catch {
+				continue;
+			}
+		} else if (/\/Filter/.test(dict)) {
+			continue;
+		} else {
+			content = bytes.toString('latin1');
+		}
+		lines.push(...showText(content));
+	}
+	return lines.join('\n');
+}
+
+const TOKEN_RE = /\((?:\\[\s\S]|[^\\)])*\)|<[0-9A-Fa-f\s]*>|\[|\]|[-+]?(?:\d+\.?\d*|\.\d+)|\/[^\s/<>()[\]]*|[A-Za-z'"*]+/g;
+
+/** Lines of text drawn by the text operators (`Tj`, `TJ`, `'`, `"`) in a content stream. */
+function showText(content: string): string[] {
+	const lines: string[] = [];
+	let line = '';
+	let strings: string[] = [];
+	let numbers: number[] = [];
+	let inArray = false;
+	const flush = () => {
+		const text = line.replace(/\s+/g, ' ').trim();
+		if (text) lines.push(text);
+		line = '';
+	};
+
+	for (const [token] of content.matchAll(TOKEN_RE)) {
+		if (token.startsWith('(')) {
+			strings.push(literalString(token.slice(1, -1)));
+		} else if (token.startsWith('<')) {
+			strings.push(hexString(token.slice(1, -1)));
+		} else if (token === '[') {
+			inArray = true;
+		} else if (token === ']') {
+			inArray = false;
+		} else if (/^[-+.\d]/.test(token)) {
+			const value = Number(token);
+			// Wide negative kerning inside TJ stands in for a space
+			if (inArray && value <= -200) strings.push(' ');
+			else numbers.push(value);
+		} else if (!token.startsWith('/') && !inArray) {
+			switch (token) {
+				case 'Tj':
+				case 'TJ':
+					line += strings.join('');
+					break;
+				case "'":
+				case '"':
+					flush();
+					line += strings.join('');
+					break;
+				case 'Td':
+				case 'TD':
+					if ((numbers.at(-1) ?? 0) !== 0) flush();
+					else line += ' ';
+					break;
+				case 'T*':
+				case 'Tm':
+				case 'ET':
+					flush();
+					break;
+			}
+			strings = [];
+			numbers = [];
+		}
+	}
+	flush();
+	return lines;
+}
+
+const ESCAPES: Record<string, string> = { n: '\n', r: '\r', t: '\t', b: '\b', f: '\f' };
+
+function literalString(body: string): string {
+	return body.replace(/\\(\d{1,3}|\r?\n|[\s\S])/g, (_, escape: string) => {
+		if (/^\d/.test(escape)) return String.fromCharCode(Number.parseInt(escape, 8) & 0xff);
+		if (escape.startsWith('\n') || escape.startsWith('\r')) return '';
+		return ESCAPES[escape] ?? escape;
+	});
+}
+
+function hexString(body: string): string {
+	const hex = body.replace(/\s+/g, '');
+	const bytes = Buffer.from(hex.length % 2 ? `${hex}0` : hex, 'hex');
+	// Two-byte glyph codes for Latin text have a zero high byte
+	return bytes.toString('latin1').replace(/\0/g, '');
+}
@@ -0,0 +1,110 @@
+import { describe, expect, test } from 'vitest';
+import { WorkQueue } from './WorkQueue.js';
+
+function deferred() {
+	let resolve: () => void = () => {};
+	const promise = new Promise<void>((r) => {
+		resolve = r;
+	});
+	return { promise, resolve };
+}
+
+describe('WorkQueue', () => {
+	test('runs jobs immediately while under capacity', () => {
+		const queue = new WorkQueue(2);
+		const started: string[] = [];
+		queue.enqueue('/dir', 'a.png', async () => {
+			started.push('a');
+		});
+		expect(started).toEqual(['a']);
+	});
+
+	test('counts pending and active jobs per directory', () => {
+		const queue = new WorkQueue(1);
+		const gate = deferred();
+		queue.enqueue('/a', '1.png', () => gate.promise);
+		queue.enqueue('/a', '2.png', async () => {});
+		queue.enqueue('/b', '3.png', async () => {});
+		expect(queue.countByDirectory()).toEqual(
+			new Map([
+				['/a', 2],
+				['/b', 1],
+			]),
+		);
+		gate.resolve();
+	});
+
+	test('holds jobs while paused and reorders pending items', async () => {
+		const queue = new WorkQueue(1);
+		const order: string[] = [];
+		queue.setPaused(true);
+		const a = queue.enqueue('/dir', 'a.png', async () => {
+			order.push('a');
+		});
+		const b = queue.enqueue('/dir', 'b.png', async () => {
+			order.push('b');
+		});
+		const c = queue.enqueue('/dir', 'c.png', async () => {
+			order.push('c');
+		});
+
+		expect(queue.snapshot().pending.map((item) => item.file)).toEqual(['a.png', 'b.png', 'c.png']);
+
+		queue.reorder([c.id]);
+		expect(queue.drop(a.id)).toBe(true);
+		expect(queue.snapshot().pending.map((item) => item.id)).toEqual([c.id, b.id]);
+
+		queue.setPaused(false);
+		await new Promise((r) => setTimeout(r, 0));
+		expect(order).toEqual(['c', 'b']);
+	});
+
+	test('places high-priority jobs ahead of normal work', () => {
+		const queue = new WorkQueue(1);
+		queue.setPaused(true);
+		queue.enqueue('/bulk', 'one.png', async () => {});
+		queue.enqueue('/bulk', 'two.png', async () => {});
+		queue.enqueue('/inbox', 'urgent.png', async () => {}, 'high');
+		queue.enqueue('/inbox', 'urgent-2.png', async () => {}, 'high');
+
+		expect(queue.snapshot().pending.map((item) => item.file)).toEqual([
+			'urgent.png',
+			'urgent-2.png',
+			'one.png',
+			'two.png',
+		]);
+	});
+
+	test('caps concurrency and drains as jobs finish', async () => {
+		const queue = new WorkQueue(1);
+		const gate = deferred();
+		queue.enqueue('/dir', 'slow.png', () => gate.promise);
+		queue.enqueue('/other', 'next.png', async () => {});
+
+		let snapshot = queue.snapshot();
+		expect(snapshot.active.map((item) => item.file)).toEqual(['slow.png']);
+		expect(snapshot.pending.map((item) => item.file)).toEqual(['next.png']);
+
+		expect(queue.dropDirectory('/other')).toBe(1);
+		gate.resolve();
+		await new Promise((r) => setTimeout(r, 0));
+		snapshot = queue.snapshot();
+		expect(snapshot.active).toEqual([]);
+		expect(snapshot.pending).toEqual([]);
+	});
+
+	test('reports job failures without stalling the queue', async () => {
+		const errors: string[] = [];
+		const queue = new WorkQueue(1, (err) => errors.push(String(err)));
+		const ran: string[] = [];
+		queue.enqueue('/dir', 'bad.png', async () => {
+			throw new Error('boom');
+		});
+		queue.enqueue('/dir', 'good.png', async () => {
+			ran.push('good');
+		});
+		await new Promise((r) => setTimeout(r, 0));
+		expect(errors).toEqual(['Error: boom']);
+		expect(ran).toEqual(['good']);
+	});
+});
@@ -0,0 +1,142 @@
+export type QueuePriority = 'high' | 'normal';
+
+export type QueueItem = {
+	id: string;
+	directory: string;
+	file: string;
+	priority: QueuePriority;
+	enqueuedAt: number;
+};
+
+export type QueueSnapshot = {
+	paused: boolean;
+	active: QueueItem[];
+	pending: QueueItem[];
+};
+
+type Job = QueueItem & { run: () => Promise<void> };
+
+/**
+ * Bounded-concurrency work queue for watch events.
+ *
+ * Jobs start immediately while there is spare capacity, so under normal load the queue is
+ * invisible; during bursts (or while paused) the pending list can be inspected, reordered,
+ * or trimmed. High-priority jobs are placed ahead of all pending normal-priority jobs.
+ */
+export class WorkQueue {
+	private pending: Job[] = [];
+	private active = new Map<string, QueueItem>();
+	private paused = false;
+	private seq = 0;
+	private idleWaiters: (() => void)[] = [];
+
+	constructor(
+		private readonly concurrency = 4,
+		private readonly onError: (err: unknown, item: QueueItem) => void = () => {},
+		private readonly onSettled: (item: QueueItem) => void = () => {},
+	) {}
+
+	enqueue(
+		directory: string,
+		file: string,
+		run: () => Promise<void>,
+		priority: QueuePriority = 'normal',
+	): QueueItem {
+		this.seq += 1;
+		const job: Job = { id: `q${this.seq}`, directory, file, priority, enqueuedAt: Date.now(), run };
+		if (priority === 'high') {
+			const firstNormal = this.pending.findIndex((pending) => pending.priority !== 'high');
+			this.pending.splice(firstNormal === -1 ? this.pending.length : firstNormal, 0, job);
+		} else {
+			this.pending.push(job);
+		}
+		this.pump();
+		return toItem(job);
+	}
+
+	snapshot(): QueueSnapshot {
+		return {
+			paused: this.paused,
+			active: [...this.active.values()],
+			pending: this.pending.map(toItem),
+		};
+	}
+
+	/** Pending plus active jobs for each directory that has any. */
+	countByDirectory(): Map<string, number> {
+		const counts = new Map<string, number>();
+		for (const item of [...this.active.values(), ...this.pending]) {
+			counts.set(item.directory, (counts.get(item.directory) ?? 0) + 1);
+		}
+		return counts;
+	}
+
+	setPaused(paused: boolean): void {
+		this.paused = paused;
+		if (!paused) this.pump();
+	}
+
+	/** Resolves once no job is running. Pause first so new ones don't start meanwhile. */
+	whenIdle(): Promise<void> {
+		if (this.active.size === 0) return Promise.resolve();
+		return new Promise((resolve) => this.idleWaiters.push(resolve));
+	}
+
+	/**
+	 * Move the given pending ids to the front in the order provided. Unknown ids are ignored and
+	 * unlisted items keep their relative order behind them.
+	 */
+	reorder(orderedIds: string[]): void {
+		const byId = new Map(this.pending.map((job) => [job.id, job]));
+		const front: Job[] = [];
+		for (const id of orderedIds) {
+			const job = byId.get(id);
+			if (job) {
+				front.push(job);
+				byId.delete(id);
+			}
+		}
+		this.pending = [...front, ...this.pending.filter((job) => byId.has(job.id))];
+	}
+
+	drop(id: string): boolean {
+		const before = this.pending.length;
+		this.pending = this.pending.filter((job) => job.id !== id);
+		return this.pending.length !== before;
+	}
+
+	dropDirectory(directory: string): number {
+		const before = this.pending.length;
+		this.pending = this.pending.filter((job) => job.directory !== directory);
+		return before - this.pending.length;
+	}
+
+	private pump(): void {
+		while (!this.paused && this.active.size < this.concurrency && this.pending.length > 0) {
+			const job = this.pending.shift() as Job;
+			const item = toItem(job);
+			this.active.set(job.id, item);
+			job
+				.run()
+				.catch((err) => this.onError(err, item))
+				.finally(() => {
+					this.active.delete(job.id);
+					this.onSettled(item);
+					if (this.active.size === 0) {
+						for (const resolve of this.idleWaiters.splice(0)) resolve();
+					}
+					this.pump();
+				});
+		}
+	}
+}
+
+function toItem(job: Job): QueueItem {
+	return {
+		id: job.id,
+		directory: job.directory,
+		file: job.file,
+		priority: job.priority,
+		enqueuedAt: job.enqueuedAt,
+	};
+}
@@ -0,0 +1,155 @@
+import { describe, expect, test } from 'vitest';
+import {
+	applyCaseStyle,
+	applyTemplate,
+	buildNameFromTemplate,
+	fiscalPeriod,
+	formatDate,
+	inTimeZone,
+	isoWeek,
+	isValidTimeZone,
+	regexMatches,
+} from './NameTemplate.js';
+
+function ctx(birthtime: Date, fiscalYearStartMonth?: number) {
+	return { originalPath: '/tmp/shot.png', birthtime, ext: '.png', prefix: 'Shot', fiscalYearStartMonth };
+}
+
+describe('isoWeek', () => {
+	test('assigns late December to the next ISO year when its Thursday falls there', () => {
+		expect(isoWeek(new Date(2024, 11, 30))).toEqual({ year: 2025, week: 1, weekday: 1 });
+	});
+
+	test('assigns early January to the previous ISO year when needed', () => {
+		expect(isoWeek(new Date(2021, 0, 3))).toEqual({ year: 2020, week: 53, weekday: 7 });
+	});
+
+	test('numbers mid-year weeks', () => {
+		expect(isoWeek(new Date(2024, 11, 26)).week).toBe(52);
+	});
+});
+
+describe('fiscalPeriod', () => {
+	test('defaults to the calendar year', () => {
+		expect(fiscalPeriod(new Date(2024, 4, 15))).toEqual({ year: 2024, quarter: 2 });
+	});
+
+	test('names the fiscal year after the year it ends in', () => {
+		expect(fiscalPeriod(new Date(2024, 4, 15), 4)).toEqual({ year: 2025, quarter: 1 });
+		expect(fiscalPeriod(new Date(2025, 2, 31), 4)).toEqual({ year: 2025, quarter: 4 });
+	});
+
+	test('ignores out-of-range start months', () => {
+		expect(fiscalPeriod(new Date(2024, 4, 15), 13)).toEqual({ year: 2024, quarter: 2 });
+	});
+});
+
+describe('formatDate', () => {
+	test('formats calendar and ISO week directives', () => {
+		const d = new Date(2024, 11, 30, 9, 5, 7);
+		expect(formatDate(d, '%Y-%m-%d %H:%M:%S')).toBe('2024-12-30 09:05:07');
+		expect(formatDate(d, '%G-W%V-%u')).toBe('2025-W01-1');
+		expect(formatDate(d, '%y/%j')).toBe('24/365');
+	});
+
+	test('keeps literal percent signs and unknown directives', () => {
+		expect(formatDate(new Date(2024, 0, 1), '100%% %Q')).toBe('100% %Q');
+	});
+});
+
+describe('inTimeZone', () => {
+	test('reads the wall clock in the given zone', () => {
+		const d = new Date(Date.UTC(2024, 11, 31, 23, 30, 0));
+		const shifted = inTimeZone(d, 'Asia/Tokyo');
+		expect([shifted.getFullYear(), shifted.getMonth(), shifted.getDate(), shifted.getHours()]).toEqual([
+			2025, 0, 1, 8,
+		]);
+	});
+
+	test('leaves the date alone without a valid zone', () => {
+		const d = new Date(2024, 0, 1);
+		expect(inTimeZone(d)).toBe(d);
+		expect(inTimeZone(d, 'Mars/Olympus')).toBe(d);
+		expect(isValidTimeZone('Mars/Olympus')).toBe(false);
+	});
+});
+
+describe('applyTemplate', () => {
+	test('expands <date:FORMAT> tokens', () => {
+		expect(applyTemplate('<date:%G-W%V>/<original>', ctx(new Date(2024, 11, 30)))).toBe('2025-W01/shot');
+	});
+
+	test('expands week and fiscal tokens using the profile start month', () => {
+		const d = new Date(2024, 9, 1);
+		expect(applyTemplate('W<week>', ctx(d))).toBe('W40');
+		expect(applyTemplate('FY<fiscal_year>-<fiscal_quarter>', ctx(d))).toBe('FY2024-Q4');
+		expect(applyTemplate('FY<fiscal_year>-<fiscal_quarter>', ctx(d, 7))).toBe('FY2025-Q2');
+	});
+
+	test('renders date variables in the profile time zone', () => {
+		const d = new Date(Date.UTC(2024, 11, 31, 23, 30, 0));
+		const utc = { ...ctx(d), timeZone: 'UTC' };
+		const tokyo = { ...ctx(d), timeZone: 'Asia/Tokyo' };
+		expect(applyTemplate('<datetime>', utc)).toBe('2024-12-31_23-30-00');
+		expect(applyTemplate('<datetime>', tokyo)).toBe('2025-01-01_08-30-00');
+	});
+	test('expands document variables and drops separators around missing ones', () => {
+		const template = '<doc_year>-<doc_month>/<doc_date>_<vendor>_<currency><amount>';
+		const date = new Date(2024, 2, 5);
+		const document = { vendor: 'Acme', amount: '12.50', currency: 'USD', date };
+		expect(applyTemplate(template, { ...ctx(new Date(2024, 5, 1)), document })).toBe(
+			'2024-03/2024-03-05_Acme_USD12.50',
+		);
+		expect(applyTemplate(template, ctx(new Date(2024, 5, 1)))).toBe('2024-06/2024-06-01');
+	});
+
+	test('files audio by its tags, keeping each tag to one safe path segment', () => {
+		const template = '<album_artist>/<album>/<track> - <title>';
+		const audio = { artist: 'AC/DC', album: 'Back in Black', title: 'Hells Bells?', track: 1 };
+		expect(applyTemplate(template, { ...ctx(new Date()), audio })).toBe(
+			'AC DC/Back in Black/01 - Hells Bells',
+		);
+		expect(applyTemplate(template, { ...ctx(new Date()), audio: { artist: 'Björk' } })).toBe(
+			'Björk/shot',
+		);
+	});
+
+	test('names files after the front window, or leaves it out', () => {
+		const d = new Date(2024, 1, 4);
+		const windowTitle = 'Xcode — MainView.swift';
+		expect(applyTemplate('<window_title> <date>', { ...ctx(d), windowTitle })).toBe(
+			'Xcode — MainView.swift 2024-02-04',
+		);
+		expect(applyTemplate('<window_title> <date>', ctx(d))).toBe('2024-02-04');
+	});
+});
+
+describe('rules', () => {
+	test('fills <match:…> from the groups a regex rule captured', () => {
+		const matches = regexMatches('^IMG_(\\d{4})(?<rest>.*)$', 'IMG_0042 edit.png');
+		expect(matches).toMatchObject({ '0': 'IMG_0042 edit.png', '1': '0042', rest: ' edit.png' });
+		expect(applyTemplate('photo-<match:1>_<match:9>', { ...ctx(new Date()), matches })).toBe(
+			'photo-0042',
+		);
+		expect(regexMatches('(', 'IMG_0042.png')).toEqual({});
+	});
+
+	test('normalizes case without touching the extension', () => {
+		expect(applyCaseStyle('Screen Shot (2)', 'kebab')).toBe('screen-shot-2');
+		expect(applyCaseStyle('Screen Shot (2)', 'snake')).toBe('screen_shot_2');
+		expect(applyCaseStyle('hello wORLD', 'title')).toBe('Hello World');
+		const shot = { ...ctx(new Date(2024, 11, 26)), ext: '.PNG', caseStyle: 'upper' as const };
+		expect(buildNameFromTemplate('<prefix>/<original>', shot)).toBe('SHOT/SHOT.png');
+		expect(buildNameFromTemplate('<original><ext>', shot)).toBe('SHOT.png');
+	});
+
+	test('puts the date in front of the name once', () => {
+		const d = new Date(2024, 11, 26);
+		const dated = { ...ctx(d), datePrefix: true };
+		expect(buildNameFromTemplate('<original>', dated)).toBe('2024-12-26_shot.png');
+		expect(buildNameFromTemplate('<date>_<original>', dated)).toBe('2024-12-26_shot.png');
+		expect(buildNameFromTemplate('<prefix>/<original>', { ...dated, caseStyle: 'kebab' })).toBe(
+			'shot/2024-12-26-shot.png',
+		);
+	});
+});
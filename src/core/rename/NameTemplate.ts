@@ -1,6 +1,8 @@
 import path from 'node:path';
 import { randomUUID } from 'node:crypto';
-import type { IProfile } from '../../types/index.js';
+import type { CaseStyle, IProfile } from '../../types/index.js';
+import type { AudioTags } from '../audio/AudioTags.js';
+import type { DocumentFields } from '../pdf/InvoiceFields.js';
 
 function pad2(n: number): string {
 	return String(n).padStart(2, '0');
@@ -62,6 +64,180 @@ export interface TemplateContext {
 	prefix: string;
 	/** Counter value for collision resolution */
 	counter?: number;
+	/** First month of the fiscal year (1-12, default 1 = January) */
+	fiscalYearStartMonth?: number;
+	/** IANA time zone to render date tokens in (default: system local time) */
+	timeZone?: string;
+	/** Fields read from the file's text, for the document variables */
+	document?: DocumentFields;
+	/** Tags read from an audio file, for the audio variables */
+	audio?: AudioTags;
+	/** Front window when the file was created (`App — Title`), for <window_title> */
+	windowTitle?: string;
+	/** Groups a regex rule captured from the file's name, for <match:N> and <match:name> */
+	matches?: Record<string, string>;
+	/** Case applied to the finished name, extension aside */
+	caseStyle?: CaseStyle;
+	/** Prefix the finished name with the file's date */
+	datePrefix?: boolean;
+}
+
+const DOCUMENT_VARIABLE_RE = /<(?:\w+:)?(?:vendor|amount|currency|doc_date|doc_year|doc_month)>/;
+const AUDIO_VARIABLE_RE = /<(?:\w+:)?(?:artist|album_artist|album|title|track|disc)>/;
+
+/**
+ * Whether a template uses the document variables, which need the file's text read first.
+ */
+export function usesDocumentTokens(template: string): boolean {
+	return DOCUMENT_VARIABLE_RE.test(template);
+}
+
+/**
+ * Whether a template uses the audio variables, which need the file's tags read first.
+ */
+export function usesAudioTokens(template: string): boolean {
+	return AUDIO_VARIABLE_RE.test(template);
+}
+
+/**
+ * Whether a template uses <window_title>, which needs the front window looked up as files arrive.
+ */
+export function usesWindowTitle(template: string): boolean {
+	return /<(?:\w+:)?window_title>/.test(template);
+}
+
+/**
+ * Whether a template has <expr:…> segments, which the name hook fills in before the rest.
+ */
+export function usesExpressions(template: string): boolean {
+	return template.includes('<expr:');
+}
+
+/** Stands in for a field the file doesn't have (e.g. its album), until the name is tidied. */
+const MISSING = '\0';
+
+/**
+ * A tag value made safe for one path segment: no separators or characters Windows rejects, and
+ * no trailing dots or spaces.
+ */
+function segmentSafe(value: string | undefined): string {
+	const safe = (value ?? '')
+		.replace(/[\\/:*?"<>|\x00-\x1f]/g, ' ')
+		.replace(/\s+/g, ' ')
+		.replace(/^[\s.]+|[\s.]+$/g, '');
+	return safe || MISSING;
+}
+
+/**
+ * Whether `timeZone` is an IANA zone name the runtime understands.
+ */
+export function isValidTimeZone(timeZone: string): boolean {
+	try {
+		new Intl.DateTimeFormat('en-US', { timeZone });
+		return true;
+	} catch {
+		return false;
+	}
+}
+
+/**
+ * Shift `d` so its local fields (getHours() and friends) read as the wall
+ * clock in `timeZone`. Unknown zones fall back to local time.
+ */
+export function inTimeZone(d: Date, timeZone?: string): Date {
+	if (!timeZone || !isValidTimeZone(timeZone)) return d;
+	const parts = new Intl.DateTimeFormat('en-US', {
+		timeZone,
+		hourCycle: 'h23',
+		year: 'numeric',
+		month: 'numeric',
+		day: 'numeric',
+		hour: 'numeric',
+		minute: 'numeric',
+		second: 'numeric',
+	}).formatToParts(d);
+	const field = (type: Intl.DateTimeFormatPartTypes) =>
+		Number(parts.find((part) => part.type === type)?.value ?? 0);
+	return new Date(
+		field('year'),
+		field('month') - 1,
+		field('day'),
+		field('hour'),
+		field('minute'),
+		field('second'),
+		d.getMilliseconds(),
+	);
+}
+
+/**
+ * ISO-8601 week-numbering year and week (weeks start on Monday; week 1 holds
+ * the year's first Thursday), so late December can belong to next year's W01.
+ */
+export function isoWeek(d: Date): { year: number; week: number; weekday: number } {
+	const weekday = d.getDay() || 7;
+	// The Thursday of this week decides which year the week belongs to.
+	const thursday = new Date(d.getFullYear(), d.getMonth(), d.getDate() + 4 - weekday);
+	const jan1 = new Date(thursday.getFullYear(), 0, 1);
+	const dayOfYear = Math.round((thursday.getTime() - jan1.getTime()) / 86_400_000);
+	return { year: thursday.getFullYear(), week: Math.floor(dayOfYear / 7) + 1, weekday };
+}
+
+/**
+ * Fiscal year and quarter for a year starting in `startMonth` (1-12). The
+ * fiscal year is named after the calendar year it ends in, so with an April
+ * start, May 2024 falls in FY2025 Q1.
+ */
+export function fiscalPeriod(d: Date, startMonth = 1): { year: number; quarter: number } {
+	const start = Number.isInteger(startMonth) && startMonth >= 1 && startMonth <= 12 ? startMonth : 1;
+	const month = d.getMonth() + 1;
+	const offset = (month - start + 12) % 12;
+	const year = start === 1 || month < start ? d.getFullYear() : d.getFullYear() + 1;
+	return { year, quarter: Math.floor(offset / 3) + 1 };
+}
+
+function dayOfYear(d: Date): number {
+	const jan1 = new Date(d.getFullYear(), 0, 1);
+	const midnight = new Date(d.getFullYear(), d.getMonth(), d.getDate());
+	return Math.round((midnight.getTime() - jan1.getTime()) / 86_400_000) + 1;
+}
+
+/**
+ * Format a date with strftime-style directives: %Y %y %m %d %H %M %S %j,
+ * ISO week dates %G %V %u, and %% for a literal percent sign. Unknown
+ * directives are left untouched.
+ */
+export function formatDate(d: Date, format: string): string {
+	const iso = isoWeek(d);
+	return format.replace(/%([A-Za-z%])/g, (match, directive: string) => {
+		switch (directive) {
+			case 'Y':
+				return String(d.getFullYear());
+			case 'y':
+				return pad2(d.getFullYear() % 100);
+			case 'm':
+				return pad2(d.getMonth() + 1);
+			case 'd':
+				return pad2(d.getDate());
+			case 'H':
+				return pad2(d.getHours());
+			case 'M':
+				return pad2(d.getMinutes());
+			case 'S':
+				return pad2(d.getSeconds());
+			case 'j':
+				return pad(dayOfYear(d), 3);
+			case 'G':
+				return String(iso.year);
+			case 'V':
+				return pad2(iso.week);
+			case 'u':
+				return String(iso.weekday);
+			case '%':
+				return '%';
+			default:
+				return match;
+		}
+	});
 }
 
 /**
@@ -80,12 +256,31 @@ export interface TemplateContext {
  * - <hour>       → 14
  * - <minute>     → 35
  * - <second>     → 42
+ * - <week>       → 51 (ISO-8601 week number)
+ * - <fiscal_year>    → 2025 (named after the calendar year it ends in)
+ * - <fiscal_quarter> → Q3
+ * - <date:FORMAT>    → strftime-style date, e.g. <date:%G-W%V> → 2024-W51
+ * - <vendor>     → Acme Supplies (from the document's text, like the next five)
+ * - <amount>     → 1234.50
+ * - <currency>   → USD
+ * - <doc_date>   → 2024-03-05 (the date printed on the document, else the file's)
+ * - <doc_year>   → 2024
+ * - <doc_month>  → 03
+ * - <artist>     → Björk (from the file's audio tags, like the next five)
+ * - <album_artist> → Björk (the album's artist, else the track's)
+ * - <album>      → Debut
+ * - <title>      → Human Behaviour (else the original name)
+ * - <track>      → 01
+ * - <disc>       → 1
+ * - <window_title> → Xcode — MainView.swift (front window when the file appeared, macOS)
+ * - <match:N>    → Group N of a regex rule's pattern (<match:0> is the whole match)
+ * - <match:name> → Named group of a regex rule's pattern
  * - <upper:var>  → UPPERCASE version of variable
  * - <lower:var>  → lowercase version of variable
  * - <slug:var>   → kebab-case version of variable
  */
 export function applyTemplate(template: string, ctx: TemplateContext): string {
-	const d = ctx.birthtime;
+	const d = inTimeZone(ctx.birthtime, ctx.timeZone);
 	const year = String(d.getFullYear());
 	const month = pad2(d.getMonth() + 1);
 	const day = pad2(d.getDate());
@@ -99,6 +294,10 @@ export function applyTemplate(template: string, ctx: TemplateContext): string {
 	const ext = (ctx.ext.startsWith('.') ? ctx.ext : `.${ctx.ext}`).toLowerCase();
 	const prefix = sanitizePrefix(ctx.prefix || 'File');
 	const counter = ctx.counter ?? 1;
+	const fiscal = fiscalPeriod(d, ctx.fiscalYearStartMonth);
+	const docDate = ctx.document?.date ?? d;
+	const docYear = String(docDate.getFullYear());
+	const docMonth = pad2(docDate.getMonth() + 1);
 
 	// Simple variable map
 	const vars: Record<string, string> = {
@@ -114,17 +313,39 @@ export function applyTemplate(template: string, ctx: TemplateContext): string {
 		hour,
 		minute,
 		second,
+		week: pad2(isoWeek(d).week),
+		fiscal_year: String(fiscal.year),
+		fiscal_quarter: `Q${fiscal.quarter}`,
 		counter: pad(counter, 3),
+		vendor: segmentSafe(ctx.document?.vendor),
+		amount: ctx.document?.amount ?? MISSING,
+		currency: ctx.document?.currency ?? MISSING,
+		doc_date: `${docYear}-${docMonth}-${pad2(docDate.getDate())}`,
+		doc_year: docYear,
+		doc_month: docMonth,
+		artist: segmentSafe(ctx.audio?.artist ?? ctx.audio?.albumArtist),
+		album_artist: segmentSafe(ctx.audio?.albumArtist ?? ctx.audio?.artist),
+		album: segmentSafe(ctx.audio?.album),
+		title: segmentSafe(ctx.audio?.title ?? original),
+		track: ctx.audio?.track ? pad2(ctx.audio.track) : MISSING,
+		disc: ctx.audio?.disc ? String(ctx.audio.disc) : MISSING,
+		window_title: segmentSafe(ctx.windowTitle),
 	};
 
 	// Process template
 	let result = template;
 
+	// Handle <date:FORMAT> with strftime-style directives
+	result = result.replace(/<date:([^<>]+)>/g, (_, format) => formatDate(d, format));
+
 	// Handle <counter:N> with custom padding
 	result = result.replace(/<counter:(\d+)>/g, (_, digits) => {
 		return pad(counter, Number.parseInt(digits, 10));
 	});
 
+	// Handle <match:N> and <match:name> with the regex rule's groups
+	result = result.replace(/<match:(\w+)>/g, (_, group) => segmentSafe(ctx.matches?.[group]));
+
 	// Handle transform modifiers: <upper:var>, <lower:var>, <slug:var>
 	result = result.replace(/<(upper|lower|slug):(\w+)>/g, (_, modifier, varName) => {
 		const value = vars[varName] ?? '';
@@ -145,6 +366,15 @@ export function applyTemplate(template: string, ctx: TemplateContext): string {
 		return vars[varName] ?? match;
 	});
 
+	// Drop missing fields with the separator after them, then any left dangling or empty
+	if (result.includes(MISSING)) {
+		result = result
+			.replace(/([_\- ]*)\0+[_\- ]*/g, '$1')
+			.replace(/(^|\/)[_\- ]+|[_\- ]+(?=$|\/)/g, '$1')
+			.replace(/\/{2,}/g, '/')
+			.replace(/^\/|\/$/g, '');
+	}
+
 	return result;
 }
 
@@ -152,6 +382,7 @@ export function applyTemplate(template: string, ctx: TemplateContext): string {
  * Convert a string to kebab-case slug.
  */
 function toSlug(s: string): string {
+	if (s === MISSING) return s;
 	return s
 		.trim()
 		.toLowerCase()
@@ -166,12 +397,94 @@ function toSlug(s: string): string {
  */
 export function buildNameFromTemplate(template: string, ctx: TemplateContext): string {
 	const name = applyTemplate(template, ctx);
+	const ext = (ctx.ext.startsWith('.') ? ctx.ext : `.${ctx.ext}`).toLowerCase();
 	// If template explicitly uses <ext>, don't auto-append (it already has the extension)
 	if (template.includes('<ext>')) {
-		return name;
+		if (!name.endsWith(ext)) return finishName(name, ctx);
+		return `${finishName(name.slice(0, -ext.length), ctx)}${ext}`;
+	}
+	return `${finishName(name, ctx)}${ext}`;
+}
+
+/**
+ * The rule's case style and date prefix applied to a name without its extension. The case
+ * applies to every folder in the name; the date goes in front of the file's own name only.
+ */
+function finishName(name: string, ctx: TemplateContext): string {
+	if (!ctx.caseStyle && !ctx.datePrefix) return name;
+	const style = ctx.caseStyle;
+	const segments = name
+		.split('/')
+		.map((segment) => (style ? applyCaseStyle(segment, style) : segment));
+	if (ctx.datePrefix) {
+		const d = inTimeZone(ctx.birthtime, ctx.timeZone);
+		const date = `${d.getFullYear()}-${pad2(d.getMonth() + 1)}-${pad2(d.getDate())}`;
+		const last = segments.pop() ?? '';
+		const separator = style === 'kebab' ? '-' : '_';
+		segments.push(!last ? date : last.startsWith(date) ? last : `${date}${separator}${last}`);
+	}
+	return segments.join('/');
+}
+
+/**
+ * `name` in the given case. Kebab and snake case keep only letters and digits between the
+ * separators; a name with none of either is left as it was.
+ */
+export function applyCaseStyle(name: string, style: CaseStyle): string {
+	switch (style) {
+		case 'lower':
+			return name.toLowerCase();
+		case 'upper':
+			return name.toUpperCase();
+		case 'title':
+			return name
+				.toLowerCase()
+				.replace(
+					/(^|[^\p{L}\p{N}'])(\p{L})/gu,
+					(_, before: string, letter: string) => before + letter.toUpperCase(),
+				);
+		case 'kebab':
+		case 'snake': {
+			const words = name.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter(Boolean);
+			return words.length ? words.join(style === 'kebab' ? '-' : '_') : name;
+		}
+		default:
+			return name;
+	}
+}
+
+/**
+ * The groups `pattern` captures from `name`, by number and by name, for <match:…>. Empty when
+ * the pattern doesn't compile or doesn't match.
+ */
+export function regexMatches(pattern: string, name: string): Record<string, string> {
+	let match: RegExpExecArray | null;
+	try {
+		match = new RegExp(pattern).exec(name);
+	} catch {
+		return {};
+	}
+	if (!match) return {};
+	const groups: Record<string, string> = {};
+	match.forEach((value, i) => {
+		if (value !== undefined) groups[String(i)] = value;
+	});
+	for (const [key, value] of Object.entries(match.groups ?? {})) {
+		if (value !== undefined) groups[key] = value;
 	}
-	const ext = ctx.ext.startsWith('.') ? ctx.ext : `.${ctx.ext}`;
-	return `${name}${ext.toLowerCase()}`;
+	return groups;
+}
+
+/** `base`, or the first `_N` variant of it not in `taken`, which is then added to `taken`. */
+export function claimName(taken: Set<string>, base: string): string {
+	const ext = path.extname(base);
+	const name = base.slice(0, base.length - ext.length);
+	let candidate = base;
+	for (let n = 2; taken.has(candidate); n++) {
+		candidate = `${name}_${n}${ext}`;
+	}
+	taken.add(candidate);
+	return candidate;
 }
 
 /**
@@ -214,6 +527,27 @@ export const DEFAULT_PROFILES: IProfile[] = [
 		prefix: 'Recording',
 		priority: 2,
 	},
+	{
+		// Off by default, like music: it moves files into subfolders
+		id: 'invoices',
+		name: 'Invoices & Receipts',
+		enabled: false,
+		pattern: '{invoice,receipt,bill,statement}*.pdf',
+		isRegex: false,
+		template: '<doc_year>-<doc_month>/<doc_date>_<vendor>_<currency><amount>',
+		prefix: 'Invoice',
+		priority: 3,
+	},
+	{
+		id: 'music',
+		name: 'Music Library',
+		enabled: false,
+		pattern: '*.{mp3,m4a,flac,ogg,opus}',
+		isRegex: false,
+		template: '<album_artist>/<album>/<track> - <title>',
+		prefix: 'Track',
+		priority: 4,
+	},
 ];
 
 /**
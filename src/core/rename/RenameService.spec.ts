@@ -2,6 +2,7 @@ import path from 'node:path';
 import { mkdtemp, writeFile } from 'node:fs/promises';
 import { tmpdir } from 'node:os';
 import { describe, expect, test } from 'vitest';
+import type { IProfile } from '../../types/index.js';
 import { RenameService } from './RenameService.js';
 import { buildName } from './NameTemplate.js';
 
@@ -62,4 +63,144 @@ describe('RenameService', () => {
 		expect(next).toBe(suffix(baseName, 2));
 		renamer.release(dir, next);
 	});
+
+	test('runs names past the name hook', async () => {
+		const renamer = new RenameService();
+		const dir = await mkdtemp(path.join(tmpdir(), 'namefix-renamer-'));
+		const profile: IProfile = {
+			id: 'shots',
+			name: 'Shots',
+			enabled: true,
+			pattern: '*',
+			template: '<prefix>_<original>',
+			prefix: 'Shot',
+			priority: 1,
+		};
+		const stat = { birthtime: new Date(2025, 8, 30, 18, 10, 10), ext: '.png' };
+		const src = path.join(dir, 'a.png');
+
+		const prepare = async () => ({ skip: 'not asked' });
+		renamer.setNameHook({
+			prepare,
+			review: async (request) => {
+				expect(request).toEqual({
+					directory: dir,
+					file: 'a.png',
+					rule: 'shots',
+					size: 0,
+					target: 'Shot_a.png',
+				});
+				return { target: 'hooked.png' };
+			},
+		});
+		const hooked = await renamer.targetForProfile(src, stat, profile);
+		expect(hooked.filename).toBe('hooked.png');
+		renamer.release(dir, hooked.filename);
+
+		renamer.setNameHook({ prepare, review: async () => ({ skip: 'vetoed' }) });
+		expect(await renamer.targetForProfile(src, stat, profile)).toEqual({
+			filename: 'a.png',
+			profile,
+			skipped: 'vetoed',
+		});
+
+		renamer.setNameHook({
+			prepare,
+			review: async () => {
+				throw new Error('client went away');
+			},
+		});
+		expect(await renamer.nameForProfile(src, stat, profile)).toEqual({ target: 'Shot_a.png' });
+	});
+
+	test('skips names from the name hook that leave the folder', async () => {
+		const renamer = new RenameService();
+		const dir = await mkdtemp(path.join(tmpdir(), 'namefix-renamer-'));
+		const profile: IProfile = {
+			id: 'shots',
+			name: 'Shots',
+			enabled: true,
+			pattern: '*',
+			template: '<prefix>_<original>',
+			prefix: 'Shot',
+			priority: 1,
+		};
+		const stat = { birthtime: new Date(2025, 8, 30, 18, 10, 10), ext: '.png' };
+		const src = path.join(dir, 'a.png');
+
+		for (const target of ['../x', '..', '.', 'sub/x.png', '/tmp/x.png', 'a\\x.png']) {
+			renamer.setNameHook({
+				prepare: async () => ({ skip: 'not asked' }),
+				review: async () => ({ target }),
+			});
+			expect(await renamer.nameForProfile(src, stat, profile)).toEqual({
+				skip: `name hook answered ${JSON.stringify(target)}, which isn't a file name`,
+			});
+		}
+	});
+
+	test('asks the name hook about rules with expressions', async () => {
+		const renamer = new RenameService();
+		const dir = await mkdtemp(path.join(tmpdir(), 'namefix-renamer-'));
+		const src = path.join(dir, 'a.png');
+		await writeFile(src, 'four');
+		const profile: IProfile = {
+			id: 'big',
+			name: 'Big',
+			enabled: true,
+			pattern: '*',
+			template: '<prefix>_<expr:size * 2>',
+			prefix: 'Shot',
+			priority: 1,
+			condition: 'size > 3',
+		};
+		const stat = { birthtime: new Date(2025, 8, 30, 18, 10, 10), ext: '.png' };
+		expect(await renamer.nameForProfile(src, stat, profile)).toEqual({
+			skip: 'rule uses expressions, which need the menu bar app',
+		});
+
+		renamer.setNameHook({
+			prepare: async (request) => {
+				expect(request).toEqual({
+					directory: dir,
+					file: 'a.png',
+					rule: 'big',
+					size: 4,
+					condition: 'size > 3',
+					template: '<prefix>_<expr:size * 2>',
+				});
+				return { template: '<prefix>_8' };
+			},
+			review: async ({ target }) => ({ target }),
+		});
+		expect(await renamer.nameForProfile(src, stat, profile)).toEqual({ target: 'Shot_8.png' });
+
+		renamer.setNameHook({
+			prepare: async () => ({ skip: "rule's condition is false" }),
+			review: async ({ target }) => ({ target }),
+		});
+		expect(await renamer.nameForProfile(src, stat, profile)).toEqual({
+			skip: "rule's condition is false",
+		});
+	});
+
+	test('names a file handed off to another profile with that profile', async () => {
+		const renamer = new RenameService();
+		const dir = await mkdtemp(path.join(tmpdir(), 'namefix-renamer-'));
+		const src = path.join(dir, 'a.png');
+		const base = { enabled: true, pattern: '*', priority: 1, template: '<prefix>_<original>' };
+		const shots: IProfile = { ...base, id: 'shots', name: 'Shots', prefix: 'Shot' };
+		const docs: IProfile = { ...base, id: 'docs', name: 'Docs', prefix: 'Doc' };
+		const stat = { birthtime: new Date(2025, 8, 30, 18, 10, 10), ext: '.png' };
+		const hook = {
+			prepare: async () => ({ skip: 'not asked' }),
+			review: async () => ({ handoff: 'docs' }),
+		};
+
+		renamer.setNameHook(hook, (id) => [shots, docs].find((p) => p.id === id));
+		expect(await renamer.nameForProfile(src, stat, shots)).toEqual({ target: 'Doc_a.png' });
+
+		renamer.setNameHook(hook);
+		expect(await renamer.nameForProfile(src, stat, shots)).toEqual({ target: 'Shot_a.png' });
+	});
 });
@@ -6,18 +6,84 @@ import {
 	buildNameFromTemplate,
 	getExt,
 	DEFAULT_TEMPLATE,
+	regexMatches,
 	type TemplateContext,
+	usesAudioTokens,
+	usesDocumentTokens,
+	usesExpressions,
 } from './NameTemplate.js';
+import { AUDIO_EXTENSIONS, readAudioTags } from '../audio/AudioTags.js';
+import { parseInvoiceFields } from '../pdf/InvoiceFields.js';
+import { extractPdfText } from '../pdf/PdfText.js';
+
+/** What a file says about itself, for the template variables that use it. */
+export type FileTags = Pick<TemplateContext, 'document' | 'audio'>;
 
 export interface RenameTarget {
 	/** The target filename (basename only) */
 	filename: string;
 	/** The profile that was used for renaming, or null if legacy mode */
 	profile: IProfile | null;
+	/** The name the template produced, when another file already held it and a suffix was added */
+	collidedWith?: string;
+	/** Why the name hook left the file as it was; `filename` is then its current name */
+	skipped?: string;
+}
+
+/** A file and the name a rule gave it, as the name hook sees them. */
+export interface NameHookRequest {
+	directory: string;
+	file: string;
+	rule: string;
+	/** The file's size in bytes */
+	size: number;
+	target: string;
+}
+
+/**
+ * The hook's say on a name: go ahead with `target`, name the file with the `handoff` profile
+ * instead, or leave it alone because of `skip`.
+ */
+export type NameHookResult = { target: string } | { handoff: string } | { skip: string };
+
+/** The name a file gets, or why it keeps its own. */
+export type NamedFile = { target: string } | { skip: string };
+
+/** A rule about to name a file, whose condition or template the hook evaluates. */
+export interface RuleHookRequest {
+	directory: string;
+	file: string;
+	rule: string;
+	/** The file's size in bytes */
+	size: number;
+	condition?: string;
+	template: string;
+}
+
+/** The rule's template with its <expr:…> segments filled in, or why it leaves the file alone. */
+export type RuleHookResult = { template: string } | { skip: string };
+
+/**
+ * Lets a client of the service take part in naming files; the menu bar app evaluates rule
+ * expressions and runs its pipelines and plugins here.
+ */
+export interface NameHook {
+	/** Asked before a rule with a condition or <expr:…> segments is applied or previewed */
+	prepare(request: RuleHookRequest): Promise<RuleHookResult>;
+	/** Asked about every name a rule produces, before it is applied or previewed */
+	review(request: NameHookRequest): Promise<NameHookResult>;
 }
 
 export class RenameService {
 	private readonly inFlightTargets = new Set<string>();
+	private nameHook: NameHook | null = null;
+	private profileById: (id: string) => IProfile | undefined = () => undefined;
+
+	/** `profiles` finds the profile a hand-off from the hook names. */
+	setNameHook(hook: NameHook | null, profiles?: (id: string) => IProfile | undefined): void {
+		this.nameHook = hook;
+		this.profileById = profiles ?? (() => undefined);
+	}
 
 	/**
 	 * Check if a file needs renaming based on a prefix pattern.
@@ -69,10 +135,34 @@ export class RenameService {
 	 */
 	async targetForProfile(
 		srcPath: string,
-		stat: { birthtime: Date; ext?: string },
+		stat: { birthtime: Date; ext?: string; windowTitle?: string },
 		profile: IProfile,
 	): Promise<RenameTarget> {
 		const dir = path.dirname(srcPath);
+		const tags = await this.tagsFor(srcPath, profile);
+		const named = await this.nameForProfile(srcPath, { ...stat, ...tags }, profile);
+		if ('skip' in named) return { filename: path.basename(srcPath), profile, skipped: named.skip };
+		const baseName = named.target;
+		// The file already has the name; reserving it would only find it taken
+		if (baseName === path.basename(srcPath)) return { filename: baseName, profile };
+		const reserved = await this.reserveTarget(dir, baseName);
+
+		return {
+			filename: reserved,
+			profile,
+			...(reserved !== baseName ? { collidedWith: baseName } : {}),
+		};
+	}
+
+	/**
+	 * The name a profile's template produces for a file, before collision handling. Nothing is
+	 * reserved, so this is safe for previews.
+	 */
+	proposeForProfile(
+		srcPath: string,
+		stat: { birthtime: Date; ext?: string; windowTitle?: string } & FileTags,
+		profile: IProfile,
+	): string {
 		const ext = (stat.ext || getExt(srcPath) || '.png').replace(/^\.+/, '.');
 		const template = profile.template || DEFAULT_TEMPLATE;
 
@@ -81,16 +171,115 @@ export class RenameService {
 			birthtime: stat.birthtime ?? new Date(),
 			ext,
 			prefix: profile.prefix || 'File',
+			fiscalYearStartMonth: profile.fiscalYearStartMonth,
+			timeZone: profile.timeZone,
+			document: stat.document,
+			audio: stat.audio,
+			windowTitle: stat.windowTitle,
+			matches: profile.isRegex ? regexMatches(profile.pattern, path.basename(srcPath)) : undefined,
+			caseStyle: profile.caseStyle,
+			datePrefix: profile.datePrefix,
 		};
 
-		// Build the base name from template
-		const baseName = buildNameFromTemplate(template, ctx);
-		const reserved = await this.reserveTarget(dir, baseName);
+		return buildNameFromTemplate(template, ctx);
+	}
 
-		return {
-			filename: reserved,
-			profile,
-		};
+	/**
+	 * The name `profile` gives the file at `srcPath`, run past the name hook when one is set. A
+	 * hook that fails or answers with no name leaves the rule's name as it is. Nothing is
+	 * reserved, so this is safe for previews.
+	 */
+	async nameForProfile(
+		srcPath: string,
+		stat: { birthtime: Date; ext?: string; windowTitle?: string } & FileTags,
+		profile: IProfile,
+	): Promise<NamedFile> {
+		const prepared = await this.prepareRule(srcPath, profile);
+		if ('skip' in prepared) return prepared;
+		const { template } = prepared;
+		const target = this.proposeForProfile(srcPath, stat, { ...profile, template });
+		const file = path.basename(srcPath);
+		if (!this.nameHook || target === file) return { target };
+		let result: NameHookResult;
+		try {
+			const directory = path.dirname(srcPath);
+			const size = await sizeOf(srcPath);
+			result = await this.nameHook.review({ directory, file, rule: profile.id, size, target });
+		} catch {
+			// The hook's client went away or didn't answer in time; the rule's name stands
+			return { target };
+		}
+		if ('skip' in result) return { skip: result.skip || 'skipped by name hook' };
+		if ('handoff' in result) return await this.handOffName(srcPath, stat, profile, result.handoff);
+		const hooked = result.target || target;
+		if (!isBareName(path.dirname(srcPath), hooked)) {
+			return { skip: `name hook answered ${JSON.stringify(hooked)}, which isn't a file name` };
+		}
+		return { target: hooked };
+	}
+
+	/**
+	 * The name the profile the hook handed the file to gives it, or the rule's own when that
+	 * profile is gone. It isn't reviewed again, so hand-offs can't go round in circles.
+	 */
+	private async handOffName(
+		srcPath: string,
+		stat: { birthtime: Date; ext?: string; windowTitle?: string } & FileTags,
+		from: IProfile,
+		to: string,
+	): Promise<NamedFile> {
+		const profile = this.profileById(to) ?? from;
+		const prepared = await this.prepareRule(srcPath, profile);
+		if ('skip' in prepared) return prepared;
+		const tags = profile === from ? {} : await this.tagsFor(srcPath, profile);
+		const named = { ...profile, template: prepared.template };
+		return { target: this.proposeForProfile(srcPath, { ...stat, ...tags }, named) };
+	}
+
+	/**
+	 * The rule's template, with its condition checked and its <expr:…> segments filled in by the
+	 * name hook when it has them. Without a hook to ask, such a rule leaves files alone rather
+	 * than name them after its expressions' text.
+	 */
+	private async prepareRule(srcPath: string, profile: IProfile): Promise<RuleHookResult> {
+		const template = profile.template || DEFAULT_TEMPLATE;
+		const condition = profile.condition?.trim();
+		if (!condition && !usesExpressions(template)) return { template };
+		if (!this.nameHook) return { skip: 'rule uses expressions, which need the menu bar app' };
+		try {
+			const size = await sizeOf(srcPath);
+			return await this.nameHook.prepare({
+				directory: path.dirname(srcPath),
+				file: path.basename(srcPath),
+				rule: profile.id,
+				size,
+				...(condition ? { condition } : {}),
+				template,
+			});
+		} catch (e: unknown) {
+			const message = e instanceof Error ? e.message : String(e);
+			return { skip: `rule's expressions could not be evaluated: ${message}` };
+		}
+	}
+
+	/**
+	 * Invoice fields from a PDF's text and tags from an audio file, read only when the profile's
+	 * template uses them. Files that can't be read have none, so those variables come out empty.
+	 */
+	async tagsFor(srcPath: string, profile: IProfile): Promise<FileTags> {
+		const ext = getExt(srcPath).toLowerCase();
+		const tags: FileTags = {};
+		try {
+			if (ext === '.pdf' && usesDocumentTokens(profile.template)) {
+				tags.document = parseInvoiceFields(await extractPdfText(srcPath));
+			}
+			if (AUDIO_EXTENSIONS.has(ext) && usesAudioTokens(profile.template)) {
+				tags.audio = await readAudioTags(srcPath);
+			}
+		} catch {
+			// Unreadable or malformed; name the file without its tags
+		}
+		return tags;
 	}
 
 	release(dir: string, target: string): void {
@@ -126,10 +315,28 @@ function splitBase(filename: string): { name: string; ext: string } {
 	return { name, ext };
 }
 
+/**
+ * Whether `name` is a file name that stays in `dir`: a bare basename, kept inside the folder by
+ * the same test applyPlan uses.
+ */
+function isBareName(dir: string, name: string): boolean {
+	const relative = path.relative(dir, path.join(dir, name));
+	const outside = !relative || relative.split(path.sep)[0] === '..' || path.isAbsolute(relative);
+	return !outside && name === path.basename(name) && !/[\\/\0]/.test(name);
+}
+
 function escapeRegExp(s: string): string {
 	return s.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
 }
 
+async function sizeOf(p: string): Promise<number> {
+	try {
+		return (await fs.stat(p)).size;
+	} catch {
+		return 0;
+	}
+}
+
 async function exists(p: string): Promise<boolean> {
 	try {
 		await fs.access(p);
@@ -9,8 +9,17 @@ export type WatchEvent = {
 	birthtimeMs: number;
 	mtimeMs: number;
 	size: number;
+	/** Executable of the process that created the file, when the backend reports it. */
+	process?: string;
 };
 
+/**
+ * How directories are watched: FSEvents via `fs.watch`, Endpoint Security on macOS, the NTFS
+ * change journal on Windows, or polling. The last two are also picked automatically, for very
+ * large directories and network shares respectively.
+ */
+export type WatchBackend = 'fsevents' | 'endpoint-security' | 'usn-journal' | 'polling';
+
 /**
  * A rename profile defines how files matching a pattern should be renamed.
  * Multiple profiles can be configured and are matched in priority order.
@@ -34,8 +43,20 @@ export interface IProfile {
 	priority: number;
 	/** Action to perform on matched files: rename (default), convert, or both */
 	action?: 'rename' | 'convert' | 'rename+convert';
+	/** First month of the fiscal year for <fiscal_year>/<fiscal_quarter> (1-12, default 1) */
+	fiscalYearStartMonth?: number;
+	/** IANA time zone for date variables, e.g. "UTC" (default: system local time) */
+	timeZone?: string;
+	/** Case applied to the new name, extension aside (default: as the template writes it) */
+	caseStyle?: CaseStyle;
+	/** Put the file's creation date (YYYY-MM-DD) in front of the new name, unless already there */
+	datePrefix?: boolean;
+	/** Expression the file must satisfy to be renamed, e.g. `size > 1000000`; see NameHook */
+	condition?: string;
 }
 
+export type CaseStyle = 'lower' | 'upper' | 'title' | 'kebab' | 'snake';
+
 export interface IConfig {
 	watchDir: string;
 	watchDirs: string[];
@@ -48,6 +69,61 @@ export interface IConfig {
 	dryRun: boolean;
 	theme: string;
 	launchOnLogin: boolean;
+	/** Directories whose events are processed ahead of other queued work. */
+	priorityDirs?: string[];
+	/** Directories listed ahead of the others, keeping their relative `watchDirs` order. */
+	pinnedDirs?: string[];
+	/**
+	 * Watch and record what would happen to each file, including skips and collisions, in the audit
+	 * log without renaming anything or raising prompts. Stronger than `dryRun`.
+	 */
+	observerMode?: boolean;
+	/**
+	 * Write hashed stand-ins instead of file paths to logs (on unless turned off). The rename
+	 * journal still records full paths, since undo needs them.
+	 */
+	logPrivacy?: boolean;
+	/**
+	 * Keeps the rename journal, audit log and logs here instead of the platform's state and logs
+	 * directories. Config stays in the config directory. Change it with `setDataDir`, which moves
+	 * the files.
+	 */
+	dataDir?: string;
+	/**
+	 * Folder synced by iCloud Drive, Dropbox or similar where config and rules are shared with the
+	 * user's other machines. History and this machine's own settings stay local.
+	 */
+	syncDir?: string;
+	/**
+	 * Let other Namefix installs on the local network find this machine over multicast DNS, pair
+	 * with it using a short code, and, once paired, read its status.
+	 */
+	lanPairing?: boolean;
+	/** Directories that only preview renames. The global `dryRun` flag overrides this list. */
+	dryRunDirs?: string[];
+	/** Limits applied to the rename journal by the background pruning task. Empty keeps everything. */
+	historyRetention?: RetentionPolicy;
+	/**
+	 * Check for other processes holding a file open (via `lsof`) before renaming it, and wait for
+	 * them to let go. Windows always defers locked files, since the rename itself fails there.
+	 */
+	checkOpenFiles?: boolean;
+	/** Watching backend for this machine. Endpoint Security needs root and Full Disk Access. */
+	watchBackend?: WatchBackend;
+	/** How often the polling backend lists a directory, in milliseconds (minimum 1000). */
+	pollIntervalMs?: number;
+	/** Per-directory rules on which apps' files may be renamed, keyed by resolved directory. */
+	originRules?: Record<string, OriginRule>;
+	/** User-defined group (e.g. `Work`, `Camera`) of each directory, keyed by resolved directory. */
+	directoryGroups?: Record<string, string>;
+	/** Named sessions, oldest first. The last one is active until it has an `endedAt`. */
+	sessions?: RenameSession[];
+	/** Imports run when a camera card mounts. */
+	importPresets?: ImportPreset[];
+	/** Where renamed files in hot folders are handed off to, keyed by resolved directory. */
+	hotFolders?: Record<string, HotFolder>;
+	/** Per-directory watching options, keyed by directory; unlisted directories use the defaults. */
+	directoryOptions?: Record<string, DirectoryOptions>;
 	/** Array of rename profiles. If empty, falls back to legacy prefix/include/exclude. */
 	profiles: IProfile[];
 }
@@ -63,11 +139,280 @@ export interface ILogger {
 	warn(msg: string, meta?: Record<string, unknown>): void;
 	error(msg: string | Error, meta?: Record<string, unknown>): void;
 	debug?(msg: string, meta?: Record<string, unknown>): void;
+	/** Log full paths, or hashed stand-ins for them when `enabled`. */
+	setPrivacy?(enabled: boolean): void;
+	/** Reopen the log file after the logs directory changed. */
+	reopen?(): Promise<void>;
 }
 
+/** The journal's latest backup, taken at startup whenever the journal was healthy. */
+export type HistoryBackup = {
+	savedAt: number;
+	entries: number;
+};
+
+/**
+ * Whether the rename journal can be read and written. While `degraded`, renames are journaled in
+ * memory only, so undo still works until the service stops.
+ */
+export type HistoryHealth = {
+	state: 'ok' | 'degraded';
+	/** `corrupt`: lines that aren't entries; `disk-full`: no room; `io-error`: anything else. */
+	reason?: 'corrupt' | 'disk-full' | 'io-error';
+	detail?: string;
+	/** Lines of a corrupt journal that were skipped. */
+	badLines?: number;
+	backup?: HistoryBackup;
+};
+
+/** `salvage` keeps what could be read; `restore` starts from the latest backup. */
+export type HistoryRepair = 'salvage' | 'restore';
+
+/** Settings sync through a file in a synced folder; see `IConfig.syncDir`. */
+export type SettingsSyncStatus = {
+	/** The synced folder, or null while sync is off. */
+	dir: string | null;
+	/**
+	 * `conflict`: settings changed here and on another machine since they last agreed; `locked`: the
+	 * settings are encrypted with a key this machine doesn't have yet; `error`: the folder or its
+	 * settings file can't be used.
+	 */
+	state: 'off' | 'ok' | 'conflict' | 'locked' | 'error';
+	lastSyncedAt?: number;
+	/** The other machine's side of a conflict. */
+	conflict?: { deviceName: string; updatedAt: number };
+	detail?: string;
+};
+
+/** Which side of a settings sync conflict to keep. */
+export type SyncResolution = 'local' | 'remote';
+
+/** A code shown on this machine for another one to pair with; see `startPairing`. */
+export type PairingCode = {
+	code: string;
+	expiresAt: number;
+};
+
+/** Another machine this one has paired with and shares the settings sync key with. */
+export type PairedPeer = {
+	id: string;
+	name: string;
+	pairedAt: number;
+};
+
+/** A Namefix install found on the local network; see `findPeers`. */
+export type NearbyPeer = {
+	id: string;
+	name: string;
+	address: string;
+	port: number;
+	paired: boolean;
+};
+
+/** Pairing with other machines on the local network; see `IConfig.lanPairing`. */
+export type PairingStatus = {
+	/** Whether this machine can be found and paired with. */
+	enabled: boolean;
+	/** The code being shown, until it is used or expires. */
+	code?: PairingCode;
+	peers: PairedPeer[];
+};
+
+/** What a paired machine reports about itself. */
+export type PeerStatus = {
+	id: string;
+	name: string;
+	running: boolean;
+	directories: string[];
+	dryRun: boolean;
+	observerMode: boolean;
+};
+
+/** A recorded rename. `rule` is the profile id, or `legacy` for the prefix-based fallback. */
+export type HistoryEntry = {
+	/** Unique id of the rename, kept in exports so a merge can tell what it already has. */
+	id?: string;
+	from: string;
+	to: string;
+	ts: number;
+	rule?: string;
+	/** Why the rename had to wait, e.g. `open in Preview (pid 412)`. */
+	deferred?: string;
+	/** Size of the file when it was renamed. Missing from entries recorded before sizes were kept. */
+	bytes?: number;
+	/** Id of the session that was active when the file was renamed. */
+	session?: string;
+};
+
+/** What merging another machine's exported history added. */
+export type HistoryMerge = {
+	imported: number;
+	/** Entries already in this machine's history. */
+	duplicates: number;
+	/** Lines of the export that weren't entries. */
+	skipped: number;
+};
+
+/** What the journal records about a rename besides its paths and time. */
+export type JournalDetails = Pick<HistoryEntry, 'rule' | 'deferred' | 'bytes' | 'session'>;
+
+/** A named run, such as a camera import, whose renames can be reviewed and undone together. */
+export type RenameSession = {
+	id: string;
+	name: string;
+	startedAt: number;
+	/** Unset while the session is active. */
+	endedAt?: number;
+};
+
+/** A session with totals of the renames recorded for it. */
+export type SessionSummary = RenameSession & { filesRenamed: number; bytesProcessed: number };
+
+export type SessionUndoResult = { undone: number; failed: number };
+
+/** Renames recorded in the journal for one watched directory (including its subfolders). */
+export type DirectoryUsage = {
+	directory: string;
+	filesRenamed: number;
+	bytesProcessed: number;
+};
+
+export type HistoryQuery = {
+	/** Matched against the old and new file names (case-insensitive substring unless `regex`). */
+	text?: string;
+	regex?: boolean;
+	since?: number;
+	until?: number;
+	/** Only renames of files in this directory or below it. */
+	directory?: string;
+	rule?: string;
+	/** Only renames made during this session. */
+	session?: string;
+	limit?: number;
+	/** Skip this many matches first, to page through history. */
+	offset?: number;
+};
+
+/**
+ * Which apps' files a directory renames, by the origin recorded on each file (see
+ * `FileOriginProbe`). App names compare case-insensitively; the denylist wins.
+ */
+export type OriginRule = {
+	/** When non-empty, only files from these apps are renamed; unknown origins are skipped. */
+	allow?: string[];
+	deny?: string[];
+};
+
+/**
+ * What happens to a file once a hot folder has renamed it: it is moved into `destination`, then
+ * `command` is run on it (e.g. to print or upload it). At least one of the two is set.
+ */
+export type HotFolder = {
+	destination?: string;
+	/** Program and arguments, run without a shell; `{file}` stands for the file's path. */
+	command?: string[];
+};
+
+/**
+ * How one directory is watched. Extensions are lowercase without the dot; when `includeExtensions`
+ * is non-empty only those are renamed, and `excludeExtensions` wins over it.
+ */
+export type DirectoryOptions = {
+	/** Watch subfolders too; files are renamed where they are. */
+	recursive?: boolean;
+	includeExtensions?: string[];
+	excludeExtensions?: string[];
+};
+
+/** Every limit is optional; an entry is pruned if it violates any of them. Oldest go first. */
+export type RetentionPolicy = {
+	maxAgeDays?: number;
+	maxEntries?: number;
+	/** Upper bound on the journal file size in bytes. */
+	maxBytes?: number;
+};
+
+export type PruneResult = {
+	removed: number;
+	/** Entries left, i.e. how many renames can still be undone. */
+	remaining: number;
+};
+
+/** What `recoverFromSleep` did after the machine woke. */
+export type WakeRecovery = {
+	/** Watchers restarted. */
+	restarted: number;
+	/** Files that arrived while the watchers were stale, queued now. */
+	caughtUp: number;
+};
+
+/** What the active rules would do to one file if it arrived now. */
+export type RenamePlanEntry = {
+	file: string;
+	/** Proposed name, or null when no rule would rename the file. */
+	target: string | null;
+	/** Profile id (`legacy` for prefix-based renames) when the file would be renamed. */
+	rule?: string;
+	/** File creation time (epoch ms). */
+	createdAt: number;
+	/** Set when the proposed name was taken, so `target` has an `_N` suffix. */
+	conflict?: boolean;
+};
+
+export type RenamePlan = {
+	directory: string;
+	entries: RenamePlanEntry[];
+};
+
+/** Outcome of renaming a folder once with `runOnce` or `applyPlan`. */
+export type RunOnceResult = {
+	directory: string;
+	renamed: number;
+	failed: number;
+	/** The last failure, as `file: reason`. */
+	error?: string;
+};
+
+/** Moves a camera card's photos into a library folder, renamed, when the card mounts. */
+export type ImportPreset = {
+	id: string;
+	name: string;
+	enabled: boolean;
+	/** Rename template, with the same variables as profiles. */
+	template: string;
+	prefix: string;
+	/** Library folder the files are moved into. */
+	destination: string;
+	/** Eject the card once every file was imported. */
+	eject: boolean;
+};
+
+/** Progress of one preset importing one card, reported after each file. */
+export type ImportProgress = {
+	preset: string;
+	/** Mount point of the card. */
+	card: string;
+	total: number;
+	imported: number;
+	failed: number;
+	done: boolean;
+	/** Set once done when the card should be ejected. */
+	eject: boolean;
+	/** The most recent failure. */
+	error?: string;
+};
+
 export interface IJournalStore extends IDispose {
-	record(from: string, to: string): Promise<void>;
+	record(from: string, to: string, details?: JournalDetails): Promise<void>;
 	undo(): Promise<{ ok: boolean; reason?: string }>;
+	undoEntry(id: string): Promise<{ ok: boolean; reason?: string }>;
+	redo(): Promise<{ ok: boolean; reason?: string }>;
+	undoSession(session: string): Promise<SessionUndoResult>;
+	search(query: HistoryQuery): Promise<HistoryEntry[]>;
+	usage(directories: string[], since: number): Promise<DirectoryUsage[]>;
+	prune(policy: RetentionPolicy): Promise<PruneResult>;
+	exportTo(file: string): Promise<number>;
+	merge(file: string): Promise<HistoryMerge>;
 }
 
 export interface IRenameService {
@@ -1,15 +1,150 @@
-import type { IConfig } from './index.js';
+import type {
+	HistoryHealth,
+	HotFolder,
+	IConfig,
+	ImportProgress,
+	OriginRule,
+	PairingStatus,
+	RenameSession,
+	SettingsSyncStatus,
+	WatchBackend,
+} from './index.js';
+
+export type DirectoryStats = {
+	directory: string;
+	/** Timestamp (ms) of the most recent file event, or null if none since startup. */
+	lastEventAt: number | null;
+	renamesToday: number;
+	errorCount: number;
+	/** Events from this directory waiting in or being processed by the work queue. */
+	queued: number;
+	skippedToday: number;
+	/** Backend the directory is being watched with, or null while it isn't watched. */
+	watchMode: WatchBackend | null;
+};
+
+/**
+ * Features the attached engine supports. UIs hide or disable controls for anything missing
+ * instead of calling into the engine and failing.
+ */
+export type ServiceCapability =
+	| 'supports_redo'
+	| 'supports_preview'
+	| 'supports_rules'
+	| 'supports_queue'
+	| 'native_engine';
+
+export type SnoozedDirectory = {
+	directory: string;
+	until: number;
+};
+
+export type DirectoryOriginRule = OriginRule & { directory: string };
+
+export type DirectoryHotFolder = HotFolder & { directory: string };
+
+export type DirectoryGroup = { directory: string; group: string };
+
+/** Everything set for one watched directory; see `getDirConfig`. */
+export type DirectoryConfig = {
+	directory: string;
+	recursive: boolean;
+	includeExtensions: string[];
+	excludeExtensions: string[];
+	/** The directory's own dry-run setting, i.e. whether it is in `dryRunDirectories`. */
+	dryRun: boolean;
+};
+
+/** A watched directory other users can also write to, where renames may hit permission errors. */
+export type SharedDirectory = {
+	directory: string;
+	ownerUid: number;
+	/** Owned by a user other than the one Namefix runs for. */
+	ownedByOther: boolean;
+	worldWritable: boolean;
+};
+
+/** A removable or external volume holding watched directories, watched only while mounted. */
+export type WatchedVolume = {
+	/** Mount point, e.g. `/Volumes/Camera Card`. */
+	root: string;
+	name: string;
+	mounted: boolean;
+	/** Watchers were stopped so the volume can be ejected. */
+	released: boolean;
+	directories: string[];
+};
+
+/** Bumped when the status payload changes shape; consumers must ignore unknown fields. */
+export const SERVICE_STATUS_SCHEMA_VERSION = 2;
 
 export type ServiceStatus = {
+	schemaVersion: number;
 	running: boolean;
 	directories: string[];
 	dryRun: boolean;
+	/** Nothing is renamed and outcomes go to the audit log instead of prompting. */
+	observerMode: boolean;
+	/** Logs get hashed stand-ins for file paths; see `IConfig.logPrivacy`. */
+	logPrivacy: boolean;
+	/** Whether rename history is being saved; see `repairHistory`. */
+	history: HistoryHealth;
+	/** Whether an undone rename can be redone; see `redo`. */
+	canRedo: boolean;
+	/** Where the journal, audit log and logs are kept, if moved from the default locations. */
+	dataDir: string | null;
+	/** Whether config and rules are syncing with other machines; see `setSyncDir`. */
+	settingsSync: SettingsSyncStatus;
+	/** Pairing with other machines on the local network; see `setLanPairing`. */
+	pairing: PairingStatus;
 	launchOnLogin: boolean;
+	priorityDirectories: string[];
+	/** Directories pinned to the top of `directories`, which lists them first. */
+	pinnedDirectories: string[];
+	/** Directories in dry-run mode on their own; every directory is when `dryRun` is set. */
+	dryRunDirectories: string[];
+	/** Directories whose events are being ignored until `until` (epoch ms). */
+	snoozedDirectories: SnoozedDirectory[];
+	watchBackend: WatchBackend;
+	/** Directories that only rename files from (or never from) certain apps. */
+	originRules: DirectoryOriginRule[];
+	/** Labels that nest directories under a shared submenu; ungrouped directories are absent. */
+	directoryGroups: DirectoryGroup[];
+	/** Directories that hand renamed files off to a destination or command. */
+	hotFolders: DirectoryHotFolder[];
+	/** One entry per directory in `directories`. */
+	directoryConfigs: DirectoryConfig[];
+	/** Watched directories shared with other users; absent on Windows. */
+	sharedDirectories: SharedDirectory[];
+	/** Volumes holding watched directories; those on unmounted or released ones aren't watched. */
+	volumes: WatchedVolume[];
+	/** The session new renames are tagged with, if one is running. */
+	activeSession: RenameSession | null;
+	directoryStats: DirectoryStats[];
+	capabilities: ServiceCapability[];
 };
 
+/**
+ * `collidedWith` names the file that already had the name a rule produced, when the rename had to
+ * fall back to a suffixed name; UIs use it to compare the two files.
+ */
 export type ServiceFileEvent =
-	| { kind: 'preview'; file: string; target: string; directory: string; timestamp: number }
-	| { kind: 'applied'; file: string; target: string; directory: string; timestamp: number }
+	| {
+			kind: 'preview';
+			file: string;
+			target: string;
+			directory: string;
+			timestamp: number;
+			collidedWith?: string;
+	  }
+	| {
+			kind: 'applied';
+			file: string;
+			target: string;
+			directory: string;
+			timestamp: number;
+			collidedWith?: string;
+	  }
 	| { kind: 'skipped'; file: string; directory: string; timestamp: number; message?: string }
 	| { kind: 'error'; file: string; directory: string; timestamp: number; message: string }
 	| {
@@ -25,11 +160,33 @@ export type ServiceFileEvent =
 
 export type ServiceToastEvent = { message: string; level: 'info' | 'warn' | 'error' };
 
+/**
+ * Renames put back by an undo: the last rename, one picked from history, or a session's. Failed
+ * single undos only raise a toast.
+ */
+export type ServiceUndoEvent = {
+	scope: 'last' | 'entry' | 'session';
+	undone: number;
+	failed: number;
+	timestamp: number;
+};
+
+/** A directory is receiving unusually many events, typically from manual reorganising. */
+export type ServiceBurstEvent = {
+	directory: string;
+	count: number;
+	windowMs: number;
+	timestamp: number;
+};
+
 export type ServiceEventMap = {
 	file: ServiceFileEvent;
 	status: ServiceStatus;
 	config: IConfig;
 	toast: ServiceToastEvent;
+	burst: ServiceBurstEvent;
+	import: ImportProgress;
+	undo: ServiceUndoEvent;
 };
 
 export type ServiceEventKey = keyof ServiceEventMap;
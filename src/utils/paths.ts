@@ -1,8 +1,8 @@
-import os from 'node:os';
 import path from 'node:path';
+import { userHome } from './user.js';
 
 const isMac = process.platform === 'darwin';
-const homeDir = os.homedir();
+const homeDir = userHome();
 
 function ensureTrailing(app: string) {
 	const trimmed = app?.trim();
@@ -19,7 +19,32 @@ export function configDir(app = 'namefix') {
 	return path.join(homeDir, '.config', appName);
 }
 
+/** Set from `IConfig.dataDir`; null keeps history and logs in the platform's default places. */
+let dataDirOverride: string | null = null;
+
+/** Keep history, the audit log, logs and caches under `dir`; null restores the defaults. */
+export function setDataDir(dir: string | null) {
+	dataDirOverride = dir && dir.length > 0 ? dir : null;
+}
+
+/** Where history and logs go with `dir` as the data directory, or by default when null. */
+export function dataLocations(
+	dir: string | null,
+	app = 'namefix',
+): { state: string; logs: string } {
+	const logsOverride = process.env.NAMEFIX_LOGS;
+	if (logsOverride && logsOverride.length > 0) {
+		return { state: dir ?? defaultStateDir(app), logs: logsOverride };
+	}
+	if (dir) return { state: dir, logs: path.join(dir, 'logs') };
+	return { state: defaultStateDir(app), logs: defaultLogsDir(app) };
+}
+
 export function stateDir(app = 'namefix') {
+	return dataDirOverride ?? defaultStateDir(app);
+}
+
+function defaultStateDir(app: string) {
 	const appName = ensureTrailing(app);
 	const xdg = process.env.XDG_STATE_HOME;
 	if (xdg && xdg.length > 0) return path.join(xdg, appName);
@@ -28,6 +53,7 @@ export function stateDir(app = 'namefix') {
 }
 
 export function cacheDir(app = 'namefix') {
+	if (dataDirOverride) return path.join(dataDirOverride, 'cache');
 	const appName = ensureTrailing(app);
 	const xdg = process.env.XDG_CACHE_HOME;
 	if (xdg && xdg.length > 0) return path.join(xdg, appName);
@@ -36,8 +62,10 @@ export function cacheDir(app = 'namefix') {
 }
 
 export function logsDir(app = 'namefix') {
-	const override = process.env.NAMEFIX_LOGS;
-	if (override && override.length > 0) return override;
+	return dataLocations(dataDirOverride, app).logs;
+}
+
+function defaultLogsDir(app: string) {
 	const appName = ensureTrailing(app);
 	const xdgState = process.env.XDG_STATE_HOME;
 	if (xdgState && xdgState.length > 0) return path.join(xdgState, appName, 'logs');
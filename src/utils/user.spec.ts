@@ -0,0 +1,22 @@
+import { describe, expect, it } from 'vitest';
+
+import { homeFromPasswd } from './user.js';
+
+const PASSWD = [
+	'# comment',
+	'root:x:0:0:root:/root:/bin/bash',
+	'ada:x:1000:1000:Ada Lovelace,,,:/home/ada:/bin/zsh',
+	'nohome:x:1001:1001::',
+].join('\n');
+
+describe('homeFromPasswd', () => {
+	it('finds the home directory of the named user', () => {
+		expect(homeFromPasswd(PASSWD, 'ada')).toBe('/home/ada');
+		expect(homeFromPasswd(PASSWD, 'root')).toBe('/root');
+	});
+
+	it('returns null for unknown users and empty home fields', () => {
+		expect(homeFromPasswd(PASSWD, 'grace')).toBeNull();
+		expect(homeFromPasswd(PASSWD, 'nohome')).toBeNull();
+	});
+});
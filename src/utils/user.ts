@@ -0,0 +1,75 @@
+import { execFileSync } from 'node:child_process';
+import fs from 'node:fs';
+import os from 'node:os';
+import path from 'node:path';
+
+/** The account that started Namefix through `sudo`, when it is running as root on their behalf. */
+export function invokingUser(): { name: string; uid: number; gid: number } | null {
+	if (process.getuid?.() !== 0) return null;
+	const name = process.env.SUDO_USER;
+	const uid = Number(process.env.SUDO_UID);
+	const gid = Number(process.env.SUDO_GID);
+	if (!name || name === 'root' || !Number.isInteger(uid) || !Number.isInteger(gid)) return null;
+	return { name, uid, gid };
+}
+
+/**
+ * Home directory of the user Namefix works for. Under `sudo` (which the Endpoint Security backend
+ * needs) that is the invoking user's home rather than root's, so config and history stay theirs.
+ */
+export function userHome(): string {
+	const user = invokingUser();
+	if (user) {
+		const home = homeOf(user.name);
+		if (home) return home;
+	}
+	return os.homedir();
+}
+
+/** Numeric id of the user Namefix works for, or null where the platform has none (Windows). */
+export function userId(): number | null {
+	return invokingUser()?.uid ?? process.getuid?.() ?? null;
+}
+
+/**
+ * Give a file Namefix created while running as root back to the invoking user, so they can still
+ * read and write it without `sudo`. A no-op otherwise.
+ */
+export function restoreOwnership(file: string): void {
+	const user = invokingUser();
+	if (!user) return;
+	try {
+		fs.chownSync(file, user.uid, user.gid);
+	} catch {
+		// The file stays root-owned; the user can fix it with chown
+	}
+}
+
+function homeOf(user: string): string | null {
+	if (process.platform === 'darwin') {
+		try {
+			const out = execFileSync('dscl', ['.', '-read', `/Users/${user}`, 'NFSHomeDirectory'], {
+				encoding: 'utf8',
+			});
+			const home = out.split(':').slice(1).join(':').trim();
+			if (home) return home;
+		} catch {
+			// Fall through to the conventional location
+		}
+		return path.join('/Users', user);
+	}
+	try {
+		return homeFromPasswd(fs.readFileSync('/etc/passwd', 'utf8'), user);
+	} catch {
+		return null;
+	}
+}
+
+/** The home directory field of `user`'s line in passwd(5) format. */
+export function homeFromPasswd(passwd: string, user: string): string | null {
+	for (const line of passwd.split('\n')) {
+		const fields = line.split(':');
+		if (fields[0] === user && fields[5]) return fields[5];
+	}
+	return null;
+}